@@ -0,0 +1,423 @@
+//! Build script.
+//!
+//! With the `openapi` feature enabled, generates the `client::raw` endpoint
+//! bindings from Immich's OpenAPI spec. The spec is read from
+//! `IMMICH_OPENAPI_SPEC` when set, otherwise from the vendored subset in
+//! `openapi/` (only the paths the crate uses, not Immich's full spec).
+//! Without the feature this does nothing.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+const DEFAULT_SPEC: &str = "openapi/immich-openapi-specs.json";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=IMMICH_OPENAPI_SPEC");
+    if env::var_os("CARGO_FEATURE_OPENAPI").is_none() {
+        return;
+    }
+
+    let spec_path = env::var_os("IMMICH_OPENAPI_SPEC")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_SPEC));
+    println!("cargo:rerun-if-changed={}", spec_path.display());
+
+    let text = fs::read_to_string(&spec_path)
+        .unwrap_or_else(|e| panic!("cannot read OpenAPI spec {}: {}", spec_path.display(), e));
+    let spec: Value = serde_json::from_str(&text)
+        .unwrap_or_else(|e| panic!("invalid OpenAPI spec {}: {}", spec_path.display(), e));
+
+    let code = Generator::new(&spec).generate(&spec_path);
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR not set")).join("raw.rs");
+    fs::write(&out, code).unwrap_or_else(|e| panic!("cannot write {}: {}", out.display(), e));
+}
+
+/// Turns an OpenAPI 3 document into Rust source.
+struct Generator<'a> {
+    spec: &'a Value,
+    /// Component schemas, by name.
+    schemas: BTreeMap<String, &'a Value>,
+    /// Component schemas that become structs rather than aliases.
+    structs: BTreeSet<String>,
+    out: String,
+}
+
+impl<'a> Generator<'a> {
+    fn new(spec: &'a Value) -> Self {
+        let schemas: BTreeMap<String, &Value> = spec
+            .pointer("/components/schemas")
+            .and_then(Value::as_object)
+            .map(|map| map.iter().map(|(name, schema)| (name.clone(), schema)).collect())
+            .unwrap_or_default();
+        let structs = schemas
+            .iter()
+            .filter(|(_, schema)| is_struct(schema))
+            .map(|(name, _)| name.clone())
+            .collect();
+        Self {
+            spec,
+            schemas,
+            structs,
+            out: String::new(),
+        }
+    }
+
+    fn generate(mut self, spec_path: &Path) -> String {
+        let version = self.spec.pointer("/info/version").and_then(Value::as_str).unwrap_or("unknown");
+        let base = self
+            .spec
+            .pointer("/servers/0/url")
+            .and_then(Value::as_str)
+            .unwrap_or("/api")
+            .trim_end_matches('/')
+            .to_string();
+        let _ = writeln!(
+            self.out,
+            "// Generated by build.rs from {} (Immich API {}). Do not edit.\n",
+            spec_path.display(),
+            version
+        );
+        let _ = writeln!(self.out, "/// Immich API version the bindings were generated from.");
+        let _ = writeln!(self.out, "pub const API_VERSION: &str = {:?};\n", version);
+        let _ = writeln!(self.out, "const BASE_PATH: &str = {:?};\n", base);
+
+        let schemas: Vec<(String, &Value)> = self.schemas.iter().map(|(n, s)| (n.clone(), *s)).collect();
+        for (name, schema) in schemas {
+            self.schema(&name, schema);
+        }
+
+        let mut operations = Vec::new();
+        if let Some(paths) = self.spec.get("paths").and_then(Value::as_object) {
+            for (path, item) in paths {
+                for method in ["get", "put", "post", "delete", "patch"] {
+                    if let Some(op) = item.get(method) {
+                        operations.push((path.clone(), method, op));
+                    }
+                }
+            }
+        }
+        let mut methods = String::new();
+        for (path, method, op) in operations {
+            self.operation(&path, method, op, &mut methods);
+        }
+        let _ = writeln!(self.out, "impl Raw<'_> {{\n{}}}", methods);
+        self.out
+    }
+
+    /// Emit a component schema as a struct or type alias.
+    fn schema(&mut self, name: &str, schema: &Value) {
+        let ident = type_ident(name);
+        doc(&mut self.out, "", schema.get("description").and_then(Value::as_str));
+        if !self.structs.contains(name) {
+            let ty = self.rust_type(schema, false);
+            let _ = writeln!(self.out, "pub type {} = {};\n", ident, ty);
+            return;
+        }
+        let required = required(schema);
+        let _ = writeln!(
+            self.out,
+            "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\npub struct {} {{",
+            ident
+        );
+        if let Some(props) = schema.get("properties").and_then(Value::as_object) {
+            for (prop, prop_schema) in props {
+                let mut ty = self.rust_type(prop_schema, false);
+                if ty == ident {
+                    ty = format!("Box<{}>", ty);
+                }
+                let nullable = prop_schema.get("nullable").and_then(Value::as_bool).unwrap_or(false);
+                let is_required = required.contains(prop.as_str());
+                doc(&mut self.out, "    ", prop_schema.get("description").and_then(Value::as_str));
+                let _ = writeln!(self.out, "    #[serde(rename = {:?})]", prop);
+                if !is_required {
+                    let _ = writeln!(
+                        self.out,
+                        "    #[serde(default, skip_serializing_if = \"Option::is_none\")]"
+                    );
+                }
+                if !is_required || nullable {
+                    ty = format!("Option<{}>", ty);
+                }
+                let _ = writeln!(self.out, "    pub {}: {},", field_ident(prop), ty);
+            }
+        }
+        let _ = writeln!(self.out, "}}\n");
+    }
+
+    /// The Rust type for a schema. `query` restricts `$ref`s to types that
+    /// can appear in a query string.
+    fn rust_type(&self, schema: &Value, query: bool) -> String {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.rsplit('/').next().unwrap_or(reference);
+            if query && self.structs.contains(name) {
+                return "String".to_string();
+            }
+            return type_ident(name);
+        }
+        if let Some([only]) = schema.get("allOf").and_then(Value::as_array).map(Vec::as_slice) {
+            return self.rust_type(only, query);
+        }
+        match schema.get("type").and_then(Value::as_str) {
+            Some("string") => "String".to_string(),
+            Some("integer") => "i64".to_string(),
+            Some("number") => "f64".to_string(),
+            Some("boolean") => "bool".to_string(),
+            Some("array") => match schema.get("items") {
+                Some(items) => format!("Vec<{}>", self.rust_type(items, query)),
+                None => "Vec<serde_json::Value>".to_string(),
+            },
+            Some("object") if !query => match schema.get("additionalProperties") {
+                Some(values) if values.is_object() => format!(
+                    "std::collections::HashMap<String, {}>",
+                    self.rust_type(values, false)
+                ),
+                _ => "serde_json::Value".to_string(),
+            },
+            _ if query => "String".to_string(),
+            _ => "serde_json::Value".to_string(),
+        }
+    }
+
+    /// Emit one operation as a method on `Raw`, plus its query struct.
+    fn operation(&mut self, path: &str, method: &str, op: &Value, methods: &mut String) {
+        let op_id = op
+            .get("operationId")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}{}", method, path));
+        let fn_name = field_ident(&op_id);
+        let type_base = type_ident(&op_id);
+
+        let params: Vec<&Value> = op
+            .get("parameters")
+            .and_then(Value::as_array)
+            .map(|p| p.iter().collect())
+            .unwrap_or_default();
+        let path_params: Vec<&str> = params
+            .iter()
+            .filter(|p| p.get("in").and_then(Value::as_str) == Some("path"))
+            .filter_map(|p| p.get("name").and_then(Value::as_str))
+            .collect();
+        let query_params: Vec<&Value> = params
+            .iter()
+            .copied()
+            .filter(|p| p.get("in").and_then(Value::as_str) == Some("query"))
+            .collect();
+
+        // Request body: JSON only; operations needing another encoding
+        // (uploads) are left to the curated client.
+        let body = match op.get("requestBody") {
+            None => None,
+            Some(body) => match body.pointer("/content/application~1json/schema") {
+                Some(schema) => Some(self.rust_type(schema, false)),
+                None => return,
+            },
+        };
+
+        let response = self.response(op);
+
+        let query_type = if query_params.is_empty() {
+            None
+        } else {
+            let name = format!("{}Query", type_base);
+            let _ = writeln!(self.out, "/// Query parameters for [`Raw::{}`].", fn_name);
+            let _ = writeln!(
+                self.out,
+                "#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]\npub struct {} {{",
+                name
+            );
+            for param in &query_params {
+                let pname = param.get("name").and_then(Value::as_str).unwrap_or_default();
+                let ty = param
+                    .get("schema")
+                    .map(|s| self.rust_type(s, true))
+                    .unwrap_or_else(|| "String".to_string());
+                doc(&mut self.out, "    ", param.get("description").and_then(Value::as_str));
+                let _ = writeln!(
+                    self.out,
+                    "    #[serde(rename = {:?}, skip_serializing_if = \"Option::is_none\")]\n    pub {}: Option<{}>,",
+                    pname,
+                    field_ident(pname),
+                    ty
+                );
+            }
+            let _ = writeln!(self.out, "}}\n");
+            Some(name)
+        };
+
+        let summary = op
+            .get("summary")
+            .or_else(|| op.get("description"))
+            .and_then(Value::as_str);
+        doc(methods, "    ", summary);
+        if summary.is_some() {
+            let _ = writeln!(methods, "    ///");
+        }
+        let _ = writeln!(methods, "    /// `{} {}` (`{}`).", method.to_uppercase(), path, op_id);
+        if op.get("deprecated").and_then(Value::as_bool) == Some(true) {
+            let _ = writeln!(methods, "    ///\n    /// Deprecated in the spec.");
+        }
+
+        let mut args = String::new();
+        for name in &path_params {
+            let _ = write!(args, ", {}: &str", field_ident(name));
+        }
+        if let Some(query) = &query_type {
+            let _ = write!(args, ", query: &{}", query);
+        }
+        if let Some(body) = &body {
+            let _ = write!(args, ", body: &{}", body);
+        }
+        let (ret, send) = match &response {
+            Response::Json(ty) => (ty.clone(), "self.send(request).await"),
+            Response::Bytes => ("Vec<u8>".to_string(), "self.send_bytes(request).await"),
+            Response::Empty => ("()".to_string(), "self.send_empty(request).await"),
+        };
+        let _ = writeln!(
+            methods,
+            "    pub async fn {}(&self{}) -> crate::error::Result<{}> {{",
+            fn_name, args, ret
+        );
+
+        let mut format_str = String::from(path);
+        let mut format_args = String::new();
+        for name in &path_params {
+            format_str = format_str.replace(&format!("{{{}}}", name), "{}");
+            let _ = write!(format_args, ", segment({})", field_ident(name));
+        }
+        let _ = writeln!(
+            methods,
+            "        let url = self.url(&format!(\"{{}}{}\", BASE_PATH{}))?;",
+            format_str, format_args
+        );
+        let _ = write!(methods, "        let request = self.client.client.{}(url)", method);
+        if query_type.is_some() {
+            let _ = write!(methods, ".query(query)");
+        }
+        if body.is_some() {
+            let _ = write!(methods, ".json(body)");
+        }
+        let _ = writeln!(methods, ";\n        {}\n    }}\n", send);
+    }
+
+    /// The success response of an operation.
+    fn response(&self, op: &Value) -> Response {
+        let Some(responses) = op.get("responses").and_then(Value::as_object) else {
+            return Response::Empty;
+        };
+        let Some(success) = responses
+            .iter()
+            .find(|(code, _)| code.starts_with('2'))
+            .map(|(_, response)| response)
+        else {
+            return Response::Empty;
+        };
+        let Some(content) = success.get("content").and_then(Value::as_object) else {
+            return Response::Empty;
+        };
+        match content.get("application/json").and_then(|c| c.get("schema")) {
+            Some(schema) => Response::Json(self.rust_type(schema, false)),
+            None if content.is_empty() => Response::Empty,
+            None => Response::Bytes,
+        }
+    }
+}
+
+enum Response {
+    Json(String),
+    Bytes,
+    Empty,
+}
+
+/// Whether a component schema becomes a struct.
+fn is_struct(schema: &Value) -> bool {
+    schema.get("enum").is_none()
+        && schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|props| !props.is_empty())
+}
+
+fn required(schema: &Value) -> BTreeSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn doc(out: &mut String, indent: &str, text: Option<&str>) {
+    if let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) {
+        for line in text.lines() {
+            let _ = writeln!(out, "{}/// {}", indent, line.trim_end());
+        }
+    }
+}
+
+/// Split an identifier into lowercase words at case changes and separators.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let boundary = c.is_ascii_uppercase()
+            && !current.is_empty()
+            && (chars[i - 1].is_ascii_lowercase()
+                || chars[i - 1].is_ascii_digit()
+                || chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase()));
+        if boundary {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn type_ident(name: &str) -> String {
+    let ident: String = words(name)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("T{}", ident)
+    } else {
+        ident
+    }
+}
+
+fn field_ident(name: &str) -> String {
+    let ident = words(name).join("_");
+    let ident = if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", ident)
+    } else {
+        ident
+    };
+    match ident.as_str() {
+        "type" | "ref" | "match" | "move" | "use" | "mod" | "loop" | "in" | "as" | "fn" | "impl" | "trait"
+        | "where" | "struct" | "enum" | "static" | "const" | "async" | "await" | "dyn" | "override" | "box"
+        | "final" | "yield" | "gen" => format!("r#{}", ident),
+        "self" | "super" | "crate" => format!("{}_", ident),
+        _ => ident,
+    }
+}
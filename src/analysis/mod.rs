@@ -0,0 +1,297 @@
+//! Analysis reports.
+//!
+//! [`AnalysisReport`] is what `analyze` writes and every later step reads:
+//! the scored duplicate groups plus summary counts and their distribution
+//! (see [`summary`]). Reports carry a
+//! [`schema_version`](AnalysisReport::schema_version); [`AnalysisReport::load`]
+//! upgrades reports written by older versions and rejects newer ones.
+//!
+//! Reports are stored as JSON, zstd-compressed JSON (`.json.zst`), or, with
+//! the `sqlite` feature, an analysis store (`.db`); see [`crate::report_io`].
+
+pub mod summary;
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+use crate::report_io;
+use crate::scoring::{megapixels, DuplicateAnalysis};
+
+pub use summary::AnalysisSummary;
+
+/// Schema version of the reports this version writes.
+///
+/// - 1: reports from before the field existed
+/// - 2: adds `schema_version`; `external_library_groups` is always present
+/// - 3: adds `megapixels` to every asset with dimensions
+/// - 4: adds `web_url` and `api_url` to every asset
+/// - 5: adds `summary`
+pub const SCHEMA_VERSION: u32 = 5;
+
+/// Report containing analysis results for all duplicate groups.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    /// Schema version the report was written with
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+
+    /// Timestamp when the analysis was generated
+    pub generated_at: DateTime<Utc>,
+
+    /// The Immich server URL that was analyzed
+    pub server_url: String,
+
+    /// Total number of duplicate groups found
+    pub total_groups: usize,
+
+    /// Total number of assets across all groups
+    pub total_assets: usize,
+
+    /// Number of groups that need manual review due to conflicts
+    pub needs_review_count: usize,
+
+    /// Number of groups containing external library (read-only) assets
+    #[serde(default)]
+    pub external_library_groups: usize,
+
+    /// Distribution of the groups by size, reclaimable space, conflict, and camera
+    #[serde(default)]
+    pub summary: AnalysisSummary,
+
+    /// Analysis results for each duplicate group
+    pub groups: Vec<DuplicateAnalysis>,
+}
+
+/// Version of reports written before `schema_version` existed.
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+impl AnalysisReport {
+    /// A report of `groups` analyzed on `server_url` just now, with the
+    /// summary counts and asset URLs filled in.
+    pub fn new(server_url: &str, groups: Vec<DuplicateAnalysis>) -> Self {
+        Self::new_with_clock(server_url, groups, &SystemClock)
+    }
+
+    /// Like [`new`](Self::new), stamped with the time from `clock`.
+    pub(crate) fn new_with_clock(server_url: &str, mut groups: Vec<DuplicateAnalysis>, clock: &dyn Clock) -> Self {
+        for group in &mut groups {
+            group.set_urls(server_url);
+        }
+        let mut report = Self {
+            schema_version: SCHEMA_VERSION,
+            generated_at: clock.now(),
+            server_url: server_url.to_string(),
+            total_groups: 0,
+            total_assets: 0,
+            needs_review_count: 0,
+            external_library_groups: 0,
+            summary: AnalysisSummary::default(),
+            groups,
+        };
+        report.recount();
+        report
+    }
+
+    /// Recompute the summary counts from the groups.
+    pub fn recount(&mut self) {
+        self.total_groups = self.groups.len();
+        // Winner plus losers
+        self.total_assets = self.groups.iter().map(|g| 1 + g.losers.len()).sum();
+        self.needs_review_count = self.groups.iter().filter(|g| g.needs_review).count();
+        self.external_library_groups = self.groups.iter().filter(|g| g.has_external_assets()).count();
+        self.summary = AnalysisSummary::from_groups(&self.groups);
+    }
+
+    /// Load a report from a JSON (or `.json.zst`) file or an analysis store
+    /// (`.db`), upgrading it to the current schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or was written
+    /// by a newer version with a schema this version doesn't know.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut report: Self = if report_io::is_database(path) {
+            store::load(path)?
+        } else {
+            report_io::read_json(path)?
+        };
+        report.upgrade()?;
+        Ok(report)
+    }
+
+    /// Save the report to a JSON (or `.json.zst`) file or an analysis store (`.db`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if report_io::is_database(path) {
+            return store::save(path, self);
+        }
+        report_io::write_json(path, self)
+    }
+
+    /// Upgrade a report written with an older schema to the current one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidReport`] if the report's schema is newer
+    /// than this version supports.
+    pub fn upgrade(&mut self) -> Result<()> {
+        if self.schema_version > SCHEMA_VERSION {
+            return Err(ImmichError::InvalidReport(format!(
+                "schema version {} is newer than the supported version {}; upgrade immich-dupes",
+                self.schema_version, SCHEMA_VERSION
+            )));
+        }
+
+        if self.schema_version < 2 {
+            // The oldest reports have no external library count
+            self.external_library_groups = self.groups.iter().filter(|g| g.has_external_assets()).count();
+        }
+        if self.schema_version < 3 {
+            for group in &mut self.groups {
+                for asset in std::iter::once(&mut group.winner).chain(&mut group.losers) {
+                    asset.megapixels = asset.dimensions.map(megapixels);
+                }
+            }
+        }
+        if self.schema_version < 4 {
+            for group in &mut self.groups {
+                group.set_urls(&self.server_url);
+            }
+        }
+        if self.schema_version < 5 {
+            self.summary = AnalysisSummary::from_groups(&self.groups);
+        }
+        self.schema_version = SCHEMA_VERSION;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod store {
+    use std::path::Path;
+
+    use super::AnalysisReport;
+    use crate::error::{ImmichError, Result};
+    use crate::store::AnalysisStore;
+
+    /// Store key holding the report-level fields of an analysis (everything but the groups).
+    const REPORT_META_KEY: &str = "analysis_report";
+
+    pub(super) fn load(path: &Path) -> Result<AnalysisReport> {
+        if !path.exists() {
+            return Err(ImmichError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )));
+        }
+        let store = AnalysisStore::open(path)?;
+        let mut header: serde_json::Value = store
+            .meta(REPORT_META_KEY)?
+            .ok_or_else(|| ImmichError::InvalidReport("not an analysis store (no report metadata)".to_string()))?;
+        header["groups"] = serde_json::Value::Array(Vec::new());
+
+        let mut report: AnalysisReport =
+            serde_json::from_value(header).map_err(|e| ImmichError::InvalidReport(e.to_string()))?;
+        report.groups = store.groups()?;
+        Ok(report)
+    }
+
+    pub(super) fn save(path: &Path, report: &AnalysisReport) -> Result<()> {
+        let mut header = serde_json::to_value(report).map_err(std::io::Error::other)?;
+        if let Some(fields) = header.as_object_mut() {
+            fields.remove("groups");
+        }
+
+        let mut store = AnalysisStore::open(path)?;
+        store.replace_groups(&report.groups)?;
+        store.set_meta(REPORT_META_KEY, &header)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+mod store {
+    use std::path::Path;
+
+    use super::AnalysisReport;
+    use crate::error::{ImmichError, Result};
+
+    fn unsupported() -> ImmichError {
+        ImmichError::InvalidReport(
+            "this build has no SQLite support (rebuild with `--features sqlite`)".to_string(),
+        )
+    }
+
+    pub(super) fn load(_path: &Path) -> Result<AnalysisReport> {
+        Err(unsupported())
+    }
+
+    pub(super) fn save(_path: &Path, _report: &AnalysisReport) -> Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::synthetic_groups;
+
+    #[test]
+    fn test_load_upgrades_and_rejects_newer_schemas() {
+        let dir = tempfile::tempdir().unwrap();
+        let groups: Vec<DuplicateAnalysis> = synthetic_groups(2, 3).unwrap().iter().map(DuplicateAnalysis::from_group).collect();
+        let report = AnalysisReport::new("http://immich.local", groups);
+        assert_eq!((report.total_groups, report.total_assets), (2, 6));
+
+        // Round trip, compressed
+        let path = dir.path().join("analysis.json.zst");
+        report.save(&path).unwrap();
+        let loaded = AnalysisReport::load(&path).unwrap();
+        assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+        assert_eq!(loaded.groups.len(), 2);
+
+        // A version 1 report has neither the version nor the external count
+        let mut legacy = serde_json::to_value(&report).unwrap();
+        legacy.as_object_mut().unwrap().remove("schema_version");
+        legacy.as_object_mut().unwrap().remove("external_library_groups");
+        legacy.as_object_mut().unwrap().remove("summary");
+        let winner = legacy["groups"][0]["winner"].as_object_mut().unwrap();
+        winner.remove("megapixels");
+        winner.remove("web_url");
+        let path = dir.path().join("legacy.json");
+        std::fs::write(&path, legacy.to_string()).unwrap();
+        let upgraded = AnalysisReport::load(&path).unwrap();
+        assert_eq!(upgraded.schema_version, SCHEMA_VERSION);
+        assert_eq!(upgraded.external_library_groups, report.external_library_groups);
+        assert_eq!(upgraded.summary, report.summary);
+        assert_eq!(upgraded.groups[0].winner.megapixels, Some(12.2));
+        assert_eq!(upgraded.groups[0].winner.width(), Some(4032));
+        assert_eq!(
+            upgraded.groups[0].winner.web_url.as_deref(),
+            Some("http://immich.local/photos/asset-000000-0")
+        );
+
+        legacy["schema_version"] = serde_json::json!(SCHEMA_VERSION + 1);
+        std::fs::write(&path, legacy.to_string()).unwrap();
+        assert!(matches!(AnalysisReport::load(&path), Err(ImmichError::InvalidReport(_))));
+    }
+
+    #[test]
+    fn test_report_format_snapshot() {
+        let clock = crate::clock::FixedClock::new("2024-07-01T12:00:00Z".parse().unwrap());
+        let groups: Vec<DuplicateAnalysis> = synthetic_groups(2, 3).unwrap().iter().map(DuplicateAnalysis::from_group).collect();
+        let report = AnalysisReport::new_with_clock("http://immich.local", groups, &clock);
+        insta::assert_snapshot!(report_io::to_stable_json(&report).unwrap());
+    }
+}
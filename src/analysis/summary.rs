@@ -0,0 +1,107 @@
+//! How duplicates are distributed across an analysis.
+//!
+//! [`AnalysisSummary`] breaks an analysis down by group size, by the space
+//! each group would free, by conflict type, and by camera model, so a large
+//! report can be sized up without reading every group.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::scoring::DuplicateAnalysis;
+
+/// Number of groups listed in [`AnalysisSummary::largest_groups`].
+pub const TOP_GROUPS: usize = 20;
+
+/// Camera key for groups where no asset records a camera.
+pub const UNKNOWN_CAMERA: &str = "unknown";
+
+/// Distribution of an analysis's duplicate groups.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisSummary {
+    /// Number of groups of each size (assets per group)
+    pub group_sizes: BTreeMap<usize, usize>,
+
+    /// The groups that would free the most space, largest first
+    pub largest_groups: Vec<GroupBytes>,
+
+    /// Number of conflicts of each type (e.g. `gps`), across all groups
+    pub conflicts: BTreeMap<String, usize>,
+
+    /// Number of groups per camera make and model (the winner's, else the
+    /// first loser's that has one)
+    pub cameras: BTreeMap<String, usize>,
+}
+
+/// Space one group would free.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupBytes {
+    /// Duplicate group identifier
+    pub duplicate_id: String,
+
+    /// Assets in the group
+    pub assets: usize,
+
+    /// Bytes freed by removing the group's losers
+    pub reclaimable_bytes: u64,
+}
+
+impl AnalysisSummary {
+    /// Summarize analyzed groups.
+    pub fn from_groups(groups: &[DuplicateAnalysis]) -> Self {
+        let mut summary = Self::default();
+        for group in groups {
+            *summary.group_sizes.entry(1 + group.losers.len()).or_default() += 1;
+            for conflict in &group.conflicts {
+                *summary.conflicts.entry(conflict.kind().to_string()).or_default() += 1;
+            }
+            let camera = std::iter::once(&group.winner)
+                .chain(&group.losers)
+                .find_map(|a| a.camera.as_deref())
+                .unwrap_or(UNKNOWN_CAMERA);
+            *summary.cameras.entry(camera.to_string()).or_default() += 1;
+        }
+
+        let mut largest: Vec<GroupBytes> = groups
+            .iter()
+            .map(|g| GroupBytes {
+                duplicate_id: g.duplicate_id.clone(),
+                assets: 1 + g.losers.len(),
+                reclaimable_bytes: g.losers.iter().filter_map(|l| l.file_size).sum(),
+            })
+            .collect();
+        // Stable, so equal groups keep their analysis order
+        largest.sort_by_key(|g| std::cmp::Reverse(g.reclaimable_bytes));
+        largest.truncate(TOP_GROUPS);
+        summary.largest_groups = largest;
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{synthetic_groups, AssetBuilder, GroupBuilder};
+
+    #[test]
+    fn test_summary_distribution() {
+        let mut groups = synthetic_groups(25, 2).unwrap();
+        groups.extend(synthetic_groups(1, 4).unwrap());
+        groups.push(
+            GroupBuilder::new("conflicting")
+                .with_asset(AssetBuilder::new("london").with_gps(51.5, -0.12).with_file_size(600_000_000))
+                .with_asset(AssetBuilder::new("paris").with_gps(48.85, 2.35).with_file_size(500_000_000))
+                .build(),
+        );
+        let analyses: Vec<DuplicateAnalysis> = groups.iter().map(DuplicateAnalysis::from_group).collect();
+        let summary = AnalysisSummary::from_groups(&analyses);
+
+        assert_eq!(summary.group_sizes, BTreeMap::from([(2, 26), (4, 1)]));
+        assert_eq!(summary.largest_groups.len(), TOP_GROUPS);
+        assert_eq!(summary.largest_groups[0].duplicate_id, "conflicting");
+        assert!(summary.largest_groups.windows(2).all(|w| w[0].reclaimable_bytes >= w[1].reclaimable_bytes));
+        assert_eq!(summary.conflicts.get("gps"), Some(&1));
+        assert_eq!(summary.cameras.get("Apple iPhone 15 Pro"), Some(&26));
+        assert_eq!(summary.cameras.get(UNKNOWN_CAMERA), Some(&1));
+    }
+}
@@ -0,0 +1,642 @@
+//! Backup archives.
+//!
+//! With [`BackupSink::Tar`](crate::models::BackupSink::Tar) or
+//! [`BackupSink::Zip`](crate::models::BackupSink::Zip), a run's backups are
+//! streamed into a single dated archive instead of thousands of loose files.
+//! Each backup is appended (and synced to disk) once it has passed its
+//! integrity checks, before its asset can be deleted, and the archive ends
+//! with a `manifest.json` listing every member. Members keep their source
+//! file's modification time.
+//!
+//! A tar archive stays readable if a run is interrupted. A zip archive's
+//! central directory is only written when the run finishes, so an
+//! interrupted zip has to be repaired (`zip -FF`) before it can be extracted.
+
+use std::io::{Read as _, Seek as _, SeekFrom, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+
+/// tar block size.
+pub(crate) const BLOCK: usize = 512;
+
+/// Longest member name a plain ustar header can hold.
+const MAX_NAME_LEN: usize = 99;
+
+/// Name of the manifest member at the end of the archive.
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// A file in a backup archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Member name in the archive
+    pub member: String,
+
+    /// Asset the file is a backup of
+    pub asset_id: String,
+
+    /// Duplicate group the asset was a loser in
+    pub group_id: String,
+
+    /// Size in bytes
+    pub size: u64,
+}
+
+/// Contents of a backup archive, stored as its last member.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// When the archive was started
+    pub created_at: DateTime<Utc>,
+
+    /// Immich server the backups were downloaded from
+    pub server_url: String,
+
+    /// Every file in the archive, in order
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl BackupManifest {
+    fn new(created_at: DateTime<Utc>, server_url: &str) -> Self {
+        Self {
+            created_at,
+            server_url: server_url.to_string(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// A single archive a run's backups are collected into.
+pub trait BackupArchive: Send + Sync + std::fmt::Debug {
+    /// Path of the archive file.
+    fn path(&self) -> &Path;
+
+    /// Append the file at `source` as `member`, syncing it to disk before
+    /// returning. If the file can't be copied in full, the archive is left
+    /// with only its previous members.
+    fn add_file<'a>(
+        &'a self,
+        source: &'a Path,
+        member: &'a str,
+        asset_id: &'a str,
+        group_id: &'a str,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Write the manifest and close the archive. Nothing can be added after.
+    fn finish(&self) -> BoxFuture<'_, Result<BackupManifest>>;
+}
+
+/// Path of a new archive in `dir` named after `created_at`.
+fn archive_path(dir: &Path, created_at: DateTime<Utc>, extension: &str) -> PathBuf {
+    dir.join(format!("backups-{}.{}", created_at.format("%Y%m%d-%H%M%S"), extension))
+}
+
+/// A backup archive in tar format, written as it is appended to.
+#[derive(Debug)]
+pub struct TarArchive {
+    path: PathBuf,
+    state: Mutex<TarState>,
+}
+
+#[derive(Debug)]
+struct TarState {
+    /// Open archive, until finished
+    file: Option<File>,
+
+    /// Length of the archive's complete members
+    len: u64,
+
+    manifest: BackupManifest,
+}
+
+impl TarArchive {
+    /// Start a new archive named `backups-<timestamp>.tar` in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file already exists or cannot be created.
+    pub async fn create(dir: &Path, server_url: &str) -> Result<Self> {
+        Self::create_with_clock(dir, server_url, &SystemClock).await
+    }
+
+    /// Like [`create`](Self::create), named and stamped with the time from `clock`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file already exists or cannot be created.
+    pub(crate) async fn create_with_clock(dir: &Path, server_url: &str, clock: &dyn Clock) -> Result<Self> {
+        let created_at = clock.now();
+        let path = archive_path(dir, created_at, "tar");
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await?;
+        Ok(Self {
+            path,
+            state: Mutex::new(TarState {
+                file: Some(file),
+                len: 0,
+                manifest: BackupManifest::new(created_at, server_url),
+            }),
+        })
+    }
+}
+
+impl BackupArchive for TarArchive {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn add_file<'a>(
+        &'a self,
+        source: &'a Path,
+        member: &'a str,
+        asset_id: &'a str,
+        group_id: &'a str,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut state = self.state.lock().await;
+            let start = state.len;
+            let file = state.file.as_mut().ok_or_else(finished_error)?;
+
+            // Drop what an append cancelled partway (e.g. by a timeout) left behind
+            if file.stream_position().await? != start {
+                file.set_len(start).await?;
+                file.seek(SeekFrom::Start(start)).await?;
+            }
+
+            let size = match append_file(file, source, member).await {
+                Ok(size) => size,
+                Err(e) => {
+                    // Drop the partial member so later members stay readable
+                    file.set_len(start).await?;
+                    file.seek(SeekFrom::Start(start)).await?;
+                    return Err(e);
+                }
+            };
+
+            state.len = file.stream_position().await?;
+            state.manifest.entries.push(ManifestEntry {
+                member: member.to_string(),
+                asset_id: asset_id.to_string(),
+                group_id: group_id.to_string(),
+                size,
+            });
+            Ok(())
+        })
+    }
+
+    fn finish(&self) -> BoxFuture<'_, Result<BackupManifest>> {
+        Box::pin(async move {
+            let mut state = self.state.lock().await;
+            let mut file = state.file.take().ok_or_else(finished_error)?;
+
+            let manifest = serde_json::to_vec_pretty(&state.manifest).map_err(std::io::Error::other)?;
+            let mtime = unix_time(state.manifest.created_at);
+            file.write_all(&tar_header(MANIFEST_NAME, manifest.len() as u64, mtime)?).await?;
+            file.write_all(&manifest).await?;
+            file.write_all(&padding(manifest.len() as u64)).await?;
+            // Two zero blocks mark the end of the archive
+            file.write_all(&[0u8; BLOCK * 2]).await?;
+            file.sync_all().await?;
+            Ok(state.manifest.clone())
+        })
+    }
+}
+
+/// Write one file as a tar member, returning its size.
+async fn append_file(archive: &mut File, source: &Path, member: &str) -> Result<u64> {
+    let mut source_file = File::open(source).await?;
+    let metadata = source_file.metadata().await?;
+    let size = metadata.len();
+    let mtime = unix_time(modified(metadata.modified()));
+
+    archive.write_all(&tar_header(member, size, mtime)?).await?;
+    // Copy no more than the header announced, even if the file is growing
+    let copied = tokio::io::copy(&mut (&mut source_file).take(size), archive).await?;
+    if copied != size {
+        return Err(shrank_error(source));
+    }
+    archive.write_all(&padding(size)).await?;
+    archive.sync_data().await?;
+    Ok(size)
+}
+
+/// Zero bytes filling a member of `size` bytes out to a whole block.
+fn padding(size: u64) -> Vec<u8> {
+    vec![0u8; (BLOCK - (size % BLOCK as u64) as usize) % BLOCK]
+}
+
+/// A backup archive in zip format. Members are stored uncompressed (photos
+/// and videos are compressed already).
+#[derive(Debug)]
+pub struct ZipArchive {
+    path: PathBuf,
+    state: Arc<parking_lot::Mutex<ZipState>>,
+}
+
+#[derive(Debug)]
+struct ZipState {
+    /// Open archive, until finished
+    writer: Option<ZipWriter<std::fs::File>>,
+
+    /// Handle on the archive file for syncing it
+    file: std::fs::File,
+
+    manifest: BackupManifest,
+}
+
+impl ZipArchive {
+    /// Start a new archive named `backups-<timestamp>.zip` in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file already exists or cannot be created.
+    pub async fn create(dir: &Path, server_url: &str) -> Result<Self> {
+        Self::create_with_clock(dir, server_url, &SystemClock).await
+    }
+
+    /// Like [`create`](Self::create), named and stamped with the time from `clock`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file already exists or cannot be created.
+    pub(crate) async fn create_with_clock(dir: &Path, server_url: &str, clock: &dyn Clock) -> Result<Self> {
+        let created_at = clock.now();
+        let path = archive_path(dir, created_at, "zip");
+        let file = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await?
+            .into_std()
+            .await;
+        Ok(Self {
+            path,
+            state: Arc::new(parking_lot::Mutex::new(ZipState {
+                writer: Some(ZipWriter::new(file.try_clone()?)),
+                file,
+                manifest: BackupManifest::new(created_at, server_url),
+            })),
+        })
+    }
+}
+
+impl BackupArchive for ZipArchive {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn add_file<'a>(
+        &'a self,
+        source: &'a Path,
+        member: &'a str,
+        asset_id: &'a str,
+        group_id: &'a str,
+    ) -> BoxFuture<'a, Result<()>> {
+        let state = Arc::clone(&self.state);
+        let source = source.to_path_buf();
+        let entry = ManifestEntry {
+            member: member.to_string(),
+            asset_id: asset_id.to_string(),
+            group_id: group_id.to_string(),
+            size: 0,
+        };
+        Box::pin(async move {
+            // The zip writer is synchronous, so it runs off the async workers
+            tokio::task::spawn_blocking(move || {
+                let mut state = state.lock();
+                let ZipState { writer, file, manifest } = &mut *state;
+                let writer = writer.as_mut().ok_or_else(finished_error)?;
+                let size = append_zip_file(writer, &source, &entry.member)?;
+                file.sync_data()?;
+                manifest.entries.push(ManifestEntry { size, ..entry });
+                Ok(())
+            })
+            .await
+            .map_err(std::io::Error::other)?
+        })
+    }
+
+    fn finish(&self) -> BoxFuture<'_, Result<BackupManifest>> {
+        let state = Arc::clone(&self.state);
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || {
+                let mut state = state.lock();
+                let mut writer = state.writer.take().ok_or_else(finished_error)?;
+
+                let manifest = serde_json::to_vec_pretty(&state.manifest).map_err(std::io::Error::other)?;
+                let options = zip_options(manifest.len() as u64, state.manifest.created_at);
+                writer.start_file(MANIFEST_NAME, options).map_err(zip_error)?;
+                writer.write_all(&manifest)?;
+                let mut file = writer.finish().map_err(zip_error)?;
+                // Drop anything an aborted member left past the end
+                let len = file.stream_position()?;
+                file.set_len(len)?;
+                file.sync_all()?;
+                Ok(state.manifest.clone())
+            })
+            .await
+            .map_err(std::io::Error::other)?
+        })
+    }
+}
+
+/// Write one file as a zip member, returning its size.
+fn append_zip_file(writer: &mut ZipWriter<std::fs::File>, source: &Path, member: &str) -> Result<u64> {
+    let mut source_file = std::fs::File::open(source)?;
+    let metadata = source_file.metadata()?;
+    let size = metadata.len();
+
+    writer
+        .start_file(member, zip_options(size, modified(metadata.modified())))
+        .map_err(zip_error)?;
+    // Copy no more than the size checked, even if the file is growing
+    let copied = std::io::copy(&mut (&mut source_file).take(size), writer).map_err(ImmichError::from);
+    let written = match copied {
+        Ok(copied) if copied != size => Err(shrank_error(source)),
+        Ok(_) => writer.flush().map_err(ImmichError::from),
+        Err(e) => Err(e),
+    };
+    if written.is_err() {
+        // Rewind over the partial member so later members stay readable
+        writer.abort_file().map_err(zip_error)?;
+    }
+    written.map(|()| size)
+}
+
+/// Options for a stored zip member of `size` bytes last modified at `mtime`.
+fn zip_options(size: u64, mtime: DateTime<Utc>) -> SimpleFileOptions {
+    // zip times start in 1980; anything earlier is clamped to the start
+    let mtime = zip::DateTime::try_from(mtime.naive_utc()).unwrap_or_default();
+    SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(size >= u64::from(u32::MAX))
+        .last_modified_time(mtime)
+}
+
+fn zip_error(e: zip::result::ZipError) -> ImmichError {
+    ImmichError::Io(std::io::Error::other(e))
+}
+
+/// Read the manifest of a finished `.tar` or `.zip` backup archive.
+///
+/// # Errors
+///
+/// Returns an error if the archive can't be read or has no manifest, as when
+/// the run writing it was interrupted.
+pub fn read_manifest(path: &Path) -> Result<BackupManifest> {
+    let bytes = if path.extension().is_some_and(|e| e == "zip") {
+        let mut zip = zip::read::ZipArchive::new(std::fs::File::open(path)?).map_err(zip_error)?;
+        let mut member = zip.by_name(MANIFEST_NAME).map_err(zip_error)?;
+        let mut bytes = Vec::new();
+        member.read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        read_tar_manifest(path)?
+    };
+    serde_json::from_slice(&bytes).map_err(|e| {
+        ImmichError::Backup(format!("{}: unreadable {}: {}", path.display(), MANIFEST_NAME, e))
+    })
+}
+
+/// The last `manifest.json` member of a tar archive.
+fn read_tar_manifest(path: &Path) -> Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut manifest = None;
+    let mut header = [0u8; BLOCK];
+
+    // Stops at the end-of-archive blocks, or where an interrupted run stopped
+    while file.read_exact(&mut header).is_ok() && header.iter().any(|&b| b != 0) {
+        let name_len = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+        let size = std::str::from_utf8(&header[124..136])
+            .ok()
+            .and_then(|field| u64::from_str_radix(field.trim_matches(|c| c == '\0' || c == ' '), 8).ok())
+            .ok_or_else(|| ImmichError::Backup(format!("{}: corrupt tar header", path.display())))?;
+
+        if &header[..name_len] == MANIFEST_NAME.as_bytes() && header[156] == b'0' {
+            let mut bytes = Vec::new();
+            (&mut file).take(size).read_to_end(&mut bytes)?;
+            file.seek(SeekFrom::Current(padding(size).len() as i64))?;
+            manifest = Some(bytes);
+        } else {
+            file.seek(SeekFrom::Current((size + padding(size).len() as u64) as i64))?;
+        }
+    }
+
+    manifest.ok_or_else(|| ImmichError::Backup(format!("{}: no {}", path.display(), MANIFEST_NAME)))
+}
+
+/// A file's modification time, or the Unix epoch if the platform doesn't
+/// record one.
+fn modified(time: std::io::Result<SystemTime>) -> DateTime<Utc> {
+    time.map(DateTime::<Utc>::from).unwrap_or_default()
+}
+
+/// `time` as seconds since the Unix epoch, for tar headers.
+fn unix_time(time: DateTime<Utc>) -> u64 {
+    time.timestamp().max(0) as u64
+}
+
+fn shrank_error(source: &Path) -> ImmichError {
+    ImmichError::Io(std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        format!("{} shrank while being archived", source.display()),
+    ))
+}
+
+fn finished_error() -> ImmichError {
+    ImmichError::Io(std::io::Error::other("backup archive already finished"))
+}
+
+/// Header blocks for a regular file: a ustar header, preceded by a pax
+/// extended header carrying the full name if it is too long for ustar.
+pub(crate) fn tar_header(name: &str, size: u64, mtime: u64) -> Result<Vec<u8>> {
+    if name.len() <= MAX_NAME_LEN {
+        return Ok(ustar_header(name, size, mtime, b'0')?.to_vec());
+    }
+
+    // Each record is "<length> path=<name>\n", where the length counts itself
+    let record = format!(" path={}\n", name);
+    let mut len = record.len() + 1;
+    while len.to_string().len() + record.len() > len {
+        len += 1;
+    }
+    let record = format!("{}{}", len, record);
+
+    let mut short = name.len().min(MAX_NAME_LEN);
+    while !name.is_char_boundary(short) {
+        short -= 1;
+    }
+    let mut headers = ustar_header("././@PaxHeader", record.len() as u64, mtime, b'x')?.to_vec();
+    headers.extend_from_slice(record.as_bytes());
+    headers.extend_from_slice(&padding(record.len() as u64));
+    headers.extend_from_slice(&ustar_header(&name[..short], size, mtime, b'0')?);
+    Ok(headers)
+}
+
+/// Build a ustar header block.
+pub(crate) fn ustar_header(name: &str, size: u64, mtime: u64, typeflag: u8) -> Result<[u8; BLOCK]> {
+    if name.len() > MAX_NAME_LEN {
+        return Err(ImmichError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("tar member name too long: '{}'", name),
+        )));
+    }
+    if size > 0o77777777777 {
+        return Err(ImmichError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("tar member too large: '{}' is {} bytes", name, size),
+        )));
+    }
+
+    let mut header = [0u8; BLOCK];
+    let mut put = |offset: usize, value: &[u8]| header[offset..offset + value.len()].copy_from_slice(value);
+
+    put(0, name.as_bytes());
+    put(100, b"0000644\0");
+    put(108, b"0000000\0");
+    put(116, b"0000000\0");
+    put(124, format!("{:011o}\0", size).as_bytes());
+    put(136, format!("{:011o}\0", mtime).as_bytes());
+    put(148, b"        ");
+    put(156, &[typeflag]);
+    put(257, b"ustar\0");
+    put(263, b"00");
+
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    /// A tar or zip member: name, tar typeflag, modification time, contents.
+    type Member = (String, u8, u64, Vec<u8>);
+
+    /// Split a tar archive into its members.
+    fn members(tar: &[u8]) -> Vec<Member> {
+        let octal = |field: &[u8]| u64::from_str_radix(std::str::from_utf8(field).unwrap(), 8).unwrap();
+        let mut members = Vec::new();
+        let mut offset = 0;
+        while offset + BLOCK <= tar.len() && tar[offset] != 0 {
+            let header = &tar[offset..offset + BLOCK];
+            let name = String::from_utf8_lossy(&header[..100]).trim_end_matches('\0').to_string();
+            let size = octal(&header[124..135]) as usize;
+            let start = offset + BLOCK;
+            members.push((name, header[156], octal(&header[136..147]), tar[start..start + size].to_vec()));
+            offset = start + size.div_ceil(BLOCK) * BLOCK;
+        }
+        members
+    }
+
+    /// A photo in `dir` last modified at 2024-06-01 00:00 UTC.
+    fn photo(dir: &Path) -> (PathBuf, DateTime<Utc>) {
+        let path = dir.join("photo.jpg");
+        std::fs::write(&path, b"jpeg bytes").unwrap();
+        let modified: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified.into()).unwrap();
+        (path, modified)
+    }
+
+    fn clock() -> FixedClock {
+        FixedClock::new("2025-01-01T12:00:00Z".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_tar_archive_with_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let (photo, modified) = photo(dir.path());
+        let long_name = format!("asset-2_{}.jpg", "x".repeat(120));
+
+        let archive = TarArchive::create_with_clock(dir.path(), "http://immich.local", &clock()).await.unwrap();
+        assert_eq!(archive.path(), dir.path().join("backups-20250101-120000.tar"));
+        archive.add_file(&photo, "asset-1_photo.jpg", "asset-1", "group-1").await.unwrap();
+        archive.add_file(&photo, &long_name, "asset-2", "group-1").await.unwrap();
+        // A missing file leaves the archive as it was
+        assert!(archive.add_file(&dir.path().join("gone.jpg"), "gone.jpg", "asset-3", "group-2").await.is_err());
+        let manifest = archive.finish().await.unwrap();
+        assert!(archive.add_file(&photo, "late.jpg", "asset-4", "group-2").await.is_err());
+
+        let tar = std::fs::read(archive.path()).unwrap();
+        assert_eq!(tar.len() % BLOCK, 0);
+        let members = members(&tar);
+        assert_eq!(members.len(), 4);
+        let mtime = modified.timestamp() as u64;
+        assert_eq!(members[0], ("asset-1_photo.jpg".to_string(), b'0', mtime, b"jpeg bytes".to_vec()));
+        assert_eq!(members[1].1, b'x');
+        let record = String::from_utf8(members[1].3.clone()).unwrap();
+        assert_eq!(record, format!("{} path={}\n", record.len(), long_name));
+        assert_eq!((members[2].2, &members[2].3[..]), (mtime, &b"jpeg bytes"[..]));
+        assert_eq!(members[3].0, MANIFEST_NAME);
+        // The manifest is stamped with the archive's creation time
+        assert_eq!(members[3].2, manifest.created_at.timestamp() as u64);
+
+        let stored: BackupManifest = serde_json::from_slice(&members[3].3).unwrap();
+        assert_eq!(stored.entries, manifest.entries);
+        assert_eq!(stored.entries.len(), 2);
+        assert_eq!(stored.entries[1].member, long_name);
+        assert_eq!(stored.entries[1].size, 10);
+        assert_eq!(read_manifest(archive.path()).unwrap().entries, manifest.entries);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_tar_has_no_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let (photo, _) = photo(dir.path());
+
+        let archive = TarArchive::create(dir.path(), "http://immich.local").await.unwrap();
+        archive.add_file(&photo, "asset-1_photo.jpg", "asset-1", "group-1").await.unwrap();
+
+        assert!(read_manifest(archive.path()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_zip_archive_with_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let (photo, modified) = photo(dir.path());
+
+        let archive = ZipArchive::create_with_clock(dir.path(), "http://immich.local", &clock()).await.unwrap();
+        assert_eq!(archive.path(), dir.path().join("backups-20250101-120000.zip"));
+        archive.add_file(&photo, "asset-1_photo.jpg", "asset-1", "group-1").await.unwrap();
+        assert!(archive.add_file(&dir.path().join("gone.jpg"), "gone.jpg", "asset-2", "group-1").await.is_err());
+        archive.add_file(&photo, "asset-3_photo.jpg", "asset-3", "group-2").await.unwrap();
+        let manifest = archive.finish().await.unwrap();
+        assert!(archive.add_file(&photo, "late.jpg", "asset-4", "group-2").await.is_err());
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(archive.path()).unwrap()).unwrap();
+        let names: Vec<&str> = zip.file_names().collect();
+        assert_eq!(names.len(), 3);
+        let mut member = zip.by_name("asset-3_photo.jpg").unwrap();
+        let mtime = member.last_modified().unwrap();
+        assert_eq!((mtime.year(), mtime.month(), mtime.day()), (2024, 6, 1));
+        let mut contents = Vec::new();
+        member.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, b"jpeg bytes");
+        drop(member);
+
+        let stored: BackupManifest = serde_json::from_reader(zip.by_name(MANIFEST_NAME).unwrap()).unwrap();
+        assert_eq!(stored.entries, manifest.entries);
+        let members: Vec<&str> = stored.entries.iter().map(|e| e.member.as_str()).collect();
+        assert_eq!(members, ["asset-1_photo.jpg", "asset-3_photo.jpg"]);
+        assert_eq!(read_manifest(archive.path()).unwrap().entries, manifest.entries);
+        assert_eq!(modified.timestamp(), 1_717_200_000);
+    }
+}
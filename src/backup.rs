@@ -0,0 +1,296 @@
+//! Backup destinations.
+//!
+//! The executor saves each loser's original through a [`BackupStore`] before
+//! deleting it. [`LocalStore`] writes files to the backup directory; with the
+//! `s3` feature, [`S3Store`] streams originals straight from Immich into an
+//! S3-compatible bucket (AWS, MinIO, Garage, ...) with no local copy.
+//!
+//! Which store a run uses is set by
+//! [`ExecutionConfig::backup_target`](crate::models::ExecutionConfig::backup_target).
+
+use std::path::{Path, PathBuf};
+
+use futures::future::BoxFuture;
+
+use crate::client::ImmichClient;
+use crate::error::Result;
+
+/// Somewhere loser backups are kept.
+pub trait BackupStore: Send + Sync + std::fmt::Debug {
+    /// Where the backup called `name` is stored, as recorded in reports and
+    /// the ledger.
+    fn location(&self, name: &str) -> PathBuf;
+
+    /// Whether backups are files on this machine at their
+    /// [`location`](Self::location), so they can be integrity-checked,
+    /// embedded into, and archived.
+    fn is_local(&self) -> bool;
+
+    /// Save an asset's original as `name`, returning its size in bytes.
+    fn put_asset<'a>(&'a self, client: &'a ImmichClient, asset_id: &'a str, name: &'a str) -> BoxFuture<'a, Result<u64>>;
+
+    /// Save a small file, such as an XMP sidecar, as `name`.
+    fn put_bytes<'a>(&'a self, name: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Backups as files in a local directory.
+#[derive(Debug, Clone)]
+pub struct LocalStore {
+    dir: PathBuf,
+}
+
+impl LocalStore {
+    /// Store backups in `dir`, which must already exist.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The backup directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl BackupStore for LocalStore {
+    fn location(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn put_asset<'a>(&'a self, client: &'a ImmichClient, asset_id: &'a str, name: &'a str) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(async move { client.download_asset(asset_id, &self.location(name)).await })
+    }
+
+    fn put_bytes<'a>(&'a self, name: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { Ok(tokio::fs::write(self.location(name), bytes).await?) })
+    }
+}
+
+#[cfg(feature = "s3")]
+pub use s3::{S3Config, S3Store};
+
+#[cfg(feature = "s3")]
+mod s3 {
+    use std::path::PathBuf;
+
+    use futures::StreamExt;
+    use futures::future::BoxFuture;
+    use object_store::aws::{AmazonS3, AmazonS3Builder};
+    use object_store::{ObjectStore, WriteMultipart};
+    use url::Url;
+
+    use super::BackupStore;
+    use crate::client::ImmichClient;
+    use crate::error::{ImmichError, Result};
+
+    /// Size of each part of a multipart upload. S3 needs at least 5 MiB for
+    /// every part but the last, and allows 10,000 parts, so this covers
+    /// originals up to ~78 GiB.
+    const PART_SIZE: usize = 8 * 1024 * 1024;
+
+    /// Parts uploaded at once; at most this many are held in memory.
+    const PARTS_IN_FLIGHT: usize = 4;
+
+    /// Where and how to reach an S3-compatible bucket.
+    #[derive(Clone)]
+    pub struct S3Config {
+        /// Service endpoint, e.g. `https://s3.eu-west-1.amazonaws.com` or
+        /// `http://minio.local:9000`. Buckets are addressed path-style.
+        pub endpoint: Url,
+
+        /// Bucket to store backups in
+        pub bucket: String,
+
+        /// Key prefix for every backup, e.g. `immich/` (may be empty)
+        pub prefix: String,
+
+        /// Signing region (`us-east-1` for MinIO unless configured otherwise)
+        pub region: String,
+
+        /// Access key ID
+        pub access_key_id: String,
+
+        /// Secret access key
+        pub secret_access_key: String,
+    }
+
+    impl std::fmt::Debug for S3Config {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("S3Config")
+                .field("endpoint", &self.endpoint.as_str())
+                .field("bucket", &self.bucket)
+                .field("prefix", &self.prefix)
+                .field("region", &self.region)
+                .field("access_key_id", &self.access_key_id)
+                .field("secret_access_key", &"<redacted>")
+                .finish()
+        }
+    }
+
+    /// Backups as objects in an S3-compatible bucket.
+    ///
+    /// Originals are streamed from Immich into a multipart upload, a part
+    /// at a time, so nothing is buffered on disk, only a few parts are held
+    /// in memory whatever the original's size, and no length is needed up
+    /// front. A backup only counts once the bucket has acknowledged every
+    /// part of it; an upload that fails partway is aborted.
+    pub struct S3Store {
+        config: S3Config,
+        /// The bucket's client, or why it couldn't be configured
+        bucket: std::result::Result<AmazonS3, String>,
+        part_size: usize,
+    }
+
+    impl S3Store {
+        /// Store backups in the configured bucket.
+        pub fn new(config: S3Config) -> Self {
+            let bucket = AmazonS3Builder::new()
+                .with_endpoint(config.endpoint.as_str().trim_end_matches('/'))
+                .with_allow_http(config.endpoint.scheme() == "http")
+                .with_virtual_hosted_style_request(false)
+                .with_bucket_name(&config.bucket)
+                .with_region(&config.region)
+                .with_access_key_id(&config.access_key_id)
+                .with_secret_access_key(&config.secret_access_key)
+                .build()
+                .map_err(|e| e.to_string());
+            Self {
+                config,
+                bucket,
+                part_size: PART_SIZE,
+            }
+        }
+
+        /// Upload originals in parts of `bytes` instead of the default 8 MiB.
+        pub fn with_part_size(mut self, bytes: usize) -> Self {
+            self.part_size = bytes.max(1);
+            self
+        }
+
+        /// Object key for the backup called `name`.
+        fn key(&self, name: &str) -> String {
+            format!("{}{}", self.config.prefix, name)
+        }
+
+        fn bucket(&self) -> Result<&AmazonS3> {
+            self.bucket
+                .as_ref()
+                .map_err(|e| ImmichError::Backup(format!("{}: {}", self.config.bucket, e)))
+        }
+
+        /// An error for `name` from the object store.
+        fn error(&self, name: &str, e: impl std::fmt::Display) -> ImmichError {
+            ImmichError::Backup(format!("{} failed to store '{}': {}", self.config.bucket, self.key(name), e))
+        }
+    }
+
+    impl std::fmt::Debug for S3Store {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            // The client's own Debug output includes the credentials
+            f.debug_struct("S3Store")
+                .field("config", &self.config)
+                .field("part_size", &self.part_size)
+                .finish()
+        }
+    }
+
+    impl BackupStore for S3Store {
+        fn location(&self, name: &str) -> PathBuf {
+            PathBuf::from(format!("s3://{}/{}", self.config.bucket, self.key(name)))
+        }
+
+        fn is_local(&self) -> bool {
+            false
+        }
+
+        fn put_asset<'a>(&'a self, client: &'a ImmichClient, asset_id: &'a str, name: &'a str) -> BoxFuture<'a, Result<u64>> {
+            Box::pin(async move {
+                let bucket = self.bucket()?;
+                let response = client.original_response(asset_id).await?;
+                let expected = response.content_length();
+
+                let upload = bucket
+                    .put_multipart(&self.key(name).into())
+                    .await
+                    .map_err(|e| self.error(name, e))?;
+                let mut writer = WriteMultipart::new_with_chunk_size(upload, self.part_size);
+                let mut stream = response.bytes_stream();
+                let mut sent = 0u64;
+                let streamed = async {
+                    while let Some(chunk) = stream.next().await {
+                        let chunk = chunk?;
+                        writer.wait_for_capacity(PARTS_IN_FLIGHT).await.map_err(|e| self.error(name, e))?;
+                        sent += chunk.len() as u64;
+                        writer.put(chunk);
+                    }
+                    match expected {
+                        Some(len) if len != sent => Err(ImmichError::Backup(format!(
+                            "downloaded {} of {} bytes of '{}'",
+                            sent,
+                            len,
+                            self.key(name)
+                        ))),
+                        _ => Ok(()),
+                    }
+                }
+                .await;
+
+                if let Err(e) = streamed {
+                    // Don't leave the parts already uploaded behind in the bucket
+                    let _ = writer.abort().await;
+                    return Err(e);
+                }
+                writer.finish().await.map_err(|e| self.error(name, e))?;
+                Ok(sent)
+            })
+        }
+
+        fn put_bytes<'a>(&'a self, name: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                self.bucket()?
+                    .put(&self.key(name).into(), bytes.into())
+                    .await
+                    .map_err(|e| self.error(name, e))?;
+                Ok(())
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+        #[tokio::test]
+        async fn test_original_uploaded_in_parts() {
+            let groups = synthetic_groups(1, 2).unwrap();
+            let server = MockServer::start(&groups).await.unwrap();
+            let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+            let asset_id = &groups[0].assets[1].id;
+            let original = client.original_response(asset_id).await.unwrap().bytes().await.unwrap();
+            assert!(original.len() > 4);
+
+            let store = S3Store::new(S3Config {
+                endpoint: Url::parse(&server.url()).unwrap(),
+                bucket: "backups".to_string(),
+                prefix: "immich/".to_string(),
+                region: "us-east-1".to_string(),
+                access_key_id: "minio".to_string(),
+                secret_access_key: "minio-secret".to_string(),
+            })
+            .with_part_size(4);
+
+            let size = store.put_asset(&client, asset_id, "a_IMG.jpg").await.unwrap();
+            assert_eq!(size, original.len() as u64);
+            assert_eq!(server.object("backups/immich/a_IMG.jpg").unwrap(), original);
+            assert_eq!(server.object_parts(), original.len().div_ceil(4));
+
+            store.put_bytes("a_IMG.jpg.xmp", b"<xmp/>".to_vec()).await.unwrap();
+            assert_eq!(server.object("backups/immich/a_IMG.jpg.xmp").unwrap(), b"<xmp/>");
+            assert!(!format!("{:?}", store).contains("minio-secret"));
+        }
+    }
+}
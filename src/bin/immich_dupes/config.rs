@@ -5,13 +5,20 @@
 //! - Linux: ~/.config/immich-dupes/config.toml
 //! - Windows: C:\Users\<user>\AppData\Roaming\immich-dupes\config.toml
 
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password};
 use directories::ProjectDirs;
+use immich_lib::doctor::{Finding, FindingLevel};
+use immich_lib::notify::{NotificationConfig, NotificationFormat};
+use immich_lib::support::REDACTED;
+use immich_lib::thumbnails::DEFAULT_MAX_BYTES;
+use immich_lib::{ArchivedPolicy, Thresholds, TrashedPolicy};
 use serde::{Deserialize, Serialize};
 
 /// Application configuration.
@@ -20,10 +27,207 @@ pub struct Config {
     /// Server connection settings.
     #[serde(default)]
     pub server: ServerConfig,
+
+    /// Albums and tags whose members need a verified transfer before deletion.
+    #[serde(default, skip_serializing_if = "ProtectionConfig::is_empty")]
+    pub protection: ProtectionConfig,
+
+    /// Webhook notified when `execute` finishes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification: Option<NotificationConfig>,
+
+    /// Local thumbnail cache used by review tools.
+    #[serde(default, skip_serializing_if = "ThumbnailCacheConfig::is_default")]
+    pub thumbnail_cache: ThumbnailCacheConfig,
+
+    /// Detection thresholds used by analysis and scenario detection.
+    #[serde(default, skip_serializing_if = "ThresholdsConfig::is_default")]
+    pub thresholds: ThresholdsConfig,
+
+    /// Named servers, selected with `--profile`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+
+    /// Profile applied by [`Config::with_profile`], if any.
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+}
+
+impl Config {
+    /// Configuration with a profile applied.
+    ///
+    /// The profile's server replaces `[server]`, its protected albums and
+    /// tags are added to the global ones, and its thresholds and
+    /// notification override the global settings they set.
+    pub fn with_profile(&self, name: &str) -> Result<Config> {
+        let Some(profile) = self.profiles.get(name) else {
+            bail!("No profile named '{}' (see `immich-dupes profiles list`)", name);
+        };
+        let mut config = self.clone();
+        config.server = ServerConfig {
+            url: Some(profile.url.clone()),
+            api_key: profile.resolve_api_key(name)?,
+        };
+        config.protection = self
+            .protection
+            .merged(profile.protection.albums.clone(), profile.protection.tags.clone());
+        config.thresholds = profile.thresholds.or(&self.thresholds);
+        if profile.notification.is_some() {
+            config.notification = profile.notification.clone();
+        }
+        config.active_profile = Some(name.to_string());
+        Ok(config)
+    }
+
+    /// Notification settings with command-line overrides applied.
+    ///
+    /// A URL on the command line enables notifications even if the config
+    /// file has none; the template and format fall back to the config file.
+    pub fn notification_with(
+        &self,
+        url: Option<String>,
+        template: Option<String>,
+        format: Option<NotificationFormat>,
+    ) -> Option<NotificationConfig> {
+        let mut notification = match (url, &self.notification) {
+            (Some(url), Some(configured)) => NotificationConfig { url, ..configured.clone() },
+            (Some(url), None) => NotificationConfig::new(url),
+            (None, Some(configured)) => configured.clone(),
+            (None, None) => return None,
+        };
+        if template.is_some() {
+            notification.template = template;
+        }
+        if let Some(format) = format {
+            notification.format = format;
+        }
+        Some(notification)
+    }
+}
+
+/// Thumbnail cache settings.
+///
+/// ```toml
+/// [thumbnail_cache]
+/// dir = "/var/cache/immich-dupes/thumbnails"
+/// max_size_mb = 2048
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThumbnailCacheConfig {
+    /// Cache directory (default: OS cache directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// Size limit in MiB (default: 512).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+}
+
+impl ThumbnailCacheConfig {
+    /// Returns true if nothing is configured.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Cache directory and size limit in bytes, with command-line overrides applied.
+    pub fn resolve(&self, dir: Option<PathBuf>, max_size_mb: Option<u64>) -> (PathBuf, u64) {
+        let dir = dir
+            .or_else(|| self.dir.clone())
+            .unwrap_or_else(thumbnail_cache_path);
+        let max_bytes = max_size_mb
+            .or(self.max_size_mb)
+            .map(|mb| mb * 1024 * 1024)
+            .unwrap_or(DEFAULT_MAX_BYTES);
+        (dir, max_bytes)
+    }
+}
+
+/// Detection thresholds, from the config file or the command line.
+///
+/// ```toml
+/// [thresholds]
+/// gps_threshold_m = 25
+/// time_tolerance_secs = 120
+/// low_confidence = 60
+/// large_file_mb = 100
+/// trashed = "ignore"
+/// archived = "protect"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, clap::Args)]
+pub struct ThresholdsConfig {
+    /// GPS coordinates further apart than this many meters are a conflict (default: 11)
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gps_threshold_m: Option<f64>,
+    /// Capture times closer than this many seconds are not a conflict (default: 60)
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_tolerance_secs: Option<i64>,
+    /// Groups below this confidence (0-100) are flagged for review (default: 50)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_confidence: Option<u8>,
+    /// Files larger than this many MiB count as large (default: 50)
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub large_file_mb: Option<u64>,
+    /// Groups with a trashed asset: "score" (like any other), "skip", or "ignore" (leave trashed assets out) (default: score)
+    #[arg(long, value_name = "POLICY")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trashed: Option<TrashedPolicy>,
+    /// Groups with an archived asset: "score" (like any other), "skip", or "protect" (never a loser) (default: score)
+    #[arg(long, value_name = "POLICY")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived: Option<ArchivedPolicy>,
+}
+
+impl ThresholdsConfig {
+    /// Returns true if nothing is configured.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These thresholds, falling back to `fallback` for any not set.
+    pub fn or(&self, fallback: &ThresholdsConfig) -> ThresholdsConfig {
+        ThresholdsConfig {
+            gps_threshold_m: self.gps_threshold_m.or(fallback.gps_threshold_m),
+            time_tolerance_secs: self.time_tolerance_secs.or(fallback.time_tolerance_secs),
+            low_confidence: self.low_confidence.or(fallback.low_confidence),
+            large_file_mb: self.large_file_mb.or(fallback.large_file_mb),
+            trashed: self.trashed.or(fallback.trashed),
+            archived: self.archived.or(fallback.archived),
+        }
+    }
+
+    /// Thresholds with command-line overrides applied over the config file.
+    pub fn resolve(&self, overrides: &ThresholdsConfig) -> Thresholds {
+        let defaults = Thresholds::default();
+        Thresholds {
+            gps_threshold_m: overrides
+                .gps_threshold_m
+                .or(self.gps_threshold_m)
+                .unwrap_or(defaults.gps_threshold_m),
+            capture_time_tolerance: overrides
+                .time_tolerance_secs
+                .or(self.time_tolerance_secs)
+                .map(chrono::Duration::seconds)
+                .unwrap_or(defaults.capture_time_tolerance),
+            low_confidence: overrides
+                .low_confidence
+                .or(self.low_confidence)
+                .unwrap_or(defaults.low_confidence),
+            large_file_bytes: overrides
+                .large_file_mb
+                .or(self.large_file_mb)
+                .map(|mb| mb * 1024 * 1024)
+                .unwrap_or(defaults.large_file_bytes),
+            trashed: overrides.trashed.or(self.trashed).unwrap_or(defaults.trashed),
+            archived: overrides.archived.or(self.archived).unwrap_or(defaults.archived),
+        }
+    }
 }
 
 /// Server connection configuration.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct ServerConfig {
     /// Immich server URL.
     pub url: Option<String>,
@@ -31,6 +235,165 @@ pub struct ServerConfig {
     pub api_key: Option<String>,
 }
 
+/// Prints a set API key as `<redacted>`.
+impl fmt::Debug for ServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerConfig")
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
+/// A named server with its own defaults.
+///
+/// ```toml
+/// [profiles.home]
+/// url = "https://photos.home.example"
+/// api_key = "..."
+///
+/// [profiles.parents]
+/// url = "https://immich.parents.example"
+/// keyring = true
+///
+/// [profiles.parents.thresholds]
+/// gps_threshold_m = 25
+/// ```
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// Immich server URL.
+    pub url: String,
+    /// API key for authentication, unless kept in the keyring.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// The API key is kept in the OS keyring (builds with `--features keyring`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyring: bool,
+    /// Albums and tags protected on this server, on top of the global ones.
+    #[serde(default, skip_serializing_if = "ProtectionConfig::is_empty")]
+    pub protection: ProtectionConfig,
+    /// Thresholds for this server, overriding the global ones they set.
+    #[serde(default, skip_serializing_if = "ThresholdsConfig::is_default")]
+    pub thresholds: ThresholdsConfig,
+    /// Webhook for this server, instead of the global one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification: Option<NotificationConfig>,
+}
+
+/// Prints a set API key as `<redacted>`.
+impl fmt::Debug for ProfileConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfileConfig")
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_ref().map(|_| REDACTED))
+            .field("keyring", &self.keyring)
+            .field("protection", &self.protection)
+            .field("thresholds", &self.thresholds)
+            .field("notification", &self.notification)
+            .finish()
+    }
+}
+
+impl ProfileConfig {
+    /// The profile's API key, read from the keyring if it is kept there.
+    pub fn resolve_api_key(&self, name: &str) -> Result<Option<String>> {
+        if self.keyring {
+            keyring_get(name).map(Some)
+        } else {
+            Ok(self.api_key.clone())
+        }
+    }
+}
+
+/// Keyring service the profile API keys are stored under, one entry per profile.
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "immich-dupes";
+
+/// Reads a profile's API key from the OS keyring.
+#[cfg(feature = "keyring")]
+pub fn keyring_get(profile: &str) -> Result<String> {
+    keyring::Entry::new(KEYRING_SERVICE, profile)
+        .and_then(|entry| entry.get_password())
+        .with_context(|| format!("Failed to read the API key for profile '{}' from the keyring", profile))
+}
+
+/// Stores a profile's API key in the OS keyring.
+#[cfg(feature = "keyring")]
+pub fn keyring_set(profile: &str, api_key: &str) -> Result<()> {
+    keyring::Entry::new(KEYRING_SERVICE, profile)
+        .and_then(|entry| entry.set_password(api_key))
+        .with_context(|| format!("Failed to store the API key for profile '{}' in the keyring", profile))
+}
+
+/// Removes a profile's API key from the OS keyring, if it is there.
+#[cfg(feature = "keyring")]
+pub fn keyring_delete(profile: &str) -> Result<()> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, profile)?;
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e)
+            .with_context(|| format!("Failed to remove the API key for profile '{}' from the keyring", profile)),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_get(profile: &str) -> Result<String> {
+    bail!(
+        "Profile '{}' keeps its API key in the OS keyring, but this build has no keyring support (rebuild with --features keyring)",
+        profile
+    )
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_set(_profile: &str, _api_key: &str) -> Result<()> {
+    bail!("This build has no keyring support (rebuild with --features keyring)")
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_delete(_profile: &str) -> Result<()> {
+    Ok(())
+}
+
+/// Deletion protection settings.
+///
+/// ```toml
+/// [protection]
+/// albums = ["Kitchen Frame"]
+/// tags = ["Frames/Living Room"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProtectionConfig {
+    /// Protected album names or IDs.
+    #[serde(default)]
+    pub albums: Vec<String>,
+    /// Protected tag names, values, or IDs.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl ProtectionConfig {
+    /// Returns true if nothing is protected.
+    pub fn is_empty(&self) -> bool {
+        self.albums.is_empty() && self.tags.is_empty()
+    }
+
+    /// Combine with albums and tags given on the command line.
+    pub fn merged(&self, albums: Vec<String>, tags: Vec<String>) -> Self {
+        let mut merged = self.clone();
+        for album in albums {
+            if !merged.albums.contains(&album) {
+                merged.albums.push(album);
+            }
+        }
+        for tag in tags {
+            if !merged.tags.contains(&tag) {
+                merged.tags.push(tag);
+            }
+        }
+        merged
+    }
+}
+
 /// Returns the path to the configuration file.
 ///
 /// Uses OS-native configuration directories via the `directories` crate.
@@ -48,6 +411,27 @@ pub fn config_path() -> PathBuf {
     }
 }
 
+/// Returns the default thumbnail cache directory.
+///
+/// Uses the OS cache directory, falling back to `thumbnails` next to the
+/// config file.
+pub fn thumbnail_cache_path() -> PathBuf {
+    match ProjectDirs::from("", "", "immich-dupes") {
+        Some(proj_dirs) => proj_dirs.cache_dir().join("thumbnails"),
+        None => config_path().with_file_name("thumbnails"),
+    }
+}
+
+/// Returns the default deletion ledger path, next to the config file.
+pub fn ledger_path() -> PathBuf {
+    config_path().with_file_name("ledger.jsonl")
+}
+
+/// Returns the default execution history directory, next to the config file.
+pub fn history_dir() -> PathBuf {
+    config_path().with_file_name("runs")
+}
+
 /// Loads configuration from the config file.
 ///
 /// Returns `Config::default()` if the file doesn't exist or parsing fails.
@@ -113,6 +497,79 @@ pub fn save(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Checks the config file for problems, for `immich-dupes doctor`.
+///
+/// Unlike [`load`], parse errors are reported rather than ignored.
+pub fn diagnose() -> Vec<Finding> {
+    let path = config_path();
+    let mut findings = Vec::new();
+
+    if !path.exists() {
+        findings.push(Finding::new(
+            "config",
+            FindingLevel::Info,
+            format!("No config file at {}; using CLI/env credentials", path.display()),
+        ));
+        return findings;
+    }
+
+    let config = match load_inner() {
+        Ok(config) => config,
+        Err(e) => {
+            findings.push(
+                Finding::new("config", FindingLevel::Error, format!("{:#}", e))
+                    .with_hint("Fix or delete the file; it is currently ignored"),
+            );
+            return findings;
+        }
+    };
+
+    match config.server.url.as_deref() {
+        Some(url) if !(url.starts_with("http://") || url.starts_with("https://")) => findings.push(
+            Finding::new("config", FindingLevel::Error, format!("Server URL '{}' has no http(s) scheme", url))
+                .with_hint("Use the full URL, e.g. https://immich.example.com"),
+        ),
+        Some(url) if url::Url::parse(url).is_err() => findings.push(Finding::new(
+            "config",
+            FindingLevel::Error,
+            format!("Server URL '{}' is not a valid URL", url),
+        )),
+        Some(_) => {}
+        None => findings.push(Finding::new("config", FindingLevel::Info, "Config file has no server URL")),
+    }
+
+    if config.server.api_key.as_deref().is_some_and(|k| k.trim().is_empty()) {
+        findings.push(
+            Finding::new("config", FindingLevel::Error, "Config file has an empty API key")
+                .with_hint("Remove the api_key line or set a valid key"),
+        );
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if (config.server.api_key.is_some() || config.profiles.values().any(|p| p.api_key.is_some()))
+            && let Ok(metadata) = fs::metadata(&path)
+            && metadata.permissions().mode() & 0o077 != 0
+        {
+            findings.push(
+                Finding::new("config", FindingLevel::Warning, "Config file with API key is readable by other users")
+                    .with_hint(format!("chmod 600 {}", path.display())),
+            );
+        }
+    }
+
+    if findings.is_empty() {
+        findings.push(Finding::new(
+            "config",
+            FindingLevel::Ok,
+            format!("Config file {} is valid", path.display()),
+        ));
+    }
+
+    findings
+}
+
 /// Prompts the user for Immich server credentials interactively.
 ///
 /// Displays prompts for URL and API key with validation.
@@ -163,6 +620,25 @@ pub fn prompt_save(config_path: &Path) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_debug_redacts_api_keys() {
+        let mut config = Config::default();
+        config.server.api_key = Some("server-secret".to_string());
+        config.profiles.insert(
+            "home".to_string(),
+            ProfileConfig {
+                url: "https://photos.home.example".to_string(),
+                api_key: Some("profile-secret".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("photos.home.example"));
+        assert!(!debug.contains("server-secret"));
+        assert!(!debug.contains("profile-secret"));
+    }
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
@@ -193,6 +669,7 @@ mod tests {
                 url: Some("https://immich.example.com".to_string()),
                 api_key: Some("test-api-key".to_string()),
             },
+            ..Default::default()
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -204,4 +681,96 @@ mod tests {
         );
         assert_eq!(parsed.server.api_key.as_deref(), Some("test-api-key"));
     }
+
+    #[test]
+    fn test_protection_config_parses_and_merges() {
+        let config: Config = toml::from_str(
+            "[protection]\nalbums = [\"Kitchen Frame\"]\ntags = [\"Frames/Living Room\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.protection.albums, vec!["Kitchen Frame".to_string()]);
+
+        let merged = config
+            .protection
+            .merged(vec!["Kitchen Frame".to_string(), "Hall".to_string()], Vec::new());
+        assert_eq!(merged.albums, vec!["Kitchen Frame".to_string(), "Hall".to_string()]);
+        assert_eq!(merged.tags, vec!["Frames/Living Room".to_string()]);
+    }
+
+    #[test]
+    fn test_thumbnail_cache_overrides() {
+        let config: Config =
+            toml::from_str("[thumbnail_cache]\ndir = \"/tmp/thumbs\"\nmax_size_mb = 64\n").unwrap();
+        let (dir, max_bytes) = config.thumbnail_cache.resolve(None, None);
+        assert_eq!(dir, PathBuf::from("/tmp/thumbs"));
+        assert_eq!(max_bytes, 64 * 1024 * 1024);
+
+        let (_, max_bytes) = config.thumbnail_cache.resolve(None, Some(8));
+        assert_eq!(max_bytes, 8 * 1024 * 1024);
+        assert_eq!(Config::default().thumbnail_cache.resolve(None, None).1, DEFAULT_MAX_BYTES);
+    }
+
+    #[test]
+    fn test_thresholds_overrides() {
+        let config: Config = toml::from_str("[thresholds]
+gps_threshold_m = 25
+large_file_mb = 100
+trashed = \"ignore\"
+").unwrap();
+        let overrides = ThresholdsConfig {
+            gps_threshold_m: Some(5.0),
+            low_confidence: Some(70),
+            ..Default::default()
+        };
+        let thresholds = config.thresholds.resolve(&overrides);
+        assert_eq!(thresholds.gps_threshold_m, 5.0);
+        assert_eq!(thresholds.low_confidence, 70);
+        assert_eq!(thresholds.large_file_bytes, 100 * 1024 * 1024);
+        assert_eq!(thresholds.trashed, TrashedPolicy::Ignore);
+        assert_eq!(thresholds.archived, ArchivedPolicy::Score);
+        assert_eq!(thresholds.capture_time_tolerance, Thresholds::default().capture_time_tolerance);
+        assert_eq!(Config::default().thresholds.resolve(&ThresholdsConfig::default()), Thresholds::default());
+    }
+
+    #[test]
+    fn test_profile_applied_over_global_settings() {
+        let config: Config = toml::from_str(
+            r#"
+[server]
+url = "https://home.example"
+api_key = "home-key"
+
+[protection]
+albums = ["Kitchen Frame"]
+
+[thresholds]
+gps_threshold_m = 25
+low_confidence = 60
+
+[profiles.parents]
+url = "https://parents.example"
+api_key = "parents-key"
+
+[profiles.parents.protection]
+albums = ["Wedding"]
+
+[profiles.parents.thresholds]
+low_confidence = 80
+"#,
+        )
+        .unwrap();
+
+        let parents = config.with_profile("parents").unwrap();
+        assert_eq!(parents.server.url.as_deref(), Some("https://parents.example"));
+        assert_eq!(parents.server.api_key.as_deref(), Some("parents-key"));
+        assert_eq!(parents.protection.albums, vec!["Kitchen Frame".to_string(), "Wedding".to_string()]);
+        let thresholds = parents.thresholds.resolve(&ThresholdsConfig::default());
+        assert_eq!(thresholds.low_confidence, 80);
+        assert_eq!(thresholds.gps_threshold_m, 25.0);
+        assert!(config.with_profile("work").is_err());
+
+        let reparsed: Config = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.profiles["parents"].url, "https://parents.example");
+        assert!(!reparsed.profiles["parents"].keyring);
+    }
 }
@@ -1,22 +1,72 @@
 //! CLI tool for managing Immich duplicates with metadata-aware selection.
 
 mod config;
+#[macro_use]
+mod output;
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
 use std::num::NonZeroU32;
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 use governor::{Quota, RateLimiter};
 use indicatif::{ProgressBar, ProgressStyle};
-use serde::{Deserialize, Serialize};
-
-use immich_lib::models::ExecutionConfig;
-use immich_lib::testing::{all_fixtures, detect_scenarios, format_report, generate_image, ScenarioReport};
-use immich_lib::{DuplicateAnalysis, Executor, ImmichClient, LetterboxAnalysis};
+use serde::Serialize;
+use serde_json::json;
+
+use output::Output;
+
+#[cfg(feature = "s3")]
+use immich_lib::backup::S3Config;
+use immich_lib::doctor::{self, Finding, FindingLevel};
+use immich_lib::integrity::{check_file, CorruptOriginal, IntegrityIssue};
+use immich_lib::models::{
+    AssetState, AssetType, BackupSink, BackupTarget, CrossLibraryPolicy, DescriptionPolicy, DuplicateGroup, ExecutionConfig,
+    ExecutionMode, ExecutionPlan, ExecutionReport, PromotionKind, ReportFormat, SharedAlbumPolicy, SharedLinkPolicy,
+};
+use immich_lib::testing::{
+    self, all_fixtures, format_report, generate_image, run_bench, BenchOptions, FixtureManifest, ScenarioFixture,
+    ScenarioRegistry, ScenarioReport,
+};
+use immich_lib::history::{self, HistoryStore};
+use immich_lib::ledger::{Ledger, LedgerAction, LedgerEntry, LedgerQuery};
+use immich_lib::metrics::{self, Metrics};
+use immich_lib::notify::{NotificationConfig, NotificationFormat};
+use immich_lib::pipeline::{self, Pipeline, PlanOptions, RunStage};
+use immich_lib::reconcile;
+use immich_lib::reports::ReportRollup;
+use immich_lib::report_io;
+use immich_lib::retention::{self, RetentionPolicy};
+use immich_lib::sidecar::sidecar_path;
+use immich_lib::stats::LibraryStats;
+use immich_lib::support::{EnvironmentInfo, SupportBundle, REDACTED};
+use immich_lib::tagging;
+use immich_lib::units;
+use immich_lib::thumbnails::{ThumbnailCache, ThumbnailSize};
+use immich_lib::verify::{AssetStatus, Verifier, VerifyProgress};
+#[cfg(feature = "sqlite")]
+use immich_lib::store::AnalysisStore;
+use immich_lib::sampling::{self, SampleOptions};
+use immich_lib::review::{album_web_url, apply_event_decision, export_review_csv, import_review_csv};
+use immich_lib::review_album::{self, DEFAULT_REVIEW_ALBUM};
+use immich_lib::risk::{self, RiskGrade, RiskOptions, RiskReport};
+use immich_lib::{
+    cluster_events, AnalysisReport, ClusterOptions, Decision, DuplicateAnalysis, Executor, GroupFilter, ImmichClient,
+    LetterboxAnalysis, ReviewDecision, ScoredAsset, SearchIndex, SelectionWarning, Thresholds,
+    UploadCheck, UploadOptions, file_checksum,
+};
+
+/// The current time as used in report and bundle file names.
+fn file_stamp() -> String {
+    Utc::now().format("%Y%m%d-%H%M%S").to_string()
+}
 
 /// Immich duplicate manager - prioritizes metadata completeness over file size
 #[derive(Parser, Debug)]
@@ -31,10 +81,19 @@ struct Args {
     #[arg(short, long, env = "IMMICH_API_KEY", required = false)]
     api_key: Option<String>,
 
+    /// Server profile from the config file; takes precedence over --url and --api-key
+    #[arg(long, env = "IMMICH_PROFILE", global = true)]
+    profile: Option<String>,
+
     /// Save credentials to config file after successful connection
     #[arg(long, global = true)]
     save: bool,
 
+    /// Emit JSON events on stdout (human output goes to stderr);
+    /// supported by analyze, watch, execute, run, prune-backups, generate-fixtures, and seed-fixtures
+    #[arg(long, global = true)]
+    json: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -43,40 +102,98 @@ struct Args {
 enum Commands {
     /// Analyze duplicates and output results to JSON
     Analyze {
-        /// Output file path for JSON results
+        /// Output file path for JSON results (`.json.zst` writes zstd-compressed)
         #[arg(short, long)]
         output: PathBuf,
+
+        /// Only analyze groups with an asset carrying this tag (full value, name, or ID), e.g. dedupe/queue
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+
+        /// Only analyze groups with every asset taken on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_name = "DATE", value_parser = parse_date_arg)]
+        after: Option<DateTime<Utc>>,
+
+        /// Only analyze groups with every asset taken before this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_name = "DATE", value_parser = parse_date_arg)]
+        before: Option<DateTime<Utc>>,
+
+        /// Only analyze groups with every asset of this type: "image" or "video"
+        #[arg(long = "type", value_name = "TYPE")]
+        asset_type: Option<AssetType>,
+
+        /// Tag winners and groups needing review in Immich, so the analysis can be followed there
+        #[arg(long)]
+        tag_state: bool,
+
+        /// Tag for winners with --tag-state
+        #[arg(long, value_name = "TAG", default_value = tagging::DEFAULT_WINNER_TAG, requires = "tag_state")]
+        winner_tag: String,
+
+        /// Tag for the assets of groups needing review with --tag-state
+        #[arg(long, value_name = "TAG", default_value = tagging::DEFAULT_REVIEW_TAG, requires = "tag_state")]
+        review_tag: String,
+
+        /// Detection thresholds (default: [thresholds] in config)
+        #[command(flatten)]
+        thresholds: config::ThresholdsConfig,
     },
 
-    /// Execute duplicate removal based on analysis JSON
-    Execute {
-        /// Path to analysis JSON from analyze command
+    /// Analyze, then analyze again whenever duplicates may have changed, until interrupted
+    Watch {
+        /// Output file path for JSON results, rewritten after each analysis
         #[arg(short, long)]
-        input: PathBuf,
+        output: PathBuf,
 
-        /// Directory to download backup files to
+        /// Seconds between analyses; with the `events` feature, only used while the server's event stream is unavailable
+        #[arg(long, default_value = "3600")]
+        interval: u64,
+
+        /// Only analyze groups with an asset carrying this tag (full value, name, or ID), e.g. dedupe/queue
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+
+        /// Detection thresholds (default: [thresholds] in config)
+        #[command(flatten)]
+        thresholds: config::ThresholdsConfig,
+    },
+
+    /// Execute duplicate removal based on analysis JSON
+    Execute(Box<ExecuteArgs>),
+
+    /// Analyze, plan, confirm, execute, and verify in one resumable step
+    Run {
+        /// Directory holding the run's state; an unfinished run here is resumed
+        #[arg(long, default_value = "immich-dupes-run")]
+        work_dir: PathBuf,
+
+        /// Directory to download backup files to (default: <WORK_DIR>/backups)
         #[arg(short, long)]
-        backup_dir: PathBuf,
+        backup_dir: Option<PathBuf>,
 
-        /// Permanently delete instead of moving to trash
+        /// Discard an unfinished run in the work directory and plan a new one
         #[arg(long, default_value = "false")]
-        force: bool,
+        fresh: bool,
 
         /// Max requests per second (default: 10)
         #[arg(long, default_value = "10")]
         rate_limit: u32,
 
-        /// Max concurrent operations (default: 5)
-        #[arg(long, default_value = "5")]
-        concurrent: usize,
+        /// Deletion ledger to append to (default: ledger.jsonl next to the config file)
+        #[arg(long)]
+        ledger: Option<PathBuf>,
 
-        /// Skip groups that need manual review
-        #[arg(long, default_value = "false")]
-        skip_review: bool,
+        /// Only plan groups with an asset carrying this tag (full value, name, or ID), e.g. dedupe/queue
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
 
-        /// Skip confirmation prompt
-        #[arg(short, long, default_value = "false")]
-        yes: bool,
+        /// After the run, tag the winner of every processed group with this tag (full value, created if needed)
+        #[arg(long, value_name = "TAG")]
+        tag_winners: Option<String>,
+
+        /// Detection thresholds (default: [thresholds] in config)
+        #[command(flatten)]
+        thresholds: config::ThresholdsConfig,
     },
 
     /// Verify post-execution state: check winners exist, losers deleted
@@ -87,6 +204,37 @@ enum Commands {
         /// Output format (text or json)
         #[arg(long, default_value = "text")]
         format: String,
+
+        /// Verify a random sample first (e.g. "5%"); all groups are verified only if it finds problems
+        #[arg(long, value_parser = parse_fraction_arg)]
+        sample: Option<f64>,
+
+        /// With --sample, keep growing a clean sample until the failure rate's 95% upper bound is below --max-failure-rate
+        #[arg(long, requires = "sample")]
+        confidence_stop: bool,
+
+        /// Acceptable failure rate for --confidence-stop
+        #[arg(long, default_value = "1%", value_parser = parse_fraction_arg)]
+        max_failure_rate: f64,
+
+        /// Seed for the random sample, to reproduce a run (default: random)
+        #[arg(long, requires = "sample")]
+        seed: Option<u64>,
+    },
+
+    /// Audit all originals on the server for zero-byte or corrupt files
+    AuditOriginals {
+        /// Download every original and sniff its contents (slow)
+        #[arg(long, default_value = "false")]
+        deep: bool,
+
+        /// With --deep, also fully decode still images
+        #[arg(long, default_value = "false")]
+        decode: bool,
+
+        /// Output file for the JSON report (stdout summary only if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Find test candidates by scanning duplicate groups and categorizing by scenario
@@ -102,6 +250,10 @@ enum Commands {
         /// Output file (stdout if not specified)
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Detection thresholds (default: [thresholds] in config)
+        #[command(flatten)]
+        thresholds: config::ThresholdsConfig,
     },
 
     /// Generate synthetic test fixtures
@@ -115,329 +267,1057 @@ enum Commands {
         scenario: Option<String>,
     },
 
+    /// Generate fixtures (or reuse generated ones), upload them to Immich, and
+    /// print the duplicate group each scenario ends up in
+    SeedFixtures {
+        /// Fixtures directory (scenario directories and base images)
+        #[arg(long, default_value = "tests/fixtures")]
+        fixtures_dir: PathBuf,
+
+        /// Only seed specific scenario (e.g., "W1", "C3")
+        #[arg(long)]
+        scenario: Option<String>,
+
+        /// Regenerate fixtures even if they were generated before
+        #[arg(long)]
+        regenerate: bool,
+
+        /// Seconds to wait for Immich to process uploads and detect duplicates
+        #[arg(long, default_value = "300")]
+        timeout: u64,
+    },
+
+    /// Benchmark analysis and execution throughput against an in-process mock server
+    Bench {
+        /// Number of synthetic duplicate groups
+        #[arg(long, default_value = "10000")]
+        groups: usize,
+
+        /// Assets per group (winner + losers)
+        #[arg(long, default_value = "2")]
+        assets_per_group: usize,
+
+        /// Max concurrent operations for the executor phase
+        #[arg(long, default_value = "5")]
+        concurrent: usize,
+
+        /// Only benchmark analysis (skip the executor phase)
+        #[arg(long, default_value = "false")]
+        analyze_only: bool,
+
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
     /// Restore backed-up files by uploading them to Immich
     Restore {
         /// Directory containing backup files from execute command
         #[arg(short, long)]
         backup_dir: PathBuf,
 
+        /// Analysis the backups were made from, to restore each asset's GPS, capture time, and description after upload
+        #[arg(long, value_name = "PATH")]
+        analysis: Option<PathBuf>,
+
         /// Preview what would be restored without uploading
         #[arg(long, default_value = "false")]
         dry_run: bool,
     },
 
+    /// Delete old backups of assets whose deletion was verified
+    PruneBackups {
+        /// Directory containing backup files and execution reports from execute command
+        #[arg(short, long)]
+        backup_dir: PathBuf,
+
+        /// Prune backups older than this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u32>,
+
+        /// Prune the oldest backups until the rest fit in this many GB
+        #[arg(long, value_name = "GB")]
+        max_size_gb: Option<f64>,
+
+        /// Preview what would be pruned without deleting anything
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+    },
+
+    /// Check backups against recorded deletions: backups of assets still on
+    /// the server, and deletions without a backup
+    Reconcile {
+        /// Directory containing backup files and execution reports from execute command
+        #[arg(short, long)]
+        backup_dir: PathBuf,
+
+        /// Ledger file (default: ledger.jsonl next to the config file)
+        #[arg(long)]
+        ledger: Option<PathBuf>,
+    },
+
     /// Letterbox duplicate management (iPhone 4:3/16:9 pairs)
     Letterbox {
         #[command(subcommand)]
         command: LetterboxCommands,
     },
-}
 
-#[derive(Subcommand, Debug)]
-enum LetterboxCommands {
-    /// Analyze all assets for letterbox pairs and output results to JSON
-    Analyze {
-        /// Output file path for JSON results
+    /// Review groups that need manual review, clustered into events
+    Review {
+        #[command(subcommand)]
+        command: ReviewCommands,
+    },
+
+    /// Manage the local thumbnail cache used by review tools
+    Thumbnails {
+        /// Cache directory (default: from config, else the OS cache directory)
+        #[arg(long, global = true)]
+        cache_dir: Option<PathBuf>,
+
+        /// Cache size limit in MiB (default: from config, else 512)
+        #[arg(long, global = true)]
+        cache_size_mb: Option<u64>,
+
+        #[command(subcommand)]
+        command: ThumbnailCommands,
+    },
+
+    /// Export groups to a spreadsheet for offline review
+    ExportReview {
+        /// Path to analysis JSON from analyze command
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output file path for the review sheet
         #[arg(short, long)]
         output: PathBuf,
+
+        /// Output format (only csv is supported)
+        #[arg(long, default_value = "csv")]
+        format: String,
     },
 
-    /// Execute letterbox removal: download 16:9 backups then delete
-    Execute {
-        /// Path to letterbox analysis JSON from analyze command
+    /// Merge reviewer decisions from a spreadsheet back into the analysis JSON
+    ImportReview {
+        /// Path to the edited review CSV
+        decisions: PathBuf,
+
+        /// Path to analysis JSON from analyze command (updated in place)
         #[arg(short, long)]
         input: PathBuf,
 
-        /// Directory to download backup files to
+        /// Reviewer recorded for rows that leave the 'reviewer' column blank
+        #[arg(long, env = "IMMICH_REVIEWER")]
+        reviewer: Option<String>,
+    },
+
+    /// Search analysis results by filename, description, city, country, or camera
+    Search {
+        /// Words to search for; every word must match (prefixes allowed)
+        query: String,
+
+        /// Path to analysis JSON from analyze command
         #[arg(short, long)]
-        backup_dir: PathBuf,
+        input: PathBuf,
 
-        /// Permanently delete instead of moving to trash
-        #[arg(long, default_value = "false")]
-        force: bool,
+        /// Maximum number of groups to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
 
-        /// Max requests per second (default: 10)
-        #[arg(long, default_value = "10")]
-        rate_limit: u32,
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
 
-        /// Skip confirmation prompt
-        #[arg(short, long, default_value = "false")]
-        yes: bool,
+    /// Show the ledger of every asset this tool has deleted, across all runs,
+    /// or the step-by-step history of one execution run
+    History {
+        #[command(subcommand)]
+        command: Option<HistoryCommands>,
+
+        /// Only entries for this asset, winner, or group ID
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Only entries whose filename contains this text (case-insensitive)
+        #[arg(long)]
+        filename: Option<String>,
+
+        /// Only entries with this Immich checksum
+        #[arg(long)]
+        checksum: Option<String>,
+
+        /// Only entries recorded on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_date_arg)]
+        since: Option<DateTime<Utc>>,
+
+        /// Ledger file (default: ledger.jsonl next to the config file)
+        #[arg(long)]
+        ledger: Option<PathBuf>,
+
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
     },
 
-    /// Verify post-execution state: check keepers exist, deletes removed
-    Verify {
-        /// Path to the letterbox analysis JSON that was used for execution
-        analysis_json: PathBuf,
+    /// Total up execution reports across runs: assets deleted, space reclaimed, and failures per run
+    Reports {
+        /// Execution reports, or directories (such as backup directories) whose execution reports to include
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Output format (text, markdown, or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Collect diagnostics into a .tar.gz (secrets redacted) to attach to a bug report
+    SupportBundle {
+        /// Output file (default: immich-dupes-support-<timestamp>.tar.gz)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Backup directory from execute; its most recent execution reports are included
+        #[arg(short, long)]
+        backup_dir: Option<PathBuf>,
 
+        /// Extra report to include, e.g. saved `verify --format json` output (repeatable)
+        #[arg(long = "report", value_name = "FILE")]
+        reports: Vec<PathBuf>,
+
+        /// Log file to include, e.g. captured console output (repeatable)
+        #[arg(long = "log", value_name = "FILE")]
+        logs: Vec<PathBuf>,
+
+        /// Deletion ledger (default: ledger.jsonl next to the config file)
+        #[arg(long)]
+        ledger: Option<PathBuf>,
+    },
+
+    /// Manage named server profiles, selected with --profile
+    Profiles {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+
+    /// Check server, API key, jobs, and local config without changing anything
+    Doctor {
         /// Output format (text or json)
         #[arg(long, default_value = "text")]
         format: String,
     },
+
+    /// Show library size, duplicates, and reclaimable space without changing anything
+    Stats {
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+
+        /// Detection thresholds (default: [thresholds] in config)
+        #[command(flatten)]
+        thresholds: config::ThresholdsConfig,
+    },
 }
 
-/// Report containing analysis results for all duplicate groups.
-#[derive(Debug, Serialize, Deserialize)]
-struct AnalysisReport {
-    /// Timestamp when the analysis was generated
-    generated_at: DateTime<Utc>,
+/// Flags of the `execute` command (boxed in [`Commands`]: there are many).
+#[derive(clap::Args, Debug)]
+struct ExecuteArgs {
+    /// Path to analysis JSON (or `.json.zst`) from analyze command
+    #[arg(short, long)]
+    input: PathBuf,
 
-    /// The Immich server URL that was analyzed
-    server_url: String,
+    /// Directory to download backup files to
+    #[arg(short, long)]
+    backup_dir: PathBuf,
 
-    /// Total number of duplicate groups found
-    total_groups: usize,
+    /// How to keep backups: "directory" (one file each), or "tar" or "zip" (one dated archive per run, with a manifest)
+    #[arg(long, value_name = "SINK", default_value = "directory")]
+    backup_sink: BackupSink,
 
-    /// Total number of assets across all groups
-    total_assets: usize,
+    /// Also write a Markdown summary of the run next to the execution report: "json" (no summary) or "markdown"
+    #[arg(long, value_name = "FORMAT", default_value = "json")]
+    report_format: ReportFormat,
 
-    /// Number of groups that need manual review due to conflicts
-    needs_review_count: usize,
+    /// Stream backups to an S3-compatible bucket instead, e.g. s3://bucket/prefix/ (needs the `s3` feature; credentials from AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY)
+    #[arg(long, value_name = "URL")]
+    backup_target: Option<String>,
 
-    /// Analysis results for each duplicate group
-    groups: Vec<DuplicateAnalysis>,
-}
+    /// S3 endpoint for --backup-target, e.g. http://minio.local:9000 (default: AWS for the region)
+    #[arg(long, value_name = "URL", env = "AWS_ENDPOINT_URL")]
+    s3_endpoint: Option<String>,
 
-/// Result of verifying a single group
-#[derive(Debug, Serialize)]
-struct GroupVerification {
-    /// Duplicate group ID
-    duplicate_id: String,
+    /// S3 signing region for --backup-target
+    #[arg(long, value_name = "REGION", env = "AWS_REGION", default_value = "us-east-1")]
+    s3_region: String,
 
-    /// Winner verification status
-    winner_status: AssetStatus,
+    /// Permanently delete instead of moving to trash
+    #[arg(long, default_value = "false")]
+    force: bool,
 
-    /// Loser verification statuses
-    loser_statuses: Vec<AssetStatus>,
+    /// Losers sent per delete request (lower it if a proxy rejects large requests)
+    #[arg(long, value_name = "N", default_value = "250")]
+    delete_chunk_size: usize,
 
-    /// Consolidation checks (GPS transferred, etc.)
-    consolidation_checks: Vec<ConsolidationCheck>,
-}
+    /// Max requests per second (default: 10)
+    #[arg(long, default_value = "10")]
+    rate_limit: u32,
 
-/// Status of a single asset in verification
-#[derive(Debug, Serialize)]
-struct AssetStatus {
-    asset_id: String,
-    filename: String,
-    /// "present", "deleted", "error"
-    status: String,
-    /// Optional error message
-    error: Option<String>,
-}
+    /// Max concurrent operations (default: 5)
+    #[arg(long, default_value = "5")]
+    concurrent: usize,
 
-/// A consolidation check result
-#[derive(Debug, Serialize)]
-struct ConsolidationCheck {
-    /// What was checked (e.g., "gps_transferred", "datetime_transferred")
-    check_type: String,
-    /// Whether the check passed
-    passed: bool,
-    /// Details about the check
-    details: String,
-}
+    /// Fail any single API operation, such as a download, taking longer than this many seconds
+    #[arg(long, value_name = "SECS")]
+    operation_timeout: Option<u64>,
 
-/// Full verification report
-#[derive(Debug, Serialize)]
-struct VerificationReport {
-    /// When verification was performed
-    verified_at: DateTime<Utc>,
+    /// Give up on a group, deleting nothing, if its backups and transfers take longer than this many seconds
+    #[arg(long, value_name = "SECS")]
+    group_timeout: Option<u64>,
 
-    /// Server URL
-    server_url: String,
+    /// Skip groups that need manual review
+    #[arg(long, default_value = "false")]
+    skip_review: bool,
 
-    /// Groups verified
-    groups_verified: usize,
+    /// Only process groups with at least this confidence (0-100) unless a reviewer decided them
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+    min_confidence: Option<u8>,
 
-    /// Winners present count
-    winners_present: usize,
+    /// Write an XMP sidecar with consolidated metadata next to each backup
+    #[arg(long, default_value = "false")]
+    sidecars: bool,
 
-    /// Winners missing count (errors)
-    winners_missing: usize,
+    /// Embed each asset's Immich-side metadata into its backup as XMP
+    #[arg(long, default_value = "false")]
+    embed_metadata: bool,
 
-    /// Losers confirmed deleted
-    losers_deleted: usize,
+    /// Fully decode downloaded image backups before deleting (slower, catches truncated files)
+    #[arg(long, default_value = "false")]
+    decode_backups: bool,
 
-    /// Losers still present (errors)
-    losers_still_present: usize,
+    /// Album (name or ID) whose members are only deleted after the winner is added and verified (repeatable)
+    #[arg(long = "protect-album", value_name = "ALBUM")]
+    protect_albums: Vec<String>,
 
-    /// Consolidation checks passed
-    consolidation_passed: usize,
+    /// Tag (name, full value, or ID) whose members are only deleted after the winner is tagged and verified (repeatable)
+    #[arg(long = "protect-tag", value_name = "TAG")]
+    protect_tags: Vec<String>,
 
-    /// Consolidation checks failed
-    consolidation_failed: usize,
+    /// Make the winner the cover of any album whose cover is a deleted loser
+    #[arg(long, default_value = "false")]
+    promote_covers: bool,
 
-    /// Per-group verification results
-    groups: Vec<GroupVerification>,
+    /// Add the winner to every memory ("On this day") the analysis found a deleted loser in
+    #[arg(long, default_value = "false")]
+    preserve_memories: bool,
 
-    /// Any anomalies detected
-    anomalies: Vec<String>,
-}
+    /// After the run, tag the winner of every processed group with this tag (full value, created if needed)
+    #[arg(long, value_name = "TAG")]
+    tag_winners: Option<String>,
 
-/// Resolves credentials from CLI args, config file, or interactive prompt.
-///
-/// Priority: CLI args (which include env vars via clap) > config file > interactive prompt
-///
-/// Returns: (url, api_key, was_prompted)
-/// The `was_prompted` flag indicates if credentials were obtained via interactive prompt.
-fn resolve_credentials(
-    cli_url: Option<&str>,
-    cli_api_key: Option<&str>,
-    config: &config::Config,
-) -> Result<(String, String, bool)> {
-    // Try CLI/env first
-    if let (Some(url), Some(key)) = (cli_url, cli_api_key) {
-        return Ok((url.to_string(), key.to_string(), false));
-    }
+    /// Groups spanning libraries: "same-library" (only delete losers in the winner's library), "skip", or "proceed"
+    #[arg(long, value_name = "POLICY", default_value = "same-library")]
+    cross_library: CrossLibraryPolicy,
 
-    // Try config file
-    if let (Some(url), Some(key)) = (&config.server.url, &config.server.api_key) {
-        return Ok((url.clone(), key.clone(), false));
-    }
+    /// Losers shared through a public link: "ignore", "repoint" (add the winner to the link first), or "review" (leave the group untouched)
+    #[arg(long, value_name = "POLICY", default_value = "ignore")]
+    shared_links: SharedLinkPolicy,
 
-    // Prompt interactively
-    let (url, key) = config::prompt_credentials()?;
-    Ok((url, key, true))
-}
+    /// Albums owned by another user that the winner would be added to (protected albums, --album-only, --promote-covers): "review" (leave the group untouched), "skip" (leave the album out), or "attempt"
+    #[arg(long, value_name = "POLICY", default_value = "review")]
+    shared_albums: SharedAlbumPolicy,
 
-/// Offers to save credentials to config file if prompted or --save flag used.
-///
-/// Returns Ok(true) if saved, Ok(false) if not saved (user declined or already saved).
-fn maybe_save_credentials(
-    url: &str,
-    api_key: &str,
-    was_prompted: bool,
-    save_flag: bool,
-    config: &config::Config,
-) -> Result<bool> {
-    // Only offer to save if prompted or --save flag
-    if !was_prompted && !save_flag {
-        return Ok(false);
-    }
+    /// Winner and loser descriptions: "fill" (copy a loser's only if the winner has none), "append", "longest", or "review" (leave groups with different descriptions untouched)
+    #[arg(long, value_name = "POLICY", default_value = "fill")]
+    description_policy: DescriptionPolicy,
 
-    let config_path = config::config_path();
+    /// Process groups whose assets belong to different users (e.g. partner sharing); by default they are skipped
+    #[arg(long, default_value = "false")]
+    allow_cross_owner: bool,
 
-    // Check if already saved (config has these exact values)
-    if config.server.url.as_deref() == Some(url)
-        && config.server.api_key.as_deref() == Some(api_key)
-    {
-        return Ok(false);
-    }
+    /// If only some of a group's losers can be deleted, restore those trashed so the group is untouched
+    #[arg(long, default_value = "false", conflicts_with = "force")]
+    rollback_partial: bool,
 
-    // Ask user if they want to save
-    if config::prompt_save(&config_path) {
-        let mut new_config = config.clone();
-        new_config.server.url = Some(url.to_string());
-        new_config.server.api_key = Some(api_key.to_string());
-        config::save(&new_config)?;
-        println!("Credentials saved to {}", config_path.display());
-        return Ok(true);
-    }
+    /// Only replace losers with the winner in their albums (and consolidate metadata); nothing is deleted
+    #[arg(long, default_value = "false", conflicts_with = "force")]
+    album_only: bool,
 
-    Ok(false)
-}
+    /// Webhook to POST a run summary to when execution finishes (overrides [notification] in config)
+    #[arg(long, value_name = "URL")]
+    notify_url: Option<String>,
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Load .env file if present
-    let _ = dotenvy::dotenv();
+    /// Notification message template, e.g. "{deleted} deleted, {failed} failed"
+    #[arg(long, value_name = "TEMPLATE")]
+    notify_template: Option<String>,
 
-    // Load config file
-    let config = config::load();
+    /// Notification body: "json" (Slack, Discord) or "text" (ntfy)
+    #[arg(long, value_name = "FORMAT")]
+    notify_format: Option<NotificationFormat>,
 
-    let args = Args::parse();
+    /// Serve Prometheus metrics at http://<ADDR>/metrics while executing (e.g. 127.0.0.1:9185)
+    #[arg(long, value_name = "ADDR")]
+    metrics_addr: Option<SocketAddr>,
+
+    /// Push metrics to this Prometheus pushgateway when execution finishes
+    #[arg(long, value_name = "URL")]
+    pushgateway: Option<String>,
+
+    /// Deletion ledger to append to (default: ledger.jsonl next to the config file)
+    #[arg(long)]
+    ledger: Option<PathBuf>,
+
+    /// Skip confirmation prompt
+    #[arg(short, long, default_value = "false")]
+    yes: bool,
+
+    /// Skip the confirmation prompt only if the plan's risk grade is low
+    #[arg(long, default_value = "false")]
+    auto_safe: bool,
+
+    /// Refuse to run unless the plan's risk grade is low, even with --yes
+    #[arg(long, default_value = "false")]
+    strict: bool,
+
+    /// Write the exact operations to perform to FILE for review, and stop without changing anything
+    #[arg(long, value_name = "FILE", conflicts_with = "plan")]
+    plan_out: Option<PathBuf>,
+
+    /// Apply a plan written by --plan-out (possibly edited) instead of planning afresh
+    #[arg(long, value_name = "FILE")]
+    plan: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ReviewCommands {
+    /// List review events (groups clustered by capture time and location)
+    Events {
+        /// Path to analysis JSON from analyze command
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Maximum hours between groups in the same event
+        #[arg(long, default_value = "3")]
+        max_gap_hours: i64,
+
+        /// Maximum kilometres between groups in the same event
+        #[arg(long, default_value = "5.0")]
+        max_distance_km: f64,
+
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Apply one decision to every group in an event, updating the analysis JSON
+    Apply {
+        /// Path to analysis JSON from analyze command (updated in place)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Event number as shown by `review events`
+        #[arg(long)]
+        event: usize,
+
+        /// Decision to apply: "accept" (process with selected winner) or "skip"
+        #[arg(long)]
+        decision: Decision,
+
+        /// Name of the person making the decision
+        #[arg(long, env = "IMMICH_REVIEWER")]
+        reviewer: Option<String>,
+
+        /// Free-text note explaining the decision
+        #[arg(long)]
+        note: Option<String>,
+
+        /// Maximum hours between groups in the same event
+        #[arg(long, default_value = "3")]
+        max_gap_hours: i64,
+
+        /// Maximum kilometres between groups in the same event
+        #[arg(long, default_value = "5.0")]
+        max_distance_km: f64,
+    },
+
+    /// Put every asset of the groups awaiting review into an Immich album, to review them there
+    Album {
+        /// Path to analysis JSON from analyze command
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Album name (created if it doesn't exist)
+        #[arg(long, default_value = DEFAULT_REVIEW_ALBUM)]
+        name: String,
+
+        /// Also remove assets of groups in the analysis that have since been decided
+        #[arg(long)]
+        cleanup: bool,
+
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryCommands {
+    /// List recorded execution runs
+    Runs {
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Show the timeline of one execution run
+    Show {
+        /// Run ID, as listed by `history runs` and printed after execute
+        run_id: String,
+
+        /// Only events for this group
+        #[arg(long)]
+        group: Option<String>,
+
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ThumbnailCommands {
+    /// Download thumbnails for groups that need review into the cache
+    Warm {
+        /// Path to analysis JSON from analyze command
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Include groups that don't need review
+        #[arg(long)]
+        all: bool,
+
+        /// Image size: thumbnail, preview, or fullsize
+        #[arg(long, default_value = "thumbnail")]
+        size: ThumbnailSize,
+    },
+
+    /// Show the cache location and usage
+    Stats,
+
+    /// Remove every cached thumbnail
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileCommands {
+    /// List configured profiles
+    List,
+
+    /// Add a profile, or replace one with the same name
+    Add {
+        /// Profile name
+        name: String,
+
+        /// Immich server URL
+        #[arg(long)]
+        url: String,
+
+        /// API key (prompted for if not given)
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Keep the API key in the OS keyring instead of the config file
+        /// (requires a build with --features keyring)
+        #[arg(long)]
+        keyring: bool,
+    },
+
+    /// Remove a profile, and its API key from the keyring
+    Remove {
+        /// Profile name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum LetterboxCommands {
+    /// Analyze all assets for letterbox pairs and output results to JSON
+    Analyze {
+        /// Output file path for JSON results
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Execute letterbox removal: download 16:9 backups then delete
+    Execute {
+        /// Path to letterbox analysis JSON from analyze command
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Directory to download backup files to
+        #[arg(short, long)]
+        backup_dir: PathBuf,
+
+        /// Permanently delete instead of moving to trash
+        #[arg(long, default_value = "false")]
+        force: bool,
+
+        /// Max requests per second (default: 10)
+        #[arg(long, default_value = "10")]
+        rate_limit: u32,
+
+        /// Deletion ledger to append to (default: ledger.jsonl next to the config file)
+        #[arg(long)]
+        ledger: Option<PathBuf>,
+
+        /// Skip confirmation prompt
+        #[arg(short, long, default_value = "false")]
+        yes: bool,
+    },
+
+    /// Verify post-execution state: check keepers exist, deletes removed
+    Verify {
+        /// Path to the letterbox analysis JSON that was used for execution
+        analysis_json: PathBuf,
+
+        /// Output format (text or json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+}
+
+/// Resolves credentials from CLI args, config file, or interactive prompt.
+///
+/// Priority: CLI args (which include env vars via clap) > config file > interactive prompt
+///
+/// Returns: (url, api_key, was_prompted)
+/// The `was_prompted` flag indicates if credentials were obtained via interactive prompt.
+fn resolve_credentials(
+    cli_url: Option<&str>,
+    cli_api_key: Option<&str>,
+    config: &config::Config,
+) -> Result<(String, String, bool)> {
+    // Try CLI/env first
+    if let (Some(url), Some(key)) = (cli_url, cli_api_key) {
+        return Ok((url.to_string(), key.to_string(), false));
+    }
+
+    // Try config file
+    if let (Some(url), Some(key)) = (&config.server.url, &config.server.api_key) {
+        return Ok((url.clone(), key.clone(), false));
+    }
+
+    // Prompt interactively
+    let (url, key) = config::prompt_credentials()?;
+    Ok((url, key, true))
+}
+
+/// Offers to save credentials to config file if prompted or --save flag used.
+///
+/// Returns Ok(true) if saved, Ok(false) if not saved (user declined or already saved).
+fn maybe_save_credentials(
+    url: &str,
+    api_key: &str,
+    was_prompted: bool,
+    save_flag: bool,
+    config: &config::Config,
+) -> Result<bool> {
+    // Only offer to save if prompted or --save flag
+    if !was_prompted && !save_flag {
+        return Ok(false);
+    }
+
+    let config_path = config::config_path();
+
+    // Check if already saved (config has these exact values)
+    if config.server.url.as_deref() == Some(url)
+        && config.server.api_key.as_deref() == Some(api_key)
+    {
+        return Ok(false);
+    }
+
+    // Ask user if they want to save
+    if config::prompt_save(&config_path) {
+        if let Some(name) = &config.active_profile {
+            // Save into the profile, leaving everything the profile overrode as it was
+            let mut file_config = config::load();
+            let profile = file_config.profiles.entry(name.clone()).or_default();
+            profile.url = url.to_string();
+            if profile.keyring {
+                config::keyring_set(name, api_key)?;
+            } else {
+                profile.api_key = Some(api_key.to_string());
+            }
+            config::save(&file_config)?;
+            println!("Credentials saved to profile '{}'", name);
+            return Ok(true);
+        }
+        let mut new_config = config.clone();
+        new_config.server.url = Some(url.to_string());
+        new_config.server.api_key = Some(api_key.to_string());
+        config::save(&new_config)?;
+        println!("Credentials saved to {}", config_path.display());
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Load .env file if present
+    let _ = dotenvy::dotenv();
+
+    // Load config file
+    let config = config::load();
+
+    let mut args = Args::parse();
+    let out = Output::new(args.json);
+
+    // A profile picks the server, over --url/--api-key and their environment variables
+    let config = match args.profile.as_deref() {
+        Some(name) if !matches!(args.command, Commands::Profiles { .. }) => {
+            args.url = None;
+            args.api_key = None;
+            config.with_profile(name)?
+        }
+        _ => config,
+    };
 
     match args.command {
-        Commands::Analyze { output } => {
+        Commands::Analyze {
+            output,
+            tag,
+            after,
+            before,
+            asset_type,
+            tag_state,
+            winner_tag,
+            review_tag,
+            thresholds,
+        } => {
             let (url, api_key, prompted) = resolve_credentials(
                 args.url.as_deref(),
                 args.api_key.as_deref(),
                 &config,
             )?;
-            run_analyze(&url, &api_key, &output).await?;
+            let options = config.thresholds.resolve(&thresholds);
+            let filter = GroupFilter {
+                after,
+                before,
+                asset_type,
+            };
+            let state_tags = tag_state.then_some((winner_tag.as_str(), review_tag.as_str()));
+            let options = AnalyzeOptions {
+                output: &output,
+                tag: tag.as_deref(),
+                filter: &filter,
+                thresholds: &options,
+                state_tags,
+            };
+            run_analyze(&url, &api_key, options, out).await?;
             // Offer to save after successful command
             maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
         }
-        Commands::Execute {
-            input,
-            backup_dir,
-            force,
-            rate_limit,
-            concurrent,
-            skip_review,
-            yes,
+        Commands::Watch {
+            output,
+            interval,
+            tag,
+            thresholds,
         } => {
+            let (url, api_key, _) = resolve_credentials(
+                args.url.as_deref(),
+                args.api_key.as_deref(),
+                &config,
+            )?;
+            let options = config.thresholds.resolve(&thresholds);
+            let interval = Duration::from_secs(interval);
+            run_watch(&url, &api_key, &output, tag.as_deref(), interval, &options, out).await?;
+        }
+        Commands::Execute(execute) => {
+            let ExecuteArgs {
+                input,
+                backup_dir,
+                backup_sink,
+                report_format,
+                backup_target,
+                s3_endpoint,
+                s3_region,
+                force,
+                delete_chunk_size,
+                rate_limit,
+                concurrent,
+                operation_timeout,
+                group_timeout,
+                skip_review,
+                min_confidence,
+                sidecars,
+                embed_metadata,
+                decode_backups,
+                protect_albums,
+                protect_tags,
+                promote_covers,
+                preserve_memories,
+                tag_winners,
+                cross_library,
+                shared_links,
+                shared_albums,
+                description_policy,
+                allow_cross_owner,
+                rollback_partial,
+                album_only,
+                notify_url,
+                notify_template,
+                notify_format,
+                metrics_addr,
+                pushgateway,
+                ledger,
+                yes,
+                auto_safe,
+                strict,
+                plan_out,
+                plan,
+            } = *execute;
             let (url, api_key, prompted) = resolve_credentials(
                 args.url.as_deref(),
                 args.api_key.as_deref(),
                 &config,
             )?;
-            run_execute(
-                &url,
-                &api_key,
-                &input,
-                &backup_dir,
+            let protection = config.protection.merged(protect_albums, protect_tags);
+            let notification = config.notification_with(notify_url, notify_template, notify_format);
+            let backup_target = match backup_target {
+                Some(target) => parse_backup_target(&target, s3_endpoint.as_deref(), &s3_region)?,
+                None => BackupTarget::Directory,
+            };
+            let options = ExecuteOptions {
+                url: &url,
+                api_key: &api_key,
+                input: &input,
+                backup_dir: &backup_dir,
+                backup_sink,
+                report_format,
+                backup_target,
                 force,
+                delete_chunk_size,
                 rate_limit,
                 concurrent,
+                operation_timeout: operation_timeout.map(Duration::from_secs),
+                group_timeout: group_timeout.map(Duration::from_secs),
                 skip_review,
+                min_confidence,
+                sidecars,
+                embed_metadata,
+                decode_backups,
+                protection: &protection,
+                promote_covers,
+                preserve_memories,
+                tag_winners,
+                cross_library,
+                shared_links,
+                shared_albums,
+                description_policy,
+                allow_cross_owner,
+                rollback_partial,
+                mode: if album_only { ExecutionMode::AlbumOnly } else { ExecutionMode::Full },
+                notification,
+                metrics_addr,
+                pushgateway: pushgateway.as_deref(),
+                ledger: &Ledger::new(ledger.unwrap_or_else(config::ledger_path)),
                 yes,
-            )
-            .await?;
+                auto_safe,
+                strict,
+                plan_out: plan_out.as_deref(),
+                plan_path: plan.as_deref(),
+            };
+            run_execute(options, out).await?;
             maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
         }
-        Commands::Verify { analysis_json, format } => {
+        Commands::Run {
+            work_dir,
+            backup_dir,
+            fresh,
+            rate_limit,
+            ledger,
+            tag,
+            tag_winners,
+            thresholds,
+        } => {
             let (url, api_key, prompted) = resolve_credentials(
                 args.url.as_deref(),
                 args.api_key.as_deref(),
                 &config,
             )?;
-            run_verify(&url, &api_key, &analysis_json, &format).await?;
+            let backup_dir = backup_dir.unwrap_or_else(|| work_dir.join("backups"));
+            let execution = ExecutionConfig {
+                requests_per_sec: rate_limit,
+                backup_dir,
+                protected_albums: config.protection.albums.clone(),
+                protected_tags: config.protection.tags.clone(),
+                ledger_path: Some(ledger.unwrap_or_else(config::ledger_path)),
+                tag_winners,
+                rollback_partial: true,
+                history_dir: Some(config::history_dir()),
+                ..Default::default()
+            };
+            let plan = PlanOptions {
+                thresholds: config.thresholds.resolve(&thresholds),
+                tag,
+                ..Default::default()
+            };
+            run_pipeline(&url, &api_key, &work_dir, fresh, &plan, execution, out).await?;
             maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
         }
-        Commands::FindTestCandidates {
+        Commands::Verify {
+            analysis_json,
             format,
-            scenario,
-            output,
+            sample,
+            confidence_stop,
+            max_failure_rate,
+            seed,
         } => {
             let (url, api_key, prompted) = resolve_credentials(
                 args.url.as_deref(),
                 args.api_key.as_deref(),
                 &config,
             )?;
-            run_find_test_candidates(&url, &api_key, &format, scenario.as_deref(), output.as_ref())
-                .await?;
+            let sampling = sample.map(|fraction| SampleOptions {
+                fraction,
+                confidence_stop,
+                max_failure_rate,
+                seed: seed.unwrap_or_else(|| Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64),
+            });
+            run_verify(&url, &api_key, &analysis_json, &format, sampling.as_ref()).await?;
             maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
         }
-        Commands::GenerateFixtures { output_dir, scenario } => {
-            run_generate_fixtures(&output_dir, scenario.as_deref())?;
-        }
-        Commands::Restore { backup_dir, dry_run } => {
+        Commands::AuditOriginals { deep, decode, output } => {
             let (url, api_key, prompted) = resolve_credentials(
                 args.url.as_deref(),
                 args.api_key.as_deref(),
                 &config,
             )?;
-            run_restore(&url, &api_key, &backup_dir, dry_run).await?;
+            run_audit_originals(&url, &api_key, deep, decode, output.as_ref()).await?;
             maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
         }
-        Commands::Letterbox { command } => {
+        Commands::FindTestCandidates {
+            format,
+            scenario,
+            output,
+            thresholds,
+        } => {
             let (url, api_key, prompted) = resolve_credentials(
                 args.url.as_deref(),
                 args.api_key.as_deref(),
                 &config,
             )?;
-            match command {
-                LetterboxCommands::Analyze { output } => {
-                    run_letterbox_analyze(&url, &api_key, &output).await?;
-                }
-                LetterboxCommands::Execute {
+            let thresholds = config.thresholds.resolve(&thresholds);
+            run_find_test_candidates(
+                &url,
+                &api_key,
+                &format,
+                scenario.as_deref(),
+                output.as_ref(),
+                &thresholds,
+            )
+            .await?;
+            maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
+        }
+        Commands::GenerateFixtures { output_dir, scenario } => {
+            run_generate_fixtures(&output_dir, scenario.as_deref(), out)?;
+        }
+        Commands::SeedFixtures {
+            fixtures_dir,
+            scenario,
+            regenerate,
+            timeout,
+        } => {
+            let (url, api_key, prompted) = resolve_credentials(
+                args.url.as_deref(),
+                args.api_key.as_deref(),
+                &config,
+            )?;
+            run_seed_fixtures(
+                &url,
+                &api_key,
+                &fixtures_dir,
+                scenario.as_deref(),
+                regenerate,
+                Duration::from_secs(timeout),
+                out,
+            )
+            .await?;
+            maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
+        }
+        Commands::Restore {
+            backup_dir,
+            analysis,
+            dry_run,
+        } => {
+            let (url, api_key, prompted) = resolve_credentials(
+                args.url.as_deref(),
+                args.api_key.as_deref(),
+                &config,
+            )?;
+            run_restore(&url, &api_key, &backup_dir, analysis.as_ref(), dry_run).await?;
+            maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
+        }
+        Commands::PruneBackups {
+            backup_dir,
+            older_than,
+            max_size_gb,
+            dry_run,
+        } => {
+            if older_than.is_none() && max_size_gb.is_none() {
+                anyhow::bail!("Nothing to prune by: give --older-than, --max-size-gb, or both");
+            }
+            let (url, api_key, prompted) = resolve_credentials(
+                args.url.as_deref(),
+                args.api_key.as_deref(),
+                &config,
+            )?;
+            let policy = RetentionPolicy {
+                max_age: older_than.map(|days| chrono::Duration::days(i64::from(days))),
+                max_total_bytes: max_size_gb.map(|gb| (gb * 1_073_741_824.0) as u64),
+            };
+            run_prune_backups(&url, &api_key, &backup_dir, &policy, dry_run, out).await?;
+            maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
+        }
+        Commands::Reconcile { backup_dir, ledger } => {
+            let (url, api_key, prompted) = resolve_credentials(
+                args.url.as_deref(),
+                args.api_key.as_deref(),
+                &config,
+            )?;
+            let ledger = Ledger::new(ledger.unwrap_or_else(config::ledger_path));
+            maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
+            run_reconcile(&url, &api_key, &backup_dir, &ledger, out).await?;
+        }
+        Commands::Letterbox { command } => {
+            let (url, api_key, prompted) = resolve_credentials(
+                args.url.as_deref(),
+                args.api_key.as_deref(),
+                &config,
+            )?;
+            match command {
+                LetterboxCommands::Analyze { output } => {
+                    run_letterbox_analyze(&url, &api_key, &output).await?;
+                }
+                LetterboxCommands::Execute {
                     input,
                     backup_dir,
                     force,
                     rate_limit,
+                    ledger,
                     yes,
                 } => {
-                    run_letterbox_execute(&url, &api_key, &input, &backup_dir, force, rate_limit, yes).await?;
+                    let ledger = Ledger::new(ledger.unwrap_or_else(config::ledger_path));
+                    let options = LetterboxExecuteOptions {
+                        input: &input,
+                        backup_dir: &backup_dir,
+                        force,
+                        rate_limit,
+                        ledger: &ledger,
+                        yes,
+                    };
+                    run_letterbox_execute(&url, &api_key, options).await?;
                 }
                 LetterboxCommands::Verify { analysis_json, format } => {
                     run_letterbox_verify(&url, &api_key, &analysis_json, &format).await?;
@@ -445,110 +1325,1326 @@ async fn main() -> Result<()> {
             }
             maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
         }
+        Commands::Review { command } => match command {
+            ReviewCommands::Events {
+                input,
+                max_gap_hours,
+                max_distance_km,
+                format,
+            } => {
+                let options = cluster_options(max_gap_hours, max_distance_km);
+                run_review_events(&input, &options, &format)?;
+            }
+            ReviewCommands::Apply {
+                input,
+                event,
+                decision,
+                reviewer,
+                note,
+                max_gap_hours,
+                max_distance_km,
+            } => {
+                let options = cluster_options(max_gap_hours, max_distance_km);
+                let decision = ReviewDecision::new(decision, reviewer, note);
+                run_review_apply(&input, event, &decision, &options)?;
+            }
+            ReviewCommands::Album {
+                input,
+                name,
+                cleanup,
+                format,
+            } => {
+                let (url, api_key, prompted) = resolve_credentials(
+                    args.url.as_deref(),
+                    args.api_key.as_deref(),
+                    &config,
+                )?;
+                run_review_album(&url, &api_key, &input, &name, cleanup, &format).await?;
+                maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
+            }
+        },
+        Commands::Bench {
+            groups,
+            assets_per_group,
+            concurrent,
+            analyze_only,
+            format,
+        } => {
+            let options = BenchOptions {
+                groups,
+                assets_per_group,
+                execute: !analyze_only,
+                max_concurrent: concurrent,
+            };
+            run_bench_command(&options, &format).await?;
+        }
+        Commands::ExportReview { input, output, format } => {
+            run_export_review(&input, &output, &format)?;
+        }
+        Commands::ImportReview {
+            decisions,
+            input,
+            reviewer,
+        } => {
+            run_import_review(&decisions, &input, reviewer.as_deref())?;
+        }
+        Commands::Search {
+            query,
+            input,
+            limit,
+            format,
+        } => {
+            run_search(&input, &query, limit, &format)?;
+        }
+        Commands::History {
+            command: Some(command),
+            ..
+        } => {
+            let dir = config::history_dir();
+            let history = history::open(&dir)
+                .with_context(|| format!("Failed to open run history: {}", dir.display()))?;
+            match command {
+                HistoryCommands::Runs { format } => run_history_runs(history.as_ref(), &dir, &format)?,
+                HistoryCommands::Show { run_id, group, format } => {
+                    run_history_show(history.as_ref(), &run_id, group.as_deref(), &format)?
+                }
+            }
+        }
+        Commands::History {
+            command: None,
+            id,
+            filename,
+            checksum,
+            since,
+            ledger,
+            format,
+        } => {
+            let query = LedgerQuery {
+                id,
+                filename,
+                checksum,
+                since,
+            };
+            let ledger = Ledger::new(ledger.unwrap_or_else(config::ledger_path));
+            run_history(&ledger, &query, &format)?;
+        }
+        Commands::SupportBundle {
+            output,
+            backup_dir,
+            reports,
+            logs,
+            ledger,
+        } => {
+            let url = args.url.clone().or_else(|| config.server.url.clone());
+            let api_key = args.api_key.clone().or_else(|| config.server.api_key.clone());
+            let files = BundleFiles {
+                backup_dir,
+                reports,
+                logs,
+                ledger: Ledger::new(ledger.unwrap_or_else(config::ledger_path)),
+            };
+            run_support_bundle(url.as_deref(), api_key.as_deref(), &config, &files, output).await?;
+        }
+        Commands::Thumbnails {
+            cache_dir,
+            cache_size_mb,
+            command,
+        } => {
+            let (dir, max_bytes) = config.thumbnail_cache.resolve(cache_dir, cache_size_mb);
+            let cache = ThumbnailCache::open(&dir, max_bytes)
+                .with_context(|| format!("Failed to open thumbnail cache at {}", dir.display()))?;
+            match command {
+                ThumbnailCommands::Warm { input, all, size } => {
+                    let (url, api_key, _) = resolve_credentials(
+                        args.url.as_deref(),
+                        args.api_key.as_deref(),
+                        &config,
+                    )?;
+                    run_thumbnails_warm(&url, &api_key, &cache, &input, all, size).await?;
+                }
+                ThumbnailCommands::Stats => print_thumbnail_stats(&cache),
+                ThumbnailCommands::Clear => {
+                    cache.clear().context("Failed to clear thumbnail cache")?;
+                    println!("Cleared thumbnail cache at {}", cache.dir().display());
+                }
+            }
+        }
+        Commands::Reports { paths, format } => run_reports(&paths, &format)?,
+        Commands::Profiles { command } => run_profiles(command, config)?,
+        Commands::Doctor { format } => {
+            // Never prompt: missing credentials are themselves a finding
+            let url = args.url.or_else(|| config.server.url.clone());
+            let api_key = args.api_key.or_else(|| config.server.api_key.clone());
+            run_doctor(url.as_deref(), api_key.as_deref(), &format).await?;
+        }
+        Commands::Stats { format, thresholds } => {
+            let (url, api_key, prompted) = resolve_credentials(
+                args.url.as_deref(),
+                args.api_key.as_deref(),
+                &config,
+            )?;
+            let thresholds = config.thresholds.resolve(&thresholds);
+            run_stats(&url, &api_key, &thresholds, &format).await?;
+            maybe_save_credentials(&url, &api_key, prompted, args.save, &config)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Files to gather into a support bundle.
+struct BundleFiles {
+    backup_dir: Option<PathBuf>,
+    reports: Vec<PathBuf>,
+    logs: Vec<PathBuf>,
+    ledger: Ledger,
+}
+
+/// Ledger entries included in a support bundle (the most recent).
+const BUNDLE_LEDGER_ENTRIES: usize = 100;
+
+async fn run_support_bundle(
+    url: Option<&str>,
+    api_key: Option<&str>,
+    config: &config::Config,
+    files: &BundleFiles,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    // Redact every API key we know of, wherever it came from, and the
+    // notification webhook (its URL is usually the credential)
+    let secrets: Vec<String> = [
+        api_key.map(String::from),
+        config.server.api_key.clone(),
+        std::env::var("IMMICH_API_KEY").ok(),
+        config.notification.as_ref().map(|n| n.url.clone()),
+    ]
+    .into_iter()
+    .chain(config.profiles.values().flat_map(|p| {
+        [p.api_key.clone(), p.notification.as_ref().map(|n| n.url.clone())]
+    }))
+    .flatten()
+    .collect();
+    let name = format!("immich-dupes-support-{}", file_stamp());
+    let mut bundle = SupportBundle::new(&name, &secrets);
+    let mut notes: Vec<String> = Vec::new();
+
+    bundle.add_json("environment.json", &EnvironmentInfo::current())?;
+
+    // Config, with the API key and webhook URL removed rather than just masked
+    let mut redacted_config = config.clone();
+    if redacted_config.server.api_key.is_some() {
+        redacted_config.server.api_key = Some(REDACTED.to_string());
+    }
+    if let Some(notification) = &mut redacted_config.notification {
+        notification.url = REDACTED.to_string();
+    }
+    for profile in redacted_config.profiles.values_mut() {
+        if profile.api_key.is_some() {
+            profile.api_key = Some(REDACTED.to_string());
+        }
+        if let Some(notification) = &mut profile.notification {
+            notification.url = REDACTED.to_string();
+        }
+    }
+    bundle.add_bytes("config.toml", toml::to_string_pretty(&redacted_config)?.into_bytes())?;
+
+    // Doctor findings include the server version, API key scope, and job state
+    let mut findings = config::diagnose();
+    match (url, api_key) {
+        (Some(url), Some(api_key)) => match ImmichClient::new(url, api_key) {
+            Ok(client) => findings.extend(doctor::diagnose(&client).await),
+            Err(e) => findings.push(Finding::new("credentials", FindingLevel::Error, e.to_string())),
+        },
+        _ => findings.push(Finding::new(
+            "credentials",
+            FindingLevel::Info,
+            "No server URL or API key; server checks skipped",
+        )),
+    }
+    bundle.add_json("doctor.json", &findings)?;
+
+    // Most recent execution reports from the backup directory
+    if let Some(backup_dir) = &files.backup_dir {
+        for prefix in ["execution-report-", "letterbox-execution-"] {
+            match latest_file_with_prefix(backup_dir, prefix) {
+                Some(path) => add_bundle_file(&mut bundle, "reports", &path, &mut notes),
+                None => notes.push(format!("No {}* in {}", prefix, backup_dir.display())),
+            }
+        }
+    }
+    for path in &files.reports {
+        add_bundle_file(&mut bundle, "reports", path, &mut notes);
+    }
+    for path in &files.logs {
+        add_bundle_file(&mut bundle, "logs", path, &mut notes);
+    }
+
+    // Tail of the deletion ledger
+    match files.ledger.read_all() {
+        Ok(entries) => {
+            let tail = &entries[entries.len().saturating_sub(BUNDLE_LEDGER_ENTRIES)..];
+            let mut lines = Vec::new();
+            for entry in tail {
+                serde_json::to_writer(&mut lines, entry)?;
+                lines.push(b'\n');
+            }
+            bundle.add_bytes("ledger-tail.jsonl", lines)?;
+        }
+        Err(e) => notes.push(format!("Could not read ledger {}: {}", files.ledger.path().display(), e)),
+    }
+
+    if !notes.is_empty() {
+        bundle.add_bytes("notes.txt", notes.join("\n").into_bytes())?;
+    }
+
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", name)));
+    bundle
+        .write_tar_gz(&output)
+        .with_context(|| format!("Failed to write support bundle: {}", output.display()))?;
+
+    println!("Support bundle written to {}", output.display());
+    for file in bundle.file_names() {
+        println!("  {}", file);
+    }
+    for note in &notes {
+        println!("  note: {}", note);
+    }
+    println!();
+    println!("API keys have been redacted. Review the contents before attaching the bundle to a public issue.");
+    Ok(())
+}
+
+/// Add a file to the bundle under `dir/`, noting (not failing on) unreadable files.
+fn add_bundle_file(bundle: &mut SupportBundle, dir: &str, path: &std::path::Path, notes: &mut Vec<String>) {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if let Err(e) = bundle.add_file(&format!("{}/{}", dir, file_name), path) {
+        notes.push(format!("Could not include {}: {}", path.display(), e));
+    }
+}
+
+/// Most recent file in `dir` whose name starts with `prefix` (names embed a sortable timestamp).
+fn latest_file_with_prefix(dir: &std::path::Path, prefix: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(prefix))
+        })
+        .max()
+}
+
+async fn run_doctor(url: Option<&str>, api_key: Option<&str>, format: &str) -> Result<()> {
+    let mut findings = config::diagnose();
+
+    match (url, api_key) {
+        (Some(url), Some(api_key)) => match ImmichClient::new(url, api_key) {
+            Ok(client) => findings.extend(doctor::diagnose(&client).await),
+            Err(e) => findings.push(
+                Finding::new("credentials", FindingLevel::Error, e.to_string())
+                    .with_hint("Check the server URL and API key"),
+            ),
+        },
+        _ => findings.push(
+            Finding::new("credentials", FindingLevel::Error, "No server URL or API key")
+                .with_hint("Pass --url/--api-key, set IMMICH_URL/IMMICH_API_KEY, or save them to the config file"),
+        ),
+    }
+
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else {
+        println!("immich-dupes doctor");
+        println!("===================");
+        for finding in &findings {
+            let label = match finding.level {
+                FindingLevel::Ok => "ok",
+                FindingLevel::Info => "info",
+                FindingLevel::Warning => "WARN",
+                FindingLevel::Error => "ERROR",
+            };
+            println!("[{:>5}] {}: {}", label, finding.check, finding.message);
+            if let Some(hint) = &finding.hint {
+                println!("        -> {}", hint);
+            }
+        }
+    }
+
+    let errors = findings.iter().filter(|f| f.level == FindingLevel::Error).count();
+    if errors > 0 {
+        anyhow::bail!("doctor found {} error(s)", errors);
+    }
+    Ok(())
+}
+
+fn run_reports(paths: &[PathBuf], format: &str) -> Result<()> {
+    let rollup = ReportRollup::from_paths(paths).context("Failed to read execution reports")?;
+    if rollup.runs.is_empty() {
+        anyhow::bail!("No execution reports found");
+    }
+
+    match format.to_ascii_lowercase().as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&rollup)?),
+        "markdown" | "md" => print!("{}", rollup.render_markdown()),
+        _ => print!("{}", rollup.render_text()),
+    }
+    Ok(())
+}
+
+async fn run_stats(url: &str, api_key: &str, thresholds: &Thresholds, format: &str) -> Result<()> {
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    let stats = LibraryStats::collect(&client, thresholds)
+        .await
+        .context("Failed to collect library statistics")?;
+
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    let gib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    println!("Library statistics for {}", stats.server_url);
+    println!("==========================");
+    println!(
+        "Assets:              {} ({} images, {} videos)",
+        stats.assets.total, stats.assets.images, stats.assets.videos
+    );
+    println!("In trash:            {}", stats.trashed);
+    if let Some(server) = &stats.server {
+        println!(
+            "Server-wide:         {} images, {} videos, {:.1} GiB across {} users",
+            server.photos,
+            server.videos,
+            gib(server.usage),
+            server.usage_by_user.len()
+        );
+    }
+    println!();
+    println!("Duplicate groups:    {}", stats.duplicate_groups);
+    println!(
+        "Duplicate assets:    {} ({} removable)",
+        stats.duplicate_assets, stats.removable_assets
+    );
+    println!("Reclaimable space:   {:.2} GiB", gib(stats.reclaimable_bytes));
+    println!(
+        "Conflicting groups:  {} ({:.1}%)",
+        stats.groups_with_conflicts,
+        stats.conflict_rate * 100.0
+    );
+    println!("Flagged for review:  {}", stats.needs_review);
+    Ok(())
+}
+
+/// How long the event stream must be quiet before a watch re-analyzes.
+#[cfg(feature = "events")]
+const WATCH_SETTLE: Duration = Duration::from_secs(30);
+
+/// Analyze, then analyze again whenever duplicates may have changed.
+///
+/// With the `events` feature, waits for the server to report library changes
+/// and for duplicate detection to catch up; otherwise (or while the event
+/// stream is unavailable), waits `interval` between analyses.
+async fn run_watch(
+    url: &str,
+    api_key: &str,
+    output: &PathBuf,
+    tag: Option<&str>,
+    interval: Duration,
+    options: &Thresholds,
+    out: Output,
+) -> Result<()> {
+    #[cfg(feature = "events")]
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    #[cfg(feature = "events")]
+    let mut events = None;
+
+    loop {
+        // Subscribe before analyzing, so changes made during the analysis aren't missed
+        #[cfg(feature = "events")]
+        if events.is_none() {
+            match client.subscribe().await {
+                Ok(stream) => events = Some(stream),
+                Err(e) => outln!(out, "Warning: can't subscribe to server events: {}", e),
+            }
+        }
+
+        let analyze = AnalyzeOptions {
+            output,
+            tag,
+            filter: &GroupFilter::default(),
+            thresholds: options,
+            state_tags: None,
+        };
+        run_analyze(url, api_key, analyze, out).await?;
+
+        #[cfg(feature = "events")]
+        if let Some(stream) = &mut events {
+            outln!(out, "Waiting for library changes...");
+            out.event("progress", json!({ "stage": "waiting" }))?;
+            match immich_lib::events::wait_for_duplicate_detection(&client, stream, WATCH_SETTLE).await {
+                Ok(changes) if !changes.is_empty() => {
+                    outln!(out, "{} library change(s), duplicate detection idle", changes.len());
+                    continue;
+                }
+                Ok(_) => outln!(out, "Warning: server closed the event stream"),
+                Err(e) => outln!(out, "Warning: event stream failed: {}", e),
+            }
+            events = None;
+        }
+
+        outln!(out, "Next analysis in {}s", interval.as_secs());
+        out.event("progress", json!({ "stage": "waiting", "seconds": interval.as_secs() }))?;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// What `analyze` was asked to do, besides which server to ask.
+struct AnalyzeOptions<'a> {
+    /// Where the analysis is written
+    output: &'a PathBuf,
+    /// Only groups with an asset carrying this tag
+    tag: Option<&'a str>,
+    /// Only groups passing this filter
+    filter: &'a GroupFilter,
+    /// Scoring thresholds
+    thresholds: &'a Thresholds,
+    /// Tags for winners and for groups needing review, if tagging state
+    state_tags: Option<(&'a str, &'a str)>,
+}
+
+async fn run_analyze(url: &str, api_key: &str, options: AnalyzeOptions<'_>, out: Output) -> Result<()> {
+    let AnalyzeOptions {
+        output,
+        tag,
+        filter,
+        thresholds: options,
+        state_tags,
+    } = options;
+    outln!(out, "Connecting to Immich server at {}...", url);
+    out.event("progress", json!({ "stage": "connecting", "url": url }))?;
+
+    // Create client
+    let client =
+        ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+
+    // Fetch duplicates
+    outln!(out, "Fetching duplicate groups...");
+    out.event("progress", json!({ "stage": "fetching" }))?;
+    let mut duplicates = client
+        .get_duplicates()
+        .await
+        .context("Failed to fetch duplicates from Immich")?;
+
+    if let Some(tag) = tag {
+        let total = duplicates.len();
+        duplicates = tagging::scope_to_tag(&client, duplicates, tag)
+            .await
+            .with_context(|| format!("Failed to scope analysis to tag '{}'", tag))?;
+        outln!(out, "Scoped to tag {}: {} of {} groups", tag, duplicates.len(), total);
+    }
+
+    if !filter.is_empty() {
+        let total = duplicates.len();
+        duplicates = filter.apply(duplicates);
+        outln!(out, "Filtered by date and type: {} of {} groups", duplicates.len(), total);
+    }
+
+    // Fill in assets the server listed without their EXIF data
+    let hydrated = DuplicateGroup::hydrate_all(&mut duplicates, &client)
+        .await
+        .context("Failed to fetch full asset details")?;
+    if hydrated > 0 {
+        outln!(out, "Fetched full details for {} assets", hydrated);
+    }
+
+    // Analyze each group
+    outln!(out, "Analyzing {} duplicate groups...", duplicates.len());
+    out.event("progress", json!({ "stage": "analyzing", "groups": duplicates.len() }))?;
+    let mut groups = DuplicateAnalysis::from_groups_with_options(&duplicates, options);
+
+    // Memories are best effort: the key may lack permission to list them
+    if let Ok(memories) = client.get_memories().await {
+        for group in &mut groups {
+            group.flag_memories(&memories);
+        }
+    }
+
+    // Calculate statistics
+    let total_groups = groups.len();
+    let total_assets: usize = groups
+        .iter()
+        .map(|g| 1 + g.losers.len()) // winner + losers
+        .sum();
+    let needs_review_count = groups.iter().filter(|g| g.needs_review).count();
+    let mut reason_counts: std::collections::BTreeMap<&'static str, usize> = Default::default();
+    for reason in groups.iter().flat_map(|g| g.review_reasons.iter()) {
+        *reason_counts.entry(reason.as_str()).or_default() += 1;
+    }
+    let external_library_groups = groups.iter().filter(|g| g.has_external_assets()).count();
+    let cross_library_groups = groups.iter().filter(|g| g.is_cross_library()).count();
+    let memory_groups = groups.iter().filter(|g| g.loser_memories().is_some()).count();
+    let trashed_or_archived_groups = groups
+        .iter()
+        .filter(|g| g.warnings.iter().any(|w| matches!(w, SelectionWarning::TrashedOrArchived { .. })))
+        .count();
+    let policy_skipped_groups = groups.iter().filter(|g| !g.should_execute(false)).count();
+    let incomplete_groups = groups.iter().filter(|g| g.has_incomplete_data()).count();
+
+    // Tagging is best effort: a failure is reported but the analysis is still written
+    let mut tagging_error = None;
+    let state_tagged = match state_tags {
+        Some((winner_tag, review_tag)) => {
+            outln!(out, "Tagging winners and groups needing review...");
+            match tagging::tag_analysis(&client, &groups, winner_tag, review_tag).await {
+                Ok(tags) => Some(tags),
+                Err(e) => {
+                    tagging_error = Some(e.to_string());
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Create report
+    let report = AnalysisReport::new(url, groups);
+
+    // Write JSON to file (zstd-compressed for .zst paths)
+    save_analysis(output, &report)?;
+
+    // Print summary
+    outln!(out);
+    outln!(out, "Analysis complete!");
+    outln!(out);
+    outln!(out, "Duplicate groups: {}", total_groups);
+    outln!(out, "Total assets: {}", total_assets);
+    if needs_review_count > 0 {
+        outln!(out, "Groups needing review: {}", needs_review_count);
+        for (reason, count) in &reason_counts {
+            outln!(out, "  {}: {}", reason, count);
+        }
+    } else {
+        outln!(out, "Groups needing review: 0");
+    }
+    if external_library_groups > 0 {
+        outln!(
+            out,
+            "Groups with external library assets: {} (read-only losers will be skipped)",
+            external_library_groups
+        );
+    }
+    if cross_library_groups > 0 {
+        outln!(
+            out,
+            "Groups spanning multiple libraries: {} (see execute --cross-library)",
+            cross_library_groups
+        );
+    }
+    if memory_groups > 0 {
+        outln!(
+            out,
+            "Groups with losers in memories: {} (see execute --preserve-memories)",
+            memory_groups
+        );
+    }
+    if trashed_or_archived_groups > 0 {
+        outln!(
+            out,
+            "Groups with trashed or archived assets: {} ({} skipped; see --trashed and --archived)",
+            trashed_or_archived_groups,
+            policy_skipped_groups
+        );
+    }
+    if incomplete_groups > 0 {
+        outln!(out, "Groups analyzed without EXIF data for some assets: {}", incomplete_groups);
+    }
+    if !report.summary.group_sizes.is_empty() {
+        outln!(out, "Group sizes:");
+        for (size, count) in &report.summary.group_sizes {
+            outln!(out, "  {} assets: {} groups", size, count);
+        }
+    }
+    if !report.summary.conflicts.is_empty() {
+        outln!(out, "Conflicts by type:");
+        for (kind, count) in &report.summary.conflicts {
+            outln!(out, "  {}: {}", kind, count);
+        }
+    }
+    if let (Some(tags), Some((winner_tag, review_tag))) = (&state_tagged, state_tags) {
+        outln!(out, "Tagged {}: {} winners", winner_tag, tags.winners);
+        outln!(out, "Tagged {}: {} assets", review_tag, tags.review);
+    }
+    if let Some(error) = &tagging_error {
+        outln!(out, "Warning: failed to tag analysis state: {}", error);
+    }
+    outln!(out);
+    outln!(out, "Output written to: {}", output.display());
+    out.event(
+        "summary",
+        json!({
+            "command": "analyze",
+            "total_groups": total_groups,
+            "total_assets": total_assets,
+            "needs_review": needs_review_count,
+            "review_reasons": reason_counts,
+            "external_library_groups": external_library_groups,
+            "cross_library_groups": cross_library_groups,
+            "memory_groups": memory_groups,
+            "incomplete_groups": incomplete_groups,
+            "state_tags": state_tagged,
+            "tagging_error": tagging_error,
+            "output": output,
+        }),
+    )?;
+
+    Ok(())
+}
+
+/// Build clustering options from CLI arguments.
+fn cluster_options(max_gap_hours: i64, max_distance_km: f64) -> ClusterOptions {
+    ClusterOptions {
+        max_gap: chrono::Duration::hours(max_gap_hours),
+        max_distance_km,
+    }
+}
+
+/// Parse a fraction such as `5%` or `0.05` from the command line.
+fn parse_fraction_arg(value: &str) -> std::result::Result<f64, String> {
+    sampling::parse_fraction(value).ok_or_else(|| format!("'{}' is not a percentage between 0% and 100%", value))
+}
+
+/// Parse a date argument: RFC 3339, or `YYYY-MM-DD` (midnight UTC).
+fn parse_date_arg(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| format!("'{}' is not a date (YYYY-MM-DD) or RFC 3339 timestamp", value))
+}
+
+/// Load an analysis report from a JSON (or `.json.zst`) file or an analysis store (`.db`).
+fn load_analysis(input: &PathBuf) -> Result<AnalysisReport> {
+    AnalysisReport::load(input).with_context(|| format!("Failed to read analysis: {}", input.display()))
+}
+
+/// Write an analysis report to a JSON (or `.json.zst`) file or an analysis store (`.db`).
+fn save_analysis(path: &PathBuf, report: &AnalysisReport) -> Result<()> {
+    report.save(path).with_context(|| format!("Failed to write analysis: {}", path.display()))
+}
+
+/// Record an execution report as a new run in the analysis store it came from.
+#[cfg(feature = "sqlite")]
+fn record_execution_in_store(path: &Path, report: &ExecutionReport) -> Result<i64> {
+    Ok(AnalysisStore::open(path)?.record_execution(report)?)
+}
+
+/// Parse `--backup-target` (`s3://bucket/prefix`) into a target, with
+/// credentials from the environment.
+#[cfg(feature = "s3")]
+fn parse_backup_target(target: &str, endpoint: Option<&str>, region: &str) -> Result<BackupTarget> {
+    let Some(location) = target.strip_prefix("s3://") else {
+        anyhow::bail!("Unsupported backup target '{}' (expected s3://bucket/prefix)", target);
+    };
+    let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+    if bucket.is_empty() {
+        anyhow::bail!("Backup target '{}' has no bucket", target);
+    }
+    // Keys are `<prefix><asset>_<file>`, so a prefix names a folder
+    let prefix = if prefix.is_empty() || prefix.ends_with('/') { prefix.to_string() } else { format!("{}/", prefix) };
+    let endpoint = match endpoint {
+        Some(endpoint) => endpoint.to_string(),
+        None => format!("https://s3.{}.amazonaws.com", region),
+    };
+    let credential = |name: &str| std::env::var(name).with_context(|| format!("{} must be set for --backup-target", name));
+
+    Ok(BackupTarget::S3(Box::new(S3Config {
+        endpoint: url::Url::parse(&endpoint).with_context(|| format!("Invalid S3 endpoint '{}'", endpoint))?,
+        bucket: bucket.to_string(),
+        prefix,
+        region: region.to_string(),
+        access_key_id: credential("AWS_ACCESS_KEY_ID")?,
+        secret_access_key: credential("AWS_SECRET_ACCESS_KEY")?,
+    })))
+}
+
+#[cfg(not(feature = "s3"))]
+fn parse_backup_target(_target: &str, _endpoint: Option<&str>, _region: &str) -> Result<BackupTarget> {
+    anyhow::bail!("this build has no S3 support (rebuild with `--features s3`)")
+}
+
+#[cfg(not(feature = "sqlite"))]
+const NO_SQLITE: &str = "this build has no SQLite support (rebuild with `--features sqlite`)";
+
+#[cfg(not(feature = "sqlite"))]
+fn record_execution_in_store(_path: &Path, _report: &ExecutionReport) -> Result<i64> {
+    anyhow::bail!(NO_SQLITE)
+}
+
+/// Prefetch thumbnails for every asset in the selected groups.
+async fn run_thumbnails_warm(
+    url: &str,
+    api_key: &str,
+    cache: &ThumbnailCache,
+    input: &PathBuf,
+    all: bool,
+    size: ThumbnailSize,
+) -> Result<()> {
+    let report = load_analysis(input)?;
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+
+    let assets: Vec<_> = report
+        .groups
+        .iter()
+        .filter(|g| all || g.needs_review)
+        .flat_map(|g| std::iter::once(&g.winner).chain(&g.losers))
+        .collect();
+
+    let pb = ProgressBar::new(assets.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
+            .expect("valid template")
+            .progress_chars("#>-"),
+    );
+
+    let mut failed = 0;
+    for asset in assets {
+        let checksum = asset.checksum.as_deref().unwrap_or_default();
+        if let Err(e) = cache.get_or_fetch(&client, &asset.asset_id, checksum, size).await {
+            failed += 1;
+            pb.println(format!("Failed to fetch {} for {}: {}", size, asset.asset_id, e));
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    if failed > 0 {
+        println!("{} thumbnails could not be fetched", failed);
+    }
+    print_thumbnail_stats(cache);
+    Ok(())
+}
+
+/// List, add, or remove server profiles in the config file.
+fn run_profiles(command: ProfileCommands, mut config: config::Config) -> Result<()> {
+    match command {
+        ProfileCommands::List => {
+            if config.profiles.is_empty() {
+                println!("No profiles configured (add one with `immich-dupes profiles add`)");
+                return Ok(());
+            }
+            let width = config.profiles.keys().map(String::len).max().unwrap_or(0);
+            for (name, profile) in &config.profiles {
+                let key = match (profile.keyring, &profile.api_key) {
+                    (true, _) => "API key in keyring",
+                    (false, Some(_)) => "API key in config",
+                    (false, None) => "no API key",
+                };
+                println!("{:<width$}  {}  ({})", name, profile.url, key, width = width);
+            }
+        }
+        ProfileCommands::Add {
+            name,
+            url,
+            api_key,
+            keyring,
+        } => {
+            url::Url::parse(&url).with_context(|| format!("Invalid server URL: {}", url))?;
+            let api_key = match api_key {
+                Some(key) => key,
+                None => dialoguer::Password::new()
+                    .with_prompt(format!("API key for {}", url))
+                    .interact()
+                    .context("Failed to read API key input")?,
+            };
+            let mut profile = config.profiles.remove(&name).unwrap_or_default();
+            profile.url = url;
+            profile.keyring = keyring;
+            if keyring {
+                config::keyring_set(&name, &api_key)?;
+                profile.api_key = None;
+            } else {
+                profile.api_key = Some(api_key);
+            }
+            config.profiles.insert(name.clone(), profile);
+            config::save(&config)?;
+            println!("Saved profile '{}' to {}", name, config::config_path().display());
+        }
+        ProfileCommands::Remove { name } => {
+            let Some(profile) = config.profiles.remove(&name) else {
+                anyhow::bail!("No profile named '{}'", name);
+            };
+            if profile.keyring {
+                config::keyring_delete(&name)?;
+            }
+            config::save(&config)?;
+            println!("Removed profile '{}'", name);
+        }
+    }
+    Ok(())
+}
+
+fn print_thumbnail_stats(cache: &ThumbnailCache) {
+    let stats = cache.stats();
+    println!("Thumbnail cache: {}", cache.dir().display());
+    println!(
+        "{} images, {:.1} of {:.1} MiB",
+        stats.entries,
+        stats.bytes as f64 / (1024.0 * 1024.0),
+        stats.max_bytes as f64 / (1024.0 * 1024.0)
+    );
+}
+
+async fn run_review_album(
+    url: &str,
+    api_key: &str,
+    input: &PathBuf,
+    name: &str,
+    cleanup: bool,
+    format: &str,
+) -> Result<()> {
+    let report = load_analysis(input)?;
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    let sync = review_album::sync_review_album(&client, name, &report.groups, cleanup)
+        .await
+        .with_context(|| format!("Failed to update review album '{}'", name))?;
+
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&sync)?);
+        return Ok(());
+    }
+
+    let Some(album_id) = &sync.album_id else {
+        println!("No groups are waiting for review; album '{}' was not created", name);
+        return Ok(());
+    };
+    println!(
+        "{} album '{}': {} groups awaiting review, {} assets added, {} removed",
+        if sync.created { "Created" } else { "Updated" },
+        name,
+        sync.pending_groups,
+        sync.added,
+        sync.removed
+    );
+    println!("  {}", album_web_url(url, album_id));
+    if !cleanup && sync.pending_groups < report.needs_review_count {
+        println!("Resolved groups stay in the album; run again with --cleanup to remove them");
+    }
+    Ok(())
+}
+
+fn run_review_events(input: &PathBuf, options: &ClusterOptions, format: &str) -> Result<()> {
+    let report = load_analysis(input)?;
+    let events = cluster_events(&report.groups, options);
+
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+        return Ok(());
+    }
+
+    println!("Review Events");
+    println!("=============");
+    println!(
+        "{} groups need review, clustered into {} events",
+        report.needs_review_count,
+        events.len()
+    );
+    println!();
+
+    for event in &events {
+        let when = match (event.start, event.end) {
+            (Some(start), Some(end)) if start.date_naive() == end.date_naive() => format!(
+                "{} {}-{}",
+                start.format("%Y-%m-%d"),
+                start.format("%H:%M"),
+                end.format("%H:%M")
+            ),
+            (Some(start), Some(end)) => format!(
+                "{} to {}",
+                start.format("%Y-%m-%d %H:%M"),
+                end.format("%Y-%m-%d %H:%M")
+            ),
+            _ => "unknown time".to_string(),
+        };
+        let location = event
+            .location
+            .map(|(lat, lon)| format!(" @ {:.4},{:.4}", lat, lon))
+            .unwrap_or_default();
+        let decided = report
+            .groups
+            .iter()
+            .filter(|g| event.duplicate_ids.contains(&g.duplicate_id) && g.decision.is_some())
+            .count();
+
+        println!(
+            "Event {:>4}: {} groups, {}{} ({} decided)",
+            event.id,
+            event.duplicate_ids.len(),
+            when,
+            location,
+            decided
+        );
+    }
+
+    Ok(())
+}
+
+fn run_search(input: &PathBuf, query: &str, limit: usize, format: &str) -> Result<()> {
+    let report = load_analysis(input)?;
+    let index = SearchIndex::build(&report.groups);
+    let mut hits = index.search(query);
+    let total = hits.len();
+    hits.truncate(limit);
+
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+        return Ok(());
+    }
+
+    println!("{} of {} groups match \"{}\"", total, report.groups.len(), query);
+    for hit in &hits {
+        let fields: Vec<&str> = hit.fields.iter().map(|f| f.as_str()).collect();
+        println!("  {}  {}  [{}]", hit.duplicate_id, hit.summary, fields.join(", "));
+    }
+    if total > hits.len() {
+        println!("  ... {} more (use --limit to show more)", total - hits.len());
+    }
+
+    Ok(())
+}
+
+fn run_history(ledger: &Ledger, query: &LedgerQuery, format: &str) -> Result<()> {
+    let entries = ledger
+        .query(query)
+        .with_context(|| format!("Failed to read ledger: {}", ledger.path().display()))?;
+
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    println!("Deletion ledger: {}", ledger.path().display());
+    println!("{} entries", entries.len());
+    println!();
+    for entry in &entries {
+        println!(
+            "{}  {:<13}  {}  {}",
+            entry.recorded_at.format("%Y-%m-%d %H:%M:%S"),
+            entry.action.as_str(),
+            entry.asset_id,
+            entry.filename
+        );
+        println!(
+            "    group {}, kept {}, checksum {}",
+            entry.group_id,
+            entry.winner_id,
+            entry.checksum.as_deref().unwrap_or("unknown")
+        );
+        if let Some(path) = &entry.backup_path {
+            println!("    backup {}", path.display());
+        }
     }
 
     Ok(())
 }
 
-async fn run_analyze(url: &str, api_key: &str, output: &PathBuf) -> Result<()> {
-    println!("Connecting to Immich server at {}...", url);
+fn run_history_runs(history: &dyn HistoryStore, dir: &Path, format: &str) -> Result<()> {
+    let runs = history
+        .runs()
+        .with_context(|| format!("Failed to read run history: {}", dir.display()))?;
 
-    // Create client
-    let client =
-        ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&runs)?);
+        return Ok(());
+    }
 
-    // Fetch duplicates
-    println!("Fetching duplicate groups...");
-    let duplicates = client
-        .get_duplicates()
-        .await
-        .context("Failed to fetch duplicates from Immich")?;
+    println!("Run history: {}", dir.display());
+    println!("{} runs", runs.len());
+    println!();
+    for run in &runs {
+        println!(
+            "{:<20}  {}  {:>6} groups{}",
+            run.run_id,
+            run.started_at
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "-".repeat(19)),
+            run.groups,
+            if run.finished { "" } else { "  (interrupted)" }
+        );
+    }
+    Ok(())
+}
 
-    // Analyze each group
-    println!("Analyzing {} duplicate groups...", duplicates.len());
-    let groups: Vec<DuplicateAnalysis> = duplicates
-        .iter()
-        .map(DuplicateAnalysis::from_group)
+fn run_history_show(history: &dyn HistoryStore, run_id: &str, group: Option<&str>, format: &str) -> Result<()> {
+    let events: Vec<_> = history
+        .timeline(run_id)
+        .with_context(|| format!("Failed to read run {}", run_id))?
+        .into_iter()
+        .filter(|e| group.is_none() || e.event.group_id() == group)
         .collect();
 
-    // Calculate statistics
-    let total_groups = groups.len();
-    let total_assets: usize = groups
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+        return Ok(());
+    }
+
+    println!("Run {}", run_id);
+    println!();
+    for event in &events {
+        println!("{}  {}", event.at.format("%Y-%m-%d %H:%M:%S%.3f"), event.event.describe());
+    }
+    Ok(())
+}
+
+fn run_review_apply(
+    input: &PathBuf,
+    event_id: usize,
+    decision: &ReviewDecision,
+    options: &ClusterOptions,
+) -> Result<()> {
+    let mut report = load_analysis(input)?;
+    let events = cluster_events(&report.groups, options);
+
+    let event = events
         .iter()
-        .map(|g| 1 + g.losers.len()) // winner + losers
-        .sum();
-    let needs_review_count = groups.iter().filter(|g| g.needs_review).count();
+        .find(|e| e.id == event_id)
+        .with_context(|| format!("Event {} not found ({} events)", event_id, events.len()))?;
 
-    // Create report
-    let report = AnalysisReport {
-        generated_at: Utc::now(),
-        server_url: url.to_string(),
-        total_groups,
-        total_assets,
-        needs_review_count,
-        groups,
-    };
+    let updated = apply_event_decision(&mut report.groups, event, decision);
+    save_analysis(input, &report)?;
 
-    // Write JSON to file
-    let file = File::create(output)
-        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
-    let writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(writer, &report)
-        .context("Failed to write JSON output")?;
+    println!(
+        "Applied '{:?}' to {} groups in event {}{}",
+        decision.decision,
+        updated,
+        event_id,
+        decision
+            .reviewer
+            .as_deref()
+            .map(|r| format!(" (reviewer: {})", r))
+            .unwrap_or_default()
+    );
 
-    // Print summary
-    println!();
-    println!("Analysis complete!");
-    println!();
-    println!("Duplicate groups: {}", total_groups);
-    println!("Total assets: {}", total_assets);
-    if needs_review_count > 0 {
-        println!(
-            "Groups needing review: {} (have metadata conflicts)",
-            needs_review_count
-        );
+    Ok(())
+}
+
+async fn run_bench_command(options: &BenchOptions, format: &str) -> Result<()> {
+    let scratch_dir = std::env::temp_dir().join(format!("immich-bench-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&scratch_dir).context("Failed to create scratch directory")?;
+
+    eprintln!(
+        "Benchmarking {} groups x {} assets against in-process mock server...",
+        options.groups, options.assets_per_group
+    );
+    let result = run_bench(options, &scratch_dir).await;
+    let _ = std::fs::remove_dir_all(&scratch_dir);
+    let report = result.context("Benchmark failed")?;
+
+    if format.eq_ignore_ascii_case("json") {
+        println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
-        println!("Groups needing review: 0");
+        println!();
+        print!("{}", report);
     }
-    println!();
-    println!("Output written to: {}", output.display());
 
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-async fn run_execute(
-    url: &str,
-    api_key: &str,
-    input: &PathBuf,
-    backup_dir: &PathBuf,
+fn run_export_review(input: &PathBuf, output: &PathBuf, format: &str) -> Result<()> {
+    if !format.eq_ignore_ascii_case("csv") {
+        anyhow::bail!("Unsupported review format '{}' (expected csv)", format);
+    }
+
+    let report = load_analysis(input)?;
+    let file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    export_review_csv(&report.groups, &report.server_url, BufWriter::new(file))
+        .context("Failed to write review CSV")?;
+
+    println!(
+        "Exported {} groups ({} need review) to {}",
+        report.groups.len(),
+        report.needs_review_count,
+        output.display()
+    );
+    println!("Fill in the 'decision' column with accept, reject, or override");
+    println!("(override also needs 'override_winner_id'), optionally with 'reviewer'");
+    println!("and 'note', then run import-review.");
+
+    Ok(())
+}
+
+fn run_import_review(decisions: &PathBuf, input: &PathBuf, reviewer: Option<&str>) -> Result<()> {
+    let mut report = load_analysis(input)?;
+    let file = File::open(decisions)
+        .with_context(|| format!("Failed to open decisions file: {}", decisions.display()))?;
+
+    let summary = import_review_csv(&mut report.groups, BufReader::new(file), reviewer)
+        .context("Failed to import review decisions")?;
+    save_analysis(input, &report)?;
+
+    println!("Imported review decisions into {}", input.display());
+    println!("  Accepted:   {}", summary.accepted);
+    println!("  Rejected:   {}", summary.rejected);
+    println!("  Overridden: {}", summary.overridden);
+    println!("  Unchanged:  {}", summary.unchanged);
+
+    Ok(())
+}
+
+/// What `execute` was asked to do: its flags (see [`ExecuteArgs`]),
+/// resolved against the config.
+struct ExecuteOptions<'a> {
+    url: &'a str,
+    api_key: &'a str,
+    input: &'a PathBuf,
+    backup_dir: &'a PathBuf,
+    backup_sink: BackupSink,
+    report_format: ReportFormat,
+    backup_target: BackupTarget,
     force: bool,
+    delete_chunk_size: usize,
     rate_limit: u32,
     concurrent: usize,
+    operation_timeout: Option<Duration>,
+    group_timeout: Option<Duration>,
     skip_review: bool,
+    min_confidence: Option<u8>,
+    sidecars: bool,
+    embed_metadata: bool,
+    decode_backups: bool,
+    protection: &'a config::ProtectionConfig,
+    promote_covers: bool,
+    preserve_memories: bool,
+    tag_winners: Option<String>,
+    cross_library: CrossLibraryPolicy,
+    shared_links: SharedLinkPolicy,
+    shared_albums: SharedAlbumPolicy,
+    description_policy: DescriptionPolicy,
+    allow_cross_owner: bool,
+    rollback_partial: bool,
+    mode: ExecutionMode,
+    notification: Option<NotificationConfig>,
+    metrics_addr: Option<SocketAddr>,
+    pushgateway: Option<&'a str>,
+    ledger: &'a Ledger,
     yes: bool,
-) -> Result<()> {
+    auto_safe: bool,
+    strict: bool,
+    plan_out: Option<&'a Path>,
+    plan_path: Option<&'a Path>,
+}
+
+async fn run_execute(options: ExecuteOptions<'_>, out: Output) -> Result<()> {
+    let ExecuteOptions {
+        url,
+        api_key,
+        input,
+        backup_dir,
+        backup_sink,
+        report_format,
+        backup_target,
+        force,
+        delete_chunk_size,
+        rate_limit,
+        concurrent,
+        operation_timeout,
+        group_timeout,
+        skip_review,
+        min_confidence,
+        sidecars,
+        embed_metadata,
+        decode_backups,
+        protection,
+        promote_covers,
+        preserve_memories,
+        tag_winners,
+        cross_library,
+        shared_links,
+        shared_albums,
+        description_policy,
+        allow_cross_owner,
+        rollback_partial,
+        mode,
+        notification,
+        metrics_addr,
+        pushgateway,
+        ledger,
+        yes,
+        auto_safe,
+        strict,
+        plan_out,
+        plan_path,
+    } = options;
     // Read and parse analysis JSON
-    let file = File::open(input)
-        .with_context(|| format!("Failed to open input file: {}", input.display()))?;
-    let reader = BufReader::new(file);
-    let report: AnalysisReport = serde_json::from_reader(reader)
-        .context("Failed to parse analysis JSON")?;
+    let report = load_analysis(input)?;
+
+    // An approved plan fixes the operations; the analysis only informs the summary
+    let plan: Option<ExecutionPlan> = match plan_path {
+        Some(path) => {
+            let plan: ExecutionPlan = report_io::read_json(path)
+                .with_context(|| format!("Failed to read execution plan: {}", path.display()))?;
+            if plan.force_delete != force {
+                anyhow::bail!(
+                    "Plan {} was made {} --force; run execute {} --force to apply it",
+                    path.display(),
+                    if plan.force_delete { "with" } else { "without" },
+                    if plan.force_delete { "with" } else { "without" }
+                );
+            }
+            Some(plan)
+        }
+        None => None,
+    };
 
-    // Filter groups based on skip_review flag
-    let groups: Vec<DuplicateAnalysis> = if skip_review {
-        report.groups.into_iter().filter(|g| !g.needs_review).collect()
-    } else {
-        report.groups
+    // Filter groups based on reviewer decisions and the skip_review flag
+    let groups: Vec<DuplicateAnalysis> = report
+        .groups
+        .into_iter()
+        .filter(|g| match &plan {
+            Some(plan) => plan.groups.iter().any(|p| p.duplicate_id == g.duplicate_id.as_str()),
+            None => g.should_execute(skip_review),
+        })
+        .collect();
+
+    // Hold back low-confidence groups that no reviewer has decided
+    let (groups, low_confidence): (Vec<DuplicateAnalysis>, Vec<DuplicateAnalysis>) = match min_confidence {
+        Some(min) if plan.is_none() => groups.into_iter().partition(|g| g.meets_confidence(min)),
+        _ => (groups, Vec::new()),
     };
+    if let Some(min) = min_confidence
+        && !low_confidence.is_empty()
+    {
+        outln!(
+            out,
+            "Skipping {} groups below confidence {} (review them to process)",
+            low_confidence.len(),
+            min
+        );
+    }
 
     if groups.is_empty() {
-        println!("No groups to process.");
+        outln!(out, "No groups to process.");
+        out.event(
+            "summary",
+            json!({
+                "command": "execute",
+                "total_groups": 0,
+                "skipped_low_confidence": low_confidence.len(),
+            }),
+        )?;
         return Ok(());
     }
 
     // Calculate assets to process
-    let total_assets: usize = groups.iter().map(|g| g.losers.len()).sum();
+    let total_assets: usize = match &plan {
+        Some(plan) => plan.assets_to_delete(),
+        None => groups.iter().map(|g| g.losers.len()).sum(),
+    };
     let estimated_size: u64 = groups
         .iter()
         .flat_map(|g| g.losers.iter())
@@ -559,316 +2655,737 @@ async fn run_execute(
     std::fs::create_dir_all(backup_dir)
         .with_context(|| format!("Failed to create backup directory: {}", backup_dir.display()))?;
 
+    let client = ImmichClient::new(url, api_key)
+        .context("Failed to create Immich client")?;
+
+    // Assess risk; shared links are best effort (the key may lack permission)
+    let shared_ids = match risk::shared_asset_ids(&client).await {
+        Ok(ids) => ids,
+        Err(e) => {
+            outln!(out, "Warning: could not check shared links: {}", e);
+            Default::default()
+        }
+    };
+    let risk_report = RiskReport::assess(&groups, &shared_ids, &RiskOptions::default());
+
     // Print execution summary
-    println!();
-    println!("Execution Plan");
-    println!("==============");
-    println!("Groups to process: {}", groups.len());
-    println!("Assets to download: {}", total_assets);
+    outln!(out);
+    outln!(out, "Execution Plan");
+    outln!(out, "==============");
+    if let Some(path) = plan_path {
+        outln!(out, "Applying plan: {}", path.display());
+    }
+    outln!(out, "Groups to process: {}", groups.len());
+    if mode == ExecutionMode::AlbumOnly {
+        outln!(out, "Mode: album-only (losers are replaced by the winner in their albums; nothing is deleted)");
+    } else {
+        outln!(out, "Assets to download: {}", total_assets);
+    }
     if estimated_size > 0 {
         let size_mb = estimated_size as f64 / 1_048_576.0;
-        println!("Estimated disk space: {:.1} MB", size_mb);
+        outln!(out, "Estimated disk space: {:.1} MB", size_mb);
+    }
+    let remote_backups = !matches!(backup_target, BackupTarget::Directory);
+    if remote_backups {
+        if backup_sink != BackupSink::Directory {
+            anyhow::bail!("--backup-sink {} needs local backups; it can't be combined with --backup-target", backup_sink);
+        }
+        if embed_metadata {
+            anyhow::bail!("--embed-metadata needs local backups; it can't be combined with --backup-target");
+        }
+        outln!(out, "Backup target: {}", backup_target.store(backup_dir).location("").display());
+    } else {
+        outln!(out, "Backup directory: {}", backup_dir.display());
+    }
+    if backup_sink != BackupSink::Directory {
+        outln!(out, "Backups: one {} archive for the run", backup_sink);
+    }
+    outln!(out, "Force delete: {}", if force { "yes (permanent)" } else { "no (trash)" });
+    if sidecars {
+        outln!(out, "XMP sidecars: yes");
+    }
+    if embed_metadata {
+        outln!(out, "Embed metadata in backups: yes");
+    }
+    if !protection.albums.is_empty() {
+        outln!(out, "Protected albums: {}", protection.albums.join(", "));
+    }
+    if !protection.tags.is_empty() {
+        outln!(out, "Protected tags: {}", protection.tags.join(", "));
+    }
+    if promote_covers {
+        outln!(out, "Promote winners to album covers: yes");
+    }
+    if preserve_memories {
+        outln!(out, "Add winners to memories: yes");
+    }
+    if let Some(tag) = &tag_winners {
+        outln!(out, "Tag winners: {}", tag);
+    }
+    if rollback_partial {
+        outln!(out, "Roll back partially deleted groups: yes");
+    }
+    let cross_library_groups = groups.iter().filter(|g| g.is_cross_library()).count();
+    if cross_library_groups > 0 {
+        outln!(
+            out,
+            "Cross-library groups: {} (policy: {})",
+            cross_library_groups,
+            cross_library
+        );
+    }
+    let cross_owner_groups = groups.iter().filter(|g| g.is_cross_owner()).count();
+    if cross_owner_groups > 0 {
+        outln!(
+            out,
+            "Groups owned by different users: {} ({})",
+            cross_owner_groups,
+            if allow_cross_owner { "processed" } else { "skipped; see --allow-cross-owner" }
+        );
+    }
+    if shared_links != SharedLinkPolicy::Ignore {
+        outln!(out, "Shared links: {}", shared_links);
+    }
+    if shared_albums != SharedAlbumPolicy::Review {
+        outln!(out, "Albums owned by other users: {}", shared_albums);
+    }
+    if description_policy != DescriptionPolicy::Fill {
+        outln!(out, "Descriptions: {}", description_policy);
+    }
+    if let Some(limit) = operation_timeout {
+        outln!(out, "Operation timeout: {}s", limit.as_secs());
+    }
+    if let Some(limit) = group_timeout {
+        outln!(out, "Group timeout: {}s", limit.as_secs());
+    }
+    if let Some(notification) = &notification {
+        outln!(out, "Notify on completion: {}", notification.redacted_url());
+    }
+    outln!(out, "Deletion ledger: {}", ledger.path().display());
+    outln!(out);
+    print_risk(out, &risk_report);
+    outln!(out);
+    out.event(
+        "plan",
+        json!({
+            "groups": groups.len(),
+            "assets": total_assets,
+            "estimated_bytes": estimated_size,
+            "backup_dir": backup_dir,
+            "force": force,
+            "mode": mode,
+            "cross_library_groups": cross_library_groups,
+            "cross_library_policy": cross_library,
+            "cross_owner_groups": cross_owner_groups,
+            "allow_cross_owner": allow_cross_owner,
+            "shared_link_policy": shared_links,
+            "shared_album_policy": shared_albums,
+            "description_policy": description_policy,
+            "ledger": ledger.path(),
+            "skipped_low_confidence": low_confidence.len(),
+            "risk": risk_report,
+        }),
+    )?;
+
+    if let Some(path) = plan_out {
+        let planner = Executor::new(
+            client.clone(),
+            ExecutionConfig {
+                requests_per_sec: rate_limit,
+                max_concurrent: concurrent,
+                backup_dir: backup_dir.clone(),
+                force_delete: force,
+                preserve_memories,
+                cross_library,
+                shared_links,
+                shared_albums,
+                description_policy,
+                allow_cross_owner,
+                mode,
+                ..Default::default()
+            },
+        );
+        let plan = planner.plan(&groups).await;
+        report_io::write_json(path, &plan)
+            .with_context(|| format!("Failed to write execution plan: {}", path.display()))?;
+        outln!(
+            out,
+            "Wrote plan for {} groups ({} assets to delete) to {}",
+            plan.groups.len(),
+            plan.assets_to_delete(),
+            path.display()
+        );
+        outln!(out, "Nothing was changed. Review the plan, then apply it with --plan {}", path.display());
+        out.event(
+            "plan_written",
+            json!({ "path": path, "groups": plan.groups.len(), "assets": plan.assets_to_delete() }),
+        )?;
+        return Ok(());
+    }
+
+    if strict && risk_report.grade > RiskGrade::Low {
+        outln!(out, "Aborted: --strict requires a low-risk plan (risk is {}).", risk_report.grade);
+        out.event("aborted", json!({ "command": "execute", "reason": "risk", "risk": risk_report.grade }))?;
+        return Ok(());
+    }
+    let auto_confirmed = auto_safe && risk_report.grade == RiskGrade::Low;
+    if auto_safe && !auto_confirmed && !yes {
+        outln!(out, "Risk is {}; --auto-safe needs confirmation.", risk_report.grade);
     }
-    println!("Backup directory: {}", backup_dir.display());
-    println!("Force delete: {}", if force { "yes (permanent)" } else { "no (trash)" });
-    println!();
 
     // Confirmation prompt
-    if !yes {
-        print!("About to download {} assets and delete them from Immich. Continue? [y/N] ", total_assets);
-        std::io::stdout().flush()?;
+    if !yes && !auto_confirmed {
+        out.prompt(match mode {
+            ExecutionMode::AlbumOnly => format!(
+                "About to replace {} assets with their winners in albums. Continue? [y/N] ",
+                total_assets
+            ),
+            ExecutionMode::Full => format!(
+                "About to download {} assets and delete them from Immich. Continue? [y/N] ",
+                total_assets
+            ),
+        })?;
 
         let mut response = String::new();
         std::io::stdin().read_line(&mut response)?;
         let response = response.trim().to_lowercase();
 
         if response != "y" && response != "yes" {
-            println!("Aborted.");
+            outln!(out, "Aborted.");
+            out.event("aborted", json!({ "command": "execute" }))?;
             return Ok(());
         }
     }
 
-    println!();
-    println!("Starting execution...");
-    println!();
-
-    // Create client and executor
-    let client = ImmichClient::new(url, api_key)
-        .context("Failed to create Immich client")?;
+    outln!(out);
+    outln!(out, "Starting execution...");
+    outln!(out);
+
+    let metrics = (metrics_addr.is_some() || pushgateway.is_some()).then(Metrics::new);
+    let _metrics_server = match (&metrics, metrics_addr) {
+        (Some(metrics), Some(addr)) => {
+            let server = metrics::serve(Arc::clone(metrics), addr)
+                .await
+                .with_context(|| format!("Failed to serve metrics on {}", addr))?;
+            outln!(out, "Serving metrics at http://{}/metrics", server.addr());
+            Some(server)
+        }
+        _ => None,
+    };
 
     let config = ExecutionConfig {
         requests_per_sec: rate_limit,
         max_concurrent: concurrent,
+        operation_timeout,
+        group_timeout,
         backup_dir: backup_dir.clone(),
+        backup_target,
         force_delete: force,
+        delete_chunk_size,
+        backup_sink,
+        write_sidecars: sidecars,
+        embed_metadata,
+        decode_backups,
+        protected_albums: protection.albums.clone(),
+        protected_tags: protection.tags.clone(),
+        promote_album_covers: promote_covers,
+        preserve_memories,
+        tag_winners,
+        ledger_path: Some(ledger.path().to_path_buf()),
+        cross_library,
+        shared_links,
+        shared_albums,
+        description_policy,
+        allow_cross_owner,
+        mode,
+        rollback_partial,
+        history_dir: Some(config::history_dir()),
+        notification,
+        metrics: metrics.clone(),
+    };
+
+    let executor = Executor::new(client, config);
+
+    // Execute
+    let exec_report = match &plan {
+        Some(plan) => executor.apply(plan).await.context("Failed to apply execution plan")?,
+        None => executor.execute_all(&groups).await,
+    };
+
+    // Print summary
+    outln!(out);
+    outln!(out, "Execution Complete");
+    outln!(out, "==================");
+    outln!(out, "Groups processed: {}", exec_report.total_groups);
+    outln!(out, "Assets downloaded: {}", exec_report.downloaded);
+    outln!(out, "Assets deleted: {}", exec_report.deleted);
+    if exec_report.reclaimed_bytes > 0 {
+        outln!(out, "Space reclaimed: {}", units::format_bytes(exec_report.reclaimed_bytes));
+    }
+    if exec_report.trashed_bytes > 0 {
+        outln!(
+            out,
+            "Moved to trash: {} (reclaimed once the trash is emptied)",
+            units::format_bytes(exec_report.trashed_bytes)
+        );
+    }
+    outln!(out, "Failed operations: {}", exec_report.failed);
+    outln!(out, "Skipped: {}", exec_report.skipped);
+    let reviewed = exec_report.results.iter().filter(|r| r.decision.is_some()).count();
+    if reviewed > 0 {
+        outln!(out, "Groups with reviewer decisions: {}", reviewed);
+    }
+    let promotions: Vec<_> = exec_report.results.iter().flat_map(|r| r.promotions.iter()).collect();
+    for (kind, label) in [
+        (PromotionKind::AlbumCover, "Album covers promoted"),
+        (PromotionKind::AlbumMembership, "Album memberships replaced"),
+        (PromotionKind::SharedLink, "Shared links re-pointed"),
+        (PromotionKind::Memory, "Memories preserved"),
+    ] {
+        let of_kind: Vec<_> = promotions.iter().filter(|p| p.kind == kind).collect();
+        if !of_kind.is_empty() {
+            let succeeded = of_kind.iter().filter(|p| p.succeeded()).count();
+            outln!(out, "{}: {} ({} failed)", label, succeeded, of_kind.len() - succeeded);
+        }
+    }
+    if let Some(rate) = &exec_report.rate_limit
+        && rate.throttled > 0
+    {
+        outln!(
+            out,
+            "Rate limited {} times: slowed to {:.1} req/s, finished at {:.1} req/s (configured {:.0})",
+            rate.throttled,
+            rate.min_rate,
+            rate.effective_rate,
+            rate.configured_rate
+        );
+    }
+    let rolled_back = exec_report.results.iter().filter(|r| !r.rolled_back_ids.is_empty()).count();
+    if rolled_back > 0 {
+        outln!(out, "Groups rolled back after a partial delete: {}", rolled_back);
+    }
+    if let Some(run_id) = &exec_report.run_id {
+        outln!(out, "Run history: immich-dupes history show {}", run_id);
+    }
+    if let Some(error) = &exec_report.history_error {
+        outln!(out, "Warning: run history not fully recorded: {}", error);
+    }
+    if let Some(archive) = &exec_report.backup_archive {
+        outln!(out, "Backup archive: {}", archive.display());
+    }
+    if let Some(error) = &exec_report.archive_error {
+        outln!(out, "Warning: backup archive: {}", error);
+    }
+    if !exec_report.tagged_winners.is_empty() {
+        outln!(out, "Winners tagged: {}", exec_report.tagged_winners.len());
+    }
+    if let Some(error) = &exec_report.tagging_error {
+        outln!(out, "Warning: failed to tag winners: {}", error);
+    }
+    if let Some(error) = &exec_report.notification_error {
+        outln!(out, "Warning: {}", error);
+    }
+    if let (Some(metrics), Some(gateway)) = (&metrics, pushgateway)
+        && let Err(e) = metrics.push(gateway, "immich_dupes").await
+    {
+        outln!(out, "Warning: failed to push metrics to {}: {}", gateway, e);
+    }
+
+    let errors: Vec<_> = exec_report
+        .results
+        .iter()
+        .flat_map(|g| g.download_results.iter())
+        .filter_map(|r| {
+            if let immich_lib::models::OperationResult::Failed { id, error } = r {
+                Some((id, error))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Show first few errors if any
+    if exec_report.failed > 0 {
+        outln!(out);
+        outln!(out, "First errors:");
+        for (id, error) in errors.iter().take(5) {
+            outln!(out, "  - {}: {}", id, error);
+        }
+    }
+
+    // Write execution report to backup directory, compressed if the analysis was
+    let timestamp = file_stamp();
+    let extension = if report_io::is_compressed(input) { "json.zst" } else { "json" };
+    let report_path = backup_dir.join(format!("execution-report-{}.{}", timestamp, extension));
+    report_io::write_json(&report_path, &exec_report)
+        .with_context(|| format!("Failed to write execution report: {}", report_path.display()))?;
+
+    outln!(out);
+    outln!(out, "Execution report: {}", report_path.display());
+
+    if report_format == ReportFormat::Markdown {
+        let summary_path = backup_dir.join(format!("execution-summary-{}.md", timestamp));
+        std::fs::write(&summary_path, exec_report.to_markdown())
+            .with_context(|| format!("Failed to write execution summary: {}", summary_path.display()))?;
+        outln!(out, "Execution summary: {}", summary_path.display());
+    }
+
+    let mut run_id = None;
+    if report_io::is_database(input) {
+        let id = record_execution_in_store(input, &exec_report)
+            .with_context(|| format!("Failed to record execution in {}", input.display()))?;
+        outln!(out, "Recorded as run {} in {}", id, input.display());
+        run_id = Some(id);
+    }
+
+    out.event(
+        "summary",
+        json!({
+            "command": "execute",
+            "total_groups": exec_report.total_groups,
+            "downloaded": exec_report.downloaded,
+            "deleted": exec_report.deleted,
+            "reclaimed_bytes": exec_report.reclaimed_bytes,
+            "trashed_bytes": exec_report.trashed_bytes,
+            "failed": exec_report.failed,
+            "skipped": exec_report.skipped,
+            "reviewed": reviewed,
+            "promotions": promotions,
+            "rolled_back_groups": rolled_back,
+            "skipped_low_confidence": low_confidence.len(),
+            "errors": errors
+                .iter()
+                .map(|(id, error)| json!({ "id": id, "error": error }))
+                .collect::<Vec<_>>(),
+            "report": report_path,
+            "run_id": run_id,
+            "history_run_id": exec_report.run_id,
+            "backup_archive": exec_report.backup_archive,
+            "archive_error": exec_report.archive_error,
+            "tagged_winners": exec_report.tagged_winners.len(),
+            "tagging_error": exec_report.tagging_error,
+            "notification_error": exec_report.notification_error,
+            "rate_limit": exec_report.rate_limit,
+        }),
+    )?;
+
+    Ok(())
+}
+
+/// Print a risk report's grade and categories.
+fn print_risk(out: Output, risk_report: &RiskReport) {
+    outln!(out, "Risk: {}", risk_report.grade.as_str().to_uppercase());
+    for count in &risk_report.categories {
+        if count.groups > 0 {
+            outln!(out, "  [{}] {} {}", count.grade, count.groups, count.category.description());
+        } else {
+            outln!(
+                out,
+                "  [{}] {} ({} assets, {:.1} MB)",
+                count.grade,
+                count.category.description(),
+                risk_report.assets_deleted,
+                risk_report.bytes_deleted as f64 / 1_048_576.0
+            );
+        }
+    }
+}
+
+/// Analyze, plan, confirm, execute, and verify, resuming an unfinished run
+/// in `work_dir` if there is one.
+async fn run_pipeline(
+    url: &str,
+    api_key: &str,
+    work_dir: &PathBuf,
+    fresh: bool,
+    plan: &PlanOptions,
+    execution: ExecutionConfig,
+    out: Output,
+) -> Result<()> {
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+
+    let unfinished = match Pipeline::resume(work_dir)
+        .with_context(|| format!("Failed to read run state in {}", work_dir.display()))?
+    {
+        Some(pipeline) if !fresh && pipeline.state().stage < RunStage::Verified => Some(pipeline),
+        _ => None,
+    };
+    let mut pipeline = match unfinished {
+        Some(pipeline) => {
+            let state = pipeline.state();
+            if state.server_url != url {
+                anyhow::bail!(
+                    "The run in {} is against {}, not {}; use --fresh to start over",
+                    work_dir.display(),
+                    state.server_url,
+                    url
+                );
+            }
+            outln!(
+                out,
+                "Resuming run started {} ({}, {} of {} groups executed)",
+                state.started_at.format("%Y-%m-%d %H:%M"),
+                state.stage,
+                state.completed.len(),
+                state.groups.len()
+            );
+            pipeline
+        }
+        None => {
+            outln!(out, "Analyzing duplicates on {}...", url);
+            out.event("progress", json!({ "stage": "analyzing", "url": url }))?;
+            Pipeline::plan(&client, url, work_dir, plan)
+                .await
+                .context("Failed to plan run")?
+        }
     };
 
-    let executor = Executor::new(client, config);
+    let state = pipeline.state();
+    let to_delete = pipeline.assets_to_delete();
+    outln!(out);
+    outln!(out, "Run Plan");
+    outln!(out, "========");
+    outln!(out, "Groups to process: {}", state.groups.len() - state.completed.len());
+    outln!(out, "Groups held for review: {} (use review and execute for these)", state.held_for_review);
+    outln!(out, "Assets to move to trash: {}", to_delete);
+    outln!(out, "Backup directory: {}", execution.backup_dir.display());
+    outln!(out, "Run state: {}", work_dir.join(pipeline::STATE_FILE).display());
+    outln!(out);
+    print_risk(out, &state.risk);
+    outln!(out);
+    out.event(
+        "plan",
+        json!({
+            "stage": state.stage,
+            "groups": state.groups.len(),
+            "completed": state.completed.len(),
+            "held_for_review": state.held_for_review,
+            "assets": to_delete,
+            "backup_dir": execution.backup_dir,
+            "risk": state.risk,
+        }),
+    )?;
+
+    // Typed confirmation: the number of assets, not just "y"
+    if pipeline.state().stage == RunStage::Planned && to_delete == 0 {
+        outln!(out, "Nothing to delete.");
+        pipeline.confirm("0")?;
+    }
+    if pipeline.state().stage == RunStage::Planned {
+        out.prompt(format!(
+            "Type the number of assets to move to trash ({}) to continue: ",
+            to_delete
+        ))?;
+        let mut response = String::new();
+        std::io::stdin().read_line(&mut response)?;
+        if !pipeline.confirm(&response)? {
+            outln!(out, "Aborted. The plan is kept; run again to resume.");
+            out.event("aborted", json!({ "command": "run" }))?;
+            return Ok(());
+        }
+    }
+
+    if pipeline.state().stage < RunStage::Executed {
+        outln!(out);
+        outln!(out, "Starting execution...");
+        outln!(out);
+        let executor = Executor::new(client.clone(), execution);
+        let report = pipeline.execute(&executor).await.context("Failed to save run progress")?;
+        outln!(out);
+        outln!(out, "Assets downloaded: {}", report.downloaded);
+        outln!(out, "Assets deleted: {}", report.deleted);
+        outln!(out, "Failed operations: {}", report.failed);
+        outln!(out, "Skipped: {}", report.skipped);
+        if !report.tagged_winners.is_empty() {
+            outln!(out, "Winners tagged: {}", report.tagged_winners.len());
+        }
+        if let Some(error) = &report.tagging_error {
+            outln!(out, "Warning: failed to tag winners: {}", error);
+        }
+
+        let report_path = work_dir.join(format!("execution-report-{}.json", file_stamp()));
+        report_io::write_json(&report_path, &report)
+            .with_context(|| format!("Failed to write execution report: {}", report_path.display()))?;
+        outln!(out, "Execution report: {}", report_path.display());
+        if let Some(run_id) = &report.run_id {
+            outln!(out, "Run history: immich-dupes history show {}", run_id);
+        }
+    }
+
+    outln!(out);
+    outln!(out, "Verifying...");
+    let verification = pipeline.verify(&client).await.context("Failed to save run progress")?;
+    outln!(out, "Groups verified: {}", verification.groups_checked);
+    if verification.is_clean() {
+        outln!(out, "All winners present and deleted losers gone.");
+    } else {
+        for id in &verification.winners_missing {
+            outln!(out, "  CRITICAL: winner {} is missing", id);
+        }
+        for id in &verification.losers_present {
+            outln!(out, "  Loser {} is still present", id);
+        }
+        for error in &verification.errors {
+            outln!(out, "  Could not check {}", error);
+        }
+    }
+
+    out.event(
+        "summary",
+        json!({
+            "command": "run",
+            "work_dir": work_dir,
+            "groups": pipeline.state().groups.len(),
+            "verification": verification,
+        }),
+    )?;
+
+    Ok(())
+}
+
+async fn run_audit_originals(
+    url: &str,
+    api_key: &str,
+    deep: bool,
+    decode: bool,
+    output: Option<&PathBuf>,
+) -> Result<()> {
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+
+    println!("Fetching all assets...");
+    let assets = client
+        .get_all_assets()
+        .await
+        .context("Failed to fetch assets from Immich")?;
+    println!("Auditing {} originals{}...", assets.len(), if deep { " (deep)" } else { "" });
 
-    // Execute
-    let exec_report = executor.execute_all(&groups).await;
+    let scratch_dir = std::env::temp_dir().join(format!("immich-audit-{}", uuid::Uuid::new_v4()));
+    if deep {
+        std::fs::create_dir_all(&scratch_dir).context("Failed to create scratch directory")?;
+    }
 
-    // Print summary
-    println!();
-    println!("Execution Complete");
-    println!("==================");
-    println!("Groups processed: {}", exec_report.total_groups);
-    println!("Assets downloaded: {}", exec_report.downloaded);
-    println!("Assets deleted: {}", exec_report.deleted);
-    println!("Failed operations: {}", exec_report.failed);
-    println!("Skipped: {}", exec_report.skipped);
+    let pb = ProgressBar::new(assets.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
+            .expect("valid template")
+            .progress_chars("#>-"),
+    );
 
-    // Show first few errors if any
-    if exec_report.failed > 0 {
-        println!();
-        println!("First errors:");
-        let errors: Vec<_> = exec_report
-            .results
-            .iter()
-            .flat_map(|g| g.download_results.iter())
-            .filter_map(|r| {
-                if let immich_lib::models::OperationResult::Failed { id, error } = r {
-                    Some((id, error))
-                } else {
+    let mut corrupt = Vec::new();
+    for asset in &assets {
+        pb.inc(1);
+
+        let recorded_size = asset.exif_info.as_ref().and_then(|e| e.file_size_in_byte);
+        let issue = if recorded_size == Some(0) {
+            Some(IntegrityIssue::ZeroBytes)
+        } else if deep {
+            let path = scratch_dir.join(format!("{}_{}", asset.id, asset.original_file_name));
+            let checked = match client.download_asset(&asset.id, &path).await {
+                Ok(_) => check_file(&path, decode).await.map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            };
+            let _ = std::fs::remove_file(&path);
+            match checked {
+                Ok(issue) => issue,
+                Err(e) => {
+                    pb.println(format!("  Could not check {}: {}", asset.id, e));
                     None
                 }
-            })
-            .take(5)
-            .collect();
+            }
+        } else {
+            None
+        };
 
-        for (id, error) in errors {
-            println!("  - {}: {}", id, error);
+        if let Some(issue) = issue {
+            corrupt.push(CorruptOriginal {
+                asset_id: asset.id.clone(),
+                filename: asset.original_file_name.clone(),
+                issue,
+            });
         }
     }
 
-    // Write execution report to backup directory
-    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
-    let report_path = backup_dir.join(format!("execution-report-{}.json", timestamp));
-    let report_file = File::create(&report_path)
-        .with_context(|| format!("Failed to create report file: {}", report_path.display()))?;
-    let writer = BufWriter::new(report_file);
-    serde_json::to_writer_pretty(writer, &exec_report)
-        .context("Failed to write execution report")?;
+    pb.finish_and_clear();
+    if deep {
+        let _ = std::fs::remove_dir_all(&scratch_dir);
+    }
 
     println!();
-    println!("Execution report: {}", report_path.display());
+    println!("Originals audited: {}", assets.len());
+    println!("Corrupt originals: {}", corrupt.len());
+    for original in &corrupt {
+        println!("  {} ({}): {}", original.asset_id, original.filename, original.issue);
+    }
+
+    if let Some(path) = output {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create output file: {}", path.display()))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &corrupt)
+            .context("Failed to write JSON output")?;
+        println!();
+        println!("Report written to: {}", path.display());
+    }
 
     Ok(())
 }
 
-async fn run_verify(url: &str, api_key: &str, analysis_json: &PathBuf, format: &str) -> Result<()> {
+async fn run_verify(
+    url: &str,
+    api_key: &str,
+    analysis_json: &PathBuf,
+    format: &str,
+    sampling: Option<&SampleOptions>,
+) -> Result<()> {
     println!("Verifying post-execution state...");
     println!("Analysis file: {}", analysis_json.display());
     println!();
 
     // Load analysis JSON
-    let file = File::open(analysis_json)
-        .with_context(|| format!("Failed to open analysis file: {}", analysis_json.display()))?;
-    let reader = BufReader::new(file);
-    let analysis: AnalysisReport = serde_json::from_reader(reader)
-        .context("Failed to parse analysis JSON")?;
+    let analysis = load_analysis(analysis_json)?;
 
     // Create client
     let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    let mut verifier = Verifier::new(client);
+    if let Some(options) = sampling {
+        verifier = verifier.with_sampling(*options);
+    }
 
-    let mut groups_verified = 0;
-    let mut winners_present = 0;
-    let mut winners_missing = 0;
-    let mut losers_deleted = 0;
-    let mut losers_still_present = 0;
-    let mut consolidation_passed = 0;
-    let mut consolidation_failed = 0;
-    let mut group_results = Vec::new();
-    let mut anomalies = Vec::new();
-
-    println!("Checking {} groups...", analysis.groups.len());
-    println!();
-
-    for group in &analysis.groups {
-        groups_verified += 1;
-
-        // Check winner exists
-        let winner_status = match client.get_asset(&group.winner.asset_id).await {
-            Ok(asset) => {
-                winners_present += 1;
-                // Winner is present - check for consolidation if needed
-                let mut consolidation_checks = Vec::new();
-
-                // Check if any loser had GPS and winner didn't originally have it
-                let winner_had_gps = group.winner.score.gps > 0;
-                let any_loser_had_gps = group.losers.iter().any(|l| l.score.gps > 0);
-
-                if !winner_had_gps && any_loser_had_gps {
-                    // GPS should have been consolidated from loser to winner
-                    let has_gps_now = asset.exif_info.as_ref().is_some_and(|e| e.has_gps());
-                    if has_gps_now {
-                        consolidation_passed += 1;
-                        consolidation_checks.push(ConsolidationCheck {
-                            check_type: "gps_transferred".to_string(),
-                            passed: true,
-                            details: "GPS coordinates successfully transferred from loser".to_string(),
-                        });
-                    } else {
-                        consolidation_failed += 1;
-                        consolidation_checks.push(ConsolidationCheck {
-                            check_type: "gps_transferred".to_string(),
-                            passed: false,
-                            details: "GPS coordinates were NOT transferred from loser".to_string(),
-                        });
-                        anomalies.push(format!(
-                            "Group {}: GPS not transferred to winner {}",
-                            group.duplicate_id, group.winner.asset_id
-                        ));
-                    }
-                }
-
-                AssetStatus {
-                    asset_id: group.winner.asset_id.clone(),
-                    filename: group.winner.filename.clone(),
-                    status: "present".to_string(),
-                    error: None,
+    let total = analysis.groups.len();
+    let report = verifier
+        .verify_with(&analysis.groups, |progress| match progress {
+            VerifyProgress::Started { checking, total, sampled } => {
+                if sampled {
+                    println!("Checking a random sample of {} of {} groups...", checking, total);
+                } else {
+                    println!("Checking {} groups...", total);
                 }
+                println!();
             }
-            Err(immich_lib::ImmichError::Api { status: 404, .. }) => {
-                winners_missing += 1;
-                anomalies.push(format!(
-                    "CRITICAL: Winner {} ({}) was deleted!",
-                    group.winner.asset_id, group.winner.filename
-                ));
-                AssetStatus {
-                    asset_id: group.winner.asset_id.clone(),
-                    filename: group.winner.filename.clone(),
-                    status: "deleted".to_string(),
-                    error: Some("Winner was incorrectly deleted".to_string()),
-                }
+            VerifyProgress::SampleExtended { upper, next } => {
+                println!();
+                println!(
+                    "Sample clean but failure rate could still be up to {:.2}%; extending to {} groups...",
+                    upper * 100.0,
+                    next
+                );
             }
-            Err(e) => {
-                winners_missing += 1;
-                anomalies.push(format!(
-                    "Error checking winner {}: {}",
-                    group.winner.asset_id, e
-                ));
-                AssetStatus {
-                    asset_id: group.winner.asset_id.clone(),
-                    filename: group.winner.filename.clone(),
-                    status: "error".to_string(),
-                    error: Some(e.to_string()),
-                }
+            VerifyProgress::Escalated { failed, total } => {
+                println!();
+                println!("Sample found {} failing groups; verifying all {} groups...", failed, total);
             }
-        };
-
-        // Check all losers are deleted (or trashed)
-        let mut loser_statuses = Vec::new();
-        for loser in &group.losers {
-            let loser_status = match client.get_asset(&loser.asset_id).await {
-                Ok(asset) => {
-                    if asset.is_trashed {
-                        // Loser is in trash - this counts as deleted
-                        losers_deleted += 1;
-                        AssetStatus {
-                            asset_id: loser.asset_id.clone(),
-                            filename: loser.filename.clone(),
-                            status: "trashed".to_string(),
-                            error: None,
-                        }
-                    } else {
-                        // Loser still exists and not trashed - this is wrong!
-                        losers_still_present += 1;
-                        anomalies.push(format!(
-                            "Loser {} ({}) still exists (not trashed), should be deleted",
-                            loser.asset_id, loser.filename
-                        ));
-                        AssetStatus {
-                            asset_id: loser.asset_id.clone(),
-                            filename: loser.filename.clone(),
-                            status: "present".to_string(),
-                            error: Some("Loser should have been deleted".to_string()),
-                        }
-                    }
-                }
-                Err(immich_lib::ImmichError::Api { status: 404, .. }) => {
-                    // Loser correctly deleted (permanently)
-                    losers_deleted += 1;
-                    AssetStatus {
-                        asset_id: loser.asset_id.clone(),
-                        filename: loser.filename.clone(),
-                        status: "deleted".to_string(),
-                        error: None,
-                    }
-                }
-                Err(e) => {
-                    // Some other error
-                    anomalies.push(format!(
-                        "Error checking loser {}: {}",
-                        loser.asset_id, e
-                    ));
-                    AssetStatus {
-                        asset_id: loser.asset_id.clone(),
-                        filename: loser.filename.clone(),
-                        status: "error".to_string(),
-                        error: Some(e.to_string()),
-                    }
+            // Progress indicator
+            VerifyProgress::GroupVerified { verified } => {
+                if verified % 10 == 0 {
+                    print!(".");
+                    let _ = std::io::stdout().flush();
                 }
-            };
-            loser_statuses.push(loser_status);
-        }
-
-        // Collect consolidation checks from winner verification
-        let consolidation_checks = if winner_status.status == "present" {
-            let mut checks = Vec::new();
-            let winner_had_gps = group.winner.score.gps > 0;
-            let any_loser_had_gps = group.losers.iter().any(|l| l.score.gps > 0);
-            if !winner_had_gps && any_loser_had_gps {
-                // Already tracked above in the winner check
-            } else if winner_had_gps {
-                checks.push(ConsolidationCheck {
-                    check_type: "gps_retained".to_string(),
-                    passed: true,
-                    details: "Winner already had GPS, no transfer needed".to_string(),
-                });
-            } else {
-                checks.push(ConsolidationCheck {
-                    check_type: "no_gps".to_string(),
-                    passed: true,
-                    details: "No GPS in group, no transfer needed".to_string(),
-                });
             }
-            checks
-        } else {
-            Vec::new()
-        };
-
-        group_results.push(GroupVerification {
-            duplicate_id: group.duplicate_id.clone(),
-            winner_status,
-            loser_statuses,
-            consolidation_checks,
-        });
-
-        // Progress indicator
-        if groups_verified % 10 == 0 {
-            print!(".");
-            std::io::stdout().flush()?;
-        }
-    }
+        })
+        .await;
     println!();
     println!();
 
-    // Build report
-    let report = VerificationReport {
-        verified_at: Utc::now(),
-        server_url: url.to_string(),
-        groups_verified,
-        winners_present,
-        winners_missing,
-        losers_deleted,
-        losers_still_present,
-        consolidation_passed,
-        consolidation_failed,
-        groups: group_results,
-        anomalies: anomalies.clone(),
-    };
-
     // Output based on format
     match format.to_lowercase().as_str() {
         "json" => {
@@ -878,26 +3395,51 @@ async fn run_verify(url: &str, api_key: &str, analysis_json: &PathBuf, format: &
             println!("Verification Report");
             println!("==================");
             println!();
-            println!("Groups verified:       {}", groups_verified);
-            println!("Winners present:       {}/{}", winners_present, groups_verified);
-            println!("Winners missing:       {}", winners_missing);
-            println!("Losers deleted:        {}", losers_deleted);
-            println!("Losers still present:  {}", losers_still_present);
+            println!("Groups verified:       {}", report.groups_verified);
+            println!("Winners present:       {}/{}", report.winners_present, report.groups_verified);
+            println!("Winners missing:       {}", report.winners_missing);
+            println!("Losers deleted:        {}", report.losers_deleted);
+            println!("Losers still present:  {}", report.losers_still_present);
             println!();
-            println!("Consolidation passed:  {}", consolidation_passed);
-            println!("Consolidation failed:  {}", consolidation_failed);
+            println!("Consolidation passed:  {}", report.consolidation_passed);
+            println!("Consolidation failed:  {}", report.consolidation_failed);
 
-            if !anomalies.is_empty() {
+            if let Some(summary) = &report.sampling {
+                let estimate = &summary.estimate;
+                let (low, high) = summary.projected_failures;
                 println!();
-                println!("Anomalies ({}):", anomalies.len());
-                for anomaly in &anomalies {
+                println!(
+                    "Sampled:               {} of {} groups (seed {}){}",
+                    estimate.sampled,
+                    estimate.population,
+                    summary.options.seed,
+                    if summary.escalated { ", escalated to full verification" } else { "" }
+                );
+                println!(
+                    "Failure rate:          {:.2}% (95% CI {:.2}%-{:.2}%)",
+                    estimate.rate * 100.0,
+                    estimate.lower * 100.0,
+                    estimate.upper * 100.0
+                );
+                println!("Projected failures:    {}-{} of {} groups", low, high, estimate.population);
+            }
+
+            if !report.anomalies.is_empty() {
+                println!();
+                println!("Anomalies ({}):", report.anomalies.len());
+                for anomaly in &report.anomalies {
                     println!("  - {}", anomaly);
                 }
             }
 
             println!();
-            if winners_missing == 0 && losers_still_present == 0 && consolidation_failed == 0 {
-                println!("VERIFICATION PASSED: All checks successful");
+            let partial = report.groups_verified < total;
+            if report.passed() {
+                if partial {
+                    println!("VERIFICATION PASSED: All sampled groups successful");
+                } else {
+                    println!("VERIFICATION PASSED: All checks successful");
+                }
             } else {
                 println!("VERIFICATION FAILED: Issues detected");
             }
@@ -913,6 +3455,7 @@ async fn run_find_test_candidates(
     format: &str,
     scenario_filter: Option<&str>,
     output: Option<&PathBuf>,
+    thresholds: &Thresholds,
 ) -> Result<()> {
     println!("Connecting to Immich server at {}...", url);
 
@@ -928,26 +3471,29 @@ async fn run_find_test_candidates(
 
     println!("Analyzing {} duplicate groups for test scenarios...", duplicates.len());
 
+    // Only the scenarios matching the prefix, if specified, so gaps are reported within them
+    let registry = match scenario_filter {
+        Some(prefix) => {
+            let prefix_upper = prefix.to_uppercase();
+            let mut registry = ScenarioRegistry::default();
+            for scenario in ScenarioRegistry::builtin().scenarios() {
+                if scenario.to_string().to_uppercase().starts_with(&prefix_upper) {
+                    registry.register(*scenario)?;
+                }
+            }
+            registry
+        }
+        None => ScenarioRegistry::builtin(),
+    };
+
     // Detect scenarios for each group
     let mut all_matches = Vec::new();
     for group in &duplicates {
-        let matches = detect_scenarios(group);
-        all_matches.extend(matches);
+        all_matches.extend(registry.detect_with(group, thresholds));
     }
 
-    // Filter by scenario prefix if specified
-    let filtered_matches = if let Some(prefix) = scenario_filter {
-        let prefix_upper = prefix.to_uppercase();
-        all_matches
-            .into_iter()
-            .filter(|m| m.scenario.to_string().to_uppercase().starts_with(&prefix_upper))
-            .collect()
-    } else {
-        all_matches
-    };
-
     // Build report
-    let report = ScenarioReport::from_matches(filtered_matches, duplicates.len());
+    let report = ScenarioReport::from_registry(&registry, all_matches, duplicates.len());
 
     // Format output
     let output_text = match format.to_lowercase().as_str() {
@@ -969,49 +3515,114 @@ async fn run_find_test_candidates(
     Ok(())
 }
 
-/// Manifest file structure for each scenario fixture
-#[derive(Debug, Serialize)]
-struct FixtureManifest {
-    scenario: String,
-    description: String,
-    images: Vec<String>,
-    expected_winner: String,
+/// Fixtures whose scenario name starts with `filter` (e.g. "W1", "C"), or all of them.
+fn select_fixtures(scenario_filter: Option<&str>) -> Vec<ScenarioFixture> {
+    let fixtures = all_fixtures();
+    match scenario_filter {
+        Some(filter) => {
+            let filter_upper = filter.to_uppercase();
+            fixtures
+                .into_iter()
+                .filter(|f| f.scenario.to_string().to_uppercase().starts_with(&filter_upper))
+                .collect()
+        }
+        None => fixtures,
+    }
 }
 
-fn run_generate_fixtures(output_dir: &PathBuf, scenario_filter: Option<&str>) -> Result<()> {
-    println!("Loading fixture definitions...");
+/// Generate one fixture's images and manifest into `output_dir/<code>`.
+///
+/// Returns whether every image was generated.
+fn generate_fixture(fixture: &ScenarioFixture, base_dir: &Path, output_dir: &Path, out: Output) -> Result<bool> {
+    let scenario_code = fixture.scenario.code();
+    let scenario_dir = output_dir.join(scenario_code);
+
+    // Create scenario subdirectory
+    std::fs::create_dir_all(&scenario_dir).with_context(|| {
+        format!(
+            "Failed to create scenario directory: {}",
+            scenario_dir.display()
+        )
+    })?;
+
+    outln!(out, "  {} - {}...", scenario_code.to_uppercase(), fixture.description);
+
+    let mut image_filenames = Vec::new();
+    let mut all_success = true;
+
+    for image in &fixture.images {
+        match generate_image(image, base_dir, &scenario_dir) {
+            Ok(path) => {
+                image_filenames.push(image.filename.clone());
+                outln!(out, "    ✓ {}", path.file_name().unwrap_or_default().to_string_lossy());
+            }
+            Err(e) => {
+                eprintln!("    ✗ {} - {}", image.filename, e);
+                all_success = false;
+            }
+        }
+    }
 
-    let fixtures = all_fixtures();
-    let total = fixtures.len();
+    // Write manifest
+    let manifest = fixture.manifest(image_filenames);
+    manifest
+        .save(&scenario_dir)
+        .with_context(|| format!("Failed to write manifest in {}", scenario_dir.display()))?;
+
+    out.event(
+        "fixture",
+        json!({
+            "scenario": manifest.scenario,
+            "description": manifest.description,
+            "images": manifest.images,
+            "failed": !all_success,
+            "manifest": scenario_dir.join(FixtureManifest::FILE_NAME),
+        }),
+    )?;
+
+    Ok(all_success)
+}
 
-    // Base images directory (contains real photos for transforms)
-    let base_dir = output_dir.join("base");
+/// Warn if the base images that fixtures are transformed from are missing.
+fn check_base_dir(base_dir: &Path, out: Output) -> Result<()> {
     if !base_dir.exists() {
-        println!("Warning: Base images directory not found: {}", base_dir.display());
-        println!("Run the fixture setup first to download base images.");
+        outln!(out, "Warning: Base images directory not found: {}", base_dir.display());
+        outln!(out, "Run the fixture setup first to download base images.");
+        out.event(
+            "warning",
+            json!({ "message": "base images directory not found", "path": base_dir }),
+        )?;
     }
+    Ok(())
+}
+
+fn run_generate_fixtures(
+    output_dir: &PathBuf,
+    scenario_filter: Option<&str>,
+    out: Output,
+) -> Result<()> {
+    outln!(out, "Loading fixture definitions...");
+
+    let total = all_fixtures().len();
+
+    // Base images directory (contains real photos for transforms)
+    let base_dir = output_dir.join("base");
+    check_base_dir(&base_dir, out)?;
 
     // Filter fixtures if scenario specified
-    let fixtures: Vec<_> = if let Some(filter) = scenario_filter {
-        let filter_upper = filter.to_uppercase();
-        fixtures
-            .into_iter()
-            .filter(|f| f.scenario.to_string().to_uppercase().starts_with(&filter_upper))
-            .collect()
-    } else {
-        fixtures
-    };
+    let fixtures = select_fixtures(scenario_filter);
 
     if fixtures.is_empty() {
         if let Some(filter) = scenario_filter {
-            println!("No fixtures found matching filter: {}", filter);
+            outln!(out, "No fixtures found matching filter: {}", filter);
         } else {
-            println!("No fixtures defined.");
+            outln!(out, "No fixtures defined.");
         }
         return Ok(());
     }
 
-    println!(
+    outln!(
+        out,
         "Generating {} of {} fixtures...",
         fixtures.len(),
         total
@@ -1025,67 +3636,116 @@ fn run_generate_fixtures(output_dir: &PathBuf, scenario_filter: Option<&str>) ->
     let mut failed_count = 0;
 
     for fixture in &fixtures {
-        let scenario_code = fixture.scenario.code();
-        let scenario_dir = output_dir.join(scenario_code);
-
-        // Create scenario subdirectory
-        std::fs::create_dir_all(&scenario_dir).with_context(|| {
-            format!(
-                "Failed to create scenario directory: {}",
-                scenario_dir.display()
-            )
-        })?;
+        if generate_fixture(fixture, &base_dir, output_dir, out)? {
+            generated_count += 1;
+        } else {
+            failed_count += 1;
+        }
+    }
 
-        println!("  {} - {}...", scenario_code.to_uppercase(), fixture.description);
+    outln!(out);
+    outln!(out, "Generation complete!");
+    outln!(out, "  Successful: {}", generated_count);
+    if failed_count > 0 {
+        outln!(out, "  Failed: {}", failed_count);
+    }
+    outln!(out, "  Output directory: {}", output_dir.display());
+    out.event(
+        "summary",
+        json!({
+            "command": "generate-fixtures",
+            "generated": generated_count,
+            "failed": failed_count,
+            "output_dir": output_dir,
+        }),
+    )?;
 
-        let mut image_filenames = Vec::new();
-        let mut all_success = true;
+    Ok(())
+}
 
-        for image in &fixture.images {
-            match generate_image(image, &base_dir, &scenario_dir) {
-                Ok(path) => {
-                    image_filenames.push(image.filename.clone());
-                    println!("    ✓ {}", path.file_name().unwrap_or_default().to_string_lossy());
-                }
-                Err(e) => {
-                    eprintln!("    ✗ {} - {}", image.filename, e);
-                    all_success = false;
-                }
-            }
-        }
+/// Interval between job queue and duplicate checks while seeding.
+const SEED_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
-        // Write manifest
-        let manifest = FixtureManifest {
-            scenario: scenario_code.to_uppercase(),
-            description: fixture.description.clone(),
-            images: image_filenames.clone(),
-            expected_winner: fixture
-                .images
-                .get(fixture.expected_winner_index)
-                .map(|i| i.filename.clone())
-                .unwrap_or_default(),
-        };
+async fn run_seed_fixtures(
+    url: &str,
+    api_key: &str,
+    fixtures_dir: &Path,
+    scenario_filter: Option<&str>,
+    regenerate: bool,
+    timeout: Duration,
+    out: Output,
+) -> Result<()> {
+    let fixtures = select_fixtures(scenario_filter);
+    if fixtures.is_empty() {
+        anyhow::bail!("No fixtures found matching filter: {}", scenario_filter.unwrap_or_default());
+    }
 
-        let manifest_path = scenario_dir.join("manifest.json");
-        let manifest_file = File::create(&manifest_path)
-            .with_context(|| format!("Failed to create manifest: {}", manifest_path.display()))?;
-        serde_json::to_writer_pretty(manifest_file, &manifest)
-            .context("Failed to write manifest JSON")?;
+    // Generate what's missing (or everything, when asked)
+    let missing: Vec<&ScenarioFixture> = fixtures
+        .iter()
+        .filter(|f| regenerate || !fixtures_dir.join(f.scenario.code()).join(FixtureManifest::FILE_NAME).exists())
+        .collect();
+    if !missing.is_empty() {
+        outln!(out, "Generating {} of {} fixtures...", missing.len(), fixtures.len());
+        let base_dir = fixtures_dir.join("base");
+        check_base_dir(&base_dir, out)?;
+        for fixture in missing {
+            generate_fixture(fixture, &base_dir, fixtures_dir, out)?;
+        }
+        outln!(out);
+    }
 
-        if all_success {
-            generated_count += 1;
-        } else {
-            failed_count += 1;
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    outln!(out, "Uploading {} scenarios to {}...", fixtures.len(), url);
+    let mut seeded = Vec::new();
+    for fixture in &fixtures {
+        let scenario_dir = fixtures_dir.join(fixture.scenario.code());
+        let scenario = testing::upload_scenario(&client, &scenario_dir)
+            .await
+            .with_context(|| format!("Failed to seed {}", scenario_dir.display()))?;
+        outln!(out, "  {}: {} uploaded", scenario.scenario, scenario.assets.len());
+        for error in &scenario.errors {
+            outln!(out, "    ✗ {}", error);
         }
+        seeded.push(scenario);
     }
 
-    println!();
-    println!("Generation complete!");
-    println!("  Successful: {}", generated_count);
-    if failed_count > 0 {
-        println!("  Failed: {}", failed_count);
+    outln!(out);
+    outln!(out, "Waiting for duplicate detection (up to {}s)...", timeout.as_secs());
+    let groups = testing::wait_for_duplicates(&client, &seeded, SEED_POLL_INTERVAL, timeout)
+        .await
+        .context("Failed waiting for duplicate detection")?;
+    testing::map_duplicate_groups(&mut seeded, &groups);
+
+    outln!(out);
+    outln!(out, "Scenario groups:");
+    for scenario in &seeded {
+        let groups = match scenario.duplicate_ids.as_slice() {
+            [] => "(no duplicate group)".to_string(),
+            ids => ids.join(", "),
+        };
+        outln!(out, "  {:<4} {}", scenario.scenario, groups);
+    }
+
+    let uploaded: usize = seeded.iter().map(|s| s.assets.len()).sum();
+    let failed: usize = seeded.iter().map(|s| s.errors.len()).sum();
+    let grouped = seeded.iter().filter(|s| !s.duplicate_ids.is_empty()).count();
+    outln!(out);
+    outln!(out, "Seeding complete!");
+    outln!(out, "  Uploaded: {}", uploaded);
+    if failed > 0 {
+        outln!(out, "  Failed: {}", failed);
     }
-    println!("  Output directory: {}", output_dir.display());
+    outln!(out, "  Scenarios in a duplicate group: {} of {}", grouped, seeded.len());
+    out.event(
+        "summary",
+        json!({
+            "command": "seed-fixtures",
+            "uploaded": uploaded,
+            "failed": failed,
+            "scenarios": seeded,
+        }),
+    )?;
 
     Ok(())
 }
@@ -1096,10 +3756,129 @@ const MEDIA_EXTENSIONS: &[&str] = &[
     "mp4", "mov", "avi", "webm", "mkv", "m4v", "wmv", "flv", "3gp",
 ];
 
-async fn run_restore(url: &str, api_key: &str, backup_dir: &PathBuf, dry_run: bool) -> Result<()> {
+async fn run_prune_backups(
+    url: &str,
+    api_key: &str,
+    backup_dir: &Path,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+    out: Output,
+) -> Result<()> {
+    outln!(out, "Pruning backups in: {}", backup_dir.display());
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    let report = retention::prune_backups(&client, backup_dir, policy, dry_run)
+        .await
+        .with_context(|| format!("Failed to prune backup directory: {}", backup_dir.display()))?;
+
+    let verb = if dry_run { "Would prune" } else { "Pruned" };
+    for backup in &report.pruned {
+        outln!(out, "  {}: {}", verb, backup.path.display());
+    }
+    outln!(out);
+    outln!(
+        out,
+        "{} {} backups ({:.1} MB); kept {} ({:.1} MB)",
+        verb,
+        report.pruned.len(),
+        report.bytes_freed as f64 / 1_048_576.0,
+        report.kept,
+        report.bytes_kept as f64 / 1_048_576.0
+    );
+    if !report.unverified.is_empty() {
+        outln!(
+            out,
+            "Kept backups of {} assets due for pruning: their deletion could not be verified",
+            report.unverified.len()
+        );
+    }
+    if report.over_budget {
+        outln!(out, "Warning: the kept backups still exceed --max-size-gb");
+    }
+    for error in &report.errors {
+        outln!(out, "Warning: {}", error);
+    }
+    out.event(
+        "summary",
+        json!({
+            "dry_run": dry_run,
+            "pruned": report.pruned,
+            "bytes_freed": report.bytes_freed,
+            "kept": report.kept,
+            "bytes_kept": report.bytes_kept,
+            "unverified": report.unverified,
+            "over_budget": report.over_budget,
+            "errors": report.errors,
+        }),
+    )?;
+
+    Ok(())
+}
+
+async fn run_reconcile(url: &str, api_key: &str, backup_dir: &Path, ledger: &Ledger, out: Output) -> Result<()> {
+    outln!(out, "Reconciling backups in: {}", backup_dir.display());
+    let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
+    let report = reconcile::reconcile_backups(&client, backup_dir, ledger)
+        .await
+        .with_context(|| format!("Failed to reconcile backup directory: {}", backup_dir.display()))?;
+
+    for backup in &report.not_deleted {
+        outln!(out, "  Still on server: {}", backup.path.display());
+    }
+    for missing in &report.missing_backups {
+        match &missing.filename {
+            Some(filename) => outln!(out, "  No backup: {} ({})", missing.asset_id, filename),
+            None => outln!(out, "  No backup: {}", missing.asset_id),
+        }
+    }
+    outln!(out);
+    outln!(
+        out,
+        "{} backups, {} recorded deletions: {} backups of assets still on the server, {} deletions without a backup",
+        report.backups,
+        report.deletions,
+        report.not_deleted.len(),
+        report.missing_backups.len()
+    );
+    out.event(
+        "summary",
+        json!({
+            "backups": report.backups,
+            "deletions": report.deletions,
+            "not_deleted": report.not_deleted,
+            "missing_backups": report.missing_backups,
+        }),
+    )?;
+
+    if !report.is_clean() {
+        anyhow::bail!(
+            "reconcile found {} discrepancies",
+            report.not_deleted.len() + report.missing_backups.len()
+        );
+    }
+    Ok(())
+}
+
+async fn run_restore(
+    url: &str,
+    api_key: &str,
+    backup_dir: &PathBuf,
+    analysis: Option<&PathBuf>,
+    dry_run: bool,
+) -> Result<()> {
     println!("Restoring from: {}", backup_dir.display());
     println!();
 
+    // Metadata recorded for each asset, by the asset ID backups are named with
+    let recorded: HashMap<String, ScoredAsset> = match analysis {
+        Some(path) => load_analysis(path)?
+            .groups
+            .into_iter()
+            .flat_map(|g| std::iter::once(g.winner).chain(g.losers))
+            .map(|a| (a.asset_id.to_string(), a))
+            .collect(),
+        None => HashMap::new(),
+    };
+
     // Scan backup directory for media files
     let entries = std::fs::read_dir(backup_dir)
         .with_context(|| format!("Failed to read backup directory: {}", backup_dir.display()))?;
@@ -1147,22 +3926,79 @@ async fn run_restore(url: &str, api_key: &str, backup_dir: &PathBuf, dry_run: bo
     // Create client and upload files
     let client = ImmichClient::new(url, api_key).context("Failed to create Immich client")?;
 
+    // Ask the server which files it already has, so they aren't re-imported
+    let mut checksums = Vec::with_capacity(media_files.len());
+    for path in &media_files {
+        match file_checksum(path).await {
+            Ok(checksum) => checksums.push((path.display().to_string(), checksum)),
+            Err(e) => eprintln!("Warning: could not checksum {}: {}", path.display(), e),
+        }
+    }
+    let checks = match client.check_bulk_upload(&checksums).await {
+        Ok(checks) => checks,
+        Err(e) => {
+            eprintln!("Warning: could not check for files already on the server: {}", e);
+            HashMap::new()
+        }
+    };
+
     let mut success_count = 0;
+    let mut skipped_count = 0;
     let mut failure_count = 0;
     let total = media_files.len();
 
     for (i, path) in media_files.iter().enumerate() {
         let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        match checks.get(&path.display().to_string()) {
+            Some(UploadCheck::Duplicate { asset_id, is_trashed }) => {
+                skipped_count += 1;
+                let trashed = if *is_trashed { ", in trash" } else { "" };
+                println!("[{}/{}] Skipping {} (already on server as {}{})", i + 1, total, filename, asset_id, trashed);
+                continue;
+            }
+            Some(UploadCheck::Reject { reason }) => {
+                failure_count += 1;
+                println!("[{}/{}] Skipping {}: rejected by server ({})", i + 1, total, filename, reason);
+                continue;
+            }
+            Some(UploadCheck::Accept) | None => {}
+        }
         print!("[{}/{}] Uploading {}... ", i + 1, total, filename);
         std::io::stdout().flush()?;
 
-        match client.upload_asset(path).await {
+        // Backups are named `{asset_id}_{filename}`, with any sidecar next to them
+        let sidecar = sidecar_path(path);
+        let options = UploadOptions {
+            sidecar: sidecar.exists().then_some(sidecar),
+            ..Default::default()
+        };
+        let asset = filename.split_once('_').and_then(|(id, _)| recorded.get(id));
+
+        match client.upload_asset_with(path, &options).await {
+            Ok(response) if response.is_duplicate() => {
+                skipped_count += 1;
+                println!("already on server as {}", response.id);
+            }
             Ok(response) => {
                 success_count += 1;
-                if response.duplicate {
-                    println!("OK (duplicate detected)");
-                } else {
-                    println!("OK (id: {})", response.id);
+                match asset {
+                    Some(asset) if asset.gps.is_some() || asset.capture_time.is_some() || asset.description.is_some() => {
+                        let (latitude, longitude) = asset.gps.unzip();
+                        match client
+                            .update_asset_metadata(
+                                &response.id,
+                                latitude,
+                                longitude,
+                                asset.capture_time.as_deref(),
+                                asset.description.as_deref(),
+                            )
+                            .await
+                        {
+                            Ok(()) => println!("OK (id: {}, metadata restored)", response.id),
+                            Err(e) => println!("OK (id: {}), but metadata not restored: {}", response.id, e),
+                        }
+                    }
+                    _ => println!("OK (id: {})", response.id),
                 }
             }
             Err(e) => {
@@ -1173,7 +4009,10 @@ async fn run_restore(url: &str, api_key: &str, backup_dir: &PathBuf, dry_run: bo
     }
 
     println!();
-    println!("Restore complete: {} uploaded, {} failed", success_count, failure_count);
+    println!(
+        "Restore complete: {} uploaded, {} already on server, {} failed",
+        success_count, skipped_count, failure_count
+    );
 
     if failure_count > 0 {
         println!();
@@ -1292,11 +4131,11 @@ async fn run_letterbox_verify(url: &str, api_key: &str, analysis_json: &PathBuf,
                 AssetStatus {
                     asset_id: pair.keeper.id.clone(),
                     filename: pair.keeper.original_file_name.clone(),
-                    status: "present".to_string(),
+                    status: AssetState::Present,
                     error: None,
                 }
             }
-            Err(immich_lib::ImmichError::Api { status: 404, .. }) => {
+            Err(immich_lib::ImmichError::NotFound { .. }) => {
                 keepers_missing += 1;
                 anomalies.push(format!(
                     "CRITICAL: Keeper {} ({}) was deleted!",
@@ -1305,7 +4144,7 @@ async fn run_letterbox_verify(url: &str, api_key: &str, analysis_json: &PathBuf,
                 AssetStatus {
                     asset_id: pair.keeper.id.clone(),
                     filename: pair.keeper.original_file_name.clone(),
-                    status: "deleted".to_string(),
+                    status: AssetState::Deleted,
                     error: Some("Keeper was incorrectly deleted".to_string()),
                 }
             }
@@ -1318,7 +4157,7 @@ async fn run_letterbox_verify(url: &str, api_key: &str, analysis_json: &PathBuf,
                 AssetStatus {
                     asset_id: pair.keeper.id.clone(),
                     filename: pair.keeper.original_file_name.clone(),
-                    status: "error".to_string(),
+                    status: AssetState::Error,
                     error: Some(e.to_string()),
                 }
             }
@@ -1333,7 +4172,7 @@ async fn run_letterbox_verify(url: &str, api_key: &str, analysis_json: &PathBuf,
                     AssetStatus {
                         asset_id: pair.delete.id.clone(),
                         filename: pair.delete.original_file_name.clone(),
-                        status: "trashed".to_string(),
+                        status: AssetState::Trashed,
                         error: None,
                     }
                 } else {
@@ -1346,18 +4185,18 @@ async fn run_letterbox_verify(url: &str, api_key: &str, analysis_json: &PathBuf,
                     AssetStatus {
                         asset_id: pair.delete.id.clone(),
                         filename: pair.delete.original_file_name.clone(),
-                        status: "present".to_string(),
+                        status: AssetState::Present,
                         error: Some("Delete should have been removed".to_string()),
                     }
                 }
             }
-            Err(immich_lib::ImmichError::Api { status: 404, .. }) => {
+            Err(immich_lib::ImmichError::NotFound { .. }) => {
                 // Delete correctly removed (permanently deleted)
                 deletes_removed += 1;
                 AssetStatus {
                     asset_id: pair.delete.id.clone(),
                     filename: pair.delete.original_file_name.clone(),
-                    status: "deleted".to_string(),
+                    status: AssetState::Deleted,
                     error: None,
                 }
             }
@@ -1370,7 +4209,7 @@ async fn run_letterbox_verify(url: &str, api_key: &str, analysis_json: &PathBuf,
                 AssetStatus {
                     asset_id: pair.delete.id.clone(),
                     filename: pair.delete.original_file_name.clone(),
-                    status: "error".to_string(),
+                    status: AssetState::Error,
                     error: Some(e.to_string()),
                 }
             }
@@ -1479,15 +4318,31 @@ struct LetterboxPairResult {
     error: Option<String>,
 }
 
-async fn run_letterbox_execute(
-    url: &str,
-    api_key: &str,
-    input: &PathBuf,
-    backup_dir: &PathBuf,
+/// What `letterbox execute` was asked to do, besides which server to ask.
+struct LetterboxExecuteOptions<'a> {
+    /// Letterbox analysis to act on
+    input: &'a PathBuf,
+    /// Where the 16:9 copies are backed up
+    backup_dir: &'a PathBuf,
+    /// Delete permanently instead of trashing
     force: bool,
+    /// Requests per second
     rate_limit: u32,
+    /// Where deletions are recorded
+    ledger: &'a Ledger,
+    /// Skip the confirmation prompt
     yes: bool,
-) -> Result<()> {
+}
+
+async fn run_letterbox_execute(url: &str, api_key: &str, options: LetterboxExecuteOptions<'_>) -> Result<()> {
+    let LetterboxExecuteOptions {
+        input,
+        backup_dir,
+        force,
+        rate_limit,
+        ledger,
+        yes,
+    } = options;
     // Read and parse letterbox analysis JSON
     let file = File::open(input)
         .with_context(|| format!("Failed to open input file: {}", input.display()))?;
@@ -1515,6 +4370,7 @@ async fn run_letterbox_execute(
     }
     println!("Backup directory:     {}", backup_dir.display());
     println!("Force delete:         {}", if force { "yes (permanent)" } else { "no (trash)" });
+    println!("Deletion ledger:      {}", ledger.path().display());
     println!();
 
     // Confirmation prompt
@@ -1585,11 +4441,42 @@ async fn run_letterbox_execute(
             Ok(_) => {
                 downloaded_count += 1;
 
+                // Record the deletion in the ledger before making it
+                let entry = |action| LedgerEntry {
+                    recorded_at: Utc::now(),
+                    action,
+                    asset_id: delete_id.clone(),
+                    checksum: Some(pair.delete.checksum.clone()),
+                    filename: delete_filename.clone(),
+                    backup_path: Some(backup_path.clone()),
+                    group_id: format!("letterbox-{}", pair.timestamp),
+                    winner_id: pair.keeper.id.clone(),
+                    server_url: client.base_url().to_string(),
+                };
+                let action = if force { LedgerAction::Deleted } else { LedgerAction::Trashed };
+                if let Err(e) = ledger.append(&[entry(action)]) {
+                    failed_count += 1;
+                    results.push(LetterboxPairResult {
+                        timestamp: pair.timestamp.clone(),
+                        keeper_id: pair.keeper.id.clone(),
+                        delete_id: delete_id.clone(),
+                        download_status: "success".to_string(),
+                        delete_status: "skipped".to_string(),
+                        error: Some(format!("Could not write deletion ledger: {}", e)),
+                    });
+                    pb.inc(1);
+                    continue;
+                }
+
                 // Rate limit before delete
                 rate_limiter.until_ready().await;
 
                 // Step 2: Delete the asset (only if download succeeded)
                 let delete_result = client.delete_assets(std::slice::from_ref(delete_id), force).await;
+                if delete_result.is_err() {
+                    // Best effort: the failure is reported below either way
+                    let _ = ledger.append(&[entry(LedgerAction::DeleteFailed)]);
+                }
 
                 match delete_result {
                     Ok(_) => {
@@ -1657,7 +4544,7 @@ async fn run_letterbox_execute(
     };
 
     // Write execution report to backup directory
-    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
+    let timestamp = file_stamp();
     let report_path = backup_dir.join(format!("letterbox-execution-{}.json", timestamp));
     let report_file = File::create(&report_path)
         .with_context(|| format!("Failed to create report file: {}", report_path.display()))?;
@@ -0,0 +1,80 @@
+//! Human and machine-readable command output.
+//!
+//! With the global `--json` flag, commands that support it write their
+//! human-readable messages to stderr and JSON events to stdout, one object
+//! per line (`{"event": "...", ...}`), so stdout can be piped into `jq`.
+//! Without it, messages go to stdout and no events are written.
+
+use std::fmt::Display;
+use std::io::Write;
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+/// Write a human-readable line through an [`Output`], like `println!`.
+macro_rules! outln {
+    ($out:expr) => {
+        $out.line("")
+    };
+    ($out:expr, $($arg:tt)*) => {
+        $out.line(format!($($arg)*))
+    };
+}
+
+/// Where a command's output goes.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub fn new(json: bool) -> Self {
+        Self { json }
+    }
+
+    /// Write a human-readable line (to stderr in JSON mode).
+    pub fn line(&self, message: impl Display) {
+        if self.json {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    }
+
+    /// Write a human-readable prompt without a newline (to stderr in JSON mode).
+    pub fn prompt(&self, message: impl Display) -> Result<()> {
+        if self.json {
+            eprint!("{}", message);
+            std::io::stderr().flush()?;
+        } else {
+            print!("{}", message);
+            std::io::stdout().flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write a JSON event to stdout (JSON mode only).
+    ///
+    /// `fields` should be a JSON object; its fields follow `"event"`.
+    pub fn event(&self, event: &str, fields: Value) -> Result<()> {
+        if !self.json {
+            return Ok(());
+        }
+
+        let mut object = Map::new();
+        object.insert("event".to_string(), Value::String(event.to_string()));
+        match fields {
+            Value::Object(fields) => object.extend(fields),
+            Value::Null => {}
+            other => {
+                object.insert("data".to_string(), other);
+            }
+        }
+
+        let mut stdout = std::io::stdout().lock();
+        serde_json::to_writer(&mut stdout, &object)?;
+        writeln!(stdout)?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+//! Blocking wrapper around [`ImmichClient`].
+//!
+//! Scripts and GUI toolkits that can't host a tokio runtime can use
+//! [`ImmichBlockingClient`] instead: it owns a small runtime and runs each
+//! call of the async client to completion on it.
+//!
+//! The blocking client must not be used from inside an async runtime (e.g. a
+//! `#[tokio::main]` function); tokio panics if a runtime is blocked on from
+//! within another. Use [`ImmichClient`] there.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::io::AsyncWrite;
+use tokio::runtime::Runtime;
+
+use crate::client::{DeleteReport, ImmichClient, UploadCheck, UploadOptions, UploadResponse};
+use crate::error::Result;
+use crate::models::{
+    AlbumResponse, ApiKeyInfo, AssetResponse, AssetStatistics, BulkIdResponse, DuplicateGroup, JobStatus, MemoryResponse,
+    PersonResponse, ServerStatistics, ServerVersion, SharedLinkIndex, SharedLinkResponse, StackResponse, TagResponse, UserInfo,
+};
+use crate::thumbnails::ThumbnailSize;
+
+/// Synchronous Immich API client.
+///
+/// Has the same typed methods as [`ImmichClient`], each blocking the calling
+/// thread until the request completes. Clones share the runtime.
+///
+/// # Example
+///
+/// ```no_run
+/// use immich_lib::ImmichBlockingClient;
+///
+/// # fn example() -> immich_lib::Result<()> {
+/// let client = ImmichBlockingClient::new("https://immich.example.com", "your-api-key")?;
+/// let duplicates = client.get_duplicates()?;
+/// println!("Found {} duplicate groups", duplicates.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ImmichBlockingClient {
+    /// The async client every call is delegated to
+    inner: ImmichClient,
+    /// Runtime the async calls are driven on
+    runtime: Arc<Runtime>,
+}
+
+impl ImmichBlockingClient {
+    /// Creates a blocking client with the given base URL and API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if [`ImmichClient::new`] fails or the runtime can't
+    /// be started.
+    pub fn new(base_url: &str, api_key: &str) -> Result<Self> {
+        Self::from_client(ImmichClient::new(base_url, api_key)?)
+    }
+
+    /// Wraps an existing async client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runtime can't be started.
+    pub fn from_client(client: ImmichClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(Self {
+            inner: client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The wrapped async client.
+    pub fn inner(&self) -> &ImmichClient {
+        &self.inner
+    }
+
+    /// Base URL of the Immich server.
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    /// See [`ImmichClient::get_duplicates`].
+    pub fn get_duplicates(&self) -> Result<Vec<DuplicateGroup>> {
+        self.runtime.block_on(self.inner.get_duplicates())
+    }
+
+    /// See [`ImmichClient::get_all_assets`].
+    pub fn get_all_assets(&self) -> Result<Vec<AssetResponse>> {
+        self.runtime.block_on(self.inner.get_all_assets())
+    }
+
+    /// See [`ImmichClient::get_tagged_assets`].
+    pub fn get_tagged_assets(&self, tag_id: &str) -> Result<Vec<AssetResponse>> {
+        self.runtime.block_on(self.inner.get_tagged_assets(tag_id))
+    }
+
+    /// See [`ImmichClient::get_asset`].
+    pub fn get_asset(&self, asset_id: &str) -> Result<AssetResponse> {
+        self.runtime.block_on(self.inner.get_asset(asset_id))
+    }
+
+    /// See [`ImmichClient::get_assets`].
+    pub fn get_assets(&self, asset_ids: &[String]) -> Result<Vec<AssetResponse>> {
+        self.runtime.block_on(self.inner.get_assets(asset_ids))
+    }
+
+    /// See [`ImmichClient::get_asset_albums`].
+    pub fn get_asset_albums(&self, asset_id: &str) -> Result<Vec<AlbumResponse>> {
+        self.runtime.block_on(self.inner.get_asset_albums(asset_id))
+    }
+
+    /// See [`ImmichClient::get_albums`].
+    pub fn get_albums(&self) -> Result<Vec<AlbumResponse>> {
+        self.runtime.block_on(self.inner.get_albums())
+    }
+
+    /// See [`ImmichClient::create_album`].
+    pub fn create_album(&self, name: &str, asset_ids: &[String]) -> Result<AlbumResponse> {
+        self.runtime.block_on(self.inner.create_album(name, asset_ids))
+    }
+
+    /// See [`ImmichClient::get_album`].
+    pub fn get_album(&self, album_id: &str) -> Result<AlbumResponse> {
+        self.runtime.block_on(self.inner.get_album(album_id))
+    }
+
+    /// See [`ImmichClient::get_shared_links`].
+    pub fn get_shared_links(&self) -> Result<Vec<SharedLinkResponse>> {
+        self.runtime.block_on(self.inner.get_shared_links())
+    }
+
+    /// See [`ImmichClient::get_asset_shared_links`].
+    pub fn get_asset_shared_links(&self, asset_id: &str) -> Result<Vec<SharedLinkResponse>> {
+        self.runtime.block_on(self.inner.get_asset_shared_links(asset_id))
+    }
+
+    /// See [`ImmichClient::shared_link_index`].
+    pub fn shared_link_index(&self) -> Result<SharedLinkIndex> {
+        self.runtime.block_on(self.inner.shared_link_index())
+    }
+
+    /// See [`ImmichClient::add_shared_link_assets`].
+    pub fn add_shared_link_assets(&self, link_id: &str, asset_ids: &[String]) -> Result<()> {
+        self.runtime.block_on(self.inner.add_shared_link_assets(link_id, asset_ids))
+    }
+
+    /// See [`ImmichClient::get_memories`].
+    pub fn get_memories(&self) -> Result<Vec<MemoryResponse>> {
+        self.runtime.block_on(self.inner.get_memories())
+    }
+
+    /// See [`ImmichClient::add_memory_assets`].
+    pub fn add_memory_assets(&self, memory_id: &str, asset_ids: &[String]) -> Result<()> {
+        self.runtime.block_on(self.inner.add_memory_assets(memory_id, asset_ids))
+    }
+
+    /// See [`ImmichClient::add_assets_to_album`].
+    pub fn add_assets_to_album(&self, album_id: &str, asset_ids: &[String]) -> Result<()> {
+        self.runtime.block_on(self.inner.add_assets_to_album(album_id, asset_ids))
+    }
+
+    /// See [`ImmichClient::remove_assets_from_album`].
+    pub fn remove_assets_from_album(&self, album_id: &str, asset_ids: &[String]) -> Result<()> {
+        self.runtime.block_on(self.inner.remove_assets_from_album(album_id, asset_ids))
+    }
+
+    /// See [`ImmichClient::add_assets_to_album_with_results`].
+    pub fn add_assets_to_album_with_results(&self, album_id: &str, asset_ids: &[String]) -> Result<Vec<BulkIdResponse>> {
+        self.runtime.block_on(self.inner.add_assets_to_album_with_results(album_id, asset_ids))
+    }
+
+    /// See [`ImmichClient::remove_assets_from_album_with_results`].
+    pub fn remove_assets_from_album_with_results(
+        &self,
+        album_id: &str,
+        asset_ids: &[String],
+    ) -> Result<Vec<BulkIdResponse>> {
+        self.runtime.block_on(self.inner.remove_assets_from_album_with_results(album_id, asset_ids))
+    }
+
+    /// See [`ImmichClient::set_album_cover`].
+    pub fn set_album_cover(&self, album_id: &str, asset_id: &str) -> Result<AlbumResponse> {
+        self.runtime.block_on(self.inner.set_album_cover(album_id, asset_id))
+    }
+
+    /// See [`ImmichClient::get_tags`].
+    pub fn get_tags(&self) -> Result<Vec<TagResponse>> {
+        self.runtime.block_on(self.inner.get_tags())
+    }
+
+    /// See [`ImmichClient::upsert_tags`].
+    pub fn upsert_tags(&self, values: &[String]) -> Result<Vec<TagResponse>> {
+        self.runtime.block_on(self.inner.upsert_tags(values))
+    }
+
+    /// See [`ImmichClient::tag_assets`].
+    pub fn tag_assets(&self, tag_id: &str, asset_ids: &[String]) -> Result<()> {
+        self.runtime.block_on(self.inner.tag_assets(tag_id, asset_ids))
+    }
+
+    /// See [`ImmichClient::get_tag`].
+    pub fn get_tag(&self, tag_id: &str) -> Result<TagResponse> {
+        self.runtime.block_on(self.inner.get_tag(tag_id))
+    }
+
+    /// See [`ImmichClient::get_people`].
+    pub fn get_people(&self) -> Result<Vec<PersonResponse>> {
+        self.runtime.block_on(self.inner.get_people())
+    }
+
+    /// See [`ImmichClient::get_person`].
+    pub fn get_person(&self, person_id: &str) -> Result<PersonResponse> {
+        self.runtime.block_on(self.inner.get_person(person_id))
+    }
+
+    /// See [`ImmichClient::get_stacks`].
+    pub fn get_stacks(&self, primary_asset_id: Option<&str>) -> Result<Vec<StackResponse>> {
+        self.runtime.block_on(self.inner.get_stacks(primary_asset_id))
+    }
+
+    /// See [`ImmichClient::get_stack`].
+    pub fn get_stack(&self, stack_id: &str) -> Result<StackResponse> {
+        self.runtime.block_on(self.inner.get_stack(stack_id))
+    }
+
+    /// See [`ImmichClient::create_stack`].
+    pub fn create_stack(&self, asset_ids: &[String]) -> Result<StackResponse> {
+        self.runtime.block_on(self.inner.create_stack(asset_ids))
+    }
+
+    /// See [`ImmichClient::delete_stack`].
+    pub fn delete_stack(&self, stack_id: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.delete_stack(stack_id))
+    }
+
+    /// See [`ImmichClient::get_server_version`].
+    pub fn get_server_version(&self) -> Result<ServerVersion> {
+        self.runtime.block_on(self.inner.get_server_version())
+    }
+
+    /// See [`ImmichClient::get_my_user`].
+    pub fn get_my_user(&self) -> Result<UserInfo> {
+        self.runtime.block_on(self.inner.get_my_user())
+    }
+
+    /// See [`ImmichClient::get_my_api_key`].
+    pub fn get_my_api_key(&self) -> Result<ApiKeyInfo> {
+        self.runtime.block_on(self.inner.get_my_api_key())
+    }
+
+    /// See [`ImmichClient::get_job_statuses`].
+    pub fn get_job_statuses(&self) -> Result<HashMap<String, JobStatus>> {
+        self.runtime.block_on(self.inner.get_job_statuses())
+    }
+
+    /// See [`ImmichClient::get_server_statistics`].
+    pub fn get_server_statistics(&self) -> Result<ServerStatistics> {
+        self.runtime.block_on(self.inner.get_server_statistics())
+    }
+
+    /// See [`ImmichClient::get_asset_statistics`].
+    pub fn get_asset_statistics(&self, is_trashed: bool) -> Result<AssetStatistics> {
+        self.runtime.block_on(self.inner.get_asset_statistics(is_trashed))
+    }
+
+    /// See [`ImmichClient::download_asset`].
+    pub fn download_asset(&self, asset_id: &str, path: &Path) -> Result<u64> {
+        self.runtime.block_on(self.inner.download_asset(asset_id, path))
+    }
+
+    /// Streams an asset's original file into a [`std::io::Write`].
+    ///
+    /// See [`ImmichClient::download_asset_to`].
+    pub fn download_asset_to<W>(&self, asset_id: &str, writer: &mut W) -> Result<u64>
+    where
+        W: Write + ?Sized,
+    {
+        self.runtime
+            .block_on(self.inner.download_asset_to(asset_id, &mut SyncWriter(writer)))
+    }
+
+    /// See [`ImmichClient::download_thumbnail`].
+    pub fn download_thumbnail(&self, asset_id: &str, size: ThumbnailSize) -> Result<Vec<u8>> {
+        self.runtime.block_on(self.inner.download_thumbnail(asset_id, size))
+    }
+
+    /// See [`ImmichClient::restore_from_trash`].
+    pub fn restore_from_trash(&self, asset_ids: &[String]) -> Result<()> {
+        self.runtime.block_on(self.inner.restore_from_trash(asset_ids))
+    }
+
+    /// See [`ImmichClient::delete_assets`].
+    pub fn delete_assets(&self, asset_ids: &[String], force: bool) -> Result<()> {
+        self.runtime.block_on(self.inner.delete_assets(asset_ids, force))
+    }
+
+    /// See [`ImmichClient::delete_assets_chunked`].
+    pub fn delete_assets_chunked(&self, asset_ids: &[String], force: bool, chunk_size: usize) -> DeleteReport {
+        self.runtime.block_on(self.inner.delete_assets_chunked(asset_ids, force, chunk_size))
+    }
+
+    /// See [`ImmichClient::update_asset_metadata`].
+    pub fn update_asset_metadata(
+        &self,
+        asset_id: &str,
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        date_time_original: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<()> {
+        self.runtime.block_on(self.inner.update_asset_metadata(
+            asset_id,
+            latitude,
+            longitude,
+            date_time_original,
+            description,
+        ))
+    }
+
+    /// See [`ImmichClient::update_assets_bulk`].
+    pub fn update_assets_bulk(
+        &self,
+        asset_ids: &[String],
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        date_time_original: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<()> {
+        self.runtime.block_on(self.inner.update_assets_bulk(
+            asset_ids,
+            latitude,
+            longitude,
+            date_time_original,
+            description,
+        ))
+    }
+
+    /// See [`ImmichClient::check_bulk_upload`].
+    pub fn check_bulk_upload(&self, checksums: &[(String, String)]) -> Result<HashMap<String, UploadCheck>> {
+        self.runtime.block_on(self.inner.check_bulk_upload(checksums))
+    }
+
+    /// See [`ImmichClient::upload_asset`].
+    pub fn upload_asset(&self, file_path: &Path) -> Result<UploadResponse> {
+        self.runtime.block_on(self.inner.upload_asset(file_path))
+    }
+
+    /// See [`ImmichClient::upload_asset_with`].
+    pub fn upload_asset_with(&self, file_path: &Path, options: &UploadOptions) -> Result<UploadResponse> {
+        self.runtime.block_on(self.inner.upload_asset_with(file_path, options))
+    }
+}
+
+/// Presents a blocking writer as an [`AsyncWrite`].
+///
+/// Writes complete immediately, which is fine on the blocking client's own
+/// runtime since nothing else runs on it.
+struct SyncWriter<'a, W: ?Sized>(&'a mut W);
+
+impl<W: Write + ?Sized> AsyncWrite for SyncWriter<'_, W> {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(self.get_mut().0.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(self.get_mut().0.flush())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[test]
+    fn test_blocking_client_matches_async() {
+        // The mock server needs a runtime of its own, outside the blocking client's
+        let server_runtime = tokio::runtime::Runtime::new().unwrap();
+        let groups = synthetic_groups(2, 2).unwrap();
+        let server = server_runtime.block_on(MockServer::start(&groups)).unwrap();
+
+        let client = ImmichBlockingClient::new(&server.url(), "test-key").unwrap();
+        let duplicates = client.get_duplicates().unwrap();
+        assert_eq!(duplicates.len(), 2);
+
+        let asset_id = &duplicates[0].assets[0].id;
+        assert_eq!(client.get_asset(asset_id).unwrap().id, *asset_id);
+
+        let mut buffer = Vec::new();
+        let written = client.download_asset_to(asset_id, &mut buffer).unwrap();
+        assert_eq!(written, buffer.len() as u64);
+        assert!(written > 0);
+
+        assert!(client.get_asset("missing").is_err());
+    }
+}
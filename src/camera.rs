@@ -0,0 +1,128 @@
+//! Camera make/model normalization.
+//!
+//! Different tools write the same camera in different ways, e.g.
+//! `NIKON CORPORATION` / `NIKON D750` vs `Nikon` / `D750`. Normalizing to a
+//! canonical `make model` key stops those cosmetic differences from being
+//! flagged as camera conflicts.
+
+/// Known manufacturer spellings mapped to a canonical make.
+///
+/// Matched against the lowercased make after trimming.
+const MAKE_ALIASES: &[(&str, &str)] = &[
+    ("nikon corporation", "nikon"),
+    ("canon inc.", "canon"),
+    ("eastman kodak company", "kodak"),
+    ("fujifilm corporation", "fujifilm"),
+    ("fuji photo film co., ltd.", "fujifilm"),
+    ("olympus imaging corp.", "olympus"),
+    ("olympus corporation", "olympus"),
+    ("olympus optical co.,ltd", "olympus"),
+    ("om digital solutions", "olympus"),
+    ("ricoh imaging company, ltd.", "ricoh"),
+    ("pentax corporation", "pentax"),
+    ("samsung techwin", "samsung"),
+    ("samsung electronics", "samsung"),
+    ("sony ericsson", "sony"),
+    ("minolta co., ltd.", "minolta"),
+    ("konica minolta camera, inc.", "konica minolta"),
+    ("leica camera ag", "leica"),
+    ("hasselblad", "hasselblad"),
+    ("lg electronics", "lg"),
+    ("motorola mobility", "motorola"),
+    ("hmd global", "nokia"),
+    ("xiaomi communications co., ltd.", "xiaomi"),
+];
+
+/// Corporate suffixes removed from makes not in the alias table.
+const MAKE_SUFFIXES: &[&str] = &[
+    " corporation",
+    " corp.",
+    " corp",
+    " co., ltd.",
+    " co.,ltd.",
+    " co., ltd",
+    " ltd.",
+    " inc.",
+    " inc",
+    " company",
+    " gmbh",
+    " ag",
+];
+
+/// Normalize a camera make to its canonical lowercase form.
+pub fn normalize_make(make: &str) -> String {
+    let make = collapse_whitespace(&make.to_lowercase());
+
+    if let Some((_, canonical)) = MAKE_ALIASES.iter().find(|(alias, _)| *alias == make) {
+        return canonical.to_string();
+    }
+
+    MAKE_SUFFIXES
+        .iter()
+        .find_map(|suffix| make.strip_suffix(suffix))
+        .map(|stripped| stripped.trim().to_string())
+        .unwrap_or(make)
+}
+
+/// Build a canonical `make model` key for conflict comparison.
+///
+/// Case is folded, whitespace collapsed, make aliases resolved, and a model
+/// that repeats the make (`NIKON D750`) has the make stripped. Returns `None`
+/// if neither make nor model is set.
+pub fn normalize_camera(make: Option<&str>, model: Option<&str>) -> Option<String> {
+    let make = make.map(normalize_make).filter(|m| !m.is_empty());
+    let mut model = model
+        .map(|m| collapse_whitespace(&m.to_lowercase()))
+        .filter(|m| !m.is_empty());
+
+    // Strip a leading make from the model ("nikon d750" -> "d750")
+    if let (Some(make), Some(m)) = (&make, &model)
+        && let Some(rest) = m.strip_prefix(make.as_str())
+        && (rest.is_empty() || rest.starts_with(' '))
+    {
+        model = Some(rest.trim().to_string()).filter(|r| !r.is_empty());
+    }
+
+    match (make, model) {
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (Some(only), None) | (None, Some(only)) => Some(only),
+        (None, None) => None,
+    }
+}
+
+/// Trim and collapse runs of whitespace to single spaces.
+fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nikon_variants_match() {
+        let a = normalize_camera(Some("NIKON CORPORATION"), Some("NIKON D750"));
+        let b = normalize_camera(Some("Nikon"), Some("D750"));
+        assert_eq!(a.as_deref(), Some("nikon d750"));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_suffix_stripping_and_distinct_models() {
+        assert_eq!(normalize_make("Canon Inc."), "canon");
+        assert_eq!(normalize_make("Acme Corp"), "acme");
+        assert_ne!(
+            normalize_camera(Some("Canon"), Some("Canon EOS 5D")),
+            normalize_camera(Some("Canon"), Some("Canon EOS 6D"))
+        );
+    }
+
+    #[test]
+    fn test_make_prefix_only_stripped_on_word_boundary() {
+        assert_eq!(
+            normalize_camera(Some("LG"), Some("LGM-V300")).as_deref(),
+            Some("lg lgm-v300")
+        );
+        assert_eq!(normalize_camera(None, None), None);
+    }
+}
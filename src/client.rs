@@ -6,13 +6,18 @@ use reqwest::header::{HeaderMap, HeaderValue, InvalidHeaderValue};
 use reqwest::multipart::{Form, Part};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use url::Url;
 
 use crate::error::{ImmichError, Result};
-use crate::models::{AssetResponse, DuplicateGroup};
+use crate::models::{
+    AlbumResponse, ApiKeyInfo, AssetResponse, AssetStatistics, BulkIdError, BulkIdResponse, DuplicateGroup, JobStatus, MemoryResponse,
+    PersonResponse, ServerStatistics, ServerVersion, SharedLinkIndex, SharedLinkResponse, StackResponse, TagResponse, UserInfo,
+};
+use crate::thumbnails::ThumbnailSize;
 
 /// Response from the Immich upload endpoint.
 #[derive(Debug, Clone, Deserialize)]
@@ -20,9 +25,334 @@ use crate::models::{AssetResponse, DuplicateGroup};
 pub struct UploadResponse {
     /// The ID of the newly created asset
     pub id: String,
-    /// Whether this was a duplicate of an existing asset
+    /// Whether this was a duplicate of an existing asset (older servers)
     #[serde(default)]
     pub duplicate: bool,
+    /// What the server did with the file (newer servers)
+    #[serde(default)]
+    pub status: Option<UploadStatus>,
+}
+
+impl UploadResponse {
+    /// Returns true if the server already had the file, in which case `id`
+    /// is the existing asset and nothing new was imported.
+    pub fn is_duplicate(&self) -> bool {
+        self.duplicate || self.status == Some(UploadStatus::Duplicate)
+    }
+}
+
+/// What the upload endpoint did with a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadStatus {
+    /// A new asset was created
+    Created,
+    /// An existing asset's file was replaced
+    Replaced,
+    /// The server already had the file
+    Duplicate,
+}
+
+/// The server's answer for one file of [`ImmichClient::check_bulk_upload`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadCheck {
+    /// The server doesn't have the file; upload it
+    Accept,
+    /// The server already has the file (same checksum)
+    Duplicate {
+        /// The existing asset
+        asset_id: String,
+        /// Whether the existing asset is in the trash
+        is_trashed: bool,
+    },
+    /// The server would refuse the file for another reason
+    Reject {
+        /// Reason given by the server (e.g. `unsupported-format`)
+        reason: String,
+    },
+}
+
+/// Assets [`ImmichClient::get_assets`] fetches at once.
+const ASSET_FETCH_CONCURRENCY: usize = 8;
+
+/// Files sent per `POST /api/assets/bulk-upload-check` request.
+const UPLOAD_CHECK_BATCH_SIZE: usize = 1000;
+
+/// Assets [`ImmichClient::delete_assets`] sends per request, staying well
+/// under the request size the server and proxies in front of it accept.
+pub const DEFAULT_DELETE_CHUNK_SIZE: usize = 250;
+
+/// Attempts made at uploading a file before a transient failure is returned.
+const MAX_UPLOAD_ATTEMPTS: u32 = 3;
+
+/// Wait before re-sending a failed upload, multiplied by the attempt number
+/// (unless the server asked for a specific delay).
+const UPLOAD_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Bytes read at a time when checksumming a file.
+const CHECKSUM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Slowest upload rate, in bytes per second, an upload is given time for
+/// before it times out.
+const MIN_UPLOAD_RATE: u64 = 256 * 1024;
+
+/// Timeout for uploading a file of `len` bytes: the client's usual 30 seconds
+/// plus the time the file takes at [`MIN_UPLOAD_RATE`].
+fn upload_timeout(len: u64) -> Duration {
+    Duration::from_secs(30 + len / MIN_UPLOAD_RATE)
+}
+
+/// Returns true if an upload failed in a way worth retrying: the connection
+/// dropped or timed out, or the server was overloaded or failed.
+fn is_transient(error: &ImmichError) -> bool {
+    match error {
+        ImmichError::Http(e) => e.is_connect() || e.is_timeout() || e.is_request() || e.is_body(),
+        ImmichError::RateLimited { .. } => true,
+        ImmichError::Api { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+/// Hex-encoded SHA-1 of a file, the checksum Immich identifies uploads by.
+///
+/// The file is read in chunks, so large videos aren't held in memory.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub async fn file_checksum(path: &Path) -> Result<String> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut context = ring::digest::Context::new(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY);
+    let mut buf = vec![0u8; CHECKSUM_CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buf[..read]);
+    }
+    Ok(context.finish().as_ref().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Outcome of [`ImmichClient::delete_assets_chunked`].
+#[derive(Debug, Default)]
+pub struct DeleteReport {
+    /// Assets asked to be deleted
+    pub requested: usize,
+    /// Assets in the chunks that were deleted, in request order
+    pub deleted: Vec<String>,
+    /// Chunks the server refused or that failed to send
+    pub failed: Vec<FailedDelete>,
+}
+
+/// Delete `asset_ids` `chunk_size` at a time with `delete`, one call per
+/// chunk, carrying on past failed chunks.
+pub(crate) async fn delete_in_chunks<'a, F, Fut>(asset_ids: &'a [String], chunk_size: usize, mut delete: F) -> DeleteReport
+where
+    F: FnMut(&'a [String]) -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut report = DeleteReport {
+        requested: asset_ids.len(),
+        ..Default::default()
+    };
+    for chunk in asset_ids.chunks(chunk_size.max(1)) {
+        match delete(chunk).await {
+            Ok(()) => report.deleted.extend_from_slice(chunk),
+            Err(error) => report.failed.push(FailedDelete {
+                asset_ids: chunk.to_vec(),
+                error,
+            }),
+        }
+    }
+    report
+}
+
+/// A chunk of assets [`ImmichClient::delete_assets_chunked`] couldn't delete.
+#[derive(Debug)]
+pub struct FailedDelete {
+    /// Assets in the chunk
+    pub asset_ids: Vec<String>,
+    /// Why the request failed
+    pub error: ImmichError,
+}
+
+impl DeleteReport {
+    /// Returns true if every chunk was deleted.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// The report as a result: the failed request's own error if there was
+    /// only one request, or [`ImmichError::PartialDelete`] summarizing every
+    /// failed chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any chunk failed.
+    pub fn into_result(mut self) -> Result<()> {
+        if self.failed.is_empty() {
+            return Ok(());
+        }
+        if self.failed.len() == 1 && self.deleted.is_empty() {
+            return Err(self.failed.remove(0).error);
+        }
+        let errors: Vec<String> = self
+            .failed
+            .iter()
+            .map(|f| format!("{} assets from {}: {}", f.asset_ids.len(), f.asset_ids[0], f.error))
+            .collect();
+        Err(ImmichError::PartialDelete {
+            deleted: self.deleted.len(),
+            requested: self.requested,
+            errors: errors.join("; "),
+        })
+    }
+}
+
+/// Optional extras for [`ImmichClient::upload_asset_with`].
+#[derive(Debug, Clone, Default)]
+pub struct UploadOptions {
+    /// Mark the new asset as a favorite
+    pub is_favorite: Option<bool>,
+    /// XMP sidecar to upload with the asset
+    pub sidecar: Option<PathBuf>,
+    /// ID of an already uploaded video to link as the live photo companion
+    pub live_photo_video_id: Option<String>,
+}
+
+/// Turn failed bulk results into an error.
+///
+/// If every failure is an asset that was already present the error is
+/// [`ImmichError::AlreadyInAlbum`]; otherwise the remaining failures decide
+/// the error kind.
+fn check_bulk_results(results: &[BulkIdResponse]) -> Result<()> {
+    let (duplicates, failed): (Vec<&BulkIdResponse>, Vec<&BulkIdResponse>) = results
+        .iter()
+        .filter(|r| !r.success)
+        .partition(|r| r.error == Some(BulkIdError::Duplicate));
+
+    if failed.is_empty() {
+        if duplicates.is_empty() {
+            return Ok(());
+        }
+        return Err(ImmichError::AlreadyInAlbum {
+            asset_ids: duplicates.iter().map(|r| r.id.clone()).collect(),
+        });
+    }
+
+    let message = format!(
+        "Bulk operation failed for {}",
+        failed
+            .iter()
+            .map(|r| format!("{} ({})", r.id, r.error.unwrap_or(BulkIdError::Unknown)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let all = |reason: BulkIdError| failed.iter().all(|r| r.error == Some(reason));
+    Err(if all(BulkIdError::NotFound) {
+        ImmichError::NotFound { message }
+    } else if all(BulkIdError::NoPermission) {
+        ImmichError::Unauthorized { status: 403, message }
+    } else {
+        ImmichError::ValidationFailed { message }
+    })
+}
+
+/// Treat assets that were already present as success.
+fn ignore_already_present(result: Result<()>) -> Result<()> {
+    match result {
+        Err(ImmichError::AlreadyInAlbum { .. }) => Ok(()),
+        other => other,
+    }
+}
+
+/// Body of an Immich error response.
+///
+/// `message` is a string, or a list of strings for validation errors.
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    message: ErrorMessage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ErrorMessage {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The message of an error response body, or the body itself if it isn't
+/// Immich's JSON error shape.
+fn error_message(body: &str) -> String {
+    match serde_json::from_str::<ErrorBody>(body) {
+        Ok(ErrorBody { message: ErrorMessage::One(message) }) => message,
+        Ok(ErrorBody { message: ErrorMessage::Many(messages) }) => messages.join("; "),
+        Err(_) => body.to_string(),
+    }
+}
+
+/// Turn an unsuccessful response into an error.
+///
+/// Statuses callers commonly branch on get their own variant:
+/// [`ImmichError::NotFound`], [`ImmichError::Unauthorized`],
+/// [`ImmichError::ValidationFailed`] and [`ImmichError::RateLimited`], the
+/// last carrying the server's `Retry-After` delay if it sent one. Anything
+/// else is [`ImmichError::Api`].
+async fn api_error(response: reqwest::Response) -> ImmichError {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, Utc::now()));
+        return ImmichError::RateLimited { retry_after };
+    }
+    let body = response.text().await.unwrap_or_default();
+    classify_error(status.as_u16(), &body)
+}
+
+/// Copy a response body into a writer chunk by chunk, returning the bytes written.
+async fn stream_to<W>(response: reqwest::Response, writer: &mut W) -> Result<u64>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut stream = response.bytes_stream();
+    let mut bytes_written: u64 = 0;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        writer.write_all(&chunk).await?;
+        bytes_written += chunk.len() as u64;
+    }
+
+    writer.flush().await?;
+    Ok(bytes_written)
+}
+
+/// The error for a response with `status` and `body`.
+fn classify_error(status: u16, body: &str) -> ImmichError {
+    let message = error_message(body);
+    match status {
+        401 | 403 => ImmichError::Unauthorized { status, message },
+        404 => ImmichError::NotFound { message },
+        400 | 422 => ImmichError::ValidationFailed { message },
+        _ => ImmichError::Api { status, message },
+    }
+}
+
+/// Parse a `Retry-After` value: delay seconds, or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means "now"
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
 }
 
 /// Client for interacting with the Immich REST API.
@@ -41,7 +371,7 @@ pub struct UploadResponse {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ImmichClient {
     /// HTTP client with default headers (API key) configured
     client: reqwest::Client,
@@ -49,6 +379,16 @@ pub struct ImmichClient {
     base_url: Url,
 }
 
+/// Shows the server only; the API key lives in the HTTP client's headers
+/// and is never printed.
+impl std::fmt::Debug for ImmichClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImmichClient")
+            .field("base_url", &self.base_url.as_str())
+            .finish_non_exhaustive()
+    }
+}
+
 impl ImmichClient {
     /// Creates a new ImmichClient with the given base URL and API key.
     ///
@@ -74,9 +414,10 @@ impl ImmichClient {
 
         // Build default headers with API key
         let mut headers = HeaderMap::new();
-        let header_value = HeaderValue::from_str(api_key).map_err(|_: InvalidHeaderValue| {
+        let mut header_value = HeaderValue::from_str(api_key).map_err(|_: InvalidHeaderValue| {
             ImmichError::InvalidApiKey
         })?;
+        header_value.set_sensitive(true);
         headers.insert("x-api-key", header_value);
 
         // Build HTTP client with defaults
@@ -88,6 +429,11 @@ impl ImmichClient {
         Ok(Self { client, base_url })
     }
 
+    /// Base URL of the Immich server.
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_str()
+    }
+
     /// Fetches all duplicate groups from the Immich server.
     ///
     /// # Returns
@@ -123,6 +469,22 @@ impl ImmichClient {
     /// - The server returns an error response (401 unauthorized, etc.)
     /// - The response cannot be parsed as JSON
     pub async fn get_all_assets(&self) -> Result<Vec<AssetResponse>> {
+        self.search_assets(serde_json::json!({})).await
+    }
+
+    /// Fetches every non-trashed asset carrying a tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails, the server returns an error
+    /// response, or the response cannot be parsed as JSON.
+    pub async fn get_tagged_assets(&self, tag_id: &str) -> Result<Vec<AssetResponse>> {
+        self.search_assets(serde_json::json!({ "tagIds": [tag_id] })).await
+    }
+
+    /// Pages through `POST /search/metadata` with the given filter, dropping
+    /// trashed assets.
+    async fn search_assets(&self, filter: serde_json::Value) -> Result<Vec<AssetResponse>> {
         const PAGE_SIZE: usize = 1000;
         let mut all_assets = Vec::new();
         let mut page: usize = 1;
@@ -143,11 +505,14 @@ impl ImmichClient {
         let url = self.base_url.join("/api/search/metadata")?;
 
         loop {
-            let body = serde_json::json!({
+            let mut body = serde_json::json!({
                 "page": page,
                 "size": PAGE_SIZE,
                 "withExif": true
             });
+            if let (Some(body), Some(filter)) = (body.as_object_mut(), filter.as_object()) {
+                body.extend(filter.clone());
+            }
 
             let response = self.client.post(url.clone()).json(&body).send().await?;
             let search_result: SearchResponse = self.handle_response(response).await?;
@@ -198,177 +563,952 @@ impl ImmichClient {
         self.handle_response(response).await
     }
 
-    /// Downloads an asset's original file to the specified path.
+    /// Fetches several assets by ID, with their EXIF metadata.
     ///
-    /// Uses streaming to avoid buffering the entire file in memory,
-    /// making it suitable for large files.
+    /// Immich has no bulk read endpoint, so the assets are fetched
+    /// concurrently, a few at a time. Assets the server doesn't have (404)
+    /// are left out; the rest are returned in the order asked for.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `asset_id` - The ID of the asset to download
-    /// * `path` - The destination path to save the file
+    /// Returns the first error other than a missing asset.
+    pub async fn get_assets(&self, asset_ids: &[String]) -> Result<Vec<AssetResponse>> {
+        let results: Vec<Result<AssetResponse>> = futures::stream::iter(asset_ids)
+            .map(|id| self.get_asset(id))
+            .buffered(ASSET_FETCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut assets = Vec::with_capacity(results.len());
+        for result in results {
+            match result {
+                Ok(asset) => assets.push(asset),
+                Err(ImmichError::NotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(assets)
+    }
+
+    /// Fetches the albums that contain an asset.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// The total number of bytes written to the file.
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_asset_albums(&self, asset_id: &str) -> Result<Vec<AlbumResponse>> {
+        let mut url = self.base_url.join("/api/albums")?;
+        url.query_pairs_mut().append_pair("assetId", asset_id);
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches every album the current user owns or has been shared.
+    ///
+    /// Album members are not included; use [`get_album`](Self::get_album).
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The HTTP request fails
-    /// - The server returns an error response
-    /// - The file cannot be created or written to
-    pub async fn download_asset(&self, asset_id: &str, path: &Path) -> Result<u64> {
-        let url = self
-            .base_url
-            .join(&format!("/api/assets/{}/original", asset_id))?;
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_albums(&self) -> Result<Vec<AlbumResponse>> {
+        let url = self.base_url.join("/api/albums")?;
         let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(ImmichError::Api {
-                status: status.as_u16(),
-                message: body,
-            });
-        }
-
-        let mut file = tokio::fs::File::create(path).await?;
-        let mut stream = response.bytes_stream();
-        let mut bytes_written: u64 = 0;
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            file.write_all(&chunk).await?;
-            bytes_written += chunk.len() as u64;
-        }
-
-        file.flush().await?;
-        Ok(bytes_written)
+    /// Creates an album containing the given assets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn create_album(&self, name: &str, asset_ids: &[String]) -> Result<AlbumResponse> {
+        let url = self.base_url.join("/api/albums")?;
+        let response = self
+            .client
+            .post(url)
+            .json(&serde_json::json!({ "albumName": name, "assetIds": asset_ids }))
+            .send()
+            .await?;
+        self.handle_response(response).await
     }
 
-    /// Deletes multiple assets in a single API call.
+    /// Fetches an album with its member assets.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `asset_ids` - The IDs of the assets to delete
-    /// * `force` - If true, permanently delete; if false, move to trash
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_album(&self, album_id: &str) -> Result<AlbumResponse> {
+        let url = self.base_url.join(&format!("/api/albums/{}", album_id))?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches the current user's shared links.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The HTTP request fails
-    /// - The server returns an error response
-    pub async fn delete_assets(&self, asset_ids: &[String], force: bool) -> Result<()> {
-        #[derive(Serialize)]
-        struct DeleteRequest<'a> {
-            ids: &'a [String],
-            force: bool,
-        }
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_shared_links(&self) -> Result<Vec<SharedLinkResponse>> {
+        let url = self.base_url.join("/api/shared-links")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
 
-        let url = self.base_url.join("/api/assets")?;
-        let body = DeleteRequest {
-            ids: asset_ids,
-            force,
+    /// Fetches the shared links through which an asset is shared: links
+    /// sharing it individually, and links sharing an album it is in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_asset_shared_links(&self, asset_id: &str) -> Result<Vec<SharedLinkResponse>> {
+        let links = self.get_shared_links().await?;
+        let albums = if links.iter().any(|l| l.album.is_some()) {
+            self.get_asset_albums(asset_id).await?
+        } else {
+            Vec::new()
         };
+        Ok(links
+            .into_iter()
+            .filter(|link| {
+                link.assets.iter().any(|a| a.id == asset_id)
+                    || link.album.as_ref().is_some_and(|shared| albums.iter().any(|a| a.id == shared.id))
+            })
+            .collect())
+    }
 
-        let response = self.client.delete(url).json(&body).send().await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(ImmichError::Api {
-                status: status.as_u16(),
-                message: body,
-            });
+    /// Fetches every shared link once and indexes it by the assets it
+    /// shares, fetching the members of each shared album.
+    ///
+    /// Prefer this to [`get_asset_shared_links`](Self::get_asset_shared_links)
+    /// when looking up many assets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn shared_link_index(&self) -> Result<SharedLinkIndex> {
+        let links = self.get_shared_links().await?;
+        let mut album_members = HashMap::new();
+        for album_id in links.iter().filter_map(|l| l.album.as_ref()).map(|a| &a.id) {
+            if !album_members.contains_key(album_id) {
+                let album = self.get_album(album_id).await?;
+                album_members.insert(album_id.clone(), album.assets.into_iter().map(|a| a.id).collect());
+            }
         }
-
-        Ok(())
+        Ok(SharedLinkIndex::new(links, &album_members))
     }
 
-    /// Updates an asset's metadata fields.
+    /// Adds assets to an individual shared link.
     ///
-    /// This method allows updating GPS coordinates, date/time, and description
-    /// for an asset. Only non-None fields will be sent in the update request.
+    /// Assets the link already shares are not an error.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `asset_id` - The ID of the asset to update
-    /// * `latitude` - New GPS latitude (optional)
-    /// * `longitude` - New GPS longitude (optional)
-    /// * `date_time_original` - New original date/time as ISO 8601 string (optional)
-    /// * `description` - New description (optional)
+    /// Returns an error if the HTTP request fails, the server returns an error
+    /// response, or any asset could not be added.
+    pub async fn add_shared_link_assets(&self, link_id: &str, asset_ids: &[String]) -> Result<()> {
+        let url = self.base_url.join(&format!("/api/shared-links/{}/assets", link_id))?;
+        let response = self
+            .client
+            .put(url)
+            .json(&serde_json::json!({ "assetIds": asset_ids }))
+            .send()
+            .await?;
+        let results: Vec<BulkIdResponse> = self.handle_response(response).await?;
+        ignore_already_present(check_bulk_results(&results))
+    }
+
+    /// Fetches the current user's memories (e.g. "On this day").
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The HTTP request fails
-    /// - The server returns an error response
-    pub async fn update_asset_metadata(
-        &self,
-        asset_id: &str,
-        latitude: Option<f64>,
-        longitude: Option<f64>,
-        date_time_original: Option<&str>,
-        description: Option<&str>,
-    ) -> Result<()> {
-        #[derive(Serialize)]
-        #[serde(rename_all = "camelCase")]
-        struct UpdateRequest<'a> {
-            #[serde(skip_serializing_if = "Option::is_none")]
-            latitude: Option<f64>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            longitude: Option<f64>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            date_time_original: Option<&'a str>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            description: Option<&'a str>,
-        }
-
-        let url = self.base_url.join(&format!("/api/assets/{}", asset_id))?;
-        let body = UpdateRequest {
-            latitude,
-            longitude,
-            date_time_original,
-            description,
-        };
-
-        let response = self.client.put(url).json(&body).send().await?;
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_memories(&self) -> Result<Vec<MemoryResponse>> {
+        let url = self.base_url.join("/api/memories")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(ImmichError::Api {
-                status: status.as_u16(),
-                message: body,
-            });
-        }
+    /// Adds assets to a memory.
+    ///
+    /// Assets already in the memory are not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails, the server returns an error
+    /// response, or any asset could not be added.
+    pub async fn add_memory_assets(&self, memory_id: &str, asset_ids: &[String]) -> Result<()> {
+        let url = self.base_url.join(&format!("/api/memories/{}/assets", memory_id))?;
+        let response = self
+            .client
+            .put(url)
+            .json(&serde_json::json!({ "ids": asset_ids }))
+            .send()
+            .await?;
+        let results: Vec<BulkIdResponse> = self.handle_response(response).await?;
+        ignore_already_present(check_bulk_results(&results))
+    }
 
-        Ok(())
+    /// Adds assets to an album.
+    ///
+    /// Assets already in the album are not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails, the server returns an error
+    /// response, or any asset could not be added.
+    pub async fn add_assets_to_album(&self, album_id: &str, asset_ids: &[String]) -> Result<()> {
+        let results = self.add_assets_to_album_with_results(album_id, asset_ids).await?;
+        ignore_already_present(check_bulk_results(&results))
     }
 
-    /// Uploads a file to Immich as a new asset.
+    /// Adds assets to an album, returning the server's result for each one.
     ///
-    /// # Arguments
+    /// An asset already in the album comes back unsuccessful with
+    /// [`BulkIdError::Duplicate`].
     ///
-    /// * `file_path` - Path to the file to upload
+    /// # Errors
     ///
-    /// # Returns
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (e.g. no such album); failures of single assets are
+    /// only in the results.
+    pub async fn add_assets_to_album_with_results(
+        &self,
+        album_id: &str,
+        asset_ids: &[String],
+    ) -> Result<Vec<BulkIdResponse>> {
+        let url = self.base_url.join(&format!("/api/albums/{}/assets", album_id))?;
+        let response = self
+            .client
+            .put(url)
+            .json(&serde_json::json!({ "ids": asset_ids }))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Removes assets from an album.
     ///
-    /// Information about the uploaded asset including its new ID.
+    /// Assets that aren't in the album are not an error.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The file cannot be read
-    /// - The HTTP request fails
-    /// - The server returns an error response
-    pub async fn upload_asset(&self, file_path: &Path) -> Result<UploadResponse> {
-        // Read file content
-        let file_content = tokio::fs::read(file_path).await?;
+    /// Returns an error if the HTTP request fails, the server returns an error
+    /// response, or any asset could not be removed.
+    pub async fn remove_assets_from_album(&self, album_id: &str, asset_ids: &[String]) -> Result<()> {
+        let results = self.remove_assets_from_album_with_results(album_id, asset_ids).await?;
+        let failed: Vec<BulkIdResponse> = results
+            .into_iter()
+            .filter(|r| !r.failed_with(BulkIdError::NotFound))
+            .collect();
+        check_bulk_results(&failed)
+    }
 
-        // Extract filename - strip asset ID prefix if present (format: {uuid}_{original})
-        let original_filename = file_path
+    /// Removes assets from an album, returning the server's result for each
+    /// one.
+    ///
+    /// An asset that isn't in the album comes back unsuccessful with
+    /// [`BulkIdError::NotFound`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (e.g. no such album); failures of single assets are
+    /// only in the results.
+    pub async fn remove_assets_from_album_with_results(
+        &self,
+        album_id: &str,
+        asset_ids: &[String],
+    ) -> Result<Vec<BulkIdResponse>> {
+        let url = self.base_url.join(&format!("/api/albums/{}/assets", album_id))?;
+        let response = self
+            .client
+            .delete(url)
+            .json(&serde_json::json!({ "ids": asset_ids }))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Sets an album's cover to one of its assets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error
+    /// response (e.g. the asset is not in the album).
+    pub async fn set_album_cover(&self, album_id: &str, asset_id: &str) -> Result<AlbumResponse> {
+        let url = self.base_url.join(&format!("/api/albums/{}", album_id))?;
+        let response = self
+            .client
+            .patch(url)
+            .json(&serde_json::json!({ "albumThumbnailAssetId": asset_id }))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches every tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_tags(&self) -> Result<Vec<TagResponse>> {
+        let url = self.base_url.join("/api/tags")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Creates tags by full value (e.g. `dedupe/processed`), along with any
+    /// missing parents, returning them. Tags that already exist are returned
+    /// as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn upsert_tags(&self, values: &[String]) -> Result<Vec<TagResponse>> {
+        let url = self.base_url.join("/api/tags")?;
+        let response = self
+            .client
+            .put(url)
+            .json(&serde_json::json!({ "tags": values }))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Attaches a tag to assets.
+    ///
+    /// Assets that already have the tag are not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails, the server returns an error
+    /// response, or any asset could not be tagged.
+    pub async fn tag_assets(&self, tag_id: &str, asset_ids: &[String]) -> Result<()> {
+        let url = self.base_url.join(&format!("/api/tags/{}/assets", tag_id))?;
+        let response = self
+            .client
+            .put(url)
+            .json(&serde_json::json!({ "ids": asset_ids }))
+            .send()
+            .await?;
+        let results: Vec<BulkIdResponse> = self.handle_response(response).await?;
+        ignore_already_present(check_bulk_results(&results))
+    }
+
+    /// Fetches a tag by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (404 for an unknown tag).
+    pub async fn get_tag(&self, tag_id: &str) -> Result<TagResponse> {
+        let url = self.base_url.join(&format!("/api/tags/{}", tag_id))?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches every person recognized by face detection, hidden ones
+    /// included.
+    ///
+    /// Handles pagination automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_people(&self) -> Result<Vec<PersonResponse>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PeoplePage {
+            people: Vec<PersonResponse>,
+            #[serde(default)]
+            has_next_page: bool,
+        }
+
+        let mut people = Vec::new();
+        for page in 1.. {
+            let mut url = self.base_url.join("/api/people")?;
+            url.query_pairs_mut()
+                .append_pair("withHidden", "true")
+                .append_pair("page", &page.to_string())
+                .append_pair("size", "500");
+            let response = self.client.get(url).send().await?;
+            let page: PeoplePage = self.handle_response(response).await?;
+            people.extend(page.people);
+            if !page.has_next_page {
+                break;
+            }
+        }
+        Ok(people)
+    }
+
+    /// Fetches a person by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (404 for an unknown person).
+    pub async fn get_person(&self, person_id: &str) -> Result<PersonResponse> {
+        let url = self.base_url.join(&format!("/api/people/{}", person_id))?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches stacks, optionally only the one whose primary asset is
+    /// `primary_asset_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_stacks(&self, primary_asset_id: Option<&str>) -> Result<Vec<StackResponse>> {
+        let mut url = self.base_url.join("/api/stacks")?;
+        if let Some(asset_id) = primary_asset_id {
+            url.query_pairs_mut().append_pair("primaryAssetId", asset_id);
+        }
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches a stack with its assets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (404 for an unknown stack).
+    pub async fn get_stack(&self, stack_id: &str) -> Result<StackResponse> {
+        let url = self.base_url.join(&format!("/api/stacks/{}", stack_id))?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Stacks assets together, the first becoming the primary asset.
+    ///
+    /// Assets already in a stack are moved into the new one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn create_stack(&self, asset_ids: &[String]) -> Result<StackResponse> {
+        let url = self.base_url.join("/api/stacks")?;
+        let response = self
+            .client
+            .post(url)
+            .json(&serde_json::json!({ "assetIds": asset_ids }))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Removes a stack. Its assets are kept and shown individually again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn delete_stack(&self, stack_id: &str) -> Result<()> {
+        let url = self.base_url.join(&format!("/api/stacks/{}", stack_id))?;
+        let response = self.client.delete(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the server version. Does not require authentication.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_server_version(&self) -> Result<ServerVersion> {
+        let url = self.base_url.join("/api/server/version")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches the user that owns the API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (401 for an invalid key).
+    pub async fn get_my_user(&self) -> Result<UserInfo> {
+        let url = self.base_url.join("/api/users/me")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches the API key used for requests, including its permissions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (404 on servers without scoped API keys).
+    pub async fn get_my_api_key(&self) -> Result<ApiKeyInfo> {
+        let url = self.base_url.join("/api/api-keys/me")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches the status of every job queue, keyed by queue name
+    /// (e.g. `duplicateDetection`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (403 for non-admin keys).
+    pub async fn get_job_statuses(&self) -> Result<HashMap<String, JobStatus>> {
+        let url = self.base_url.join("/api/jobs")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches asset counts and storage for the whole server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an
+    /// error response (403 for non-admin keys).
+    pub async fn get_server_statistics(&self) -> Result<ServerStatistics> {
+        let url = self.base_url.join("/api/server/statistics")?;
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Fetches asset counts for the key's user, either for the library or the trash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn get_asset_statistics(&self, is_trashed: bool) -> Result<AssetStatistics> {
+        let mut url = self.base_url.join("/api/assets/statistics")?;
+        url.query_pairs_mut()
+            .append_pair("isTrashed", if is_trashed { "true" } else { "false" });
+        let response = self.client.get(url).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Downloads an asset's original file to the specified path.
+    ///
+    /// Uses streaming to avoid buffering the entire file in memory,
+    /// making it suitable for large files.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset_id` - The ID of the asset to download
+    /// * `path` - The destination path to save the file
+    ///
+    /// # Returns
+    ///
+    /// The total number of bytes written to the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The server returns an error response
+    /// - The file cannot be created or written to
+    pub async fn download_asset(&self, asset_id: &str, path: &Path) -> Result<u64> {
+        let response = self.original_response(asset_id).await?;
+        let mut file = tokio::fs::File::create(path).await?;
+        stream_to(response, &mut file).await
+    }
+
+    /// Streams an asset's original file into a writer.
+    ///
+    /// Like [`download_asset`](Self::download_asset), but the destination can
+    /// be anything that implements [`AsyncWrite`]: an object store upload, an
+    /// archive entry, or a hasher, with no temporary file. Nothing is written
+    /// if the server returns an error. The writer is flushed but not shut down.
+    ///
+    /// # Returns
+    ///
+    /// The total number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails, the server returns an error
+    /// response, or the writer fails.
+    pub async fn download_asset_to<W>(&self, asset_id: &str, writer: &mut W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let response = self.original_response(asset_id).await?;
+        stream_to(response, writer).await
+    }
+
+    /// Reads the first `len` bytes of an asset's original (fewer if the file
+    /// is shorter), enough to sniff its format without downloading it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn original_prefix(&self, asset_id: &str, len: usize) -> Result<Vec<u8>> {
+        let url = self
+            .base_url
+            .join(&format!("/api/assets/{}/original", asset_id))?;
+        let range = format!("bytes=0-{}", len.saturating_sub(1));
+        let response = self.client.get(url).header(reqwest::header::RANGE, range).send().await?;
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        // A server that ignores the range sends the whole file: stop reading early
+        let mut stream = response.bytes_stream();
+        let mut prefix = Vec::with_capacity(len);
+        while prefix.len() < len
+            && let Some(chunk) = stream.next().await
+        {
+            let chunk = chunk?;
+            let take = chunk.len().min(len - prefix.len());
+            prefix.extend_from_slice(&chunk[..take]);
+        }
+        Ok(prefix)
+    }
+
+    /// Request an asset's original file, turning an error status into an error.
+    pub(crate) async fn original_response(&self, asset_id: &str) -> Result<reqwest::Response> {
+        let url = self
+            .base_url
+            .join(&format!("/api/assets/{}/original", asset_id))?;
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        Ok(response)
+    }
+
+    /// Downloads one of an asset's server-rendered images: the thumbnail,
+    /// the preview, or the full-size rendition.
+    ///
+    /// These are JPEG or WebP images Immich has already generated, so they
+    /// are far smaller than most originals and readable whatever the
+    /// original's format. Review tools should go through a
+    /// [`ThumbnailCache`](crate::thumbnails::ThumbnailCache) rather than
+    /// calling this for every redraw.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn download_thumbnail(&self, asset_id: &str, size: ThumbnailSize) -> Result<Vec<u8>> {
+        let mut url = self
+            .base_url
+            .join(&format!("/api/assets/{}/thumbnail", asset_id))?;
+        url.query_pairs_mut().append_pair("size", size.as_str());
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Restores assets from the trash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the server returns an error response.
+    pub async fn restore_from_trash(&self, asset_ids: &[String]) -> Result<()> {
+        let url = self.base_url.join("/api/trash/restore/assets")?;
+        let response = self
+            .client
+            .post(url)
+            .json(&serde_json::json!({ "ids": asset_ids }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        Ok(())
+    }
+
+    /// Deletes multiple assets, [`DEFAULT_DELETE_CHUNK_SIZE`] per request.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset_ids` - The IDs of the assets to delete
+    /// * `force` - If true, permanently delete; if false, move to trash
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any request fails: the request's own error if it
+    /// was the only one, otherwise [`ImmichError::PartialDelete`]. Other
+    /// chunks may have been deleted; use [`delete_assets_chunked`](Self::delete_assets_chunked)
+    /// to know which.
+    pub async fn delete_assets(&self, asset_ids: &[String], force: bool) -> Result<()> {
+        self.delete_assets_chunked(asset_ids, force, DEFAULT_DELETE_CHUNK_SIZE)
+            .await
+            .into_result()
+    }
+
+    /// Deletes multiple assets, `chunk_size` per request.
+    ///
+    /// A failed chunk doesn't stop the rest; the report lists the assets
+    /// deleted and each chunk that failed, with its error.
+    pub async fn delete_assets_chunked(&self, asset_ids: &[String], force: bool, chunk_size: usize) -> DeleteReport {
+        delete_in_chunks(asset_ids, chunk_size, |chunk| self.delete_asset_chunk(chunk, force)).await
+    }
+
+    /// `DELETE /api/assets` for one chunk of assets.
+    pub(crate) async fn delete_asset_chunk(&self, asset_ids: &[String], force: bool) -> Result<()> {
+        #[derive(Serialize)]
+        struct DeleteRequest<'a> {
+            ids: &'a [String],
+            force: bool,
+        }
+
+        let url = self.base_url.join("/api/assets")?;
+        let body = DeleteRequest {
+            ids: asset_ids,
+            force,
+        };
+
+        let response = self.client.delete(url).json(&body).send().await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        Ok(())
+    }
+
+    /// Updates an asset's metadata fields.
+    ///
+    /// This method allows updating GPS coordinates, date/time, and description
+    /// for an asset. Only non-None fields will be sent in the update request.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset_id` - The ID of the asset to update
+    /// * `latitude` - New GPS latitude (optional)
+    /// * `longitude` - New GPS longitude (optional)
+    /// * `date_time_original` - New original date/time as ISO 8601 string (optional)
+    /// * `description` - New description (optional)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The server returns an error response
+    pub async fn update_asset_metadata(
+        &self,
+        asset_id: &str,
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        date_time_original: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<()> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct UpdateRequest<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            latitude: Option<f64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            longitude: Option<f64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            date_time_original: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            description: Option<&'a str>,
+        }
+
+        let url = self.base_url.join(&format!("/api/assets/{}", asset_id))?;
+        let body = UpdateRequest {
+            latitude,
+            longitude,
+            date_time_original,
+            description,
+        };
+
+        let response = self.client.put(url).json(&body).send().await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        Ok(())
+    }
+
+    /// Updates the same metadata fields on several assets in one API call.
+    ///
+    /// Uses the bulk asset update endpoint (`PUT /api/assets`); every asset
+    /// gets the same values. Only non-None fields are sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset_ids` - The IDs of the assets to update
+    /// * `latitude` - New GPS latitude (optional)
+    /// * `longitude` - New GPS longitude (optional)
+    /// * `date_time_original` - New original date/time as ISO 8601 string (optional)
+    /// * `description` - New description (optional)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The server returns an error response
+    pub async fn update_assets_bulk(
+        &self,
+        asset_ids: &[String],
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        date_time_original: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<()> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct BulkUpdateRequest<'a> {
+            ids: &'a [String],
+            #[serde(skip_serializing_if = "Option::is_none")]
+            latitude: Option<f64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            longitude: Option<f64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            date_time_original: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            description: Option<&'a str>,
+        }
+
+        let url = self.base_url.join("/api/assets")?;
+        let body = BulkUpdateRequest {
+            ids: asset_ids,
+            latitude,
+            longitude,
+            date_time_original,
+            description,
+        };
+
+        let response = self.client.put(url).json(&body).send().await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        Ok(())
+    }
+
+    /// Asks the server which files it already has, before uploading them.
+    ///
+    /// Uses the bulk upload check endpoint (`POST /api/assets/bulk-upload-check`),
+    /// in batches of 1000. Each file is given as a caller-chosen ID (e.g. its
+    /// path) and its SHA-1 checksum, hex or base64 encoded (see
+    /// [`file_checksum`]); the result maps each ID to the server's answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The server returns an error response
+    pub async fn check_bulk_upload(&self, checksums: &[(String, String)]) -> Result<HashMap<String, UploadCheck>> {
+        #[derive(Serialize)]
+        struct CheckItem<'a> {
+            id: &'a str,
+            checksum: &'a str,
+        }
+
+        #[derive(Serialize)]
+        struct CheckRequest<'a> {
+            assets: Vec<CheckItem<'a>>,
+        }
+
+        #[derive(Deserialize)]
+        struct CheckResponse {
+            results: Vec<CheckResult>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CheckResult {
+            id: String,
+            action: String,
+            #[serde(default)]
+            reason: Option<String>,
+            #[serde(default)]
+            asset_id: Option<String>,
+            #[serde(default)]
+            is_trashed: bool,
+        }
+
+        let url = self.base_url.join("/api/assets/bulk-upload-check")?;
+        let mut checks = HashMap::with_capacity(checksums.len());
+
+        for batch in checksums.chunks(UPLOAD_CHECK_BATCH_SIZE) {
+            let body = CheckRequest {
+                assets: batch
+                    .iter()
+                    .map(|(id, checksum)| CheckItem { id, checksum })
+                    .collect(),
+            };
+            let response = self.client.post(url.clone()).json(&body).send().await?;
+            if !response.status().is_success() {
+                return Err(api_error(response).await);
+            }
+
+            let response: CheckResponse = response.json().await?;
+            for result in response.results {
+                let check = match (result.action.as_str(), result.reason, result.asset_id) {
+                    ("accept", _, _) => UploadCheck::Accept,
+                    (_, Some(reason), Some(asset_id)) if reason == "duplicate" => UploadCheck::Duplicate {
+                        asset_id,
+                        is_trashed: result.is_trashed,
+                    },
+                    (_, reason, _) => UploadCheck::Reject {
+                        reason: reason.unwrap_or_else(|| "unknown".to_string()),
+                    },
+                };
+                checks.insert(result.id, check);
+            }
+        }
+
+        Ok(checks)
+    }
+
+    /// Uploads a file to Immich as a new asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the file to upload
+    ///
+    /// # Returns
+    ///
+    /// Information about the uploaded asset including its new ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file cannot be read
+    /// - The HTTP request fails
+    /// - The server returns an error response
+    pub async fn upload_asset(&self, file_path: &Path) -> Result<UploadResponse> {
+        self.upload_asset_with(file_path, &UploadOptions::default()).await
+    }
+
+    /// Uploads a file to Immich as a new asset, with a sidecar, favorite
+    /// flag, or live photo companion.
+    ///
+    /// The file is streamed from disk, so memory use doesn't grow with its
+    /// size, and the request is given longer to finish the larger the file
+    /// is. If the connection drops or times out, or the server answers 429
+    /// or 5xx, the whole file is sent again (Immich can't resume a partial
+    /// upload), up to three attempts in all.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the file to upload
+    /// * `options` - Extras to send with the file
+    ///
+    /// # Returns
+    ///
+    /// Information about the uploaded asset including its new ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file or sidecar cannot be read
+    /// - The HTTP request fails
+    /// - The server returns an error response
+    pub async fn upload_asset_with(&self, file_path: &Path, options: &UploadOptions) -> Result<UploadResponse> {
+        // Extract filename - strip asset ID prefix if present (format: {uuid}_{original})
+        let original_filename = file_path
             .file_name()
             .and_then(|n| n.to_str())
             .map(|name| {
@@ -384,11 +1524,11 @@ impl ImmichClient {
             })
             .unwrap_or_else(|| "unknown".to_string());
 
-        // Get file modification time for timestamps
-        let file_time = tokio::fs::metadata(file_path)
-            .await
+        // Get file size and modification time for timestamps
+        let metadata = tokio::fs::metadata(file_path).await?;
+        let file_time = metadata
+            .modified()
             .ok()
-            .and_then(|m| m.modified().ok())
             .map(DateTime::<Utc>::from)
             .unwrap_or_else(Utc::now);
 
@@ -408,31 +1548,119 @@ impl ImmichClient {
             _ => "application/octet-stream",
         };
 
-        // Build multipart form
-        let file_part = Part::bytes(file_content)
-            .file_name(original_filename.clone())
-            .mime_str(mime_type)?;
+        let sidecar = match &options.sidecar {
+            Some(sidecar) => Some(tokio::fs::read(sidecar).await?),
+            None => None,
+        };
+        let device_asset_id = format!("restore-{}", uuid::Uuid::new_v4());
+        let url = self.base_url.join("/api/assets")?;
+
+        let mut attempt = 1;
+        loop {
+            // The file is streamed from disk, so each attempt re-opens it
+            let file = tokio::fs::File::open(file_path).await?;
+            let file_part = Part::stream_with_length(file, metadata.len())
+                .file_name(original_filename.clone())
+                .mime_str(mime_type)?;
 
-        let form = Form::new()
-            .part("assetData", file_part)
-            .text("deviceAssetId", format!("restore-{}", uuid::Uuid::new_v4()))
-            .text("deviceId", "immich-dupes-restore")
-            .text("fileCreatedAt", file_time_str.clone())
-            .text("fileModifiedAt", file_time_str);
+            let mut form = Form::new()
+                .part("assetData", file_part)
+                .text("deviceAssetId", device_asset_id.clone())
+                .text("deviceId", "immich-dupes-restore")
+                .text("fileCreatedAt", file_time_str.clone())
+                .text("fileModifiedAt", file_time_str.clone());
 
-        let url = self.base_url.join("/api/assets")?;
-        let response = self.client.post(url).multipart(form).send().await?;
+            if let Some(is_favorite) = options.is_favorite {
+                form = form.text("isFavorite", is_favorite.to_string());
+            }
+            if let Some(video_id) = &options.live_photo_video_id {
+                form = form.text("livePhotoVideoId", video_id.clone());
+            }
+            if let Some(sidecar) = &sidecar {
+                let sidecar_part = Part::bytes(sidecar.clone())
+                    .file_name(format!("{}.xmp", original_filename))
+                    .mime_str("application/xml")?;
+                form = form.part("sidecarData", sidecar_part);
+            }
 
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ImmichError::Api {
-                status: status.as_u16(),
-                message: body,
-            })
+            let result = match self
+                .client
+                .post(url.clone())
+                .timeout(upload_timeout(metadata.len()))
+                .multipart(form)
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => return Ok(response.json().await?),
+                Ok(response) => Err(api_error(response).await),
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Err(e) if attempt < MAX_UPLOAD_ATTEMPTS && is_transient(&e) => {
+                    let delay = match e {
+                        ImmichError::RateLimited { retry_after: Some(delay) } => delay,
+                        _ => UPLOAD_RETRY_DELAY * attempt,
+                    };
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Subscribes to the server's live events over its websocket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection cannot be upgraded to a websocket
+    /// or the socket.io handshake fails.
+    #[cfg(feature = "events")]
+    pub async fn subscribe(&self) -> Result<crate::events::EventStream<reqwest::Upgraded>> {
+        use base64::Engine;
+
+        let mut url = self.base_url.join("/api/socket.io/")?;
+        url.query_pairs_mut()
+            .append_pair("EIO", "4")
+            .append_pair("transport", "websocket");
+        let key = base64::engine::general_purpose::STANDARD.encode(uuid::Uuid::new_v4().as_bytes());
+        let response = self
+            .client
+            .get(url)
+            .header(reqwest::header::CONNECTION, "Upgrade")
+            .header(reqwest::header::UPGRADE, "websocket")
+            .header(reqwest::header::SEC_WEBSOCKET_VERSION, "13")
+            .header(reqwest::header::SEC_WEBSOCKET_KEY, &key)
+            .send()
+            .await?;
+        if response.status().is_success() {
+            return Err(ImmichError::Events(format!(
+                "server answered {} instead of switching to a websocket",
+                response.status()
+            )));
+        }
+        if response.status() != reqwest::StatusCode::SWITCHING_PROTOCOLS {
+            return Err(api_error(response).await);
         }
+        // Only a WebSocket server that read our key can answer with this
+        let accept = response.headers().get(reqwest::header::SEC_WEBSOCKET_ACCEPT);
+        if accept.and_then(|value| value.to_str().ok()) != Some(crate::events::accept_key(&key).as_str()) {
+            return Err(ImmichError::Events(
+                "server answered the websocket upgrade with the wrong Sec-WebSocket-Accept".to_string(),
+            ));
+        }
+        crate::events::EventStream::handshake(response.upgrade().await?).await
+    }
+
+    /// Low-level bindings for the endpoints in the OpenAPI spec the crate was
+    /// built with (see [`raw`]).
+    ///
+    /// Prefer the methods on `ImmichClient`; these exist for endpoints it
+    /// does not cover yet.
+    #[cfg(feature = "openapi")]
+    pub fn raw(&self) -> raw::Raw<'_> {
+        raw::Raw { client: self }
     }
 
     /// Handles an HTTP response, parsing success responses or extracting error details.
@@ -440,16 +1668,360 @@ impl ImmichClient {
         &self,
         response: reqwest::Response,
     ) -> Result<T> {
-        let status = response.status();
-
-        if status.is_success() {
+        if response.status().is_success() {
             Ok(response.json().await?)
         } else {
-            let body = response.text().await.unwrap_or_default();
-            Err(ImmichError::Api {
-                status: status.as_u16(),
-                message: body,
-            })
+            Err(api_error(response).await)
+        }
+    }
+}
+
+/// Typed endpoint bindings generated from Immich's OpenAPI spec.
+///
+/// One method per operation, named after its `operationId` (`getAssetInfo`
+/// becomes [`Raw::get_asset_info`]), with request and response types
+/// generated from the spec's schemas. They track the spec exactly and change
+/// whenever it does; the curated methods on [`ImmichClient`] are the stable
+/// surface.
+///
+/// Generated by `build.rs` from the file named by `IMMICH_OPENAPI_SPEC` at
+/// build time, or else from `openapi/immich-openapi-specs.json`. The vendored
+/// spec is a hand-maintained subset of Immich's, covering only the paths
+/// this crate uses (albums, assets, duplicates, shared links, tag assets,
+/// trash restore, server version, and the current user); build against
+/// Immich's full spec for bindings to every endpoint.
+#[cfg(feature = "openapi")]
+pub mod raw {
+    use serde::de::DeserializeOwned;
+    use url::Url;
+
+    use super::{ImmichClient, api_error};
+    use crate::error::Result;
+
+    /// Endpoint bindings borrowed from an [`ImmichClient`].
+    ///
+    /// Created by [`ImmichClient::raw`].
+    #[derive(Debug, Clone, Copy)]
+    pub struct Raw<'a> {
+        pub(super) client: &'a ImmichClient,
+    }
+
+    impl Raw<'_> {
+        fn url(&self, path: &str) -> Result<Url> {
+            Ok(self.client.base_url.join(path)?)
+        }
+
+        async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+            self.client.handle_response(request.send().await?).await
+        }
+
+        async fn send_empty(&self, request: reqwest::RequestBuilder) -> Result<()> {
+            let response = request.send().await?;
+            if !response.status().is_success() {
+                return Err(api_error(response).await);
+            }
+            Ok(())
+        }
+
+        async fn send_bytes(&self, request: reqwest::RequestBuilder) -> Result<Vec<u8>> {
+            let response = request.send().await?;
+            if !response.status().is_success() {
+                return Err(api_error(response).await);
+            }
+            Ok(response.bytes().await?.to_vec())
+        }
+    }
+
+    /// Percent-encode a path parameter.
+    fn segment(value: &str) -> String {
+        url::form_urlencoded::byte_serialize(value.as_bytes())
+            .collect::<String>()
+            .replace('+', "%20")
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/raw.rs"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_hides_api_key() {
+        let client = ImmichClient::new("https://immich.example.com", "secret-key").unwrap();
+        let debug = format!("{:?}", client);
+        assert!(debug.contains("immich.example.com"));
+        assert!(!debug.contains("secret-key"));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2024-06-15T10:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse_retry_after("30", now), Some(Duration::from_secs(30)));
+        assert_eq!(
+            parse_retry_after("Sat, 15 Jun 2024 10:00:05 GMT", now),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(parse_retry_after("Sat, 15 Jun 2024 09:00:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_error_body_classified() {
+        let body = r#"{"message":["ids must be an array","ids must be UUIDs"],"error":"Bad Request","statusCode":400}"#;
+        match classify_error(400, body) {
+            ImmichError::ValidationFailed { message } => {
+                assert_eq!(message, "ids must be an array; ids must be UUIDs")
+            }
+            other => panic!("unexpected {other:?}"),
         }
+        assert!(matches!(
+            classify_error(404, r#"{"message":"Not found or no asset.read access","statusCode":404}"#),
+            ImmichError::NotFound { message } if message == "Not found or no asset.read access"
+        ));
+        assert!(matches!(classify_error(403, ""), ImmichError::Unauthorized { status: 403, .. }));
+        assert!(matches!(
+            classify_error(502, "<html>Bad Gateway</html>"),
+            ImmichError::Api { status: 502, message } if message == "<html>Bad Gateway</html>"
+        ));
+
+        let bulk = |id: &str, error: Option<BulkIdError>| BulkIdResponse {
+            id: id.to_string(),
+            success: error.is_none(),
+            error,
+        };
+        assert!(matches!(
+            check_bulk_results(&[bulk("a", None), bulk("b", Some(BulkIdError::Duplicate))]),
+            Err(ImmichError::AlreadyInAlbum { asset_ids }) if asset_ids == ["b"]
+        ));
+        assert!(matches!(
+            check_bulk_results(&[bulk("a", Some(BulkIdError::Duplicate)), bulk("b", Some(BulkIdError::NotFound))]),
+            Err(ImmichError::NotFound { .. })
+        ));
+        assert!(check_bulk_results(&[bulk("a", None)]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_download_asset_to_writer() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let groups = synthetic_groups(1, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("original.jpg");
+        let asset_id = &groups[0].assets[0].id;
+
+        let mut buffer = Vec::new();
+        let written = client.download_asset_to(asset_id, &mut buffer).await.unwrap();
+        assert_eq!(client.download_asset(asset_id, &path).await.unwrap(), written);
+        assert_eq!(std::fs::read(&path).unwrap(), buffer);
+
+        let mut untouched = Vec::new();
+        assert!(client.download_asset_to("missing", &mut untouched).await.is_err());
+        assert!(untouched.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upload_with_sidecar_then_update() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let server = MockServer::start(&synthetic_groups(1, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("0f8fad5b-d9cb-469f-a165-70867728950e_IMG_1.jpg");
+        std::fs::write(&path, b"jpeg").unwrap();
+        std::fs::write(dir.path().join("0f8fad5b-d9cb-469f-a165-70867728950e_IMG_1.jpg.xmp"), b"<x:xmpmeta/>").unwrap();
+
+        let options = UploadOptions {
+            is_favorite: Some(true),
+            sidecar: Some(crate::sidecar::sidecar_path(&path)),
+            ..Default::default()
+        };
+        let uploaded = client.upload_asset_with(&path, &options).await.unwrap();
+        assert_eq!(server.uploads(), ["IMG_1.jpg"]);
+        assert_eq!(server.sidecar_uploads(), ["IMG_1.jpg.xmp"]);
+
+        client
+            .update_asset_metadata(&uploaded.id, Some(51.5), Some(-0.12), None, Some("Beach"))
+            .await
+            .unwrap();
+        let exif = client.get_asset(&uploaded.id).await.unwrap().exif_info.unwrap();
+        assert_eq!((exif.latitude, exif.description.as_deref()), (Some(51.5), Some("Beach")));
+    }
+
+    #[tokio::test]
+    async fn test_upload_retried_after_transient_failure() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let server = MockServer::start(&synthetic_groups(1, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.mp4");
+        std::fs::write(&path, vec![7u8; 3 * 1024 * 1024]).unwrap();
+
+        server.rate_limit_next(2);
+        let uploaded = client.upload_asset(&path).await.unwrap();
+        assert_eq!(uploaded.id, "uploaded-0");
+        assert_eq!(server.uploads(), ["clip.mp4"]);
+
+        // Out of attempts: the last failure is returned
+        server.rate_limit_next(MAX_UPLOAD_ATTEMPTS as usize);
+        let err = client.upload_asset(&path).await.unwrap_err();
+        assert!(matches!(err, ImmichError::RateLimited { .. }));
+        assert_eq!(server.uploads().len(), 1);
+
+        assert_eq!(upload_timeout(0), Duration::from_secs(30));
+        assert_eq!(upload_timeout(4 * 1024 * 1024 * 1024), Duration::from_secs(30 + 16384));
+    }
+
+    #[tokio::test]
+    async fn test_people_tags_and_stacks() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let server = MockServer::start(&synthetic_groups(1, 3).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        server.add_person("person-1", "Ada");
+        server.add_tag("tag-1", "Frames/Kitchen", &[]);
+
+        let people = client.get_people().await.unwrap();
+        assert_eq!(people.len(), 1);
+        assert_eq!(client.get_person("person-1").await.unwrap().name, "Ada");
+        assert!(matches!(client.get_person("nobody").await, Err(ImmichError::NotFound { .. })));
+
+        let tag = client.get_tag("tag-1").await.unwrap();
+        assert_eq!((tag.name.as_str(), tag.value.as_str()), ("Kitchen", "Frames/Kitchen"));
+
+        let ids: Vec<String> = (0..3).map(|i| format!("asset-000000-{}", i)).collect();
+        let stack = client.create_stack(&ids).await.unwrap();
+        assert_eq!(stack.primary_asset_id, ids[0]);
+        assert_eq!(stack.assets.len(), 3);
+        assert_eq!(client.get_stack(&stack.id).await.unwrap().assets[2].id, ids[2]);
+        assert_eq!(client.get_stacks(Some(&ids[0])).await.unwrap().len(), 1);
+        assert!(client.get_stacks(Some(&ids[1])).await.unwrap().is_empty());
+
+        client.delete_stack(&stack.id).await.unwrap();
+        assert!(client.get_stacks(None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_file_checksum_spans_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("video.mp4");
+        let content: Vec<u8> = (0..CHECKSUM_CHUNK_SIZE * 2 + 7).map(|i| i as u8).collect();
+        std::fs::write(&path, &content).unwrap();
+
+        let whole = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, &content);
+        let expected: String = whole.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(file_checksum(&path).await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_check_bulk_upload() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let server = MockServer::start(&synthetic_groups(1, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        client.delete_assets(&["asset-000000-1".to_string()], false).await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new.jpg");
+        std::fs::write(&path, b"jpeg").unwrap();
+        let checksum = file_checksum(&path).await.unwrap();
+        assert_eq!(checksum, "14993722cfd06f0e85fcc82a9dc489400beeabb9");
+
+        let checks = client
+            .check_bulk_upload(&[
+                ("kept".to_string(), "checksum-0-0".to_string()),
+                ("trashed".to_string(), "checksum-0-1".to_string()),
+                ("new".to_string(), checksum),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            checks["kept"],
+            UploadCheck::Duplicate { asset_id: "asset-000000-0".to_string(), is_trashed: false }
+        );
+        assert_eq!(
+            checks["trashed"],
+            UploadCheck::Duplicate { asset_id: "asset-000000-1".to_string(), is_trashed: true }
+        );
+        assert_eq!(checks["new"], UploadCheck::Accept);
+    }
+
+    #[tokio::test]
+    async fn test_album_results_per_asset() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let server = MockServer::start(&synthetic_groups(1, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        server.add_album("album-1", "Holiday", &["asset-000000-0"]);
+        let ids = ["asset-000000-0".to_string(), "asset-000000-1".to_string()];
+
+        let added = client.add_assets_to_album_with_results("album-1", &ids).await.unwrap();
+        assert!(added[0].failed_with(BulkIdError::Duplicate));
+        assert!(added[1].success);
+        client.add_assets_to_album("album-1", &ids).await.unwrap();
+
+        let removed = client.remove_assets_from_album_with_results("album-1", &ids[1..]).await.unwrap();
+        assert!(removed[0].success);
+        let removed = client.remove_assets_from_album_with_results("album-1", &ids[1..]).await.unwrap();
+        assert!(removed[0].failed_with(BulkIdError::NotFound));
+        client.remove_assets_from_album("album-1", &ids[1..]).await.unwrap();
+
+        let unknown: BulkIdResponse =
+            serde_json::from_str(r#"{"id":"a","success":false,"error":"something_new"}"#).unwrap();
+        assert_eq!(unknown.error, Some(BulkIdError::Unknown));
+    }
+
+    #[tokio::test]
+    async fn test_delete_assets_in_chunks() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let server = MockServer::start(&synthetic_groups(3, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        server.limit_delete_ids(2);
+        let ids: Vec<String> = (0..3).flat_map(|g| (0..2).map(move |i| format!("asset-{:06}-{}", g, i))).collect();
+
+        // One oversized request is refused outright
+        let error = client.delete_assets(&ids, false).await.unwrap_err();
+        assert_eq!(error.status(), Some(413));
+        assert!(server.deleted_assets().is_empty());
+
+        // A chunk that fails doesn't stop the others, and is reported
+        server.fail_deletes_of(&["asset-000001-0"]);
+        let report = client.delete_assets_chunked(&ids, false, 2).await;
+        assert_eq!(report.deleted, ["asset-000000-0", "asset-000000-1", "asset-000002-0", "asset-000002-1"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].asset_ids, ["asset-000001-0", "asset-000001-1"]);
+        assert!(!report.is_complete());
+        assert!(matches!(
+            report.into_result(),
+            Err(ImmichError::PartialDelete { deleted: 4, requested: 6, .. })
+        ));
+    }
+
+    #[cfg(feature = "openapi")]
+    #[tokio::test]
+    async fn test_raw_bindings() {
+        use crate::testing::mock_server::{MockServer, synthetic_groups};
+
+        let server = MockServer::start(&synthetic_groups(1, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+
+        let version = client.raw().get_server_version().await.unwrap();
+        assert_eq!((version.major, version.minor), (1, 132));
+
+        let user = client.raw().get_my_user().await.unwrap();
+        assert!(user.is_admin);
+        assert_eq!(user.quota_size_in_bytes, None);
+
+        let asset_id = client.get_duplicates().await.unwrap()[0].assets[0].id.clone();
+        let query = raw::DownloadAssetQuery::default();
+        assert!(!client.raw().download_asset(&asset_id, &query).await.unwrap().is_empty());
+
+        let body = raw::BulkIdsDto { ids: vec![asset_id] };
+        let missing = client.raw().remove_asset_from_album("no-such-album", &body).await;
+        assert!(matches!(missing, Err(ImmichError::NotFound { .. })));
     }
 }
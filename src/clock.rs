@@ -0,0 +1,40 @@
+//! Time sources.
+//!
+//! Reports and runs are stamped with the current time. Code that stamps them
+//! takes a [`Clock`], so tests and snapshot comparisons can use a
+//! [`FixedClock`] and get identical output on every run.
+
+use chrono::{DateTime, Utc};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// The current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at one instant.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl FixedClock {
+    /// A clock stopped at `time`.
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self(time)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -0,0 +1,313 @@
+//! Read-only diagnostics of an Immich server.
+//!
+//! [`diagnose`] checks the things that most often explain surprising
+//! results — an unreachable server, a bad or under-scoped API key, an old
+//! server, a stale or stuck duplicate-detection job, a full trash — and
+//! returns findings with a hint on how to fix each problem. Nothing on the
+//! server is modified.
+
+use serde::Serialize;
+
+use crate::client::ImmichClient;
+use crate::error::ImmichError;
+use crate::models::ServerVersion;
+
+/// Oldest server version with the duplicates API this tool relies on.
+pub const MIN_SERVER_VERSION: ServerVersion = ServerVersion::new(1, 106, 0);
+
+/// API key permissions needed by analyze, execute, and restore.
+pub const REQUIRED_PERMISSIONS: &[&str] = &[
+    "duplicate.read",
+    "asset.read",
+    "asset.download",
+    "asset.update",
+    "asset.delete",
+    "asset.upload",
+];
+
+/// Name of the duplicate-detection queue in `GET /api/jobs`.
+const DUPLICATE_JOB: &str = "duplicateDetection";
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingLevel {
+    /// Check passed
+    Ok,
+    /// Useful context, no action needed
+    Info,
+    /// Likely to cause surprising results
+    Warning,
+    /// Prevents the tool from working
+    Error,
+}
+
+/// Result of one diagnostic check.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    /// Short name of the check (e.g. `auth`)
+    pub check: String,
+
+    /// How serious the finding is
+    pub level: FindingLevel,
+
+    /// What was found
+    pub message: String,
+
+    /// What to do about it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Finding {
+    /// Create a finding without a hint.
+    pub fn new(check: &str, level: FindingLevel, message: impl Into<String>) -> Self {
+        Self {
+            check: check.to_string(),
+            level,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    /// Attach an actionable hint.
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// Run all server checks.
+///
+/// Stops early if the server is unreachable or the API key is rejected,
+/// since every later check would fail for the same reason.
+pub async fn diagnose(client: &ImmichClient) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    // Connectivity and version (unauthenticated)
+    match client.get_server_version().await {
+        Ok(version) if version < MIN_SERVER_VERSION => findings.push(
+            Finding::new(
+                "version",
+                FindingLevel::Error,
+                format!("Server {} is older than the minimum supported {}", version, MIN_SERVER_VERSION),
+            )
+            .with_hint("Upgrade Immich; the duplicates API is required"),
+        ),
+        Ok(version) => findings.push(Finding::new(
+            "version",
+            FindingLevel::Ok,
+            format!("Server {} is reachable", version),
+        )),
+        Err(e) => match e.status() {
+            Some(status) => findings.push(
+                Finding::new(
+                    "version",
+                    FindingLevel::Warning,
+                    format!("Server responded but version is unknown (HTTP {})", status),
+                )
+                .with_hint("Check the URL points at the Immich server, not a proxy login page"),
+            ),
+            None => {
+                findings.push(
+                    Finding::new("connectivity", FindingLevel::Error, format!("Cannot reach server: {}", e))
+                        .with_hint("Check the URL, network access, and TLS certificate"),
+                );
+                return findings;
+            }
+        },
+    }
+
+    // Authentication
+    match client.get_my_user().await {
+        Ok(user) => findings.push(Finding::new(
+            "auth",
+            FindingLevel::Ok,
+            format!(
+                "API key belongs to {}{}",
+                user.email,
+                if user.is_admin { " (admin)" } else { "" }
+            ),
+        )),
+        Err(e) => {
+            findings.push(
+                Finding::new("auth", FindingLevel::Error, format!("API key rejected: {}", e))
+                    .with_hint("Create a new API key under Account Settings → API Keys"),
+            );
+            return findings;
+        }
+    }
+
+    findings.push(check_scope(client).await);
+    findings.push(check_duplicate_job(client).await);
+
+    // Trash
+    match client.get_asset_statistics(true).await {
+        Ok(stats) if stats.total > 0 => findings.push(
+            Finding::new("trash", FindingLevel::Info, format!("{} assets in trash", stats.total))
+                .with_hint("Executing moves losers to trash; empty it in Immich to reclaim space"),
+        ),
+        Ok(_) => findings.push(Finding::new("trash", FindingLevel::Ok, "Trash is empty")),
+        Err(e) => findings.push(Finding::new(
+            "trash",
+            FindingLevel::Warning,
+            format!("Could not read trash size: {}", e),
+        )),
+    }
+
+    // Pending duplicates
+    match client.get_duplicates().await {
+        Ok(groups) => findings.push(Finding::new(
+            "duplicates",
+            FindingLevel::Info,
+            format!(
+                "{} duplicate groups ({} assets) pending",
+                groups.len(),
+                groups.iter().map(|g| g.assets.len()).sum::<usize>()
+            ),
+        )),
+        Err(e) => findings.push(
+            Finding::new("duplicates", FindingLevel::Error, format!("Cannot list duplicates: {}", e))
+                .with_hint("The API key needs the duplicate.read permission"),
+        ),
+    }
+
+    findings
+}
+
+/// Check the API key grants the permissions this tool needs.
+async fn check_scope(client: &ImmichClient) -> Finding {
+    match client.get_my_api_key().await {
+        Ok(key) => {
+            let missing = missing_permissions(&key.permissions);
+            if missing.is_empty() {
+                Finding::new("scope", FindingLevel::Ok, "API key has all required permissions")
+            } else {
+                Finding::new(
+                    "scope",
+                    FindingLevel::Warning,
+                    format!("API key '{}' is missing: {}", key.name, missing.join(", ")),
+                )
+                .with_hint("Edit the key's permissions, or create one with 'all'")
+            }
+        }
+        Err(e) => Finding::new(
+            "scope",
+            FindingLevel::Info,
+            format!("Could not read API key permissions: {}", e),
+        ),
+    }
+}
+
+/// Check the duplicate-detection job is idle and healthy.
+///
+/// Immich doesn't expose when a job last ran, so freshness is judged from
+/// the queue: running or waiting jobs mean the duplicate list is still
+/// changing.
+async fn check_duplicate_job(client: &ImmichClient) -> Finding {
+    let statuses = match client.get_job_statuses().await {
+        Ok(statuses) => statuses,
+        Err(ImmichError::Unauthorized { .. }) => {
+            return Finding::new(
+                "duplicate_job",
+                FindingLevel::Info,
+                "Job status requires an admin API key",
+            );
+        }
+        Err(e) => {
+            return Finding::new(
+                "duplicate_job",
+                FindingLevel::Warning,
+                format!("Could not read job status: {}", e),
+            );
+        }
+    };
+
+    let Some(job) = statuses.get(DUPLICATE_JOB) else {
+        return Finding::new(
+            "duplicate_job",
+            FindingLevel::Warning,
+            "Server reports no duplicate detection queue",
+        )
+        .with_hint("Enable duplicate detection under Administration → Settings → Machine Learning");
+    };
+
+    let counts = &job.job_counts;
+    if job.queue_status.is_paused {
+        Finding::new("duplicate_job", FindingLevel::Warning, "Duplicate detection queue is paused")
+            .with_hint("Resume it under Administration → Jobs")
+    } else if counts.active > 0 || counts.waiting > 0 || counts.delayed > 0 {
+        Finding::new(
+            "duplicate_job",
+            FindingLevel::Warning,
+            format!(
+                "Duplicate detection is running ({} active, {} waiting)",
+                counts.active,
+                counts.waiting + counts.delayed
+            ),
+        )
+        .with_hint("Wait for it to finish before analyzing; results may be incomplete")
+    } else if counts.failed > 0 {
+        Finding::new(
+            "duplicate_job",
+            FindingLevel::Warning,
+            format!("{} duplicate detection jobs failed", counts.failed),
+        )
+        .with_hint("Check the server logs, then re-run Duplicate Detection under Administration → Jobs")
+    } else {
+        Finding::new("duplicate_job", FindingLevel::Ok, "Duplicate detection is idle")
+            .with_hint("Immich doesn't report when it last ran; re-run it after large uploads")
+    }
+}
+
+/// Required permissions not granted by `permissions`.
+fn missing_permissions(permissions: &[String]) -> Vec<&'static str> {
+    if permissions.iter().any(|p| p == "all") {
+        return Vec::new();
+    }
+    REQUIRED_PERMISSIONS
+        .iter()
+        .copied()
+        .filter(|required| !permissions.iter().any(|p| p == required))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[test]
+    fn test_missing_permissions() {
+        assert!(missing_permissions(&["all".to_string()]).is_empty());
+        let granted = vec!["duplicate.read".to_string(), "asset.read".to_string()];
+        assert_eq!(
+            missing_permissions(&granted),
+            vec!["asset.download", "asset.update", "asset.delete", "asset.upload"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_against_mock_server() {
+        let server = MockServer::start(&synthetic_groups(2, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "doctor-key").unwrap();
+
+        let findings = diagnose(&client).await;
+        let level = |check: &str| findings.iter().find(|f| f.check == check).map(|f| f.level);
+
+        assert_eq!(level("version"), Some(FindingLevel::Ok));
+        assert_eq!(level("auth"), Some(FindingLevel::Ok));
+        assert_eq!(level("scope"), Some(FindingLevel::Ok));
+        assert_eq!(level("duplicate_job"), Some(FindingLevel::Ok));
+        let duplicates = findings.iter().find(|f| f.check == "duplicates").unwrap();
+        assert!(duplicates.message.starts_with("2 duplicate groups"));
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_unreachable_stops_early() {
+        let client = ImmichClient::new("http://127.0.0.1:9", "doctor-key").unwrap();
+        let findings = diagnose(&client).await;
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].level, FindingLevel::Error);
+    }
+}
@@ -0,0 +1,213 @@
+//! Embed Immich-side metadata into backup files.
+//!
+//! Metadata edited in the Immich web UI lives only in Immich's database, so a
+//! byte-for-byte backup doesn't carry it. When enabled, the executor writes
+//! each asset's GPS, capture time (in the asset's time zone), description,
+//! rating, and camera info into the backup itself as an XMP packet, the same
+//! one [`SidecarMetadata::to_xmp`] renders for sidecars.
+//!
+//! JPEG and PNG backups get the packet in place (an `APP1` segment or an
+//! `iTXt` chunk), replacing any XMP already there and leaving the original
+//! EXIF and image data untouched. Formats that can't carry it this way (HEIC,
+//! video, ...) get an XMP sidecar next to the backup instead.
+
+use std::path::Path;
+
+use img_parts::jpeg::{markers, Jpeg, JpegSegment};
+use img_parts::png::{Png, PngChunk};
+use img_parts::{Bytes, DynImage};
+
+use crate::error::{ImmichError, Result};
+use crate::sidecar::{sidecar_path, write_sidecar, SidecarMetadata};
+
+/// Namespace that prefixes an XMP `APP1` segment in a JPEG.
+const JPEG_XMP_NAMESPACE: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// Largest `APP1` payload a JPEG segment can hold (length field minus itself).
+const JPEG_SEGMENT_MAX: usize = u16::MAX as usize - 2;
+
+/// Keyword that marks an XMP `iTXt` chunk in a PNG.
+const PNG_XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp\0";
+
+/// Where [`embed_metadata`] put the metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Embedded {
+    /// Nothing to write
+    Nothing,
+    /// Written into the backup file itself
+    InFile,
+    /// The format can't carry XMP, so it went to a sidecar next to the backup
+    Sidecar,
+}
+
+/// Write metadata into a backup file in place.
+///
+/// Does nothing if `metadata` is empty. JPEG and PNG files are rewritten
+/// through a temporary file in the same directory, so an interrupted run
+/// never leaves a half-written backup. Other formats get an XMP sidecar,
+/// unless one is already there (e.g. from `write_sidecars`).
+///
+/// # Errors
+///
+/// Returns [`ImmichError::MetadataEmbed`] if the file looks like a JPEG or
+/// PNG but can't be parsed, or the packet is too large for a JPEG segment,
+/// and [`ImmichError::Io`] if the file can't be read or written.
+pub async fn embed_metadata(file_path: &Path, metadata: &SidecarMetadata) -> Result<Embedded> {
+    if metadata.is_empty() {
+        return Ok(Embedded::Nothing);
+    }
+
+    let original = Bytes::from(tokio::fs::read(file_path).await?);
+    let xmp = metadata.to_xmp();
+    let image = DynImage::from_bytes(original)
+        .map_err(|e| embed_error(file_path, &e.to_string()))?;
+
+    let embedded = match image {
+        Some(DynImage::Jpeg(jpeg)) => embed_jpeg(jpeg, &xmp).map_err(|e| embed_error(file_path, &e))?,
+        Some(DynImage::Png(png)) => embed_png(png, &xmp),
+        _ => {
+            if !tokio::fs::try_exists(sidecar_path(file_path)).await? {
+                write_sidecar(file_path, metadata).await?;
+            }
+            return Ok(Embedded::Sidecar);
+        }
+    };
+
+    let mut temp = file_path.as_os_str().to_os_string();
+    temp.push(".embed.tmp");
+    tokio::fs::write(&temp, &embedded).await?;
+    if let Err(e) = tokio::fs::rename(&temp, file_path).await {
+        let _ = tokio::fs::remove_file(&temp).await;
+        return Err(e.into());
+    }
+    Ok(Embedded::InFile)
+}
+
+/// Replace the XMP `APP1` segment of a JPEG, keeping it after the leading
+/// `APPn` segments (JFIF, EXIF) as readers expect.
+fn embed_jpeg(mut jpeg: Jpeg, xmp: &str) -> std::result::Result<Bytes, String> {
+    let mut contents = Vec::with_capacity(JPEG_XMP_NAMESPACE.len() + xmp.len());
+    contents.extend_from_slice(JPEG_XMP_NAMESPACE);
+    contents.extend_from_slice(xmp.as_bytes());
+    if contents.len() > JPEG_SEGMENT_MAX {
+        return Err(format!("XMP packet of {} bytes doesn't fit in a JPEG segment", xmp.len()));
+    }
+
+    let segments = jpeg.segments_mut();
+    segments.retain(|s| !(s.marker() == markers::APP1 && s.contents().starts_with(JPEG_XMP_NAMESPACE)));
+    let at = segments
+        .iter()
+        .position(|s| !(markers::APP0..=markers::APP15).contains(&s.marker()))
+        .unwrap_or(segments.len());
+    segments.insert(at, JpegSegment::new_with_contents(markers::APP1, Bytes::from(contents)));
+
+    Ok(jpeg.encoder().bytes())
+}
+
+/// Replace the XMP `iTXt` chunk of a PNG, placing it just before `IEND`.
+fn embed_png(mut png: Png, xmp: &str) -> Bytes {
+    // Keyword, then no compression, empty language and translated keyword
+    let mut contents = Vec::with_capacity(PNG_XMP_KEYWORD.len() + 4 + xmp.len());
+    contents.extend_from_slice(PNG_XMP_KEYWORD);
+    contents.extend_from_slice(&[0, 0, 0, 0]);
+    contents.extend_from_slice(xmp.as_bytes());
+
+    let chunks = png.chunks_mut();
+    chunks.retain(|c| !(&c.kind() == b"iTXt" && c.contents().starts_with(PNG_XMP_KEYWORD)));
+    let at = chunks.iter().position(|c| &c.kind() == b"IEND").unwrap_or(chunks.len());
+    chunks.insert(at, PngChunk::new(*b"iTXt", Bytes::from(contents)));
+
+    png.encoder().bytes()
+}
+
+fn embed_error(file_path: &Path, reason: &str) -> ImmichError {
+    ImmichError::MetadataEmbed(format!("{}: {}", file_path.display(), reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> SidecarMetadata {
+        SidecarMetadata {
+            latitude: Some(-33.5),
+            longitude: Some(151.25),
+            date_time_original: Some("2024-06-15T14:30:00+10:00".into()),
+            description: Some("Harbour & bridge".into()),
+            ..Default::default()
+        }
+    }
+
+    fn encode(format: image::ImageFormat) -> Vec<u8> {
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(4, 4).write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    fn assert_has_metadata(bytes: &[u8]) {
+        let text = String::from_utf8_lossy(bytes);
+        assert!(text.contains("<exif:GPSLatitude>33,30.000000S</exif:GPSLatitude>"), "{}", text);
+        assert!(text.contains("<exif:DateTimeOriginal>2024-06-15T14:30:00+10:00</exif:DateTimeOriginal>"));
+        assert!(text.contains("Harbour &amp; bridge"));
+    }
+
+    #[tokio::test]
+    async fn test_embed_jpeg_replaces_xmp_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, encode(image::ImageFormat::Jpeg)).unwrap();
+
+        // Embedding twice must leave a single, decodable XMP segment
+        assert_eq!(embed_metadata(&path, &metadata()).await.unwrap(), Embedded::InFile);
+        assert_eq!(embed_metadata(&path, &metadata()).await.unwrap(), Embedded::InFile);
+
+        let bytes = std::fs::read(&path).unwrap();
+        let jpeg = Jpeg::from_bytes(Bytes::from(bytes.clone())).unwrap();
+        let xmp_segments = jpeg
+            .segments_by_marker(markers::APP1)
+            .filter(|s| s.contents().starts_with(JPEG_XMP_NAMESPACE))
+            .count();
+        assert_eq!(xmp_segments, 1);
+        assert_has_metadata(&bytes);
+        assert_eq!(image::load_from_memory(&bytes).unwrap().width(), 4);
+        assert!(!sidecar_path(&path).exists());
+    }
+
+    #[tokio::test]
+    async fn test_embed_png_adds_xmp_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.png");
+        std::fs::write(&path, encode(image::ImageFormat::Png)).unwrap();
+
+        assert_eq!(embed_metadata(&path, &metadata()).await.unwrap(), Embedded::InFile);
+
+        let bytes = std::fs::read(&path).unwrap();
+        let png = Png::from_bytes(Bytes::from(bytes.clone())).unwrap();
+        assert_eq!(png.chunks().last().map(|c| c.kind()), Some(*b"IEND"));
+        assert_eq!(png.chunks_by_type(*b"iTXt").count(), 1);
+        assert_has_metadata(&bytes);
+        assert_eq!(image::load_from_memory(&bytes).unwrap().width(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_embed_other_format_writes_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.mp4");
+        std::fs::write(&path, b"not an image").unwrap();
+
+        assert_eq!(embed_metadata(&path, &metadata()).await.unwrap(), Embedded::Sidecar);
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"not an image");
+        assert_has_metadata(&std::fs::read(sidecar_path(&path)).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_embed_empty_metadata_leaves_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, b"untouched").unwrap();
+
+        assert_eq!(embed_metadata(&path, &SidecarMetadata::default()).await.unwrap(), Embedded::Nothing);
+        assert_eq!(std::fs::read(&path).unwrap(), b"untouched");
+    }
+}
@@ -6,6 +6,7 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum ImmichError {
     /// HTTP request failed (network error, timeout, etc.)
+    #[cfg(feature = "native")]
     #[error("HTTP request failed: {0}")]
     Http(#[from] reqwest::Error),
 
@@ -18,6 +19,48 @@ pub enum ImmichError {
         message: String,
     },
 
+    /// The requested resource does not exist (HTTP 404)
+    #[error("Not found: {message}")]
+    NotFound {
+        /// Error message from the API
+        message: String,
+    },
+
+    /// The API key was rejected or lacks permission (HTTP 401 or 403)
+    #[error("Unauthorized ({status}): {message}")]
+    Unauthorized {
+        /// HTTP status code
+        status: u16,
+        /// Error message from the API
+        message: String,
+    },
+
+    /// The server rejected the request as invalid (HTTP 400 or 422)
+    #[error("Validation failed: {message}")]
+    ValidationFailed {
+        /// Error message from the API, with multiple messages joined by "; "
+        message: String,
+    },
+
+    /// Every asset in a bulk add was already in the album (or already had
+    /// the tag)
+    #[error("Already present: {}", asset_ids.join(", "))]
+    AlreadyInAlbum {
+        /// Assets that were already present
+        asset_ids: Vec<String>,
+    },
+
+    /// The server is rate limiting requests (HTTP 429)
+    #[error("Rate limited by server{}", retry_after.map(|d| format!(" (retry after {}s)", d.as_secs())).unwrap_or_default())]
+    RateLimited {
+        /// Delay requested by the server's `Retry-After` header
+        retry_after: Option<std::time::Duration>,
+    },
+
+    /// An operation took longer than the configured timeout
+    #[error("Timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
     /// Invalid URL format
     #[error("Invalid URL: {0}")]
     Url(#[from] url::ParseError),
@@ -30,9 +73,103 @@ pub enum ImmichError {
     #[error("Asset not found: {0}")]
     AssetNotFound(String),
 
+    /// Some chunks of a chunked delete failed
+    #[error("Deleted {deleted} of {requested} assets; failed: {errors}")]
+    PartialDelete {
+        /// Assets deleted
+        deleted: usize,
+        /// Assets asked to be deleted
+        requested: usize,
+        /// Each failed chunk and its error
+        errors: String,
+    },
+
     /// File I/O error
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// CSV reading or writing failed
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// A review decision referenced an unknown group or asset
+    #[error("Invalid review decision: {0}")]
+    InvalidDecision(String),
+
+    /// Embedding metadata into a backup file failed
+    #[error("Metadata embed failed: {0}")]
+    MetadataEmbed(String),
+
+    /// A protected album or tag was transferred to the winner, but the
+    /// server doesn't show the winner in it afterwards
+    #[error("Winner {winner_id} not in {target} after transfer")]
+    TransferNotApplied {
+        /// The winner the album or tag was transferred to
+        winner_id: String,
+        /// The album or tag, e.g. `album 'Holidays'`
+        target: String,
+    },
+
+    /// Tenant configuration is invalid
+    #[error("Invalid tenant: {0}")]
+    InvalidTenant(String),
+
+    /// A test scenario definition is invalid (e.g. a duplicate code)
+    #[error("Invalid scenario: {0}")]
+    InvalidScenario(String),
+
+    /// An execution plan can't be applied (e.g. it was made for another server)
+    #[error("Invalid execution plan: {0}")]
+    InvalidPlan(String),
+
+    /// A run was asked to execute before its plan was confirmed
+    #[error("Run has not been confirmed")]
+    NotConfirmed,
+
+    /// Sending the completion notification failed
+    #[error("Notification failed: {0}")]
+    Notification(String),
+
+    /// An analysis report could not be read or has an unsupported schema
+    #[error("Invalid analysis report: {0}")]
+    InvalidReport(String),
+
+    /// A backup destination rejected or lost a backup
+    #[error("Backup store error: {0}")]
+    Backup(String),
+
+    /// The server's event stream failed or broke protocol
+    #[error("Event stream error: {0}")]
+    Events(String),
+
+    /// The Docker test environment couldn't be started or set up
+    #[cfg(feature = "docker")]
+    #[error("Docker test environment error: {0}")]
+    Docker(String),
+
+    /// Analysis store query failed
+    #[cfg(feature = "sqlite")]
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+}
+
+impl ImmichError {
+    /// HTTP status of an error response from the server, if this is one.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Api { status, .. } | Self::Unauthorized { status, .. } => Some(*status),
+            Self::NotFound { .. } => Some(404),
+            Self::ValidationFailed { .. } => Some(400),
+            Self::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this is Immich's own answer for a missing asset, as
+    /// opposed to a 404 from a wrong URL, a proxy, or a missing route.
+    pub fn is_asset_not_found(&self) -> bool {
+        matches!(self, Self::NotFound { message } if message.starts_with("Not found or no asset."))
+    }
 }
 
 /// Convenience type alias for Results using ImmichError.
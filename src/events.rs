@@ -0,0 +1,510 @@
+//! Live server events.
+//!
+//! Immich pushes events to its web and mobile clients over socket.io when
+//! assets are uploaded, changed, trashed, restored, or deleted. An
+//! [`EventStream`] (opened with [`ImmichClient::subscribe`]) speaks just
+//! enough of the protocol (a WebSocket carrying Engine.IO v4 and socket.io
+//! packets on the default namespace) to receive them as [`ServerEvent`]s.
+//!
+//! Immich has no event for duplicate detection finishing, so
+//! [`wait_for_duplicate_detection`] waits for the library to change and then
+//! for the duplicate-detection queue to drain, letting a watcher re-analyze
+//! as soon as there may be new duplicates instead of polling on an interval.
+
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::client::ImmichClient;
+use crate::error::{ImmichError, Result};
+
+/// Largest frame accepted from the server.
+const MAX_FRAME_LEN: u64 = 16 * 1024 * 1024;
+
+/// Name of the duplicate-detection queue in `GET /api/jobs`.
+const DUPLICATE_JOB: &str = "duplicateDetection";
+
+/// WebSocket opcodes.
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// An event pushed by the server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerEvent {
+    /// An asset finished uploading
+    AssetUploaded { asset_id: String },
+    /// An asset's metadata changed
+    AssetUpdated { asset_id: String },
+    /// Assets were moved to the trash
+    AssetsTrashed { asset_ids: Vec<String> },
+    /// Assets were restored from the trash
+    AssetsRestored { asset_ids: Vec<String> },
+    /// An asset was permanently deleted
+    AssetDeleted { asset_id: String },
+    /// Any other event, with its first argument
+    Other { name: String, payload: Value },
+}
+
+impl ServerEvent {
+    /// Parse a socket.io event from its name and arguments.
+    fn parse(name: &str, args: &[Value]) -> Self {
+        let first = args.first().cloned().unwrap_or(Value::Null);
+        let id = |v: &Value| v.get("id").and_then(Value::as_str).map(str::to_string);
+        let ids = |v: &Value| -> Option<Vec<String>> {
+            v.as_array()
+                .map(|ids| ids.iter().filter_map(|i| i.as_str().map(str::to_string)).collect())
+        };
+
+        let event = match name {
+            "on_upload_success" => id(&first).map(|asset_id| Self::AssetUploaded { asset_id }),
+            "on_asset_update" => id(&first).map(|asset_id| Self::AssetUpdated { asset_id }),
+            "on_asset_trash" => ids(&first).map(|asset_ids| Self::AssetsTrashed { asset_ids }),
+            "on_asset_restore" => ids(&first).map(|asset_ids| Self::AssetsRestored { asset_ids }),
+            "on_asset_delete" => first.as_str().map(|id| Self::AssetDeleted { asset_id: id.to_string() }),
+            _ => None,
+        };
+        event.unwrap_or_else(|| Self::Other {
+            name: name.to_string(),
+            payload: first,
+        })
+    }
+
+    /// Returns true if the event adds, changes, or removes assets, and so
+    /// may change the server's duplicate groups.
+    pub fn changes_library(&self) -> bool {
+        !matches!(self, Self::Other { .. })
+    }
+}
+
+/// A subscription to the server's events.
+///
+/// Pings from the server are answered while waiting for the next event.
+/// [`next`](Self::next) is cancel safe, so it can be raced against a timeout.
+pub struct EventStream<S> {
+    io: S,
+
+    /// Bytes read but not yet parsed into a frame
+    read_buf: Vec<u8>,
+
+    /// Fragments of a message not yet complete
+    message: Vec<u8>,
+
+    /// Frames not yet completely written
+    outgoing: Vec<u8>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> EventStream<S> {
+    /// Join the default socket.io namespace over an open WebSocket.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::Events`] if the server refuses the connection
+    /// or does not speak the protocol.
+    pub async fn handshake(io: S) -> Result<Self> {
+        let mut stream = Self {
+            io,
+            read_buf: Vec::new(),
+            message: Vec::new(),
+            outgoing: Vec::new(),
+        };
+
+        match stream.read_packet().await? {
+            Some(packet) if packet.starts_with('0') => {}
+            other => return Err(protocol_error("open packet", other)),
+        }
+        stream.write_text("40").await?;
+        loop {
+            match stream.read_packet().await? {
+                Some(packet) if packet.starts_with("40") => return Ok(stream),
+                Some(packet) if packet.starts_with("44") => {
+                    return Err(ImmichError::Events(format!("connection refused: {}", &packet[2..])));
+                }
+                Some(packet) if packet.starts_with('2') => stream.write_text("3").await?,
+                other => return Err(protocol_error("namespace connect", other)),
+            }
+        }
+    }
+
+    /// Wait for the next event. Returns `None` once the server closes the
+    /// connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails or a packet is malformed.
+    pub async fn next(&mut self) -> Result<Option<ServerEvent>> {
+        while let Some(packet) = self.read_packet().await? {
+            let Some(kind) = packet.get(..1) else {
+                continue;
+            };
+            let data = &packet[1..];
+            match kind {
+                // Engine.IO ping
+                "2" => self.write_text("3").await?,
+                // Engine.IO close, or socket.io disconnect
+                "1" => return Ok(None),
+                "4" if data.starts_with('1') => return Ok(None),
+                // socket.io event, possibly with an ack ID before the arguments
+                "4" if data.starts_with('2') => {
+                    let args = data[1..].trim_start_matches(|c: char| c.is_ascii_digit());
+                    let args: Vec<Value> = serde_json::from_str(args)
+                        .map_err(|e| ImmichError::Events(format!("malformed event: {}", e)))?;
+                    if let Some(name) = args.first().and_then(Value::as_str) {
+                        return Ok(Some(ServerEvent::parse(name, &args[1..])));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Read the next text message, answering WebSocket pings. Returns `None`
+    /// once the connection is closed.
+    async fn read_packet(&mut self) -> Result<Option<String>> {
+        self.flush_outgoing().await?;
+        loop {
+            let Some((fin, opcode, payload)) = self.read_frame().await? else {
+                return Ok(None);
+            };
+            match opcode {
+                OP_CLOSE => {
+                    let _ = self.write_frame(OP_CLOSE, &[]).await;
+                    return Ok(None);
+                }
+                OP_PING => self.write_frame(OP_PONG, &payload).await?,
+                OP_PONG => {}
+                OP_TEXT | OP_BINARY | OP_CONTINUATION => {
+                    self.message.extend_from_slice(&payload);
+                    if (self.message.len() as u64) > MAX_FRAME_LEN {
+                        return Err(ImmichError::Events("message too large".to_string()));
+                    }
+                    if fin {
+                        return String::from_utf8(std::mem::take(&mut self.message))
+                            .map(Some)
+                            .map_err(|_| ImmichError::Events("message is not UTF-8".to_string()));
+                    }
+                }
+                other => return Err(ImmichError::Events(format!("unknown opcode {:#x}", other))),
+            }
+        }
+    }
+
+    /// Read one WebSocket frame. Returns `None` at end of stream.
+    async fn read_frame(&mut self) -> Result<Option<(bool, u8, Vec<u8>)>> {
+        loop {
+            if let Some(frame) = take_frame(&mut self.read_buf)? {
+                return Ok(Some(frame));
+            }
+            // `read_buf` only appends once bytes arrive, so cancelling loses nothing
+            if self.io.read_buf(&mut self.read_buf).await? == 0 {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Send a text message.
+    async fn write_text(&mut self, text: &str) -> Result<()> {
+        self.write_frame(OP_TEXT, text.as_bytes()).await
+    }
+
+    /// Send one masked frame, as clients must.
+    async fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<()> {
+        let frame = &mut self.outgoing;
+        frame.push(0x80 | opcode);
+        match payload.len() {
+            len @ 0..=125 => frame.push(0x80 | len as u8),
+            len @ 126..=0xFFFF => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        let [a, b, c, d, ..] = *uuid::Uuid::new_v4().as_bytes();
+        let mask = [a, b, c, d];
+        frame.extend_from_slice(&mask);
+        let start = frame.len();
+        frame.extend_from_slice(payload);
+        apply_mask(&mut frame[start..], mask);
+
+        self.flush_outgoing().await
+    }
+
+    /// Write queued frames. Bytes are only dropped from the queue once
+    /// written, so a cancelled write is finished by the next call.
+    async fn flush_outgoing(&mut self) -> Result<()> {
+        while !self.outgoing.is_empty() {
+            let written = self.io.write(&self.outgoing).await?;
+            if written == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::WriteZero).into());
+            }
+            self.outgoing.drain(..written);
+        }
+        self.io.flush().await?;
+        Ok(())
+    }
+}
+
+/// Remove the first complete frame from `buf`, if there is one.
+fn take_frame(buf: &mut Vec<u8>) -> Result<Option<(bool, u8, Vec<u8>)>> {
+    let [first, second, ..] = buf[..] else {
+        return Ok(None);
+    };
+    let fin = first & 0x80 != 0;
+    let opcode = first & 0x0F;
+    let masked = second & 0x80 != 0;
+    let (len, mut offset) = match second & 0x7F {
+        126 if buf.len() >= 4 => (u64::from(u16::from_be_bytes([buf[2], buf[3]])), 4),
+        127 if buf.len() >= 10 => {
+            let mut len = [0; 8];
+            len.copy_from_slice(&buf[2..10]);
+            (u64::from_be_bytes(len), 10)
+        }
+        126 | 127 => return Ok(None),
+        len => (u64::from(len), 2),
+    };
+    if len > MAX_FRAME_LEN {
+        return Err(ImmichError::Events(format!("frame of {} bytes is too large", len)));
+    }
+
+    let mask_len = if masked { 4 } else { 0 };
+    if buf.len() < offset + mask_len + len as usize {
+        return Ok(None);
+    }
+    let mask = match buf[offset..offset + mask_len] {
+        [a, b, c, d] => Some([a, b, c, d]),
+        _ => None,
+    };
+    offset += mask_len;
+
+    let mut payload: Vec<u8> = buf.drain(..offset + len as usize).skip(offset).collect();
+    if let Some(mask) = mask {
+        apply_mask(&mut payload, mask);
+    }
+    Ok(Some((fin, opcode, payload)))
+}
+
+/// The `Sec-WebSocket-Accept` value a server must answer `key` with:
+/// base64 of the SHA-1 of the key and the protocol's GUID.
+pub(crate) fn accept_key(key: &str) -> String {
+    use base64::Engine;
+
+    let digest = sha1(format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key).as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+/// SHA-1, only to check the WebSocket handshake (not for security).
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (out, word) in digest.chunks_exact_mut(4).zip(h) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// XOR a payload with a frame's masking key.
+fn apply_mask(payload: &mut [u8], mask: [u8; 4]) {
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+}
+
+/// Error for an unexpected packet (or end of stream) during the handshake.
+fn protocol_error(expected: &str, got: Option<String>) -> ImmichError {
+    match got {
+        Some(packet) => ImmichError::Events(format!("expected {}, got {:?}", expected, packet)),
+        None => ImmichError::Events(format!("connection closed before {}", expected)),
+    }
+}
+
+/// Wait until there may be new duplicates to analyze: for an event that
+/// changes the library, then for events to stop arriving for `settle`, then
+/// for the duplicate-detection queue to drain (checked every `settle`).
+///
+/// Returns the library-changing events seen, or an empty list if the server
+/// closed the connection first.
+///
+/// # Errors
+///
+/// Returns an error if the connection fails or the job status can't be fetched.
+pub async fn wait_for_duplicate_detection<S>(
+    client: &ImmichClient,
+    events: &mut EventStream<S>,
+    settle: Duration,
+) -> Result<Vec<ServerEvent>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut changes = Vec::new();
+    loop {
+        match events.next().await? {
+            Some(event) if event.changes_library() => {
+                changes.push(event);
+                break;
+            }
+            Some(_) => {}
+            None => return Ok(changes),
+        }
+    }
+
+    // Uploads arrive in bursts; wait for a quiet spell
+    while let Ok(next) = tokio::time::timeout(settle, events.next()).await {
+        match next? {
+            Some(event) if event.changes_library() => changes.push(event),
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    loop {
+        let jobs = client.get_job_statuses().await?;
+        let busy = jobs.get(DUPLICATE_JOB).is_some_and(|job| {
+            job.queue_status.is_active || job.job_counts.active > 0 || job.job_counts.waiting > 0
+        });
+        if !busy {
+            return Ok(changes);
+        }
+        tokio::time::sleep(settle).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+    use tokio::io::DuplexStream;
+
+    /// Write an unmasked text frame, as a server does.
+    async fn send(server: &mut DuplexStream, text: &str) {
+        let mut frame = vec![0x81, text.len() as u8];
+        frame.extend_from_slice(text.as_bytes());
+        server.write_all(&frame).await.unwrap();
+    }
+
+    /// Read a masked text frame written by the client.
+    async fn receive(server: &mut DuplexStream) -> String {
+        let mut header = [0u8; 6];
+        server.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[0], 0x81);
+        assert_ne!(header[1] & 0x80, 0, "client frames must be masked");
+        let mut payload = vec![0u8; (header[1] & 0x7F) as usize];
+        server.read_exact(&mut payload).await.unwrap();
+        apply_mask(&mut payload, header[2..6].try_into().unwrap());
+        String::from_utf8(payload).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let (client_io, mut server) = tokio::io::duplex(4096);
+        let server_task = tokio::spawn(async move {
+            send(&mut server, r#"0{"sid":"abc","pingInterval":25000,"pingTimeout":20000}"#).await;
+            assert_eq!(receive(&mut server).await, "40");
+            send(&mut server, r#"40{"sid":"def"}"#).await;
+            send(&mut server, "2").await;
+            send(&mut server, r#"42["on_asset_trash",["a1","a2"]]"#).await;
+            send(&mut server, r#"42["on_new_release",{"version":"v2"}]"#).await;
+            send(&mut server, r#"42["on_upload_success",{"id":"a3"}]"#).await;
+            assert_eq!(receive(&mut server).await, "3");
+            server.write_all(&[0x88, 0x00]).await.unwrap();
+            server
+        });
+
+        let mut events = EventStream::handshake(client_io).await.unwrap();
+        let trashed = events.next().await.unwrap().unwrap();
+        assert_eq!(trashed, ServerEvent::AssetsTrashed { asset_ids: vec!["a1".into(), "a2".into()] });
+        let other = events.next().await.unwrap().unwrap();
+        assert!(!other.changes_library());
+        assert!(matches!(other, ServerEvent::Other { ref name, .. } if name == "on_new_release"));
+        assert_eq!(
+            events.next().await.unwrap(),
+            Some(ServerEvent::AssetUploaded { asset_id: "a3".into() })
+        );
+        assert_eq!(events.next().await.unwrap(), None);
+        server_task.await.unwrap();
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // Example handshake from RFC 6455, section 1.3
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        assert_eq!(
+            sha1(b"abc").iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_duplicate_detection() {
+        let mock = MockServer::start(&synthetic_groups(1, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&mock.url(), "test-key").unwrap();
+        let (client_io, mut server) = tokio::io::duplex(4096);
+        tokio::spawn(async move {
+            send(&mut server, r#"0{"sid":"abc"}"#).await;
+            receive(&mut server).await;
+            send(&mut server, "40").await;
+            send(&mut server, r#"42["on_config_update"]"#).await;
+            send(&mut server, r#"42["on_asset_delete","a1"]"#).await;
+            send(&mut server, r#"42["on_asset_delete","a2"]"#).await;
+            // Keep the connection open past the settle time
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            drop(server);
+        });
+
+        let mut events = EventStream::handshake(client_io).await.unwrap();
+        let changes = wait_for_duplicate_detection(&client, &mut events, Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                ServerEvent::AssetDeleted { asset_id: "a1".into() },
+                ServerEvent::AssetDeleted { asset_id: "a2".into() },
+            ]
+        );
+    }
+}
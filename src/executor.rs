@@ -3,28 +3,46 @@
 //! This module provides the `Executor` struct which handles rate-limited,
 //! concurrent execution of duplicate processing operations including
 //! downloading backups and deleting duplicates.
+//!
+//! Each group is first planned, deciding the concrete operations to perform
+//! without changing anything, and then the plan is applied. The two steps
+//! can also be run separately, so a saved [`ExecutionPlan`] can be inspected
+//! and approved before anything is mutated.
 
-use std::num::NonZeroU32;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
-use governor::{Quota, RateLimiter};
+use chrono::DateTime;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use nonzero_ext::nonzero;
-use tokio::sync::Semaphore;
+use parking_lot::Mutex;
+use tokio::sync::{OwnedMutexGuard, Semaphore};
 
-use crate::client::ImmichClient;
-use crate::error::Result;
+use crate::archive::BackupArchive;
+use crate::backup::BackupStore;
+use crate::client::{delete_in_chunks, ImmichClient};
+use crate::clock::{Clock, SystemClock};
+use crate::embed::embed_metadata;
+use crate::error::{ImmichError, Result};
+use crate::history::{self, ExecutionEvent, HistoryStore, RunRecorder};
+use crate::integrity::{check_bytes, check_file, IntegrityIssue, SNIFF_LEN};
+use crate::ledger::{Ledger, LedgerAction, LedgerEntry};
+use crate::metrics::Metrics;
+use crate::rate_limit::AdaptiveRateLimiter;
 use crate::models::{
-    ConsolidationResult, ExecutionConfig, ExecutionReport, GroupResult, OperationResult,
+    AlbumResponse, AssetState, BackupSink, ConsolidationResult, CrossLibraryPolicy, DescriptionPolicy, ExecutionConfig, ExecutionMode,
+    ExecutionPlan, ExecutionReport, ExifInfo, FieldTransfer, GroupPlan, GroupResult, MetadataField, MetadataSources,
+    OperationResult, PlannedOperation, Promotion, PromotionKind, SharedAlbumPolicy, SharedLinkIndex, SharedLinkPolicy,
+    STALE_ANALYSIS,
 };
-use crate::scoring::DuplicateAnalysis;
+use crate::scoring::{DuplicateAnalysis, ReviewReason};
+use crate::sidecar::{sidecar_path, write_sidecar, SidecarMetadata};
+use crate::tagging;
 
-/// Type alias for the governor rate limiter.
-type DirectRateLimiter = RateLimiter<
-    governor::state::NotKeyed,
-    governor::state::InMemoryState,
-    governor::clock::DefaultClock,
->;
+/// Groups planned ahead together, so winners needing the same metadata
+/// update can share one bulk request.
+const METADATA_BATCH_SIZE: usize = 50;
 
 /// Executor for duplicate processing operations.
 ///
@@ -52,14 +70,46 @@ pub struct Executor {
     /// The Immich API client
     client: ImmichClient,
 
-    /// Rate limiter for API requests
-    rate_limiter: DirectRateLimiter,
+    /// Rate limiter for API requests, adapting to 429 responses
+    rate_limiter: Arc<AdaptiveRateLimiter>,
 
     /// Semaphore for concurrent operation control
     concurrency: Arc<Semaphore>,
 
     /// Execution configuration
     config: ExecutionConfig,
+
+    /// Where run histories are recorded, if anywhere (see
+    /// [`with_history`](Self::with_history) and `history_dir`)
+    history: Option<Arc<dyn HistoryStore>>,
+
+    /// Event recorder for the run in progress, if history is recorded
+    recorder: Mutex<Option<RunRecorder>>,
+
+    /// First error writing the event history, reported with the run
+    history_error: Mutex<Option<String>>,
+
+    /// One lock per album, so changes to the same album never interleave
+    album_locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+
+    /// Winners whose planned metadata update was already sent in a bulk request
+    bulk_updated: Mutex<HashSet<String>>,
+
+    /// Archive for the run in progress, if backups go to an archive
+    archive: Mutex<Option<Arc<dyn BackupArchive>>>,
+
+    /// Shared links of the run in progress, listed when first needed
+    shared_links: Mutex<Option<Arc<SharedLinkIndex>>>,
+
+    /// Where loser backups are saved
+    store: Arc<dyn BackupStore>,
+
+    /// Time source for plans, ledger entries, and run history
+    clock: Arc<dyn Clock>,
+
+    /// User the API key belongs to, looked up when first needed (`None` if
+    /// it couldn't be)
+    user_id: tokio::sync::OnceCell<Option<String>>,
 }
 
 impl Executor {
@@ -70,44 +120,137 @@ impl Executor {
     /// * `client` - The Immich API client to use for operations
     /// * `config` - Execution configuration (rate limits, concurrency, backup dir)
     pub fn new(client: ImmichClient, config: ExecutionConfig) -> Self {
-        // Create rate limiter with configured requests per second
-        let quota = Quota::per_second(
-            NonZeroU32::new(config.requests_per_sec).unwrap_or(nonzero!(10u32)),
-        );
-        let rate_limiter = RateLimiter::direct(quota);
+        // Start at the configured requests per second (10 if unset)
+        let requests_per_sec = if config.requests_per_sec == 0 { 10 } else { config.requests_per_sec };
+        let rate_limiter = Arc::new(AdaptiveRateLimiter::new(requests_per_sec));
 
         // Create semaphore for concurrency control
         let concurrency = Arc::new(Semaphore::new(config.max_concurrent));
+        let store = config.backup_target.store(&config.backup_dir);
 
         Self {
             client,
             rate_limiter,
             concurrency,
             config,
+            history: None,
+            recorder: Mutex::new(None),
+            history_error: Mutex::new(None),
+            album_locks: Mutex::new(HashMap::new()),
+            bulk_updated: Mutex::new(HashSet::new()),
+            archive: Mutex::new(None),
+            shared_links: Mutex::new(None),
+            store,
+            clock: Arc::new(SystemClock),
+            user_id: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    /// Save backups to `store` instead of the configured backup target.
+    pub fn with_backup_store(mut self, store: Arc<dyn BackupStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Record each run's history in `store` instead of the configured
+    /// `history_dir`.
+    pub fn with_history(mut self, store: Arc<dyn HistoryStore>) -> Self {
+        self.history = Some(store);
+        self
+    }
+
+    /// Take timestamps from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Pace requests with `rate_limiter`, shared with whatever else talks to
+    /// the same server, instead of one of the executor's own.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<AdaptiveRateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Every shared link, listed once for the run in progress and reused
+    /// by each group it plans.
+    async fn shared_link_index(&self) -> Result<Arc<SharedLinkIndex>> {
+        if let Some(index) = self.shared_links.lock().clone() {
+            return Ok(index);
+        }
+        let index = Arc::new(self.rate_limited(|| self.client.shared_link_index()).await?);
+        *self.shared_links.lock() = Some(Arc::clone(&index));
+        Ok(index)
+    }
+
+    /// Lock an album while changing it.
+    ///
+    /// Groups changing the same album take turns, so one group's add and
+    /// remove (and their retries) can't interleave with another's; different
+    /// albums are changed concurrently. Hold one album lock at a time.
+    async fn lock_album(&self, album_id: &str) -> OwnedMutexGuard<()> {
+        let lock = Arc::clone(self.album_locks.lock().entry(album_id.to_string()).or_default());
+        lock.lock_owned().await
+    }
+
+    /// Append an event to the run's history, if one is being recorded.
+    fn record_event(&self, event: ExecutionEvent) {
+        let Some(recorder) = self.recorder.lock().clone() else {
+            return;
+        };
+        if let Err(e) = recorder.record(event) {
+            self.history_error.lock().get_or_insert_with(|| e.to_string());
         }
     }
 
     /// Wait for rate limit and acquire concurrency permit before executing an operation.
     ///
     /// This helper ensures all API operations respect rate limits and concurrency bounds.
-    async fn rate_limited<F, T>(&self, op: F) -> Result<T>
+    /// If the server answers 429, the rate limiter backs off and the operation is
+    /// retried (up to [`MAX_RATE_LIMIT_ATTEMPTS`](crate::rate_limit::MAX_RATE_LIMIT_ATTEMPTS)
+    /// attempts in all).
+    async fn rate_limited<F, Fut, T>(&self, op: F) -> Result<T>
     where
-        F: std::future::Future<Output = Result<T>>,
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
     {
-        // Wait for rate limit allowance
-        self.rate_limiter.until_ready().await;
+        let op = &op;
+        self.rate_limiter
+            .run(|| async move {
+                // Acquire concurrency permit (released at the end of this attempt)
+                let _permit = self.concurrency.acquire().await.expect("semaphore closed");
 
-        // Acquire concurrency permit (automatically released when dropped)
-        let _permit = self.concurrency.acquire().await.expect("semaphore closed");
+                // Execute the operation, timing it if metrics are being collected
+                match &self.config.metrics {
+                    Some(metrics) => {
+                        let started = std::time::Instant::now();
+                        let result = self.with_operation_timeout(op()).await;
+                        metrics.observe_request(started.elapsed());
+                        result
+                    }
+                    None => self.with_operation_timeout(op()).await,
+                }
+            })
+            .await
+    }
 
-        // Execute the operation
-        op.await
+    /// Fail `operation` with [`ImmichError::Timeout`] if it takes longer
+    /// than [`ExecutionConfig::operation_timeout`].
+    async fn with_operation_timeout<T>(&self, operation: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        match self.config.operation_timeout {
+            Some(limit) => tokio::time::timeout(limit, operation)
+                .await
+                .unwrap_or(Err(ImmichError::Timeout(limit))),
+            None => operation.await,
+        }
     }
 
     /// Execute processing for all duplicate groups.
     ///
     /// Iterates through all groups, downloading backups and deleting duplicates
-    /// for each. Shows progress via console progress bars.
+    /// for each. Shows progress via console progress bars. If a notification
+    /// is configured, it is sent once every group has been processed; a
+    /// failure to send is recorded in the report rather than returned.
     ///
     /// # Arguments
     ///
@@ -117,6 +260,81 @@ impl Executor {
     ///
     /// An execution report summarizing all operations and their outcomes.
     pub async fn execute_all(&self, groups: &[DuplicateAnalysis]) -> ExecutionReport {
+        self.execute_all_with(groups, |_| {}).await
+    }
+
+    /// Like [`execute_all`](Self::execute_all), calling `on_group` as each
+    /// group finishes, e.g. to checkpoint progress so an interrupted run can
+    /// be resumed.
+    pub async fn execute_all_with<F>(&self, groups: &[DuplicateAnalysis], on_group: F) -> ExecutionReport
+    where
+        F: FnMut(&GroupResult),
+    {
+        let work: Vec<GroupWork> = groups.iter().map(GroupWork::Analysis).collect();
+        self.run(&work, on_group).await
+    }
+
+    /// Plan the processing of all duplicate groups, without changing anything.
+    ///
+    /// See [`plan_group`](Self::plan_group). The plan can be saved, edited,
+    /// and later carried out with [`apply`](Self::apply).
+    pub async fn plan(&self, groups: &[DuplicateAnalysis]) -> ExecutionPlan {
+        let mut plan = ExecutionPlan {
+            created_at: self.clock.now(),
+            server_url: self.client.base_url().to_string(),
+            force_delete: self.config.force_delete,
+            groups: Vec::with_capacity(groups.len()),
+        };
+        for analysis in groups {
+            plan.groups.push(self.plan_group(analysis).await);
+        }
+        self.shared_links.lock().take();
+        plan
+    }
+
+    /// Apply a plan made by [`plan`](Self::plan), possibly edited since.
+    ///
+    /// Groups are processed as by [`execute_all`](Self::execute_all), with
+    /// the same safeguards: a loser is only deleted if the plan deletes it
+    /// and it was backed up in this run.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidPlan`], without changing anything, if
+    /// the plan was made for another server or with a different
+    /// `force_delete` setting, or if a group would download or delete an
+    /// asset other than its losers (see [`GroupPlan::validate`]).
+    pub async fn apply(&self, plan: &ExecutionPlan) -> Result<ExecutionReport> {
+        if plan.server_url.trim_end_matches('/') != self.client.base_url().trim_end_matches('/') {
+            return Err(ImmichError::InvalidPlan(format!(
+                "plan is for {}, not {}",
+                plan.server_url,
+                self.client.base_url()
+            )));
+        }
+        if plan.force_delete != self.config.force_delete {
+            let mode = |force| if force { "permanent delete" } else { "trash" };
+            return Err(ImmichError::InvalidPlan(format!(
+                "plan was made for {}, but the executor is configured for {}",
+                mode(plan.force_delete),
+                mode(self.config.force_delete)
+            )));
+        }
+
+        for group in &plan.groups {
+            group.validate()?;
+        }
+
+        let work: Vec<GroupWork> = plan.groups.iter().map(GroupWork::Plan).collect();
+        Ok(self.run(&work, |_| {}).await)
+    }
+
+    /// Process groups one by one (planned in batches), with progress bars,
+    /// history, and notification.
+    async fn run<F>(&self, groups: &[GroupWork<'_>], mut on_group: F) -> ExecutionReport
+    where
+        F: FnMut(&GroupResult),
+    {
         let mut report = ExecutionReport::new();
 
         if groups.is_empty() {
@@ -149,31 +367,186 @@ impl Executor {
             return report;
         }
 
-        // Process each group
-        for analysis in groups {
-            group_pb.set_message(format!(
-                "Processing group {} ({} losers)",
-                analysis.duplicate_id,
-                analysis.losers.len()
-            ));
+        let sink = self.config.backup_sink;
+        if sink != BackupSink::Directory && !self.store.is_local() {
+            overall_pb.finish_with_message(format!("{} backups need a local backup directory", sink));
+            report.archive_error = Some(format!("{} backups need a local backup directory", sink));
+            return report;
+        }
+        match sink.open(&self.config.backup_dir, self.client.base_url(), self.clock.as_ref()).await {
+            Ok(archive) => {
+                report.backup_archive = archive.as_ref().map(|a| a.path().to_path_buf());
+                *self.archive.lock() = archive;
+            }
+            Err(e) => {
+                overall_pb.finish_with_message(format!("Failed to create backup archive: {}", e));
+                report.archive_error = Some(e.to_string());
+                return report;
+            }
+        }
+
+        let store = match (&self.history, &self.config.history_dir) {
+            (Some(store), _) => Some(Ok(Arc::clone(store))),
+            (None, Some(dir)) => Some(history::open(dir)),
+            (None, None) => None,
+        };
+        match store.map(|store| RunRecorder::start(store?, Arc::clone(&self.clock))) {
+            Some(Ok(recorder)) => {
+                report.run_id = Some(recorder.run_id().to_string());
+                *self.recorder.lock() = Some(recorder);
+            }
+            Some(Err(e)) => *self.history_error.lock() = Some(e.to_string()),
+            None => {}
+        }
+        self.record_event(ExecutionEvent::RunStarted {
+            server_url: self.client.base_url().to_string(),
+            groups: groups.len(),
+            force_delete: self.config.force_delete,
+        });
+
+        // Process groups in batches: the batch's analyzed groups are planned
+        // first, so winners needing the same metadata update share one request
+        for batch in groups.chunks(METADATA_BATCH_SIZE) {
+            let mut plans = Vec::with_capacity(batch.len());
+            for work in batch {
+                plans.push(match work {
+                    GroupWork::Analysis(analysis) => {
+                        group_pb.set_message(format!("Planning group {}", analysis.duplicate_id));
+                        Cow::Owned(self.plan_group(analysis).await)
+                    }
+                    GroupWork::Plan(group) => Cow::Borrowed(*group),
+                });
+            }
+            self.bulk_update_metadata(&plans).await;
 
-            let result = self.execute_group(analysis, &group_pb).await;
-            report.add_group_result(result);
+            for (work, group) in batch.iter().zip(&plans) {
+                group_pb.set_message(format!(
+                    "Processing group {} ({} losers)",
+                    work.duplicate_id(),
+                    work.losers()
+                ));
 
-            overall_pb.inc(1);
+                let result = self.apply_group(group, &group_pb).await;
+                on_group(&result);
+                report.add_group_result(result);
+
+                overall_pb.inc(1);
+            }
+            // Forget winners whose group never reached its metadata step
+            self.bulk_updated.lock().clear();
         }
 
         overall_pb.finish_with_message("Complete");
         group_pb.finish_and_clear();
+        report.rate_limit = Some(self.rate_limiter.stats());
+        report.finished_at = Some(self.clock.now());
+
+        self.shared_links.lock().take();
+        let archive = self.archive.lock().take();
+        if let Some(archive) = archive
+            && let Err(e) = archive.finish().await
+        {
+            report.archive_error = Some(format!("backups archived, but the manifest could not be written: {}", e));
+        }
+
+        if let Some(tag) = &self.config.tag_winners {
+            self.tag_winners(tag, &mut report).await;
+        }
+
+        self.record_event(ExecutionEvent::RunFinished {
+            deleted: report.deleted,
+            failed: report.failed,
+            skipped: report.skipped,
+        });
+        *self.recorder.lock() = None;
+        report.history_error = self.history_error.lock().take();
+
+        if let Some(notification) = &self.config.notification
+            && let Err(e) = notification.send(&report).await
+        {
+            report.notification_error = Some(e.to_string());
+        }
 
         report
     }
 
+    /// Send the metadata updates that several of `plans` share as one bulk
+    /// request each, remembering their winners so the groups don't send
+    /// them again. After a failed bulk request each group sends its own.
+    ///
+    /// Each bulk request is recorded in the run's history, and each group
+    /// it covered notes it in its consolidation result.
+    async fn bulk_update_metadata(&self, plans: &[Cow<'_, GroupPlan>]) {
+        type UpdateKey<'a> = (Option<(u64, u64)>, Option<&'a str>, Option<&'a str>);
+        let mut shared: BTreeMap<UpdateKey<'_>, Vec<(String, String)>> = BTreeMap::new();
+        let operations = plans
+            .iter()
+            .flat_map(|plan| plan.operations.iter().map(move |op| (&plan.duplicate_id, op)));
+        for (group_id, op) in operations {
+            if let PlannedOperation::UpdateMetadata {
+                asset_id,
+                latitude,
+                longitude,
+                date_time_original,
+                description,
+                ..
+            } = op
+            {
+                let gps = latitude.zip(*longitude).map(|(lat, lon)| (lat.to_bits(), lon.to_bits()));
+                let key = (gps, date_time_original.as_deref(), description.as_deref());
+                shared.entry(key).or_default().push((group_id.clone(), asset_id.clone()));
+            }
+        }
+
+        for ((gps, date_time_original, description), groups) in shared {
+            if groups.len() < 2 {
+                continue;
+            }
+            let (group_ids, ids): (Vec<String>, Vec<String>) = groups.into_iter().unzip();
+            let (latitude, longitude) = gps.map(|(lat, lon)| (f64::from_bits(lat), f64::from_bits(lon))).unzip();
+            let sent = self
+                .rate_limited(|| async {
+                    self.client
+                        .update_assets_bulk(&ids, latitude, longitude, date_time_original, description)
+                        .await
+                })
+                .await
+                .is_ok();
+            if sent {
+                self.bulk_updated.lock().extend(ids.iter().cloned());
+                self.record_event(ExecutionEvent::MetadataBulkUpdated {
+                    group_ids,
+                    winner_ids: ids,
+                });
+            }
+        }
+    }
+
+    /// Tag the winner of every group that deleted or replaced a loser.
+    async fn tag_winners(&self, tag: &str, report: &mut ExecutionReport) {
+        let winners: Vec<String> = report
+            .results
+            .iter()
+            .filter(|r| !r.deleted_ids.is_empty() || !r.promotions.is_empty())
+            .map(|r| r.winner_id.clone())
+            .collect();
+        if winners.is_empty() {
+            return;
+        }
+
+        match self
+            .rate_limited(|| async { tagging::tag_assets(&self.client, tag, &winners).await })
+            .await
+        {
+            Ok(_) => report.tagged_winners = winners,
+            Err(e) => report.tagging_error = Some(e.to_string()),
+        }
+    }
+
     /// Execute processing for a single duplicate group.
     ///
-    /// 1. Consolidates metadata from losers to winner (GPS, datetime, description)
-    /// 2. Downloads backup copies of all loser assets
-    /// 3. Deletes only those that were successfully downloaded
+    /// Plans the group with [`plan_group`](Self::plan_group) and applies the
+    /// plan with [`apply_group`](Self::apply_group).
     ///
     /// # Arguments
     ///
@@ -188,216 +561,2860 @@ impl Executor {
         analysis: &DuplicateAnalysis,
         pb: &ProgressBar,
     ) -> GroupResult {
-        let mut download_results = Vec::new();
+        pb.set_message("Planning");
+        let group = self.plan_group(analysis).await;
+        self.apply_group(&group, pb).await
+    }
 
-        // Step 1: Consolidate metadata from losers to winner
-        pb.set_message("Checking metadata consolidation");
-        let consolidation_result = self.consolidate_metadata(analysis).await;
+    /// Plan the operations for a single duplicate group, without changing anything.
+    ///
+    /// Groups whose assets belong to different users are skipped entirely
+    /// unless [`ExecutionConfig::allow_cross_owner`] is set. So are groups
+    /// whose winner was deleted or trashed, or with an asset that changed
+    /// (checksum or update time) since the analysis, with a
+    /// [`STALE_ANALYSIS`] reason.
+    ///
+    /// 1. Looks up the winner and losers for metadata (GPS, datetime) the
+    ///    winner lacks and a loser has, to copy to the winner, and merges
+    ///    their descriptions by the [`DescriptionPolicy`] (which may hold the
+    ///    group for review)
+    /// 2. Plans a download of each loser, skipping external library assets and
+    ///    losers the cross-library policy protects
+    /// 3. Plans the deletion of the downloaded losers, unless the group has a
+    ///    zero-byte or corrupt original
+    ///
+    /// In [`ExecutionMode::AlbumOnly`], steps 2 and 3 are replaced by looking
+    /// up each loser's albums and planning to replace the loser with the
+    /// winner in each one.
+    pub async fn plan_group(&self, analysis: &DuplicateAnalysis) -> GroupPlan {
+        // Another user's asset can't (and shouldn't) be deleted with this key
+        if analysis.is_cross_owner() && !self.config.allow_cross_owner {
+            return GroupPlan {
+                duplicate_id: analysis.duplicate_id.clone(),
+                winner_id: analysis.winner.asset_id.to_string(),
+                decision: analysis.decision.clone(),
+                losers: loser_ids(analysis),
+                operations: vec![PlannedOperation::Skip {
+                    asset_id: analysis.duplicate_id.clone(),
+                    reason: "Group has assets owned by different users (cross-owner groups not allowed)".to_string(),
+                }],
+            };
+        }
+
+        if let Some(reason) = self.stale_reason(analysis).await {
+            return GroupPlan {
+                duplicate_id: analysis.duplicate_id.clone(),
+                winner_id: analysis.winner.asset_id.to_string(),
+                decision: analysis.decision.clone(),
+                losers: loser_ids(analysis),
+                operations: vec![PlannedOperation::Skip {
+                    asset_id: analysis.duplicate_id.clone(),
+                    reason,
+                }],
+            };
+        }
+
+        let mut operations = Vec::new();
+        // Reason to leave the group's losers alone, for review
+        let mut held = None;
+
+        if let Some(update) = self.plan_metadata_update(analysis, &mut held).await {
+            operations.push(update);
+        }
+
+        if self.config.mode == ExecutionMode::AlbumOnly {
+            let mut shared_album = None;
+            for loser in &analysis.losers {
+                let albums = match self
+                    .rate_limited(|| async { self.client.get_asset_albums(&loser.asset_id).await })
+                    .await
+                {
+                    Ok(albums) => albums,
+                    Err(e) => {
+                        operations.push(PlannedOperation::Skip {
+                            asset_id: loser.asset_id.to_string(),
+                            reason: format!("Could not list albums: {}", e),
+                        });
+                        continue;
+                    }
+                };
+                for album in albums {
+                    if self.config.shared_albums != SharedAlbumPolicy::Attempt && !self.can_edit(&album).await {
+                        let reason = shared_album_reason(&album, self.config.shared_albums);
+                        if self.config.shared_albums == SharedAlbumPolicy::Review {
+                            shared_album.get_or_insert(reason);
+                        } else {
+                            operations.push(PlannedOperation::Skip {
+                                asset_id: loser.asset_id.to_string(),
+                                reason,
+                            });
+                        }
+                        continue;
+                    }
+                    operations.push(PlannedOperation::ReplaceInAlbum {
+                        album_id: album.id,
+                        album_name: album.album_name,
+                        loser_id: loser.asset_id.to_string(),
+                    });
+                }
+            }
+            if let Some(reason) = shared_album {
+                operations.retain(|op| !matches!(op, PlannedOperation::ReplaceInAlbum { .. }));
+                operations.push(PlannedOperation::Skip {
+                    asset_id: analysis.duplicate_id.clone(),
+                    reason,
+                });
+            }
+            return GroupPlan {
+                duplicate_id: analysis.duplicate_id.clone(),
+                winner_id: analysis.winner.asset_id.to_string(),
+                decision: analysis.decision.clone(),
+                losers: loser_ids(analysis),
+                operations,
+            };
+        }
 
-        // Step 2: Download each loser asset
+        let cross_library = analysis.is_cross_library();
+        let mut delete_ids = Vec::new();
         for loser in &analysis.losers {
-            pb.set_message(format!("Downloading {}", loser.filename));
+            // External library assets are read-only and cannot be deleted via the API
+            let reason = if loser.is_external {
+                Some("Asset belongs to an external library (read-only)")
+            } else {
+                match self.config.cross_library {
+                    CrossLibraryPolicy::Skip if cross_library => {
+                        Some("Group spans multiple libraries (cross-library policy: skip)")
+                    }
+                    CrossLibraryPolicy::SameLibrary if loser.library_id != analysis.winner.library_id => {
+                        Some("Asset is in a different library from the winner (cross-library policy: same-library)")
+                    }
+                    _ => None,
+                }
+            };
+
+            operations.push(match reason {
+                Some(reason) => PlannedOperation::Skip {
+                    asset_id: loser.asset_id.to_string(),
+                    reason: reason.to_string(),
+                },
+                None => {
+                    delete_ids.push(loser.asset_id.to_string());
+                    PlannedOperation::Download {
+                        asset_id: loser.asset_id.to_string(),
+                        filename: loser.filename.to_string(),
+                        checksum: loser.checksum.as_deref().map(str::to_string),
+                        file_size: loser.file_size,
+                    }
+                }
+            });
+        }
 
-            let result = self.download_loser(&loser.asset_id, &loser.filename).await;
-            download_results.push(result);
+        // Shared links that would lose a loser: re-point them, or hold the group
+        if self.config.shared_links != SharedLinkPolicy::Ignore && held.is_none() {
+            let index = self.shared_link_index().await;
+            for loser_id in &delete_ids {
+                match index.as_ref().map(|index| index.links_for(loser_id)) {
+                    Ok(links) if self.config.shared_links == SharedLinkPolicy::Review => {
+                        if let Some(link) = links.first() {
+                            held.get_or_insert_with(|| {
+                                format!(
+                                    "Loser {} is in shared link {} (shared-link policy: review)",
+                                    loser_id, link.id
+                                )
+                            });
+                        }
+                    }
+                    Ok(links) => operations.extend(links.into_iter().map(|link| {
+                        let (album_id, album_name) =
+                            link.album.as_ref().map(|a| (a.id.clone(), a.album_name.clone())).unzip();
+                        PlannedOperation::RepointSharedLink {
+                            link_id: link.id.clone(),
+                            link_name: link.description.clone().or(album_name).unwrap_or_default(),
+                            album_id,
+                            loser_id: loser_id.clone(),
+                        }
+                    })),
+                    Err(e) => {
+                        held.get_or_insert_with(|| format!("Could not list shared links: {}", e));
+                    }
+                }
+            }
+        }
+        // Protected albums the winner can't be added to: hold the group
+        if !self.config.protected_albums.is_empty()
+            && self.config.shared_albums == SharedAlbumPolicy::Review
+            && held.is_none()
+        {
+            for loser_id in &delete_ids {
+                match self
+                    .rate_limited(|| async { self.client.get_asset_albums(loser_id).await })
+                    .await
+                {
+                    Ok(albums) => {
+                        for album in albums
+                            .iter()
+                            .filter(|a| is_protected(&self.config.protected_albums, &[&a.id, &a.album_name]))
+                        {
+                            if !self.can_edit(album).await {
+                                held.get_or_insert_with(|| shared_album_reason(album, SharedAlbumPolicy::Review));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        held.get_or_insert_with(|| format!("Could not list albums: {}", e));
+                    }
+                }
+                if held.is_some() {
+                    break;
+                }
+            }
         }
 
-        // Collect successfully downloaded asset IDs for deletion
-        let downloaded_ids: Vec<String> = download_results
-            .iter()
-            .filter_map(|r| match r {
-                OperationResult::Success { id, .. } => Some(id.clone()),
-                _ => None,
-            })
-            .collect();
+        // Memories the analysis found the deleted losers in
+        if self.config.preserve_memories
+            && held.is_none()
+            && let Some(memories) = analysis.loser_memories()
+        {
+            for (memory_id, losers) in memories {
+                if let Some(loser_id) = losers.iter().find(|l| delete_ids.contains(l)) {
+                    operations.push(PlannedOperation::AddToMemory {
+                        memory_id: memory_id.clone(),
+                        loser_id: loser_id.clone(),
+                    });
+                }
+            }
+        }
+
+        if let Some(reason) = &held {
+            for op in operations.iter_mut() {
+                if let PlannedOperation::Download { asset_id, .. } = op {
+                    *op = PlannedOperation::Skip {
+                        asset_id: asset_id.clone(),
+                        reason: reason.clone(),
+                    };
+                }
+            }
+            delete_ids.clear();
+        }
+
+        if !analysis.corrupt_asset_ids.is_empty() {
+            operations.push(PlannedOperation::Skip {
+                asset_id: analysis.duplicate_id.clone(),
+                reason: "Group has a zero-byte or corrupt original".to_string(),
+            });
+        } else if let Some(reason) = held {
+            operations.push(PlannedOperation::Skip {
+                asset_id: analysis.duplicate_id.clone(),
+                reason,
+            });
+        } else if delete_ids.is_empty() {
+            operations.push(PlannedOperation::Skip {
+                asset_id: analysis.duplicate_id.clone(),
+                reason: "No losers can be deleted".to_string(),
+            });
+        } else {
+            operations.push(PlannedOperation::Delete { asset_ids: delete_ids });
+        }
+
+        GroupPlan {
+            duplicate_id: analysis.duplicate_id.clone(),
+            winner_id: analysis.winner.asset_id.to_string(),
+            decision: analysis.decision.clone(),
+            losers: loser_ids(analysis),
+            operations,
+        }
+    }
 
-        // Step 3: Only delete if we have successfully downloaded assets
-        let delete_result = if downloaded_ids.is_empty() {
+    /// Apply the operations planned for a single duplicate group.
+    ///
+    /// 1. Updates the winner's metadata, and replaces losers with the winner
+    ///    in albums, if planned
+    /// 2. Downloads backup copies of the planned losers, checking each for
+    ///    corruption and optionally writing an XMP sidecar next to it,
+    ///    embedding metadata, transferring protected album and tag
+    ///    membership, and re-pointing shared links at the winner
+    /// 3. Optionally promotes the winner to album covers held by those
+    ///    losers, and adds it to the memories they appear in
+    /// 4. Deletes the planned losers that were successfully downloaded, once
+    ///    the winner's original passes a size check and format sniff (if it
+    ///    doesn't, the group is left for review)
+    ///
+    /// If [`ExecutionConfig::group_timeout`] passes before step 4, the group
+    /// is given up on: its delete step fails, and nothing is deleted. A plan
+    /// that fails [`GroupPlan::validate`] fails the same way, before
+    /// anything is changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The plan for this group
+    /// * `pb` - Progress bar to update with status messages
+    ///
+    /// # Returns
+    ///
+    /// A group result detailing the outcome of each operation.
+    pub async fn apply_group(&self, group: &GroupPlan, pb: &ProgressBar) -> GroupResult {
+        let group_id = group.duplicate_id.clone();
+        self.record_event(ExecutionEvent::GroupStarted {
+            group_id: group_id.clone(),
+            winner_id: group.winner_id.clone(),
+            losers: group.loser_ids().len(),
+        });
+        if let Err(e) = group.validate() {
+            return self.failed_group(group, e.to_string(), PreparedGroup::default());
+        }
+
+        // Steps 1 and 2, given up on if the group takes too long
+        let mut prepared = PreparedGroup::default();
+        let timed_out = match self.config.group_timeout {
+            Some(limit) => tokio::time::timeout(limit, self.prepare_group(group, pb, &mut prepared))
+                .await
+                .err()
+                .map(|_| limit),
+            None => {
+                self.prepare_group(group, pb, &mut prepared).await;
+                None
+            }
+        };
+        self.record_downloads(&group_id, &prepared.download_results);
+        if let Some(limit) = timed_out {
+            return self.timed_out_group(group, limit, prepared);
+        }
+        let PreparedGroup {
+            consolidation_result,
+            download_results,
+            mut promotions,
+            covers,
+            corrupt_backup,
+            backup_sizes,
+            downloaded_ids,
+            delete_ids,
+        } = prepared;
+
+        // Step 3: Only delete if every original is intact and we have successful downloads
+        let mut deleted_ids = Vec::new();
+        let mut rolled_back_ids = Vec::new();
+        let skip_reason = if corrupt_backup {
+            Some("Group has a zero-byte or corrupt original")
+        } else if let Some(reason) = group.delete_skipped() {
+            Some(reason)
+        } else if downloaded_ids.is_empty() {
+            Some("No assets were successfully downloaded")
+        } else if delete_ids.is_empty() {
+            Some("Plan deletes none of the downloaded assets")
+        } else {
+            None
+        };
+        // Step 3a: Only delete losers in favour of an intact winner
+        let winner_issue = match skip_reason {
+            None if group.has_delete_step() => self.check_winner(&group.winner_id).await,
+            _ => None,
+        };
+        let skip_reason = skip_reason.or(winner_issue.as_deref());
+        let delete_result = if !group.has_delete_step() {
+            None
+        } else if let Some(reason) = skip_reason {
             Some(OperationResult::Skipped {
-                id: analysis.duplicate_id.clone(),
-                reason: "No assets were successfully downloaded".to_string(),
+                id: group_id.clone(),
+                reason: reason.to_string(),
             })
         } else {
-            pb.set_message(format!("Deleting {} assets", downloaded_ids.len()));
+            pb.set_message(format!("Deleting {} assets", delete_ids.len()));
 
-            match self.delete_assets(&downloaded_ids).await {
-                Ok(()) => Some(OperationResult::Success {
-                    id: analysis.duplicate_id.clone(),
-                    path: None,
-                }),
+            // Record the deletion before making it, so the ledger never misses one
+            let action = if self.config.force_delete {
+                LedgerAction::Deleted
+            } else {
+                LedgerAction::Trashed
+            };
+            let recorded = self.record_in_ledger(group, &download_results, &delete_ids, action);
+
+            match recorded {
                 Err(e) => Some(OperationResult::Failed {
-                    id: analysis.duplicate_id.clone(),
-                    error: e.to_string(),
+                    id: group_id.clone(),
+                    error: format!("Not deleted: could not write deletion ledger: {}", e),
                 }),
+                Ok(()) => match self.delete_assets(&delete_ids).await {
+                    Ok(()) => {
+                        deleted_ids = delete_ids.clone();
+                        Some(OperationResult::Success {
+                            id: group_id.clone(),
+                            path: None,
+                        })
+                    }
+                    Err(e) => {
+                        let outcome = self.after_failed_delete(group, &download_results, &delete_ids, e).await;
+                        deleted_ids = outcome.deleted_ids;
+                        rolled_back_ids = outcome.rolled_back_ids;
+                        Some(outcome.result)
+                    }
+                },
             }
         };
 
-        GroupResult {
-            duplicate_id: analysis.duplicate_id.clone(),
-            winner_id: analysis.winner.asset_id.clone(),
+        // Step 4: Hand the covers of deleted losers' albums to the winner
+        let covers: Vec<CoverSwap> = covers.into_iter().filter(|c| deleted_ids.contains(&c.loser_id)).collect();
+        if !covers.is_empty() {
+            pb.set_message("Promoting winner to album covers");
+            promotions.extend(self.promote_album_covers(&group.winner_id, &covers).await);
+        }
+
+        // Space the deleted losers took: freed now if permanently deleted,
+        // or once the trash is emptied
+        let deleted_bytes: u64 = deleted_ids
+            .iter()
+            .filter_map(|id| backup_sizes.get(id).copied().or_else(|| group.file_size(id)))
+            .sum();
+        let (reclaimed_bytes, trashed_bytes) = if self.config.force_delete {
+            (deleted_bytes, 0)
+        } else {
+            (0, deleted_bytes)
+        };
+
+        if let Some(delete) = &delete_result {
+            self.record_event(match delete {
+                OperationResult::Success { .. } => ExecutionEvent::Deleted {
+                    group_id: group_id.clone(),
+                    asset_ids: deleted_ids.clone(),
+                },
+                OperationResult::Failed { error, .. } => ExecutionEvent::DeleteFailed {
+                    group_id: group_id.clone(),
+                    error: error.clone(),
+                    deleted_ids: deleted_ids.clone(),
+                    rolled_back_ids: rolled_back_ids.clone(),
+                },
+                OperationResult::Skipped { id, reason } => ExecutionEvent::Skipped {
+                    group_id: group_id.clone(),
+                    asset_id: id.clone(),
+                    reason: reason.clone(),
+                },
+            });
+        }
+        self.record_event(ExecutionEvent::GroupFinished { group_id });
+
+        let result = GroupResult {
+            duplicate_id: group.duplicate_id.clone(),
+            winner_id: group.winner_id.clone(),
             consolidation_result,
             download_results,
             delete_result,
+            reclaimed_bytes,
+            trashed_bytes,
+            deleted_ids,
+            rolled_back_ids,
+            promotions,
+            decision: group.decision.clone(),
+        };
+        if let Some(metrics) = &self.config.metrics {
+            record_metrics(metrics, &result);
         }
+        result
     }
 
-    /// Consolidate metadata from loser assets to the winner.
+    /// Steps 1 and 2 of [`apply_group`](Self::apply_group): everything up
+    /// to the delete step.
     ///
-    /// Checks if the winner lacks GPS, datetime, or description that any loser has,
-    /// and transfers the metadata to preserve it before deletion.
-    async fn consolidate_metadata(
-        &self,
-        analysis: &DuplicateAnalysis,
-    ) -> Option<ConsolidationResult> {
-        // Fetch winner asset to check what metadata it already has
-        let winner_asset = match self
-            .rate_limited(async { self.client.get_asset(&analysis.winner.asset_id).await })
-            .await
-        {
-            Ok(asset) => asset,
-            Err(_) => return None, // Can't consolidate if we can't fetch winner
-        };
-
-        let winner_exif = winner_asset.exif_info.as_ref();
-        let winner_has_gps = winner_exif.map(|e| e.has_gps()).unwrap_or(false);
-        let winner_has_datetime = winner_exif
-            .and_then(|e| e.date_time_original.as_ref())
-            .is_some();
-        let winner_has_description = winner_exif.and_then(|e| e.description.as_ref()).is_some();
+    /// Results are recorded in `prepared` as each step finishes, so a group
+    /// given up on partway keeps the metadata, backups, and transfers it
+    /// already made.
+    async fn prepare_group(&self, group: &GroupPlan, pb: &ProgressBar, prepared: &mut PreparedGroup) {
+        let group_id = group.duplicate_id.clone();
 
-        // If winner has all metadata, no consolidation needed
-        if winner_has_gps && winner_has_datetime && winner_has_description {
-            return None;
+        // Step 1: Consolidate metadata from losers to winner
+        pb.set_message("Consolidating metadata");
+        prepared.consolidation_result = self.apply_metadata_update(group).await;
+        if let Some(consolidation) = prepared.consolidation_result.as_ref().filter(|c| c.any_transferred()) {
+            let applied: Vec<&FieldTransfer> = consolidation.fields.iter().filter(|f| f.applied).collect();
+            self.record_event(ExecutionEvent::Consolidated {
+                group_id: group_id.clone(),
+                source_id: applied.iter().find_map(|f| f.source_asset_id.clone()),
+                fields: applied.iter().map(|f| f.field.to_string()).collect(),
+            });
         }
 
-        // Find best source for each missing field from losers (owned values)
-        let mut best_gps: Option<(f64, f64, String)> = None;
-        let mut best_datetime: Option<(String, String)> = None;
-        let mut best_description: Option<(String, String)> = None;
-
-        for loser in &analysis.losers {
-            let loser_asset = match self
-                .rate_limited(async { self.client.get_asset(&loser.asset_id).await })
-                .await
-            {
-                Ok(asset) => asset,
-                Err(_) => continue, // Skip losers we can't fetch
-            };
-
-            if let Some(exif) = &loser_asset.exif_info {
-                // Check GPS
-                if !winner_has_gps
-                    && best_gps.is_none()
-                    && exif.has_gps()
-                    && let (Some(lat), Some(lon)) = (exif.latitude, exif.longitude)
-                {
-                    best_gps = Some((lat, lon, loser.asset_id.clone()));
-                }
+        // Step 1a: Replace losers with the winner in albums
+        prepared.promotions = self.replace_in_albums(group, pb).await;
 
-                // Check datetime
-                if !winner_has_datetime
-                    && best_datetime.is_none()
-                    && let Some(dt) = &exif.date_time_original
-                {
-                    best_datetime = Some((dt.clone(), loser.asset_id.clone()));
+        // Step 2: Download each planned loser
+        for operation in &group.operations {
+            match operation {
+                PlannedOperation::Download { asset_id, filename, .. } => {
+                    pb.set_message(format!("Downloading {}", filename));
+                    let (result, bytes) = self.download_loser(asset_id, filename).await;
+                    if let Some(bytes) = bytes {
+                        prepared.backup_sizes.insert(asset_id.clone(), bytes);
+                    }
+                    prepared.download_results.push(result);
                 }
-
-                // Check description
-                if !winner_has_description
-                    && best_description.is_none()
-                    && let Some(desc) = &exif.description
-                {
-                    best_description = Some((desc.clone(), loser.asset_id.clone()));
+                PlannedOperation::Skip { asset_id, reason } if *asset_id != group_id => {
+                    prepared.download_results.push(OperationResult::Skipped {
+                        id: asset_id.clone(),
+                        reason: reason.clone(),
+                    });
                 }
-            }
-
-            // If we've found all we need, stop searching
-            if (winner_has_gps || best_gps.is_some())
-                && (winner_has_datetime || best_datetime.is_some())
-                && (winner_has_description || best_description.is_some())
-            {
-                break;
+                _ => {}
             }
         }
 
-        // Nothing to consolidate
-        if best_gps.is_none() && best_datetime.is_none() && best_description.is_none() {
-            return None;
+        // Step 2a: Check backups for zero-byte or corrupt files
+        pb.set_message("Checking backup integrity");
+        prepared.corrupt_backup = self.check_backups(&mut prepared.download_results).await;
+
+        // Step 2b: Record consolidated metadata next to each backup
+        if self.config.write_sidecars && !prepared.download_results.is_empty() {
+            pb.set_message("Writing XMP sidecars");
+            self.write_sidecars(group, &mut prepared.download_results).await;
         }
 
-        // Prepare update parameters
-        let (latitude, longitude) = match &best_gps {
-            Some((lat, lon, _)) => (Some(*lat), Some(*lon)),
-            None => (None, None),
-        };
-        let date_time_original = best_datetime.as_ref().map(|(dt, _)| dt.as_str());
-        let description = best_description.as_ref().map(|(desc, _)| desc.as_str());
-
-        // Determine source asset ID (prefer GPS source, then datetime, then description)
-        let source_asset_id = best_gps
-            .as_ref()
-            .map(|(_, _, id)| id.clone())
-            .or_else(|| best_datetime.as_ref().map(|(_, id)| id.clone()))
-            .or_else(|| best_description.as_ref().map(|(_, id)| id.clone()));
-
-        // Update winner with consolidated metadata
-        let update_result = self
-            .rate_limited(async {
-                self.client
-                    .update_asset_metadata(
-                        &analysis.winner.asset_id,
-                        latitude,
-                        longitude,
-                        date_time_original,
-                        description,
-                    )
-                    .await
-            })
-            .await;
+        // Step 2c: Embed each asset's Immich-side metadata into its backup
+        if self.config.embed_metadata {
+            pb.set_message("Embedding metadata in backups");
+            self.embed_backup_metadata(&mut prepared.download_results).await;
+        }
 
-        if update_result.is_ok() {
-            Some(ConsolidationResult {
-                gps_transferred: best_gps.is_some(),
-                datetime_transferred: best_datetime.is_some(),
-                description_transferred: best_description.is_some(),
-                source_asset_id,
-            })
-        } else {
-            None // Consolidation failed, but we can still proceed with download/delete
+        // Then move each finished backup (and sidecar) into the run's archive
+        let archive = self.archive.lock().clone();
+        if let Some(archive) = archive {
+            pb.set_message("Archiving backups");
+            archive_backups(archive.as_ref(), &group_id, &mut prepared.download_results).await;
         }
-    }
 
-    /// Download a loser asset to the backup directory.
-    ///
-    /// Files are named as `{asset_id}_{filename}` to avoid collisions.
-    async fn download_loser(&self, asset_id: &str, filename: &str) -> OperationResult {
-        // Build path with asset ID prefix to avoid collisions
-        let safe_filename = format!("{}_{}", asset_id, filename);
-        let path = self.config.backup_dir.join(&safe_filename);
+        // Step 2d: Move protected album/tag membership to the winner and verify it
+        if !self.config.protected_albums.is_empty() || !self.config.protected_tags.is_empty() {
+            pb.set_message("Transferring protected album and tag membership");
+            self.transfer_protected(&group.winner_id, &mut prepared.download_results).await;
+        }
 
-        let download_result = self
-            .rate_limited(async { self.client.download_asset(asset_id, &path).await })
-            .await;
+        // Step 2e: Add the winner to shared links that share a loser
+        let repointed = self.repoint_shared_links(group, &mut prepared.download_results, pb).await;
+        prepared.promotions.extend(repointed);
 
-        match download_result {
-            Ok(_bytes) => OperationResult::Success {
-                id: asset_id.to_string(),
-                path: Some(path),
-            },
-            Err(e) => OperationResult::Failed {
-                id: asset_id.to_string(),
-                error: e.to_string(),
+        // Collect successfully downloaded asset IDs the plan deletes
+        prepared.downloaded_ids = prepared
+            .download_results
+            .iter()
+            .filter_map(|r| match r {
+                OperationResult::Success { id, .. } => Some(id.clone()),
+                _ => None,
+            })
+            .collect();
+        let planned_deletes = group.delete_ids();
+        prepared.delete_ids = prepared
+            .downloaded_ids
+            .iter()
+            .filter(|id| planned_deletes.contains(id))
+            .cloned()
+            .collect();
+
+        // Step 2f: Find the albums a deleted loser is the cover of (promoted after the delete)
+        let deleting = !prepared.corrupt_backup && group.delete_skipped().is_none() && !prepared.delete_ids.is_empty();
+        if self.config.promote_album_covers && deleting {
+            pb.set_message("Finding album covers held by losers");
+            let (covers, held) = self.find_album_covers(&prepared.delete_ids).await;
+            prepared.covers = covers;
+            prepared.promotions.extend(held);
+        }
+
+        // Step 2g: Add the winner to memories a deleted loser appears in
+        if deleting {
+            let memories = self.add_to_memories(group, &prepared.delete_ids, pb).await;
+            prepared.promotions.extend(memories);
+        }
+    }
+
+    /// Record the outcome of each of a group's downloads and skips.
+    fn record_downloads(&self, group_id: &str, download_results: &[OperationResult]) {
+        for result in download_results {
+            self.record_event(match result {
+                OperationResult::Success { id, path } => ExecutionEvent::Downloaded {
+                    group_id: group_id.to_string(),
+                    asset_id: id.clone(),
+                    bytes: path.as_ref().and_then(|p| std::fs::metadata(p).ok()).map(|m| m.len()),
+                    path: path.clone(),
+                },
+                OperationResult::Failed { id, error } => ExecutionEvent::DownloadFailed {
+                    group_id: group_id.to_string(),
+                    asset_id: id.clone(),
+                    error: error.clone(),
+                },
+                OperationResult::Skipped { id, reason } => ExecutionEvent::Skipped {
+                    group_id: group_id.to_string(),
+                    asset_id: id.clone(),
+                    reason: reason.clone(),
+                },
+            });
+        }
+    }
+
+    /// The result of a group given up on after `limit`, before anything was
+    /// deleted, with what its earlier steps had done by then.
+    fn timed_out_group(&self, group: &GroupPlan, limit: Duration, prepared: PreparedGroup) -> GroupResult {
+        let error = format!("Group timed out after {:?}; nothing was deleted", limit);
+        self.failed_group(group, error, prepared)
+    }
+
+    /// The result of a group whose delete step failed with `error` before
+    /// anything was deleted, after `prepared`.
+    fn failed_group(&self, group: &GroupPlan, error: String, prepared: PreparedGroup) -> GroupResult {
+        let group_id = group.duplicate_id.clone();
+        self.record_event(ExecutionEvent::DeleteFailed {
+            group_id: group_id.clone(),
+            error: error.clone(),
+            deleted_ids: Vec::new(),
+            rolled_back_ids: Vec::new(),
+        });
+        self.record_event(ExecutionEvent::GroupFinished { group_id: group_id.clone() });
+
+        let result = GroupResult {
+            duplicate_id: group_id.clone(),
+            winner_id: group.winner_id.clone(),
+            consolidation_result: prepared.consolidation_result,
+            download_results: prepared.download_results,
+            delete_result: Some(OperationResult::Failed { id: group_id, error }),
+            deleted_ids: Vec::new(),
+            rolled_back_ids: Vec::new(),
+            reclaimed_bytes: 0,
+            trashed_bytes: 0,
+            promotions: prepared.promotions,
+            decision: group.decision.clone(),
+        };
+        if let Some(metrics) = &self.config.metrics {
+            record_metrics(metrics, &result);
+        }
+        result
+    }
+
+    /// Work out which losers a failed delete request removed anyway.
+    ///
+    /// If every loser went, the group counts as processed. If only some
+    /// did and `rollback_partial` is set (and the losers went to the trash),
+    /// they are restored so the group is left untouched.
+    async fn after_failed_delete(
+        &self,
+        group: &GroupPlan,
+        download_results: &[OperationResult],
+        ids: &[String],
+        error: ImmichError,
+    ) -> DeleteOutcome {
+        let mut deleted_ids = Vec::new();
+        for id in ids {
+            let lookup = self.rate_limited(|| async { self.client.get_asset(id).await }).await;
+            if AssetState::from_lookup(&lookup).is_removed() {
+                deleted_ids.push(id.clone());
+            }
+        }
+        if deleted_ids.len() == ids.len() {
+            return DeleteOutcome {
+                result: OperationResult::Success {
+                    id: group.duplicate_id.clone(),
+                    path: None,
+                },
+                deleted_ids,
+                rolled_back_ids: Vec::new(),
+            };
+        }
+
+        let mut error = error.to_string();
+        let mut rolled_back_ids = Vec::new();
+        if !deleted_ids.is_empty() {
+            if self.config.rollback_partial && !self.config.force_delete {
+                match self
+                    .rate_limited(|| async { self.client.restore_from_trash(&deleted_ids).await })
+                    .await
+                {
+                    Ok(()) => {
+                        error.push_str(&format!("; restored {} trashed losers", deleted_ids.len()));
+                        rolled_back_ids = std::mem::take(&mut deleted_ids);
+                    }
+                    Err(e) => error.push_str(&format!("; rollback failed: {}", e)),
+                }
+            }
+            if !deleted_ids.is_empty() {
+                error.push_str(&format!("; {} of {} losers were deleted", deleted_ids.len(), ids.len()));
+            }
+        }
+
+        let not_deleted: Vec<String> = ids
+            .iter()
+            .filter(|id| !deleted_ids.contains(id) && !rolled_back_ids.contains(id))
+            .cloned()
+            .collect();
+        let ledger = self
+            .record_in_ledger(group, download_results, &not_deleted, LedgerAction::DeleteFailed)
+            .and_then(|()| self.record_in_ledger(group, download_results, &rolled_back_ids, LedgerAction::Restored));
+        if let Err(ledger_error) = ledger {
+            error.push_str(&format!(" (ledger not updated: {})", ledger_error));
+        }
+
+        DeleteOutcome {
+            result: OperationResult::Failed {
+                id: group.duplicate_id.clone(),
+                error,
             },
+            deleted_ids,
+            rolled_back_ids,
         }
     }
 
-    /// Delete assets using the API.
-    async fn delete_assets(&self, asset_ids: &[String]) -> Result<()> {
-        self.rate_limited(async {
+    /// Append a ledger entry for each successfully downloaded loser in `ids`.
+    ///
+    /// Does nothing if no ledger is configured or `ids` is empty.
+    fn record_in_ledger(
+        &self,
+        group: &GroupPlan,
+        download_results: &[OperationResult],
+        ids: &[String],
+        action: LedgerAction,
+    ) -> Result<()> {
+        let Some(ledger_path) = &self.config.ledger_path else {
+            return Ok(());
+        };
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let recorded_at = self.clock.now();
+        let entries: Vec<LedgerEntry> = download_results
+            .iter()
+            .filter_map(|result| match result {
+                OperationResult::Success { id, path } if ids.contains(id) => Some((id, path)),
+                _ => None,
+            })
+            .filter_map(|(id, backup_path)| {
+                let (checksum, filename) = group.operations.iter().find_map(|op| match op {
+                    PlannedOperation::Download {
+                        asset_id,
+                        filename,
+                        checksum,
+                        ..
+                    } if asset_id == id => Some((checksum, filename)),
+                    _ => None,
+                })?;
+                Some(LedgerEntry {
+                    recorded_at,
+                    action,
+                    asset_id: id.clone(),
+                    checksum: checksum.clone(),
+                    filename: filename.clone(),
+                    backup_path: backup_path.clone(),
+                    group_id: group.duplicate_id.clone(),
+                    winner_id: group.winner_id.clone(),
+                    server_url: self.client.base_url().to_string(),
+                })
+            })
+            .collect();
+
+        Ledger::new(ledger_path).append(&entries)
+    }
+
+    /// ID of the user the API key belongs to, looked up once.
+    async fn user_id(&self) -> Option<&str> {
+        self.user_id
+            .get_or_init(|| async {
+                self.rate_limited(|| async { self.client.get_my_user().await })
+                    .await
+                    .ok()
+                    .map(|user| user.id)
+            })
+            .await
+            .as_deref()
+    }
+
+    /// Returns true if the API key's user may change `album`. If the user
+    /// can't be looked up, every album is assumed to be editable.
+    async fn can_edit(&self, album: &AlbumResponse) -> bool {
+        self.user_id().await.is_none_or(|id| album.is_editable_by(id))
+    }
+
+    /// Compare each asset of the group with the server, returning why the
+    /// group must be skipped if the analysis no longer matches.
+    ///
+    /// Checksums and update times are only compared where the analysis
+    /// recorded them. A loser that is already deleted or trashed (say, by an
+    /// earlier run) is left to the delete step; a winner that is gone is not.
+    async fn stale_reason(&self, analysis: &DuplicateAnalysis) -> Option<String> {
+        for scored in std::iter::once(&analysis.winner).chain(&analysis.losers) {
+            let id = &scored.asset_id;
+            let is_winner = std::ptr::eq(scored, &analysis.winner);
+            let asset = match self.rate_limited(|| async { self.client.get_asset(id).await }).await {
+                Ok(asset) => asset,
+                Err(ImmichError::NotFound { .. }) if is_winner => {
+                    return Some(format!("{}: winner {} no longer exists", STALE_ANALYSIS, id));
+                }
+                Err(ImmichError::NotFound { .. }) => continue,
+                Err(e) => return Some(format!("Could not check asset {} for changes: {}", id, e)),
+            };
+            if asset.is_trashed && !is_winner {
+                continue;
+            }
+
+            let changed = if asset.is_trashed {
+                Some("was trashed")
+            } else if scored.checksum.as_deref().is_some_and(|c| c != asset.checksum) {
+                Some("was modified")
+            } else if let (Some(then), Some(now)) = (scored.updated_at.as_deref(), asset.updated_at.as_deref())
+                && then != now
+            {
+                Some("was updated")
+            } else {
+                None
+            };
+            if let Some(changed) = changed {
+                return Some(format!("{}: asset {} {} since it was analyzed", STALE_ANALYSIS, id, changed));
+            }
+        }
+        None
+    }
+
+    /// Plan the consolidation of metadata from loser assets to the winner.
+    ///
+    /// Checks if the winner lacks GPS or datetime that any loser has, and
+    /// merges loser descriptions into the winner's by the
+    /// [`DescriptionPolicy`], and plans to transfer the metadata to preserve
+    /// it before deletion. With [`DescriptionPolicy::Review`], a loser whose
+    /// description differs from the winner's, or losers that disagree when
+    /// the winner has none, set `held` to hold the group.
+    async fn plan_metadata_update(
+        &self,
+        analysis: &DuplicateAnalysis,
+        held: &mut Option<String>,
+    ) -> Option<PlannedOperation> {
+        // Fetch winner asset to check what metadata it already has
+        let winner_asset = match self
+            .rate_limited(|| async { self.client.get_asset(&analysis.winner.asset_id).await })
+            .await
+        {
+            Ok(asset) => asset,
+            Err(_) => return None, // Can't consolidate if we can't fetch winner
+        };
+
+        let policy = self.config.description_policy;
+        let winner_exif = winner_asset.exif_info.as_ref();
+        let winner_has_gps = winner_exif.map(|e| e.has_gps()).unwrap_or(false);
+        let winner_has_datetime = winner_exif
+            .and_then(|e| e.date_time_original.as_ref())
+            .is_some();
+        let winner_description = winner_exif
+            .and_then(|e| e.description.as_deref())
+            .filter(|d| !d.trim().is_empty());
+        // Every loser's description is collected, to spot disagreement when filling an
+        // empty one; only the Fill policy with a described winner needs none
+        let wants_descriptions = winner_description.is_none() || policy != DescriptionPolicy::Fill;
+
+        // If winner has all metadata, no consolidation needed
+        if winner_has_gps && winner_has_datetime && !wants_descriptions {
+            return None;
+        }
+
+        // Find the best source for each missing field among the losers (owned
+        // values). Losers are visited best metadata score first, so a field
+        // of equal quality comes from the better-scored loser.
+        let mut losers: Vec<_> = analysis.losers.iter().collect();
+        losers.sort_by(|a, b| b.score.cmp(&a.score));
+        let mut best_gps: Option<(f64, f64, usize, String)> = None;
+        let mut best_datetime: Option<(String, bool, String)> = None;
+        let mut descriptions: Vec<(String, String)> = Vec::new();
+
+        for loser in losers {
+            let loser_asset = match self
+                .rate_limited(|| async { self.client.get_asset(&loser.asset_id).await })
+                .await
+            {
+                Ok(asset) => asset,
+                Err(_) => continue, // Skip losers we can't fetch
+            };
+
+            if let Some(exif) = &loser_asset.exif_info {
+                // Check GPS, preferring the most precise coordinates
+                if !winner_has_gps
+                    && exif.has_gps()
+                    && let (Some(lat), Some(lon)) = (exif.latitude, exif.longitude)
+                {
+                    let precision = gps_precision(lat, lon);
+                    if best_gps.as_ref().is_none_or(|(_, _, best, _)| precision > *best) {
+                        best_gps = Some((lat, lon, precision, loser.asset_id.to_string()));
+                    }
+                }
+
+                // Check datetime, preferring one with a known timezone
+                if !winner_has_datetime && let Some(dt) = &exif.date_time_original {
+                    let has_timezone = exif.has_timezone();
+                    if best_datetime.as_ref().is_none_or(|(_, best, _)| has_timezone && !best) {
+                        best_datetime = Some((dt.to_string(), has_timezone, loser.asset_id.to_string()));
+                    }
+                }
+
+                // Check description
+                if wants_descriptions
+                    && let Some(desc) = exif.description.as_deref().filter(|d| !d.trim().is_empty())
+                {
+                    descriptions.push((desc.to_string(), loser.asset_id.to_string()));
+                }
+            }
+        }
+
+        if policy == DescriptionPolicy::Review {
+            let conflict = match winner_description {
+                Some(winner_description) => descriptions
+                    .iter()
+                    .find(|(d, _)| d.trim() != winner_description.trim())
+                    .map(|(_, loser_id)| format!("Loser {} has a different description from the winner", loser_id)),
+                // With no description of its own, the winner can't take one
+                // loser's over another's
+                None => descriptions.split_first().and_then(|((first, first_id), rest)| {
+                    rest.iter().find(|(d, _)| d.trim() != first.trim()).map(|(_, loser_id)| {
+                        format!(
+                            "Losers {} and {} have different descriptions and the winner has none",
+                            first_id, loser_id
+                        )
+                    })
+                }),
+            };
+            if let Some(conflict) = conflict {
+                held.get_or_insert_with(|| format!("{} (description policy: review)", conflict));
+            }
+        }
+
+        let loser_descriptions: Vec<&str> = descriptions.iter().map(|(d, _)| d.as_str()).collect();
+        let best_description = policy
+            .merge(winner_description, &loser_descriptions)
+            .map(|merged| {
+                // Credit the first loser whose text made it into the winner's description
+                let source = descriptions
+                    .iter()
+                    .find(|(d, _)| merged.contains(d.trim()))
+                    .map(|(_, id)| id.clone());
+                (merged, source)
+            });
+
+        // Nothing to consolidate
+        if best_gps.is_none() && best_datetime.is_none() && best_description.is_none() {
+            return None;
+        }
+
+        let sources = MetadataSources {
+            gps: best_gps.as_ref().map(|(_, _, _, id)| id.clone()),
+            datetime: best_datetime.as_ref().map(|(_, _, id)| id.clone()),
+            description: best_description.as_ref().and_then(|(_, id)| id.clone()),
+        };
+        let (latitude, longitude) = match &best_gps {
+            Some((lat, lon, _, _)) => (Some(*lat), Some(*lon)),
+            None => (None, None),
+        };
+
+        Some(PlannedOperation::UpdateMetadata {
+            asset_id: analysis.winner.asset_id.to_string(),
+            source_id: sources.first().map(str::to_string),
+            sources,
+            latitude,
+            longitude,
+            date_time_original: best_datetime.map(|(dt, _, _)| dt),
+            description: best_description.map(|(desc, _)| desc),
+        })
+    }
+
+    /// Apply the group's planned metadata update, if it has one.
+    ///
+    /// A failed update is not reported as an error: the group can still be
+    /// backed up and deleted, and its fields are recorded as not applied.
+    async fn apply_metadata_update(&self, group: &GroupPlan) -> Option<ConsolidationResult> {
+        let PlannedOperation::UpdateMetadata {
+            asset_id,
+            source_id,
+            sources,
+            latitude,
+            longitude,
+            date_time_original,
+            description,
+        } = group
+            .operations
+            .iter()
+            .find(|op| matches!(op, PlannedOperation::UpdateMetadata { .. }))?
+        else {
+            return None;
+        };
+
+        let update = MetadataUpdate {
+            latitude: latitude.zip(*longitude),
+            date_time_original: date_time_original.as_deref(),
+            description: description.as_deref(),
+        };
+
+        // Immich can accept an update and silently drop fields, so read the
+        // winner back, and send any missing fields once more
+        let bulk_updated = self.bulk_updated.lock().remove(asset_id);
+        let applied = bulk_updated || self.send_metadata_update(asset_id, &update).await;
+        let mut missing = Vec::new();
+        let mut retried = false;
+        if applied
+            && let Some(unapplied) = self.unapplied_fields(asset_id, &update).await
+            && !unapplied.is_empty()
+        {
+            retried = true;
+            let retry = update.only(&unapplied);
+            missing = unapplied;
+            if self.send_metadata_update(asset_id, &retry).await
+                && let Some(unapplied) = self.unapplied_fields(asset_id, &retry).await
+            {
+                missing = unapplied;
+            }
+        }
+        let winner = if missing.is_empty() {
+            None
+        } else {
+            self.rate_limited(|| async { self.client.get_asset(asset_id).await }).await.ok()
+        };
+
+        let field_sources = [
+            (MetadataField::Gps, &sources.gps),
+            (MetadataField::Datetime, &sources.datetime),
+            (MetadataField::Description, &sources.description),
+        ];
+        Some(ConsolidationResult {
+            fields: field_sources
+                .into_iter()
+                .filter_map(|(field, source)| {
+                    let discrepancy = missing.contains(&field).then(|| {
+                        let exif = winner.as_ref().and_then(|w| w.exif_info.as_ref());
+                        MetadataUpdate::found(exif, field).unwrap_or_else(|| "none".to_string())
+                    });
+                    Some(FieldTransfer {
+                        field,
+                        value: update.value(field)?,
+                        source_asset_id: source.clone().or_else(|| source_id.clone()),
+                        applied: applied && discrepancy.is_none(),
+                        discrepancy,
+                    })
+                })
+                .collect(),
+            retried,
+            bulk: bulk_updated,
+        })
+    }
+
+    /// Send a metadata update for the winner. Returns true if the server
+    /// accepted it.
+    async fn send_metadata_update(&self, asset_id: &str, update: &MetadataUpdate<'_>) -> bool {
+        let (latitude, longitude) = update.latitude.unzip();
+        self.rate_limited(|| async {
             self.client
-                .delete_assets(asset_ids, self.config.force_delete)
+                .update_asset_metadata(asset_id, latitude, longitude, update.date_time_original, update.description)
                 .await
         })
         .await
+        .is_ok()
+    }
+
+    /// Read the winner back and list the fields of `update` it doesn't have,
+    /// or `None` if it can't be read.
+    async fn unapplied_fields(&self, asset_id: &str, update: &MetadataUpdate<'_>) -> Option<Vec<MetadataField>> {
+        let winner = self
+            .rate_limited(|| async { self.client.get_asset(asset_id).await })
+            .await
+            .ok()?;
+        Some(update.unapplied(winner.exif_info.as_ref()))
+    }
+
+    /// Check each downloaded local backup for zero-byte or corrupt content.
+    ///
+    /// Corrupt backups are marked failed. Returns true if any backup was
+    /// corrupt, which blocks deletion of the whole group.
+    async fn check_backups(&self, download_results: &mut [OperationResult]) -> bool {
+        let mut corrupt = false;
+        // Remote backups were checked for size as they were stored
+        if !self.store.is_local() {
+            return false;
+        }
+
+        for result in download_results.iter_mut() {
+            let OperationResult::Success {
+                id,
+                path: Some(path),
+            } = result
+            else {
+                continue;
+            };
+
+            let error = match check_file(path, self.config.decode_backups).await {
+                Ok(None) => continue,
+                Ok(Some(issue)) => format!("Corrupt original: {}", issue),
+                Err(e) => format!("Failed to check backup: {}", e),
+            };
+
+            corrupt = true;
+            *result = OperationResult::Failed {
+                id: id.clone(),
+                error,
+            };
+        }
+
+        corrupt
+    }
+
+    /// Size-check and sniff the winner's original before its losers are
+    /// deleted.
+    ///
+    /// Returns why the group needs review instead if the original is empty,
+    /// doesn't match its extension, or can't be checked.
+    async fn check_winner(&self, winner_id: &str) -> Option<String> {
+        let review = |detail: String| {
+            Some(format!(
+                "Needs review ({}): {}",
+                ReviewReason::CorruptOriginal.as_str(),
+                detail
+            ))
+        };
+        let winner = match self.rate_limited(|| async { self.client.get_asset(winner_id).await }).await {
+            Ok(winner) => winner,
+            Err(e) => return review(format!("could not check the winner's original: {}", e)),
+        };
+        let issue = if winner.exif_info.as_ref().and_then(|e| e.file_size_in_byte) == Some(0) {
+            Some(IntegrityIssue::ZeroBytes)
+        } else {
+            let prefix = match self
+                .rate_limited(|| async { self.client.original_prefix(winner_id, SNIFF_LEN).await })
+                .await
+            {
+                Ok(prefix) => prefix,
+                Err(e) => return review(format!("could not read the winner's original: {}", e)),
+            };
+            let extension = std::path::Path::new(&winner.original_file_name)
+                .extension()
+                .and_then(|e| e.to_str());
+            check_bytes(&prefix, extension)
+        };
+        review(format!("the winner's original is corrupt ({})", issue?))
+    }
+
+    /// Transfer protected album and tag membership from each loser to the winner.
+    ///
+    /// A loser in a protected album (or with a protected tag) stays in Immich
+    /// unless the winner is added to that album (or tagged) and a re-fetch of
+    /// the winner confirms it. Losers whose transfer fails or can't be verified
+    /// are marked failed, and losers held for review are marked skipped, so
+    /// they are not deleted.
+    async fn transfer_protected(&self, winner_id: &str, download_results: &mut [OperationResult]) {
+        for result in download_results.iter_mut() {
+            let OperationResult::Success { id, .. } = result else {
+                continue;
+            };
+
+            match self.transfer_protected_for(id, winner_id).await {
+                Ok(None) => {}
+                Ok(Some(reason)) => {
+                    *result = OperationResult::Skipped {
+                        id: id.clone(),
+                        reason,
+                    };
+                }
+                Err(e) => {
+                    *result = OperationResult::Failed {
+                        id: id.clone(),
+                        error: format!("Protected membership not transferred: {}", e),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Transfer and verify protected membership of one loser.
+    ///
+    /// Returns the reason the loser is held for review instead, if it is in
+    /// a protected album owned by another user under the review policy.
+    async fn transfer_protected_for(&self, loser_id: &str, winner_id: &str) -> Result<Option<String>> {
+        let winner = vec![winner_id.to_string()];
+
+        // Albums
+        if !self.config.protected_albums.is_empty() {
+            let albums = self
+                .rate_limited(|| async { self.client.get_asset_albums(loser_id).await })
+                .await?;
+            let mut protected = Vec::new();
+            for album in albums
+                .iter()
+                .filter(|a| is_protected(&self.config.protected_albums, &[&a.id, &a.album_name]))
+            {
+                if self.config.shared_albums == SharedAlbumPolicy::Attempt || self.can_edit(album).await {
+                    protected.push(album);
+                } else if self.config.shared_albums == SharedAlbumPolicy::Review {
+                    // Held at planning, unless the plan was made before the album was shared
+                    return Ok(Some(shared_album_reason(album, SharedAlbumPolicy::Review)));
+                }
+            }
+
+            if !protected.is_empty() {
+                for album in &protected {
+                    let _album = self.lock_album(&album.id).await;
+                    self.rate_limited(|| async { self.client.add_assets_to_album(&album.id, &winner).await })
+                        .await?;
+                }
+
+                let winner_albums = self
+                    .rate_limited(|| async { self.client.get_asset_albums(winner_id).await })
+                    .await?;
+                if let Some(missing) = protected
+                    .iter()
+                    .find(|p| !winner_albums.iter().any(|a| a.id == p.id))
+                {
+                    return Err(ImmichError::TransferNotApplied {
+                        winner_id: winner_id.to_string(),
+                        target: format!("album '{}'", missing.album_name),
+                    });
+                }
+            }
+        }
+
+        // Tags
+        if !self.config.protected_tags.is_empty() {
+            let loser = self
+                .rate_limited(|| async { self.client.get_asset(loser_id).await })
+                .await?;
+            let protected: Vec<_> = loser
+                .tags
+                .iter()
+                .filter(|t| is_protected(&self.config.protected_tags, &[&t.id, &t.name, &t.value]))
+                .collect();
+
+            if !protected.is_empty() {
+                for tag in &protected {
+                    self.rate_limited(|| async { self.client.tag_assets(&tag.id, &winner).await })
+                        .await?;
+                }
+
+                let winner_asset = self
+                    .rate_limited(|| async { self.client.get_asset(winner_id).await })
+                    .await?;
+                if let Some(missing) = protected
+                    .iter()
+                    .find(|p| !winner_asset.tags.iter().any(|t| t.id == p.id))
+                {
+                    return Err(ImmichError::TransferNotApplied {
+                        winner_id: winner_id.to_string(),
+                        target: format!("tag '{}'", missing.value),
+                    });
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Apply the group's planned album replacements: add the winner to each
+    /// album, then remove the loser.
+    ///
+    /// The loser is only removed once the winner is in the album, so a
+    /// failure never leaves the album without either.
+    async fn replace_in_albums(&self, group: &GroupPlan, pb: &ProgressBar) -> Vec<Promotion> {
+        let winner = vec![group.winner_id.clone()];
+        let mut promotions = Vec::new();
+
+        for operation in &group.operations {
+            let PlannedOperation::ReplaceInAlbum {
+                album_id,
+                album_name,
+                loser_id,
+            } = operation
+            else {
+                continue;
+            };
+
+            pb.set_message(format!("Replacing {} in album {}", loser_id, album_name));
+            let loser = vec![loser_id.clone()];
+            let result = async {
+                let _album = self.lock_album(album_id).await;
+                self.rate_limited(|| async { self.client.add_assets_to_album(album_id, &winner).await })
+                    .await?;
+                self.rate_limited(|| async { self.client.remove_assets_from_album(album_id, &loser).await })
+                    .await
+            }
+            .await;
+
+            let error = result.err().map(|e| e.to_string());
+            self.record_event(ExecutionEvent::ReplacedInAlbum {
+                group_id: group.duplicate_id.clone(),
+                album_id: album_id.clone(),
+                loser_id: loser_id.clone(),
+                error: error.clone(),
+            });
+            promotions.push(Promotion {
+                kind: PromotionKind::AlbumMembership,
+                target_id: album_id.clone(),
+                target_name: album_name.clone(),
+                replaced_asset_id: loser_id.clone(),
+                error,
+            });
+        }
+
+        promotions
+    }
+
+    /// Apply the group's planned shared link re-points for losers that were
+    /// backed up: add the winner to each link, or to the album it shares.
+    ///
+    /// Losers whose links can't be re-pointed are marked failed so they are
+    /// not deleted out from under the link's recipients.
+    async fn repoint_shared_links(
+        &self,
+        group: &GroupPlan,
+        download_results: &mut [OperationResult],
+        pb: &ProgressBar,
+    ) -> Vec<Promotion> {
+        let winner = vec![group.winner_id.clone()];
+        let mut promotions = Vec::new();
+
+        for operation in &group.operations {
+            let PlannedOperation::RepointSharedLink {
+                link_id,
+                link_name,
+                album_id,
+                loser_id,
+            } = operation
+            else {
+                continue;
+            };
+            let Some(result) = download_results
+                .iter_mut()
+                .find(|r| matches!(r, OperationResult::Success { id, .. } if id == loser_id))
+            else {
+                continue;
+            };
+
+            pb.set_message(format!("Re-pointing shared link {}", link_id));
+            let outcome = match album_id {
+                Some(album_id) => {
+                    let _album = self.lock_album(album_id).await;
+                    self.rate_limited(|| async { self.client.add_assets_to_album(album_id, &winner).await })
+                        .await
+                }
+                None => {
+                    self.rate_limited(|| async { self.client.add_shared_link_assets(link_id, &winner).await })
+                        .await
+                }
+            };
+
+            let error = outcome.err().map(|e| e.to_string());
+            if let Some(error) = &error {
+                *result = OperationResult::Failed {
+                    id: loser_id.clone(),
+                    error: format!("Shared link {} not re-pointed: {}", link_id, error),
+                };
+            }
+            self.record_event(ExecutionEvent::RepointedSharedLink {
+                group_id: group.duplicate_id.clone(),
+                link_id: link_id.clone(),
+                loser_id: loser_id.clone(),
+                error: error.clone(),
+            });
+            promotions.push(Promotion {
+                kind: PromotionKind::SharedLink,
+                target_id: link_id.clone(),
+                target_name: link_name.clone(),
+                replaced_asset_id: loser_id.clone(),
+                error,
+            });
+        }
+
+        promotions
+    }
+
+    /// Apply the group's planned memory additions for losers being deleted.
+    ///
+    /// Failures are reported, but don't stop the deletion: the memory only
+    /// loses one of several copies of the moment.
+    async fn add_to_memories(&self, group: &GroupPlan, loser_ids: &[String], pb: &ProgressBar) -> Vec<Promotion> {
+        let winner = vec![group.winner_id.clone()];
+        let mut promotions = Vec::new();
+
+        for operation in &group.operations {
+            let PlannedOperation::AddToMemory { memory_id, loser_id } = operation else {
+                continue;
+            };
+            if !loser_ids.contains(loser_id) {
+                continue;
+            }
+
+            pb.set_message(format!("Adding winner to memory {}", memory_id));
+            let result = self
+                .rate_limited(|| async { self.client.add_memory_assets(memory_id, &winner).await })
+                .await;
+            promotions.push(Promotion {
+                kind: PromotionKind::Memory,
+                target_id: memory_id.clone(),
+                target_name: String::new(),
+                replaced_asset_id: loser_id.clone(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+
+        promotions
+    }
+
+    /// Albums whose cover is one of `loser_ids`, to be handed to the winner
+    /// once the loser is deleted.
+    ///
+    /// Albums that can't be listed, or that the shared album policy leaves
+    /// alone, are reported as failed promotions straight away.
+    async fn find_album_covers(&self, loser_ids: &[String]) -> (Vec<CoverSwap>, Vec<Promotion>) {
+        let mut covers = Vec::new();
+        let mut promotions: Vec<Promotion> = Vec::new();
+
+        for loser_id in loser_ids {
+            let albums = match self
+                .rate_limited(|| async { self.client.get_asset_albums(loser_id).await })
+                .await
+            {
+                Ok(albums) => albums,
+                Err(e) => {
+                    promotions.push(Promotion {
+                        kind: PromotionKind::AlbumCover,
+                        target_id: String::new(),
+                        target_name: String::new(),
+                        replaced_asset_id: loser_id.clone(),
+                        error: Some(format!("Could not list albums: {}", e)),
+                    });
+                    continue;
+                }
+            };
+
+            for album in albums
+                .into_iter()
+                .filter(|a| a.album_thumbnail_asset_id.as_deref() == Some(loser_id.as_str()))
+            {
+                if self.config.shared_albums != SharedAlbumPolicy::Attempt && !self.can_edit(&album).await {
+                    promotions.push(Promotion {
+                        kind: PromotionKind::AlbumCover,
+                        target_id: album.id.clone(),
+                        target_name: album.album_name.clone(),
+                        replaced_asset_id: loser_id.clone(),
+                        error: Some(shared_album_reason(&album, self.config.shared_albums)),
+                    });
+                    continue;
+                }
+                covers.push(CoverSwap {
+                    album_id: album.id,
+                    album_name: album.album_name,
+                    loser_id: loser_id.clone(),
+                });
+            }
+        }
+
+        (covers, promotions)
+    }
+
+    /// Make the winner the cover of each album in `covers`.
+    ///
+    /// Only called for losers that were actually deleted, so the winner
+    /// never joins an album whose loser stays. The winner is added to the
+    /// album first, since Immich only accepts a cover from the album's own
+    /// assets. Failures are reported; Immich has already picked a new cover.
+    async fn promote_album_covers(&self, winner_id: &str, covers: &[CoverSwap]) -> Vec<Promotion> {
+        let winner = vec![winner_id.to_string()];
+        let mut promotions = Vec::new();
+
+        for cover in covers {
+            let result = async {
+                let _album = self.lock_album(&cover.album_id).await;
+                self.rate_limited(|| async { self.client.add_assets_to_album(&cover.album_id, &winner).await })
+                    .await?;
+                self.rate_limited(|| async { self.client.set_album_cover(&cover.album_id, winner_id).await })
+                    .await
+            }
+            .await;
+
+            promotions.push(Promotion {
+                kind: PromotionKind::AlbumCover,
+                target_id: cover.album_id.clone(),
+                target_name: cover.album_name.clone(),
+                replaced_asset_id: cover.loser_id.clone(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+
+        promotions
+    }
+
+    /// Write XMP sidecars with the group's consolidated metadata next to each backup.
+    ///
+    /// The sidecar starts from the winner's metadata and fills any gaps from the
+    /// losers in order. If the metadata can't be collected or a sidecar can't be
+    /// written, the affected downloads are marked failed so their assets are not
+    /// deleted without the portable record.
+    async fn write_sidecars(
+        &self,
+        group: &GroupPlan,
+        download_results: &mut [OperationResult],
+    ) {
+        let metadata = match self.collect_sidecar_metadata(group).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                for result in download_results.iter_mut() {
+                    if let OperationResult::Success { id, .. } = result {
+                        *result = OperationResult::Failed {
+                            id: id.clone(),
+                            error: format!("Failed to collect sidecar metadata: {}", e),
+                        };
+                    }
+                }
+                return;
+            }
+        };
+
+        for result in download_results.iter_mut() {
+            if let OperationResult::Success {
+                id,
+                path: Some(path),
+            } = result
+                && let Err(e) = self.store_sidecar(path, &metadata).await
+            {
+                *result = OperationResult::Failed {
+                    id: id.clone(),
+                    error: format!("Failed to write XMP sidecar: {}", e),
+                };
+            }
+        }
+    }
+
+    /// Write the sidecar for the backup at `path`: next to a local backup, or
+    /// as another object in a remote store.
+    async fn store_sidecar(&self, path: &std::path::Path, metadata: &SidecarMetadata) -> Result<()> {
+        if self.store.is_local() {
+            return write_sidecar(path, metadata).await.map(|_| ());
+        }
+        let name = sidecar_path(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.store.put_bytes(&name, metadata.to_xmp().into_bytes()).await
+    }
+
+    /// Embed each downloaded asset's own Immich-side metadata into its backup file.
+    ///
+    /// Failures mark the download failed so the asset isn't deleted while its
+    /// database-only metadata exists nowhere else.
+    async fn embed_backup_metadata(&self, download_results: &mut [OperationResult]) {
+        for result in download_results.iter_mut() {
+            let OperationResult::Success {
+                id,
+                path: Some(path),
+            } = result
+            else {
+                continue;
+            };
+
+            let embedded = async {
+                if !self.store.is_local() {
+                    return Err(ImmichError::Backup(format!(
+                        "{} is not a local file; embedding needs a local backup",
+                        path.display()
+                    )));
+                }
+                let asset = self
+                    .rate_limited(|| async { self.client.get_asset(id).await })
+                    .await?;
+                let metadata = asset
+                    .exif_info
+                    .as_ref()
+                    .map(SidecarMetadata::from_exif)
+                    .unwrap_or_default();
+                embed_metadata(path, &metadata).await.map(|_| ())
+            }
+            .await;
+
+            if let Err(e) = embedded {
+                *result = OperationResult::Failed {
+                    id: id.clone(),
+                    error: format!("Failed to embed metadata in backup: {}", e),
+                };
+            }
+        }
+    }
+
+    /// Collect consolidated metadata for a group (winner first, then losers).
+    async fn collect_sidecar_metadata(
+        &self,
+        group: &GroupPlan,
+    ) -> Result<SidecarMetadata> {
+        let mut metadata = SidecarMetadata::default();
+
+        let asset_ids = std::iter::once(group.winner_id.as_str()).chain(group.loser_ids());
+
+        for asset_id in asset_ids {
+            let asset = self
+                .rate_limited(|| async { self.client.get_asset(asset_id).await })
+                .await?;
+            if let Some(exif) = &asset.exif_info {
+                metadata.merge_missing(&SidecarMetadata::from_exif(exif));
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Download a loser asset to the backup store, returning the result and
+    /// the size of the backup if it was written.
+    ///
+    /// Backups are named as `{asset_id}_{filename}` to avoid collisions.
+    async fn download_loser(&self, asset_id: &str, filename: &str) -> (OperationResult, Option<u64>) {
+        // Build name with asset ID prefix to avoid collisions
+        let safe_filename = format!("{}_{}", asset_id, filename);
+
+        let download_result = self
+            .rate_limited(|| async { self.store.put_asset(&self.client, asset_id, &safe_filename).await })
+            .await;
+
+        match download_result {
+            // Local backups get the full integrity check later
+            Ok(0) if !self.store.is_local() => (
+                OperationResult::Failed {
+                    id: asset_id.to_string(),
+                    error: format!("Corrupt original: {}", IntegrityIssue::ZeroBytes),
+                },
+                None,
+            ),
+            Ok(bytes) => (
+                OperationResult::Success {
+                    id: asset_id.to_string(),
+                    path: Some(self.store.location(&safe_filename)),
+                },
+                Some(bytes),
+            ),
+            // Already gone, e.g. when re-running an interrupted execution
+            Err(e) if AssetState::from_error(&e) == AssetState::Deleted => (
+                OperationResult::Skipped {
+                    id: asset_id.to_string(),
+                    reason: "Asset was already deleted".to_string(),
+                },
+                None,
+            ),
+            Err(e) => (
+                OperationResult::Failed {
+                    id: asset_id.to_string(),
+                    error: e.to_string(),
+                },
+                None,
+            ),
+        }
+    }
+
+    /// Delete assets using the API, [`ExecutionConfig::delete_chunk_size`]
+    /// per request. Each chunk is retried on its own if rate limited, and a
+    /// chunk that fails doesn't stop the rest.
+    async fn delete_assets(&self, asset_ids: &[String]) -> Result<()> {
+        let force = self.config.force_delete;
+        delete_in_chunks(asset_ids, self.config.delete_chunk_size, |chunk| {
+            self.rate_limited(move || async move { self.client.delete_asset_chunk(chunk, force).await })
+        })
+        .await
+        .into_result()
+    }
+}
+
+/// A group to process: analyzed (planned when its turn comes) or already planned.
+#[derive(Clone, Copy)]
+enum GroupWork<'a> {
+    Analysis(&'a DuplicateAnalysis),
+    Plan(&'a GroupPlan),
+}
+
+impl GroupWork<'_> {
+    fn duplicate_id(&self) -> &str {
+        match self {
+            Self::Analysis(analysis) => &analysis.duplicate_id,
+            Self::Plan(group) => &group.duplicate_id,
+        }
+    }
+
+    fn losers(&self) -> usize {
+        match self {
+            Self::Analysis(analysis) => analysis.losers.len(),
+            Self::Plan(group) => group.loser_ids().len(),
+        }
+    }
+}
+
+/// What a group's steps before the delete step did.
+#[derive(Default)]
+struct PreparedGroup {
+    consolidation_result: Option<ConsolidationResult>,
+    download_results: Vec<OperationResult>,
+    promotions: Vec<Promotion>,
+    /// Albums whose cover goes to the winner once their loser is deleted
+    covers: Vec<CoverSwap>,
+    /// Whether a downloaded original was zero-byte or corrupt
+    corrupt_backup: bool,
+    /// Size of each loser's backup as downloaded
+    backup_sizes: HashMap<String, u64>,
+    /// Losers backed up
+    downloaded_ids: Vec<String>,
+    /// Losers backed up that the plan deletes
+    delete_ids: Vec<String>,
+}
+
+/// An album whose cover is a loser the group deletes.
+struct CoverSwap {
+    album_id: String,
+    album_name: String,
+    loser_id: String,
+}
+
+/// What a group's delete step did.
+struct DeleteOutcome {
+    result: OperationResult,
+    deleted_ids: Vec<String>,
+    rolled_back_ids: Vec<String>,
+}
+
+/// Append each downloaded backup, and its sidecar if there is one, to
+/// `archive`, removing the loose files once they are safely archived.
+///
+/// A backup that can't be archived is marked failed so its asset is not
+/// deleted; its loose file is kept.
+async fn archive_backups(archive: &dyn BackupArchive, group_id: &str, download_results: &mut [OperationResult]) {
+    for result in download_results.iter_mut() {
+        let OperationResult::Success {
+            id,
+            path: Some(path),
+        } = result
+        else {
+            continue;
+        };
+        let Some(member) = path.file_name().map(|name| name.to_string_lossy().into_owned()) else {
+            continue;
+        };
+
+        let sidecar = sidecar_path(path);
+        let archived = async {
+            archive.add_file(path, &member, id, group_id).await?;
+            if tokio::fs::try_exists(&sidecar).await? {
+                archive.add_file(&sidecar, &format!("{}.xmp", member), id, group_id).await?;
+            }
+            Ok::<_, ImmichError>(())
+        }
+        .await;
+
+        match archived {
+            Ok(()) => {
+                // The archive is synced, so the loose copies are no longer needed
+                let _ = tokio::fs::remove_file(&*path).await;
+                let _ = tokio::fs::remove_file(&sidecar).await;
+                *path = archive.path().to_path_buf();
+            }
+            Err(e) => {
+                *result = OperationResult::Failed {
+                    id: id.clone(),
+                    error: format!("Failed to archive backup: {}", e),
+                };
+            }
+        }
+    }
+}
+
+/// Add a group's downloads, deletions, failures, and reclaimed bytes to `metrics`.
+fn record_metrics(metrics: &Metrics, result: &GroupResult) {
+    let downloaded: Vec<&str> = result
+        .download_results
+        .iter()
+        .filter_map(|r| match r {
+            OperationResult::Success { id, .. } => Some(id.as_str()),
+            _ => None,
+        })
+        .collect();
+    metrics.add_downloads(downloaded.len() as u64);
+
+    let failures = result
+        .download_results
+        .iter()
+        .chain(&result.delete_result)
+        .filter(|r| matches!(r, OperationResult::Failed { .. }))
+        .count();
+    metrics.add_failures(failures as u64);
+
+    if !result.deleted_ids.is_empty() {
+        // The backed-up sizes the report counts, not the planned ones
+        let bytes = result.reclaimed_bytes + result.trashed_bytes;
+        metrics.add_deletions(result.deleted_ids.len() as u64, bytes);
+    }
+}
+
+/// Metadata written to the winner in one update.
+struct MetadataUpdate<'a> {
+    latitude: Option<(f64, f64)>,
+    date_time_original: Option<&'a str>,
+    description: Option<&'a str>,
+}
+
+impl MetadataUpdate<'_> {
+    /// The update restricted to `fields`.
+    fn only(&self, fields: &[MetadataField]) -> Self {
+        Self {
+            latitude: self.latitude.filter(|_| fields.contains(&MetadataField::Gps)),
+            date_time_original: self.date_time_original.filter(|_| fields.contains(&MetadataField::Datetime)),
+            description: self.description.filter(|_| fields.contains(&MetadataField::Description)),
+        }
+    }
+
+    /// The value written to `field`, as recorded in a [`FieldTransfer`].
+    fn value(&self, field: MetadataField) -> Option<String> {
+        match field {
+            MetadataField::Gps => self.latitude.map(|(lat, lon)| format!("{},{}", lat, lon)),
+            MetadataField::Datetime => self.date_time_original.map(str::to_string),
+            MetadataField::Description => self.description.map(str::to_string),
+        }
+    }
+
+    /// The value `exif` has for `field`, formatted as by [`value`](Self::value).
+    fn found(exif: Option<&ExifInfo>, field: MetadataField) -> Option<String> {
+        let exif = exif?;
+        match field {
+            MetadataField::Gps => exif.latitude.zip(exif.longitude).map(|(lat, lon)| format!("{},{}", lat, lon)),
+            MetadataField::Datetime => exif.date_time_original.clone(),
+            MetadataField::Description => exif.description.clone(),
+        }
+    }
+
+    /// Fields of this update that `exif` (the winner's, read back) doesn't
+    /// have. Coordinates are compared to within about a centimetre and
+    /// date/times as instants.
+    fn unapplied(&self, exif: Option<&ExifInfo>) -> Vec<MetadataField> {
+        let gps_applied = self.latitude.is_none_or(|(lat, lon)| {
+            exif.and_then(|e| e.latitude.zip(e.longitude))
+                .is_some_and(|(l, o)| (l - lat).abs() < 1e-7 && (o - lon).abs() < 1e-7)
+        });
+        let datetime_applied = self.date_time_original.is_none_or(|expected| {
+            exif.and_then(|e| e.date_time_original.as_deref()).is_some_and(|found| {
+                match (DateTime::parse_from_rfc3339(expected), DateTime::parse_from_rfc3339(found)) {
+                    (Ok(expected), Ok(found)) => expected == found,
+                    _ => expected == found,
+                }
+            })
+        });
+        let description_applied = self.description.is_none_or(|expected| {
+            exif.and_then(|e| e.description.as_deref())
+                .is_some_and(|found| found.trim() == expected.trim())
+        });
+
+        [
+            (MetadataField::Gps, gps_applied),
+            (MetadataField::Datetime, datetime_applied),
+            (MetadataField::Description, description_applied),
+        ]
+        .into_iter()
+        .filter(|(_, applied)| !applied)
+        .map(|(field, _)| field)
+        .collect()
+    }
+}
+
+/// Precision of GPS coordinates: the decimal places of the less precise one.
+///
+/// Coordinates are compared as written, so `51.5` counts one place and
+/// `51.50123` five.
+fn gps_precision(latitude: f64, longitude: f64) -> usize {
+    let places = |value: f64| value.to_string().split_once('.').map_or(0, |(_, fraction)| fraction.len());
+    places(latitude).min(places(longitude))
+}
+
+/// IDs of every loser in `analysis`, for its plan.
+fn loser_ids(analysis: &DuplicateAnalysis) -> Vec<String> {
+    analysis.losers.iter().map(|l| l.asset_id.to_string()).collect()
+}
+
+/// Why an album owned by another user is left alone under `policy`.
+fn shared_album_reason(album: &AlbumResponse, policy: SharedAlbumPolicy) -> String {
+    format!(
+        "Album '{}' is owned by another user (shared album policy: {})",
+        album.album_name, policy
+    )
+}
+
+/// Returns true if any of `keys` (ID, name, ...) matches a protected entry.
+///
+/// Names are compared case-insensitively.
+fn is_protected(protected: &[String], keys: &[&str]) -> bool {
+    protected
+        .iter()
+        .any(|p| keys.iter().any(|k| !k.is_empty() && k.eq_ignore_ascii_case(p)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DuplicateGroup;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    const WINNER: &str = "asset-000000-0";
+    const LOSER: &str = "asset-000000-1";
+
+    /// A mock server holding duplicate groups, their analyses, and a
+    /// directory for backups.
+    struct TestRun {
+        server: MockServer,
+        analyses: Vec<DuplicateAnalysis>,
+        dir: tempfile::TempDir,
+    }
+
+    impl TestRun {
+        /// `count` synthetic groups of `assets_per_group` assets each.
+        async fn start(count: usize, assets_per_group: usize) -> Self {
+            Self::with_groups(&synthetic_groups(count, assets_per_group).unwrap()).await
+        }
+
+        /// The given groups.
+        async fn with_groups(groups: &[DuplicateGroup]) -> Self {
+            Self {
+                server: MockServer::start(groups).await.unwrap(),
+                analyses: groups.iter().map(DuplicateAnalysis::from_group).collect(),
+                dir: tempfile::tempdir().unwrap(),
+            }
+        }
+
+        fn client(&self) -> ImmichClient {
+            ImmichClient::new(&self.server.url(), "test-key").unwrap()
+        }
+
+        /// Requests unthrottled and backups in the run's directory; tests
+        /// set the rest with `..run.config()`.
+        fn config(&self) -> ExecutionConfig {
+            ExecutionConfig {
+                requests_per_sec: u32::MAX,
+                backup_dir: self.dir.path().to_path_buf(),
+                ..Default::default()
+            }
+        }
+
+        fn executor(&self, config: ExecutionConfig) -> Executor {
+            Executor::new(self.client(), config)
+        }
+
+        /// Execute every group with `config`.
+        async fn execute(&self, config: ExecutionConfig) -> ExecutionReport {
+            self.executor(config).execute_all(&self.analyses).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shared_album_policy() {
+        let execute = |policy| async move {
+            let run = TestRun::start(1, 2).await;
+            run.server.add_shared_album("album-partner", "Frame Feed", "partner", &[LOSER]);
+            let executor = run.executor(ExecutionConfig {
+                protected_albums: vec!["frame feed".to_string()],
+                shared_albums: policy,
+                ..run.config()
+            });
+            let plan = executor.plan(&run.analyses).await;
+            let report = executor.execute_all(&run.analyses).await;
+            (plan, report, run.server.album_assets("album-partner"))
+        };
+
+        // The group is held at planning, before anything is downloaded
+        let (plan, report, _) = execute(SharedAlbumPolicy::Review).await;
+        assert_eq!(
+            plan.groups[0].delete_skipped(),
+            Some("Album 'Frame Feed' is owned by another user (shared album policy: review)")
+        );
+        assert_eq!(report.deleted, 0);
+
+        // A plan made before the album was shared holds the loser when applied
+        let run = TestRun::start(1, 2).await;
+        let executor = run.executor(ExecutionConfig {
+            protected_albums: vec!["frame feed".to_string()],
+            shared_albums: SharedAlbumPolicy::Review,
+            ..run.config()
+        });
+        let plan = executor.plan(&run.analyses).await;
+        run.server.add_shared_album("album-partner", "Frame Feed", "partner", &[LOSER]);
+        let report = executor.apply(&plan).await.unwrap();
+        assert_eq!(report.deleted, 0);
+        assert!(matches!(
+            &report.results[0].download_results[0],
+            OperationResult::Skipped { id, reason }
+                if id == LOSER && reason == "Album 'Frame Feed' is owned by another user (shared album policy: review)"
+        ));
+
+        // The album is left out, and the loser deleted
+        let (_, report, members) = execute(SharedAlbumPolicy::Skip).await;
+        assert_eq!(report.deleted, 1);
+        assert_eq!(members, [LOSER]);
+
+        // The transfer is refused, so the loser is kept
+        let (_, report, _) = execute(SharedAlbumPolicy::Attempt).await;
+        assert_eq!(report.deleted, 0);
+        assert!(matches!(
+            &report.results[0].download_results[0],
+            OperationResult::Failed { error, .. } if error.contains("403")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_deletions_recorded_in_ledger() {
+        let run = TestRun::start(1, 3).await;
+        let ledger_path = run.dir.path().join("ledger.jsonl");
+
+        let report = run
+            .execute(ExecutionConfig {
+                ledger_path: Some(ledger_path.clone()),
+                ..run.config()
+            })
+            .await;
+        assert_eq!(report.deleted, 2);
+
+        let entries = Ledger::new(&ledger_path).read_all().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.action == LedgerAction::Trashed && e.winner_id == WINNER));
+        assert!(entries.iter().all(|e| e.checksum.is_some() && e.backup_path.as_ref().is_some_and(|p| p.exists())));
+        assert!(entries.iter().any(|e| e.asset_id == LOSER));
+    }
+
+    #[tokio::test]
+    async fn test_already_deleted_loser_skipped() {
+        let mut run = TestRun::start(1, 2).await;
+        let mut gone = run.analyses[0].losers[0].clone();
+        gone.asset_id = "asset-deleted-earlier".into();
+        run.analyses[0].losers.push(gone);
+
+        let report = run.execute(run.config()).await;
+
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.deleted, 1);
+    }
+
+    #[tokio::test]
+    async fn test_edited_plan_applied() {
+        let run = TestRun::start(1, 3).await;
+        let executor = run.executor(run.config());
+
+        // Planning changes nothing, and the plan survives a round trip
+        let plan = executor.plan(&run.analyses).await;
+        assert!(run.server.deleted_assets().is_empty());
+        assert_eq!(plan.assets_to_delete(), 2);
+        let json = serde_json::to_string(&plan).unwrap();
+        let mut plan: ExecutionPlan = serde_json::from_str(&json).unwrap();
+
+        // Keep one loser: it is still backed up, but not deleted
+        for op in &mut plan.groups[0].operations {
+            if let PlannedOperation::Delete { asset_ids } = op {
+                asset_ids.retain(|id| id != LOSER);
+            }
+        }
+
+        // Deleting the winner, or an asset outside the group, is refused
+        for id in ["asset-000000-0", "asset-000001-1"] {
+            let mut edited = plan.clone();
+            for op in &mut edited.groups[0].operations {
+                if let PlannedOperation::Delete { asset_ids } = op {
+                    asset_ids.push(id.to_string());
+                }
+            }
+            assert!(matches!(executor.apply(&edited).await, Err(ImmichError::InvalidPlan(_))));
+        }
+        assert!(run.server.deleted_assets().is_empty());
+
+        let forced = run.executor(ExecutionConfig {
+            force_delete: true,
+            ..run.config()
+        });
+        assert!(matches!(forced.apply(&plan).await, Err(ImmichError::InvalidPlan(_))));
+
+        let report = executor.apply(&plan).await.unwrap();
+        assert_eq!((report.downloaded, report.deleted), (2, 1));
+        let deleted = run.server.deleted_assets();
+        assert_eq!(deleted.len(), 1);
+        assert!(!deleted.iter().any(|id| id == LOSER));
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_winner_holds_group_for_review() {
+        let run = TestRun::start(2, 2).await;
+        run.server.corrupt_originals_of(&["asset-000000-0"]);
+
+        let report = run.execute(run.config()).await;
+        let Some(OperationResult::Skipped { reason, .. }) = &report.results[0].delete_result else {
+            panic!("expected a skipped delete, got {:?}", report.results[0].delete_result);
+        };
+        assert!(reason.starts_with("Needs review (corrupt_original)"), "{}", reason);
+        // Only the group with an intact winner lost its loser
+        let deleted: Vec<String> = run.server.deleted_assets().into_iter().collect();
+        assert_eq!(deleted, ["asset-000001-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_album_only_mode_replaces_without_deleting() {
+        let run = TestRun::start(1, 2).await;
+        run.server.add_album("album-1", "Trip", &[LOSER]);
+
+        let report = run
+            .execute(ExecutionConfig {
+                mode: ExecutionMode::AlbumOnly,
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!((report.downloaded, report.deleted, report.failed), (0, 0, 0));
+        assert!(report.results[0].delete_result.is_none());
+        assert!(run.server.deleted_assets().is_empty());
+        assert_eq!(run.server.album_assets("album-1"), vec![WINNER.to_string()]);
+        let promotion = &report.results[0].promotions[0];
+        assert_eq!((promotion.kind, promotion.succeeded()), (PromotionKind::AlbumMembership, true));
+    }
+
+    #[tokio::test]
+    async fn test_shared_link_policy() {
+        let execute = |policy| async move {
+            let run = TestRun::start(3, 2).await;
+            run.server.add_shared_link("link-1", &[LOSER]);
+            run.server.add_album("album-1", "Trip", &[LOSER]);
+            run.server.share_album("link-2", "album-1");
+            let report = run
+                .execute(ExecutionConfig {
+                    shared_links: policy,
+                    ..run.config()
+                })
+                .await;
+            (run, report)
+        };
+
+        // Both links show the winner before the loser goes, and the links
+        // are listed once for all three groups
+        let (run, report) = execute(SharedLinkPolicy::Repoint).await;
+        assert_eq!(report.deleted, 3);
+        assert_eq!(run.server.shared_link_requests(), 1);
+        assert!(run.server.shared_link_assets("link-1").contains(&WINNER.to_string()));
+        assert!(run.server.album_assets("album-1").contains(&WINNER.to_string()));
+        let promotions = &report.results[0].promotions;
+        assert_eq!(promotions.len(), 2);
+        assert!(promotions.iter().all(|p| p.kind == PromotionKind::SharedLink && p.succeeded()));
+
+        // The group is left alone, with the reason recorded
+        let (run, report) = execute(SharedLinkPolicy::Review).await;
+        assert_eq!((report.downloaded, report.deleted), (2, 2));
+        assert!(!run.server.deleted_assets().contains(LOSER));
+        assert!(matches!(
+            &report.results[0].delete_result,
+            Some(OperationResult::Skipped { reason, .. }) if reason.contains("shared link link-1")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_album_locks_serialize_same_album() {
+        let client = ImmichClient::new("http://localhost:2283", "test-key").unwrap();
+        let executor = Executor::new(client, ExecutionConfig::default());
+        let wait = std::time::Duration::from_millis(50);
+
+        let held = executor.lock_album("album-1").await;
+        assert!(tokio::time::timeout(wait, executor.lock_album("album-1")).await.is_err());
+        assert!(tokio::time::timeout(wait, executor.lock_album("album-2")).await.is_ok());
+
+        drop(held);
+        assert!(tokio::time::timeout(wait, executor.lock_album("album-1")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_preserve_memories() {
+        let mut run = TestRun::start(1, 2).await;
+        run.server.add_memory("memory-1", &[LOSER]);
+        let memories = run.client().get_memories().await.unwrap();
+        run.analyses[0].flag_memories(&memories);
+        assert_eq!(run.analyses[0].loser_memories().unwrap()["memory-1"], vec![LOSER.to_string()]);
+
+        let report = run
+            .execute(ExecutionConfig {
+                preserve_memories: true,
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!(report.deleted, 1);
+        assert!(run.server.memory_assets("memory-1").contains(&WINNER.to_string()));
+        let promotion = &report.results[0].promotions[0];
+        assert_eq!((promotion.kind, promotion.succeeded()), (PromotionKind::Memory, true));
+    }
+
+    #[tokio::test]
+    async fn test_backups_archived() {
+        for sink in [BackupSink::Tar, BackupSink::Zip] {
+            let run = TestRun::start(2, 2).await;
+
+            let report = run
+                .execute(ExecutionConfig {
+                    backup_sink: sink,
+                    write_sidecars: true,
+                    ..run.config()
+                })
+                .await;
+
+            assert_eq!(report.deleted, 2, "{}", sink);
+            assert!(report.archive_error.is_none());
+            let archive = report.backup_archive.clone().unwrap();
+            assert_eq!(archive.extension().unwrap(), sink.as_str());
+            let OperationResult::Success { id, path: Some(path) } = &report.results[0].download_results[0] else {
+                panic!("loser not backed up");
+            };
+            // Reports point at the archive itself; its manifest names the member
+            assert_eq!(path, &archive);
+
+            // Only the archive is left: backups and sidecars were moved into it
+            let files: Vec<_> = std::fs::read_dir(run.dir.path()).unwrap().map(|e| e.unwrap().path()).collect();
+            assert_eq!(files, vec![archive.clone()]);
+            let bytes = std::fs::read(&archive).unwrap();
+            let start = b"{\n  \"created_at\"";
+            let manifest_at = bytes.windows(start.len()).rposition(|w| w == start).unwrap();
+            let manifest: crate::archive::BackupManifest =
+                serde_json::Deserializer::from_slice(&bytes[manifest_at..])
+                    .into_iter()
+                    .next()
+                    .unwrap()
+                    .unwrap();
+            let members: Vec<&str> = manifest
+                .entries
+                .iter()
+                .filter(|e| e.asset_id == *id)
+                .map(|e| e.member.as_str())
+                .collect();
+            assert_eq!(members.len(), 2);
+            assert!(members[0].starts_with(id.as_str()));
+            assert_eq!(members[1], format!("{}.xmp", members[0]));
+        }
+    }
+
+    #[cfg(feature = "s3")]
+    #[tokio::test]
+    async fn test_backups_streamed_to_s3() {
+        use crate::backup::S3Config;
+        use crate::models::BackupTarget;
+
+        let run = TestRun::start(1, 2).await;
+
+        let report = run
+            .execute(ExecutionConfig {
+                backup_target: BackupTarget::S3(Box::new(S3Config {
+                    endpoint: url::Url::parse(&run.server.url()).unwrap(),
+                    bucket: "backups".to_string(),
+                    prefix: "immich/".to_string(),
+                    region: "us-east-1".to_string(),
+                    access_key_id: "minio".to_string(),
+                    secret_access_key: "minio-secret".to_string(),
+                })),
+                write_sidecars: true,
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!(report.deleted, 1);
+        let OperationResult::Success { id, path: Some(path) } = &report.results[0].download_results[0] else {
+            panic!("loser not backed up");
+        };
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with(id.as_str()));
+        assert_eq!(path, &std::path::PathBuf::from(format!("s3://backups/immich/{}", name)));
+        let object = run.server.object(&format!("backups/immich/{}", name)).unwrap();
+        assert_eq!(&object[..4], &[0xFF, 0xD8, 0xFF, 0xE0]);
+        assert!(run.server.object(&format!("backups/immich/{}.xmp", name)).is_some());
+        // Nothing was written locally
+        assert_eq!(std::fs::read_dir(run.dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_processed_winners_tagged() {
+        let run = TestRun::start(1, 2).await;
+
+        let report = run
+            .execute(ExecutionConfig {
+                tag_winners: Some("dedupe/processed".to_string()),
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!(report.deleted, 1);
+        assert_eq!(report.tagged_winners, vec![WINNER.to_string()]);
+        assert!(report.tagging_error.is_none());
+        assert_eq!(run.server.tagged_assets("dedupe/processed"), vec![WINNER.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_cross_library_policy() {
+        let mut run = TestRun::start(2, 2).await;
+        run.analyses[0].winner.library_id = Some("library-external".into());
+        assert!(run.analyses[0].is_cross_library());
+        let config = |cross_library| ExecutionConfig {
+            cross_library,
+            ..run.config()
+        };
+
+        // The upload loser isn't deleted in favour of the external winner
+        let report = run.execute(config(CrossLibraryPolicy::SameLibrary)).await;
+        assert_eq!(report.deleted, 1);
+        assert!(matches!(
+            report.results[0].download_results[0],
+            OperationResult::Skipped { .. }
+        ));
+
+        let report = run.execute(config(CrossLibraryPolicy::Proceed)).await;
+        assert_eq!(report.deleted, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cross_owner_groups_skipped_unless_allowed() {
+        let mut groups = synthetic_groups(2, 2).unwrap();
+        groups[0].assets[1].owner_id = "partner".to_string();
+        let run = TestRun::with_groups(&groups).await;
+        assert!(run.analyses[0].review_reasons.contains(&crate::scoring::ReviewReason::MixedOwners));
+        let config = |allow_cross_owner| ExecutionConfig {
+            allow_cross_owner,
+            ..run.config()
+        };
+
+        let report = run.execute(config(false)).await;
+        assert_eq!(report.deleted, 1);
+        assert!(report.results[0].deleted_ids.is_empty());
+
+        let report = run.execute(config(true)).await;
+        assert_eq!(report.deleted, 2);
+    }
+
+    #[tokio::test]
+    async fn test_stale_groups_skipped() {
+        let mut groups = synthetic_groups(4, 2).unwrap();
+        groups[3].assets[0].updated_at = Some("2024-06-01T12:00:00.000Z".to_string());
+        let mut run = TestRun::with_groups(&groups).await;
+        assert_eq!(run.analyses[3].winner.updated_at.as_deref(), Some("2024-06-01T12:00:00.000Z"));
+
+        // Winner deleted, loser re-uploaded with other content, and winner
+        // edited since the analysis
+        run.client().delete_assets(&["asset-000001-0".to_string()], true).await.unwrap();
+        run.analyses[2].losers[0].checksum = Some("checksum-before".into());
+        run.analyses[3].winner.updated_at = Some("2024-01-01T00:00:00.000Z".into());
+
+        let plan = run.executor(run.config()).plan(&run.analyses).await;
+        let stale: Vec<bool> = plan.groups.iter().map(GroupPlan::is_stale).collect();
+        assert_eq!(stale, [false, true, true, true]);
+        assert_eq!(plan.assets_to_delete(), 1);
+
+        let reasons: Vec<&str> = plan.groups[1..].iter().filter_map(GroupPlan::delete_skipped).collect();
+        assert_eq!(
+            reasons,
+            [
+                "Stale analysis: winner asset-000001-0 no longer exists",
+                "Stale analysis: asset asset-000002-1 was modified since it was analyzed",
+                "Stale analysis: asset asset-000003-0 was updated since it was analyzed",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consolidation_prefers_best_source_per_field() {
+        let mut groups = synthetic_groups(1, 3).unwrap();
+        groups[0].assets[0].exif_info.as_mut().unwrap().date_time_original = None;
+        // The precise coordinates come from the loser without a timezone
+        let precise = groups[0].assets[1].exif_info.as_mut().unwrap();
+        (precise.latitude, precise.longitude, precise.time_zone) = (Some(51.50123), Some(-0.12345), None);
+        let coarse = groups[0].assets[2].exif_info.as_mut().unwrap();
+        (coarse.latitude, coarse.longitude) = (Some(51.5), Some(-0.1));
+        let run = TestRun::with_groups(&groups).await;
+        assert_eq!(run.analyses[0].winner.asset_id.as_ref(), WINNER);
+
+        let plan = run.executor(run.config()).plan_group(&run.analyses[0]).await;
+        let Some(PlannedOperation::UpdateMetadata {
+            source_id,
+            sources,
+            latitude,
+            ..
+        }) = plan.operations.first()
+        else {
+            panic!("expected a metadata update, got {:?}", plan.operations);
+        };
+        assert_eq!(*latitude, Some(51.50123));
+        assert_eq!(sources.gps.as_deref(), Some(LOSER));
+        assert_eq!(sources.datetime.as_deref(), Some("asset-000000-2"));
+        assert_eq!(source_id.as_deref(), Some(LOSER));
+    }
+
+    #[tokio::test]
+    async fn test_consolidation_result_per_field() {
+        let run = TestRun::start(1, 2).await;
+
+        let report = run.execute(run.config()).await;
+        let consolidation = report.results[0].consolidation_result.as_ref().unwrap();
+        assert!(consolidation.transferred(MetadataField::Gps));
+        assert!(!consolidation.transferred(MetadataField::Datetime));
+        let gps = consolidation.field(MetadataField::Gps).unwrap();
+        assert_eq!(gps.source_asset_id.as_deref(), Some(LOSER));
+        assert_eq!(gps.value, "51.5,-0.12");
+        assert!(!consolidation.retried);
+    }
+
+    #[tokio::test]
+    async fn test_shared_metadata_updates_sent_in_bulk() {
+        let mut groups = synthetic_groups(3, 2).unwrap();
+        // The first two winners get the same coordinates, the third its own
+        let gps = groups[0].assets[1].exif_info.clone();
+        groups[1].assets[1].exif_info = gps;
+        let run = TestRun::with_groups(&groups).await;
+        let runs = run.dir.path().join("runs");
+
+        let report = run
+            .execute(ExecutionConfig {
+                history_dir: Some(runs.clone()),
+                ..run.config()
+            })
+            .await;
+        assert_eq!(run.server.bulk_update_requests(), 1);
+        for (i, result) in report.results.iter().enumerate() {
+            let consolidation = result.consolidation_result.as_ref().unwrap();
+            assert!(consolidation.transferred(MetadataField::Gps));
+            assert!(!consolidation.retried);
+            assert_eq!(consolidation.bulk, i < 2);
+        }
+
+        // The bulk request is in the run's history, ahead of its groups
+        let timeline = history::open(runs).unwrap().timeline(report.run_id.as_deref().unwrap()).unwrap();
+        assert!(matches!(
+            &timeline[1].event,
+            ExecutionEvent::MetadataBulkUpdated { group_ids, winner_ids }
+                if group_ids == &[run.analyses[0].duplicate_id.clone(), run.analyses[1].duplicate_id.clone()]
+                    && winner_ids == &["asset-000000-0", "asset-000001-0"]
+        ));
+        let winner = run.client().get_asset("asset-000001-0").await.unwrap();
+        assert_eq!(winner.exif_info.unwrap().latitude, Some(51.5));
+    }
+
+    #[tokio::test]
+    async fn test_consolidation_read_back_flags_ignored_fields() {
+        let mut groups = synthetic_groups(1, 2).unwrap();
+        groups[0].assets[1].exif_info.as_mut().unwrap().description = Some("Beach".to_string());
+        let run = TestRun::with_groups(&groups).await;
+        // Accepted with 200, but the coordinates never stick
+        run.server.ignore_updates_of(&["latitude", "longitude"]);
+
+        let report = run.execute(run.config()).await;
+        let consolidation = report.results[0].consolidation_result.as_ref().unwrap();
+        assert!(consolidation.retried);
+        assert!(consolidation.transferred(MetadataField::Description));
+        let gps = consolidation.field(MetadataField::Gps).unwrap();
+        assert!(!gps.applied);
+        assert_eq!(gps.discrepancy.as_deref(), Some("none"));
+    }
+
+    #[tokio::test]
+    async fn test_description_policy() {
+        let mut groups = synthetic_groups(1, 2).unwrap();
+        groups[0].assets[0].exif_info.as_mut().unwrap().description = Some("Beach".to_string());
+        groups[0].assets[1].exif_info.as_mut().unwrap().description = Some("Sunset at the beach".to_string());
+        let run = TestRun::with_groups(&groups).await;
+        assert_eq!(run.analyses[0].winner.asset_id.as_ref(), WINNER);
+
+        let plan = |description_policy| {
+            let executor = run.executor(ExecutionConfig {
+                description_policy,
+                ..run.config()
+            });
+            let analysis = &run.analyses[0];
+            async move { executor.plan_group(analysis).await }
+        };
+        let description = |plan: &GroupPlan| {
+            plan.operations.iter().find_map(|op| match op {
+                PlannedOperation::UpdateMetadata { description, .. } => description.clone(),
+                _ => None,
+            })
+        };
+
+        // The winner's description is kept by default
+        assert_eq!(description(&plan(DescriptionPolicy::Fill).await), None);
+        assert_eq!(
+            description(&plan(DescriptionPolicy::Append).await).as_deref(),
+            Some("Beach\n\nSunset at the beach")
+        );
+        assert_eq!(
+            description(&plan(DescriptionPolicy::Longest).await).as_deref(),
+            Some("Sunset at the beach")
+        );
+
+        // Different descriptions hold the group for review
+        let held = plan(DescriptionPolicy::Review).await;
+        assert!(!held.operations.iter().any(|op| matches!(op, PlannedOperation::Delete { .. })));
+        assert!(held.operations.iter().any(|op| matches!(
+            op,
+            PlannedOperation::Skip { reason, .. } if reason.contains("description policy: review")
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_description_review_holds_disagreeing_losers() {
+        let mut groups = synthetic_groups(2, 3).unwrap();
+        for (group, descriptions) in groups.iter_mut().zip([["Beach", "Lake"], ["Beach", " Beach "]]) {
+            group.assets[0].exif_info.as_mut().unwrap().description = None;
+            for (asset, description) in group.assets[1..].iter_mut().zip(descriptions) {
+                asset.exif_info.as_mut().unwrap().description = Some(description.to_string());
+            }
+        }
+        let run = TestRun::with_groups(&groups).await;
+        let executor = run.executor(ExecutionConfig {
+            description_policy: DescriptionPolicy::Review,
+            ..run.config()
+        });
+
+        // Losers disagree and the winner has nothing to go by
+        let held = executor.plan_group(&run.analyses[0]).await;
+        assert!(held.delete_skipped().is_some_and(|r| r.contains("the winner has none")));
+
+        // Losers agree, so their description fills the winner's
+        let filled = executor.plan_group(&run.analyses[1]).await;
+        assert!(filled.delete_skipped().is_none());
+        assert!(filled.operations.iter().any(|op| matches!(
+            op,
+            PlannedOperation::UpdateMetadata { description: Some(d), .. } if d == "Beach"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_winner_promoted_to_album_cover() {
+        let run = TestRun::start(1, 2).await;
+        run.server.add_album("album-1", "Holiday", &[LOSER, "asset-other"]);
+        run.server.add_album("album-2", "Favourites", &["asset-other", LOSER]);
+
+        let report = run
+            .execute(ExecutionConfig {
+                promote_album_covers: true,
+                ..run.config()
+            })
+            .await;
+
+        let promotions = &report.results[0].promotions;
+        assert_eq!(promotions.len(), 1, "only the album the loser is the cover of");
+        assert!(promotions[0].succeeded());
+        assert_eq!(promotions[0].target_id, "album-1");
+        assert_eq!(run.server.album_cover("album-1").as_deref(), Some(WINNER));
+        assert_eq!(run.server.album_cover("album-2").as_deref(), Some("asset-other"));
+    }
+
+    #[tokio::test]
+    async fn test_album_cover_left_alone_when_delete_fails() {
+        let run = TestRun::start(1, 2).await;
+        run.server.add_album("album-1", "Holiday", &[LOSER, "asset-other"]);
+        run.server.fail_deletes_of(&[LOSER]);
+
+        let report = run
+            .execute(ExecutionConfig {
+                promote_album_covers: true,
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!(report.deleted, 0);
+        assert!(report.results[0].promotions.is_empty());
+        assert_eq!(run.server.album_assets("album-1"), [LOSER, "asset-other"]);
+        assert_eq!(run.server.album_cover("album-1").as_deref(), Some(LOSER));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_recorded() {
+        let run = TestRun::start(2, 3).await;
+        let metrics = Metrics::new();
+
+        let report = run
+            .execute(ExecutionConfig {
+                metrics: Some(Arc::clone(&metrics)),
+                ..run.config()
+            })
+            .await;
+        assert_eq!(report.deleted, 4);
+
+        let text = metrics.render();
+        assert!(text.contains("immich_dupes_downloads_total 4\n"));
+        assert!(text.contains("immich_dupes_deletions_total 4\n"));
+        assert!(text.contains("immich_dupes_failures_total 0\n"));
+        assert!(!text.contains("immich_dupes_api_requests_total 0\n"));
+
+        // Bytes are the backed-up sizes, as in the report
+        let bytes: u64 = report.results.iter().map(|r| r.reclaimed_bytes + r.trashed_bytes).sum();
+        assert_eq!(bytes, 4 * 4096);
+        assert!(text.contains(&format!("immich_dupes_bytes_reclaimed_total {}\n", bytes)));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_retried_with_backoff() {
+        let run = TestRun::start(2, 2).await;
+        run.server.rate_limit_next(3);
+
+        let report = run
+            .execute(ExecutionConfig {
+                requests_per_sec: 1000,
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.deleted, 2);
+        let rate = report.rate_limit.unwrap();
+        assert_eq!(rate.throttled, 3);
+        assert_eq!(rate.configured_rate, 1000.0);
+        assert_eq!(rate.min_rate, 125.0);
+    }
+
+    #[tokio::test]
+    async fn test_partial_delete_rolled_back() {
+        let run = TestRun::start(2, 3).await;
+        let stuck = run.analyses[0].losers[0].asset_id.to_string();
+        run.server.fail_deletes_of(&[&stuck]);
+        let config = |rollback_partial| ExecutionConfig {
+            ledger_path: Some(run.dir.path().join("ledger.jsonl")),
+            rollback_partial,
+            ..run.config()
+        };
+
+        // Without rollback, the report says exactly which loser went
+        let report = run.executor(config(false)).execute_all(&run.analyses[..1]).await;
+        let result = &report.results[0];
+        let other = run.analyses[0].losers[1].asset_id.to_string();
+        assert!(matches!(result.delete_result, Some(OperationResult::Failed { .. })));
+        assert_eq!(result.deleted_ids, vec![other.clone()]);
+        assert_eq!(report.deleted, 1);
+
+        // With rollback, the trashed loser is restored and the group untouched
+        run.client().restore_from_trash(std::slice::from_ref(&other)).await.unwrap();
+        let report = run.execute(config(true)).await;
+        assert!(report.results[0].deleted_ids.is_empty());
+        assert_eq!(report.results[0].rolled_back_ids, vec![other.clone()]);
+        assert_eq!(report.results[1].deleted_ids.len(), 2);
+        assert_eq!(report.deleted, 2);
+        assert!(!run.server.deleted_assets().contains(&other));
+
+        let ledger = Ledger::new(run.dir.path().join("ledger.jsonl")).read_all().unwrap();
+        let restored: Vec<_> = ledger.iter().filter(|e| e.action == LedgerAction::Restored).collect();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].asset_id, other);
+    }
+
+    #[tokio::test]
+    async fn test_hung_downloads_time_out() {
+        let run = TestRun::start(2, 2).await;
+        let hung = run.analyses[0].losers[0].asset_id.to_string();
+        run.server.stall_downloads_of(&[&hung]);
+
+        // One permit, so a permit held by the hung download would stall the next group
+        let config = |operation_timeout, group_timeout| ExecutionConfig {
+            max_concurrent: 1,
+            operation_timeout,
+            group_timeout,
+            ..run.config()
+        };
+        let limit = Some(Duration::from_millis(200));
+
+        // The download fails, and the next group goes ahead
+        let report = run.execute(config(limit, None)).await;
+        assert!(matches!(
+            &report.results[0].download_results[0],
+            OperationResult::Failed { id, error } if *id == hung && error.starts_with("Timed out")
+        ));
+        assert!(report.results[0].deleted_ids.is_empty());
+        assert_eq!(report.results[1].deleted_ids.len(), 1);
+
+        // The group is given up on, with nothing deleted
+        let report = run.executor(config(None, limit)).execute_all(&run.analyses[..1]).await;
+        assert!(matches!(
+            &report.results[0].delete_result,
+            Some(OperationResult::Failed { error, .. }) if error.starts_with("Group timed out")
+        ));
+        assert_eq!(report.failed, 1);
+        assert!(!run.server.deleted_assets().contains(&hung));
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_group_keeps_partial_results() {
+        let run = TestRun::start(2, 3).await;
+        run.server.stall_downloads_of(&["asset-000000-2"]);
+
+        let report = run
+            .execute(ExecutionConfig {
+                max_concurrent: 1,
+                group_timeout: Some(Duration::from_millis(300)),
+                ..run.config()
+            })
+            .await;
+
+        // The hung group keeps the metadata and backup it made before giving up
+        let hung = &report.results[0];
+        assert!(matches!(
+            &hung.delete_result,
+            Some(OperationResult::Failed { error, .. }) if error.starts_with("Group timed out")
+        ));
+        assert!(hung.consolidation_result.is_some());
+        let [OperationResult::Success { id, path: Some(path) }] = hung.download_results.as_slice() else {
+            panic!("expected one finished backup, got {:?}", hung.download_results);
+        };
+        assert_eq!(id, "asset-000000-1");
+        assert!(path.exists());
+
+        // And the run goes on to the next group
+        assert_eq!(report.results[1].deleted_ids.len(), 2);
+        assert!(!run.server.deleted_assets().iter().any(|id| id.starts_with("asset-000000")));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_bytes_split_by_trash_and_force() {
+        for force_delete in [false, true] {
+            let run = TestRun::start(2, 3).await;
+            let report = run
+                .execute(ExecutionConfig {
+                    force_delete,
+                    ..run.config()
+                })
+                .await;
+
+            // Four losers, each backed up as the mock's 4 KiB original
+            let deleted_bytes = 4 * 4096;
+            assert_eq!(report.results[0].deleted_ids.len(), 2);
+            assert_eq!(report.deleted_bytes(), deleted_bytes);
+            if force_delete {
+                assert_eq!((report.reclaimed_bytes, report.trashed_bytes), (deleted_bytes, 0));
+            } else {
+                assert_eq!((report.reclaimed_bytes, report.trashed_bytes), (0, deleted_bytes));
+                assert_eq!(report.results[1].trashed_bytes, 2 * 4096);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_history_recorded() {
+        let run = TestRun::start(2, 2).await;
+        run.server.fail_deletes_of(&[run.analyses[1].losers[0].asset_id.as_str()]);
+        let runs = run.dir.path().join("runs");
+
+        let report = run
+            .execute(ExecutionConfig {
+                history_dir: Some(runs.clone()),
+                ..run.config()
+            })
+            .await;
+        assert!(report.history_error.is_none());
+
+        let timeline = history::open(runs).unwrap().timeline(report.run_id.as_deref().unwrap()).unwrap();
+        let kinds: Vec<&str> = timeline
+            .iter()
+            .map(|e| match &e.event {
+                ExecutionEvent::RunStarted { .. } => "run_started",
+                ExecutionEvent::GroupStarted { .. } => "group_started",
+                ExecutionEvent::Consolidated { .. } => "consolidated",
+                ExecutionEvent::Downloaded { bytes, .. } => {
+                    assert_eq!(*bytes, Some(4096));
+                    "downloaded"
+                }
+                ExecutionEvent::Deleted { .. } => "deleted",
+                ExecutionEvent::DeleteFailed { error, .. } => {
+                    assert!(error.contains("500"), "{}", error);
+                    "delete_failed"
+                }
+                ExecutionEvent::GroupFinished { .. } => "group_finished",
+                ExecutionEvent::RunFinished { .. } => "run_finished",
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "run_started",
+                "group_started",
+                "consolidated",
+                "downloaded",
+                "deleted",
+                "group_finished",
+                "group_started",
+                "consolidated",
+                "downloaded",
+                "delete_failed",
+                "group_finished",
+                "run_finished"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fixed_clock_stamps_plan_and_history() {
+        let run = TestRun::start(1, 2).await;
+        let runs = run.dir.path().join("runs");
+        let now = "2024-07-01T12:00:00Z".parse().unwrap();
+
+        let executor = run
+            .executor(ExecutionConfig {
+                history_dir: Some(runs.clone()),
+                ..run.config()
+            })
+            .with_clock(Arc::new(crate::clock::FixedClock::new(now)));
+        assert_eq!(executor.plan(&run.analyses).await.created_at, now);
+
+        let report = executor.execute_all(&run.analyses).await;
+        assert_eq!(report.run_id.as_deref(), Some("20240701-120000"));
+        let timeline = history::open(runs).unwrap().timeline("20240701-120000").unwrap();
+        assert!(!timeline.is_empty() && timeline.iter().all(|e| e.at == now));
+    }
+
+    #[test]
+    fn test_is_protected_matches_id_or_name() {
+        let protected = vec!["Frame Feed".to_string(), "album-9".to_string()];
+        assert!(is_protected(&protected, &["album-1", "frame feed"]));
+        assert!(is_protected(&protected, &["album-9", "Holiday"]));
+        assert!(!is_protected(&protected, &["album-2", "Holiday"]));
+        assert!(!is_protected(&[], &["album-1", "Frame Feed"]));
+    }
+
+    #[tokio::test]
+    async fn test_protected_album_transferred_before_delete() {
+        let run = TestRun::start(1, 2).await;
+        run.server.add_album("album-1", "Frame Feed", &[LOSER]);
+
+        let report = run
+            .execute(ExecutionConfig {
+                protected_albums: vec!["frame feed".to_string()],
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.deleted, 1);
+        assert!(run.server.album_assets("album-1").contains(&WINNER.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_loser_kept_when_protected_tag_transfer_fails() {
+        let config = |run: &TestRun| ExecutionConfig {
+            protected_tags: vec!["frame feed".to_string()],
+            ..run.config()
+        };
+
+        // Tagging the winner fails: the tagged loser stays
+        let run = TestRun::start(1, 2).await;
+        run.server.add_tag("tag-1", "Frame Feed", &[LOSER]);
+        run.server.fail_tagging_with(&["tag-1"]);
+        let report = run.execute(config(&run)).await;
+        assert_eq!((report.failed, report.deleted), (1, 0));
+        assert!(!run.server.deleted_assets().contains(LOSER));
+        assert_eq!(run.server.tagged_assets("Frame Feed"), [LOSER]);
+
+        // Once the winner can be tagged, the loser goes
+        let run = TestRun::start(1, 2).await;
+        run.server.add_tag("tag-1", "Frame Feed", &[LOSER]);
+        let report = run.execute(config(&run)).await;
+        assert_eq!((report.failed, report.deleted), (0, 1));
+        assert!(run.server.tagged_assets("Frame Feed").contains(&WINNER.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_unverified_transfer_blocks_delete() {
+        let run = TestRun::start(1, 2).await;
+        run.server.add_album_ignoring_adds("album-1", "Frame Feed", &[LOSER]);
+
+        let report = run
+            .execute(ExecutionConfig {
+                protected_albums: vec!["frame feed".to_string()],
+                ..run.config()
+            })
+            .await;
+
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.deleted, 0);
+        assert!(matches!(
+            &report.results[0].download_results[0],
+            OperationResult::Failed { error, .. } if error.ends_with("not in album 'Frame Feed' after transfer")
+        ));
     }
 }
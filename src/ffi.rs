@@ -0,0 +1,258 @@
+//! C ABI over the analysis core, for Python notebooks and other languages.
+//!
+//! Every function takes and returns JSON as NUL-terminated UTF-8 strings, in
+//! the same shapes as the API and the `analyze` report, so a caller needs
+//! nothing but a JSON parser. Analysis runs the exact code
+//! [`DuplicateAnalysis`] uses, so winners match the CLI's.
+//!
+//! Strings returned by this module are owned by the caller and must be
+//! released with [`immich_string_free`]. On failure a function returns null,
+//! and [`immich_last_error`] describes what went wrong.
+//!
+//! Build the shared library with:
+//!
+//! ```text
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use serde::Serialize;
+
+use crate::blocking::ImmichBlockingClient;
+use crate::models::{AssetResponse, DuplicateGroup};
+use crate::scoring::{detect_conflicts, rank_assets, DuplicateAnalysis, WinnerStrategy};
+
+thread_local! {
+    /// Error from the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Fetch every duplicate group from an Immich server.
+///
+/// Returns a JSON array of duplicate groups, as served by `GET /api/duplicates`.
+///
+/// # Safety
+///
+/// `base_url` and `api_key` must be valid pointers to NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn immich_fetch_duplicates(base_url: *const c_char, api_key: *const c_char) -> *mut c_char {
+    ffi_call(|| {
+        // SAFETY: upheld by the caller
+        let (base_url, api_key) = unsafe { (read_str(base_url)?, read_str(api_key)?) };
+        let client = ImmichBlockingClient::new(base_url, api_key).map_err(|e| e.to_string())?;
+        let groups = client.get_duplicates().map_err(|e| e.to_string())?;
+        to_json(&groups)
+    })
+}
+
+/// Analyze duplicate groups: score every asset, select winners, and detect
+/// conflicts.
+///
+/// Takes a JSON array of duplicate groups (e.g. from
+/// [`immich_fetch_duplicates`]) and returns a JSON array with one analysis
+/// per group, as in the `groups` of an `analyze` report.
+///
+/// # Safety
+///
+/// `groups_json` must be a valid pointer to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn immich_analyze_groups(groups_json: *const c_char) -> *mut c_char {
+    ffi_call(|| {
+        // SAFETY: upheld by the caller
+        let groups: Vec<DuplicateGroup> = from_json(unsafe { read_str(groups_json)? })?;
+        let analyses: Vec<DuplicateAnalysis> = groups.iter().map(DuplicateAnalysis::from_group).collect();
+        to_json(&analyses)
+    })
+}
+
+/// Detect metadata conflicts (GPS, capture time, camera, ...) among assets.
+///
+/// Takes a JSON array of assets and returns a JSON array of conflicts.
+///
+/// # Safety
+///
+/// `assets_json` must be a valid pointer to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn immich_detect_conflicts(assets_json: *const c_char) -> *mut c_char {
+    ffi_call(|| {
+        // SAFETY: upheld by the caller
+        let assets: Vec<AssetResponse> = from_json(unsafe { read_str(assets_json)? })?;
+        to_json(&detect_conflicts(&assets))
+    })
+}
+
+/// Order assets from winner to last loser.
+///
+/// Takes a JSON array of assets and a strategy (`"quality"` or
+/// `"metadata"`; null for the default, quality), and returns a JSON array of
+/// indices into the assets.
+///
+/// # Safety
+///
+/// `assets_json` must be a valid pointer to a NUL-terminated string, and
+/// `strategy` either null or one too.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn immich_rank_assets(assets_json: *const c_char, strategy: *const c_char) -> *mut c_char {
+    ffi_call(|| {
+        // SAFETY: upheld by the caller
+        let assets: Vec<AssetResponse> = from_json(unsafe { read_str(assets_json)? })?;
+        let strategy = if strategy.is_null() {
+            WinnerStrategy::default()
+        } else {
+            // SAFETY: upheld by the caller
+            match unsafe { read_str(strategy)? } {
+                "quality" => WinnerStrategy::Quality,
+                "metadata" => WinnerStrategy::Metadata,
+                other => return Err(format!("unknown strategy: {}", other)),
+            }
+        };
+        to_json(&rank_assets(&assets, strategy))
+    })
+}
+
+/// Describe the last error on this thread, or return null if there was none.
+///
+/// The string must be released with [`immich_string_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn immich_last_error() -> *mut c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_deref() {
+        Some(message) => into_c_string(message.to_string()),
+        None => ptr::null_mut(),
+    })
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that hasn't
+/// already been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn immich_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: the string was made by `CString::into_raw`, as the caller guarantees
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Run an FFI call, recording any error or panic (which must not unwind
+/// into the caller) and returning null for it.
+fn ffi_call(f: impl FnOnce() -> std::result::Result<String, String>) -> *mut c_char {
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(format!("panic: {}", message))
+    });
+    match result {
+        Ok(json) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            into_c_string(json)
+        }
+        Err(message) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Borrow a C string argument as UTF-8.
+///
+/// # Safety
+///
+/// `s` must be null or a valid pointer to a NUL-terminated string.
+unsafe fn read_str<'a>(s: *const c_char) -> std::result::Result<&'a str, String> {
+    if s.is_null() {
+        return Err("null string argument".to_string());
+    }
+    // SAFETY: non-null, and NUL-terminated as the caller guarantees
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| format!("argument is not UTF-8: {}", e))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> std::result::Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> std::result::Result<String, String> {
+    serde_json::to_string(value).map_err(|e| e.to_string())
+}
+
+/// Hand a string to the caller. JSON never contains a NUL byte, and any other
+/// message has them dropped.
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s.replace('\0', ""))
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    /// Take ownership of a returned string.
+    fn take(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let owned = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { immich_string_free(s) };
+        Some(owned)
+    }
+
+    #[test]
+    fn test_analysis_matches_library() {
+        let groups = synthetic_groups(3, 3).unwrap();
+        let input = CString::new(serde_json::to_string(&groups).unwrap()).unwrap();
+
+        let output = take(unsafe { immich_analyze_groups(input.as_ptr()) }).unwrap();
+        let analyses: Vec<DuplicateAnalysis> = serde_json::from_str(&output).unwrap();
+        assert_eq!(analyses.len(), 3);
+        for (analysis, group) in analyses.iter().zip(&groups) {
+            let expected = DuplicateAnalysis::from_group(group);
+            assert_eq!(analysis.winner.asset_id, expected.winner.asset_id);
+        }
+
+        let assets = CString::new(serde_json::to_string(&groups[0].assets).unwrap()).unwrap();
+        let order = take(unsafe { immich_rank_assets(assets.as_ptr(), ptr::null()) }).unwrap();
+        let order: Vec<usize> = serde_json::from_str(&order).unwrap();
+        assert_eq!(order, rank_assets(&groups[0].assets, WinnerStrategy::Quality));
+        assert!(take(unsafe { immich_detect_conflicts(assets.as_ptr()) }).is_some());
+    }
+
+    #[test]
+    fn test_errors_reported_through_last_error() {
+        let bad = CString::new("not json").unwrap();
+        assert!(take(unsafe { immich_analyze_groups(bad.as_ptr()) }).is_none());
+        assert!(take(immich_last_error()).unwrap().starts_with("invalid JSON"));
+
+        let assets = CString::new("[]").unwrap();
+        let strategy = CString::new("newest").unwrap();
+        assert!(take(unsafe { immich_rank_assets(assets.as_ptr(), strategy.as_ptr()) }).is_none());
+
+        // A successful call clears the error
+        assert!(take(unsafe { immich_detect_conflicts(assets.as_ptr()) }).is_some());
+        assert!(take(immich_last_error()).is_none());
+        assert!(take(unsafe { immich_analyze_groups(ptr::null()) }).is_none());
+    }
+
+    #[test]
+    fn test_fetch_duplicates() {
+        let server_runtime = tokio::runtime::Runtime::new().unwrap();
+        let server = server_runtime.block_on(MockServer::start(&synthetic_groups(2, 2).unwrap())).unwrap();
+        let url = CString::new(server.url()).unwrap();
+        let key = CString::new("test-key").unwrap();
+
+        let output = take(unsafe { immich_fetch_duplicates(url.as_ptr(), key.as_ptr()) }).unwrap();
+        let groups: Vec<DuplicateGroup> = serde_json::from_str(&output).unwrap();
+        assert_eq!(groups.len(), 2);
+    }
+}
@@ -0,0 +1,142 @@
+//! Date range and asset type filters for duplicate groups.
+//!
+//! A first pass over a large library is often limited to a slice of it, say
+//! the photos taken between 2015 and 2019. [`GroupFilter`] keeps the groups
+//! whose assets all fall in that slice, before they are analyzed. A group
+//! straddling the range is left out, so a loser outside it is never deleted
+//! by a run that was meant to leave that part of the library alone.
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{AssetResponse, AssetType, DuplicateGroup};
+use crate::review::parse_capture_time;
+
+/// Which duplicate groups to keep. The default keeps every group.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupFilter {
+    /// Keep assets dated at or after this time
+    pub after: Option<DateTime<Utc>>,
+
+    /// Keep assets dated before this time
+    pub before: Option<DateTime<Utc>>,
+
+    /// Keep only assets of this type
+    pub asset_type: Option<AssetType>,
+}
+
+impl GroupFilter {
+    /// Whether the filter keeps every group.
+    pub fn is_empty(&self) -> bool {
+        self.after.is_none() && self.before.is_none() && self.asset_type.is_none()
+    }
+
+    /// Whether an asset is in the filtered slice.
+    ///
+    /// With a date bound set, assets without a readable date are left out.
+    pub fn matches_asset(&self, asset: &AssetResponse) -> bool {
+        if self.asset_type.as_ref().is_some_and(|t| *t != asset.asset_type) {
+            return false;
+        }
+        if self.after.is_none() && self.before.is_none() {
+            return true;
+        }
+        asset_date(asset).is_some_and(|date| {
+            self.after.is_none_or(|after| date >= after) && self.before.is_none_or(|before| date < before)
+        })
+    }
+
+    /// Whether every asset of a group is in the filtered slice.
+    pub fn matches(&self, group: &DuplicateGroup) -> bool {
+        group.assets.iter().all(|a| self.matches_asset(a))
+    }
+
+    /// Keep only the groups whose assets are all in the filtered slice.
+    pub fn apply(&self, groups: Vec<DuplicateGroup>) -> Vec<DuplicateGroup> {
+        if self.is_empty() {
+            return groups;
+        }
+        groups.into_iter().filter(|g| self.matches(g)).collect()
+    }
+}
+
+/// When an asset was taken: its EXIF capture time, or the file's creation
+/// time when EXIF has none.
+pub fn asset_date(asset: &AssetResponse) -> Option<DateTime<Utc>> {
+    asset
+        .exif_info
+        .as_ref()
+        .and_then(|e| e.date_time_original.as_deref())
+        .and_then(parse_capture_time)
+        .or_else(|| parse_capture_time(&asset.file_created_at))
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::testing::AssetBuilder;
+    use chrono::TimeZone;
+
+    fn group(id: &str, assets: Vec<AssetResponse>) -> DuplicateGroup {
+        DuplicateGroup {
+            duplicate_id: id.to_string(),
+            assets,
+            extra: Default::default(),
+        }
+    }
+
+    fn taken(id: &str, year: i32) -> AssetBuilder {
+        AssetBuilder::new(id).with_capture_time(Utc.with_ymd_and_hms(year, 6, 15, 10, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_filter_by_date_and_type() {
+        let groups = vec![
+            group("2014", vec![taken("a", 2014).build(), taken("b", 2014).build()]),
+            group("2017", vec![taken("c", 2017).build(), taken("d", 2017).build()]),
+            group("2017-video", vec![taken("e", 2017).video(3.0).build(), taken("f", 2017).video(3.0).build()]),
+            group("2020", vec![taken("g", 2020).build(), taken("h", 2020).build()]),
+        ];
+        let ids = |filter: &GroupFilter| -> Vec<String> {
+            filter.apply(groups.clone()).into_iter().map(|g| g.duplicate_id).collect()
+        };
+
+        assert_eq!(ids(&GroupFilter::default()).len(), 4);
+
+        let years = GroupFilter {
+            after: Some(Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap()),
+            before: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+            asset_type: None,
+        };
+        assert_eq!(ids(&years), ["2017", "2017-video"]);
+
+        let photos = GroupFilter {
+            asset_type: Some(AssetType::Image),
+            ..years
+        };
+        assert_eq!(ids(&photos), ["2017"]);
+    }
+
+    #[test]
+    fn test_group_straddling_range_is_left_out() {
+        let groups = vec![
+            group("mixed", vec![taken("a", 2017).build(), taken("b", 2021).build()]),
+            group("inside", vec![taken("c", 2016).build(), taken("d", 2018).build()]),
+        ];
+        let years = GroupFilter {
+            after: Some(Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap()),
+            before: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+            asset_type: None,
+        };
+
+        assert!(!years.matches(&groups[0]));
+        let kept: Vec<String> = years.apply(groups).into_iter().map(|g| g.duplicate_id).collect();
+        assert_eq!(kept, ["inside"]);
+    }
+
+    #[test]
+    fn test_asset_date_falls_back_to_file_creation() {
+        let mut asset = taken("a", 2017).build();
+        asset.exif_info = None;
+        assert_eq!(asset_date(&asset), Some(Utc.with_ymd_and_hms(2017, 6, 15, 10, 0, 0).unwrap()));
+    }
+}
@@ -0,0 +1,532 @@
+//! Per-run event history of execution, for forensic review.
+//!
+//! The executor appends an [`ExecutionEvent`] for every step it takes to a
+//! [`HistoryStore`]: one JSON Lines file per run in a history directory
+//! ([`RunHistory`]), or with the `sqlite` feature a SQLite database in that
+//! directory. Unlike the execution report, which summarizes the outcome,
+//! the history keeps the order and time of each step, so
+//! [`HistoryStore::timeline`] can show what happened in any past run,
+//! including one that was interrupted.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+
+/// Something the executor did.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ExecutionEvent {
+    /// A run began
+    RunStarted {
+        /// Immich server the run is against
+        server_url: String,
+        /// Groups to process
+        groups: usize,
+        /// Whether losers are permanently deleted rather than trashed
+        force_delete: bool,
+    },
+    /// Processing of a group began
+    GroupStarted {
+        /// Duplicate group ID
+        group_id: String,
+        /// Asset kept
+        winner_id: String,
+        /// Losers in the group
+        losers: usize,
+    },
+    /// Metadata was copied from a loser to the winner
+    Consolidated {
+        /// Duplicate group ID
+        group_id: String,
+        /// Loser the metadata came from
+        source_id: Option<String>,
+        /// Fields copied, e.g. `gps`
+        fields: Vec<String>,
+    },
+    /// Winners of several groups in a batch were given the same metadata
+    /// in one bulk request, ahead of their groups
+    MetadataBulkUpdated {
+        /// Duplicate groups whose winners were updated
+        group_ids: Vec<String>,
+        /// Winners updated
+        winner_ids: Vec<String>,
+    },
+    /// A loser was replaced by the winner in an album
+    ReplacedInAlbum {
+        /// Duplicate group ID
+        group_id: String,
+        /// Album updated
+        album_id: String,
+        /// Loser removed from the album
+        loser_id: String,
+        /// Why the replacement failed, if it did
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// The winner was added to a shared link sharing a loser
+    RepointedSharedLink {
+        /// Duplicate group ID
+        group_id: String,
+        /// Shared link updated
+        link_id: String,
+        /// Loser the link shares
+        loser_id: String,
+        /// Why the re-point failed, if it did
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A loser was backed up
+    Downloaded {
+        /// Duplicate group ID
+        group_id: String,
+        /// Loser backed up
+        asset_id: String,
+        /// Size of the backup, if it could be read
+        bytes: Option<u64>,
+        /// Where the backup was written
+        path: Option<PathBuf>,
+    },
+    /// A loser could not be backed up
+    DownloadFailed {
+        /// Duplicate group ID
+        group_id: String,
+        /// Loser that failed
+        asset_id: String,
+        /// Why
+        error: String,
+    },
+    /// A loser, or the group's delete step, was skipped
+    Skipped {
+        /// Duplicate group ID
+        group_id: String,
+        /// Loser skipped, or the group ID if the delete step was
+        asset_id: String,
+        /// Why
+        reason: String,
+    },
+    /// Losers were deleted (or trashed)
+    Deleted {
+        /// Duplicate group ID
+        group_id: String,
+        /// Losers removed
+        asset_ids: Vec<String>,
+    },
+    /// The delete step failed
+    DeleteFailed {
+        /// Duplicate group ID
+        group_id: String,
+        /// Why, with the HTTP status if the server answered
+        error: String,
+        /// Losers that went anyway
+        deleted_ids: Vec<String>,
+        /// Losers restored from the trash afterwards
+        rolled_back_ids: Vec<String>,
+    },
+    /// Processing of a group finished
+    GroupFinished {
+        /// Duplicate group ID
+        group_id: String,
+    },
+    /// The run finished
+    RunFinished {
+        /// Assets deleted
+        deleted: usize,
+        /// Operations that failed
+        failed: usize,
+        /// Operations skipped
+        skipped: usize,
+    },
+}
+
+impl ExecutionEvent {
+    /// The group the event belongs to, if any.
+    pub fn group_id(&self) -> Option<&str> {
+        match self {
+            Self::RunStarted { .. } | Self::MetadataBulkUpdated { .. } | Self::RunFinished { .. } => None,
+            Self::GroupStarted { group_id, .. }
+            | Self::Consolidated { group_id, .. }
+            | Self::ReplacedInAlbum { group_id, .. }
+            | Self::RepointedSharedLink { group_id, .. }
+            | Self::Downloaded { group_id, .. }
+            | Self::DownloadFailed { group_id, .. }
+            | Self::Skipped { group_id, .. }
+            | Self::Deleted { group_id, .. }
+            | Self::DeleteFailed { group_id, .. }
+            | Self::GroupFinished { group_id } => Some(group_id),
+        }
+    }
+
+    /// One-line human-readable description.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::RunStarted {
+                server_url,
+                groups,
+                force_delete,
+            } => format!(
+                "run started against {} ({} groups, {})",
+                server_url,
+                groups,
+                if *force_delete { "permanent delete" } else { "trash" }
+            ),
+            Self::GroupStarted {
+                group_id,
+                winner_id,
+                losers,
+            } => format!("group {} started (keeping {}, {} losers)", group_id, winner_id, losers),
+            Self::Consolidated {
+                group_id,
+                source_id,
+                fields,
+            } => format!(
+                "group {} consolidated {} from {}",
+                group_id,
+                fields.join(", "),
+                source_id.as_deref().unwrap_or("unknown")
+            ),
+            Self::MetadataBulkUpdated { group_ids, winner_ids } => format!(
+                "metadata sent to {} winners in one request (groups {})",
+                winner_ids.len(),
+                group_ids.join(", ")
+            ),
+            Self::ReplacedInAlbum {
+                group_id,
+                album_id,
+                loser_id,
+                error,
+            } => match error {
+                Some(error) => format!(
+                    "group {} failed to replace {} in album {}: {}",
+                    group_id, loser_id, album_id, error
+                ),
+                None => format!("group {} replaced {} in album {}", group_id, loser_id, album_id),
+            },
+            Self::RepointedSharedLink {
+                group_id,
+                link_id,
+                loser_id,
+                error,
+            } => match error {
+                Some(error) => format!(
+                    "group {} failed to re-point shared link {} from {}: {}",
+                    group_id, link_id, loser_id, error
+                ),
+                None => format!("group {} re-pointed shared link {} from {}", group_id, link_id, loser_id),
+            },
+            Self::Downloaded {
+                group_id,
+                asset_id,
+                bytes,
+                ..
+            } => match bytes {
+                Some(bytes) => format!("group {} downloaded {} ({} bytes)", group_id, asset_id, bytes),
+                None => format!("group {} downloaded {}", group_id, asset_id),
+            },
+            Self::DownloadFailed {
+                group_id,
+                asset_id,
+                error,
+            } => format!("group {} failed to download {}: {}", group_id, asset_id, error),
+            Self::Skipped {
+                group_id,
+                asset_id,
+                reason,
+            } if asset_id == group_id => format!("group {} not deleted: {}", group_id, reason),
+            Self::Skipped {
+                group_id,
+                asset_id,
+                reason,
+            } => format!("group {} skipped {}: {}", group_id, asset_id, reason),
+            Self::Deleted { group_id, asset_ids } => {
+                format!("group {} deleted {}", group_id, asset_ids.join(", "))
+            }
+            Self::DeleteFailed {
+                group_id,
+                error,
+                deleted_ids,
+                rolled_back_ids,
+            } => {
+                let mut line = format!("group {} delete failed: {}", group_id, error);
+                if !deleted_ids.is_empty() {
+                    line.push_str(&format!(" (deleted anyway: {})", deleted_ids.join(", ")));
+                }
+                if !rolled_back_ids.is_empty() {
+                    line.push_str(&format!(" (restored: {})", rolled_back_ids.join(", ")));
+                }
+                line
+            }
+            Self::GroupFinished { group_id } => format!("group {} finished", group_id),
+            Self::RunFinished {
+                deleted,
+                failed,
+                skipped,
+            } => format!(
+                "run finished: {} deleted, {} failed, {} skipped",
+                deleted, failed, skipped
+            ),
+        }
+    }
+}
+
+/// An event with the time it was recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// When the event was recorded
+    pub at: DateTime<Utc>,
+
+    /// What happened
+    #[serde(flatten)]
+    pub event: ExecutionEvent,
+}
+
+/// Summary of one recorded run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    /// Run ID, as passed to [`HistoryStore::timeline`]
+    pub run_id: String,
+
+    /// When the first event was recorded
+    pub started_at: Option<DateTime<Utc>>,
+
+    /// Groups started
+    pub groups: usize,
+
+    /// Whether the run recorded its end (false if it was interrupted)
+    pub finished: bool,
+}
+
+/// Where runs and their events are kept.
+///
+/// [`RunHistory`] keeps them as files in a directory; with the `sqlite`
+/// feature [`open`] keeps them in a SQLite database instead
+/// (`store::SqliteHistory`).
+pub trait HistoryStore: std::fmt::Debug + Send + Sync {
+    /// Begin a run, named after `started_at`, and return its ID.
+    fn create_run(&self, started_at: DateTime<Utc>) -> Result<String>;
+
+    /// Append an event to run `run_id`.
+    fn append(&self, run_id: &str, event: &RecordedEvent) -> Result<()>;
+
+    /// Every recorded run, oldest first.
+    fn runs(&self) -> Result<Vec<RunSummary>>;
+
+    /// Every event of a run, in the order recorded.
+    fn timeline(&self, run_id: &str) -> Result<Vec<RecordedEvent>>;
+}
+
+/// The history kept in `dir`: a SQLite database (`history.db`) with the
+/// `sqlite` feature, or else one JSON Lines file per run.
+///
+/// # Errors
+///
+/// Returns an error if the directory or database can't be created.
+pub fn open(dir: impl Into<PathBuf>) -> Result<Arc<dyn HistoryStore>> {
+    let dir = dir.into();
+    #[cfg(feature = "sqlite")]
+    {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Arc::new(crate::store::SqliteHistory::open(dir.join("history.db"))?))
+    }
+    #[cfg(not(feature = "sqlite"))]
+    {
+        Ok(Arc::new(RunHistory::new(dir)))
+    }
+}
+
+/// Run ID for a run started at `started_at`: its time, then `-2`, `-3` and
+/// so on for later runs started in the same second.
+pub(crate) fn run_id_for(started_at: DateTime<Utc>, attempt: u32) -> String {
+    let stamp = started_at.format("%Y%m%d-%H%M%S");
+    if attempt == 1 { stamp.to_string() } else { format!("{}-{}", stamp, attempt) }
+}
+
+/// Summary of run `run_id` from its events.
+pub(crate) fn summarize(run_id: String, events: &[RecordedEvent]) -> RunSummary {
+    RunSummary {
+        started_at: events.first().map(|e| e.at),
+        groups: events
+            .iter()
+            .filter(|e| matches!(e.event, ExecutionEvent::GroupStarted { .. }))
+            .count(),
+        finished: events.iter().any(|e| matches!(e.event, ExecutionEvent::RunFinished { .. })),
+        run_id,
+    }
+}
+
+/// Directory holding one event file per run.
+#[derive(Debug, Clone)]
+pub struct RunHistory {
+    dir: PathBuf,
+}
+
+impl RunHistory {
+    /// Use the history in `dir`; it is created when the first run starts.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory holding the history.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Start recording a new run, named after the current time.
+    pub fn start_run(&self) -> Result<RunRecorder> {
+        RunRecorder::start(Arc::new(self.clone()), Arc::new(SystemClock))
+    }
+
+    fn run_path(&self, run_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", run_id))
+    }
+}
+
+impl HistoryStore for RunHistory {
+    fn create_run(&self, started_at: DateTime<Utc>) -> Result<String> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut attempt = 1;
+        loop {
+            let run_id = run_id_for(started_at, attempt);
+            match OpenOptions::new().write(true).create_new(true).open(self.run_path(&run_id)) {
+                Ok(_) => return Ok(run_id),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => attempt += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn append(&self, run_id: &str, event: &RecordedEvent) -> Result<()> {
+        let mut line = serde_json::to_vec(event).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+        // One write per event, so events recorded concurrently don't interleave
+        let mut file = OpenOptions::new().append(true).open(self.run_path(run_id))?;
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    fn runs(&self) -> Result<Vec<RunSummary>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut run_ids: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_suffix(".jsonl").map(str::to_string)
+            })
+            .collect();
+        run_ids.sort();
+
+        run_ids
+            .into_iter()
+            .map(|run_id| {
+                let events = self.timeline(&run_id)?;
+                Ok(summarize(run_id, &events))
+            })
+            .collect()
+    }
+
+    fn timeline(&self, run_id: &str) -> Result<Vec<RecordedEvent>> {
+        let path = self.run_path(run_id);
+        let file = File::open(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ImmichError::NotFound {
+                message: format!("no run {} in {}", run_id, self.dir.display()),
+            },
+            _ => e.into(),
+        })?;
+
+        let mut events = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line).map_err(|e| {
+                ImmichError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} line {}: {}", path.display(), number + 1, e),
+                ))
+            })?;
+            events.push(event);
+        }
+        Ok(events)
+    }
+}
+
+/// Appends the events of one run.
+#[derive(Debug, Clone)]
+pub struct RunRecorder {
+    run_id: String,
+    store: Arc<dyn HistoryStore>,
+    clock: Arc<dyn Clock>,
+}
+
+impl RunRecorder {
+    /// Begin a new run in `store`, named and timestamped with the time from
+    /// `clock`.
+    pub fn start(store: Arc<dyn HistoryStore>, clock: Arc<dyn Clock>) -> Result<Self> {
+        let run_id = store.create_run(clock.now())?;
+        Ok(Self { run_id, store, clock })
+    }
+
+    /// ID of the run being recorded.
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Append an event, timestamped now.
+    pub fn record(&self, event: ExecutionEvent) -> Result<()> {
+        let recorded = RecordedEvent { at: self.clock.now(), event };
+        self.store.append(&self.run_id, &recorded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_recorded_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = RunHistory::new(dir.path().join("runs"));
+        assert!(history.runs().unwrap().is_empty());
+
+        let first = history.start_run().unwrap();
+        first
+            .record(ExecutionEvent::GroupStarted {
+                group_id: "dup-1".to_string(),
+                winner_id: "w".to_string(),
+                losers: 1,
+            })
+            .unwrap();
+        first
+            .record(ExecutionEvent::RunFinished {
+                deleted: 1,
+                failed: 0,
+                skipped: 0,
+            })
+            .unwrap();
+        // A second run in the same second gets its own file
+        let second = history.start_run().unwrap();
+        assert_ne!(first.run_id(), second.run_id());
+
+        let runs = history.runs().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].run_id, first.run_id());
+        assert_eq!((runs[0].groups, runs[0].finished), (1, true));
+        assert!(!runs[1].finished);
+
+        let timeline = history.timeline(first.run_id()).unwrap();
+        assert_eq!(timeline[0].event.group_id(), Some("dup-1"));
+        assert!(matches!(history.timeline("missing"), Err(ImmichError::NotFound { .. })));
+    }
+}
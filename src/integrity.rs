@@ -0,0 +1,218 @@
+//! Detection of zero-byte and corrupt original files.
+//!
+//! Deleting a group's siblings is only safe if the remaining originals and the
+//! downloaded backups are intact. This module checks files with a size check,
+//! a magic-byte sniff against the file extension, and an optional full decode.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// Number of leading bytes needed to sniff a file format.
+pub const SNIFF_LEN: usize = 16;
+
+/// Why a file is considered corrupt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IntegrityIssue {
+    /// The file is empty
+    ZeroBytes,
+
+    /// The file's leading bytes don't match its extension
+    FormatMismatch {
+        /// Format implied by the extension
+        expected: String,
+        /// Format detected from magic bytes, if any
+        detected: Option<String>,
+    },
+
+    /// The image could not be decoded
+    DecodeFailed {
+        /// Decoder error message
+        error: String,
+    },
+}
+
+impl std::fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroBytes => write!(f, "file is zero bytes"),
+            Self::FormatMismatch { expected, detected } => write!(
+                f,
+                "expected {} but found {}",
+                expected,
+                detected.as_deref().unwrap_or("unrecognized data")
+            ),
+            Self::DecodeFailed { error } => write!(f, "decode failed: {}", error),
+        }
+    }
+}
+
+/// An original flagged by an integrity audit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorruptOriginal {
+    /// Asset ID
+    pub asset_id: String,
+
+    /// Original filename
+    pub filename: String,
+
+    /// What is wrong with the file
+    pub issue: IntegrityIssue,
+}
+
+/// Detect a file format from its leading bytes.
+///
+/// Returns a short format name, or `None` if the bytes aren't recognized.
+pub fn sniff_format(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpeg")
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G']) {
+        Some("png")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("gif")
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        Some("webp")
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"AVI ") {
+        Some("avi")
+    } else if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+        Some("tiff")
+    } else if bytes.starts_with(b"FUJIFILMCCD-RAW") {
+        Some("raf")
+    } else if bytes.starts_with(b"IIRO") || bytes.starts_with(b"IIRS") {
+        Some("orf")
+    } else if bytes.starts_with(b"IIU\0") {
+        Some("rw2")
+    } else if bytes.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some("matroska")
+    } else if bytes.get(4..8) == Some(b"ftyp") {
+        Some("isobmff")
+    } else {
+        None
+    }
+}
+
+/// Formats accepted for a file extension, or `None` if the extension is unknown.
+fn expected_formats(extension: &str) -> Option<&'static [&'static str]> {
+    let formats: &'static [&'static str] = match extension.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => &["jpeg"],
+        "png" => &["png"],
+        "gif" => &["gif"],
+        "webp" => &["webp"],
+        "tif" | "tiff" | "dng" | "nef" | "cr2" | "arw" | "pef" | "srw" => &["tiff"],
+        "raf" => &["raf"],
+        "orf" => &["orf", "tiff"],
+        "rw2" => &["rw2", "tiff"],
+        "heic" | "heif" | "avif" | "cr3" | "mp4" | "mov" | "m4v" | "3gp" => &["isobmff"],
+        "mkv" | "webm" => &["matroska"],
+        "avi" => &["avi"],
+        _ => return None,
+    };
+    Some(formats)
+}
+
+/// Check a file's leading bytes against its extension.
+///
+/// Files with unknown extensions are not flagged.
+pub fn check_bytes(bytes: &[u8], extension: Option<&str>) -> Option<IntegrityIssue> {
+    if bytes.is_empty() {
+        return Some(IntegrityIssue::ZeroBytes);
+    }
+
+    let expected = expected_formats(extension?)?;
+    let detected = sniff_format(bytes);
+
+    if detected.is_some_and(|d| expected.contains(&d)) {
+        None
+    } else {
+        Some(IntegrityIssue::FormatMismatch {
+            expected: expected[0].to_string(),
+            detected: detected.map(String::from),
+        })
+    }
+}
+
+/// Check a file on disk for corruption.
+///
+/// Runs the size check and magic-byte sniff; when `decode` is true, still
+/// images in formats the `image` crate supports are also fully decoded.
+///
+/// # Returns
+///
+/// `None` if the file looks intact, otherwise the first issue found.
+pub async fn check_file(path: &Path, decode: bool) -> Result<Option<IntegrityIssue>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    (&mut file).take(SNIFF_LEN as u64).read_to_end(&mut header).await?;
+
+    let extension = path.extension().and_then(|e| e.to_str());
+    if let Some(issue) = check_bytes(&header, extension) {
+        return Ok(Some(issue));
+    }
+
+    let decodable = matches!(sniff_format(&header), Some("jpeg" | "png" | "gif" | "webp"));
+    if decode && decodable {
+        let path = path.to_path_buf();
+        let decoded = tokio::task::spawn_blocking(move || {
+            image::ImageReader::open(&path)?
+                .with_guessed_format()?
+                .decode()
+                .map(|_| ())
+                .map_err(std::io::Error::other)
+        })
+        .await
+        .map_err(std::io::Error::other)?;
+
+        if let Err(e) = decoded {
+            return Ok(Some(IntegrityIssue::DecodeFailed {
+                error: e.to_string(),
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_bytes_zero_and_mismatch() {
+        assert_eq!(check_bytes(&[], Some("jpg")), Some(IntegrityIssue::ZeroBytes));
+        assert_eq!(
+            check_bytes(b"<html>error</html>", Some("JPG")),
+            Some(IntegrityIssue::FormatMismatch {
+                expected: "jpeg".into(),
+                detected: None,
+            })
+        );
+        assert_eq!(check_bytes(&[0xFF, 0xD8, 0xFF, 0xE1], Some("jpeg")), None);
+        assert_eq!(check_bytes(b"\0\0\0\x18ftypheic", Some("heic")), None);
+    }
+
+    #[test]
+    fn test_unknown_extension_not_flagged() {
+        assert_eq!(check_bytes(b"anything", Some("xyz")), None);
+        assert_eq!(check_bytes(b"anything", None), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_file_decode_truncated_png() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.png");
+        tokio::fs::write(&path, [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A])
+            .await
+            .unwrap();
+
+        assert_eq!(check_file(&path, false).await.unwrap(), None);
+        assert!(matches!(
+            check_file(&path, true).await.unwrap(),
+            Some(IntegrityIssue::DecodeFailed { .. })
+        ));
+    }
+}
@@ -0,0 +1,250 @@
+//! Shared, immutable strings for the analysis data model.
+//!
+//! An analysis of a large library holds several strings per asset, and many
+//! of them repeat: every photo from one phone has the same camera string,
+//! thousands share a city, conflicts list the same timezones again and
+//! again. [`SharedStr`] is a reference-counted string that is cheap to clone,
+//! and an [`Interner`] hands out one allocation per distinct value, so a
+//! batch of analyses stores each repeated string once.
+//!
+//! `SharedStr` dereferences to `str`, compares equal to `str` and `String`,
+//! and serializes as a plain string, so it can stand in for `String` in
+//! serialized reports without changing their format.
+
+use std::borrow::{Borrow, Cow};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An immutable, reference-counted string.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedStr(Arc<str>);
+
+impl SharedStr {
+    /// The string contents.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The contents as an owned `String`.
+    pub fn into_string(self) -> String {
+        self.0.to_string()
+    }
+
+    /// Returns true if both values point at the same allocation.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Address of the shared allocation, for counting distinct allocations.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.0.as_ptr()
+    }
+}
+
+impl Default for SharedStr {
+    fn default() -> Self {
+        Self::from("")
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for SharedStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for SharedStr {
+    fn from(value: &str) -> Self {
+        Self(Arc::from(value))
+    }
+}
+
+impl From<String> for SharedStr {
+    fn from(value: String) -> Self {
+        Self(Arc::from(value))
+    }
+}
+
+impl From<&String> for SharedStr {
+    fn from(value: &String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl From<Box<str>> for SharedStr {
+    fn from(value: Box<str>) -> Self {
+        Self(Arc::from(value))
+    }
+}
+
+impl From<Cow<'_, str>> for SharedStr {
+    fn from(value: Cow<'_, str>) -> Self {
+        Self::from(value.as_ref())
+    }
+}
+
+impl From<Arc<str>> for SharedStr {
+    fn from(value: Arc<str>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<SharedStr> for String {
+    fn from(value: SharedStr) -> Self {
+        value.into_string()
+    }
+}
+
+impl From<SharedStr> for Arc<str> {
+    fn from(value: SharedStr) -> Self {
+        value.0
+    }
+}
+
+impl PartialEq<str> for SharedStr {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for SharedStr {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for SharedStr {
+    fn eq(&self, other: &String) -> bool {
+        &*self.0 == other.as_str()
+    }
+}
+
+impl PartialEq<SharedStr> for str {
+    fn eq(&self, other: &SharedStr) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<SharedStr> for &str {
+    fn eq(&self, other: &SharedStr) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<SharedStr> for String {
+    fn eq(&self, other: &SharedStr) -> bool {
+        self.as_str() == &*other.0
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Serialize for SharedStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Hands out one [`SharedStr`] per distinct value.
+///
+/// Interned strings live as long as anything refers to them; dropping the
+/// interner only drops its own references.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<SharedStr>,
+}
+
+impl Interner {
+    /// Create an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shared copy of `value`, allocating it on first use.
+    pub fn intern(&mut self, value: &str) -> SharedStr {
+        if let Some(existing) = self.strings.get(value) {
+            return existing.clone();
+        }
+        let shared = SharedStr::from(value);
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    /// Number of distinct strings interned.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns true if nothing has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interner_shares_equal_strings() {
+        let mut interner = Interner::new();
+        let a = interner.intern("Apple iPhone 15 Pro");
+        let b = interner.intern(&String::from("Apple iPhone 15 Pro"));
+        let c = interner.intern("Canon EOS R5");
+
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&c));
+        assert_eq!(interner.len(), 2);
+        assert_eq!(a, "Apple iPhone 15 Pro");
+        assert_eq!(serde_json::to_string(&a).unwrap(), "\"Apple iPhone 15 Pro\"");
+        let back: SharedStr = serde_json::from_str("\"Canon EOS R5\"").unwrap();
+        assert_eq!(back, c);
+    }
+
+    #[test]
+    fn test_conversions_from_and_to_owned_strings() {
+        let owned = String::from("Paris");
+        let shared = SharedStr::from(owned.clone());
+        assert_eq!(shared, owned);
+        assert_eq!(SharedStr::from(Cow::Borrowed("Paris")), shared);
+        assert_eq!(SharedStr::from(Box::<str>::from("Paris")), shared);
+
+        let arc: Arc<str> = shared.clone().into();
+        assert!(SharedStr::from(arc).ptr_eq(&shared));
+        assert_eq!(shared.into_string(), owned);
+    }
+}
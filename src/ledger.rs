@@ -0,0 +1,256 @@
+//! Append-only ledger of every asset the tool has deleted.
+//!
+//! Each deletion is appended as one JSON line, across all runs, so there is
+//! a permanent record of what was removed, where its backup went, and the
+//! checksum needed to match a restored file. Entries are written *before*
+//! the delete request; if the request then fails, a `delete_failed` entry is
+//! appended rather than rewriting history.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ImmichError, Result};
+
+/// What happened to an asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerAction {
+    /// Moved to the Immich trash
+    Trashed,
+    /// Permanently deleted (`--force`)
+    Deleted,
+    /// The delete request recorded just before this entry failed
+    DeleteFailed,
+    /// Restored from the trash after the rest of its group failed to delete
+    Restored,
+}
+
+impl LedgerAction {
+    /// Short name of the action.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerAction::Trashed => "trashed",
+            LedgerAction::Deleted => "deleted",
+            LedgerAction::DeleteFailed => "delete_failed",
+            LedgerAction::Restored => "restored",
+        }
+    }
+}
+
+/// One ledger line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// When the entry was written
+    pub recorded_at: DateTime<Utc>,
+
+    /// What happened to the asset
+    pub action: LedgerAction,
+
+    /// Immich asset ID
+    pub asset_id: String,
+
+    /// Immich checksum (base64 SHA-1), if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+
+    /// Original filename
+    pub filename: String,
+
+    /// Where the backup was written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<PathBuf>,
+
+    /// Duplicate group ID, or `letterbox-<timestamp>` for a letterbox pair
+    pub group_id: String,
+
+    /// Asset kept in its place
+    pub winner_id: String,
+
+    /// Immich server the asset was deleted from
+    pub server_url: String,
+}
+
+/// Filters for [`Ledger::query`]. Empty filters match everything.
+#[derive(Debug, Clone, Default)]
+pub struct LedgerQuery {
+    /// Exact asset ID, winner ID, or group ID
+    pub id: Option<String>,
+
+    /// Case-insensitive substring of the filename
+    pub filename: Option<String>,
+
+    /// Exact checksum
+    pub checksum: Option<String>,
+
+    /// Only entries recorded at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl LedgerQuery {
+    /// Returns true if the entry passes every filter.
+    pub fn matches(&self, entry: &LedgerEntry) -> bool {
+        let id_matches = self.id.as_deref().is_none_or(|id| {
+            entry.asset_id == id || entry.winner_id == id || entry.group_id == id
+        });
+        let filename_matches = self.filename.as_deref().is_none_or(|needle| {
+            entry
+                .filename
+                .to_lowercase()
+                .contains(&needle.to_lowercase())
+        });
+        let checksum_matches = self
+            .checksum
+            .as_deref()
+            .is_none_or(|c| entry.checksum.as_deref() == Some(c));
+        let since_matches = self.since.is_none_or(|since| entry.recorded_at >= since);
+
+        id_matches && filename_matches && checksum_matches && since_matches
+    }
+}
+
+/// An append-only JSONL ledger file.
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    path: PathBuf,
+}
+
+impl Ledger {
+    /// Use the ledger at `path`; the file is created on first append.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the ledger file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append entries and flush them to disk.
+    pub fn append(&self, entries: &[LedgerEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut lines = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut lines, entry).map_err(std::io::Error::other)?;
+            lines.push(b'\n');
+        }
+
+        // One write per batch so concurrent groups don't interleave lines
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&lines)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Read every entry, oldest first. A missing ledger is empty.
+    pub fn read_all(&self) -> Result<Vec<LedgerEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut entries = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).map_err(|e| {
+                ImmichError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} line {}: {}", self.path.display(), number + 1, e),
+                ))
+            })?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    /// Entries matching `query`, oldest first.
+    pub fn query(&self, query: &LedgerQuery) -> Result<Vec<LedgerEntry>> {
+        Ok(self
+            .read_all()?
+            .into_iter()
+            .filter(|e| query.matches(e))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(asset_id: &str, filename: &str, action: LedgerAction) -> LedgerEntry {
+        LedgerEntry {
+            recorded_at: "2025-01-01T12:00:00Z".parse().unwrap(),
+            action,
+            asset_id: asset_id.to_string(),
+            checksum: Some(format!("sha1-{}", asset_id)),
+            filename: filename.to_string(),
+            backup_path: Some(PathBuf::from(format!("/backups/{}", filename))),
+            group_id: "dup-1".to_string(),
+            winner_id: "winner".to_string(),
+            server_url: "http://immich.local/".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_append_across_runs_and_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::new(dir.path().join("nested").join("ledger.jsonl"));
+        assert!(ledger.read_all().unwrap().is_empty());
+
+        ledger
+            .append(&[
+                entry("a", "IMG_0001.JPG", LedgerAction::Trashed),
+                entry("b", "beach.heic", LedgerAction::Trashed),
+            ])
+            .unwrap();
+        // A later run appends rather than overwriting
+        let later = Ledger::new(ledger.path());
+        later.append(&[entry("b", "beach.heic", LedgerAction::DeleteFailed)]).unwrap();
+
+        let all = later.read_all().unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2].action, LedgerAction::DeleteFailed);
+
+        let by_id = LedgerQuery {
+            id: Some("b".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(later.query(&by_id).unwrap().len(), 2);
+
+        let by_name = LedgerQuery {
+            filename: Some("img_0001".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(later.query(&by_name).unwrap()[0].asset_id, "a");
+
+        let by_group = LedgerQuery {
+            id: Some("dup-1".to_string()),
+            since: Some("2025-01-01T13:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(later.query(&by_group).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_line_reports_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        std::fs::write(&path, "\n{not json}\n").unwrap();
+
+        let err = Ledger::new(&path).read_all().unwrap_err().to_string();
+        assert!(err.contains("line 2"), "got {}", err);
+    }
+}
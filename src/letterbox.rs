@@ -8,9 +8,9 @@
 
 use std::collections::HashMap;
 
-use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use crate::clock::{Clock, SystemClock};
 use crate::models::AssetResponse;
 
 /// Aspect ratio classification for iPhone photos.
@@ -166,9 +166,7 @@ fn is_iphone_asset(asset: &AssetResponse) -> bool {
 
 /// Get aspect ratio from asset dimensions.
 fn get_asset_aspect_ratio(asset: &AssetResponse) -> Option<AspectRatio> {
-    let exif = asset.exif_info.as_ref()?;
-    let width = exif.exif_image_width?;
-    let height = exif.exif_image_height?;
+    let (width, height) = asset.exif_info.as_ref()?.display_dimensions()?;
     detect_aspect_ratio(width, height)
 }
 
@@ -290,6 +288,11 @@ impl LetterboxAnalysis {
     ///
     /// Analysis report with detected pairs and statistics.
     pub fn from_assets(assets: &[AssetResponse]) -> Self {
+        Self::from_assets_with_clock(assets, &SystemClock)
+    }
+
+    /// Like [`from_assets`](Self::from_assets), stamped with the time from `clock`.
+    pub(crate) fn from_assets_with_clock(assets: &[AssetResponse], clock: &dyn Clock) -> Self {
         // Count non-iPhone assets
         let skipped_non_iphone = assets
             .iter()
@@ -351,7 +354,7 @@ impl LetterboxAnalysis {
             total_space_recoverable,
             skipped_ambiguous,
             skipped_non_iphone,
-            analyzed_at: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            analyzed_at: clock.now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
         }
     }
 
@@ -379,8 +382,7 @@ mod tests {
         make: Option<&str>,
         model: Option<&str>,
         timestamp: Option<&str>,
-        lat: Option<f64>,
-        lon: Option<f64>,
+        gps: Option<(f64, f64)>,
     ) -> AssetResponse {
         let exif = ExifInfo {
             exif_image_width: width,
@@ -388,8 +390,8 @@ mod tests {
             make: make.map(String::from),
             model: model.map(String::from),
             date_time_original: timestamp.map(String::from),
-            latitude: lat,
-            longitude: lon,
+            latitude: gps.map(|(lat, _)| lat),
+            longitude: gps.map(|(_, lon)| lon),
             // Required fields with defaults
             city: None,
             state: None,
@@ -406,6 +408,8 @@ mod tests {
             orientation: None,
             modify_date: None,
             projection_type: None,
+            fps: None,
+            extra: Default::default(),
         };
 
         AssetResponse {
@@ -413,6 +417,7 @@ mod tests {
             original_file_name: format!("{}.HEIC", id),
             file_created_at: "2024-12-23T10:30:45Z".to_string(),
             local_date_time: "2024-12-23T10:30:45".to_string(),
+            updated_at: None,
             asset_type: AssetType::Image,
             exif_info: Some(exif),
             checksum: "abc123".to_string(),
@@ -425,6 +430,13 @@ mod tests {
             original_mime_type: Some("image/heic".to_string()),
             duplicate_id: None,
             thumbhash: None,
+            is_external: false,
+            library_id: None,
+            tags: Vec::new(),
+            people: Vec::new(),
+            stack: None,
+            visibility: None,
+            extra: Default::default(),
         }
     }
 
@@ -539,8 +551,7 @@ mod tests {
                 Some("Apple"),
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45.123Z"),
-                Some(51.5074),
-                Some(-0.1278),
+                Some((51.5074, -0.1278)),
             ),
             mock_asset(
                 "asset-16-9",
@@ -549,8 +560,7 @@ mod tests {
                 Some("Apple"),
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45.456Z"),
-                Some(51.5074),
-                Some(-0.1278),
+                Some((51.5074, -0.1278)),
             ),
         ];
 
@@ -574,7 +584,6 @@ mod tests {
                 Some("Galaxy S23"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9",
@@ -584,7 +593,6 @@ mod tests {
                 Some("Galaxy S23"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
         ];
 
@@ -604,7 +612,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 None, // No timestamp
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9",
@@ -614,7 +621,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 None, // No timestamp
                 None,
-                None,
             ),
         ];
 
@@ -634,7 +640,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-4-3-b",
@@ -644,7 +649,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9",
@@ -654,7 +658,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
         ];
 
@@ -674,7 +677,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9-a",
@@ -684,7 +686,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9-b",
@@ -694,7 +695,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
         ];
 
@@ -715,7 +715,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "pair1-16-9",
@@ -725,7 +724,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             // Pair 2 at 11:00:00
             mock_asset(
@@ -736,7 +734,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T11:00:00Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "pair2-16-9",
@@ -746,7 +743,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T11:00:00Z"),
                 None,
-                None,
             ),
         ];
 
@@ -765,8 +761,7 @@ mod tests {
                 Some("Apple"),
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
-                Some(51.5074), // London
-                Some(-0.1278),
+                Some((51.5074, -0.1278)), // London
             ),
             mock_asset(
                 "loc2-16-9",
@@ -775,8 +770,7 @@ mod tests {
                 Some("Apple"),
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
-                Some(40.7128), // New York
-                Some(-74.0060),
+                Some((40.7128, -74.0060)), // New York
             ),
         ];
 
@@ -796,8 +790,7 @@ mod tests {
                 Some("Apple"),
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
-                Some(51.5074),
-                Some(-0.1278),
+                Some((51.5074, -0.1278)),
             ),
             mock_asset(
                 "asset-16-9",
@@ -806,8 +799,7 @@ mod tests {
                 Some("Apple"),
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
-                Some(51.5074), // Same GPS
-                Some(-0.1278),
+                Some((51.5074, -0.1278)), // Same GPS
             ),
         ];
 
@@ -825,7 +817,6 @@ mod tests {
             Some("iPhone 15 Pro Max"),
             Some("2024-12-23T10:30:45Z"),
             None,
-            None,
         );
         asset_4_3.is_trashed = true;
 
@@ -837,7 +828,6 @@ mod tests {
             Some("iPhone 15 Pro Max"),
             Some("2024-12-23T10:30:45Z"),
             None,
-            None,
         );
 
         let assets = vec![asset_4_3, asset_16_9];
@@ -857,7 +847,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9",
@@ -867,7 +856,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
         ];
 
@@ -887,7 +875,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9",
@@ -897,7 +884,6 @@ mod tests {
                 Some("iPhone 14 Pro"), // Different model
                 Some("2024-12-23T10:30:45Z"),
                 None,
-                None,
             ),
         ];
 
@@ -916,7 +902,6 @@ mod tests {
             Some("iPhone 15 Pro Max"),
             Some("2024-12-23T10:30:45Z"),
             None,
-            None,
         )];
 
         let pairs = find_letterbox_pairs(&assets);
@@ -934,7 +919,6 @@ mod tests {
             Some("iPhone 15 Pro Max"),
             Some("2024-12-23T10:30:45Z"),
             None,
-            None,
         )];
 
         let pairs = find_letterbox_pairs(&assets);
@@ -953,7 +937,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45.123Z"),
                 None,
-                None,
             ),
             mock_asset(
                 "asset-16-9",
@@ -963,7 +946,6 @@ mod tests {
                 Some("iPhone 15 Pro Max"),
                 Some("2024-12-23T10:30:45.999Z"), // Different sub-second
                 None,
-                None,
             ),
         ];
 
@@ -1006,6 +988,8 @@ mod tests {
             orientation: None,
             modify_date: None,
             projection_type: None,
+            fps: None,
+            extra: Default::default(),
         };
 
         AssetResponse {
@@ -1013,6 +997,7 @@ mod tests {
             original_file_name: format!("{}.HEIC", id),
             file_created_at: "2024-12-23T10:30:45Z".to_string(),
             local_date_time: "2024-12-23T10:30:45".to_string(),
+            updated_at: None,
             asset_type: AssetType::Image,
             exif_info: Some(exif),
             checksum: "abc123".to_string(),
@@ -1025,6 +1010,13 @@ mod tests {
             original_mime_type: Some("image/heic".to_string()),
             duplicate_id: None,
             thumbhash: None,
+            is_external: false,
+            library_id: None,
+            tags: Vec::new(),
+            people: Vec::new(),
+            stack: None,
+            visibility: None,
+            extra: Default::default(),
         }
     }
 
@@ -6,8 +6,10 @@
 //! # Example
 //!
 //! ```no_run
+//! # #[cfg(feature = "native")]
 //! use immich_lib::ImmichClient;
 //!
+//! # #[cfg(feature = "native")]
 //! # async fn example() -> immich_lib::Result<()> {
 //! let client = ImmichClient::new("https://immich.example.com", "your-api-key")?;
 //! let duplicates = client.get_duplicates().await?;
@@ -18,17 +20,105 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Features
+//!
+//! The client, executor, and everything else that talks to a server or the
+//! filesystem are behind the default `native` feature. Built with
+//! `default-features = false`, the crate has no tokio or reqwest dependency
+//! and [`models`], [`scoring`], and [`letterbox`] compile to `wasm32`, so a
+//! web UI can score and rank duplicate groups itself.
 
+#[cfg(feature = "native")]
+pub mod analysis;
+#[cfg(feature = "native")]
+pub mod archive;
+#[cfg(feature = "native")]
+pub mod backup;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod camera;
+#[cfg(feature = "native")]
 pub mod client;
+pub mod clock;
+#[cfg(feature = "native")]
+pub mod doctor;
+#[cfg(feature = "native")]
+pub mod embed;
 pub mod error;
+#[cfg(feature = "events")]
+pub mod events;
+#[cfg(feature = "native")]
 pub mod executor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod history;
+#[cfg(feature = "native")]
+pub mod integrity;
+pub mod intern;
+pub mod ledger;
 pub mod letterbox;
+#[cfg(feature = "native")]
+pub mod metrics;
 pub mod models;
+#[cfg(feature = "native")]
+pub mod notify;
+#[cfg(feature = "native")]
+pub mod pipeline;
+#[cfg(feature = "native")]
+pub mod rate_limit;
+#[cfg(feature = "native")]
+pub mod reconcile;
+#[cfg(feature = "native")]
+pub mod report_io;
+#[cfg(feature = "native")]
+pub mod reports;
+#[cfg(feature = "native")]
+pub mod retention;
+pub mod review;
+#[cfg(feature = "native")]
+pub mod review_album;
+#[cfg(feature = "native")]
+pub mod risk;
+pub mod sampling;
 pub mod scoring;
+pub mod search;
+#[cfg(feature = "native")]
+pub mod sidecar;
+#[cfg(feature = "native")]
+pub mod stats;
+#[cfg(feature = "sqlite")]
+pub mod store;
+#[cfg(feature = "native")]
+pub mod support;
+#[cfg(feature = "native")]
+pub mod tagging;
+#[cfg(feature = "native")]
+pub mod tenant;
+#[cfg(feature = "native")]
 pub mod testing;
+#[cfg(feature = "native")]
+pub mod thumbnails;
+pub mod units;
+#[cfg(feature = "native")]
+pub mod verify;
 
-pub use client::{ImmichClient, UploadResponse};
+#[cfg(feature = "native")]
+pub use analysis::AnalysisReport;
+#[cfg(feature = "blocking")]
+pub use blocking::ImmichBlockingClient;
+#[cfg(feature = "native")]
+pub use client::{DeleteReport, FailedDelete, ImmichClient, UploadCheck, UploadOptions, UploadResponse, UploadStatus, file_checksum};
+pub use clock::{Clock, FixedClock, SystemClock};
 pub use error::{ImmichError, Result};
+#[cfg(feature = "native")]
 pub use executor::Executor;
+pub use filter::GroupFilter;
+pub use intern::{Interner, SharedStr};
 pub use letterbox::{detect_aspect_ratio, find_letterbox_pairs, AspectRatio, LetterboxAnalysis, LetterboxPair};
-pub use scoring::{detect_conflicts, DuplicateAnalysis, MetadataConflict, MetadataScore, ScoredAsset};
+pub use review::{cluster_events, ClusterOptions, Decision, ReviewDecision, ReviewEvent};
+#[allow(deprecated)]
+pub use scoring::ConflictOptions;
+pub use scoring::{detect_conflicts, detect_conflicts_with_options, rank_assets, ArchivedPolicy, DuplicateAnalysis, MetadataConflict, MetadataScore, ReviewReason, ScoredAsset, SelectionWarning, Thresholds, TrashedPolicy, WinnerStrategy};
+pub use search::{SearchField, SearchHit, SearchIndex};
@@ -0,0 +1,245 @@
+//! Prometheus metrics for long-running executions.
+//!
+//! A [`Metrics`] registry counts API requests, downloads, deletions,
+//! failures, and bytes reclaimed, and keeps a histogram of API request
+//! latency. Put one in [`ExecutionConfig::metrics`](crate::models::ExecutionConfig)
+//! and read it while the run is in progress with [`serve`] (a `/metrics`
+//! scrape endpoint), or afterwards with [`Metrics::push`] (a Prometheus
+//! pushgateway, for runs that end before they can be scraped).
+//!
+//! Everything is rendered in the Prometheus text exposition format.
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::error::{ImmichError, Result};
+
+/// Upper bounds (seconds) of the request latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Counters and histograms for one process.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    api_requests: AtomicU64,
+    downloads: AtomicU64,
+    deletions: AtomicU64,
+    failures: AtomicU64,
+    bytes_reclaimed: AtomicU64,
+    request_latency: Histogram,
+}
+
+impl Metrics {
+    /// Create an empty registry, ready to share between tasks.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Record one API request and how long it took.
+    pub fn observe_request(&self, latency: Duration) {
+        self.api_requests.fetch_add(1, Ordering::Relaxed);
+        self.request_latency.observe(latency.as_secs_f64());
+    }
+
+    /// Record successfully downloaded backups.
+    pub fn add_downloads(&self, count: u64) {
+        self.downloads.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Record deleted assets and the bytes their deletion frees.
+    pub fn add_deletions(&self, count: u64, bytes: u64) {
+        self.deletions.fetch_add(count, Ordering::Relaxed);
+        self.bytes_reclaimed.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record failed operations.
+    pub fn add_failures(&self, count: u64) {
+        self.failures.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Render every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counters = [
+            ("immich_dupes_api_requests_total", "Immich API requests made", &self.api_requests),
+            ("immich_dupes_downloads_total", "Loser backups downloaded", &self.downloads),
+            ("immich_dupes_deletions_total", "Assets deleted or trashed", &self.deletions),
+            ("immich_dupes_failures_total", "Operations that failed", &self.failures),
+            ("immich_dupes_bytes_reclaimed_total", "Bytes freed by deletions", &self.bytes_reclaimed),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value.load(Ordering::Relaxed));
+        }
+        self.request_latency.render(
+            "immich_dupes_request_duration_seconds",
+            "Immich API request latency",
+            &mut out,
+        );
+        out
+    }
+
+    /// Push the current values to a Prometheus pushgateway under `job`.
+    ///
+    /// Replaces the job's previous metrics (`PUT /metrics/job/<job>`).
+    pub async fn push(&self, gateway_url: &str, job: &str) -> Result<()> {
+        let url = url::Url::parse(gateway_url)?.join(&format!("metrics/job/{}", job))?;
+        let response = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?
+            .put(url)
+            .header(reqwest::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(self.render())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(ImmichError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Cumulative histogram with fixed buckets.
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Observations at or below each bound in [`LATENCY_BUCKETS`]
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    /// Sum of observations, in microseconds
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, seconds: f64) {
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            if seconds <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add((seconds * 1_000_000.0) as u64, Ordering::Relaxed);
+    }
+
+    fn render(&self, name: &str, help: &str, out: &mut String) {
+        let count = self.count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, bucket.load(Ordering::Relaxed));
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "{}_sum {}", name, sum);
+        let _ = writeln!(out, "{}_count {}", name, count);
+    }
+}
+
+/// A running `/metrics` endpoint.
+///
+/// The endpoint stops when this value is dropped.
+pub struct MetricsServer {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MetricsServer {
+    /// Address the endpoint is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Serve `metrics` at `http://<addr>/metrics` for Prometheus to scrape.
+///
+/// Any other path returns 404. Use port 0 to pick a free port.
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) -> Result<MetricsServer> {
+    let listener = TcpListener::bind(addr).await?;
+    let addr = listener.local_addr()?;
+
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let metrics = Arc::clone(&metrics);
+            tokio::spawn(async move {
+                // Only the request line matters; scrapers send small GETs
+                let mut buffer = [0u8; 1024];
+                let Ok(read) = stream.read(&mut buffer).await else {
+                    return;
+                };
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+                let (status, body) = if path == "/metrics" || path.starts_with("/metrics?") {
+                    ("200 OK", metrics.render())
+                } else {
+                    ("404 Not Found", "not found\n".to_string())
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    Ok(MetricsServer { addr, handle })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters_and_histogram() {
+        let metrics = Metrics::new();
+        metrics.observe_request(Duration::from_millis(20));
+        metrics.observe_request(Duration::from_millis(300));
+        metrics.add_deletions(2, 4096);
+
+        let text = metrics.render();
+        assert!(text.contains("immich_dupes_api_requests_total 2\n"));
+        assert!(text.contains("immich_dupes_deletions_total 2\n"));
+        assert!(text.contains("immich_dupes_bytes_reclaimed_total 4096\n"));
+        assert!(text.contains("immich_dupes_request_duration_seconds_bucket{le=\"0.025\"} 1\n"));
+        assert!(text.contains("immich_dupes_request_duration_seconds_bucket{le=\"0.5\"} 2\n"));
+        assert!(text.contains("immich_dupes_request_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("immich_dupes_request_duration_seconds_count 2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_serve_metrics_endpoint() {
+        let metrics = Metrics::new();
+        metrics.add_downloads(3);
+        let server = serve(Arc::clone(&metrics), "127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+        let body = reqwest::get(format!("http://{}/metrics", server.addr()))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(body.contains("immich_dupes_downloads_total 3\n"));
+    }
+}
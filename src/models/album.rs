@@ -0,0 +1,228 @@
+//! Album and shared link response types.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// An album (`GET /api/albums`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumResponse {
+    /// Album ID
+    pub id: String,
+
+    /// Album name
+    pub album_name: String,
+
+    /// Whether the album is shared with other users or via a link
+    #[serde(default)]
+    pub shared: bool,
+
+    /// Whether the album has shared links
+    #[serde(default)]
+    pub has_shared_link: bool,
+
+    /// Number of assets in the album
+    #[serde(default)]
+    pub asset_count: u64,
+
+    /// Asset shown as the album cover
+    #[serde(default)]
+    pub album_thumbnail_asset_id: Option<String>,
+
+    /// User who owns the album
+    #[serde(default)]
+    pub owner_id: Option<String>,
+
+    /// Users the album is shared with, and their roles
+    #[serde(default)]
+    pub album_users: Vec<AlbumUser>,
+
+    /// Album members (only included by `GET /api/albums/{id}`)
+    #[serde(default)]
+    pub assets: Vec<AssetRef>,
+}
+
+impl AlbumResponse {
+    /// Returns true if `user_id` may add assets to and remove them from the
+    /// album: it owns the album or is one of its editors. An album whose
+    /// owner isn't known is assumed to be editable.
+    pub fn is_editable_by(&self, user_id: &str) -> bool {
+        self.owner_id.as_deref().is_none_or(|owner| owner == user_id)
+            || self
+                .album_users
+                .iter()
+                .any(|u| u.user.id == user_id && u.role == AlbumUserRole::Editor)
+    }
+}
+
+/// A user an album is shared with.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AlbumUser {
+    /// The user
+    pub user: UserRef,
+
+    /// What the user may do in the album
+    #[serde(default)]
+    pub role: AlbumUserRole,
+}
+
+/// What a user an album is shared with may do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlbumUserRole {
+    /// Add and remove assets
+    Editor,
+    /// Only view the album
+    #[default]
+    Viewer,
+}
+
+/// A user referenced by ID inside another response.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserRef {
+    /// User ID
+    pub id: String,
+}
+
+/// An asset referenced by ID inside another response.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssetRef {
+    /// Asset ID
+    pub id: String,
+}
+
+/// Why one asset of a bulk add or remove failed, as the server reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkIdError {
+    /// Already there (adding)
+    Duplicate,
+    /// The API key may not change the album or the asset
+    NoPermission,
+    /// No such asset, or (removing) the asset isn't there
+    NotFound,
+    /// Any other failure
+    #[serde(other)]
+    Unknown,
+}
+
+impl BulkIdError {
+    /// The reason as the server sends it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BulkIdError::Duplicate => "duplicate",
+            BulkIdError::NoPermission => "no_permission",
+            BulkIdError::NotFound => "not_found",
+            BulkIdError::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for BulkIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Result for one asset of a bulk add or remove (albums, memories, tags,
+/// and shared links).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BulkIdResponse {
+    /// Asset ID
+    #[serde(alias = "assetId")]
+    pub id: String,
+
+    /// Whether the asset was added or removed
+    pub success: bool,
+
+    /// Why not, if it wasn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<BulkIdError>,
+}
+
+impl BulkIdResponse {
+    /// Returns true if the asset failed with `reason`.
+    pub fn failed_with(&self, reason: BulkIdError) -> bool {
+        !self.success && self.error == Some(reason)
+    }
+}
+
+/// What a shared link shares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SharedLinkType {
+    /// A whole album
+    Album,
+    /// Individually chosen assets
+    Individual,
+}
+
+/// A public shared link (`GET /api/shared-links`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedLinkResponse {
+    /// Shared link ID
+    pub id: String,
+
+    /// Album or individual assets
+    #[serde(rename = "type")]
+    pub link_type: SharedLinkType,
+
+    /// Description shown to recipients
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Shared assets (individual links)
+    #[serde(default)]
+    pub assets: Vec<AssetRef>,
+
+    /// Shared album (album links; its members are not included)
+    #[serde(default)]
+    pub album: Option<AlbumResponse>,
+}
+
+/// Every shared link, indexed by the assets each one shares.
+///
+/// Built once per run (see `ImmichClient::shared_link_index`) instead of
+/// listing the shared links again for each asset.
+#[derive(Debug, Clone, Default)]
+pub struct SharedLinkIndex {
+    /// Every shared link
+    links: Vec<SharedLinkResponse>,
+
+    /// Asset ID -> positions in `links` of the links sharing it
+    by_asset: HashMap<String, Vec<usize>>,
+}
+
+impl SharedLinkIndex {
+    /// Index `links`, where `album_members` gives the member asset IDs of
+    /// each album an album link shares.
+    pub fn new(links: Vec<SharedLinkResponse>, album_members: &HashMap<String, Vec<String>>) -> Self {
+        let mut by_asset: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, link) in links.iter().enumerate() {
+            let members = link
+                .album
+                .as_ref()
+                .and_then(|album| album_members.get(&album.id))
+                .into_iter()
+                .flatten();
+            for asset_id in link.assets.iter().map(|a| &a.id).chain(members) {
+                let positions = by_asset.entry(asset_id.clone()).or_default();
+                if positions.last() != Some(&i) {
+                    positions.push(i);
+                }
+            }
+        }
+        Self { links, by_asset }
+    }
+
+    /// The links through which `asset_id` is shared: individually, or by
+    /// an album it is in.
+    pub fn links_for(&self, asset_id: &str) -> Vec<&SharedLinkResponse> {
+        self.by_asset
+            .get(asset_id)
+            .map(|positions| positions.iter().map(|&i| &self.links[i]).collect())
+            .unwrap_or_default()
+    }
+}
@@ -1,8 +1,12 @@
 //! Asset response types.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::exif::ExifInfo;
+use super::person::PersonResponse;
+use super::stack::AssetStack;
+use super::tag::TagResponse;
 
 /// Type of asset (image or video).
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -15,6 +19,18 @@ pub enum AssetType {
     Video,
 }
 
+impl std::str::FromStr for AssetType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "image" | "photo" => Ok(Self::Image),
+            "video" => Ok(Self::Video),
+            other => Err(format!("Unknown asset type '{}' (expected image or video)", other)),
+        }
+    }
+}
+
 /// Asset response from the Immich API.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -31,6 +47,10 @@ pub struct AssetResponse {
     /// Local date/time (timezone-aware)
     pub local_date_time: String,
 
+    /// When the asset last changed on the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+
     /// Asset type (image or video)
     #[serde(rename = "type")]
     pub asset_type: AssetType,
@@ -47,7 +67,9 @@ pub struct AssetResponse {
     /// Whether the asset is marked as favorite
     pub is_favorite: bool,
 
-    /// Whether the asset is archived
+    /// Whether the asset is archived (sent by servers before `visibility`
+    /// replaced it; use [`archived`](Self::archived))
+    #[serde(default)]
     pub is_archived: bool,
 
     /// Whether the asset has metadata
@@ -70,6 +92,35 @@ pub struct AssetResponse {
     /// Thumbhash for quick preview (nullable)
     #[serde(default)]
     pub thumbhash: Option<String>,
+
+    /// Whether the asset belongs to an external (read-only) library
+    #[serde(default)]
+    pub is_external: bool,
+
+    /// Library ID for assets imported from an external library (null for uploads)
+    #[serde(default)]
+    pub library_id: Option<String>,
+
+    /// Tags attached to the asset (returned by `GET /api/assets/{id}`)
+    #[serde(default)]
+    pub tags: Vec<TagResponse>,
+
+    /// People recognized in the asset (returned by `GET /api/assets/{id}`)
+    #[serde(default)]
+    pub people: Vec<PersonResponse>,
+
+    /// Stack the asset belongs to, if any
+    #[serde(default)]
+    pub stack: Option<AssetStack>,
+
+    /// Where the asset is shown (`timeline`, `archive`, `hidden`, or
+    /// `locked`; absent on older servers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+
+    /// Fields this version of the models doesn't know, kept as sent
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl AssetResponse {
@@ -77,4 +128,30 @@ impl AssetResponse {
     pub fn has_exif(&self) -> bool {
         self.exif_info.is_some()
     }
+
+    /// Returns true if the asset is archived, on servers that send
+    /// `isArchived` and on those that send `visibility` instead.
+    pub fn archived(&self) -> bool {
+        self.is_archived || self.visibility.as_deref() == Some("archive")
+    }
+
+    /// Returns true if this asset lives in an external library.
+    ///
+    /// External library assets are read-only: Immich re-reads their metadata
+    /// from disk and they cannot be deleted through the API.
+    pub fn is_external_library(&self) -> bool {
+        self.is_external || self.library_id.is_some()
+    }
+
+    /// Duration in seconds, parsed from the `H:MM:SS.ffffff` duration string.
+    ///
+    /// Returns `None` if the string is malformed.
+    pub fn duration_seconds(&self) -> Option<f64> {
+        let mut parts = self.duration.trim().splitn(3, ':');
+        let hours: f64 = parts.next()?.parse().ok()?;
+        let minutes: f64 = parts.next()?.parse().ok()?;
+        let seconds: f64 = parts.next()?.parse().ok()?;
+        let total = hours * 3600.0 + minutes * 60.0 + seconds;
+        (total.is_finite() && total >= 0.0).then_some(total)
+    }
 }
@@ -1,11 +1,19 @@
 //! Duplicate group response types.
 
-use serde::Deserialize;
+#[cfg(feature = "native")]
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use super::asset::AssetResponse;
+#[cfg(feature = "native")]
+use crate::client::ImmichClient;
+#[cfg(feature = "native")]
+use crate::error::Result;
 
 /// A group of duplicate assets identified by Immich.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DuplicateGroup {
     /// Unique identifier for this duplicate group
@@ -13,4 +21,100 @@ pub struct DuplicateGroup {
 
     /// Assets in this duplicate group
     pub assets: Vec<AssetResponse>,
+
+    /// Fields this version of the models doesn't know, kept as sent
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(feature = "native")]
+impl DuplicateGroup {
+    /// Replace assets listed without their EXIF data (a slim response) with
+    /// the server's full asset responses.
+    ///
+    /// Only incomplete assets are fetched. Assets the server no longer has,
+    /// or has no EXIF data for yet, are left as they are, and
+    /// [`DuplicateAnalysis`](crate::scoring::DuplicateAnalysis) will flag the
+    /// group as incomplete. Returns the number of assets filled in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an asset cannot be fetched.
+    pub async fn hydrate(&mut self, client: &ImmichClient) -> Result<usize> {
+        Self::hydrate_all(std::slice::from_mut(self), client).await
+    }
+
+    /// [`hydrate`](Self::hydrate) many groups, fetching every incomplete
+    /// asset in one batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an asset cannot be fetched.
+    pub async fn hydrate_all(groups: &mut [DuplicateGroup], client: &ImmichClient) -> Result<usize> {
+        let incomplete = Self::incomplete_asset_ids(groups);
+        if incomplete.is_empty() {
+            return Ok(0);
+        }
+        let assets = client.get_assets(&incomplete).await?;
+        Ok(Self::fill_in(groups, assets))
+    }
+
+    /// IDs of the assets listed without their EXIF data.
+    pub(crate) fn incomplete_asset_ids(groups: &[DuplicateGroup]) -> Vec<String> {
+        groups
+            .iter()
+            .flat_map(|g| &g.assets)
+            .filter(|a| !a.has_exif())
+            .map(|a| a.id.clone())
+            .collect()
+    }
+
+    /// Replace listed assets with the full responses in `assets` that have
+    /// EXIF data. Returns the number replaced.
+    pub(crate) fn fill_in(groups: &mut [DuplicateGroup], assets: Vec<AssetResponse>) -> usize {
+        let mut full: HashMap<String, AssetResponse> = assets
+            .into_iter()
+            .filter(|a| a.has_exif())
+            .map(|a| (a.id.clone(), a))
+            .collect();
+
+        let mut hydrated = 0;
+        for asset in groups.iter_mut().flat_map(|g| &mut g.assets) {
+            if let Some(full) = full.remove(&asset.id) {
+                *asset = full;
+                hydrated += 1;
+            }
+        }
+        hydrated
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::scoring::DuplicateAnalysis;
+    use crate::testing::builder::AssetBuilder;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[tokio::test]
+    async fn test_hydrate_fills_in_slim_assets() {
+        let server = MockServer::start(&synthetic_groups(2, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let mut groups = client.get_duplicates().await.unwrap();
+        for asset in &mut groups[0].assets {
+            asset.exif_info = None;
+        }
+        groups[1].assets.push(AssetBuilder::new("gone").build());
+
+        let analysis = DuplicateAnalysis::from_group(&groups[0]);
+        assert!(analysis.has_incomplete_data());
+        assert!(!DuplicateAnalysis::from_group(&synthetic_groups(1, 2).unwrap()[0]).has_incomplete_data());
+
+        // The asset the server doesn't have stays slim
+        assert_eq!(DuplicateGroup::hydrate_all(&mut groups, &client).await.unwrap(), 2);
+        assert!(groups[0].assets.iter().all(|a| a.has_exif()));
+        assert!(!DuplicateAnalysis::from_group(&groups[0]).has_incomplete_data());
+        assert!(DuplicateAnalysis::from_group(&groups[1]).has_incomplete_data());
+        assert_eq!(groups[0].hydrate(&client).await.unwrap(), 0);
+    }
 }
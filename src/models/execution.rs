@@ -3,24 +3,130 @@
 //! These types capture configuration, results, and outcomes for
 //! the duplicate execution workflow.
 
-use std::path::PathBuf;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "s3")]
+use crate::backup::{S3Config, S3Store};
+use crate::archive::{BackupArchive, TarArchive, ZipArchive};
+use crate::backup::{BackupStore, LocalStore};
+use crate::clock::Clock;
+use crate::client::DEFAULT_DELETE_CHUNK_SIZE;
+use crate::error::{ImmichError, Result};
+use crate::metrics::Metrics;
+use crate::models::AssetResponse;
+use crate::notify::NotificationConfig;
+use crate::rate_limit::RateLimitStats;
+use crate::review::ReviewDecision;
+use crate::units::format_bytes;
+
 /// Configuration for the execution pipeline.
 #[derive(Debug, Clone)]
 pub struct ExecutionConfig {
     /// Maximum requests per second to the Immich API
+    ///
+    /// The executor slows down when the server responds 429 Too Many
+    /// Requests, and speeds back up to this rate once requests succeed.
     pub requests_per_sec: u32,
 
     /// Maximum concurrent operations
     pub max_concurrent: usize,
 
+    /// Longest a single API operation (such as a download) may take before
+    /// it fails (none to wait indefinitely)
+    pub operation_timeout: Option<Duration>,
+
+    /// Longest a group may take to reach its delete step before it is given
+    /// up on, with nothing deleted (none to wait indefinitely)
+    pub group_timeout: Option<Duration>,
+
     /// Directory to save backup downloads before deletion
     pub backup_dir: PathBuf,
 
+    /// Where backups are saved: the backup directory, or (with the `s3`
+    /// feature) an S3-compatible bucket
+    pub backup_target: BackupTarget,
+
     /// If true, permanently delete assets; if false, move to trash
     pub force_delete: bool,
+
+    /// Losers sent per delete request
+    pub delete_chunk_size: usize,
+
+    /// How backups are kept in the backup directory
+    pub backup_sink: BackupSink,
+
+    /// If true, write an XMP sidecar with consolidated metadata next to each backup
+    pub write_sidecars: bool,
+
+    /// If true, embed each asset's Immich-side metadata into its backup as XMP
+    pub embed_metadata: bool,
+
+    /// If true, fully decode downloaded image backups when checking integrity
+    pub decode_backups: bool,
+
+    /// Albums (by name or ID) whose loser members must be transferred to the
+    /// winner, and the transfer verified, before the loser is deleted
+    pub protected_albums: Vec<String>,
+
+    /// Tags (by name, full value, or ID) handled the same way as protected albums
+    pub protected_tags: Vec<String>,
+
+    /// If true, make the winner the cover of any album whose cover was a
+    /// loser, once that loser is deleted (adding the winner to the album if
+    /// needed)
+    pub promote_album_covers: bool,
+
+    /// If true, add the winner to every memory the analysis found a deleted
+    /// loser in
+    pub preserve_memories: bool,
+
+    /// Tag (by full value, e.g. `dedupe/processed`) to attach to the winner of
+    /// every group the run processed, created if needed (none to skip)
+    pub tag_winners: Option<String>,
+
+    /// Append-only ledger to record every deletion in (none to skip recording)
+    pub ledger_path: Option<PathBuf>,
+
+    /// How to handle groups whose assets live in different libraries
+    pub cross_library: CrossLibraryPolicy,
+
+    /// How to handle losers that are shared through a public link
+    pub shared_links: SharedLinkPolicy,
+
+    /// What to do with albums owned by another user that the winner would
+    /// be added to
+    pub shared_albums: SharedAlbumPolicy,
+
+    /// How to consolidate loser descriptions into the winner's
+    pub description_policy: DescriptionPolicy,
+
+    /// If true, process groups whose assets belong to different users;
+    /// otherwise they are left untouched
+    pub allow_cross_owner: bool,
+
+    /// What to do with each group: the full backup and delete, or only
+    /// replace losers with the winner in albums
+    pub mode: ExecutionMode,
+
+    /// If true, and not force-deleting, restore a group's trashed losers
+    /// when only some of them could be deleted, so the group is untouched
+    pub rollback_partial: bool,
+
+    /// Directory to record the run's event history in (none to skip); see
+    /// [`crate::history::open`]
+    pub history_dir: Option<PathBuf>,
+
+    /// Webhook to notify when the run finishes (none to skip)
+    pub notification: Option<NotificationConfig>,
+
+    /// Registry to count requests, downloads, deletions, and failures in (none to skip)
+    pub metrics: Option<Arc<Metrics>>,
 }
 
 impl Default for ExecutionConfig {
@@ -28,21 +134,470 @@ impl Default for ExecutionConfig {
         Self {
             requests_per_sec: 10,
             max_concurrent: 5,
+            operation_timeout: None,
+            group_timeout: None,
             backup_dir: PathBuf::from("./backups"),
+            backup_target: BackupTarget::default(),
             force_delete: false,
+            delete_chunk_size: DEFAULT_DELETE_CHUNK_SIZE,
+            backup_sink: BackupSink::default(),
+            write_sidecars: false,
+            embed_metadata: false,
+            decode_backups: false,
+            protected_albums: Vec::new(),
+            protected_tags: Vec::new(),
+            promote_album_covers: false,
+            preserve_memories: false,
+            tag_winners: None,
+            ledger_path: None,
+            cross_library: CrossLibraryPolicy::default(),
+            shared_links: SharedLinkPolicy::default(),
+            shared_albums: SharedAlbumPolicy::default(),
+            description_policy: DescriptionPolicy::default(),
+            allow_cross_owner: false,
+            mode: ExecutionMode::default(),
+            rollback_partial: false,
+            history_dir: None,
+            notification: None,
+            metrics: None,
+        }
+    }
+}
+
+/// How the executor keeps loser backups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupSink {
+    /// One file per backup (plus any sidecar)
+    #[default]
+    Directory,
+    /// A single `backups-<timestamp>.tar` per run, ending with a manifest of
+    /// its contents; see [`TarArchive`]
+    Tar,
+    /// A single `backups-<timestamp>.zip` per run, ending with a manifest of
+    /// its contents; see [`ZipArchive`]
+    Zip,
+}
+
+impl BackupSink {
+    /// Short name of the sink (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Directory => "directory",
+            Self::Tar => "tar",
+            Self::Zip => "zip",
+        }
+    }
+
+    /// Start this sink's archive for a run in `dir`, named and stamped with
+    /// the time from `clock`, or `None` if backups are kept as loose files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be created.
+    pub async fn open(&self, dir: &Path, server_url: &str, clock: &dyn Clock) -> Result<Option<Arc<dyn BackupArchive>>> {
+        Ok(match self {
+            Self::Directory => None,
+            Self::Tar => Some(Arc::new(TarArchive::create_with_clock(dir, server_url, clock).await?)),
+            Self::Zip => Some(Arc::new(ZipArchive::create_with_clock(dir, server_url, clock).await?)),
+        })
+    }
+}
+
+impl std::fmt::Display for BackupSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for BackupSink {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "directory" => Ok(Self::Directory),
+            "tar" => Ok(Self::Tar),
+            "zip" => Ok(Self::Zip),
+            other => Err(format!("Unknown backup sink '{}' (expected directory, tar, or zip)", other)),
         }
     }
 }
 
+/// Where the executor saves loser backups.
+#[derive(Debug, Clone, Default)]
+pub enum BackupTarget {
+    /// Files in [`ExecutionConfig::backup_dir`]
+    #[default]
+    Directory,
+    /// Objects in an S3-compatible bucket, streamed from Immich with no
+    /// local copy
+    #[cfg(feature = "s3")]
+    S3(Box<S3Config>),
+}
+
+impl BackupTarget {
+    /// Open the store for this target, with `backup_dir` for local backups.
+    pub fn store(&self, backup_dir: &Path) -> Arc<dyn BackupStore> {
+        match self {
+            Self::Directory => Arc::new(LocalStore::new(backup_dir)),
+            #[cfg(feature = "s3")]
+            Self::S3(config) => Arc::new(S3Store::new(config.as_ref().clone())),
+        }
+    }
+}
+
+/// How the executor handles groups whose assets live in different libraries.
+///
+/// External library assets are never deleted through the API, whatever the
+/// policy; this decides what happens to the uploaded copies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrossLibraryPolicy {
+    /// Only delete losers in the same library as the winner, so an upload is
+    /// never deleted in favour of a copy Immich doesn't manage
+    #[default]
+    SameLibrary,
+    /// Leave cross-library groups untouched
+    Skip,
+    /// Delete every deletable loser, as for any other group
+    Proceed,
+}
+
+impl CrossLibraryPolicy {
+    /// Short name of the policy (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SameLibrary => "same-library",
+            Self::Skip => "skip",
+            Self::Proceed => "proceed",
+        }
+    }
+}
+
+impl std::fmt::Display for CrossLibraryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for CrossLibraryPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "same-library" => Ok(Self::SameLibrary),
+            "skip" => Ok(Self::Skip),
+            "proceed" => Ok(Self::Proceed),
+            other => Err(format!(
+                "Unknown cross-library policy '{}' (expected same-library, skip, or proceed)",
+                other
+            )),
+        }
+    }
+}
+
+/// How the executor handles losers that are shared through a public link.
+///
+/// Deleting a shared loser removes it from the link, so recipients lose it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SharedLinkPolicy {
+    /// Delete shared losers without checking their links
+    #[default]
+    Ignore,
+    /// Add the winner to each link (or its album) before the loser is deleted
+    Repoint,
+    /// Leave groups with a shared loser untouched, for review
+    Review,
+}
+
+impl SharedLinkPolicy {
+    /// Short name of the policy (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ignore => "ignore",
+            Self::Repoint => "repoint",
+            Self::Review => "review",
+        }
+    }
+}
+
+impl std::fmt::Display for SharedLinkPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for SharedLinkPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ignore" => Ok(Self::Ignore),
+            "repoint" => Ok(Self::Repoint),
+            "review" => Ok(Self::Review),
+            other => Err(format!(
+                "Unknown shared-link policy '{}' (expected ignore, repoint, or review)",
+                other
+            )),
+        }
+    }
+}
+
+/// How the executor handles albums owned by another user that the API key's
+/// user can't edit, when the winner has to be added to one (protected
+/// albums, `--album-only`, and album cover promotion).
+///
+/// Immich refuses changes to such an album with 403.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SharedAlbumPolicy {
+    /// Leave the album out of the transfer, and process the group as if the
+    /// loser weren't in it
+    Skip,
+    /// Leave groups that would need to change such an album untouched, for
+    /// review
+    #[default]
+    Review,
+    /// Try anyway; the transfer fails as it would for any other error
+    Attempt,
+}
+
+impl SharedAlbumPolicy {
+    /// Short name of the policy (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Review => "review",
+            Self::Attempt => "attempt",
+        }
+    }
+}
+
+impl std::fmt::Display for SharedAlbumPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for SharedAlbumPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "review" => Ok(Self::Review),
+            "attempt" => Ok(Self::Attempt),
+            other => Err(format!(
+                "Unknown shared-album policy '{}' (expected skip, review, or attempt)",
+                other
+            )),
+        }
+    }
+}
+
+/// How the executor consolidates loser descriptions into the winner's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DescriptionPolicy {
+    /// Copy a loser's description only when the winner has none
+    #[default]
+    Fill,
+    /// Append every loser description the winner's doesn't already contain,
+    /// separated by [`DESCRIPTION_SEPARATOR`]
+    Append,
+    /// Keep the longest of the winner's and the losers' descriptions
+    Longest,
+    /// As `fill`, but leave groups where the winner and a loser have
+    /// different descriptions, or the winner has none and the losers
+    /// disagree, untouched, for review
+    Review,
+}
+
+/// Separator between descriptions merged by [`DescriptionPolicy::Append`].
+pub const DESCRIPTION_SEPARATOR: &str = "\n\n";
+
+/// Start of the skip reason given to a group whose assets changed on the
+/// server since they were analyzed.
+pub const STALE_ANALYSIS: &str = "Stale analysis";
+
+impl DescriptionPolicy {
+    /// Short name of the policy (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fill => "fill",
+            Self::Append => "append",
+            Self::Longest => "longest",
+            Self::Review => "review",
+        }
+    }
+
+    /// The winner's new description after merging the losers' (in loser
+    /// order), or `None` to leave it as it is. Blank descriptions are
+    /// ignored.
+    ///
+    /// [`Review`](Self::Review) merges as [`Fill`](Self::Fill); the executor
+    /// holds conflicting groups itself.
+    pub fn merge(&self, winner: Option<&str>, losers: &[&str]) -> Option<String> {
+        let winner = winner.map(str::trim).filter(|d| !d.is_empty());
+        let mut losers = losers.iter().map(|d| d.trim()).filter(|d| !d.is_empty());
+        match (self, winner) {
+            (Self::Fill | Self::Review, Some(_)) => None,
+            (Self::Fill | Self::Review, None) => losers.next().map(str::to_string),
+            (Self::Append, _) => {
+                let mut merged = winner.map(str::to_string).unwrap_or_default();
+                for description in losers {
+                    if !merged.contains(description) {
+                        if !merged.is_empty() {
+                            merged.push_str(DESCRIPTION_SEPARATOR);
+                        }
+                        merged.push_str(description);
+                    }
+                }
+                (Some(merged.as_str()) != winner && !merged.is_empty()).then_some(merged)
+            }
+            (Self::Longest, _) => {
+                // The first of equally long descriptions wins, the winner's first of all
+                let longest = losers.fold(winner, |longest, d| match longest {
+                    Some(l) if l.chars().count() >= d.chars().count() => Some(l),
+                    _ => Some(d),
+                });
+                longest.filter(|l| Some(*l) != winner).map(str::to_string)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DescriptionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for DescriptionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fill" => Ok(Self::Fill),
+            "append" => Ok(Self::Append),
+            "longest" => Ok(Self::Longest),
+            "review" => Ok(Self::Review),
+            other => Err(format!(
+                "Unknown description policy '{}' (expected fill, append, longest, or review)",
+                other
+            )),
+        }
+    }
+}
+
+/// What the executor does with each duplicate group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutionMode {
+    /// Consolidate metadata, back up the losers, and delete them
+    #[default]
+    Full,
+    /// Consolidate metadata and replace each loser with the winner in its
+    /// albums; nothing is downloaded or deleted
+    AlbumOnly,
+}
+
+impl ExecutionMode {
+    /// Short name of the mode (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::AlbumOnly => "album-only",
+        }
+    }
+}
+
+impl std::fmt::Display for ExecutionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Server-side state of an asset, as observed by looking it up.
+///
+/// Serialized as `present`, `trashed`, `deleted`, or `error`, matching the
+/// strings older reports used; capitalized spellings are accepted on read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetState {
+    /// The asset exists and is not in the trash
+    #[serde(alias = "Present")]
+    Present,
+    /// The asset is in the Immich trash
+    #[serde(alias = "Trashed")]
+    Trashed,
+    /// The asset no longer exists (404)
+    #[serde(alias = "Deleted")]
+    Deleted,
+    /// The lookup failed, so the state is unknown
+    #[serde(alias = "Error")]
+    Error,
+}
+
+impl AssetState {
+    /// State of an asset the server returned.
+    pub fn of(asset: &AssetResponse) -> Self {
+        if asset.is_trashed {
+            AssetState::Trashed
+        } else {
+            AssetState::Present
+        }
+    }
+
+    /// Classify the result of an asset lookup.
+    pub fn from_lookup(lookup: &Result<AssetResponse>) -> Self {
+        match lookup {
+            Ok(asset) => Self::of(asset),
+            Err(e) => Self::from_error(e),
+        }
+    }
+
+    /// Classify an error from a request about a single asset.
+    pub fn from_error(error: &ImmichError) -> Self {
+        match error {
+            ImmichError::NotFound { .. } => AssetState::Deleted,
+            _ => AssetState::Error,
+        }
+    }
+
+    /// Returns true if the asset is trashed or deleted.
+    pub fn is_removed(&self) -> bool {
+        matches!(self, AssetState::Trashed | AssetState::Deleted)
+    }
+
+    /// Short name of the state.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetState::Present => "present",
+            AssetState::Trashed => "trashed",
+            AssetState::Deleted => "deleted",
+            AssetState::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for AssetState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Result of a single operation (download or delete).
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "status", rename_all = "snake_case")]
 pub enum OperationResult {
     /// Operation completed successfully
     Success {
         /// Asset ID that was processed
         id: String,
-        /// Path where file was saved (for downloads)
+        /// Path where file was saved (for downloads); for an archived
+        /// backup, the archive it was added to (its manifest names the member)
         #[serde(skip_serializing_if = "Option::is_none")]
         path: Option<PathBuf>,
     },
@@ -66,32 +621,453 @@ pub enum OperationResult {
 
 /// Result of metadata consolidation from loser assets to winner.
 ///
-/// Tracks which metadata fields were transferred and from which asset.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Lists each field the winner was given, with its value and the loser it
+/// came from, so every transfer can be checked on its own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "StoredConsolidationResult")]
 pub struct ConsolidationResult {
-    /// Whether GPS coordinates were transferred
-    pub gps_transferred: bool,
+    /// Fields written to the winner (or attempted, if not applied)
+    #[serde(default)]
+    pub fields: Vec<FieldTransfer>,
+
+    /// Whether the update was sent a second time because the winner didn't
+    /// show some fields on read-back
+    #[serde(default)]
+    pub retried: bool,
+
+    /// Whether the update was sent before the group started, in one bulk
+    /// request shared with other winners of its batch
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bulk: bool,
+}
+
+/// A [`ConsolidationResult`] as read from a report: either the per-field
+/// form, or the `*_transferred` flags of reports written before it.
+#[derive(Deserialize)]
+struct StoredConsolidationResult {
+    #[serde(default)]
+    fields: Vec<FieldTransfer>,
+    #[serde(default)]
+    retried: bool,
+    #[serde(default)]
+    bulk: bool,
+    #[serde(default)]
+    gps_transferred: bool,
+    #[serde(default)]
+    datetime_transferred: bool,
+    #[serde(default)]
+    description_transferred: bool,
+    #[serde(default)]
+    source_asset_id: Option<String>,
+    #[serde(default)]
+    sources: MetadataSources,
+}
+
+impl From<StoredConsolidationResult> for ConsolidationResult {
+    fn from(stored: StoredConsolidationResult) -> Self {
+        let mut fields = stored.fields;
+        if fields.is_empty() {
+            // Old reports only said which fields were transferred, and from where
+            let legacy = [
+                (MetadataField::Gps, stored.gps_transferred, stored.sources.gps),
+                (MetadataField::Datetime, stored.datetime_transferred, stored.sources.datetime),
+                (MetadataField::Description, stored.description_transferred, stored.sources.description),
+            ];
+            fields = legacy
+                .into_iter()
+                .filter(|(_, transferred, _)| *transferred)
+                .map(|(field, _, source)| FieldTransfer {
+                    field,
+                    value: String::new(),
+                    source_asset_id: source.or_else(|| stored.source_asset_id.clone()),
+                    applied: true,
+                    discrepancy: None,
+                })
+                .collect();
+        }
+        Self {
+            fields,
+            retried: stored.retried,
+            bulk: stored.bulk,
+        }
+    }
+}
 
-    /// Whether date/time was transferred
-    pub datetime_transferred: bool,
+/// A metadata field consolidated onto the winner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldTransfer {
+    /// Field written
+    pub field: MetadataField,
 
-    /// Whether description was transferred
-    pub description_transferred: bool,
+    /// Value written: `latitude,longitude` for GPS, the RFC 3339 date/time,
+    /// or the description (empty in reports written before values were
+    /// recorded)
+    pub value: String,
 
-    /// Asset ID that provided the consolidated metadata
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Loser the value came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source_asset_id: Option<String>,
+
+    /// Whether the server accepted the update and, when the winner could be
+    /// read back, the winner has the value
+    pub applied: bool,
+
+    /// What the winner had on read-back instead of the value (`none` if
+    /// nothing), when the server accepted the update but didn't apply it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discrepancy: Option<String>,
+}
+
+/// A metadata field the executor consolidates onto the winner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataField {
+    /// GPS coordinates
+    Gps,
+    /// Original date/time
+    Datetime,
+    /// Description
+    Description,
+}
+
+impl MetadataField {
+    /// Short name of the field (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gps => "gps",
+            Self::Datetime => "datetime",
+            Self::Description => "description",
+        }
+    }
+}
+
+impl std::fmt::Display for MetadataField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The loser each consolidated metadata field was taken from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataSources {
+    /// Source of the GPS coordinates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gps: Option<String>,
+
+    /// Source of the original date/time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datetime: Option<String>,
+
+    /// Source of the description (the first loser whose text it includes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl MetadataSources {
+    /// Returns true if no field has a source.
+    pub fn is_empty(&self) -> bool {
+        self.gps.is_none() && self.datetime.is_none() && self.description.is_none()
+    }
+
+    /// The first source, preferring GPS, then date/time, then description.
+    pub fn first(&self) -> Option<&str> {
+        self.gps.as_deref().or(self.datetime.as_deref()).or(self.description.as_deref())
+    }
 }
 
 impl ConsolidationResult {
     /// Check if any consolidation was performed.
     pub fn any_transferred(&self) -> bool {
-        self.gps_transferred || self.datetime_transferred || self.description_transferred
+        self.fields.iter().any(|f| f.applied)
+    }
+
+    /// Returns true if `field` was written to the winner.
+    pub fn transferred(&self, field: MetadataField) -> bool {
+        self.fields.iter().any(|f| f.field == field && f.applied)
+    }
+
+    /// The transfer of `field`, if it was planned.
+    pub fn field(&self, field: MetadataField) -> Option<&FieldTransfer> {
+        self.fields.iter().find(|f| f.field == field)
+    }
+}
+
+/// Operations the executor will perform on the server, decided before
+/// anything is changed.
+///
+/// Made by [`Executor::plan`](crate::Executor::plan) and carried out by
+/// [`Executor::apply`](crate::Executor::apply). A plan can be saved, read,
+/// edited (e.g. dropping a group or a loser's download), and approved
+/// before it is applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    /// When the plan was made
+    pub created_at: DateTime<Utc>,
+
+    /// Immich server the plan is for
+    pub server_url: String,
+
+    /// Whether losers are permanently deleted rather than trashed
+    pub force_delete: bool,
+
+    /// Operations for each group
+    pub groups: Vec<GroupPlan>,
+}
+
+impl ExecutionPlan {
+    /// Losers the plan deletes.
+    pub fn assets_to_delete(&self) -> usize {
+        self.groups.iter().map(|g| g.delete_ids().len()).sum()
+    }
+}
+
+/// Operations for one duplicate group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupPlan {
+    /// The duplicate group identifier
+    pub duplicate_id: String,
+
+    /// The winner asset ID
+    pub winner_id: String,
+
+    /// Reviewer decision the group is executed under, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<ReviewDecision>,
+
+    /// Every loser of the group when it was planned. Operations may only
+    /// download or delete these.
+    #[serde(default)]
+    pub losers: Vec<String>,
+
+    /// Operations, in the order they are applied
+    pub operations: Vec<PlannedOperation>,
+}
+
+impl GroupPlan {
+    /// Check that the plan only downloads and deletes the group's losers,
+    /// as an edited plan might not.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidPlan`] if a download or delete names
+    /// the winner or an asset that isn't one of [`losers`](Self::losers).
+    pub fn validate(&self) -> Result<()> {
+        let targets = self.operations.iter().flat_map(|op| match op {
+            PlannedOperation::Download { asset_id, .. } => std::slice::from_ref(asset_id),
+            PlannedOperation::Delete { asset_ids } => asset_ids.as_slice(),
+            _ => &[],
+        });
+        for id in targets {
+            if *id == self.winner_id {
+                return Err(ImmichError::InvalidPlan(format!(
+                    "group {} would delete its winner {}",
+                    self.duplicate_id, id
+                )));
+            }
+            if !self.losers.contains(id) {
+                return Err(ImmichError::InvalidPlan(format!(
+                    "group {} would delete {}, which isn't one of its losers",
+                    self.duplicate_id, id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Losers the group downloads, skips, or replaces in albums, in plan order.
+    pub fn loser_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = Vec::new();
+        for op in &self.operations {
+            let id = match op {
+                PlannedOperation::Download { asset_id, .. } => asset_id,
+                PlannedOperation::Skip { asset_id, .. } if *asset_id != self.duplicate_id => asset_id,
+                PlannedOperation::ReplaceInAlbum { loser_id, .. } => loser_id,
+                _ => continue,
+            };
+            if !ids.contains(&id.as_str()) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// Returns true if the group has a delete step, planned or skipped.
+    pub fn has_delete_step(&self) -> bool {
+        self.operations.iter().any(|op| match op {
+            PlannedOperation::Download { .. } | PlannedOperation::Delete { .. } => true,
+            PlannedOperation::Skip { asset_id, .. } => *asset_id == self.duplicate_id,
+            _ => false,
+        })
+    }
+
+    /// Losers the delete operation removes, if their backups succeed.
+    pub fn delete_ids(&self) -> &[String] {
+        self.operations
+            .iter()
+            .find_map(|op| match op {
+                PlannedOperation::Delete { asset_ids } => Some(asset_ids.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Why the group's delete step is skipped, if the plan skips it.
+    pub fn delete_skipped(&self) -> Option<&str> {
+        self.operations.iter().find_map(|op| match op {
+            PlannedOperation::Skip { asset_id, reason } if *asset_id == self.duplicate_id => Some(reason.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns true if the group is skipped because its analysis is stale.
+    pub fn is_stale(&self) -> bool {
+        self.delete_skipped().is_some_and(|reason| reason.starts_with(STALE_ANALYSIS))
+    }
+
+    /// File size the plan recorded for a loser's download, if known.
+    pub fn file_size(&self, loser_id: &str) -> Option<u64> {
+        self.operations.iter().find_map(|op| match op {
+            PlannedOperation::Download { asset_id, file_size, .. } if asset_id == loser_id => *file_size,
+            _ => None,
+        })
+    }
+}
+
+/// One operation in a [`GroupPlan`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PlannedOperation {
+    /// Copy metadata the winner lacks from the losers onto the winner
+    UpdateMetadata {
+        /// Asset updated (the winner)
+        asset_id: String,
+        /// Loser the metadata comes from (the first of `sources`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_id: Option<String>,
+        /// Loser each field comes from
+        #[serde(default, skip_serializing_if = "MetadataSources::is_empty")]
+        sources: MetadataSources,
+        /// Latitude to set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        latitude: Option<f64>,
+        /// Longitude to set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        longitude: Option<f64>,
+        /// Original date/time to set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        date_time_original: Option<String>,
+        /// Description to set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+
+    /// Back up a loser to the backup directory
+    Download {
+        /// Loser to back up
+        asset_id: String,
+        /// Original filename, used to name the backup
+        filename: String,
+        /// Immich checksum (base64 SHA-1), recorded in the ledger
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checksum: Option<String>,
+        /// File size in bytes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file_size: Option<u64>,
+    },
+
+    /// Replace a loser with the winner in an album: add the winner, then
+    /// remove the loser
+    ReplaceInAlbum {
+        /// Album to update
+        album_id: String,
+        /// Album name, for display
+        album_name: String,
+        /// Loser to remove from the album
+        loser_id: String,
+    },
+
+    /// Make a shared link show the winner before a loser it shares is
+    /// deleted: add the winner to the link, or to the link's album
+    RepointSharedLink {
+        /// Shared link to update
+        link_id: String,
+        /// Link description (or its album's name), for display
+        #[serde(default)]
+        link_name: String,
+        /// Album the link shares, if it shares an album
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        album_id: Option<String>,
+        /// Loser the link shares
+        loser_id: String,
+    },
+
+    /// Add the winner to a memory a loser about to be deleted appears in
+    AddToMemory {
+        /// Memory to update
+        memory_id: String,
+        /// Loser in the memory
+        loser_id: String,
+    },
+
+    /// Leave a loser alone, or (with the group ID) skip the group's delete step
+    Skip {
+        /// Loser skipped, or the group ID
+        asset_id: String,
+        /// Why
+        reason: String,
+    },
+
+    /// Delete (or trash) these losers; only those backed up in the same
+    /// apply are deleted
+    Delete {
+        /// Losers to delete
+        asset_ids: Vec<String>,
+    },
+}
+
+/// Where the winner can be promoted in place of a loser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromotionKind {
+    /// Album cover (thumbnail)
+    AlbumCover,
+    /// Membership of an album
+    AlbumMembership,
+    /// Assets shared by a public link
+    SharedLink,
+    /// A memory (e.g. "On this day")
+    Memory,
+}
+
+/// The winner taking over a place a loser held (e.g. an album cover).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Promotion {
+    /// What kind of place
+    pub kind: PromotionKind,
+
+    /// ID of the album, shared link, or memory
+    pub target_id: String,
+
+    /// Name of the album, or description of the shared link
+    pub target_name: String,
+
+    /// Loser that held the place
+    pub replaced_asset_id: String,
+
+    /// Why the promotion failed, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Promotion {
+    /// Returns true if the winner was promoted.
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
     }
 }
 
 /// Result of processing a single duplicate group.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupResult {
     /// The duplicate group identifier
     pub duplicate_id: String,
@@ -100,19 +1076,85 @@ pub struct GroupResult {
     pub winner_id: String,
 
     /// Result of metadata consolidation (if attempted)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub consolidation_result: Option<ConsolidationResult>,
 
     /// Results of downloading each loser asset
     pub download_results: Vec<OperationResult>,
 
     /// Result of deleting assets (if downloads succeeded)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub delete_result: Option<OperationResult>,
+
+    /// Losers confirmed deleted (or trashed), checked one by one if the
+    /// delete request failed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deleted_ids: Vec<String>,
+
+    /// Losers restored from the trash after a partial failure
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rolled_back_ids: Vec<String>,
+
+    /// Bytes freed by the permanently deleted losers: the size of each
+    /// backup, or else the size the analysis recorded
+    #[serde(default)]
+    pub reclaimed_bytes: u64,
+
+    /// Bytes of the losers moved to the trash, freed once it is emptied
+    #[serde(default)]
+    pub trashed_bytes: u64,
+
+    /// Places the winner was promoted into before the losers were deleted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promotions: Vec<Promotion>,
+
+    /// Reviewer decision the group was executed under, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<ReviewDecision>,
+}
+
+/// What `execute` writes alongside its JSON execution report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportFormat {
+    /// Only the JSON report
+    #[default]
+    Json,
+    /// A Markdown summary too (see [`ExecutionReport::to_markdown`]); the
+    /// JSON report is still written, as other commands read it
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Short name of the format (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Markdown => "markdown",
+        }
+    }
+}
+
+impl std::fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "markdown" | "md" => Ok(Self::Markdown),
+            other => Err(format!("Unknown report format '{}' (expected json or markdown)", other)),
+        }
+    }
 }
 
 /// Summary report of the entire execution.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionReport {
     /// Total number of duplicate groups processed
     pub total_groups: usize,
@@ -129,8 +1171,52 @@ pub struct ExecutionReport {
     /// Number of operations that were skipped
     pub skipped: usize,
 
+    /// Bytes freed by permanently deleted assets
+    #[serde(default)]
+    pub reclaimed_bytes: u64,
+
+    /// Bytes of assets moved to the trash, freed once it is emptied
+    #[serde(default)]
+    pub trashed_bytes: u64,
+
+    /// When the run finished (missing from reports written before it was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+
     /// Detailed results for each group
     pub results: Vec<GroupResult>,
+
+    /// ID of the run's event history, if recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+
+    /// Why the event history could not be written, if it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_error: Option<String>,
+
+    /// Archive the run's backups were written to, if archiving
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_archive: Option<PathBuf>,
+
+    /// Why the backup archive could not be created or finished, if it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_error: Option<String>,
+
+    /// Winners tagged with the configured winner tag
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tagged_winners: Vec<String>,
+
+    /// Why the winners could not be tagged, if it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tagging_error: Option<String>,
+
+    /// Why the completion notification could not be sent, if it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification_error: Option<String>,
+
+    /// How the request rate adapted to the server's rate limiting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitStats>,
 }
 
 impl ExecutionReport {
@@ -142,13 +1228,26 @@ impl ExecutionReport {
             deleted: 0,
             failed: 0,
             skipped: 0,
+            reclaimed_bytes: 0,
+            trashed_bytes: 0,
+            finished_at: None,
             results: Vec::new(),
+            run_id: None,
+            history_error: None,
+            backup_archive: None,
+            archive_error: None,
+            tagged_winners: Vec::new(),
+            tagging_error: None,
+            notification_error: None,
+            rate_limit: None,
         }
     }
 
     /// Add a group result and update counters.
     pub fn add_group_result(&mut self, result: GroupResult) {
         self.total_groups += 1;
+        self.reclaimed_bytes += result.reclaimed_bytes;
+        self.trashed_bytes += result.trashed_bytes;
 
         // Count download outcomes
         for download in &result.download_results {
@@ -162,21 +1261,133 @@ impl ExecutionReport {
         // Count delete outcomes
         if let Some(ref delete) = result.delete_result {
             match delete {
-                OperationResult::Success { .. } => {
-                    // Count deleted losers (download successes that were deleted)
-                    self.deleted += result
-                        .download_results
-                        .iter()
-                        .filter(|r| matches!(r, OperationResult::Success { .. }))
-                        .count();
+                OperationResult::Success { .. } => self.deleted += result.deleted_ids.len(),
+                OperationResult::Failed { .. } => {
+                    // Some losers may have gone even though the request failed
+                    self.deleted += result.deleted_ids.len();
+                    self.failed += 1;
                 }
-                OperationResult::Failed { .. } => self.failed += 1,
                 OperationResult::Skipped { .. } => self.skipped += 1,
             }
         }
 
         self.results.push(result);
     }
+
+    /// Bytes of every deleted asset, whether freed now or once the trash is
+    /// emptied.
+    pub fn deleted_bytes(&self) -> u64 {
+        self.reclaimed_bytes + self.trashed_bytes
+    }
+
+    /// Human-readable Markdown summary of the run: a table of counts, the
+    /// metadata consolidated onto each winner, and every failure with its
+    /// reason.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let consolidated: Vec<(&GroupResult, &ConsolidationResult)> = self
+            .results
+            .iter()
+            .filter_map(|r| r.consolidation_result.as_ref().filter(|c| c.any_transferred()).map(|c| (r, c)))
+            .collect();
+
+        let _ = writeln!(out, "## Execution report");
+        let _ = writeln!(out);
+        if let Some(finished_at) = self.finished_at {
+            let _ = writeln!(out, "Finished {}", finished_at.format("%Y-%m-%d %H:%M UTC"));
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(out, "| | Count |");
+        let _ = writeln!(out, "|---|---:|");
+        let _ = writeln!(out, "| Groups | {} |", self.total_groups);
+        let _ = writeln!(out, "| Backed up | {} |", self.downloaded);
+        let _ = writeln!(out, "| Deleted | {} |", self.deleted);
+        let _ = writeln!(out, "| Space reclaimed | {} |", format_bytes(self.reclaimed_bytes));
+        let _ = writeln!(out, "| Space in trash | {} |", format_bytes(self.trashed_bytes));
+        let _ = writeln!(out, "| Metadata consolidated | {} |", consolidated.len());
+        let _ = writeln!(out, "| Failed | {} |", self.failed);
+        let _ = writeln!(out, "| Skipped | {} |", self.skipped);
+
+        if !consolidated.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "### Consolidations");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "| Group | Winner | Fields |");
+            let _ = writeln!(out, "|---|---|---|");
+            for (group, consolidation) in consolidated {
+                let fields: Vec<String> = consolidation
+                    .fields
+                    .iter()
+                    .filter(|f| f.applied)
+                    .map(|f| match &f.source_asset_id {
+                        Some(source) => format!("{} (from {})", f.field, source),
+                        None => f.field.to_string(),
+                    })
+                    .collect();
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    markdown_cell(&group.duplicate_id),
+                    markdown_cell(&group.winner_id),
+                    markdown_cell(&fields.join(", "))
+                );
+            }
+        }
+
+        let failures: Vec<(&str, &str, &str)> = self
+            .results
+            .iter()
+            .flat_map(|r| r.download_results.iter().chain(&r.delete_result).map(move |op| (r, op)))
+            .filter_map(|(r, op)| match op {
+                OperationResult::Failed { id, error } => Some((r.duplicate_id.as_str(), id.as_str(), error.as_str())),
+                _ => None,
+            })
+            .collect();
+        let run_errors: Vec<(&str, &String)> = [
+            ("Event history", &self.history_error),
+            ("Backup archive", &self.archive_error),
+            ("Winner tagging", &self.tagging_error),
+            ("Notification", &self.notification_error),
+        ]
+        .into_iter()
+        .filter_map(|(what, error)| error.as_ref().map(|e| (what, e)))
+        .collect();
+
+        if !failures.is_empty() || !run_errors.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "### Failures");
+            let _ = writeln!(out);
+        }
+        if !failures.is_empty() {
+            let _ = writeln!(out, "| Group | Asset | Reason |");
+            let _ = writeln!(out, "|---|---|---|");
+            for &(group_id, id, error) in &failures {
+                // A failed delete step is reported against the group itself
+                let asset = if id == group_id { "(delete)" } else { id };
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    markdown_cell(group_id),
+                    markdown_cell(asset),
+                    markdown_cell(error)
+                );
+            }
+        }
+        if !run_errors.is_empty() {
+            if !failures.is_empty() {
+                let _ = writeln!(out);
+            }
+            for (what, error) in run_errors {
+                let _ = writeln!(out, "- **{}:** {}", what, error.replace('\n', " "));
+            }
+        }
+        out
+    }
+}
+
+/// Text made safe for a Markdown table cell.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
 }
 
 impl Default for ExecutionReport {
@@ -184,3 +1395,146 @@ impl Default for ExecutionReport {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_state_round_trips_old_report_strings() {
+        for state in [
+            AssetState::Present,
+            AssetState::Trashed,
+            AssetState::Deleted,
+            AssetState::Error,
+        ] {
+            let json = serde_json::to_string(&state).unwrap();
+            assert_eq!(json, format!("\"{}\"", state));
+            assert_eq!(serde_json::from_str::<AssetState>(&json).unwrap(), state);
+        }
+
+        let old: AssetState = serde_json::from_str("\"Trashed\"").unwrap();
+        assert_eq!(old, AssetState::Trashed);
+        assert!(serde_json::from_str::<AssetState>("\"gone\"").is_err());
+    }
+
+    #[test]
+    fn test_asset_state_from_lookup() {
+        let not_found: Result<AssetResponse> = Err(ImmichError::NotFound {
+            message: "Not found".to_string(),
+        });
+        assert_eq!(AssetState::from_lookup(&not_found), AssetState::Deleted);
+        assert!(AssetState::from_lookup(&not_found).is_removed());
+
+        let unavailable: Result<AssetResponse> = Err(ImmichError::Api {
+            status: 503,
+            message: "Unavailable".to_string(),
+        });
+        assert_eq!(AssetState::from_lookup(&unavailable), AssetState::Error);
+    }
+
+    #[test]
+    fn test_description_policies() {
+        let losers = ["Beach, 2019", "  ", "Sunset at the beach"];
+        assert_eq!(DescriptionPolicy::Fill.merge(None, &losers).as_deref(), Some("Beach, 2019"));
+        assert_eq!(DescriptionPolicy::Fill.merge(Some("Beach"), &losers), None);
+        assert_eq!(DescriptionPolicy::Review.merge(Some("Beach"), &losers), None);
+
+        // Text the winner already has isn't appended twice
+        assert_eq!(
+            DescriptionPolicy::Append.merge(Some("Beach, 2019"), &losers).as_deref(),
+            Some("Beach, 2019\n\nSunset at the beach")
+        );
+        assert_eq!(DescriptionPolicy::Append.merge(Some("Beach, 2019"), &losers[..2]), None);
+
+        assert_eq!(
+            DescriptionPolicy::Longest.merge(Some("Beach"), &losers).as_deref(),
+            Some("Sunset at the beach")
+        );
+        assert_eq!(DescriptionPolicy::Longest.merge(Some("Sunset at the beach!"), &losers), None);
+        assert_eq!(DescriptionPolicy::Longest.merge(Some("x"), &[]), None);
+
+        assert_eq!("Longest".parse::<DescriptionPolicy>().unwrap(), DescriptionPolicy::Longest);
+        assert!("newest".parse::<DescriptionPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_legacy_consolidation_flags_read_as_fields() {
+        let json = include_str!("../../tests/fixtures/reports/legacy-execution-report.json");
+        let report: ExecutionReport = serde_json::from_str(json).unwrap();
+
+        let first = report.results[0].consolidation_result.as_ref().unwrap();
+        assert!(first.transferred(MetadataField::Gps) && first.transferred(MetadataField::Datetime));
+        assert!(!first.transferred(MetadataField::Description));
+        assert_eq!(first.field(MetadataField::Gps).unwrap().source_asset_id.as_deref(), Some("asset-l1"));
+        assert_eq!(first.field(MetadataField::Datetime).unwrap().source_asset_id.as_deref(), Some("asset-l2"));
+
+        // Without per-field sources, the single source applies to every field
+        let second = report.results[1].consolidation_result.as_ref().unwrap();
+        assert_eq!(second.fields.len(), 1);
+        assert_eq!(second.fields[0].field, MetadataField::Description);
+        assert_eq!(second.fields[0].source_asset_id.as_deref(), Some("asset-l3"));
+
+        // The current form round-trips unchanged
+        let reread: ConsolidationResult = serde_json::from_str(&serde_json::to_string(first).unwrap()).unwrap();
+        assert_eq!(reread.fields, first.fields);
+    }
+
+    #[test]
+    fn test_report_markdown_summary() {
+        let mut report = ExecutionReport::new();
+        report.add_group_result(GroupResult {
+            duplicate_id: "dup-1".to_string(),
+            winner_id: "w1".to_string(),
+            consolidation_result: Some(ConsolidationResult {
+                fields: vec![FieldTransfer {
+                    field: MetadataField::Gps,
+                    value: "51.5,-0.1".to_string(),
+                    source_asset_id: Some("l1".to_string()),
+                    applied: true,
+                    discrepancy: None,
+                }],
+                retried: false,
+                bulk: false,
+            }),
+            download_results: vec![OperationResult::Success { id: "l1".to_string(), path: None }],
+            delete_result: Some(OperationResult::Success { id: "dup-1".to_string(), path: None }),
+            deleted_ids: vec!["l1".to_string()],
+            rolled_back_ids: Vec::new(),
+            reclaimed_bytes: 2048,
+            trashed_bytes: 0,
+            promotions: Vec::new(),
+            decision: None,
+        });
+        report.add_group_result(GroupResult {
+            duplicate_id: "dup-2".to_string(),
+            winner_id: "w2".to_string(),
+            consolidation_result: None,
+            download_results: vec![OperationResult::Failed {
+                id: "l2".to_string(),
+                error: "HTTP 500 | upstream".to_string(),
+            }],
+            delete_result: Some(OperationResult::Failed {
+                id: "dup-2".to_string(),
+                error: "Not deleted".to_string(),
+            }),
+            deleted_ids: Vec::new(),
+            rolled_back_ids: Vec::new(),
+            reclaimed_bytes: 0,
+            trashed_bytes: 0,
+            promotions: Vec::new(),
+            decision: None,
+        });
+        report.notification_error = Some("webhook refused".to_string());
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| Deleted | 1 |"));
+        assert!(markdown.contains("| Space reclaimed | 2.0 KiB |"));
+        assert!(markdown.contains("| Metadata consolidated | 1 |"));
+        assert!(markdown.contains("| dup-1 | w1 | gps (from l1) |"));
+        assert!(markdown.contains("| dup-2 | l2 | HTTP 500 \\| upstream |"));
+        assert!(markdown.contains("| dup-2 | (delete) | Not deleted |"));
+        assert!(markdown.contains("- **Notification:** webhook refused"));
+        assert_eq!("md".parse::<ReportFormat>().unwrap(), ReportFormat::Markdown);
+    }
+}
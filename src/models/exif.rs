@@ -1,6 +1,7 @@
 //! EXIF metadata response types.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// EXIF metadata for an asset.
 ///
@@ -76,6 +77,14 @@ pub struct ExifInfo {
     /// Projection type for 360 photos
     #[serde(default)]
     pub projection_type: Option<String>,
+
+    /// Video frame rate in frames per second
+    #[serde(default)]
+    pub fps: Option<f64>,
+
+    /// Fields this version of the models doesn't know, kept as sent
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl ExifInfo {
@@ -108,4 +117,32 @@ impl ExifInfo {
     pub fn has_location(&self) -> bool {
         self.city.is_some() || self.country.is_some()
     }
+
+    /// Returns true if the orientation rotates the image by 90° or 270°.
+    ///
+    /// Accepts both the numeric EXIF value (5–8 are quarter turns) and
+    /// exiftool-style text such as "Rotate 90 CW".
+    pub fn is_quarter_turn(&self) -> bool {
+        let Some(orientation) = self.orientation.as_deref().map(str::trim) else {
+            return false;
+        };
+        match orientation.parse::<u8>() {
+            Ok(value) => (5..=8).contains(&value),
+            Err(_) => orientation.contains("90") || orientation.contains("270"),
+        }
+    }
+
+    /// Image dimensions (width, height) as displayed, after applying orientation.
+    ///
+    /// `exif_image_width`/`exif_image_height` are the stored pixel dimensions;
+    /// a rotated copy of the same photo can report them swapped. Comparing
+    /// display dimensions makes such copies match.
+    pub fn display_dimensions(&self) -> Option<(u32, u32)> {
+        let (width, height) = (self.exif_image_width?, self.exif_image_height?);
+        if self.is_quarter_turn() {
+            Some((height, width))
+        } else {
+            Some((width, height))
+        }
+    }
 }
@@ -0,0 +1,26 @@
+//! Memory response types.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::AssetRef;
+
+/// A memory, such as "On this day" (`GET /api/memories`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryResponse {
+    /// Memory ID
+    pub id: String,
+
+    /// Kind of memory (e.g. `on_this_day`)
+    #[serde(rename = "type", default)]
+    pub memory_type: String,
+
+    /// When the memory is shown
+    #[serde(default)]
+    pub memory_at: Option<DateTime<Utc>>,
+
+    /// Assets in the memory
+    #[serde(default)]
+    pub assets: Vec<AssetRef>,
+}
@@ -2,14 +2,38 @@
 //!
 //! These types map to the Immich API response DTOs.
 
+mod album;
 mod asset;
 mod duplicate;
 mod exif;
+#[cfg(feature = "native")]
 mod execution;
+mod memory;
+mod person;
+mod server;
+mod stack;
+mod tag;
 
+pub use album::{
+    AlbumResponse, AlbumUser, AlbumUserRole, AssetRef, BulkIdError, BulkIdResponse, SharedLinkIndex, SharedLinkResponse,
+    SharedLinkType, UserRef,
+};
 pub use asset::{AssetResponse, AssetType};
 pub use duplicate::DuplicateGroup;
 pub use exif::ExifInfo;
+pub use memory::MemoryResponse;
+pub use person::PersonResponse;
+pub use server::{
+    ApiKeyInfo, AssetStatistics, JobCounts, JobStatus, QueueStatus, ServerStatistics, ServerVersion, UserInfo,
+    UserUsage,
+};
+pub use stack::{AssetStack, StackResponse};
+pub use tag::TagResponse;
+#[cfg(feature = "native")]
 pub use execution::{
-    ConsolidationResult, ExecutionConfig, ExecutionReport, GroupResult, OperationResult,
+    AssetState, BackupSink, BackupTarget, ConsolidationResult, CrossLibraryPolicy, DescriptionPolicy,
+    ExecutionConfig, ExecutionMode, ExecutionPlan, ExecutionReport, FieldTransfer, GroupPlan, GroupResult,
+    MetadataField, MetadataSources, OperationResult, PlannedOperation, Promotion, PromotionKind, ReportFormat, SharedAlbumPolicy,
+    SharedLinkPolicy,
+    DESCRIPTION_SEPARATOR, STALE_ANALYSIS,
 };
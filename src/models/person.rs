@@ -0,0 +1,33 @@
+//! Person (face recognition) response types.
+
+use serde::{Deserialize, Serialize};
+
+/// A person recognized by face detection (`GET /api/people`), or one
+/// recognized in an asset.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonResponse {
+    /// Person ID
+    pub id: String,
+
+    /// Name given to the person (empty until named)
+    #[serde(default)]
+    pub name: String,
+
+    /// Birth date (`YYYY-MM-DD`)
+    #[serde(default)]
+    pub birth_date: Option<String>,
+
+    /// Server path of the face thumbnail
+    #[serde(default)]
+    pub thumbnail_path: String,
+
+    /// Whether the person is hidden from the people list
+    #[serde(default)]
+    pub is_hidden: bool,
+
+    /// Whether the person is marked as favorite
+    #[serde(default)]
+    pub is_favorite: bool,
+}
+
@@ -0,0 +1,181 @@
+//! Server, user, and job status response types.
+
+use serde::{Deserialize, Serialize};
+
+/// Immich server version (`GET /api/server/version`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct ServerVersion {
+    /// Major version
+    pub major: u32,
+
+    /// Minor version
+    pub minor: u32,
+
+    /// Patch version
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    /// Create a version from its components.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+}
+
+impl std::fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The user that owns the API key (`GET /api/users/me`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserInfo {
+    /// User ID
+    pub id: String,
+
+    /// Email address
+    pub email: String,
+
+    /// Display name
+    #[serde(default)]
+    pub name: String,
+
+    /// Whether the user is a server administrator
+    #[serde(default)]
+    pub is_admin: bool,
+}
+
+/// The API key used for the request (`GET /api/api-keys/me`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyInfo {
+    /// API key ID
+    pub id: String,
+
+    /// Name given to the key
+    #[serde(default)]
+    pub name: String,
+
+    /// Granted permissions (`all` grants everything)
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// Job counts for one queue.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobCounts {
+    /// Jobs currently running
+    #[serde(default)]
+    pub active: u64,
+
+    /// Jobs completed since the server started
+    #[serde(default)]
+    pub completed: u64,
+
+    /// Jobs that failed
+    #[serde(default)]
+    pub failed: u64,
+
+    /// Jobs delayed for retry
+    #[serde(default)]
+    pub delayed: u64,
+
+    /// Jobs waiting to run
+    #[serde(default)]
+    pub waiting: u64,
+
+    /// Jobs waiting in a paused queue
+    #[serde(default)]
+    pub paused: u64,
+}
+
+/// Whether a queue is running or paused.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueStatus {
+    /// Whether the queue is processing jobs
+    #[serde(default)]
+    pub is_active: bool,
+
+    /// Whether the queue has been paused
+    #[serde(default)]
+    pub is_paused: bool,
+}
+
+/// Status of one job queue (an entry in `GET /api/jobs`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    /// Job counts
+    #[serde(default)]
+    pub job_counts: JobCounts,
+
+    /// Queue state
+    #[serde(default)]
+    pub queue_status: QueueStatus,
+}
+
+/// Asset counts (`GET /api/assets/statistics`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AssetStatistics {
+    /// Number of images
+    #[serde(default)]
+    pub images: u64,
+
+    /// Number of videos
+    #[serde(default)]
+    pub videos: u64,
+
+    /// Total number of assets
+    #[serde(default)]
+    pub total: u64,
+}
+
+/// Storage used by one user (an entry in [`ServerStatistics::usage_by_user`]).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserUsage {
+    /// User ID
+    #[serde(default)]
+    pub user_id: String,
+
+    /// Display name
+    #[serde(default)]
+    pub user_name: String,
+
+    /// Number of images
+    #[serde(default)]
+    pub photos: u64,
+
+    /// Number of videos
+    #[serde(default)]
+    pub videos: u64,
+
+    /// Bytes used
+    #[serde(default)]
+    pub usage: u64,
+}
+
+/// Server-wide asset counts and storage (`GET /api/server/statistics`, admin only).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatistics {
+    /// Number of images
+    #[serde(default)]
+    pub photos: u64,
+
+    /// Number of videos
+    #[serde(default)]
+    pub videos: u64,
+
+    /// Bytes used by all users
+    #[serde(default)]
+    pub usage: u64,
+
+    /// Breakdown per user
+    #[serde(default)]
+    pub usage_by_user: Vec<UserUsage>,
+}
@@ -0,0 +1,36 @@
+//! Stack response types.
+
+use serde::{Deserialize, Serialize};
+
+use super::asset::AssetResponse;
+
+/// A stack of assets shown as one, such as a burst or RAW+JPEG pair
+/// (`GET /api/stacks`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackResponse {
+    /// Stack ID
+    pub id: String,
+
+    /// Asset shown for the stack
+    pub primary_asset_id: String,
+
+    /// Assets in the stack, the primary asset included
+    #[serde(default)]
+    pub assets: Vec<AssetResponse>,
+}
+
+/// The stack an asset belongs to, as summarized in an asset response.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetStack {
+    /// Stack ID
+    pub id: String,
+
+    /// Asset shown for the stack
+    pub primary_asset_id: String,
+
+    /// Number of assets in the stack
+    #[serde(default)]
+    pub asset_count: u64,
+}
@@ -0,0 +1,26 @@
+//! Tag response types.
+
+use serde::{Deserialize, Serialize};
+
+/// A tag (`GET /api/tags`), or one attached to an asset.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagResponse {
+    /// Tag ID
+    pub id: String,
+
+    /// Tag name (last path component)
+    pub name: String,
+
+    /// Full hierarchical value (e.g. `Frames/Kitchen`)
+    #[serde(default)]
+    pub value: String,
+
+    /// Parent tag, for nested tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+
+    /// Display color (e.g. `#ff0000`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
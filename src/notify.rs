@@ -0,0 +1,288 @@
+//! Webhook notifications when an execution run finishes.
+//!
+//! Runs started from cron have nobody watching them. With a
+//! [`NotificationConfig`] in the [`ExecutionConfig`](crate::models::ExecutionConfig),
+//! the executor POSTs a summary of the [`ExecutionReport`] to a webhook when
+//! the run completes.
+//!
+//! The JSON payload carries the rendered message as both `text` (Slack,
+//! Mattermost) and `content` (Discord), plus the counts as `summary`. The
+//! text format posts the message alone as `text/plain`, which is what ntfy
+//! and most generic endpoints expect.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ImmichError, Result};
+use crate::models::{ExecutionReport, OperationResult};
+use crate::support::REDACTED;
+
+/// Message used when no template is configured.
+pub const DEFAULT_TEMPLATE: &str =
+    "immich-dupes: {groups} groups processed, {deleted} deleted, {failed} failed, {anomalies} anomalies";
+
+/// Anomalies listed in the `{anomaly_list}` placeholder (the rest are counted).
+const MAX_LISTED_ANOMALIES: usize = 5;
+
+/// How the notification body is encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationFormat {
+    /// JSON with `text`, `content`, and `summary` fields (Slack, Discord)
+    #[default]
+    Json,
+    /// The rendered message as plain text (ntfy, generic endpoints)
+    Text,
+}
+
+impl std::str::FromStr for NotificationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            other => Err(format!("Unknown notification format '{}' (expected json or text)", other)),
+        }
+    }
+}
+
+/// Where and how to send the completion notification.
+///
+/// ```toml
+/// [notification]
+/// url = "https://ntfy.sh/my-immich-runs"
+/// format = "text"
+/// template = "{deleted} duplicates removed, {failed} failures"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// Webhook URL to POST to
+    pub url: String,
+
+    /// Message template (default: [`DEFAULT_TEMPLATE`])
+    ///
+    /// Placeholders: `{groups}`, `{downloaded}`, `{deleted}`, `{failed}`,
+    /// `{skipped}`, `{anomalies}` (count), and `{anomaly_list}` (the first
+    /// few, one per line).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+
+    /// Body encoding
+    #[serde(default)]
+    pub format: NotificationFormat,
+}
+
+impl NotificationConfig {
+    /// Notify `url` with the default template and JSON body.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            template: None,
+            format: NotificationFormat::default(),
+        }
+    }
+
+    /// The webhook URL with everything after the host redacted, for display.
+    /// The path and query of a webhook URL are usually its secret.
+    pub fn redacted_url(&self) -> String {
+        match url::Url::parse(&self.url) {
+            Ok(url) => match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{}://{}:{}/{}", url.scheme(), host, port, REDACTED),
+                (Some(host), None) => format!("{}://{}/{}", url.scheme(), host, REDACTED),
+                (None, _) => REDACTED.to_string(),
+            },
+            Err(_) => REDACTED.to_string(),
+        }
+    }
+
+    /// Render the message for `summary`.
+    pub fn render(&self, summary: &RunSummary) -> String {
+        let mut listed: Vec<String> = summary.anomalies.iter().take(MAX_LISTED_ANOMALIES).cloned().collect();
+        if summary.anomalies.len() > MAX_LISTED_ANOMALIES {
+            listed.push(format!("... and {} more", summary.anomalies.len() - MAX_LISTED_ANOMALIES));
+        }
+
+        self.template
+            .as_deref()
+            .unwrap_or(DEFAULT_TEMPLATE)
+            .replace("{groups}", &summary.groups.to_string())
+            .replace("{downloaded}", &summary.downloaded.to_string())
+            .replace("{deleted}", &summary.deleted.to_string())
+            .replace("{failed}", &summary.failed.to_string())
+            .replace("{skipped}", &summary.skipped.to_string())
+            .replace("{anomalies}", &summary.anomalies.len().to_string())
+            .replace("{anomaly_list}", &listed.join("\n"))
+    }
+
+    /// POST the summary of `report` to the webhook.
+    ///
+    /// Uses its own HTTP client, so the Immich API key is never sent to the
+    /// webhook. Errors leave out the webhook URL.
+    pub async fn send(&self, report: &ExecutionReport) -> Result<()> {
+        let summary = RunSummary::from_report(report);
+        let message = self.render(&summary);
+
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+        let request = client.post(&self.url);
+        let request = match self.format {
+            NotificationFormat::Json => request.json(&serde_json::json!({
+                "text": message,
+                "content": message,
+                "summary": summary,
+            })),
+            NotificationFormat::Text => request
+                .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(message),
+        };
+
+        let response = request.send().await.map_err(reqwest::Error::without_url)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ImmichError::Notification(format!(
+                "webhook returned {}: {}",
+                status,
+                body.trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Counts and anomalies from an execution report, as sent in a notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    /// Groups processed
+    pub groups: usize,
+    /// Assets downloaded
+    pub downloaded: usize,
+    /// Assets deleted
+    pub deleted: usize,
+    /// Operations that failed
+    pub failed: usize,
+    /// Operations that were skipped
+    pub skipped: usize,
+    /// Failed operations and skipped deletions, one line each
+    pub anomalies: Vec<String>,
+}
+
+impl RunSummary {
+    /// Summarize an execution report.
+    pub fn from_report(report: &ExecutionReport) -> Self {
+        let mut anomalies = Vec::new();
+        for group in &report.results {
+            for result in &group.download_results {
+                if let OperationResult::Failed { id, error } = result {
+                    anomalies.push(format!("{}: {}", id, error));
+                }
+            }
+            match &group.delete_result {
+                Some(OperationResult::Failed { id, error }) => {
+                    anomalies.push(format!("group {}: {}", id, error));
+                }
+                Some(OperationResult::Skipped { id, reason }) => {
+                    anomalies.push(format!("group {} not deleted: {}", id, reason));
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            groups: report.total_groups,
+            downloaded: report.downloaded,
+            deleted: report.deleted,
+            failed: report.failed,
+            skipped: report.skipped,
+            anomalies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GroupResult;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    fn report() -> ExecutionReport {
+        let mut report = ExecutionReport::new();
+        report.add_group_result(GroupResult {
+            duplicate_id: "dup-1".to_string(),
+            winner_id: "w".to_string(),
+            consolidation_result: None,
+            download_results: vec![
+                OperationResult::Success { id: "a".to_string(), path: None },
+                OperationResult::Failed { id: "b".to_string(), error: "timeout".to_string() },
+            ],
+            delete_result: Some(OperationResult::Success { id: "dup-1".to_string(), path: None }),
+            deleted_ids: vec!["a".to_string()],
+            rolled_back_ids: Vec::new(),
+            reclaimed_bytes: 0,
+            trashed_bytes: 0,
+            promotions: Vec::new(),
+            decision: None,
+        });
+        report
+    }
+
+    #[test]
+    fn test_render_default_and_custom_templates() {
+        let summary = RunSummary::from_report(&report());
+        assert_eq!(summary.anomalies, vec!["b: timeout".to_string()]);
+
+        let config = NotificationConfig::new("http://example.invalid/hook");
+        assert_eq!(
+            config.render(&summary),
+            "immich-dupes: 1 groups processed, 1 deleted, 1 failed, 1 anomalies"
+        );
+
+        let config = NotificationConfig {
+            template: Some("{deleted}/{downloaded} deleted\n{anomaly_list}".to_string()),
+            ..config
+        };
+        assert_eq!(config.render(&summary), "1/1 deleted\nb: timeout");
+    }
+
+    #[test]
+    fn test_redacted_url_keeps_only_the_host() {
+        let config = NotificationConfig::new("https://hooks.slack.com/services/T000/B000/secret");
+        assert_eq!(config.redacted_url(), "https://hooks.slack.com/<redacted>");
+        let config = NotificationConfig::new("http://localhost:8080/hook?token=secret");
+        assert_eq!(config.redacted_url(), "http://localhost:8080/<redacted>");
+        assert_eq!(NotificationConfig::new("not a url").redacted_url(), "<redacted>");
+    }
+
+    #[tokio::test]
+    async fn test_send_posts_to_webhook() {
+        let server = MockServer::start(&synthetic_groups(1, 2).unwrap()).await.unwrap();
+
+        // JSON carries the message and the counts
+        NotificationConfig::new(server.webhook_url()).send(&report()).await.unwrap();
+        // Text is the message alone
+        let config = NotificationConfig {
+            template: Some("{deleted} deleted".to_string()),
+            format: NotificationFormat::Text,
+            ..NotificationConfig::new(server.webhook_url())
+        };
+        config.send(&report()).await.unwrap();
+
+        let bodies = server.webhook_bodies();
+        assert_eq!(bodies.len(), 2);
+        let json: serde_json::Value = serde_json::from_slice(&bodies[0]).unwrap();
+        assert_eq!(json["text"], json["content"]);
+        assert_eq!(json["summary"]["deleted"], 1);
+        assert_eq!(bodies[1], b"1 deleted");
+
+        // A refused notification is an error that leaves out the URL
+        let refused = NotificationConfig::new(format!("{}/not-a-hook/secret", server.url()));
+        let error = refused.send(&report()).await.unwrap_err().to_string();
+        assert!(error.contains("404"), "{}", error);
+        assert!(!error.contains("secret"), "{}", error);
+
+        let unreachable = NotificationConfig::new("http://127.0.0.1:1/hook/secret");
+        let error = unreachable.send(&report()).await.unwrap_err().to_string();
+        assert!(!error.contains("secret"), "{}", error);
+    }
+}
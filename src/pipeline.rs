@@ -0,0 +1,381 @@
+//! One-shot analyze, plan, execute, and verify, resumable across runs.
+//!
+//! A [`Pipeline`] keeps its state in a run directory: the plan (the groups
+//! to execute and their risk report), how far the run got, and which groups
+//! have been executed. The state is saved after every step and every group,
+//! so a run that is interrupted, whether at the confirmation prompt or
+//! halfway through deleting, picks up where it stopped when reopened with
+//! [`Pipeline::resume`], without re-analyzing or re-executing anything.
+//!
+//! The steps are:
+//!
+//! 1. [`Pipeline::plan`] fetches and analyzes the duplicate groups and
+//!    assesses the risk of executing them.
+//! 2. [`Pipeline::confirm`] checks the caller's confirmation, which must be
+//!    the number of assets about to be deleted.
+//! 3. [`Pipeline::execute`] executes the groups not yet executed.
+//! 4. [`Pipeline::verify`] checks each executed group's winner survived and
+//!    its deleted losers are gone.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::client::ImmichClient;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+use crate::executor::Executor;
+use crate::models::{AssetState, DuplicateGroup, ExecutionReport};
+use crate::report_io;
+use crate::risk::{self, RiskOptions, RiskReport};
+use crate::scoring::{DuplicateAnalysis, Thresholds};
+use crate::tagging;
+
+/// File in the run directory holding the run's state.
+pub const STATE_FILE: &str = "run.json";
+
+/// How far a run has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStage {
+    /// Analyzed and planned; waiting for confirmation
+    Planned,
+    /// Confirmed; execution has not finished
+    Confirmed,
+    /// Every group executed; not yet verified
+    Executed,
+    /// Verified; the run is finished
+    Verified,
+}
+
+impl RunStage {
+    /// Stable identifier, as used in the state file.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Planned => "planned",
+            Self::Confirmed => "confirmed",
+            Self::Executed => "executed",
+            Self::Verified => "verified",
+        }
+    }
+}
+
+impl std::fmt::Display for RunStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options for planning a run.
+#[derive(Debug, Clone, Default)]
+pub struct PlanOptions {
+    /// Detection thresholds
+    pub thresholds: Thresholds,
+
+    /// Limits for the risk report
+    pub risk: RiskOptions,
+
+    /// Only plan groups with an asset carrying this tag (by full value, name, or ID)
+    pub tag: Option<String>,
+}
+
+/// Persistent state of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunState {
+    /// When the run was planned
+    pub started_at: DateTime<Utc>,
+
+    /// Immich server the run is against
+    pub server_url: String,
+
+    /// How far the run has got
+    pub stage: RunStage,
+
+    /// Groups needing review, which the run leaves alone
+    pub held_for_review: usize,
+
+    /// Groups to execute
+    pub groups: Vec<DuplicateAnalysis>,
+
+    /// Risk of executing `groups`
+    pub risk: RiskReport,
+
+    /// Executed groups, with the losers each one deleted
+    #[serde(default)]
+    pub completed: BTreeMap<String, Vec<String>>,
+
+    /// Outcome of verification, once run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<RunVerification>,
+}
+
+/// Outcome of verifying the executed groups.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunVerification {
+    /// Groups checked
+    pub groups_checked: usize,
+
+    /// Winners that are no longer present
+    pub winners_missing: Vec<String>,
+
+    /// Deleted losers that are still present
+    pub losers_present: Vec<String>,
+
+    /// Assets that could not be checked, with the error
+    pub errors: Vec<String>,
+}
+
+impl RunVerification {
+    /// Returns true if every check passed.
+    pub fn is_clean(&self) -> bool {
+        self.winners_missing.is_empty() && self.losers_present.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// A resumable analyze-plan-execute-verify run.
+#[derive(Debug)]
+pub struct Pipeline {
+    dir: PathBuf,
+    state: RunState,
+}
+
+impl Pipeline {
+    /// Analyze the server's duplicates and plan a new run in `dir`.
+    ///
+    /// Groups needing review (and groups a reviewer skipped) are left out of
+    /// the plan. Any earlier run in `dir` is replaced.
+    pub async fn plan(
+        client: &ImmichClient,
+        server_url: &str,
+        dir: impl Into<PathBuf>,
+        options: &PlanOptions,
+    ) -> Result<Self> {
+        Self::plan_with_clock(client, server_url, dir, options, &SystemClock).await
+    }
+
+    /// Like [`plan`](Self::plan), stamping the run's start with the time
+    /// from `clock`.
+    pub(crate) async fn plan_with_clock(
+        client: &ImmichClient,
+        server_url: &str,
+        dir: impl Into<PathBuf>,
+        options: &PlanOptions,
+        clock: &dyn Clock,
+    ) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut duplicates = client.get_duplicates().await?;
+        if let Some(tag) = &options.tag {
+            duplicates = tagging::scope_to_tag(client, duplicates, tag).await?;
+        }
+        DuplicateGroup::hydrate_all(&mut duplicates, client).await?;
+        let mut analyses = DuplicateAnalysis::from_groups_with_options(&duplicates, &options.thresholds);
+        if let Ok(memories) = client.get_memories().await {
+            for analysis in &mut analyses {
+                analysis.flag_memories(&memories);
+            }
+        }
+        let (groups, held): (Vec<_>, Vec<_>) = analyses
+            .into_iter()
+            .partition(|g| g.should_execute(true));
+
+        // Shared links are best effort: the key may lack permission to list them
+        let shared_ids = risk::shared_asset_ids(client).await.unwrap_or_default();
+        let risk = RiskReport::assess(&groups, &shared_ids, &options.risk);
+
+        let pipeline = Self {
+            dir,
+            state: RunState {
+                started_at: clock.now(),
+                server_url: server_url.to_string(),
+                stage: RunStage::Planned,
+                held_for_review: held.len(),
+                groups,
+                risk,
+                completed: BTreeMap::new(),
+                verification: None,
+            },
+        };
+        pipeline.save()?;
+        Ok(pipeline)
+    }
+
+    /// Reopen the run in `dir`, if there is one.
+    pub fn resume(dir: impl Into<PathBuf>) -> Result<Option<Self>> {
+        let dir = dir.into();
+        let path = dir.join(STATE_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let state = report_io::read_json(&path)?;
+        Ok(Some(Self { dir, state }))
+    }
+
+    /// The run directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The run's state.
+    pub fn state(&self) -> &RunState {
+        &self.state
+    }
+
+    /// Groups not yet executed.
+    pub fn remaining(&self) -> Vec<DuplicateAnalysis> {
+        self.state
+            .groups
+            .iter()
+            .filter(|g| !self.state.completed.contains_key(g.duplicate_id.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Assets the remaining groups will delete.
+    pub fn assets_to_delete(&self) -> usize {
+        self.remaining().iter().map(|g| g.losers.len()).sum()
+    }
+
+    /// Confirm the plan with `typed`, which must be the number of assets to
+    /// delete.
+    ///
+    /// Returns false (and leaves the run planned) if it isn't.
+    pub fn confirm(&mut self, typed: &str) -> Result<bool> {
+        if self.state.stage >= RunStage::Confirmed {
+            return Ok(true);
+        }
+        if typed.trim() != self.assets_to_delete().to_string() {
+            return Ok(false);
+        }
+        self.state.stage = RunStage::Confirmed;
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Execute the groups not yet executed, saving progress after each one.
+    ///
+    /// The report covers only the groups executed by this call.
+    pub async fn execute(&mut self, executor: &Executor) -> Result<ExecutionReport> {
+        if self.state.stage < RunStage::Confirmed {
+            return Err(ImmichError::NotConfirmed);
+        }
+
+        let remaining = self.remaining();
+        let mut checkpoint_error = None;
+        let report = executor
+            .execute_all_with(&remaining, |result| {
+                self.state.completed.insert(result.duplicate_id.clone(), result.deleted_ids.clone());
+                if let Err(e) = self.save()
+                    && checkpoint_error.is_none()
+                {
+                    checkpoint_error = Some(e);
+                }
+            })
+            .await;
+
+        if let Some(e) = checkpoint_error {
+            return Err(e);
+        }
+        self.state.stage = self.state.stage.max(RunStage::Executed);
+        self.save()?;
+        Ok(report)
+    }
+
+    /// Check every executed group: the winner is still present and the
+    /// losers it deleted are trashed or gone.
+    pub async fn verify(&mut self, client: &ImmichClient) -> Result<RunVerification> {
+        let mut verification = RunVerification::default();
+
+        for group in &self.state.groups {
+            let Some(deleted) = self.state.completed.get(group.duplicate_id.as_str()) else {
+                continue;
+            };
+            verification.groups_checked += 1;
+
+            let winner = group.winner.asset_id.as_str();
+            let lookup = client.get_asset(winner).await;
+            match (AssetState::from_lookup(&lookup), lookup) {
+                (AssetState::Present, _) => {}
+                (AssetState::Error, Err(e)) => verification.errors.push(format!("{}: {}", winner, e)),
+                _ => verification.winners_missing.push(winner.to_string()),
+            }
+
+            for loser in deleted {
+                let lookup = client.get_asset(loser).await;
+                match (AssetState::from_lookup(&lookup), lookup) {
+                    (AssetState::Present, _) => verification.losers_present.push(loser.clone()),
+                    (AssetState::Error, Err(e)) => verification.errors.push(format!("{}: {}", loser, e)),
+                    _ => {}
+                }
+            }
+        }
+
+        if self.state.stage >= RunStage::Executed {
+            self.state.stage = RunStage::Verified;
+        }
+        self.state.verification = Some(verification.clone());
+        self.save()?;
+        Ok(verification)
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = self.dir.join(STATE_FILE);
+        let partial = path.with_extension("partial");
+        report_io::write_json(&partial, &self.state)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ExecutionConfig;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[tokio::test]
+    async fn test_run_resumes_and_verifies() {
+        let server = MockServer::start(&synthetic_groups(3, 3).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut pipeline = Pipeline::plan(&client, &server.url(), dir.path(), &PlanOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(pipeline.assets_to_delete(), 6);
+        assert!(!pipeline.confirm("yes").unwrap());
+
+        // Not confirmed, so reopening finds the plan waiting
+        let mut pipeline = Pipeline::resume(dir.path()).unwrap().unwrap();
+        assert_eq!(pipeline.state().stage, RunStage::Planned);
+        assert!(pipeline.confirm(" 6\n").unwrap());
+
+        // Pretend the first group finished before an interruption
+        let first = pipeline.state.groups[0].duplicate_id.to_string();
+        pipeline.state.completed.insert(first, Vec::new());
+        pipeline.save().unwrap();
+
+        let mut pipeline = Pipeline::resume(dir.path()).unwrap().unwrap();
+        assert_eq!(pipeline.state().stage, RunStage::Confirmed);
+        assert_eq!(pipeline.remaining().len(), 2);
+
+        let config = ExecutionConfig {
+            backup_dir: dir.path().join("backups"),
+            ..Default::default()
+        };
+        let executor = Executor::new(ImmichClient::new(&server.url(), "test-key").unwrap(), config);
+        let report = pipeline.execute(&executor).await.unwrap();
+        assert_eq!(report.total_groups, 2);
+        assert_eq!(server.deleted_assets().len(), 4);
+        assert!(pipeline.remaining().is_empty());
+
+        let verification = pipeline.verify(&client).await.unwrap();
+        assert!(verification.is_clean(), "{:?}", verification);
+        assert_eq!(verification.groups_checked, 3);
+        let pipeline = Pipeline::resume(dir.path()).unwrap().unwrap();
+        assert_eq!(pipeline.state().stage, RunStage::Verified);
+    }
+}
@@ -0,0 +1,199 @@
+//! Request rate limiting that adapts to the server.
+//!
+//! A fixed rate either leaves throughput on the table or trips the server's
+//! own limits. The [`AdaptiveRateLimiter`] starts at the configured rate,
+//! halves it whenever the server answers `429 Too Many Requests` (pausing
+//! for any `Retry-After` delay), and climbs back toward the configured rate
+//! after each second's worth of requests that succeed.
+
+use std::future::Future;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::error::{ImmichError, Result};
+
+/// Attempts at a request the server keeps rate limiting before giving up.
+pub const MAX_RATE_LIMIT_ATTEMPTS: u32 = 5;
+
+/// Lowest rate the limiter backs off to, in requests per second.
+const MIN_RATE: f64 = 0.5;
+
+/// Pause after a 429 without a `Retry-After` header.
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Fraction of the configured rate regained after each clean stretch.
+const RAMP_STEP: f64 = 0.1;
+
+/// How the rate changed over a run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitStats {
+    /// Rate the run was configured with, in requests per second
+    pub configured_rate: f64,
+
+    /// Rate in effect when the run finished
+    pub effective_rate: f64,
+
+    /// Lowest rate reached
+    pub min_rate: f64,
+
+    /// Number of `429 Too Many Requests` responses
+    pub throttled: u32,
+}
+
+/// A rate limiter that backs off on 429 responses and ramps up again.
+#[derive(Debug)]
+pub struct AdaptiveRateLimiter {
+    configured_rate: f64,
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    /// Current rate in requests per second
+    rate: f64,
+    /// Earliest time the next request may start
+    next_slot: Instant,
+    /// Successes since the rate last changed
+    successes: u32,
+    min_rate: f64,
+    throttled: u32,
+}
+
+impl AdaptiveRateLimiter {
+    /// Create a limiter allowing `requests_per_sec` to start with.
+    pub fn new(requests_per_sec: u32) -> Self {
+        let rate = f64::from(requests_per_sec.max(1));
+        Self {
+            configured_rate: rate,
+            state: Mutex::new(LimiterState {
+                rate,
+                next_slot: Instant::now(),
+                successes: 0,
+                min_rate: rate,
+                throttled: 0,
+            }),
+        }
+    }
+
+    /// Wait until the next request may start.
+    pub async fn until_ready(&self) {
+        let slot = {
+            let mut state = self.state.lock();
+            let slot = state.next_slot.max(Instant::now());
+            state.next_slot = slot + Duration::from_secs_f64(1.0 / state.rate);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Record a request the server accepted.
+    ///
+    /// After a second's worth of consecutive successes at the current rate,
+    /// the rate steps back up toward the configured rate.
+    pub fn on_success(&self) {
+        let mut state = self.state.lock();
+        if state.rate >= self.configured_rate {
+            return;
+        }
+        state.successes += 1;
+        if f64::from(state.successes) >= state.rate {
+            state.rate = (state.rate + self.configured_rate * RAMP_STEP).min(self.configured_rate);
+            state.successes = 0;
+        }
+    }
+
+    /// Record a `429` response: halve the rate and pause new requests for
+    /// `retry_after` (one second if the server didn't say).
+    pub fn on_rate_limited(&self, retry_after: Option<Duration>) {
+        let mut state = self.state.lock();
+        state.rate = (state.rate / 2.0).max(MIN_RATE);
+        state.min_rate = state.min_rate.min(state.rate);
+        state.successes = 0;
+        state.throttled += 1;
+        let resume = Instant::now() + retry_after.unwrap_or(DEFAULT_BACKOFF);
+        state.next_slot = state.next_slot.max(resume);
+    }
+
+    /// Run a request at the limiter's pace.
+    ///
+    /// If the server answers 429, the limiter backs off and the request is
+    /// retried, up to [`MAX_RATE_LIMIT_ATTEMPTS`] attempts in all.
+    ///
+    /// # Errors
+    ///
+    /// Returns the request's error, or [`ImmichError::RateLimited`] if the
+    /// last attempt was rate limited too.
+    pub async fn run<F, Fut, T>(&self, op: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            self.until_ready().await;
+            match op().await {
+                Err(ImmichError::RateLimited { retry_after }) => {
+                    self.on_rate_limited(retry_after);
+                    if attempt >= MAX_RATE_LIMIT_ATTEMPTS {
+                        return Err(ImmichError::RateLimited { retry_after });
+                    }
+                    attempt += 1;
+                }
+                result => {
+                    if result.is_ok() {
+                        self.on_success();
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Rate changes so far.
+    pub fn stats(&self) -> RateLimitStats {
+        let state = self.state.lock();
+        RateLimitStats {
+            configured_rate: self.configured_rate,
+            effective_rate: state.rate,
+            min_rate: state.min_rate,
+            throttled: state.throttled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backs_off_and_ramps_up() {
+        let limiter = AdaptiveRateLimiter::new(10);
+
+        limiter.on_rate_limited(Some(Duration::from_millis(50)));
+        limiter.on_rate_limited(Some(Duration::ZERO));
+        let stats = limiter.stats();
+        assert_eq!(stats.effective_rate, 2.5);
+        assert_eq!(stats.min_rate, 2.5);
+        assert_eq!(stats.throttled, 2);
+
+        // The Retry-After pause holds back the next request
+        let started = Instant::now();
+        limiter.until_ready().await;
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        // Three successes at 2.5/s step the rate up by a tenth of 10/s
+        for _ in 0..3 {
+            limiter.on_success();
+        }
+        assert_eq!(limiter.stats().effective_rate, 3.5);
+
+        for _ in 0..100 {
+            limiter.on_success();
+        }
+        assert_eq!(limiter.stats().effective_rate, 10.0);
+        assert_eq!(limiter.stats().min_rate, 2.5);
+    }
+}
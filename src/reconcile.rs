@@ -0,0 +1,205 @@
+//! Backup reconciliation.
+//!
+//! Every deleted loser should have a backup, and every backup should be of
+//! an asset that is gone. [`reconcile_backups`] checks both ways for one
+//! backup directory: backups of assets still live on the server (a deletion
+//! that failed, or never ran), and deletions recorded in the directory's
+//! execution reports or the ledger that have no backup.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::client::ImmichClient;
+use crate::error::Result;
+use crate::ledger::{Ledger, LedgerAction, LedgerEntry};
+use crate::retention::{BackupFile, list_backups, reported_deletions};
+
+/// A recorded deletion with no backup in the backup directory.
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingBackup {
+    /// Asset that was deleted
+    pub asset_id: String,
+
+    /// Original filename, if the ledger recorded it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+
+    /// Where the ledger says the backup was written, if it recorded it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<PathBuf>,
+}
+
+/// Outcome of reconciling a backup directory with the server.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileReport {
+    /// Backups in the directory
+    pub backups: usize,
+
+    /// Deletions recorded for the directory
+    pub deletions: usize,
+
+    /// Backups whose asset still exists on the server, outside the trash
+    pub not_deleted: Vec<BackupFile>,
+
+    /// Recorded deletions without a backup
+    pub missing_backups: Vec<MissingBackup>,
+}
+
+impl ReconcileReport {
+    /// Returns true if the backups and deletions match up.
+    pub fn is_clean(&self) -> bool {
+        self.not_deleted.is_empty() && self.missing_backups.is_empty()
+    }
+}
+
+/// The latest ledger entry of each asset deleted from `server_url` whose
+/// backup was written to `dir`.
+fn ledger_entries_for(entries: Vec<LedgerEntry>, server_url: &str, dir: &Path) -> HashMap<String, LedgerEntry> {
+    let dir = dir.canonicalize().ok();
+    let in_dir = |path: &Path| path.parent().and_then(|p| p.canonicalize().ok()) == dir;
+
+    // The same server may be recorded with or without a trailing slash
+    let server_url = server_url.trim_end_matches('/');
+    let mut latest = HashMap::new();
+    for entry in entries {
+        let written_here = entry.backup_path.as_deref().is_some_and(in_dir);
+        if entry.server_url.trim_end_matches('/') == server_url && written_here {
+            latest.insert(entry.asset_id.clone(), entry);
+        }
+    }
+    latest
+}
+
+/// Reconcile the backups in `dir` with the deletions recorded for it and
+/// the server's assets.
+///
+/// Deletions are the assets listed as deleted by the execution reports in
+/// `dir`, and the ledger's trashed or deleted assets whose backup went to
+/// `dir` (an asset restored or whose delete failed since is not counted). A
+/// deletion counts as backed up if a backup of the asset is in `dir`, or the
+/// file the ledger recorded (such as a tar archive) still exists.
+///
+/// Every backup's asset is looked up on the server; one that exists and
+/// isn't trashed is reported as not deleted, unless the ledger says it was
+/// restored on purpose.
+///
+/// # Errors
+///
+/// Returns an error if the directory, its execution reports, or the ledger
+/// cannot be read, or the assets cannot be looked up.
+pub async fn reconcile_backups(client: &ImmichClient, dir: &Path, ledger: &Ledger) -> Result<ReconcileReport> {
+    let backups = list_backups(dir)?;
+    let ledger = ledger_entries_for(ledger.read_all()?, client.base_url(), dir);
+
+    // Asset ID -> ledger entry, if there is one; sorted for a stable report
+    let mut deletions: BTreeMap<String, Option<&LedgerEntry>> =
+        reported_deletions(dir)?.into_iter().map(|id| (id, None)).collect();
+    for (asset_id, entry) in &ledger {
+        match entry.action {
+            LedgerAction::Trashed | LedgerAction::Deleted => {
+                deletions.insert(asset_id.clone(), Some(entry));
+            }
+            LedgerAction::DeleteFailed | LedgerAction::Restored => {
+                deletions.remove(asset_id);
+            }
+        }
+    }
+
+    let backed_up: HashSet<&str> = backups.iter().flat_map(|b| &b.asset_ids).map(String::as_str).collect();
+    let missing_backups = deletions
+        .iter()
+        .filter(|(asset_id, entry)| {
+            let recorded_file_exists = entry.and_then(|e| e.backup_path.as_deref()).is_some_and(Path::exists);
+            !backed_up.contains(asset_id.as_str()) && !recorded_file_exists
+        })
+        .map(|(asset_id, entry)| MissingBackup {
+            asset_id: asset_id.clone(),
+            filename: entry.map(|e| e.filename.clone()),
+            backup_path: entry.and_then(|e| e.backup_path.clone()),
+        })
+        .collect();
+
+    let mut ids: Vec<String> = backed_up.iter().map(|id| id.to_string()).collect();
+    ids.sort();
+    let live: HashSet<String> = client
+        .get_assets(&ids)
+        .await?
+        .into_iter()
+        .filter(|asset| !asset.is_trashed)
+        .map(|asset| asset.id)
+        .collect();
+    let restored = |asset_id: &str| ledger.get(asset_id).is_some_and(|e| e.action == LedgerAction::Restored);
+    let not_deleted = backups
+        .iter()
+        .filter(|b| b.asset_ids.iter().any(|id| live.contains(id) && !restored(id)))
+        .cloned()
+        .collect();
+
+    Ok(ReconcileReport {
+        backups: backups.len(),
+        deletions: deletions.len(),
+        not_deleted,
+        missing_backups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    fn ledger_entry(asset_id: &str, action: LedgerAction, backup_path: PathBuf, server_url: &str) -> LedgerEntry {
+        LedgerEntry {
+            recorded_at: "2025-01-01T12:00:00Z".parse().unwrap(),
+            action,
+            asset_id: asset_id.to_string(),
+            checksum: None,
+            filename: "IMG.jpg".to_string(),
+            backup_path: Some(backup_path),
+            group_id: "group".to_string(),
+            winner_id: "winner".to_string(),
+            server_url: server_url.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_finds_drift() {
+        let groups = synthetic_groups(5, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let [deleted, failed, restored, unbacked, other_dir] = [0, 1, 2, 3, 4].map(|g| groups[g].assets[1].id.clone());
+        client.delete_assets(&[deleted.clone(), unbacked.clone()], false).await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        for id in [&deleted, &failed, &restored] {
+            std::fs::write(dir.path().join(format!("{}_IMG.jpg", id)), b"jpeg").unwrap();
+        }
+        let report = serde_json::json!({ "results": [{ "deleted_ids": [deleted] }] });
+        std::fs::write(dir.path().join("execution-report-20250101-000000.json"), report.to_string()).unwrap();
+
+        let url = client.base_url().to_string();
+        let here = |id: &str| dir.path().join(format!("{}_IMG.jpg", id));
+        let ledger = Ledger::new(dir.path().join("ledger.jsonl"));
+        ledger
+            .append(&[
+                ledger_entry(&failed, LedgerAction::Trashed, here(&failed), &url),
+                ledger_entry(&restored, LedgerAction::Trashed, here(&restored), &url),
+                ledger_entry(&restored, LedgerAction::Restored, here(&restored), &url),
+                // Recorded by a run given the URL with a trailing slash
+                ledger_entry(&unbacked, LedgerAction::Trashed, here(&unbacked), &format!("{}/", url)),
+                ledger_entry(&other_dir, LedgerAction::Trashed, PathBuf::from("/elsewhere/IMG.jpg"), &url),
+            ])
+            .unwrap();
+
+        let report = reconcile_backups(&client, dir.path(), &ledger).await.unwrap();
+        assert_eq!((report.backups, report.deletions), (3, 3));
+        let not_deleted: Vec<&str> = report.not_deleted.iter().map(|b| b.asset_ids[0].as_str()).collect();
+        assert_eq!(not_deleted, [failed.as_str()]);
+        assert_eq!(report.missing_backups.len(), 1);
+        assert_eq!(report.missing_backups[0].asset_id, unbacked);
+        assert_eq!(report.missing_backups[0].filename.as_deref(), Some("IMG.jpg"));
+        assert!(!report.is_clean());
+    }
+}
@@ -0,0 +1,128 @@
+//! Reading and writing JSON report files, optionally zstd-compressed.
+//!
+//! Analysis reports for large libraries can reach hundreds of MB. Paths
+//! ending in `.zst` (e.g. `analysis.json.zst`) are transparently compressed
+//! on write and decompressed on read. Both directions stream through
+//! serde, so the uncompressed JSON is never held in memory.
+//!
+//! Paths ending in `.db`, `.sqlite`, or `.sqlite3` name a SQLite analysis
+//! store instead (see `store`, feature `sqlite`).
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// zstd compression level used for report files.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Extension that marks a report file as zstd-compressed.
+const ZSTD_EXTENSION: &str = "zst";
+
+/// Extensions that mark a path as a SQLite analysis store.
+const DATABASE_EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+/// Whether a report path names a SQLite analysis store, based on its extension.
+pub fn is_database(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| DATABASE_EXTENSIONS.iter().any(|d| e.eq_ignore_ascii_case(d)))
+}
+
+/// Whether a report path should be zstd-compressed, based on its extension.
+pub fn is_compressed(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(ZSTD_EXTENSION))
+}
+
+/// Read a JSON report, decompressing it if the path ends in `.zst`.
+pub fn read_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+    let path = path.as_ref();
+    let reader = BufReader::new(File::open(path)?);
+
+    let value = if is_compressed(path) {
+        serde_json::from_reader(zstd::Decoder::with_buffer(reader)?)
+    } else {
+        serde_json::from_reader(reader)
+    };
+
+    Ok(value.map_err(std::io::Error::other)?)
+}
+
+/// Write a pretty-printed JSON report, compressing it if the path ends in `.zst`.
+pub fn write_json<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<()> {
+    let path = path.as_ref();
+    let writer = BufWriter::new(File::create(path)?);
+
+    if is_compressed(path) {
+        let mut encoder = zstd::Encoder::new(writer, COMPRESSION_LEVEL)?;
+        serde_json::to_writer_pretty(&mut encoder, value).map_err(std::io::Error::other)?;
+        encoder.finish()?.flush()?;
+    } else {
+        let mut writer = writer;
+        serde_json::to_writer_pretty(&mut writer, value).map_err(std::io::Error::other)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Serialize a report as pretty JSON with object keys sorted.
+///
+/// Maps such as [`HashMap`](std::collections::HashMap)s serialize in arbitrary
+/// order; this form is byte-for-byte stable, for comparing reports across
+/// runs and in snapshot tests.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialized.
+pub fn to_stable_json<T: Serialize>(value: &T) -> Result<String> {
+    // serde_json::Value keeps object keys in a sorted map
+    let value = serde_json::to_value(value).map_err(std::io::Error::other)?;
+    Ok(serde_json::to_string_pretty(&value).map_err(std::io::Error::other)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_plain_and_compressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let value: Vec<String> = (0..1000).map(|i| format!("asset-{}", i)).collect();
+
+        let plain = dir.path().join("analysis.json");
+        let compressed = dir.path().join("analysis.json.zst");
+        write_json(&plain, &value).unwrap();
+        write_json(&compressed, &value).unwrap();
+
+        assert!(!is_compressed(&plain));
+        assert!(is_compressed(&compressed));
+        assert_eq!(read_json::<Vec<String>>(&plain).unwrap(), value);
+        assert_eq!(read_json::<Vec<String>>(&compressed).unwrap(), value);
+
+        // The compressed file is actually zstd, not JSON
+        let raw = std::fs::read(&compressed).unwrap();
+        assert_eq!(&raw[..4], &[0x28, 0xB5, 0x2F, 0xFD]);
+        assert!(raw.len() < std::fs::metadata(&plain).unwrap().len() as usize);
+    }
+
+    #[test]
+    fn test_database_paths() {
+        assert!(is_database(Path::new("analysis.db")));
+        assert!(is_database(Path::new("analysis.SQLite")));
+        assert!(!is_database(Path::new("analysis.json")));
+        assert!(!is_database(Path::new("analysis.db.zst")));
+    }
+
+    #[test]
+    fn test_stable_json_sorts_keys() {
+        let value: std::collections::HashMap<&str, u32> = [("zeta", 1), ("alpha", 2), ("mid", 3)].into();
+        assert_eq!(to_stable_json(&value).unwrap(), "{\n  \"alpha\": 2,\n  \"mid\": 3,\n  \"zeta\": 1\n}");
+    }
+}
@@ -0,0 +1,324 @@
+//! Rollups of execution reports across runs.
+//!
+//! Every `execute` writes an execution report to its backup directory.
+//! [`ReportRollup`] merges any number of them into cumulative totals, with
+//! one row per run so space reclaimed and failures can be followed over
+//! time, and renders the result as plain text or Markdown.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::models::ExecutionReport;
+use crate::report_io;
+use crate::units::format_bytes;
+
+/// Prefix of the execution reports `execute` writes to the backup directory.
+const EXECUTION_REPORT_PREFIX: &str = "execution-report-";
+
+/// Counts from one execution report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunTotals {
+    /// Report the counts were read from
+    pub source: PathBuf,
+
+    /// When the run finished: recorded in the report, or else taken from
+    /// the report's file name (`execution-report-YYYYMMDD-HHMMSS`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// Groups processed
+    pub groups: usize,
+
+    /// Assets deleted
+    pub deleted: usize,
+
+    /// Operations that failed
+    pub failed: usize,
+
+    /// Operations that were skipped
+    pub skipped: usize,
+
+    /// Bytes freed by permanently deleted assets
+    pub reclaimed_bytes: u64,
+
+    /// Bytes of assets moved to the trash, freed once it is emptied
+    pub trashed_bytes: u64,
+}
+
+impl RunTotals {
+    /// Counts from `report`, read from `source`.
+    pub fn from_report(source: &Path, report: &ExecutionReport) -> Self {
+        Self {
+            source: source.to_path_buf(),
+            finished_at: report.finished_at.or_else(|| stamp_of(source)),
+            groups: report.total_groups,
+            deleted: report.deleted,
+            failed: report.failed,
+            skipped: report.skipped,
+            reclaimed_bytes: report.reclaimed_bytes,
+            trashed_bytes: report.trashed_bytes,
+        }
+    }
+
+    /// Failed operations per group processed (0 for a run with no groups).
+    pub fn failure_rate(&self) -> f64 {
+        if self.groups == 0 {
+            0.0
+        } else {
+            self.failed as f64 / self.groups as f64
+        }
+    }
+}
+
+/// Time in a report file name such as `execution-report-20250101-120000.json`.
+fn stamp_of(path: &Path) -> Option<DateTime<Utc>> {
+    let name = path.file_name()?.to_str()?;
+    let stamp = name.strip_prefix(EXECUTION_REPORT_PREFIX)?.get(..15)?;
+    NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S").ok().map(|t| t.and_utc())
+}
+
+/// Cumulative totals across execution reports, with a row per run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReportRollup {
+    /// Each run, oldest first (runs of unknown time last)
+    pub runs: Vec<RunTotals>,
+
+    /// Groups processed across all runs
+    pub groups: usize,
+
+    /// Assets deleted across all runs
+    pub deleted: usize,
+
+    /// Operations that failed across all runs
+    pub failed: usize,
+
+    /// Operations that were skipped across all runs
+    pub skipped: usize,
+
+    /// Bytes freed by permanent deletes across all runs
+    pub reclaimed_bytes: u64,
+
+    /// Bytes moved to the trash across all runs
+    pub trashed_bytes: u64,
+}
+
+impl ReportRollup {
+    /// Read and merge the execution reports at `paths`. A directory stands
+    /// for every execution report in it (`execution-report-*`), such as a
+    /// backup directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory or report cannot be read, or a
+    /// report isn't an execution report.
+    pub fn from_paths(paths: &[PathBuf]) -> Result<Self> {
+        let mut rollup = Self::default();
+        for path in paths {
+            if path.is_dir() {
+                for report in report_files(path)? {
+                    rollup.add(&report, &report_io::read_json(&report)?);
+                }
+            } else {
+                rollup.add(path, &report_io::read_json(path)?);
+            }
+        }
+        Ok(rollup)
+    }
+
+    /// Add a run's report, read from `source`.
+    pub fn add(&mut self, source: &Path, report: &ExecutionReport) {
+        let run = RunTotals::from_report(source, report);
+        self.groups += run.groups;
+        self.deleted += run.deleted;
+        self.failed += run.failed;
+        self.skipped += run.skipped;
+        self.reclaimed_bytes += run.reclaimed_bytes;
+        self.trashed_bytes += run.trashed_bytes;
+
+        // Keep the runs in time order, unknown times last
+        let at = match run.finished_at {
+            Some(t) => self.runs.partition_point(|r| r.finished_at.is_some_and(|at| at <= t)),
+            None => self.runs.len(),
+        };
+        self.runs.insert(at, run);
+    }
+
+    /// Failed operations per group processed, across all runs.
+    pub fn failure_rate(&self) -> f64 {
+        if self.groups == 0 {
+            0.0
+        } else {
+            self.failed as f64 / self.groups as f64
+        }
+    }
+
+    /// Plain-text summary: totals, then a table of runs.
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Execution reports: {}", self.runs.len());
+        let _ = writeln!(out, "Groups processed:  {}", self.groups);
+        let _ = writeln!(out, "Assets deleted:    {}", self.deleted);
+        let _ = writeln!(out, "Space reclaimed:   {}", format_bytes(self.reclaimed_bytes));
+        let _ = writeln!(out, "Moved to trash:    {}", format_bytes(self.trashed_bytes));
+        let _ = writeln!(
+            out,
+            "Failures:          {} ({:.2} per group)",
+            self.failed,
+            self.failure_rate()
+        );
+        let _ = writeln!(out, "Skipped:           {}", self.skipped);
+
+        if !self.runs.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(
+                out,
+                "{:<20} {:>7} {:>8} {:>12} {:>12} {:>7} {:>8}",
+                "Finished", "Groups", "Deleted", "Reclaimed", "In trash", "Failed", "Skipped"
+            );
+            for run in &self.runs {
+                let _ = writeln!(
+                    out,
+                    "{:<20} {:>7} {:>8} {:>12} {:>12} {:>7} {:>8}",
+                    finished(run),
+                    run.groups,
+                    run.deleted,
+                    format_bytes(run.reclaimed_bytes),
+                    format_bytes(run.trashed_bytes),
+                    run.failed,
+                    run.skipped
+                );
+            }
+        }
+        out
+    }
+
+    /// Markdown summary: a totals list, then a table of runs.
+    pub fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "## Execution summary");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "- **Runs:** {}", self.runs.len());
+        let _ = writeln!(out, "- **Groups processed:** {}", self.groups);
+        let _ = writeln!(out, "- **Assets deleted:** {}", self.deleted);
+        let _ = writeln!(out, "- **Space reclaimed:** {}", format_bytes(self.reclaimed_bytes));
+        let _ = writeln!(out, "- **Moved to trash:** {}", format_bytes(self.trashed_bytes));
+        let _ = writeln!(
+            out,
+            "- **Failures:** {} ({:.2} per group)",
+            self.failed,
+            self.failure_rate()
+        );
+        let _ = writeln!(out, "- **Skipped:** {}", self.skipped);
+
+        if !self.runs.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "| Finished | Groups | Deleted | Reclaimed | In trash | Failed | Skipped |");
+            let _ = writeln!(out, "|---|---:|---:|---:|---:|---:|---:|");
+            for run in &self.runs {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} | {} | {} |",
+                    finished(run),
+                    run.groups,
+                    run.deleted,
+                    format_bytes(run.reclaimed_bytes),
+                    format_bytes(run.trashed_bytes),
+                    run.failed,
+                    run.skipped
+                );
+            }
+        }
+        out
+    }
+}
+
+/// The execution reports in `dir`, by name.
+fn report_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut reports = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_report = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with(EXECUTION_REPORT_PREFIX));
+        if is_report && path.is_file() {
+            reports.push(path);
+        }
+    }
+    reports.sort();
+    Ok(reports)
+}
+
+/// When a run finished, for display.
+fn finished(run: &RunTotals) -> String {
+    match run.finished_at {
+        Some(at) => at.format("%Y-%m-%d %H:%M").to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GroupResult, OperationResult};
+
+    fn report(deleted: &[&str], failed: usize, reclaimed_bytes: u64) -> ExecutionReport {
+        let mut report = ExecutionReport::new();
+        report.add_group_result(GroupResult {
+            duplicate_id: "dup".to_string(),
+            winner_id: "w".to_string(),
+            consolidation_result: None,
+            download_results: (0..failed)
+                .map(|i| OperationResult::Failed {
+                    id: format!("f{}", i),
+                    error: "timeout".to_string(),
+                })
+                .collect(),
+            delete_result: Some(OperationResult::Success {
+                id: "dup".to_string(),
+                path: None,
+            }),
+            deleted_ids: deleted.iter().map(|id| id.to_string()).collect(),
+            rolled_back_ids: Vec::new(),
+            reclaimed_bytes,
+            trashed_bytes: 0,
+            promotions: Vec::new(),
+            decision: None,
+        });
+        report
+    }
+
+    #[test]
+    fn test_reports_rolled_up_in_time_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let later = dir.path().join("execution-report-20250108-090000.json");
+        let earlier = dir.path().join("execution-report-20250101-090000.json");
+        report_io::write_json(&later, &report(&["a"], 2, 2048)).unwrap();
+        report_io::write_json(&earlier, &report(&["b", "c"], 0, 3 * 1024 * 1024)).unwrap();
+        std::fs::write(dir.path().join("b_IMG.jpg"), b"jpeg").unwrap();
+
+        // A report outside the directory, recording when it finished
+        let elsewhere = tempfile::tempdir().unwrap();
+        let mut timed = report(&["d"], 1, 0);
+        timed.finished_at = Some("2025-01-04T12:00:00Z".parse().unwrap());
+        let timed_path = elsewhere.path().join("weekly.json");
+        report_io::write_json(&timed_path, &timed).unwrap();
+
+        let rollup = ReportRollup::from_paths(&[dir.path().to_path_buf(), timed_path.clone()]).unwrap();
+        let sources: Vec<&Path> = rollup.runs.iter().map(|r| r.source.as_path()).collect();
+        assert_eq!(sources, [earlier.as_path(), timed_path.as_path(), later.as_path()]);
+        assert_eq!((rollup.groups, rollup.deleted, rollup.failed), (3, 4, 3));
+        assert_eq!(rollup.reclaimed_bytes, 3 * 1024 * 1024 + 2048);
+        assert_eq!(rollup.runs[2].failure_rate(), 2.0);
+
+        let text = rollup.render_text();
+        assert!(text.contains("Space reclaimed:   3.0 MiB"));
+        assert!(text.contains("2025-01-08 09:00"));
+        let markdown = rollup.render_markdown();
+        assert!(markdown.contains("- **Assets deleted:** 4"));
+        assert!(markdown.contains("| 2025-01-04 12:00 | 1 | 1 | 0 B | 0 B | 1 | 0 |"));
+    }
+}
@@ -0,0 +1,454 @@
+//! Backup retention.
+//!
+//! Loser backups otherwise pile up forever. [`prune_backups`] deletes the
+//! backups in a backup directory that are older than a retention period, or
+//! the oldest ones beyond a size budget, but only for assets whose deletion
+//! was verified: an execution report in the directory must list the asset as
+//! deleted, and the server must confirm it is trashed or gone.
+//!
+//! Loose backups are pruned one by one. A tar or zip archive is pruned as a
+//! whole, once every asset in its manifest is verified; an archive without a
+//! readable manifest is always kept. Backups streamed to an S3 bucket are
+//! not covered; use the bucket's lifecycle rules for those.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::client::ImmichClient;
+use crate::clock::{Clock, SystemClock};
+use crate::archive::read_manifest;
+use crate::error::Result;
+use crate::report_io;
+use crate::sidecar::sidecar_path;
+
+/// Prefix of the execution reports `execute` writes to the backup directory.
+const EXECUTION_REPORT_PREFIX: &str = "execution-report-";
+
+/// How long backups are kept, and how much space they may take.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    /// Prune backups older than this (none to keep them regardless of age)
+    pub max_age: Option<Duration>,
+
+    /// Prune the oldest backups until the rest fit in this many bytes (none
+    /// for no budget)
+    pub max_total_bytes: Option<u64>,
+}
+
+/// A loser backup, or an archive of them, in a backup directory.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupFile {
+    /// Path of the backup
+    pub path: PathBuf,
+
+    /// Assets the backup holds: the one from a loose backup's
+    /// `{asset_id}_{filename}` name, or every asset in an archive's manifest
+    /// (none if the manifest can't be read)
+    pub asset_ids: Vec<String>,
+
+    /// Whether this is a tar or zip archive of several backups
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub archive: bool,
+
+    /// Size of the backup and its sidecar, if any, in bytes
+    pub bytes: u64,
+
+    /// When the backup was written
+    pub modified: DateTime<Utc>,
+
+    /// XMP sidecar written next to the backup, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar: Option<PathBuf>,
+}
+
+/// Outcome of pruning a backup directory.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    /// Backups deleted (or that would be, on a dry run), oldest first
+    pub pruned: Vec<BackupFile>,
+
+    /// Bytes freed by the pruned backups
+    pub bytes_freed: u64,
+
+    /// Backups kept
+    pub kept: usize,
+
+    /// Bytes taken by the kept backups
+    pub bytes_kept: u64,
+
+    /// Assets whose backups the policy would prune, but were kept because
+    /// their deletion couldn't be verified
+    pub unverified: Vec<String>,
+
+    /// Whether the kept backups still exceed the policy's size budget
+    pub over_budget: bool,
+
+    /// Backups that could not be read or deleted
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// List the backups in a backup directory, oldest first.
+///
+/// Backups are the files named `{asset_id}_{filename}`, with their sidecars
+/// counted alongside, and the `.tar` and `.zip` backup archives. Execution
+/// reports are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the directory or a file's metadata cannot be read.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupFile>> {
+    let mut backups = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if !path.is_file() || name.starts_with(EXECUTION_REPORT_PREFIX) || name.ends_with(".xmp") {
+            continue;
+        }
+
+        let metadata = std::fs::metadata(&path)?;
+        if name.ends_with(".tar") || name.ends_with(".zip") {
+            let mut asset_ids: Vec<String> = read_manifest(&path)
+                .map(|manifest| manifest.entries.into_iter().map(|e| e.asset_id).collect())
+                .unwrap_or_default();
+            asset_ids.sort();
+            asset_ids.dedup();
+            backups.push(BackupFile {
+                asset_ids,
+                archive: true,
+                bytes: metadata.len(),
+                modified: metadata.modified()?.into(),
+                sidecar: None,
+                path,
+            });
+            continue;
+        }
+
+        let Some((asset_id, _)) = name.split_once('_') else {
+            continue;
+        };
+        let sidecar = Some(sidecar_path(&path)).filter(|s| s.is_file());
+        let sidecar_bytes = match &sidecar {
+            Some(sidecar) => std::fs::metadata(sidecar)?.len(),
+            None => 0,
+        };
+        backups.push(BackupFile {
+            asset_ids: vec![asset_id.to_string()],
+            archive: false,
+            bytes: metadata.len() + sidecar_bytes,
+            modified: metadata.modified()?.into(),
+            sidecar,
+            path,
+        });
+    }
+
+    backups.sort_by(|a, b| a.modified.cmp(&b.modified).then_with(|| a.path.cmp(&b.path)));
+    Ok(backups)
+}
+
+/// Assets the execution reports in a backup directory list as deleted.
+///
+/// # Errors
+///
+/// Returns an error if the directory or a report cannot be read.
+pub fn reported_deletions(dir: &Path) -> Result<HashSet<String>> {
+    let mut deleted = HashSet::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_report = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with(EXECUTION_REPORT_PREFIX));
+        if !is_report {
+            continue;
+        }
+
+        let report: serde_json::Value = report_io::read_json(&path)?;
+        let ids = report["results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|group| group["deleted_ids"].as_array())
+            .flatten()
+            .filter_map(|id| id.as_str());
+        deleted.extend(ids.map(str::to_string));
+    }
+
+    Ok(deleted)
+}
+
+/// Pick the backups to prune, oldest first.
+///
+/// Walks the backups from oldest to newest, pruning each that is older than
+/// the policy's age limit or still over its size budget, if all its assets
+/// are in `verified`. Returns the indices of the backups to prune and the
+/// assets kept only because they weren't verified.
+pub fn select_for_pruning(
+    backups: &[BackupFile],
+    policy: &RetentionPolicy,
+    verified: &HashSet<String>,
+    now: DateTime<Utc>,
+) -> (Vec<usize>, Vec<String>) {
+    let mut total: u64 = backups.iter().map(|b| b.bytes).sum();
+    let mut prune = Vec::new();
+    let mut unverified = Vec::new();
+
+    for (index, backup) in backups.iter().enumerate() {
+        let too_old = policy.max_age.is_some_and(|age| now - backup.modified > age);
+        let over_budget = policy.max_total_bytes.is_some_and(|budget| total > budget);
+        if !too_old && !over_budget {
+            continue;
+        }
+
+        let pending: Vec<&String> = backup.asset_ids.iter().filter(|id| !verified.contains(*id)).collect();
+        if pending.is_empty() && !backup.asset_ids.is_empty() {
+            total -= backup.bytes;
+            prune.push(index);
+        } else {
+            unverified.extend(pending.into_iter().cloned());
+        }
+    }
+
+    (prune, unverified)
+}
+
+/// Prune a backup directory according to `policy`.
+///
+/// A backup is only pruned if an execution report in `dir` lists its asset
+/// as deleted and the server confirms the asset is trashed or gone; a
+/// restored or unreachable asset keeps its backup. A 404 only counts as gone
+/// if it is Immich's own "asset not found" answer, and the server must
+/// answer a version check first, so a wrong URL or path prefix prunes
+/// nothing. With `dry_run`, nothing is deleted and the report lists what
+/// would be.
+///
+/// # Errors
+///
+/// Returns an error if the directory or its execution reports cannot be
+/// read, or the server doesn't answer as Immich.
+pub async fn prune_backups(
+    client: &ImmichClient,
+    dir: &Path,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+) -> Result<PruneReport> {
+    prune_backups_with_clock(client, dir, policy, dry_run, &SystemClock).await
+}
+
+/// Like [`prune_backups`], measuring backup ages from the time on `clock`.
+///
+/// # Errors
+///
+/// Returns an error if the directory or its execution reports cannot be
+/// read, or the server doesn't answer as Immich.
+pub(crate) async fn prune_backups_with_clock(
+    client: &ImmichClient,
+    dir: &Path,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+    clock: &dyn Clock,
+) -> Result<PruneReport> {
+    let backups = list_backups(dir)?;
+    let reported = reported_deletions(dir)?;
+
+    // Every asset would look deleted to a server that 404s everything
+    client.get_server_version().await?;
+
+    // Only ask the server about reported assets the policy could prune:
+    // any of them under a size budget, otherwise only the old ones
+    let now = clock.now();
+    let mut verified = HashSet::new();
+    for backup in &backups {
+        let candidate = policy.max_total_bytes.is_some()
+            || policy.max_age.is_some_and(|age| now - backup.modified > age);
+        if !candidate {
+            continue;
+        }
+        for asset_id in &backup.asset_ids {
+            if !reported.contains(asset_id) || verified.contains(asset_id) {
+                continue;
+            }
+            let gone = match client.get_asset(asset_id).await {
+                Ok(asset) => asset.is_trashed,
+                Err(e) => e.is_asset_not_found(),
+            };
+            if gone {
+                verified.insert(asset_id.clone());
+            }
+        }
+    }
+
+    let (prune, unverified) = select_for_pruning(&backups, policy, &verified, now);
+    let mut report = PruneReport {
+        unverified,
+        ..Default::default()
+    };
+    for backup in backups.iter().filter(|b| b.archive && b.asset_ids.is_empty()) {
+        report.errors.push(format!("{}: no readable manifest, kept", backup.path.display()));
+    }
+    let prune: HashSet<usize> = prune.into_iter().collect();
+
+    for (index, backup) in backups.into_iter().enumerate() {
+        if !prune.contains(&index) {
+            report.kept += 1;
+            report.bytes_kept += backup.bytes;
+            continue;
+        }
+
+        if !dry_run {
+            let removed = std::fs::remove_file(&backup.path).and_then(|()| match &backup.sidecar {
+                Some(sidecar) => std::fs::remove_file(sidecar),
+                None => Ok(()),
+            });
+            if let Err(e) = removed {
+                report.errors.push(format!("{}: {}", backup.path.display(), e));
+                report.kept += 1;
+                report.bytes_kept += backup.bytes;
+                continue;
+            }
+        }
+        report.bytes_freed += backup.bytes;
+        report.pruned.push(backup);
+    }
+
+    report.over_budget = policy.max_total_bytes.is_some_and(|budget| report.bytes_kept > budget);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    /// Write a backup file `days` old.
+    fn write_backup(dir: &Path, name: &str, bytes: usize, days: i64) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![0u8; bytes]).unwrap();
+        let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(days as u64 * 86_400);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        path
+    }
+
+    /// Write a finished tar archive named `name` holding a backup of each asset.
+    async fn write_archive(dir: &Path, name: &str, assets: &[&str]) -> PathBuf {
+        use crate::archive::{BackupArchive, TarArchive};
+
+        let scratch = tempfile::tempdir().unwrap();
+        let photo = scratch.path().join("photo.jpg");
+        std::fs::write(&photo, vec![0u8; 100]).unwrap();
+        let archive = TarArchive::create(scratch.path(), "http://immich.local").await.unwrap();
+        for asset in assets {
+            archive.add_file(&photo, &format!("{}_photo.jpg", asset), asset, "group").await.unwrap();
+        }
+        archive.finish().await.unwrap();
+
+        let path = dir.join(name);
+        std::fs::rename(archive.path(), &path).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_prune_only_verified_deletions() {
+        let groups = synthetic_groups(3, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let [gone, newer, restored] = [0, 1, 2].map(|g| groups[g].assets[1].id.clone());
+        client.delete_assets(&[gone.clone(), newer.clone()], true).await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let old = write_backup(dir.path(), &format!("{}_a.jpg", gone), 100, 60);
+        std::fs::write(sidecar_path(&old), b"<xmp/>").unwrap();
+        write_backup(dir.path(), &format!("{}_b.jpg", newer), 100, 10);
+        // Restored since the run, so its backup stays however old it is
+        write_backup(dir.path(), &format!("{}_c.jpg", restored), 100, 90);
+        // Not in any report
+        write_backup(dir.path(), "unknown-asset_d.jpg", 100, 90);
+        let report = serde_json::json!({
+            "results": [{ "deleted_ids": [gone, newer, restored] }],
+        });
+        std::fs::write(dir.path().join("execution-report-20250101-000000.json"), report.to_string()).unwrap();
+
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::days(30)),
+            max_total_bytes: None,
+        };
+        let preview = prune_backups(&client, dir.path(), &policy, true).await.unwrap();
+        assert_eq!(preview.pruned.len(), 1);
+        assert!(old.exists());
+
+        let pruned = prune_backups(&client, dir.path(), &policy, false).await.unwrap();
+        assert_eq!(pruned.pruned.len(), 1);
+        assert_eq!(pruned.pruned[0].asset_ids, [gone]);
+        assert_eq!(pruned.bytes_freed, 106);
+        assert!(!old.exists() && !sidecar_path(&old).exists());
+        assert_eq!(pruned.kept, 3);
+        assert!(pruned.unverified.contains(&restored));
+
+        // A budget of 150 bytes prunes the newer verified backup too
+        let policy = RetentionPolicy {
+            max_age: None,
+            max_total_bytes: Some(150),
+        };
+        let pruned = prune_backups(&client, dir.path(), &policy, false).await.unwrap();
+        assert_eq!(pruned.pruned.len(), 1);
+        assert_eq!(pruned.pruned[0].asset_ids, [newer]);
+        assert_eq!(pruned.bytes_kept, 200);
+        assert_eq!(pruned.unverified.len(), 2);
+        assert!(pruned.over_budget);
+    }
+
+    #[tokio::test]
+    async fn test_prune_nothing_when_server_not_immich() {
+        let groups = synthetic_groups(1, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let gone = groups[0].assets[1].id.clone();
+        client.delete_assets(std::slice::from_ref(&gone), true).await.unwrap();
+        // Every request now gets a proxy's 404, which must not read as "deleted"
+        server.hide_behind_path_prefix();
+
+        let dir = tempfile::tempdir().unwrap();
+        let old = write_backup(dir.path(), &format!("{}_a.jpg", gone), 100, 60);
+        let report = serde_json::json!({ "results": [{ "deleted_ids": [gone] }] });
+        std::fs::write(dir.path().join("execution-report-20250101-000000.json"), report.to_string()).unwrap();
+
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::days(30)),
+            max_total_bytes: Some(0),
+        };
+        assert!(prune_backups(&client, dir.path(), &policy, false).await.is_err());
+        assert!(old.exists());
+    }
+
+    #[tokio::test]
+    async fn test_prune_archives_whole() {
+        let groups = synthetic_groups(2, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let [gone, restored] = [0, 1].map(|g| groups[g].assets[1].id.clone());
+        client.delete_assets(std::slice::from_ref(&gone), true).await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let verified = write_archive(dir.path(), "backups-1.tar", &[&gone]).await;
+        let mixed = write_archive(dir.path(), "backups-2.tar", &[&gone, &restored]).await;
+        let report = serde_json::json!({ "results": [{ "deleted_ids": [gone, restored] }] });
+        std::fs::write(dir.path().join("execution-report-20250101-000000.json"), report.to_string()).unwrap();
+
+        let policy = RetentionPolicy {
+            max_age: None,
+            max_total_bytes: Some(0),
+        };
+        let pruned = prune_backups(&client, dir.path(), &policy, false).await.unwrap();
+        assert_eq!(pruned.pruned.len(), 1);
+        assert!(pruned.pruned[0].archive);
+        assert!(!verified.exists());
+        // One asset of the other archive is back on the server, so all of it stays
+        assert!(mixed.exists());
+        assert_eq!(pruned.unverified, [restored]);
+        assert!(pruned.over_budget);
+    }
+}
@@ -0,0 +1,747 @@
+//! Review helpers for groups flagged with `needs_review`.
+//!
+//! Reviewing hundreds of conflicted groups one at a time is tedious, and many
+//! of them come from the same shoot. This module clusters flagged groups into
+//! "events" by capture time and GPS proximity so a single decision can be
+//! applied to a whole event at once.
+
+use std::io::{Read, Write};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+use crate::scoring::{haversine_m, DuplicateAnalysis, ScoredAsset};
+
+/// A reviewer decision for a duplicate group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// Trust the selected winner and process the group
+    Accept,
+    /// Leave the group untouched
+    Skip,
+}
+
+impl std::str::FromStr for Decision {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "accept" => Ok(Self::Accept),
+            "skip" => Ok(Self::Skip),
+            other => Err(format!("Unknown decision '{}' (expected accept or skip)", other)),
+        }
+    }
+}
+
+/// A decision together with who made it, when, and why.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ReviewDecisionRepr")]
+pub struct ReviewDecision {
+    /// The decision itself
+    pub decision: Decision,
+
+    /// Who made the decision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewer: Option<String>,
+
+    /// When the decision was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// Free-text note explaining the decision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl ReviewDecision {
+    /// Record a decision made now.
+    pub fn new(decision: Decision, reviewer: Option<String>, note: Option<String>) -> Self {
+        Self::new_with_clock(decision, reviewer, note, &SystemClock)
+    }
+
+    /// Like [`new`](Self::new), stamped with the time from `clock`.
+    pub(crate) fn new_with_clock(
+        decision: Decision,
+        reviewer: Option<String>,
+        note: Option<String>,
+        clock: &dyn Clock,
+    ) -> Self {
+        Self {
+            decision,
+            reviewer: reviewer.filter(|r| !r.trim().is_empty()),
+            timestamp: Some(clock.now()),
+            note: note.filter(|n| !n.trim().is_empty()),
+        }
+    }
+}
+
+impl From<Decision> for ReviewDecision {
+    fn from(decision: Decision) -> Self {
+        Self {
+            decision,
+            reviewer: None,
+            timestamp: None,
+            note: None,
+        }
+    }
+}
+
+/// Serialized forms of a review decision.
+///
+/// Analysis files written before attribution was added store the bare
+/// decision string, so both forms are accepted.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ReviewDecisionRepr {
+    Bare(Decision),
+    Full {
+        decision: Decision,
+        #[serde(default)]
+        reviewer: Option<String>,
+        #[serde(default)]
+        timestamp: Option<DateTime<Utc>>,
+        #[serde(default)]
+        note: Option<String>,
+    },
+}
+
+impl From<ReviewDecisionRepr> for ReviewDecision {
+    fn from(repr: ReviewDecisionRepr) -> Self {
+        match repr {
+            ReviewDecisionRepr::Bare(decision) => decision.into(),
+            ReviewDecisionRepr::Full {
+                decision,
+                reviewer,
+                timestamp,
+                note,
+            } => Self {
+                decision,
+                reviewer,
+                timestamp,
+                note,
+            },
+        }
+    }
+}
+
+/// Options controlling how groups are clustered into events.
+#[derive(Debug, Clone)]
+pub struct ClusterOptions {
+    /// Maximum gap between consecutive capture times within one event
+    pub max_gap: Duration,
+
+    /// Maximum distance in kilometres between consecutive groups with GPS
+    pub max_distance_km: f64,
+}
+
+impl Default for ClusterOptions {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::hours(3),
+            max_distance_km: 5.0,
+        }
+    }
+}
+
+/// A cluster of review groups that likely belong to the same event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewEvent {
+    /// Sequential event number (1-based, stable for a given analysis)
+    pub id: usize,
+
+    /// Earliest capture time in the event (if known)
+    pub start: Option<DateTime<Utc>>,
+
+    /// Latest capture time in the event (if known)
+    pub end: Option<DateTime<Utc>>,
+
+    /// Location of the first group in the event with GPS
+    pub location: Option<(f64, f64)>,
+
+    /// Duplicate group IDs belonging to this event
+    pub duplicate_ids: Vec<String>,
+}
+
+/// Parse an EXIF/ISO capture timestamp into UTC.
+///
+/// Accepts RFC 3339 (`2024-06-15T14:00:00.000Z`, `2024-06-15T14:00:00+02:00`)
+/// and naive forms (`2024-06-15T14:00:00`, `2024:06:15 14:00:00`), treating
+/// naive values as UTC.
+pub fn parse_capture_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    const NAIVE_FORMATS: [&str; 3] = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y:%m:%d %H:%M:%S",
+    ];
+    NAIVE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        .map(|naive| naive.and_utc())
+}
+
+/// Capture time and optional location for a group being clustered.
+type Anchor = (DateTime<Utc>, Option<(f64, f64)>);
+
+/// All assets in a group, winner first.
+fn group_assets(analysis: &DuplicateAnalysis) -> impl Iterator<Item = &ScoredAsset> {
+    std::iter::once(&analysis.winner).chain(analysis.losers.iter())
+}
+
+/// Representative capture time for a group (winner first, then any loser).
+fn group_time(analysis: &DuplicateAnalysis) -> Option<DateTime<Utc>> {
+    group_assets(analysis).find_map(|a| a.capture_time.as_deref().and_then(parse_capture_time))
+}
+
+/// Representative GPS location for a group (winner first, then any loser).
+fn group_location(analysis: &DuplicateAnalysis) -> Option<(f64, f64)> {
+    group_assets(analysis).find_map(|a| a.gps)
+}
+
+/// Cluster groups that need review into events.
+///
+/// Groups are sorted by capture time and a new event starts whenever the gap
+/// to the previous group exceeds `max_gap`, or both groups have GPS and are
+/// further apart than `max_distance_km`. Groups without a capture time each
+/// form their own event at the end of the list.
+pub fn cluster_events(groups: &[DuplicateAnalysis], options: &ClusterOptions) -> Vec<ReviewEvent> {
+    let mut timed: Vec<(Anchor, &str)> = Vec::new();
+    let mut untimed: Vec<&DuplicateAnalysis> = Vec::new();
+
+    for group in groups.iter().filter(|g| g.needs_review) {
+        match group_time(group) {
+            Some(time) => timed.push(((time, group_location(group)), &group.duplicate_id)),
+            None => untimed.push(group),
+        }
+    }
+
+    timed.sort_by_key(|((time, _), _)| *time);
+
+    let mut events: Vec<ReviewEvent> = Vec::new();
+    let mut last: Option<Anchor> = None;
+
+    for ((time, location), duplicate_id) in timed {
+        let starts_new = match last {
+            None => true,
+            Some((last_time, last_location)) => {
+                let too_late = time - last_time > options.max_gap;
+                let too_far = match (last_location, location) {
+                    (Some(a), Some(b)) => haversine_m(a, b) / 1000.0 > options.max_distance_km,
+                    _ => false,
+                };
+                too_late || too_far
+            }
+        };
+
+        if starts_new {
+            events.push(ReviewEvent {
+                id: events.len() + 1,
+                start: Some(time),
+                end: Some(time),
+                location,
+                duplicate_ids: Vec::new(),
+            });
+        }
+
+        if let Some(event) = events.last_mut() {
+            event.end = Some(time);
+            if event.location.is_none() {
+                event.location = location;
+            }
+            event.duplicate_ids.push(duplicate_id.to_string());
+        }
+
+        // Keep the last known location so GPS-less groups don't break a cluster
+        let carried_location = location.or(last.and_then(|(_, l)| l));
+        last = Some((time, carried_location));
+    }
+
+    for group in untimed {
+        events.push(ReviewEvent {
+            id: events.len() + 1,
+            start: None,
+            end: None,
+            location: group_location(group),
+            duplicate_ids: vec![group.duplicate_id.clone()],
+        });
+    }
+
+    events
+}
+
+/// Apply a decision to every group in an event.
+///
+/// Returns the number of groups updated.
+pub fn apply_event_decision(
+    groups: &mut [DuplicateAnalysis],
+    event: &ReviewEvent,
+    decision: &ReviewDecision,
+) -> usize {
+    let mut updated = 0;
+    for group in groups.iter_mut() {
+        if event.duplicate_ids.contains(&group.duplicate_id) {
+            group.decision = Some(decision.clone());
+            updated += 1;
+        }
+    }
+    updated
+}
+
+/// One row of the review spreadsheet (one duplicate group per row).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewRow {
+    /// Duplicate group identifier
+    pub duplicate_id: String,
+
+    /// Whether the group was flagged for review
+    #[serde(default)]
+    pub needs_review: bool,
+
+    /// Selected winner asset ID
+    #[serde(default)]
+    pub winner_id: String,
+
+    /// Selected winner filename
+    #[serde(default)]
+    pub winner_filename: String,
+
+    /// Immich web link to the winner
+    #[serde(default)]
+    pub winner_link: String,
+
+    /// Immich web links to the losers (space separated)
+    #[serde(default)]
+    pub loser_links: String,
+
+    /// Conflict types (space separated)
+    #[serde(default)]
+    pub conflicts: String,
+
+    /// Review reasons (space separated)
+    #[serde(default)]
+    pub review_reasons: String,
+
+    /// Reviewer decision: accept, reject, override (or blank)
+    #[serde(default)]
+    pub decision: String,
+
+    /// Asset ID to use as the winner when the decision is `override`
+    #[serde(default)]
+    pub override_winner_id: String,
+
+    /// Who made the decision (blank uses the importer's default reviewer)
+    #[serde(default)]
+    pub reviewer: String,
+
+    /// Free-text note explaining the decision
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Counts of decisions merged back from a review spreadsheet.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    /// Groups accepted with the selected winner
+    pub accepted: usize,
+    /// Groups rejected (skipped)
+    pub rejected: usize,
+    /// Groups accepted with a reviewer-chosen winner
+    pub overridden: usize,
+    /// Rows with a blank decision
+    pub unchanged: usize,
+}
+
+/// Build an Immich web UI link for an asset.
+pub fn asset_web_url(server_url: &str, asset_id: &str) -> String {
+    format!("{}/photos/{}", server_url.trim_end_matches('/'), asset_id)
+}
+
+/// Build an Immich web UI link for an album.
+pub fn album_web_url(server_url: &str, album_id: &str) -> String {
+    format!("{}/albums/{}", server_url.trim_end_matches('/'), album_id)
+}
+
+/// Build an Immich API URL for an asset.
+pub fn asset_api_url(server_url: &str, asset_id: &str) -> String {
+    format!("{}/api/assets/{}", server_url.trim_end_matches('/'), asset_id)
+}
+
+/// Write one CSV row per group for spreadsheet review.
+///
+/// The `decision` column is pre-filled with any existing decision so a sheet
+/// can be exported, edited, and imported repeatedly.
+pub fn export_review_csv<W: Write>(
+    groups: &[DuplicateAnalysis],
+    server_url: &str,
+    writer: W,
+) -> Result<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+
+    for group in groups {
+        let loser_links: Vec<String> = group
+            .losers
+            .iter()
+            .map(|l| asset_web_url(server_url, &l.asset_id))
+            .collect();
+        let conflicts: Vec<&str> = group.conflicts.iter().map(|c| c.kind()).collect();
+        let reasons: Vec<&str> = group.review_reasons.iter().map(|r| r.as_str()).collect();
+        let decision = match group.decision.as_ref().map(|d| d.decision) {
+            Some(Decision::Accept) => "accept",
+            Some(Decision::Skip) => "reject",
+            None => "",
+        };
+        let existing = group.decision.as_ref();
+
+        csv_writer.serialize(ReviewRow {
+            duplicate_id: group.duplicate_id.clone(),
+            needs_review: group.needs_review,
+            winner_id: group.winner.asset_id.to_string(),
+            winner_filename: group.winner.filename.to_string(),
+            winner_link: asset_web_url(server_url, &group.winner.asset_id),
+            loser_links: loser_links.join(" "),
+            conflicts: conflicts.join(" "),
+            review_reasons: reasons.join(" "),
+            decision: decision.to_string(),
+            override_winner_id: String::new(),
+            reviewer: existing.and_then(|d| d.reviewer.clone()).unwrap_or_default(),
+            note: existing.and_then(|d| d.note.clone()).unwrap_or_default(),
+        })?;
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+/// A validated decision parsed from a review row.
+enum RowDecision {
+    Accept,
+    Reject,
+    Override(String),
+    Unchanged,
+}
+
+/// Merge reviewer decisions from a CSV back into the analysis groups.
+///
+/// All rows are validated before anything is applied: unknown group IDs,
+/// unknown decisions, and override asset IDs that don't belong to the group
+/// are reported together and leave `groups` untouched. New decisions without
+/// a `reviewer` are attributed to `default_reviewer`; a decision that matches
+/// the one already recorded keeps its reviewer and timestamp.
+pub fn import_review_csv<R: Read>(
+    groups: &mut [DuplicateAnalysis],
+    reader: R,
+    default_reviewer: Option<&str>,
+) -> Result<ImportSummary> {
+    import_review_csv_with_clock(groups, reader, default_reviewer, &SystemClock)
+}
+
+/// Like [`import_review_csv`], stamping new decisions with the time from `clock`.
+pub(crate) fn import_review_csv_with_clock<R: Read>(
+    groups: &mut [DuplicateAnalysis],
+    reader: R,
+    default_reviewer: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<ImportSummary> {
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let mut parsed: Vec<(usize, RowDecision, ReviewRow)> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    for (line, row) in csv_reader.deserialize::<ReviewRow>().enumerate() {
+        let row = row?;
+        // Header is line 1, so data rows start at line 2
+        let line = line + 2;
+
+        let Some(index) = groups.iter().position(|g| g.duplicate_id == row.duplicate_id) else {
+            errors.push(format!("line {}: unknown group {}", line, row.duplicate_id));
+            continue;
+        };
+
+        if !row.winner_id.is_empty() && !groups[index].contains_asset(&row.winner_id) {
+            errors.push(format!(
+                "line {}: winner {} is not in group {}",
+                line, row.winner_id, row.duplicate_id
+            ));
+            continue;
+        }
+
+        let decision = match row.decision.trim().to_lowercase().as_str() {
+            "" => RowDecision::Unchanged,
+            "accept" | "accepted" => RowDecision::Accept,
+            "reject" | "rejected" | "skip" => RowDecision::Reject,
+            "override" => {
+                let winner_id = row.override_winner_id.trim();
+                if !groups[index].contains_asset(winner_id) {
+                    errors.push(format!(
+                        "line {}: override winner '{}' is not in group {}",
+                        line, winner_id, row.duplicate_id
+                    ));
+                    continue;
+                }
+                RowDecision::Override(winner_id.to_string())
+            }
+            other => {
+                errors.push(format!("line {}: unknown decision '{}'", line, other));
+                continue;
+            }
+        };
+
+        parsed.push((index, decision, row));
+    }
+
+    if !errors.is_empty() {
+        return Err(ImmichError::InvalidDecision(errors.join("; ")));
+    }
+
+    let mut summary = ImportSummary::default();
+    for (index, decision, row) in parsed {
+        let group = &mut groups[index];
+
+        // A decision re-imported as it was exported keeps when and by whom
+        // it was made; only an explicit reviewer or the note is updated
+        let decided = match &decision {
+            RowDecision::Accept => Some(Decision::Accept),
+            RowDecision::Reject => Some(Decision::Skip),
+            RowDecision::Override(winner_id) if group.winner.asset_id == *winner_id => Some(Decision::Accept),
+            RowDecision::Override(_) | RowDecision::Unchanged => None,
+        };
+        if let Some(existing) = group.decision.as_mut().filter(|d| Some(d.decision) == decided) {
+            if !row.reviewer.trim().is_empty() {
+                existing.reviewer = Some(row.reviewer);
+            }
+            existing.note = Some(row.note).filter(|n| !n.trim().is_empty());
+            summary.unchanged += 1;
+            continue;
+        }
+
+        let reviewer = Some(row.reviewer)
+            .filter(|r| !r.trim().is_empty())
+            .or_else(|| default_reviewer.map(String::from));
+        let record = |decision| Some(ReviewDecision::new_with_clock(decision, reviewer, Some(row.note), clock));
+
+        match decision {
+            RowDecision::Accept => {
+                group.decision = record(Decision::Accept);
+                summary.accepted += 1;
+            }
+            RowDecision::Reject => {
+                group.decision = record(Decision::Skip);
+                summary.rejected += 1;
+            }
+            RowDecision::Override(winner_id) => {
+                group.set_winner(&winner_id);
+                group.decision = record(Decision::Accept);
+                summary.overridden += 1;
+            }
+            RowDecision::Unchanged => summary.unchanged += 1,
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intern::SharedStr;
+    use crate::scoring::MetadataScore;
+
+    fn scored(id: &str, time: Option<&str>, gps: Option<(f64, f64)>) -> ScoredAsset {
+        ScoredAsset {
+            asset_id: id.into(),
+            filename: format!("{}.jpg", id).into(),
+            checksum: None,
+            updated_at: None,
+            score: MetadataScore::default(),
+            file_size: None,
+            dimensions: None,
+            megapixels: None,
+            is_external: false,
+            library_id: None,
+            owner_id: None,
+            capture_time: time.map(SharedStr::from),
+            gps,
+            description: None,
+            city: None,
+            country: None,
+            camera: None,
+            duration: None,
+            fps: None,
+            web_url: None,
+            api_url: None,
+        }
+    }
+
+    fn group(id: &str, time: Option<&str>, gps: Option<(f64, f64)>) -> DuplicateAnalysis {
+        DuplicateAnalysis {
+            duplicate_id: id.to_string(),
+            winner: scored(&format!("{}-w", id), time, gps),
+            losers: vec![scored(&format!("{}-l", id), None, None)],
+            conflicts: Vec::new(),
+            needs_review: true,
+            review_reasons: Vec::new(),
+            decision: None,
+            corrupt_asset_ids: Vec::new(),
+            confidence: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_capture_time_formats() {
+        assert!(parse_capture_time("2024-06-15T14:00:00.000Z").is_some());
+        assert!(parse_capture_time("2024-06-15T14:00:00+02:00").is_some());
+        assert!(parse_capture_time("2024-06-15T14:00:00").is_some());
+        assert!(parse_capture_time("2024:06:15 14:00:00").is_some());
+        assert!(parse_capture_time("not a date").is_none());
+    }
+
+    #[test]
+    fn test_cluster_by_time_gap() {
+        let groups = vec![
+            group("a", Some("2024-06-15T10:00:00Z"), None),
+            group("b", Some("2024-06-15T11:00:00Z"), None),
+            group("c", Some("2024-06-20T09:00:00Z"), None),
+        ];
+
+        let events = cluster_events(&groups, &ClusterOptions::default());
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].duplicate_ids, vec!["a", "b"]);
+        assert_eq!(events[1].duplicate_ids, vec!["c"]);
+    }
+
+    #[test]
+    fn test_cluster_splits_on_distance() {
+        let groups = vec![
+            group("london", Some("2024-06-15T10:00:00Z"), Some((51.5074, -0.1278))),
+            group("paris", Some("2024-06-15T10:30:00Z"), Some((48.8566, 2.3522))),
+        ];
+
+        let events = cluster_events(&groups, &ClusterOptions::default());
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn test_untimed_groups_are_singletons() {
+        let groups = vec![group("a", None, None), group("b", None, None)];
+        let events = cluster_events(&groups, &ClusterOptions::default());
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.start.is_none()));
+    }
+
+    #[test]
+    fn test_apply_event_decision() {
+        let mut groups = vec![
+            group("a", Some("2024-06-15T10:00:00Z"), None),
+            group("b", Some("2024-06-15T10:05:00Z"), None),
+        ];
+        let events = cluster_events(&groups, &ClusterOptions::default());
+
+        let decision = ReviewDecision::new(Decision::Accept, Some("sam".into()), None);
+        let updated = apply_event_decision(&mut groups, &events[0], &decision);
+        assert_eq!(updated, 2);
+        assert!(groups.iter().all(|g| g.should_execute(true)));
+    }
+
+    #[test]
+    fn test_review_decision_accepts_bare_string() {
+        let bare: ReviewDecision = serde_json::from_str("\"skip\"").unwrap();
+        assert_eq!(bare, Decision::Skip.into());
+
+        let full: ReviewDecision =
+            serde_json::from_str(r#"{"decision":"accept","reviewer":"sam","note":"ok"}"#).unwrap();
+        assert_eq!(full.decision, Decision::Accept);
+        assert_eq!(full.reviewer.as_deref(), Some("sam"));
+    }
+
+    #[test]
+    fn test_export_review_csv_rows() {
+        let groups = vec![group("a", None, None)];
+
+        let mut buffer = Vec::new();
+        export_review_csv(&groups, "https://immich.example.com/", &mut buffer).unwrap();
+        let exported = String::from_utf8(buffer).unwrap();
+
+        assert!(exported.starts_with("duplicate_id,needs_review,winner_id"));
+        assert!(exported.contains("https://immich.example.com/photos/a-w"));
+        assert!(exported.contains("https://immich.example.com/photos/a-l"));
+    }
+
+    #[test]
+    fn test_import_review_csv_applies_decisions() {
+        let mut groups = vec![group("a", None, None), group("b", None, None), group("c", None, None)];
+        let csv = "duplicate_id,decision,override_winner_id,reviewer,note\n\
+                   a,Rejected,,alex,blurry\n\
+                   b,override,b-l,,\n\
+                   c,,,,\n";
+
+        let summary = import_review_csv(&mut groups, csv.as_bytes(), Some("sam")).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                accepted: 0,
+                rejected: 1,
+                overridden: 1,
+                unchanged: 1,
+            }
+        );
+        let a = groups[0].decision.as_ref().unwrap();
+        assert_eq!(a.decision, Decision::Skip);
+        assert_eq!(a.reviewer.as_deref(), Some("alex"));
+        assert_eq!(a.note.as_deref(), Some("blurry"));
+        assert!(a.timestamp.is_some());
+
+        let b = groups[1].decision.as_ref().unwrap();
+        assert_eq!(groups[1].winner.asset_id, "b-l");
+        assert_eq!(groups[1].losers[0].asset_id, "b-w");
+        assert_eq!(b.decision, Decision::Accept);
+        assert_eq!(b.reviewer.as_deref(), Some("sam"));
+        assert!(b.note.is_none());
+        assert!(groups[2].decision.is_none());
+    }
+
+    #[test]
+    fn test_reimported_decisions_keep_when_and_who() {
+        let mut groups = vec![group("a", None, None), group("b", None, None)];
+        let decided_at = "2025-01-01T09:00:00Z".parse().unwrap();
+        groups[0].decision = Some(ReviewDecision {
+            decision: Decision::Skip,
+            reviewer: Some("alex".to_string()),
+            timestamp: Some(decided_at),
+            note: None,
+        });
+        groups[1].decision = Some(ReviewDecision {
+            timestamp: Some(decided_at),
+            ..Decision::Accept.into()
+        });
+
+        let mut exported = Vec::new();
+        export_review_csv(&groups, "https://immich.example.com", &mut exported).unwrap();
+        let summary = import_review_csv(&mut groups, exported.as_slice(), Some("sam")).unwrap();
+
+        assert_eq!(summary.unchanged, 2);
+        let a = groups[0].decision.as_ref().unwrap();
+        assert_eq!(a.reviewer.as_deref(), Some("alex"));
+        assert_eq!(a.timestamp, Some(decided_at));
+        let b = groups[1].decision.as_ref().unwrap();
+        assert!(b.reviewer.is_none());
+        assert_eq!(b.timestamp, Some(decided_at));
+    }
+
+    #[test]
+    fn test_import_rejects_unknown_asset() {
+        let mut groups = vec![group("a", None, None)];
+        let csv = "duplicate_id,decision,override_winner_id\na,override,not-in-group\n";
+
+        let result = import_review_csv(&mut groups, csv.as_bytes(), None);
+        assert!(matches!(result, Err(ImmichError::InvalidDecision(_))));
+        assert_eq!(groups[0].winner.asset_id, "a-w");
+        assert!(groups[0].decision.is_none());
+    }
+}
@@ -0,0 +1,172 @@
+//! Review queue as an Immich album.
+//!
+//! Groups flagged `needs_review` can be reviewed inside Immich itself: every
+//! asset of a group still waiting for a decision is collected into one album
+//! (by default [`DEFAULT_REVIEW_ALBUM`]). Once groups are resolved by a
+//! decision, their assets can be removed from the album again. Anything else
+//! in the album, such as photos added by hand, is left alone.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::client::ImmichClient;
+use crate::error::Result;
+use crate::scoring::DuplicateAnalysis;
+
+/// Album the review queue is exported to unless another name is given.
+pub const DEFAULT_REVIEW_ALBUM: &str = "Needs Review – Duplicates";
+
+/// What [`sync_review_album`] changed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReviewAlbumSync {
+    /// ID of the review album (`None` if there was nothing to review and no
+    /// album yet)
+    pub album_id: Option<String>,
+    /// Whether the album was created by this sync
+    pub created: bool,
+    /// Groups still waiting for review
+    pub pending_groups: usize,
+    /// Assets added to the album
+    pub added: usize,
+    /// Resolved assets removed from the album
+    pub removed: usize,
+}
+
+/// Returns true if a group is flagged for review and has no decision yet.
+pub fn is_pending(group: &DuplicateAnalysis) -> bool {
+    group.needs_review && group.decision.is_none()
+}
+
+/// Asset IDs of every pending group, each group's winner first.
+pub fn pending_asset_ids(groups: &[DuplicateAnalysis]) -> Vec<String> {
+    groups
+        .iter()
+        .filter(|g| is_pending(g))
+        .flat_map(|g| std::iter::once(&g.winner).chain(&g.losers))
+        .map(|a| a.asset_id.to_string())
+        .collect()
+}
+
+/// Asset IDs of every group in the analysis that was flagged for review and
+/// has since been decided, less any asset still in a pending group.
+pub fn resolved_asset_ids(groups: &[DuplicateAnalysis]) -> HashSet<String> {
+    let pending: HashSet<String> = pending_asset_ids(groups).into_iter().collect();
+    groups
+        .iter()
+        .filter(|g| g.needs_review && g.decision.is_some())
+        .flat_map(|g| std::iter::once(&g.winner).chain(&g.losers))
+        .map(|a| a.asset_id.to_string())
+        .filter(|id| !pending.contains(id))
+        .collect()
+}
+
+/// Create or update the album `name` so it holds every asset of the pending
+/// groups.
+///
+/// The album is found by name and created if it doesn't exist (unless no
+/// group is pending). With `cleanup`, assets of groups in `groups` that have
+/// been resolved are removed, so the album shows what still needs a
+/// decision; assets the analysis doesn't know about are never removed.
+/// Without it, nothing is removed.
+///
+/// # Errors
+///
+/// Returns an error if the albums cannot be fetched, or the album cannot be
+/// created or changed.
+pub async fn sync_review_album(
+    client: &ImmichClient,
+    name: &str,
+    groups: &[DuplicateAnalysis],
+    cleanup: bool,
+) -> Result<ReviewAlbumSync> {
+    let pending = pending_asset_ids(groups);
+    let mut sync = ReviewAlbumSync {
+        pending_groups: groups.iter().filter(|g| is_pending(g)).count(),
+        ..Default::default()
+    };
+
+    let existing = client.get_albums().await?.into_iter().find(|a| a.album_name == name);
+    let Some(existing) = existing else {
+        if !pending.is_empty() {
+            let album = client.create_album(name, &pending).await?;
+            sync.album_id = Some(album.id);
+            sync.created = true;
+            sync.added = pending.len();
+        }
+        return Ok(sync);
+    };
+
+    let album = client.get_album(&existing.id).await?;
+    let members: HashSet<&str> = album.assets.iter().map(|a| a.id.as_str()).collect();
+    let to_add: Vec<String> = pending.iter().filter(|id| !members.contains(id.as_str())).cloned().collect();
+    if !to_add.is_empty() {
+        client.add_assets_to_album(&album.id, &to_add).await?;
+        sync.added = to_add.len();
+    }
+
+    if cleanup {
+        let resolved = resolved_asset_ids(groups);
+        let to_remove: Vec<String> = members
+            .into_iter()
+            .filter(|id| resolved.contains(*id))
+            .map(str::to_string)
+            .collect();
+        if !to_remove.is_empty() {
+            client.remove_assets_from_album(&album.id, &to_remove).await?;
+            sync.removed = to_remove.len();
+        }
+    }
+
+    sync.album_id = Some(album.id);
+    Ok(sync)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::Decision;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[tokio::test]
+    async fn test_review_album_synced_and_cleaned_up() {
+        let groups = synthetic_groups(3, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let mut analyses: Vec<DuplicateAnalysis> = groups.iter().map(DuplicateAnalysis::from_group).collect();
+        analyses[0].needs_review = true;
+        analyses[1].needs_review = true;
+
+        let sync = sync_review_album(&client, DEFAULT_REVIEW_ALBUM, &analyses, false).await.unwrap();
+        assert!(sync.created);
+        assert_eq!((sync.pending_groups, sync.added), (2, 4));
+        let album_id = sync.album_id.unwrap();
+        assert_eq!(server.album_assets(&album_id), pending_asset_ids(&analyses));
+
+        // An asset added by hand is not the analysis's to remove
+        client.add_assets_to_album(&album_id, &["by-hand".to_string()]).await.unwrap();
+
+        // A decision resolves the group; it stays in the album until cleanup
+        analyses[0].decision = Some(Decision::Skip.into());
+        let sync = sync_review_album(&client, DEFAULT_REVIEW_ALBUM, &analyses, false).await.unwrap();
+        assert!(!sync.created);
+        assert_eq!((sync.added, sync.removed), (0, 0));
+        assert_eq!(server.album_assets(&album_id).len(), 5);
+
+        // Cleanup with only part of the analysis leaves the other groups alone
+        let sync = sync_review_album(&client, DEFAULT_REVIEW_ALBUM, &analyses[1..], true).await.unwrap();
+        assert_eq!(sync.removed, 0);
+
+        let sync = sync_review_album(&client, DEFAULT_REVIEW_ALBUM, &analyses, true).await.unwrap();
+        assert_eq!(sync.album_id.as_deref(), Some(album_id.as_str()));
+        assert_eq!(sync.removed, 2);
+        let mut expected = pending_asset_ids(&analyses[1..]);
+        expected.push("by-hand".to_string());
+        assert_eq!(server.album_assets(&album_id), expected);
+
+        // Nothing pending and no album: nothing is created
+        analyses[1].decision = Some(Decision::Accept.into());
+        let sync = sync_review_album(&client, "Other", &analyses, true).await.unwrap();
+        assert_eq!(sync.album_id, None);
+    }
+}
@@ -0,0 +1,300 @@
+//! Risk assessment of an execution plan.
+//!
+//! Before deleting anything, the plan is checked for the kinds of groups
+//! that most often go wrong: metadata conflicts, assets owned by different
+//! users, losers that are part of a public shared link, RAW losers, and runs
+//! that delete a great deal at once. A [`RiskReport`] counts each category
+//! and grades the plan as a whole, so a caller can ask for confirmation (or
+//! refuse to run) according to the grade.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::ImmichClient;
+use crate::error::Result;
+use crate::models::SharedLinkType;
+use crate::scoring::{DuplicateAnalysis, ScoredAsset};
+
+/// Camera RAW file extensions.
+const RAW_EXTENSIONS: &[&str] = &[
+    "3fr", "ari", "arw", "cr2", "cr3", "crw", "dcr", "dng", "erf", "iiq", "k25", "kdc", "mef", "mos", "mrw",
+    "nef", "nrw", "orf", "pef", "raf", "raw", "rw2", "rwl", "sr2", "srf", "srw", "x3f",
+];
+
+/// How risky a plan (or a category within it) is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskGrade {
+    /// Nothing unusual
+    Low,
+    /// Worth a look before confirming
+    Medium,
+    /// Likely to delete something someone will miss
+    High,
+}
+
+impl RiskGrade {
+    /// Stable identifier, as used in JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+impl std::fmt::Display for RiskGrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A kind of risk found in a plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskCategory {
+    /// The group has metadata conflicts
+    Conflicts,
+    /// The group's assets belong to different users
+    CrossOwner,
+    /// A loser is part of a public shared link, which deleting it breaks
+    SharedLink,
+    /// A loser is a camera RAW file
+    RawLoser,
+    /// The run deletes more assets or bytes than the configured limits
+    LargeDeletion,
+}
+
+impl RiskCategory {
+    /// Stable identifier, as used in JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Conflicts => "conflicts",
+            Self::CrossOwner => "cross_owner",
+            Self::SharedLink => "shared_link",
+            Self::RawLoser => "raw_loser",
+            Self::LargeDeletion => "large_deletion",
+        }
+    }
+
+    /// How much this category contributes to the overall grade.
+    pub fn grade(&self) -> RiskGrade {
+        match self {
+            Self::CrossOwner | Self::SharedLink => RiskGrade::High,
+            Self::Conflicts | Self::RawLoser | Self::LargeDeletion => RiskGrade::Medium,
+        }
+    }
+
+    /// Short human-readable description.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Conflicts => "groups with metadata conflicts",
+            Self::CrossOwner => "groups with assets owned by different users",
+            Self::SharedLink => "groups with a loser in a shared link",
+            Self::RawLoser => "groups deleting a RAW file",
+            Self::LargeDeletion => "deletion exceeds the large-run limits",
+        }
+    }
+}
+
+impl std::fmt::Display for RiskCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Limits above which a run counts as a large deletion.
+#[derive(Debug, Clone, Copy)]
+pub struct RiskOptions {
+    /// Assets deleted in one run
+    pub large_deletion_assets: usize,
+
+    /// Bytes deleted in one run
+    pub large_deletion_bytes: u64,
+}
+
+impl Default for RiskOptions {
+    fn default() -> Self {
+        Self {
+            large_deletion_assets: 1000,
+            large_deletion_bytes: 50 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Number of groups in one risk category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryCount {
+    /// The category
+    pub category: RiskCategory,
+
+    /// Its contribution to the overall grade
+    pub grade: RiskGrade,
+
+    /// Groups in the category (0 for run-wide categories)
+    pub groups: usize,
+}
+
+/// Risk assessment of a set of groups about to be executed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskReport {
+    /// Overall grade: the highest grade of any category present
+    pub grade: RiskGrade,
+
+    /// Categories present, most severe first
+    pub categories: Vec<CategoryCount>,
+
+    /// Assets the run deletes
+    pub assets_deleted: usize,
+
+    /// Bytes the run deletes (where sizes are known)
+    pub bytes_deleted: u64,
+
+    /// Duplicate group IDs with at least one group-level risk
+    pub risky_groups: Vec<String>,
+}
+
+impl RiskReport {
+    /// Assess `groups`.
+    ///
+    /// `shared_asset_ids` are the assets reachable through a shared link (see
+    /// [`shared_asset_ids`]); pass an empty set if they are unknown.
+    pub fn assess(groups: &[DuplicateAnalysis], shared_asset_ids: &HashSet<String>, options: &RiskOptions) -> Self {
+        let mut counts = [
+            RiskCategory::CrossOwner,
+            RiskCategory::SharedLink,
+            RiskCategory::Conflicts,
+            RiskCategory::RawLoser,
+        ]
+        .map(|category| (category, 0usize));
+        let mut risky_groups = Vec::new();
+
+        for group in groups {
+            let categories = group_risks(group, shared_asset_ids);
+            for (category, count) in counts.iter_mut() {
+                if categories.contains(category) {
+                    *count += 1;
+                }
+            }
+            if !categories.is_empty() {
+                risky_groups.push(group.duplicate_id.clone());
+            }
+        }
+
+        let assets_deleted = groups.iter().map(|g| g.losers.len()).sum();
+        let bytes_deleted = groups
+            .iter()
+            .flat_map(|g| g.losers.iter())
+            .filter_map(|l| l.file_size)
+            .sum();
+
+        let mut categories: Vec<CategoryCount> = counts
+            .into_iter()
+            .filter(|(_, groups)| *groups > 0)
+            .map(|(category, groups)| CategoryCount {
+                category,
+                grade: category.grade(),
+                groups,
+            })
+            .collect();
+        if assets_deleted > options.large_deletion_assets || bytes_deleted > options.large_deletion_bytes {
+            categories.push(CategoryCount {
+                category: RiskCategory::LargeDeletion,
+                grade: RiskCategory::LargeDeletion.grade(),
+                groups: 0,
+            });
+        }
+        categories.sort_by_key(|c| std::cmp::Reverse(c.grade));
+
+        Self {
+            grade: categories.iter().map(|c| c.grade).max().unwrap_or(RiskGrade::Low),
+            categories,
+            assets_deleted,
+            bytes_deleted,
+            risky_groups,
+        }
+    }
+}
+
+/// Group-level risks of one group.
+pub fn group_risks(group: &DuplicateAnalysis, shared_asset_ids: &HashSet<String>) -> Vec<RiskCategory> {
+    let mut risks = Vec::new();
+    if !group.conflicts.is_empty() {
+        risks.push(RiskCategory::Conflicts);
+    }
+    if group.is_cross_owner() {
+        risks.push(RiskCategory::CrossOwner);
+    }
+    if group.losers.iter().any(|l| shared_asset_ids.contains(l.asset_id.as_str())) {
+        risks.push(RiskCategory::SharedLink);
+    }
+    if group.losers.iter().any(is_raw) {
+        risks.push(RiskCategory::RawLoser);
+    }
+    risks
+}
+
+/// Returns true if the asset's file name has a camera RAW extension.
+pub fn is_raw(asset: &ScoredAsset) -> bool {
+    asset
+        .filename
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| RAW_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// IDs of every asset reachable through one of the user's shared links,
+/// including the members of shared albums.
+pub async fn shared_asset_ids(client: &ImmichClient) -> Result<HashSet<String>> {
+    let mut ids = HashSet::new();
+    for link in client.get_shared_links().await? {
+        ids.extend(link.assets.into_iter().map(|a| a.id));
+        if link.link_type == SharedLinkType::Album
+            && let Some(album) = link.album
+        {
+            let album = client.get_album(&album.id).await?;
+            ids.extend(album.assets.into_iter().map(|a| a.id));
+        }
+    }
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intern::SharedStr;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[tokio::test]
+    async fn test_assess_grades_plan() {
+        let server = MockServer::start(&synthetic_groups(4, 2).unwrap()).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let mut groups: Vec<DuplicateAnalysis> =
+            client.get_duplicates().await.unwrap().iter().map(DuplicateAnalysis::from_group).collect();
+        let options = RiskOptions::default();
+
+        let clean = RiskReport::assess(&groups, &HashSet::new(), &options);
+        assert_eq!(clean.grade, RiskGrade::Low);
+        assert!(clean.categories.is_empty());
+        assert_eq!(clean.assets_deleted, 4);
+
+        groups[1].losers[0].filename = SharedStr::from("IMG_0001.CR2");
+        let report = RiskReport::assess(&groups, &HashSet::new(), &options);
+        assert_eq!(report.grade, RiskGrade::Medium);
+        assert_eq!(report.risky_groups, vec![groups[1].duplicate_id.clone()]);
+
+        // A loser in a shared album makes the plan high risk
+        let loser = groups[2].losers[0].asset_id.to_string();
+        server.add_album("album-1", "Holiday", &[&loser]);
+        server.share_album("link-1", "album-1");
+        let shared = shared_asset_ids(&client).await.unwrap();
+        let report = RiskReport::assess(&groups, &shared, &RiskOptions { large_deletion_assets: 3, ..options });
+        assert_eq!(report.grade, RiskGrade::High);
+        let categories: Vec<_> = report.categories.iter().map(|c| c.category).collect();
+        assert_eq!(
+            categories,
+            vec![RiskCategory::SharedLink, RiskCategory::RawLoser, RiskCategory::LargeDeletion]
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Random sampling for progressive post-execution verification.
+//!
+//! Verifying every group of a large library is slow. Instead, a random
+//! sample is verified first and its failure rate extrapolated to the whole
+//! run with a 95% Wilson score interval. A sample with any failures escalates
+//! to full verification; a clean sample can stop early, optionally only once
+//! the interval is tight enough ([`SampleOptions::confidence_stop`]).
+
+use serde::Serialize;
+
+/// z-score for a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// Smallest sample worth extrapolating from, unless there are fewer groups.
+pub const MIN_SAMPLE_SIZE: usize = 30;
+
+/// How to sample groups for verification.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SampleOptions {
+    /// Fraction of groups to verify first (0 < fraction <= 1)
+    pub fraction: f64,
+
+    /// Keep sampling until the upper bound of the failure rate is below
+    /// `max_failure_rate`, instead of stopping after the first clean sample
+    pub confidence_stop: bool,
+
+    /// Acceptable failure rate for `confidence_stop` (fraction, e.g. 0.01)
+    pub max_failure_rate: f64,
+
+    /// Seed for the sampling order, so a run can be reproduced
+    pub seed: u64,
+}
+
+/// What to do after verifying a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleStep {
+    /// The sample is clean enough; stop verifying
+    Stop,
+    /// The sample is clean but too small; verify up to this many groups
+    Extend(usize),
+    /// The sample found failures; verify every group
+    Escalate,
+}
+
+/// Failure rate observed in a sample, extrapolated to the whole population.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FailureEstimate {
+    /// Groups verified
+    pub sampled: usize,
+
+    /// Verified groups with at least one anomaly
+    pub failed: usize,
+
+    /// Total groups in the run
+    pub population: usize,
+
+    /// Observed failure rate (fraction)
+    pub rate: f64,
+
+    /// Lower bound of the 95% confidence interval (fraction)
+    pub lower: f64,
+
+    /// Upper bound of the 95% confidence interval (fraction)
+    pub upper: f64,
+}
+
+impl FailureEstimate {
+    /// Estimate the population failure rate from `failed` of `sampled` groups.
+    pub fn new(failed: usize, sampled: usize, population: usize) -> Self {
+        let (rate, lower, upper) = if sampled == 0 {
+            (0.0, 0.0, 1.0)
+        } else if sampled >= population {
+            // Everything was verified; the rate is exact
+            let rate = failed as f64 / sampled as f64;
+            (rate, rate, rate)
+        } else {
+            let (lower, upper) = wilson_interval(failed, sampled);
+            (failed as f64 / sampled as f64, lower, upper)
+        };
+
+        Self {
+            sampled,
+            failed,
+            population,
+            rate,
+            lower,
+            upper,
+        }
+    }
+
+    /// Projected range of failing groups across the population.
+    pub fn projected_failures(&self) -> (usize, usize) {
+        let unverified = self.population.saturating_sub(self.sampled) as f64;
+        (
+            self.failed + (self.lower * unverified).floor() as usize,
+            self.failed + (self.upper * unverified).ceil() as usize,
+        )
+    }
+}
+
+/// 95% Wilson score interval for `successes` of `trials`.
+fn wilson_interval(successes: usize, trials: usize) -> (f64, f64) {
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = Z_95 * Z_95;
+
+    let denominator = 1.0 + z2 / n;
+    let centre = (p + z2 / (2.0 * n)) / denominator;
+    let margin = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+
+    // Pin the bounds exactly when nothing (or everything) failed
+    let lower = if successes == 0 { 0.0 } else { (centre - margin).max(0.0) };
+    let upper = if successes == trials { 1.0 } else { (centre + margin).min(1.0) };
+    (lower, upper)
+}
+
+/// Parse a sample size such as `5%` or `0.05` into a fraction.
+pub fn parse_fraction(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let fraction = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+        None => value.parse::<f64>().ok()?,
+    };
+    (fraction > 0.0 && fraction <= 1.0).then_some(fraction)
+}
+
+/// Number of groups in the initial sample.
+pub fn sample_size(population: usize, fraction: f64) -> usize {
+    let size = (population as f64 * fraction).ceil() as usize;
+    size.max(MIN_SAMPLE_SIZE).min(population)
+}
+
+/// A random order of `0..population`; verifying a prefix of it verifies a random sample.
+pub fn sample_order(population: usize, seed: u64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..population).collect();
+    let mut rng = SplitMix64(seed);
+
+    // Fisher-Yates shuffle
+    for i in (1..order.len()).rev() {
+        let j = (rng.next() % (i as u64 + 1)) as usize;
+        order.swap(i, j);
+    }
+    order
+}
+
+/// Decide whether a verified sample is enough.
+pub fn next_step(estimate: &FailureEstimate, options: &SampleOptions) -> SampleStep {
+    if estimate.failed > 0 {
+        SampleStep::Escalate
+    } else if estimate.sampled >= estimate.population
+        || !options.confidence_stop
+        || estimate.upper <= options.max_failure_rate
+    {
+        SampleStep::Stop
+    } else {
+        // Double the sample and check again
+        SampleStep::Extend((estimate.sampled * 2).min(estimate.population))
+    }
+}
+
+/// Small, fast PRNG; good enough for choosing a sample.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fraction_and_sample_size() {
+        assert_eq!(parse_fraction("5%"), Some(0.05));
+        assert_eq!(parse_fraction("0.25"), Some(0.25));
+        assert_eq!(parse_fraction("0%"), None);
+        assert_eq!(parse_fraction("150%"), None);
+        assert_eq!(parse_fraction("lots"), None);
+
+        assert_eq!(sample_size(30_000, 0.05), 1_500);
+        assert_eq!(sample_size(100, 0.05), MIN_SAMPLE_SIZE);
+        assert_eq!(sample_size(10, 0.05), 10);
+    }
+
+    #[test]
+    fn test_sample_order_is_a_seeded_permutation() {
+        let order = sample_order(1_000, 42);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..1_000).collect::<Vec<_>>());
+        assert_ne!(order, sorted);
+        assert_eq!(order, sample_order(1_000, 42));
+        assert_ne!(order, sample_order(1_000, 43));
+    }
+
+    #[test]
+    fn test_estimate_interval() {
+        // Clean sample of 1,500: upper bound ~0.26%
+        let clean = FailureEstimate::new(0, 1_500, 30_000);
+        assert_eq!(clean.rate, 0.0);
+        assert_eq!(clean.lower, 0.0);
+        assert!((clean.upper - 0.00255).abs() < 0.0001, "got {}", clean.upper);
+        assert_eq!(clean.projected_failures().0, 0);
+        assert!(clean.projected_failures().1 <= 74);
+
+        // 15 failures in 1,500 brackets 1%
+        let failing = FailureEstimate::new(15, 1_500, 30_000);
+        assert!(failing.lower < 0.01 && failing.upper > 0.01);
+
+        // Full verification is exact
+        let full = FailureEstimate::new(3, 300, 300);
+        assert_eq!((full.lower, full.upper), (0.01, 0.01));
+        assert_eq!(full.projected_failures(), (3, 3));
+    }
+
+    #[test]
+    fn test_next_step() {
+        let options = SampleOptions {
+            fraction: 0.05,
+            confidence_stop: false,
+            max_failure_rate: 0.001,
+            seed: 1,
+        };
+        let clean = FailureEstimate::new(0, 1_500, 30_000);
+        assert_eq!(next_step(&clean, &options), SampleStep::Stop);
+        assert_eq!(
+            next_step(&FailureEstimate::new(1, 1_500, 30_000), &options),
+            SampleStep::Escalate
+        );
+
+        // With confidence-stop, 0.26% is not tight enough for 0.1%
+        let strict = SampleOptions {
+            confidence_stop: true,
+            ..options
+        };
+        assert_eq!(next_step(&clean, &strict), SampleStep::Extend(3_000));
+        let loose = SampleOptions {
+            max_failure_rate: 0.01,
+            ..strict
+        };
+        assert_eq!(next_step(&clean, &loose), SampleStep::Stop);
+    }
+}
@@ -3,9 +3,16 @@
 //! This module provides scoring algorithms for ranking assets by metadata completeness
 //! and detecting conflicts between duplicate assets.
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::models::{AssetResponse, DuplicateGroup};
+use crate::camera::normalize_camera;
+use crate::intern::{Interner, SharedStr};
+use crate::models::{AssetResponse, AssetType, DuplicateGroup, MemoryResponse};
+use crate::review::{asset_api_url, asset_web_url, parse_capture_time, Decision, ReviewDecision};
 
 /// Weight values for metadata categories.
 /// Higher weights indicate more valuable metadata that's harder to recover.
@@ -18,9 +25,187 @@ mod weights {
     pub const LOCATION: u32 = 10; // Reverse-geocoded, derivable from GPS
 }
 
-/// GPS coordinate threshold for conflict detection.
-/// Approximately 11 meters at the equator.
-const GPS_THRESHOLD: f64 = 0.0001;
+/// Confidence penalties (out of 100) for evidence against a group being true duplicates.
+mod penalties {
+    pub const CAPTURE_TIME: u8 = 30; // Different moments are likely different photos
+    pub const GPS: u8 = 25; // Different places
+    pub const CAMERA_INFO: u8 = 20; // Different cameras
+    pub const ASPECT_RATIO: u8 = 20; // Different framing (crop or different shot)
+    pub const TIMEZONE: u8 = 10; // Usually an import/offset issue
+    pub const TIMEZONE_SHIFT: u8 = 10; // Same moment, different clock offset
+    pub const NO_CAPTURE_TIME: u8 = 15; // Nothing corroborates the match
+    pub const CORRUPT: u8 = 40; // Zero-byte original
+}
+
+/// Default confidence below which groups are flagged for review.
+const DEFAULT_LOW_CONFIDENCE: u8 = 50;
+
+/// Relative aspect-ratio difference above which dimensions are considered to disagree.
+const ASPECT_RATIO_TOLERANCE: f64 = 0.01;
+
+/// Videos within this many seconds of the longest copy count as full length.
+const VIDEO_DURATION_TOLERANCE_SECS: f64 = 0.5;
+
+/// Default GPS distance threshold for conflict detection, in meters.
+const DEFAULT_GPS_THRESHOLD_M: f64 = 11.0;
+
+/// Default capture-time tolerance for conflict detection, in seconds.
+const DEFAULT_CAPTURE_TIME_TOLERANCE_SECS: i64 = 60;
+
+/// Default size above which a file counts as large, in bytes (50 MiB).
+const DEFAULT_LARGE_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Seconds in an hour (used to recognize timezone shifts).
+const SECS_PER_HOUR: i64 = 3600;
+
+/// Largest whole-hour difference treated as a timezone shift (UTC-12 to UTC+14).
+const MAX_TIMEZONE_SHIFT_HOURS: i64 = 14;
+
+/// A 12-hour difference is more likely an AM/PM mix-up than a timezone shift.
+const AM_PM_SHIFT_HOURS: i64 = 12;
+
+/// Mean Earth radius in meters (used for haversine distance).
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// Tunable detection thresholds.
+///
+/// Shared by conflict detection, review flagging, and scenario detection
+/// ([`crate::testing::ScenarioContext`]), so they always agree. Also carries
+/// the policies for trashed and archived assets applied during analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thresholds {
+    /// Coordinates further apart than this many meters are a GPS conflict
+    pub gps_threshold_m: f64,
+
+    /// Capture times closer than this are not a conflict; differences within
+    /// this of a whole number of hours are reported as a timezone shift
+    pub capture_time_tolerance: Duration,
+
+    /// Groups below this confidence (0-100) are flagged for review
+    pub low_confidence: u8,
+
+    /// Files larger than this many bytes count as large
+    pub large_file_bytes: u64,
+
+    /// How groups with trashed assets are analyzed
+    pub trashed: TrashedPolicy,
+
+    /// How groups with archived assets are analyzed
+    pub archived: ArchivedPolicy,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            gps_threshold_m: DEFAULT_GPS_THRESHOLD_M,
+            capture_time_tolerance: Duration::seconds(DEFAULT_CAPTURE_TIME_TOLERANCE_SECS),
+            low_confidence: DEFAULT_LOW_CONFIDENCE,
+            large_file_bytes: DEFAULT_LARGE_FILE_BYTES,
+            trashed: TrashedPolicy::default(),
+            archived: ArchivedPolicy::default(),
+        }
+    }
+}
+
+/// Former name of [`Thresholds`], from when it only held conflict thresholds.
+#[deprecated(note = "renamed to `Thresholds`")]
+pub type ConflictOptions = Thresholds;
+
+/// How analysis treats groups with a trashed asset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrashedPolicy {
+    /// Score trashed assets like any other
+    #[default]
+    Score,
+    /// Mark the group skipped, so the executor leaves it untouched
+    Skip,
+    /// Leave trashed assets out of the group, as if already deleted
+    Ignore,
+}
+
+impl TrashedPolicy {
+    /// Short name of the policy (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Score => "score",
+            Self::Skip => "skip",
+            Self::Ignore => "ignore",
+        }
+    }
+}
+
+impl std::fmt::Display for TrashedPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for TrashedPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "score" => Ok(Self::Score),
+            "skip" => Ok(Self::Skip),
+            "ignore" => Ok(Self::Ignore),
+            other => Err(format!("Unknown trashed policy '{}' (expected score, skip, or ignore)", other)),
+        }
+    }
+}
+
+/// How analysis treats groups with an archived asset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArchivedPolicy {
+    /// Score archived assets like any other
+    #[default]
+    Score,
+    /// Mark the group skipped, so the executor leaves it untouched
+    Skip,
+    /// Never make an archived asset a loser: an archived asset wins, and a
+    /// group with more than one is marked skipped
+    Protect,
+}
+
+impl ArchivedPolicy {
+    /// Short name of the policy (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Score => "score",
+            Self::Skip => "skip",
+            Self::Protect => "protect",
+        }
+    }
+}
+
+impl std::fmt::Display for ArchivedPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ArchivedPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "score" => Ok(Self::Score),
+            "skip" => Ok(Self::Skip),
+            "protect" => Ok(Self::Protect),
+            other => Err(format!("Unknown archived policy '{}' (expected score, skip, or protect)", other)),
+        }
+    }
+}
+
+/// Great-circle distance between two (latitude, longitude) coordinates in meters.
+pub fn haversine_m((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
 
 /// Metadata completeness score for an asset.
 ///
@@ -129,23 +314,223 @@ pub enum MetadataConflict {
     /// Different timezones across duplicates
     Timezone {
         /// List of unique timezone values
-        values: Vec<String>,
+        values: Vec<SharedStr>,
     },
 
     /// Different camera make/model combinations across duplicates
     CameraInfo {
         /// List of unique camera identifiers
-        values: Vec<String>,
+        values: Vec<SharedStr>,
     },
 
     /// Different original capture times across duplicates
     CaptureTime {
         /// List of unique capture timestamps
-        values: Vec<String>,
+        values: Vec<SharedStr>,
+
+        /// How the difference was interpreted (e.g. how far apart the times are)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interpretation: Option<String>,
+    },
+
+    /// Capture times differ by a whole number of hours, most likely because
+    /// one copy was imported with the wrong timezone
+    TimezoneShift {
+        /// List of unique capture timestamps
+        values: Vec<SharedStr>,
+
+        /// Largest difference between the timestamps, in hours
+        hours: i64,
+    },
+}
+
+impl MetadataConflict {
+    /// Short snake_case name of the conflict type (matches the serialized tag).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Gps { .. } => "gps",
+            Self::Timezone { .. } => "timezone",
+            Self::CameraInfo { .. } => "camera_info",
+            Self::CaptureTime { .. } => "capture_time",
+            Self::TimezoneShift { .. } => "timezone_shift",
+        }
+    }
+
+    /// How much this conflict lowers a group's confidence score.
+    fn confidence_penalty(&self) -> u8 {
+        match self {
+            Self::Gps { .. } => penalties::GPS,
+            Self::Timezone { .. } => penalties::TIMEZONE,
+            Self::CameraInfo { .. } => penalties::CAMERA_INFO,
+            Self::CaptureTime { .. } => penalties::CAPTURE_TIME,
+            Self::TimezoneShift { .. } => penalties::TIMEZONE_SHIFT,
+        }
+    }
+}
+
+/// Why a group was flagged for manual review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewReason {
+    /// GPS coordinates differ beyond the threshold
+    GpsConflict,
+    /// Timezones differ
+    TimezoneConflict,
+    /// Camera make/model differ
+    CameraConflict,
+    /// Capture times differ beyond the tolerance
+    LargeTimeDelta,
+    /// Capture times differ by whole hours (likely a timezone offset)
+    TimezoneShift,
+    /// An original is zero bytes
+    CorruptOriginal,
+    /// Group mixes images and videos
+    MixedAssetTypes,
+    /// Group contains an external (read-only) library asset
+    ExternalLibraryAsset,
+    /// Assets belong to different users (e.g. a partner-shared library)
+    MixedOwners,
+    /// Confidence score is below the review threshold
+    LowConfidence,
+}
+
+impl ReviewReason {
+    /// The review reason raised by a metadata conflict.
+    pub fn from_conflict(conflict: &MetadataConflict) -> Self {
+        match conflict {
+            MetadataConflict::Gps { .. } => Self::GpsConflict,
+            MetadataConflict::Timezone { .. } => Self::TimezoneConflict,
+            MetadataConflict::CameraInfo { .. } => Self::CameraConflict,
+            MetadataConflict::CaptureTime { .. } => Self::LargeTimeDelta,
+            MetadataConflict::TimezoneShift { .. } => Self::TimezoneShift,
+        }
+    }
+
+    /// Short snake_case name (matches the serialized form).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GpsConflict => "gps_conflict",
+            Self::TimezoneConflict => "timezone_conflict",
+            Self::CameraConflict => "camera_conflict",
+            Self::LargeTimeDelta => "large_time_delta",
+            Self::TimezoneShift => "timezone_shift",
+            Self::CorruptOriginal => "corrupt_original",
+            Self::MixedAssetTypes => "mixed_asset_types",
+            Self::ExternalLibraryAsset => "external_library_asset",
+            Self::MixedOwners => "mixed_owners",
+            Self::LowConfidence => "low_confidence",
+        }
+    }
+}
+
+impl std::fmt::Display for ReviewReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Something about a group that changes how its winner and losers should be handled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SelectionWarning {
+    /// Assets live in different libraries, so deleting a loser may not
+    /// remove the same kind of copy the winner is
+    CrossLibrary {
+        /// Library of each distinct kind of copy, in group order (`None` for uploads)
+        library_ids: Vec<Option<String>>,
     },
+    /// Losers appear in memories (e.g. "On this day"), which lose them when
+    /// they are deleted
+    InMemories {
+        /// Losers in each memory, by memory ID
+        memories: BTreeMap<String, Vec<String>>,
+    },
+    /// Assets are already trashed or archived in Immich (see
+    /// [`TrashedPolicy`] and [`ArchivedPolicy`])
+    TrashedOrArchived {
+        /// Trashed assets, including any left out of the analysis
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        trashed: Vec<String>,
+        /// Archived assets
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        archived: Vec<String>,
+    },
+    /// Assets came without EXIF data (a slim response, or metadata not yet
+    /// extracted), so the winner was chosen on partial information (see
+    /// [`DuplicateGroup::hydrate`])
+    IncompleteData {
+        /// Assets without EXIF data
+        asset_ids: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for SelectionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CrossLibrary { library_ids } => {
+                let names: Vec<&str> = library_ids
+                    .iter()
+                    .map(|id| id.as_deref().unwrap_or("upload"))
+                    .collect();
+                write!(f, "assets span libraries: {}", names.join(", "))
+            }
+            Self::InMemories { memories } => write!(f, "losers appear in {} memories", memories.len()),
+            Self::TrashedOrArchived { trashed, archived } => {
+                write!(f, "{} trashed and {} archived assets", trashed.len(), archived.len())
+            }
+            Self::IncompleteData { asset_ids } => write!(f, "{} assets without EXIF data", asset_ids.len()),
+        }
+    }
+}
+
+/// Confidence (0–100) that a group's assets are true duplicates.
+///
+/// Byte-identical files (equal checksums) are certain. Otherwise confidence
+/// starts at 100 and is reduced for each conflict by severity, for differing
+/// aspect ratios, for zero-byte originals, and when no asset has a capture
+/// time to corroborate the match.
+pub fn confidence_score(
+    assets: &[AssetResponse],
+    conflicts: &[MetadataConflict],
+    has_corrupt: bool,
+) -> u8 {
+    let identical = assets
+        .split_first()
+        .is_some_and(|(first, rest)| rest.iter().all(|a| a.checksum == first.checksum));
+    if identical && !has_corrupt {
+        return 100;
+    }
+
+    let mut penalty: u32 = conflicts.iter().map(|c| u32::from(c.confidence_penalty())).sum();
+
+    if has_corrupt {
+        penalty += u32::from(penalties::CORRUPT);
+    }
+
+    let ratios: Vec<f64> = assets
+        .iter()
+        .filter_map(|a| a.exif_info.as_ref())
+        .filter_map(|e| e.display_dimensions())
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .map(|(w, h)| f64::from(w) / f64::from(h))
+        .collect();
+    let min_ratio = ratios.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_ratio = ratios.iter().copied().fold(0.0, f64::max);
+    if ratios.len() > 1 && (max_ratio - min_ratio) / min_ratio > ASPECT_RATIO_TOLERANCE {
+        penalty += u32::from(penalties::ASPECT_RATIO);
+    }
+
+    let any_capture_time = assets
+        .iter()
+        .any(|a| a.exif_info.as_ref().is_some_and(|e| e.date_time_original.is_some()));
+    if !any_capture_time {
+        penalty += u32::from(penalties::NO_CAPTURE_TIME);
+    }
+
+    100u32.saturating_sub(penalty) as u8
 }
 
-/// Detect metadata conflicts across a set of assets.
+/// Detect metadata conflicts across a set of assets using default thresholds.
 ///
 /// A conflict is detected when multiple assets have different values
 /// for the same metadata field. This helps identify cases where
@@ -159,7 +544,27 @@ pub enum MetadataConflict {
 ///
 /// A vector of detected conflicts (empty if no conflicts found)
 pub fn detect_conflicts(assets: &[AssetResponse]) -> Vec<MetadataConflict> {
+    detect_conflicts_with_options(assets, &Thresholds::default())
+}
+
+/// Detect metadata conflicts across a set of assets with custom thresholds.
+pub fn detect_conflicts_with_options(
+    assets: &[AssetResponse],
+    options: &Thresholds,
+) -> Vec<MetadataConflict> {
+    detect_conflicts_interned(assets, options, &mut Interner::new())
+}
+
+/// Detect metadata conflicts, sharing conflict values through `interner`.
+fn detect_conflicts_interned(
+    assets: &[AssetResponse],
+    options: &Thresholds,
+    interner: &mut Interner,
+) -> Vec<MetadataConflict> {
     let mut conflicts = Vec::new();
+    let mut intern_all = |values: Vec<String>| -> Vec<SharedStr> {
+        values.iter().map(|v| interner.intern(v)).collect()
+    };
 
     // Check GPS conflicts
     let gps_values: Vec<(f64, f64)> = assets
@@ -171,8 +576,8 @@ pub fn detect_conflicts(assets: &[AssetResponse]) -> Vec<MetadataConflict> {
         })
         .collect();
 
-    if has_gps_conflict(&gps_values) {
-        let unique_gps = dedupe_gps(&gps_values);
+    if has_gps_conflict(&gps_values, options.gps_threshold_m) {
+        let unique_gps = dedupe_gps(&gps_values, options.gps_threshold_m);
         conflicts.push(MetadataConflict::Gps { values: unique_gps });
     }
 
@@ -184,26 +589,24 @@ pub fn detect_conflicts(assets: &[AssetResponse]) -> Vec<MetadataConflict> {
         .collect();
 
     if let Some(unique) = find_unique_strings(&timezone_values) {
-        conflicts.push(MetadataConflict::Timezone { values: unique });
+        conflicts.push(MetadataConflict::Timezone { values: intern_all(unique) });
     }
 
-    // Check camera info conflicts
-    let camera_values: Vec<String> = assets
+    // Check camera info conflicts, comparing normalized make/model so that
+    // cosmetic differences ("NIKON CORPORATION NIKON D750" vs "Nikon D750") match
+    let camera_values: Vec<(String, String)> = assets
         .iter()
         .filter_map(|a| a.exif_info.as_ref())
         .filter_map(|e| {
+            let key = normalize_camera(e.make.as_deref(), e.model.as_deref())?;
             let make = e.make.as_deref().unwrap_or("");
             let model = e.model.as_deref().unwrap_or("");
-            if make.is_empty() && model.is_empty() {
-                None
-            } else {
-                Some(format!("{} {}", make, model).trim().to_string())
-            }
+            Some((format!("{} {}", make, model).trim().to_string(), key))
         })
         .collect();
 
-    if let Some(unique) = find_unique_strings(&camera_values) {
-        conflicts.push(MetadataConflict::CameraInfo { values: unique });
+    if let Some(unique) = find_unique_by_key(&camera_values) {
+        conflicts.push(MetadataConflict::CameraInfo { values: intern_all(unique) });
     }
 
     // Check capture time conflicts
@@ -214,23 +617,99 @@ pub fn detect_conflicts(assets: &[AssetResponse]) -> Vec<MetadataConflict> {
         .collect();
 
     if let Some(unique) = find_unique_strings(&capture_time_values) {
-        conflicts.push(MetadataConflict::CaptureTime { values: unique });
+        // Unparseable timestamps can't be compared, so they stay a plain conflict
+        let spread = unique
+            .iter()
+            .map(|v| parse_capture_time(v))
+            .collect::<Option<Vec<DateTime<Utc>>>>()
+            .map(|times| classify_time_spread(&times, options.capture_time_tolerance));
+
+        match spread {
+            Some(TimeSpread::WithinTolerance) => {}
+            Some(TimeSpread::WholeHours(hours)) => {
+                conflicts.push(MetadataConflict::TimezoneShift {
+                    values: intern_all(unique),
+                    hours,
+                });
+            }
+            Some(TimeSpread::Different(secs)) => {
+                conflicts.push(MetadataConflict::CaptureTime {
+                    values: intern_all(unique),
+                    interpretation: Some(format!("differ by up to {}", format_seconds(secs))),
+                });
+            }
+            None => conflicts.push(MetadataConflict::CaptureTime {
+                values: intern_all(unique),
+                interpretation: None,
+            }),
+        }
     }
 
     conflicts
 }
 
-/// Check if GPS coordinates have conflicts beyond the threshold.
-fn has_gps_conflict(coords: &[(f64, f64)]) -> bool {
+/// How far apart a set of capture times are.
+#[derive(Debug, PartialEq, Eq)]
+enum TimeSpread {
+    /// All times are within the tolerance of each other
+    WithinTolerance,
+    /// Times differ by (roughly) a whole number of hours, up to this many,
+    /// within the range of real-world UTC offsets
+    WholeHours(i64),
+    /// Times genuinely differ, by up to this many seconds
+    Different(i64),
+}
+
+/// Classify the largest pairwise difference between capture times.
+fn classify_time_spread(times: &[DateTime<Utc>], tolerance: Duration) -> TimeSpread {
+    let tolerance = tolerance.num_seconds().abs();
+    let mut max_diff = 0;
+    let mut whole_hours = true;
+
+    for (i, a) in times.iter().enumerate() {
+        for b in &times[i + 1..] {
+            let diff = (*a - *b).num_seconds().abs();
+            max_diff = max_diff.max(diff);
+
+            let remainder = diff % SECS_PER_HOUR;
+            if remainder.min(SECS_PER_HOUR - remainder) > tolerance {
+                whole_hours = false;
+            }
+        }
+    }
+
+    let hours = (max_diff + SECS_PER_HOUR / 2) / SECS_PER_HOUR;
+    let plausible_shift = hours <= MAX_TIMEZONE_SHIFT_HOURS && hours != AM_PM_SHIFT_HOURS;
+
+    if max_diff <= tolerance {
+        TimeSpread::WithinTolerance
+    } else if whole_hours && plausible_shift {
+        TimeSpread::WholeHours(hours)
+    } else {
+        TimeSpread::Different(max_diff)
+    }
+}
+
+/// Format a number of seconds as e.g. `2h 05m`, `3m 20s`, or `45s`.
+fn format_seconds(secs: i64) -> String {
+    if secs >= SECS_PER_HOUR {
+        format!("{}h {:02}m", secs / SECS_PER_HOUR, (secs % SECS_PER_HOUR) / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Check if any two GPS coordinates are further apart than `threshold_m` meters.
+pub fn has_gps_conflict(coords: &[(f64, f64)], threshold_m: f64) -> bool {
     if coords.len() < 2 {
         return false;
     }
 
     for i in 0..coords.len() {
         for j in (i + 1)..coords.len() {
-            let (lat1, lon1) = coords[i];
-            let (lat2, lon2) = coords[j];
-            if (lat1 - lat2).abs() > GPS_THRESHOLD || (lon1 - lon2).abs() > GPS_THRESHOLD {
+            if haversine_m(coords[i], coords[j]) > threshold_m {
                 return true;
             }
         }
@@ -239,17 +718,15 @@ fn has_gps_conflict(coords: &[(f64, f64)]) -> bool {
     false
 }
 
-/// Deduplicate GPS coordinates within threshold.
-fn dedupe_gps(coords: &[(f64, f64)]) -> Vec<(f64, f64)> {
+/// Deduplicate GPS coordinates within `threshold_m` meters.
+fn dedupe_gps(coords: &[(f64, f64)], threshold_m: f64) -> Vec<(f64, f64)> {
     let mut unique: Vec<(f64, f64)> = Vec::new();
 
-    for &(lat, lon) in coords {
-        let is_duplicate = unique.iter().any(|&(ulat, ulon)| {
-            (lat - ulat).abs() <= GPS_THRESHOLD && (lon - ulon).abs() <= GPS_THRESHOLD
-        });
+    for &coord in coords {
+        let is_duplicate = unique.iter().any(|&u| haversine_m(coord, u) <= threshold_m);
 
         if !is_duplicate {
-            unique.push((lat, lon));
+            unique.push(coord);
         }
     }
 
@@ -259,18 +736,23 @@ fn dedupe_gps(coords: &[(f64, f64)]) -> Vec<(f64, f64)> {
 /// Find unique string values (case-insensitive, trimmed).
 /// Returns None if there are 0 or 1 unique values.
 fn find_unique_strings(values: &[String]) -> Option<Vec<String>> {
-    if values.is_empty() {
-        return None;
-    }
+    let keyed: Vec<(String, String)> = values
+        .iter()
+        .map(|v| (v.trim().to_string(), v.trim().to_lowercase()))
+        .collect();
+    find_unique_by_key(&keyed)
+}
 
-    let mut seen: Vec<String> = Vec::new();
+/// Find values with unique comparison keys, given `(display, key)` pairs.
+/// Returns the first display value for each key, or None if there are 0 or 1.
+fn find_unique_by_key(values: &[(String, String)]) -> Option<Vec<String>> {
+    let mut seen: Vec<&str> = Vec::new();
     let mut unique_original: Vec<String> = Vec::new();
 
-    for value in values {
-        let normalized = value.trim().to_lowercase();
-        if !normalized.is_empty() && !seen.contains(&normalized) {
-            seen.push(normalized);
-            unique_original.push(value.trim().to_string());
+    for (display, key) in values {
+        if !key.is_empty() && !seen.contains(&key.as_str()) {
+            seen.push(key);
+            unique_original.push(display.clone());
         }
     }
 
@@ -281,14 +763,82 @@ fn find_unique_strings(values: &[String]) -> Option<Vec<String>> {
     }
 }
 
+/// How the winner of a duplicate group is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WinnerStrategy {
+    /// Best quality: largest dimensions, then largest file; for groups of
+    /// only videos, full duration, then largest file. What
+    /// [`DuplicateAnalysis`] uses.
+    #[default]
+    Quality,
+
+    /// Highest metadata score, with quality breaking ties.
+    Metadata,
+}
+
+/// Order a group's assets from winner to last loser.
+///
+/// Returns indices into `assets`. Sorts are stable, so assets that rank
+/// the same keep their order in the group and the first of them wins.
+pub fn rank_assets(assets: &[AssetResponse], strategy: WinnerStrategy) -> Vec<usize> {
+    let file_size = |a: &AssetResponse| a.exif_info.as_ref().and_then(|e| e.file_size_in_byte).unwrap_or(0);
+    let mut order: Vec<usize> = (0..assets.len()).collect();
+
+    if !assets.is_empty() && assets.iter().all(|a| a.asset_type == AssetType::Video) {
+        // Videos: full-length copies first (longest duration), then largest file.
+        // Durations within the tolerance of the longest count as full length, so
+        // container rounding doesn't let a smaller re-encode win.
+        let durations: Vec<Option<f64>> = assets.iter().map(|a| a.duration_seconds()).collect();
+        let longest = durations.iter().flatten().copied().fold(0.0, f64::max);
+        let full_length = |i: usize| durations[i].is_some_and(|d| d >= longest - VIDEO_DURATION_TOLERANCE_SECS);
+        order.sort_by(|&a, &b| {
+            full_length(b)
+                .cmp(&full_length(a))
+                .then_with(|| {
+                    // Among truncated copies, longer is better
+                    if full_length(a) {
+                        std::cmp::Ordering::Equal
+                    } else {
+                        durations[b].unwrap_or(0.0).total_cmp(&durations[a].unwrap_or(0.0))
+                    }
+                })
+                .then_with(|| file_size(&assets[b]).cmp(&file_size(&assets[a])))
+        });
+    } else {
+        // Largest dimensions (width × height), then larger file
+        let pixels = |a: &AssetResponse| {
+            a.exif_info
+                .as_ref()
+                .and_then(|e| e.display_dimensions())
+                .map(|(w, h)| u64::from(w) * u64::from(h))
+                .unwrap_or(0)
+        };
+        order.sort_by_key(|&i| std::cmp::Reverse((pixels(&assets[i]), file_size(&assets[i]))));
+    }
+
+    if strategy == WinnerStrategy::Metadata {
+        order.sort_by_key(|&i| std::cmp::Reverse(MetadataScore::from_asset(&assets[i]).total));
+    }
+    order
+}
+
 /// A scored asset with metadata score and file information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScoredAsset {
     /// Asset unique identifier
-    pub asset_id: String,
+    pub asset_id: SharedStr,
 
     /// Original filename
-    pub filename: String,
+    pub filename: SharedStr,
+
+    /// Immich checksum (base64 SHA-1), recorded in the deletion ledger and
+    /// compared at execute time to detect a stale analysis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<SharedStr>,
+
+    /// When the asset last changed on the server, as of the analysis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<SharedStr>,
 
     /// Metadata completeness score (used for consolidation decisions)
     pub score: MetadataScore,
@@ -296,8 +846,89 @@ pub struct ScoredAsset {
     /// File size in bytes (secondary tiebreaker)
     pub file_size: Option<u64>,
 
-    /// Image dimensions (width, height) in pixels - primary selection criteria
+    /// Image dimensions (width, height) in pixels as displayed (orientation
+    /// applied) - primary selection criteria
     pub dimensions: Option<(u32, u32)>,
+
+    /// Resolution in megapixels, to one decimal place (from `dimensions`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub megapixels: Option<f64>,
+
+    /// Whether the asset lives in an external (read-only) library
+    #[serde(default)]
+    pub is_external: bool,
+
+    /// Library the asset was imported from (`None` for uploads)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_id: Option<SharedStr>,
+
+    /// User who owns the asset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<SharedStr>,
+
+    /// Original capture time from EXIF (used for review clustering)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_time: Option<SharedStr>,
+
+    /// GPS coordinates (latitude, longitude) if present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gps: Option<(f64, f64)>,
+
+    /// Description (caption) from EXIF (used for search)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<SharedStr>,
+
+    /// Reverse-geocoded city (used for search)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub city: Option<SharedStr>,
+
+    /// Reverse-geocoded country (used for search)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<SharedStr>,
+
+    /// Camera make and model as written in EXIF (used for search)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera: Option<SharedStr>,
+
+    /// Duration in seconds (videos only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+
+    /// Frame rate in frames per second (videos only, if reported)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+
+    /// Link to the asset in the Immich web UI (set with [`DuplicateAnalysis::set_urls`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_url: Option<String>,
+
+    /// The asset's API endpoint (set with [`DuplicateAnalysis::set_urls`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+}
+
+impl ScoredAsset {
+    /// Displayed width in pixels.
+    pub fn width(&self) -> Option<u32> {
+        self.dimensions.map(|(w, _)| w)
+    }
+
+    /// Displayed height in pixels.
+    pub fn height(&self) -> Option<u32> {
+        self.dimensions.map(|(_, h)| h)
+    }
+
+    /// Fill in the web UI and API URLs for the asset on `server_url`.
+    pub fn set_urls(&mut self, server_url: &str) {
+        self.web_url = Some(asset_web_url(server_url, &self.asset_id));
+        self.api_url = Some(asset_api_url(server_url, &self.asset_id));
+    }
+}
+
+/// Resolution in megapixels, to one decimal place.
+pub fn megapixels((width, height): (u32, u32)) -> f64 {
+    let megapixels = f64::from(width) * f64::from(height) / 1_000_000.0;
+    (megapixels * 10.0).round() / 10.0
 }
 
 /// Analysis result for a duplicate group.
@@ -318,18 +949,98 @@ pub struct DuplicateAnalysis {
     /// Detected metadata conflicts
     pub conflicts: Vec<MetadataConflict>,
 
-    /// Whether manual review is recommended due to conflicts
+    /// Whether manual review is recommended (true when `review_reasons` is non-empty)
     pub needs_review: bool,
+
+    /// Why the group needs review
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub review_reasons: Vec<ReviewReason>,
+
+    /// Reviewer decision for this group (set via `immich-dupes review`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<ReviewDecision>,
+
+    /// Assets whose original is zero bytes according to Immich
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub corrupt_asset_ids: Vec<String>,
+
+    /// Confidence (0–100) that the assets are true duplicates
+    ///
+    /// Analyses written before this field existed load as 0.
+    #[serde(default)]
+    pub confidence: u8,
+
+    /// Warnings about the winner selection
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<SelectionWarning>,
 }
 
 impl DuplicateAnalysis {
+    /// Returns true if any asset in the group lives in an external library.
+    pub fn has_external_assets(&self) -> bool {
+        self.winner.is_external || self.losers.iter().any(|l| l.is_external)
+    }
+
+    /// Returns true if the group's assets live in more than one library.
+    pub fn is_cross_library(&self) -> bool {
+        self.losers.iter().any(|l| l.library_id != self.winner.library_id)
+    }
+
+    /// Warn if any loser appears in one of `memories`.
+    ///
+    /// Memories come from the server rather than the duplicate group, so this
+    /// is a separate step after analysis. Any earlier memory warning is replaced.
+    pub fn flag_memories(&mut self, memories: &[MemoryResponse]) {
+        self.warnings.retain(|w| !matches!(w, SelectionWarning::InMemories { .. }));
+        let mut found = BTreeMap::new();
+        for memory in memories {
+            let losers: Vec<String> = self
+                .losers
+                .iter()
+                .filter(|l| memory.assets.iter().any(|a| a.id == *l.asset_id))
+                .map(|l| l.asset_id.to_string())
+                .collect();
+            if !losers.is_empty() {
+                found.insert(memory.id.clone(), losers);
+            }
+        }
+        if !found.is_empty() {
+            self.warnings.push(SelectionWarning::InMemories { memories: found });
+        }
+    }
+
+    /// Losers in each memory, by memory ID, as flagged by [`Self::flag_memories`].
+    pub fn loser_memories(&self) -> Option<&BTreeMap<String, Vec<String>>> {
+        self.warnings.iter().find_map(|w| match w {
+            SelectionWarning::InMemories { memories } => Some(memories),
+            _ => None,
+        })
+    }
+
+    /// Returns true if the group's assets belong to more than one user.
+    ///
+    /// Assets with no recorded owner (older analyses) never count as a mismatch.
+    pub fn is_cross_owner(&self) -> bool {
+        let Some(owner) = &self.winner.owner_id else {
+            return false;
+        };
+        self.losers
+            .iter()
+            .any(|l| l.owner_id.as_ref().is_some_and(|o| o != owner))
+    }
+
     /// Analyze a duplicate group and select a winner.
     ///
-    /// The winner is selected based on:
+    /// The winner is selected by [`rank_assets`] with
+    /// [`WinnerStrategy::Quality`]:
     /// 1. Largest dimensions (width × height pixels) - best quality
     /// 2. Largest file size (tiebreaker)
     /// 3. First in list (stable sort, final tiebreaker)
     ///
+    /// Groups made up entirely of videos instead prefer the longest duration
+    /// (so a truncated re-encode never beats the original), then the largest
+    /// file.
+    ///
     /// Metadata scores are still calculated and stored for consolidation decisions.
     ///
     /// # Arguments
@@ -340,53 +1051,172 @@ impl DuplicateAnalysis {
     ///
     /// Analysis result with winner, losers, and conflict information
     pub fn from_group(group: &DuplicateGroup) -> Self {
-        // Score all assets and capture dimensions
-        let mut scored: Vec<ScoredAsset> = group
-            .assets
+        Self::from_group_with_options(group, &Thresholds::default())
+    }
+
+    /// Analyze a duplicate group using custom detection thresholds.
+    pub fn from_group_with_options(group: &DuplicateGroup, options: &Thresholds) -> Self {
+        Self::from_group_interned(group, options, &mut Interner::new())
+    }
+
+    /// Analyze many groups, storing each repeated string (camera, place,
+    /// timezone, ...) once across the whole batch.
+    ///
+    /// Produces the same analyses as calling [`Self::from_group_with_options`]
+    /// on each group, in a fraction of the memory for large libraries.
+    pub fn from_groups_with_options(groups: &[DuplicateGroup], options: &Thresholds) -> Vec<Self> {
+        let mut interner = Interner::new();
+        groups
             .iter()
-            .map(|asset| {
-                let dimensions = asset.exif_info.as_ref().and_then(|e| {
-                    match (e.exif_image_width, e.exif_image_height) {
-                        (Some(w), Some(h)) => Some((w, h)),
-                        _ => None,
-                    }
-                });
+            .map(|group| Self::from_group_interned(group, options, &mut interner))
+            .collect()
+    }
+
+    fn from_group_interned(group: &DuplicateGroup, options: &Thresholds, interner: &mut Interner) -> Self {
+        // Trashed assets may be left out, unless that would leave nothing
+        let assets: Cow<[AssetResponse]> = if options.trashed == TrashedPolicy::Ignore
+            && group.assets.iter().any(|a| a.is_trashed)
+            && !group.assets.iter().all(|a| a.is_trashed)
+        {
+            Cow::Owned(group.assets.iter().filter(|a| !a.is_trashed).cloned().collect())
+        } else {
+            Cow::Borrowed(&group.assets)
+        };
+
+        // Archived assets may be ranked ahead of the rest, so they never lose
+        let mut order = rank_assets(&assets, WinnerStrategy::Quality);
+        if options.archived == ArchivedPolicy::Protect {
+            order.sort_by_key(|&index| !assets[index].archived());
+        }
+
+        // Score all assets and capture dimensions, winner first
+        let mut scored: Vec<ScoredAsset> = order
+            .into_iter()
+            .map(|index| {
+                let asset = &assets[index];
+                let exif = asset.exif_info.as_ref();
+                let is_video = asset.asset_type == AssetType::Video;
+                let dimensions = exif.and_then(|e| e.display_dimensions());
                 ScoredAsset {
-                    asset_id: asset.id.clone(),
-                    filename: asset.original_file_name.clone(),
+                    asset_id: SharedStr::from(&asset.id),
+                    filename: SharedStr::from(&asset.original_file_name),
+                    checksum: Some(asset.checksum.as_str())
+                        .filter(|c| !c.is_empty())
+                        .map(SharedStr::from),
+                    updated_at: asset.updated_at.as_deref().map(SharedStr::from),
                     score: MetadataScore::from_asset(asset),
                     file_size: asset.exif_info.as_ref().and_then(|e| e.file_size_in_byte),
                     dimensions,
+                    megapixels: dimensions.map(megapixels),
+                    is_external: asset.is_external_library(),
+                    library_id: asset.library_id.as_deref().map(|id| interner.intern(id)),
+                    owner_id: Some(asset.owner_id.as_str())
+                        .filter(|o| !o.is_empty())
+                        .map(|o| interner.intern(o)),
+                    capture_time: asset
+                        .exif_info
+                        .as_ref()
+                        .and_then(|e| e.date_time_original.as_deref())
+                        .map(|t| interner.intern(t)),
+                    gps: asset.exif_info.as_ref().and_then(|e| {
+                        match (e.latitude, e.longitude) {
+                            (Some(lat), Some(lon)) => Some((lat, lon)),
+                            _ => None,
+                        }
+                    }),
+                    description: exif
+                        .and_then(|e| e.description.as_deref())
+                        .filter(|d| !d.trim().is_empty())
+                        .map(|d| interner.intern(d)),
+                    city: exif.and_then(|e| e.city.as_deref()).map(|c| interner.intern(c)),
+                    country: exif.and_then(|e| e.country.as_deref()).map(|c| interner.intern(c)),
+                    camera: exif.and_then(|e| {
+                        let make = e.make.as_deref().unwrap_or("");
+                        let model = e.model.as_deref().unwrap_or("");
+                        Some(format!("{} {}", make, model))
+                            .filter(|c| !c.trim().is_empty())
+                            .map(|c| interner.intern(c.trim()))
+                    }),
+                    duration: is_video.then(|| asset.duration_seconds()).flatten(),
+                    fps: exif.and_then(|e| e.fps).filter(|_| is_video),
+                    web_url: None,
+                    api_url: None,
                 }
             })
             .collect();
 
-        // Sort by dimensions (pixels) descending, then file size descending (stable sort)
-        scored.sort_by(|a, b| {
-            // Primary: largest dimensions (width × height)
-            let pixels_a = a
-                .dimensions
-                .map(|(w, h)| u64::from(w) * u64::from(h))
-                .unwrap_or(0);
-            let pixels_b = b
-                .dimensions
-                .map(|(w, h)| u64::from(w) * u64::from(h))
-                .unwrap_or(0);
-
-            match pixels_b.cmp(&pixels_a) {
-                std::cmp::Ordering::Equal => {
-                    // Secondary: larger file size wins
-                    let size_a = a.file_size.unwrap_or(0);
-                    let size_b = b.file_size.unwrap_or(0);
-                    size_b.cmp(&size_a)
-                }
-                other => other,
+        // Detect conflicts
+        let conflicts = detect_conflicts_interned(&assets, options, interner);
+
+        // A zero-byte original means the group can't be resolved safely
+        let corrupt_asset_ids: Vec<String> = scored
+            .iter()
+            .filter(|a| a.file_size == Some(0))
+            .map(|a| a.asset_id.to_string())
+            .collect();
+
+        let confidence = confidence_score(&assets, &conflicts, !corrupt_asset_ids.is_empty());
+
+        let mut review_reasons: Vec<ReviewReason> = Vec::new();
+        let mut flag = |reason: ReviewReason| {
+            if !review_reasons.contains(&reason) {
+                review_reasons.push(reason);
             }
-        });
+        };
+        conflicts.iter().map(ReviewReason::from_conflict).for_each(&mut flag);
+        if !corrupt_asset_ids.is_empty() {
+            flag(ReviewReason::CorruptOriginal);
+        }
+        if assets.iter().any(|a| a.asset_type != assets[0].asset_type) {
+            flag(ReviewReason::MixedAssetTypes);
+        }
+        if assets.iter().any(|a| a.is_external_library()) {
+            flag(ReviewReason::ExternalLibraryAsset);
+        }
+        let mut owners = assets.iter().map(|a| a.owner_id.as_str()).filter(|o| !o.is_empty());
+        if let Some(first) = owners.next()
+            && owners.any(|o| o != first)
+        {
+            flag(ReviewReason::MixedOwners);
+        }
+        if confidence < options.low_confidence {
+            flag(ReviewReason::LowConfidence);
+        }
+        let needs_review = !review_reasons.is_empty();
 
-        // Detect conflicts
-        let conflicts = detect_conflicts(&group.assets);
-        let needs_review = !conflicts.is_empty();
+        let mut library_ids: Vec<Option<String>> = Vec::new();
+        for asset in assets.iter() {
+            if !library_ids.contains(&asset.library_id) {
+                library_ids.push(asset.library_id.clone());
+            }
+        }
+        let mut warnings = Vec::new();
+        if library_ids.len() > 1 {
+            warnings.push(SelectionWarning::CrossLibrary { library_ids });
+        }
+
+        let trashed: Vec<String> = group.assets.iter().filter(|a| a.is_trashed).map(|a| a.id.clone()).collect();
+        let archived: Vec<String> = assets.iter().filter(|a| a.archived()).map(|a| a.id.clone()).collect();
+        let skip_reason = if options.trashed == TrashedPolicy::Skip && !trashed.is_empty() {
+            Some("Group has a trashed asset (trashed policy: skip)")
+        } else if options.archived == ArchivedPolicy::Skip && !archived.is_empty() {
+            Some("Group has an archived asset (archived policy: skip)")
+        } else if options.archived == ArchivedPolicy::Protect && archived.len() > 1 {
+            Some("Group has more than one archived asset (archived policy: protect)")
+        } else {
+            None
+        };
+        let decision = skip_reason.map(|reason| ReviewDecision {
+            note: Some(reason.to_string()),
+            ..Decision::Skip.into()
+        });
+        if !trashed.is_empty() || !archived.is_empty() {
+            warnings.push(SelectionWarning::TrashedOrArchived { trashed, archived });
+        }
+        let incomplete: Vec<String> = assets.iter().filter(|a| !a.has_exif()).map(|a| a.id.clone()).collect();
+        if !incomplete.is_empty() {
+            warnings.push(SelectionWarning::IncompleteData { asset_ids: incomplete });
+        }
 
         // Split into winner and losers
         let winner = scored.remove(0);
@@ -398,7 +1228,63 @@ impl DuplicateAnalysis {
             losers,
             conflicts,
             needs_review,
+            review_reasons,
+            decision,
+            corrupt_asset_ids,
+            confidence,
+            warnings,
+        }
+    }
+
+    /// Returns true if any asset was analyzed without its EXIF data.
+    pub fn has_incomplete_data(&self) -> bool {
+        self.warnings.iter().any(|w| matches!(w, SelectionWarning::IncompleteData { .. }))
+    }
+
+    /// Returns true if the asset ID belongs to this group.
+    pub fn contains_asset(&self, asset_id: &str) -> bool {
+        self.winner.asset_id == asset_id || self.losers.iter().any(|l| l.asset_id == asset_id)
+    }
+
+    /// Fill in every asset's web UI and API URLs for `server_url`, so
+    /// reports and review tools can link straight to them.
+    pub fn set_urls(&mut self, server_url: &str) {
+        for asset in std::iter::once(&mut self.winner).chain(&mut self.losers) {
+            asset.set_urls(server_url);
+        }
+    }
+
+    /// Promote a loser to winner, demoting the current winner to a loser.
+    ///
+    /// Returns false if the asset is not a loser in this group.
+    pub fn set_winner(&mut self, asset_id: &str) -> bool {
+        if self.winner.asset_id == asset_id {
+            return true;
         }
+        let Some(index) = self.losers.iter().position(|l| l.asset_id == asset_id) else {
+            return false;
+        };
+        std::mem::swap(&mut self.winner, &mut self.losers[index]);
+        true
+    }
+
+    /// Returns true if this group should be processed by the executor.
+    ///
+    /// An explicit reviewer decision always wins; otherwise groups that need
+    /// review are only processed when `skip_review` is false.
+    pub fn should_execute(&self, skip_review: bool) -> bool {
+        match self.decision.as_ref().map(|d| d.decision) {
+            Some(Decision::Accept) => true,
+            Some(Decision::Skip) => false,
+            None => !(skip_review && self.needs_review),
+        }
+    }
+
+    /// Returns true if this group may be processed without review at `min_confidence`.
+    ///
+    /// Groups with an explicit reviewer decision are not held back by confidence.
+    pub fn meets_confidence(&self, min_confidence: u8) -> bool {
+        self.decision.is_some() || self.confidence >= min_confidence
     }
 }
 
@@ -406,6 +1292,115 @@ impl DuplicateAnalysis {
 mod tests {
     use super::*;
 
+    /// Build a minimal asset from JSON, merging `extra` fields into the defaults.
+    fn asset_json(id: &str, extra: serde_json::Value) -> AssetResponse {
+        let mut value = serde_json::json!({
+            "id": id,
+            "originalFileName": format!("{}.jpg", id),
+            "fileCreatedAt": "2024-01-01T00:00:00Z",
+            "localDateTime": "2024-01-01T00:00:00",
+            "type": "IMAGE",
+            "exifInfo": null,
+            "checksum": "abc",
+            "isTrashed": false,
+            "isFavorite": false,
+            "isArchived": false,
+            "hasMetadata": true,
+            "duration": "0:00:00.000000",
+            "ownerId": "owner-1"
+        });
+        if let (Some(base), Some(extra)) = (value.as_object_mut(), extra.as_object()) {
+            for (k, v) in extra {
+                base.insert(k.clone(), v.clone());
+            }
+        }
+        serde_json::from_value(value).expect("valid asset JSON")
+    }
+
+    #[test]
+    fn test_external_library_assets_flagged() {
+        let group = DuplicateGroup {
+            duplicate_id: "dup-1".to_string(),
+            assets: vec![
+                asset_json("uploaded", serde_json::json!({})),
+                asset_json("external", serde_json::json!({ "libraryId": "lib-1" })),
+            ],
+            extra: Default::default(),
+        };
+
+        let analysis = DuplicateAnalysis::from_group(&group);
+        assert!(analysis.has_external_assets());
+        let external = analysis
+            .losers
+            .iter()
+            .chain(std::iter::once(&analysis.winner))
+            .find(|a| a.asset_id == "external")
+            .unwrap();
+        assert!(external.is_external);
+    }
+
+    #[test]
+    fn test_zero_byte_original_needs_review() {
+        let group = DuplicateGroup {
+            duplicate_id: "dup-1".to_string(),
+            assets: vec![
+                asset_json("good", serde_json::json!({ "exifInfo": { "fileSizeInByte": 1024 } })),
+                asset_json("empty", serde_json::json!({ "exifInfo": { "fileSizeInByte": 0 } })),
+            ],
+            extra: Default::default(),
+        };
+
+        let analysis = DuplicateAnalysis::from_group(&group);
+        assert!(analysis.conflicts.is_empty());
+        assert!(analysis.needs_review);
+        assert_eq!(analysis.corrupt_asset_ids, vec!["empty".to_string()]);
+    }
+
+    #[test]
+    fn test_trashed_and_archived_policies() {
+        let gps = serde_json::json!({ "latitude": 51.5, "longitude": -0.12 });
+        let group = DuplicateGroup {
+            duplicate_id: "dup-1".to_string(),
+            assets: vec![
+                asset_json("trashed", serde_json::json!({ "isTrashed": true, "exifInfo": gps })),
+                asset_json("archived", serde_json::json!({ "isArchived": true })),
+                asset_json("plain", serde_json::json!({})),
+            ],
+            extra: Default::default(),
+        };
+        let analyze = |trashed, archived| {
+            let options = Thresholds {
+                trashed,
+                archived,
+                ..Default::default()
+            };
+            DuplicateAnalysis::from_group_with_options(&group, &options)
+        };
+
+        // By default both are scored, and reported
+        let scored = analyze(TrashedPolicy::Score, ArchivedPolicy::Score);
+        assert_eq!(scored.winner.asset_id, "trashed");
+        assert!(scored.should_execute(false));
+        assert!(scored.warnings.contains(&SelectionWarning::TrashedOrArchived {
+            trashed: vec!["trashed".to_string()],
+            archived: vec!["archived".to_string()],
+        }));
+
+        let skipped = analyze(TrashedPolicy::Skip, ArchivedPolicy::Score);
+        assert!(!skipped.should_execute(false));
+        assert!(skipped.decision.unwrap().note.unwrap().contains("trashed policy"));
+
+        // Ignored trashed assets are left out, but still reported
+        let ignored = analyze(TrashedPolicy::Ignore, ArchivedPolicy::Score);
+        assert_eq!(ignored.losers.len(), 1);
+        assert!(!ignored.contains_asset("trashed"));
+        assert!(ignored.should_execute(false));
+
+        let protected = analyze(TrashedPolicy::Ignore, ArchivedPolicy::Protect);
+        assert_eq!(protected.winner.asset_id, "archived");
+        assert_eq!(protected.losers[0].asset_id, "plain");
+    }
+
     #[test]
     fn test_metadata_score_default() {
         let score = MetadataScore::default();
@@ -416,11 +1411,265 @@ mod tests {
     fn test_gps_conflict_detection() {
         // Same coordinates within threshold
         let coords = vec![(51.5074, -0.1278), (51.5074, -0.1278)];
-        assert!(!has_gps_conflict(&coords));
+        assert!(!has_gps_conflict(&coords, DEFAULT_GPS_THRESHOLD_M));
 
         // Different coordinates beyond threshold
         let coords = vec![(51.5074, -0.1278), (52.0, -0.5)];
-        assert!(has_gps_conflict(&coords));
+        assert!(has_gps_conflict(&coords, DEFAULT_GPS_THRESHOLD_M));
+    }
+
+    fn exif_time(id: &str, time: &str) -> AssetResponse {
+        asset_json(id, serde_json::json!({ "exifInfo": { "dateTimeOriginal": time } }))
+    }
+
+    fn capture_time_interpretation(conflicts: &[MetadataConflict]) -> Option<Option<String>> {
+        conflicts.iter().find_map(|c| match c {
+            MetadataConflict::CaptureTime { interpretation, .. } => Some(interpretation.clone()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_capture_time_within_tolerance_not_conflict() {
+        let assets = vec![
+            exif_time("a", "2024-06-15T10:00:00.000Z"),
+            exif_time("b", "2024-06-15T10:00:02.000Z"),
+        ];
+        assert!(detect_conflicts(&assets).is_empty());
+    }
+
+    #[test]
+    fn test_capture_time_whole_hour_offset_is_timezone_shift() {
+        let assets = vec![
+            exif_time("a", "2024-06-15T10:00:00.000Z"),
+            exif_time("b", "2024-06-15T12:00:01.000Z"),
+        ];
+        let conflicts = detect_conflicts(&assets);
+        assert_eq!(conflicts.len(), 1);
+        assert!(matches!(
+            conflicts[0],
+            MetadataConflict::TimezoneShift { hours: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_twelve_hour_offset_is_capture_time_conflict() {
+        let assets = vec![
+            exif_time("a", "2024-01-15T08:00:00+00:00"),
+            exif_time("b", "2024-01-15T20:00:00+00:00"),
+        ];
+        let interpretation = capture_time_interpretation(&detect_conflicts(&assets));
+        assert_eq!(interpretation, Some(Some("differ by up to 12h 00m".to_string())));
+    }
+
+    #[test]
+    fn test_capture_time_tolerance_is_configurable() {
+        let assets = vec![
+            exif_time("a", "2024-06-15T10:00:00.000Z"),
+            exif_time("b", "2024-06-15T10:03:20.000Z"),
+        ];
+        let interpretation = capture_time_interpretation(&detect_conflicts(&assets));
+        assert_eq!(interpretation, Some(Some("differ by up to 3m 20s".to_string())));
+
+        let options = Thresholds {
+            capture_time_tolerance: Duration::minutes(5),
+            ..Default::default()
+        };
+        assert!(detect_conflicts_with_options(&assets, &options).is_empty());
+    }
+
+    #[test]
+    fn test_camera_cosmetic_difference_not_conflict() {
+        let camera = |id: &str, make: &str, model: &str| {
+            asset_json(id, serde_json::json!({ "exifInfo": { "make": make, "model": model } }))
+        };
+
+        let same = vec![
+            camera("a", "NIKON CORPORATION", "NIKON D750"),
+            camera("b", "Nikon", "D750"),
+        ];
+        assert!(detect_conflicts(&same).is_empty());
+
+        let different = vec![camera("a", "NIKON CORPORATION", "NIKON D750"), camera("b", "Canon", "EOS R5")];
+        assert!(matches!(
+            detect_conflicts(&different).as_slice(),
+            [MetadataConflict::CameraInfo { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_confidence_score() {
+        let photo = |id: &str, checksum: &str, time: &str, w: u32, h: u32| {
+            asset_json(
+                id,
+                serde_json::json!({
+                    "checksum": checksum,
+                    "exifInfo": { "dateTimeOriginal": time, "exifImageWidth": w, "exifImageHeight": h }
+                }),
+            )
+        };
+        let score = |assets: Vec<AssetResponse>| {
+            DuplicateAnalysis::from_group(&DuplicateGroup {
+                duplicate_id: "dup-1".to_string(),
+                assets,
+                extra: Default::default(),
+            })
+            .confidence
+        };
+
+        // Byte-identical files are certain
+        assert_eq!(score(vec![asset_json("a", serde_json::json!({})), asset_json("b", serde_json::json!({}))]), 100);
+
+        // Agreeing metadata and a resized copy
+        let t = "2024-06-15T10:00:00Z";
+        assert_eq!(score(vec![photo("a", "x", t, 4000, 3000), photo("b", "y", t, 2000, 1500)]), 100);
+
+        // Capture-time conflict and a crop
+        let later = "2024-06-15T10:07:00Z";
+        assert_eq!(score(vec![photo("a", "x", t, 4000, 3000), photo("b", "y", later, 3000, 3000)]), 50);
+
+        // Nothing corroborates the match
+        let bare = |id: &str, checksum: &str| asset_json(id, serde_json::json!({ "checksum": checksum }));
+        assert_eq!(score(vec![bare("a", "x"), bare("b", "y")]), 85);
+    }
+
+    #[test]
+    fn test_meets_confidence_respects_decision() {
+        let group = DuplicateGroup {
+            duplicate_id: "dup-1".to_string(),
+            assets: vec![
+                asset_json("a", serde_json::json!({ "checksum": "x" })),
+                asset_json("b", serde_json::json!({ "checksum": "y" })),
+            ],
+            extra: Default::default(),
+        };
+        let mut analysis = DuplicateAnalysis::from_group(&group);
+        assert!(!analysis.meets_confidence(90));
+        assert!(analysis.meets_confidence(80));
+
+        analysis.decision = Some(Decision::Accept.into());
+        assert!(analysis.meets_confidence(90));
+    }
+
+    #[test]
+    fn test_review_reasons() {
+        let video = asset_json("v", serde_json::json!({ "type": "VIDEO", "libraryId": "lib-1" }));
+        let group = DuplicateGroup {
+            duplicate_id: "dup-1".to_string(),
+            assets: vec![
+                exif_time("a", "2024-06-15T10:00:00Z"),
+                exif_time("b", "2024-06-15T13:00:00Z"),
+                video,
+            ],
+            extra: Default::default(),
+        };
+
+        let analysis = DuplicateAnalysis::from_group(&group);
+        assert!(analysis.needs_review);
+        assert_eq!(
+            analysis.review_reasons,
+            vec![
+                ReviewReason::TimezoneShift,
+                ReviewReason::MixedAssetTypes,
+                ReviewReason::ExternalLibraryAsset,
+            ]
+        );
+
+        let json = serde_json::to_value(&analysis).unwrap();
+        assert_eq!(json["review_reasons"][0], "timezone_shift");
+
+        let clean = DuplicateAnalysis::from_group(&DuplicateGroup {
+            duplicate_id: "dup-2".to_string(),
+            assets: vec![exif_time("a", "2024-06-15T10:00:00Z"), exif_time("b", "2024-06-15T10:00:00Z")],
+            extra: Default::default(),
+        });
+        assert!(!clean.needs_review);
+        assert!(clean.review_reasons.is_empty());
+    }
+
+    #[test]
+    fn test_batch_analysis_shares_repeated_strings() {
+        let camera = serde_json::json!({ "exifInfo": { "make": "Apple", "model": "iPhone 15 Pro", "city": "Chania" } });
+        let groups: Vec<DuplicateGroup> = (0..2)
+            .map(|g| DuplicateGroup {
+                duplicate_id: format!("dup-{}", g),
+                assets: vec![
+                    asset_json(&format!("{}-a", g), camera.clone()),
+                    asset_json(&format!("{}-b", g), camera.clone()),
+                ],
+                extra: Default::default(),
+            })
+            .collect();
+
+        let analyses = DuplicateAnalysis::from_groups_with_options(&groups, &Thresholds::default());
+        let first = analyses[0].winner.camera.as_ref().unwrap();
+        let last = analyses[1].losers[0].camera.as_ref().unwrap();
+        assert_eq!(first, "Apple iPhone 15 Pro");
+        assert!(first.ptr_eq(last));
+        assert!(analyses[0].winner.city.as_ref().unwrap().ptr_eq(analyses[1].winner.city.as_ref().unwrap()));
+
+        // Same results as analyzing one group at a time
+        let single = DuplicateAnalysis::from_group(&groups[1]);
+        assert_eq!(serde_json::to_value(&analyses[1]).unwrap(), serde_json::to_value(&single).unwrap());
+    }
+
+    #[test]
+    fn test_video_winner_prefers_full_duration_then_size() {
+        let video = |id: &str, duration: &str, size: u64| {
+            asset_json(
+                id,
+                serde_json::json!({
+                    "type": "VIDEO",
+                    "duration": duration,
+                    "exifInfo": { "fileSizeInByte": size, "exifImageWidth": 1920, "exifImageHeight": 1080, "fps": 29.97 }
+                }),
+            )
+        };
+
+        // The truncated re-encode is the largest file but must not win
+        let analysis = DuplicateAnalysis::from_group(&DuplicateGroup {
+            duplicate_id: "dup-1".to_string(),
+            assets: vec![
+                video("truncated", "0:00:08.500000", 90_000_000),
+                video("reencode", "0:01:12.300000", 20_000_000),
+                video("original", "0:01:12.033000", 60_000_000),
+            ],
+            extra: Default::default(),
+        });
+        assert_eq!(analysis.winner.asset_id, "original");
+        assert_eq!(analysis.winner.duration, Some(72.033));
+        assert_eq!(analysis.winner.fps, Some(29.97));
+        let losers: Vec<_> = analysis.losers.iter().map(|l| l.asset_id.as_str()).collect();
+        assert_eq!(losers, vec!["reencode", "truncated"]);
+
+        // Images never get a duration
+        let image = asset_json("image", serde_json::json!({}));
+        assert_eq!(image.duration_seconds(), Some(0.0));
+        let images = DuplicateAnalysis::from_group(&DuplicateGroup {
+            duplicate_id: "dup-2".to_string(),
+            assets: vec![image, asset_json("image-2", serde_json::json!({}))],
+            extra: Default::default(),
+        });
+        assert_eq!(images.winner.duration, None);
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        // London to Paris is roughly 344 km
+        let d = haversine_m((51.5074, -0.1278), (48.8566, 2.3522));
+        assert!((d - 343_500.0).abs() < 1_500.0, "got {}", d);
+
+        // 0.0001° of longitude is ~11 m at the equator but only ~2 m at 80°N
+        assert!(haversine_m((0.0, 0.0), (0.0, 0.0001)) > 10.0);
+        assert!(haversine_m((80.0, 0.0), (80.0, 0.0001)) < 2.0);
+    }
+
+    #[test]
+    fn test_gps_threshold_is_configurable() {
+        let coords = vec![(51.50740, -0.12780), (51.50745, -0.12785)]; // ~6.5 m
+        assert!(!has_gps_conflict(&coords, 11.0));
+        assert!(has_gps_conflict(&coords, 5.0));
+        assert_eq!(dedupe_gps(&coords, 5.0).len(), 2);
     }
 
     #[test]
@@ -0,0 +1,312 @@
+//! Full-text search over analysis results.
+//!
+//! [`SearchIndex`] tokenizes each group's filenames, descriptions, cities,
+//! countries, and camera models into an inverted index, so a query like
+//! `crete beach` finds the matching groups in a large analysis without
+//! scanning every asset. Every query term must match (as a whole word or a
+//! word prefix) somewhere in the group.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::intern::SharedStr;
+use crate::scoring::{DuplicateAnalysis, ScoredAsset};
+
+/// Field of an asset that a search term matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    /// Original filename
+    Filename,
+    /// EXIF description
+    Description,
+    /// Reverse-geocoded city
+    City,
+    /// Reverse-geocoded country
+    Country,
+    /// Camera make and model
+    Camera,
+}
+
+impl SearchField {
+    /// Short name of the field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchField::Filename => "filename",
+            SearchField::Description => "description",
+            SearchField::City => "city",
+            SearchField::Country => "country",
+            SearchField::Camera => "camera",
+        }
+    }
+}
+
+/// A group matching a search query.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    /// Duplicate group identifier
+    pub duplicate_id: String,
+
+    /// Position of the group in the analysis
+    pub index: usize,
+
+    /// Relevance (higher is better); whole-word matches outrank prefix matches
+    pub score: u32,
+
+    /// Fields that matched at least one term
+    pub fields: Vec<SearchField>,
+
+    /// One-line description of the group
+    pub summary: String,
+}
+
+/// Where a token occurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Posting {
+    group: usize,
+    field: SearchField,
+}
+
+/// Inverted index over the searchable text of analysis groups.
+#[derive(Debug)]
+pub struct SearchIndex<'a> {
+    groups: &'a [DuplicateAnalysis],
+    postings: BTreeMap<String, Vec<Posting>>,
+}
+
+impl<'a> SearchIndex<'a> {
+    /// Index the winner and losers of every group.
+    pub fn build(groups: &'a [DuplicateAnalysis]) -> Self {
+        let mut postings: BTreeMap<String, Vec<Posting>> = BTreeMap::new();
+
+        for (group, analysis) in groups.iter().enumerate() {
+            for asset in std::iter::once(&analysis.winner).chain(&analysis.losers) {
+                for (field, text) in searchable_fields(asset) {
+                    for token in tokenize(text) {
+                        let entry = postings.entry(token).or_default();
+                        let posting = Posting { group, field };
+                        if !entry.contains(&posting) {
+                            entry.push(posting);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self { groups, postings }
+    }
+
+    /// Find groups matching every term of `query`, best matches first.
+    ///
+    /// Terms match case-insensitively as whole words or word prefixes, so
+    /// `cret` finds `Crete`. Ties keep analysis order.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms = tokenize(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        // Per group: accumulated score and matched fields
+        let mut matches: Option<HashMap<usize, (u32, BTreeSet<SearchField>)>> = None;
+
+        for term in &terms {
+            let mut term_matches: HashMap<usize, (u32, BTreeSet<SearchField>)> = HashMap::new();
+            for (token, postings) in self
+                .postings
+                .range(term.clone()..)
+                .take_while(|(token, _)| token.starts_with(term.as_str()))
+            {
+                let weight = if token == term { 2 } else { 1 };
+                for posting in postings {
+                    let entry = term_matches.entry(posting.group).or_default();
+                    entry.0 = entry.0.max(weight);
+                    entry.1.insert(posting.field);
+                }
+            }
+
+            matches = Some(match matches {
+                None => term_matches,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(group, (score, mut fields))| {
+                        let (term_score, term_fields) = term_matches.remove(&group)?;
+                        fields.extend(term_fields);
+                        Some((group, (score + term_score, fields)))
+                    })
+                    .collect(),
+            });
+        }
+
+        let mut hits: Vec<SearchHit> = matches
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(index, (score, fields))| {
+                let analysis = &self.groups[index];
+                SearchHit {
+                    duplicate_id: analysis.duplicate_id.clone(),
+                    index,
+                    score,
+                    fields: fields.into_iter().collect(),
+                    summary: summarize(analysis),
+                }
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.index.cmp(&b.index)));
+        hits
+    }
+
+    /// Number of distinct indexed tokens.
+    pub fn token_count(&self) -> usize {
+        self.postings.len()
+    }
+}
+
+/// One-line description of a group: winner filename, copies, place, camera, caption.
+pub fn summarize(analysis: &DuplicateAnalysis) -> String {
+    let assets: Vec<&ScoredAsset> = std::iter::once(&analysis.winner).chain(&analysis.losers).collect();
+    let first = |f: fn(&ScoredAsset) -> Option<&SharedStr>| assets.iter().find_map(|a| f(a)).map(|s| s.to_string());
+
+    let mut parts = vec![format!(
+        "{} (+{} {})",
+        analysis.winner.filename,
+        analysis.losers.len(),
+        if analysis.losers.len() == 1 { "copy" } else { "copies" }
+    )];
+
+    let place: Vec<String> = [first(|a| a.city.as_ref()), first(|a| a.country.as_ref())]
+        .into_iter()
+        .flatten()
+        .collect();
+    if !place.is_empty() {
+        parts.push(place.join(", "));
+    }
+    if let Some(camera) = first(|a| a.camera.as_ref()) {
+        parts.push(camera);
+    }
+    if let Some(description) = first(|a| a.description.as_ref()) {
+        parts.push(format!("\"{}\"", description));
+    }
+
+    parts.join(" · ")
+}
+
+/// Searchable text of an asset, by field.
+fn searchable_fields(asset: &ScoredAsset) -> impl Iterator<Item = (SearchField, &str)> {
+    [
+        (SearchField::Filename, Some(&asset.filename)),
+        (SearchField::Description, asset.description.as_ref()),
+        (SearchField::City, asset.city.as_ref()),
+        (SearchField::Country, asset.country.as_ref()),
+        (SearchField::Camera, asset.camera.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(field, text)| Some((field, text?.as_str())))
+}
+
+/// Split text into lowercase alphanumeric words.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::MetadataScore;
+
+    fn asset(filename: &str, city: Option<&str>, description: Option<&str>) -> ScoredAsset {
+        ScoredAsset {
+            asset_id: format!("{}-id", filename).into(),
+            filename: filename.into(),
+            checksum: None,
+            updated_at: None,
+            score: MetadataScore::default(),
+            file_size: None,
+            dimensions: None,
+            megapixels: None,
+            is_external: false,
+            library_id: None,
+            owner_id: None,
+            capture_time: None,
+            gps: None,
+            description: description.map(SharedStr::from),
+            city: city.map(SharedStr::from),
+            country: city.map(|_| "Greece".into()),
+            camera: Some("Canon EOS 5D".into()),
+            duration: None,
+            fps: None,
+            web_url: None,
+            api_url: None,
+        }
+    }
+
+    fn group(id: &str, winner: ScoredAsset, loser: ScoredAsset) -> DuplicateAnalysis {
+        DuplicateAnalysis {
+            duplicate_id: id.to_string(),
+            winner,
+            losers: vec![loser],
+            conflicts: Vec::new(),
+            needs_review: false,
+            review_reasons: Vec::new(),
+            decision: None,
+            corrupt_asset_ids: Vec::new(),
+            confidence: 100,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn groups() -> Vec<DuplicateAnalysis> {
+        vec![
+            group(
+                "dup-1",
+                asset("crete2019.jpg", None, None),
+                asset("crete2019-copy.jpg", None, None),
+            ),
+            group(
+                "dup-2",
+                asset("IMG_0001.jpg", Some("Chania"), None),
+                asset("IMG_0001 (1).jpg", None, Some("Beach day on Crete")),
+            ),
+            group(
+                "dup-3",
+                asset("beach_volleyball.jpg", Some("Brighton"), None),
+                asset("beach_volleyball-edited.jpg", None, None),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_all_terms_must_match_across_assets() {
+        let groups = groups();
+        let index = SearchIndex::build(&groups);
+
+        let hits = index.search("Crete beach");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].duplicate_id, "dup-2");
+        assert_eq!(hits[0].fields, vec![SearchField::Description]);
+
+        assert!(index.search("crete mars").is_empty());
+        assert!(index.search("  ").is_empty());
+    }
+
+    #[test]
+    fn test_whole_words_rank_above_prefixes() {
+        let groups = groups();
+        let index = SearchIndex::build(&groups);
+
+        let hits = index.search("crete");
+        let ranked: Vec<_> = hits.iter().map(|h| (h.duplicate_id.as_str(), h.score)).collect();
+        assert_eq!(ranked, vec![("dup-2", 2), ("dup-1", 1)]);
+
+        let chania = index.search("chan");
+        assert_eq!(chania[0].fields, vec![SearchField::City]);
+        assert_eq!(
+            chania[0].summary,
+            "IMG_0001.jpg (+1 copy) · Chania, Greece · Canon EOS 5D · \"Beach day on Crete\""
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! XMP sidecar export for backup files.
+//!
+//! Backups are byte-for-byte copies of the original file, so metadata that
+//! only lives in Immich (or was consolidated from another duplicate) would be
+//! lost. This module writes a small XMP sidecar next to each backup recording
+//! the group's consolidated GPS, capture time, description, rating, and
+//! camera info.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::models::ExifInfo;
+
+/// Metadata recorded in an XMP sidecar.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SidecarMetadata {
+    /// GPS latitude in decimal degrees
+    pub latitude: Option<f64>,
+
+    /// GPS longitude in decimal degrees
+    pub longitude: Option<f64>,
+
+    /// Original capture date/time (ISO 8601)
+    pub date_time_original: Option<String>,
+
+    /// Image description/caption
+    pub description: Option<String>,
+
+    /// Camera manufacturer
+    pub make: Option<String>,
+
+    /// Camera model
+    pub model: Option<String>,
+
+    /// Star rating (0–5), as set in Immich
+    #[serde(default)]
+    pub rating: Option<u8>,
+}
+
+impl SidecarMetadata {
+    /// Extract sidecar fields from an asset's EXIF info.
+    pub fn from_exif(exif: &ExifInfo) -> Self {
+        let (latitude, longitude) = match (exif.latitude, exif.longitude) {
+            (Some(lat), Some(lon)) => (Some(lat), Some(lon)),
+            _ => (None, None),
+        };
+
+        Self {
+            latitude,
+            longitude,
+            date_time_original: exif
+                .date_time_original
+                .as_deref()
+                .map(|dt| in_time_zone(dt, exif.time_zone.as_deref())),
+            description: exif.description.clone().filter(|d| !d.is_empty()),
+            make: exif.make.clone(),
+            model: exif.model.clone(),
+            rating: exif.rating.filter(|r| *r <= 5),
+        }
+    }
+
+    /// Fill any missing fields from another source, keeping existing values.
+    ///
+    /// GPS coordinates are treated as a pair and only copied together.
+    pub fn merge_missing(&mut self, other: &SidecarMetadata) {
+        if self.latitude.is_none() || self.longitude.is_none() {
+            self.latitude = other.latitude;
+            self.longitude = other.longitude;
+        }
+        if self.date_time_original.is_none() {
+            self.date_time_original.clone_from(&other.date_time_original);
+        }
+        if self.description.is_none() {
+            self.description.clone_from(&other.description);
+        }
+        if self.make.is_none() {
+            self.make.clone_from(&other.make);
+        }
+        if self.model.is_none() {
+            self.model.clone_from(&other.model);
+        }
+        if self.rating.is_none() {
+            self.rating = other.rating;
+        }
+    }
+
+    /// Returns true if no fields are set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Render the metadata as an XMP packet.
+    pub fn to_xmp(&self) -> String {
+        let mut properties = String::new();
+
+        if let (Some(lat), Some(lon)) = (self.latitude, self.longitude) {
+            properties.push_str(&format!(
+                "   <exif:GPSLatitude>{}</exif:GPSLatitude>\n",
+                format_gps_coordinate(lat, 'N', 'S')
+            ));
+            properties.push_str(&format!(
+                "   <exif:GPSLongitude>{}</exif:GPSLongitude>\n",
+                format_gps_coordinate(lon, 'E', 'W')
+            ));
+        }
+
+        if let Some(dt) = &self.date_time_original {
+            properties.push_str(&format!(
+                "   <exif:DateTimeOriginal>{}</exif:DateTimeOriginal>\n",
+                escape_xml(dt)
+            ));
+        }
+
+        if let Some(make) = &self.make {
+            properties.push_str(&format!("   <tiff:Make>{}</tiff:Make>\n", escape_xml(make)));
+        }
+
+        if let Some(model) = &self.model {
+            properties.push_str(&format!("   <tiff:Model>{}</tiff:Model>\n", escape_xml(model)));
+        }
+
+        if let Some(rating) = self.rating {
+            properties.push_str(&format!("   <xmp:Rating>{}</xmp:Rating>\n", rating));
+        }
+
+        if let Some(desc) = &self.description {
+            properties.push_str(&format!(
+                "   <dc:description>\n    <rdf:Alt>\n     <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n    </rdf:Alt>\n   </dc:description>\n",
+                escape_xml(desc)
+            ));
+        }
+
+        format!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+             <x:xmpmeta xmlns:x=\"adobe:ns:meta/\" x:xmptk=\"immich-lib\">\n\
+             <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  \
+             <rdf:Description rdf:about=\"\"\n    \
+             xmlns:exif=\"http://ns.adobe.com/exif/1.0/\"\n    \
+             xmlns:tiff=\"http://ns.adobe.com/tiff/1.0/\"\n    \
+             xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n    \
+             xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+             {}  </rdf:Description>\n\
+             </rdf:RDF>\n\
+             </x:xmpmeta>\n\
+             <?xpacket end=\"w\"?>\n",
+            properties
+        )
+    }
+}
+
+/// Returns the sidecar path for a backup file (`photo.jpg` -> `photo.jpg.xmp`).
+pub fn sidecar_path(file_path: &Path) -> PathBuf {
+    let mut name = file_path.as_os_str().to_os_string();
+    name.push(".xmp");
+    PathBuf::from(name)
+}
+
+/// Write an XMP sidecar next to a backup file.
+///
+/// # Returns
+///
+/// The path of the written sidecar.
+pub async fn write_sidecar(file_path: &Path, metadata: &SidecarMetadata) -> Result<PathBuf> {
+    let path = sidecar_path(file_path);
+    tokio::fs::write(&path, metadata.to_xmp()).await?;
+    Ok(path)
+}
+
+/// Express an RFC 3339 capture time in the asset's time zone, so it is
+/// written as the local time the photo was taken.
+///
+/// Immich returns capture times in UTC alongside the zone. Only fixed offsets
+/// (`UTC+10`, `+05:30`) can be applied; named zones and unparseable times are
+/// returned unchanged.
+fn in_time_zone(date_time: &str, time_zone: Option<&str>) -> String {
+    let offset = time_zone.and_then(parse_fixed_offset);
+    match (DateTime::parse_from_rfc3339(date_time), offset) {
+        (Ok(parsed), Some(offset)) => parsed.with_timezone(&offset).to_rfc3339(),
+        _ => date_time.to_string(),
+    }
+}
+
+/// Parse a fixed UTC offset such as `UTC+10`, `UTC-3:30`, or `+05:30`.
+fn parse_fixed_offset(zone: &str) -> Option<FixedOffset> {
+    let zone = zone.trim();
+    let rest = zone.strip_prefix("UTC").or_else(|| zone.strip_prefix("GMT")).unwrap_or(zone);
+    if rest.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match rest.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Format a decimal coordinate in XMP `DDD,MM.mmmmK` form.
+fn format_gps_coordinate(value: f64, positive: char, negative: char) -> String {
+    let reference = if value >= 0.0 { positive } else { negative };
+    let abs = value.abs();
+    let degrees = abs.trunc();
+    let minutes = (abs - degrees) * 60.0;
+    format!("{},{:.6}{}", degrees as u32, minutes, reference)
+}
+
+/// Escape the XML special characters in text content.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_gps_coordinate() {
+        assert_eq!(format_gps_coordinate(51.5, 'N', 'S'), "51,30.000000N");
+        assert_eq!(format_gps_coordinate(-0.25, 'E', 'W'), "0,15.000000W");
+    }
+
+    #[test]
+    fn test_merge_missing_keeps_existing() {
+        let mut winner = SidecarMetadata {
+            description: Some("Winner".into()),
+            ..Default::default()
+        };
+        let loser = SidecarMetadata {
+            latitude: Some(1.0),
+            longitude: Some(2.0),
+            description: Some("Loser".into()),
+            ..Default::default()
+        };
+
+        winner.merge_missing(&loser);
+        assert_eq!(winner.latitude, Some(1.0));
+        assert_eq!(winner.description.as_deref(), Some("Winner"));
+    }
+
+    #[test]
+    fn test_to_xmp_escapes_description() {
+        let metadata = SidecarMetadata {
+            description: Some("Fish & <chips>".into()),
+            ..Default::default()
+        };
+        let xmp = metadata.to_xmp();
+        assert!(xmp.contains("Fish &amp; &lt;chips&gt;"));
+        assert!(!xmp.contains("GPSLatitude"));
+    }
+
+    #[test]
+    fn test_capture_time_in_fixed_time_zone() {
+        let exif: ExifInfo = serde_json::from_value(serde_json::json!({
+            "dateTimeOriginal": "2024-06-15T04:30:00+00:00",
+            "timeZone": "UTC+10",
+            "rating": 4,
+        }))
+        .unwrap();
+        let metadata = SidecarMetadata::from_exif(&exif);
+        assert_eq!(metadata.date_time_original.as_deref(), Some("2024-06-15T14:30:00+10:00"));
+        assert!(metadata.to_xmp().contains("<xmp:Rating>4</xmp:Rating>"));
+
+        assert_eq!(parse_fixed_offset("-03:30"), FixedOffset::west_opt(3 * 3600 + 1800));
+        assert_eq!(parse_fixed_offset("Australia/Sydney"), None);
+        assert_eq!(in_time_zone("2024-06-15T04:30:00Z", Some("Australia/Sydney")), "2024-06-15T04:30:00Z");
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(
+            sidecar_path(Path::new("/backups/abc_photo.jpg")),
+            PathBuf::from("/backups/abc_photo.jpg.xmp")
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Library-wide statistics.
+//!
+//! [`LibraryStats::collect`] gives a quick overview of a library before a
+//! run: how many assets it holds, how many are duplicates, how much space
+//! removing the duplicates would reclaim, and how often duplicate groups
+//! disagree about metadata. Nothing on the server is modified.
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::ImmichClient;
+use crate::error::Result;
+use crate::models::{AssetStatistics, ServerStatistics};
+use crate::scoring::{DuplicateAnalysis, Thresholds};
+
+/// Overview of a library and its duplicates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryStats {
+    /// Server the statistics were collected from
+    pub server_url: String,
+
+    /// Asset counts for the key's user
+    pub assets: AssetStatistics,
+
+    /// Assets in the trash
+    pub trashed: u64,
+
+    /// Server-wide counts and storage (`None` unless the key belongs to an admin)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<ServerStatistics>,
+
+    /// Duplicate groups
+    pub duplicate_groups: usize,
+
+    /// Assets in duplicate groups, winners included
+    pub duplicate_assets: usize,
+
+    /// Assets that would be removed (every loser)
+    pub removable_assets: usize,
+
+    /// Bytes freed by removing every loser
+    pub reclaimable_bytes: u64,
+
+    /// Groups with at least one metadata conflict
+    pub groups_with_conflicts: usize,
+
+    /// Fraction of groups with a metadata conflict (0 to 1)
+    pub conflict_rate: f64,
+
+    /// Groups flagged for review
+    pub needs_review: usize,
+}
+
+impl LibraryStats {
+    /// Fetch asset counts and duplicates from the server and summarize them.
+    ///
+    /// Server-wide statistics need an admin key; when the server refuses them
+    /// (HTTP 403) they are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the asset statistics, server statistics (other
+    /// than a refusal), or duplicates can't be fetched.
+    pub async fn collect(client: &ImmichClient, thresholds: &Thresholds) -> Result<Self> {
+        let assets = client.get_asset_statistics(false).await?;
+        let trashed = client.get_asset_statistics(true).await?.total;
+        let server = match client.get_server_statistics().await {
+            Ok(server) => Some(server),
+            Err(e) if e.status() == Some(403) => None,
+            Err(e) => return Err(e),
+        };
+        let groups = DuplicateAnalysis::from_groups_with_options(&client.get_duplicates().await?, thresholds);
+        Ok(Self::from_analyses(client.base_url(), assets, trashed, server, &groups))
+    }
+
+    /// Summarize already analyzed duplicate groups.
+    pub fn from_analyses(
+        server_url: &str,
+        assets: AssetStatistics,
+        trashed: u64,
+        server: Option<ServerStatistics>,
+        groups: &[DuplicateAnalysis],
+    ) -> Self {
+        let removable_assets: usize = groups.iter().map(|g| g.losers.len()).sum();
+        let groups_with_conflicts = groups.iter().filter(|g| !g.conflicts.is_empty()).count();
+        Self {
+            server_url: server_url.to_string(),
+            assets,
+            trashed,
+            server,
+            duplicate_groups: groups.len(),
+            duplicate_assets: groups.len() + removable_assets,
+            removable_assets,
+            reclaimable_bytes: groups
+                .iter()
+                .flat_map(|g| &g.losers)
+                .filter_map(|l| l.file_size)
+                .sum(),
+            groups_with_conflicts,
+            conflict_rate: if groups.is_empty() {
+                0.0
+            } else {
+                groups_with_conflicts as f64 / groups.len() as f64
+            },
+            needs_review: groups.iter().filter(|g| g.needs_review).count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ImmichError;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[tokio::test]
+    async fn test_collect_summarizes_duplicates() {
+        let mut groups = synthetic_groups(4, 3).unwrap();
+        // Move one group's second copy far away, so it conflicts on GPS
+        groups[0].assets[2].exif_info.as_mut().unwrap().latitude = Some(48.85);
+        groups[0].assets[2].exif_info.as_mut().unwrap().longitude = Some(2.35);
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+
+        let stats = LibraryStats::collect(&client, &Thresholds::default()).await.unwrap();
+        assert_eq!((stats.duplicate_groups, stats.duplicate_assets, stats.removable_assets), (4, 12, 8));
+        assert_eq!(stats.reclaimable_bytes, 4 * (12288 + 8192));
+        assert_eq!(stats.groups_with_conflicts, 1);
+        assert_eq!(stats.conflict_rate, 0.25);
+        assert_eq!(stats.server.unwrap().photos, 120);
+    }
+
+    #[tokio::test]
+    async fn test_server_statistics_only_left_out_when_refused() {
+        let groups = synthetic_groups(1, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+
+        server.refuse_server_statistics("403 Forbidden");
+        let stats = LibraryStats::collect(&client, &Thresholds::default()).await.unwrap();
+        assert!(stats.server.is_none());
+
+        server.refuse_server_statistics("404 Not Found");
+        let result = LibraryStats::collect(&client, &Thresholds::default()).await;
+        assert!(matches!(result, Err(ImmichError::NotFound { .. })));
+    }
+}
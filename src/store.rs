@@ -0,0 +1,761 @@
+//! SQLite-backed analysis store (feature `sqlite`).
+//!
+//! An analysis of a very large library is a 100MB+ JSON document that has
+//! to be parsed in full before anything can be done with it. An
+//! [`AnalysisStore`] keeps the same data in a SQLite database instead: one
+//! row per group, scored asset, and conflict, plus the results of each
+//! execution run, so single groups and common questions ("which groups
+//! need review?", "which groups contain this asset?") are answered with an
+//! indexed query.
+//!
+//! Queryable fields get their own columns; each asset and conflict is also
+//! stored as JSON so it round-trips exactly, including fields added later.
+//!
+//! The same database can hold the executor's per-run event history
+//! ([`SqliteHistory`]), which [`crate::history::open`] uses in place of
+//! JSON Lines files when this feature is enabled.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+use crate::history::{self, HistoryStore, RecordedEvent, RunSummary};
+use crate::models::{ExecutionReport, GroupResult, OperationResult};
+use crate::scoring::{DuplicateAnalysis, MetadataConflict, ScoredAsset};
+
+/// Schema version, stored in `PRAGMA user_version`.
+pub const SCHEMA_VERSION: i32 = 3;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS groups (
+    position          INTEGER PRIMARY KEY,
+    duplicate_id      TEXT NOT NULL UNIQUE,
+    winner_id         TEXT NOT NULL,
+    needs_review      INTEGER NOT NULL,
+    confidence        INTEGER NOT NULL,
+    review_reasons    TEXT NOT NULL,
+    decision          TEXT,
+    corrupt_asset_ids TEXT NOT NULL,
+    warnings          TEXT NOT NULL DEFAULT '[]'
+);
+
+-- rank 0 is the winner; losers follow in analysis order
+CREATE TABLE IF NOT EXISTS scored_assets (
+    group_position INTEGER NOT NULL REFERENCES groups(position) ON DELETE CASCADE,
+    rank           INTEGER NOT NULL,
+    asset_id       TEXT NOT NULL,
+    filename       TEXT NOT NULL,
+    checksum       TEXT,
+    file_size      INTEGER,
+    score          INTEGER NOT NULL,
+    is_external    INTEGER NOT NULL,
+    data           TEXT NOT NULL,
+    PRIMARY KEY (group_position, rank)
+);
+CREATE INDEX IF NOT EXISTS scored_assets_asset_id ON scored_assets(asset_id);
+CREATE INDEX IF NOT EXISTS scored_assets_checksum ON scored_assets(checksum);
+
+CREATE TABLE IF NOT EXISTS conflicts (
+    group_position INTEGER NOT NULL REFERENCES groups(position) ON DELETE CASCADE,
+    idx            INTEGER NOT NULL,
+    kind           TEXT NOT NULL,
+    data           TEXT NOT NULL,
+    PRIMARY KEY (group_position, idx)
+);
+
+CREATE TABLE IF NOT EXISTS execution_runs (
+    run_id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at  TEXT NOT NULL,
+    total_groups INTEGER NOT NULL,
+    downloaded   INTEGER NOT NULL,
+    deleted      INTEGER NOT NULL,
+    failed       INTEGER NOT NULL,
+    skipped      INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS execution_results (
+    run_id       INTEGER NOT NULL REFERENCES execution_runs(run_id) ON DELETE CASCADE,
+    duplicate_id TEXT NOT NULL,
+    winner_id    TEXT NOT NULL,
+    status       TEXT NOT NULL,
+    data         TEXT NOT NULL,
+    PRIMARY KEY (run_id, duplicate_id)
+);
+CREATE INDEX IF NOT EXISTS execution_results_status ON execution_results(run_id, status);
+
+-- per-run event history (history::HistoryStore), seq in recording order
+CREATE TABLE IF NOT EXISTS history_runs (
+    run_id     TEXT PRIMARY KEY,
+    started_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS history_events (
+    run_id   TEXT NOT NULL REFERENCES history_runs(run_id) ON DELETE CASCADE,
+    seq      INTEGER NOT NULL,
+    group_id TEXT,
+    data     TEXT NOT NULL,
+    PRIMARY KEY (run_id, seq)
+);
+CREATE INDEX IF NOT EXISTS history_events_group_id ON history_events(run_id, group_id);
+";
+
+/// Columns selected when loading a group.
+const GROUP_COLUMNS: &str =
+    "position, duplicate_id, needs_review, confidence, review_reasons, decision, corrupt_asset_ids, warnings";
+
+/// Summary of a recorded execution run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExecutionRun {
+    /// Identifier assigned when the run was recorded
+    pub run_id: i64,
+
+    /// When the run was recorded
+    pub recorded_at: DateTime<Utc>,
+
+    /// Groups processed
+    pub total_groups: usize,
+
+    /// Assets downloaded
+    pub downloaded: usize,
+
+    /// Assets deleted
+    pub deleted: usize,
+
+    /// Operations that failed
+    pub failed: usize,
+
+    /// Operations that were skipped
+    pub skipped: usize,
+}
+
+/// Analysis results and execution history in a SQLite database.
+#[derive(Debug)]
+pub struct AnalysisStore {
+    conn: Connection,
+}
+
+impl AnalysisStore {
+    /// Open (or create) the store at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open a temporary in-memory store.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(ImmichError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "analysis store has schema version {}, but this build supports up to {}",
+                    version, SCHEMA_VERSION
+                ),
+            )));
+        }
+
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")?;
+        if version == 1 {
+            conn.execute_batch("ALTER TABLE groups ADD COLUMN warnings TEXT NOT NULL DEFAULT '[]';")?;
+        }
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self { conn })
+    }
+
+    /// Store a value under `key` as JSON (e.g. report-level fields).
+    pub fn set_meta<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, to_json(value)?],
+        )?;
+        Ok(())
+    }
+
+    /// Value stored under `key`, if any.
+    pub fn meta<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let json: Option<String> = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?;
+        json.map(|json| from_json(&json)).transpose()
+    }
+
+    /// Replace every stored group with `groups`, in one transaction.
+    ///
+    /// Execution history is kept.
+    pub fn replace_groups(&mut self, groups: &[DuplicateAnalysis]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute_batch("DELETE FROM conflicts; DELETE FROM scored_assets; DELETE FROM groups;")?;
+        {
+            let mut insert_group = tx.prepare(
+                "INSERT INTO groups (position, duplicate_id, winner_id, needs_review, confidence,
+                                     review_reasons, decision, corrupt_asset_ids, warnings)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            let mut insert_asset = tx.prepare(
+                "INSERT INTO scored_assets (group_position, rank, asset_id, filename, checksum,
+                                            file_size, score, is_external, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            let mut insert_conflict = tx.prepare(
+                "INSERT INTO conflicts (group_position, idx, kind, data) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+
+            for (position, group) in groups.iter().enumerate() {
+                let position = position as i64;
+                insert_group.execute(params![
+                    position,
+                    group.duplicate_id,
+                    group.winner.asset_id.as_str(),
+                    group.needs_review,
+                    group.confidence,
+                    to_json(&group.review_reasons)?,
+                    group.decision.as_ref().map(to_json).transpose()?,
+                    to_json(&group.corrupt_asset_ids)?,
+                    to_json(&group.warnings)?,
+                ])?;
+
+                for (rank, asset) in std::iter::once(&group.winner).chain(&group.losers).enumerate() {
+                    insert_asset.execute(params![
+                        position,
+                        rank as i64,
+                        asset.asset_id.as_str(),
+                        asset.filename.as_str(),
+                        asset.checksum.as_deref(),
+                        asset.file_size.map(|size| size as i64),
+                        asset.score.total,
+                        asset.is_external,
+                        to_json(asset)?,
+                    ])?;
+                }
+
+                for (idx, conflict) in group.conflicts.iter().enumerate() {
+                    insert_conflict.execute(params![
+                        position,
+                        idx as i64,
+                        conflict_kind(conflict),
+                        to_json(conflict)?,
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Number of stored groups.
+    pub fn group_count(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row("SELECT COUNT(*) FROM groups", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Every group, in analysis order.
+    pub fn groups(&self) -> Result<Vec<DuplicateAnalysis>> {
+        self.load_groups("1 = 1", [])
+    }
+
+    /// The group with the given duplicate ID.
+    pub fn group(&self, duplicate_id: &str) -> Result<Option<DuplicateAnalysis>> {
+        Ok(self.load_groups("duplicate_id = ?1", [duplicate_id])?.pop())
+    }
+
+    /// Groups flagged for manual review.
+    pub fn groups_needing_review(&self) -> Result<Vec<DuplicateAnalysis>> {
+        self.load_groups("needs_review = 1", [])
+    }
+
+    /// Groups containing the asset (as winner or loser).
+    pub fn groups_containing(&self, asset_id: &str) -> Result<Vec<DuplicateAnalysis>> {
+        self.load_groups(
+            "position IN (SELECT group_position FROM scored_assets WHERE asset_id = ?1)",
+            [asset_id],
+        )
+    }
+
+    /// Groups with at least one conflict of the given kind (e.g. `gps`).
+    pub fn groups_with_conflict(&self, kind: &str) -> Result<Vec<DuplicateAnalysis>> {
+        self.load_groups(
+            "position IN (SELECT group_position FROM conflicts WHERE kind = ?1)",
+            [kind],
+        )
+    }
+
+    /// Total size of every loser with a known file size.
+    pub fn reclaimable_bytes(&self) -> Result<u64> {
+        let bytes: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(file_size), 0) FROM scored_assets WHERE rank > 0",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(bytes as u64)
+    }
+
+    /// Record an execution report as a new run and return its ID.
+    pub fn record_execution(&mut self, report: &ExecutionReport) -> Result<i64> {
+        self.record_execution_with_clock(report, &SystemClock)
+    }
+
+    /// Like [`record_execution`](Self::record_execution), stamping the run
+    /// with the time from `clock`.
+    pub(crate) fn record_execution_with_clock(&mut self, report: &ExecutionReport, clock: &dyn Clock) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO execution_runs (recorded_at, total_groups, downloaded, deleted, failed, skipped)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                clock.now().to_rfc3339(),
+                report.total_groups as i64,
+                report.downloaded as i64,
+                report.deleted as i64,
+                report.failed as i64,
+                report.skipped as i64,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO execution_results (run_id, duplicate_id, winner_id, status, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for result in &report.results {
+                insert.execute(params![
+                    run_id,
+                    result.duplicate_id,
+                    result.winner_id,
+                    group_status(result),
+                    to_json(result)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// Every recorded execution run, oldest first.
+    pub fn execution_runs(&self) -> Result<Vec<ExecutionRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT run_id, recorded_at, total_groups, downloaded, deleted, failed, skipped
+             FROM execution_runs ORDER BY run_id",
+        )?;
+        let runs = stmt
+            .query_map([], |row| {
+                let recorded_at: String = row.get(1)?;
+                Ok(ExecutionRun {
+                    run_id: row.get(0)?,
+                    recorded_at: DateTime::parse_from_rfc3339(&recorded_at)
+                        .map(|t| t.with_timezone(&Utc))
+                        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(e)))?,
+                    total_groups: row.get::<_, i64>(2)? as usize,
+                    downloaded: row.get::<_, i64>(3)? as usize,
+                    deleted: row.get::<_, i64>(4)? as usize,
+                    failed: row.get::<_, i64>(5)? as usize,
+                    skipped: row.get::<_, i64>(6)? as usize,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+
+    /// Duplicate IDs of groups in `run_id` with a failed download or delete.
+    pub fn failed_groups(&self, run_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT duplicate_id FROM execution_results
+             WHERE run_id = ?1 AND status = 'failed' ORDER BY duplicate_id",
+        )?;
+        let ids = stmt
+            .query_map([run_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    /// Load groups matching a `WHERE` clause over the `groups` table.
+    fn load_groups<P: rusqlite::Params>(&self, filter: &str, params: P) -> Result<Vec<DuplicateAnalysis>> {
+        let mut group_stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM groups WHERE {} ORDER BY position",
+            GROUP_COLUMNS, filter
+        ))?;
+        let mut asset_stmt = self
+            .conn
+            .prepare_cached("SELECT data FROM scored_assets WHERE group_position = ?1 ORDER BY rank")?;
+        let mut conflict_stmt = self
+            .conn
+            .prepare_cached("SELECT data FROM conflicts WHERE group_position = ?1 ORDER BY idx")?;
+
+        let rows = group_stmt
+            .query_map(params, GroupRow::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut groups = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut assets = asset_stmt
+                .query_map([row.position], |r| r.get::<_, String>(0))?
+                .map(|json| from_json::<ScoredAsset>(&json?))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter();
+            let conflicts = conflict_stmt
+                .query_map([row.position], |r| r.get::<_, String>(0))?
+                .map(|json| from_json::<MetadataConflict>(&json?))
+                .collect::<Result<Vec<_>>>()?;
+
+            let Some(winner) = assets.next() else {
+                return Err(invalid_data(format!("group {} has no assets", row.duplicate_id)));
+            };
+
+            groups.push(DuplicateAnalysis {
+                duplicate_id: row.duplicate_id,
+                winner,
+                losers: assets.collect(),
+                conflicts,
+                needs_review: row.needs_review,
+                review_reasons: from_json(&row.review_reasons)?,
+                decision: row.decision.as_deref().map(from_json).transpose()?,
+                corrupt_asset_ids: from_json(&row.corrupt_asset_ids)?,
+                confidence: row.confidence,
+                warnings: from_json(&row.warnings)?,
+            });
+        }
+        Ok(groups)
+    }
+}
+
+/// Raw `groups` row, before its JSON columns are decoded.
+struct GroupRow {
+    position: i64,
+    duplicate_id: String,
+    needs_review: bool,
+    confidence: u8,
+    review_reasons: String,
+    decision: Option<String>,
+    corrupt_asset_ids: String,
+    warnings: String,
+}
+
+impl GroupRow {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            position: row.get(0)?,
+            duplicate_id: row.get(1)?,
+            needs_review: row.get(2)?,
+            confidence: row.get(3)?,
+            review_reasons: row.get(4)?,
+            decision: row.get(5)?,
+            corrupt_asset_ids: row.get(6)?,
+            warnings: row.get(7)?,
+        })
+    }
+}
+
+/// Conflict kind as serialized in its `type` tag (e.g. `gps`).
+fn conflict_kind(conflict: &MetadataConflict) -> String {
+    serde_json::to_value(conflict)
+        .ok()
+        .and_then(|v| v.get("type")?.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Per-run event history in an analysis store's database.
+///
+/// The store is shared by everything recording a run, so it is kept
+/// behind a lock.
+#[derive(Debug)]
+pub struct SqliteHistory {
+    store: Mutex<AnalysisStore>,
+}
+
+impl SqliteHistory {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(AnalysisStore::open(path)?))
+    }
+
+    /// Keep the history in `store`.
+    pub fn new(store: AnalysisStore) -> Self {
+        Self {
+            store: Mutex::new(store),
+        }
+    }
+}
+
+impl HistoryStore for SqliteHistory {
+    fn create_run(&self, started_at: DateTime<Utc>) -> Result<String> {
+        let store = self.store.lock();
+        let mut attempt = 1;
+        loop {
+            let run_id = history::run_id_for(started_at, attempt);
+            let inserted = store.conn.execute(
+                "INSERT OR IGNORE INTO history_runs (run_id, started_at) VALUES (?1, ?2)",
+                params![run_id, started_at.to_rfc3339()],
+            )?;
+            if inserted == 1 {
+                return Ok(run_id);
+            }
+            attempt += 1;
+        }
+    }
+
+    fn append(&self, run_id: &str, event: &RecordedEvent) -> Result<()> {
+        self.store.lock().conn.execute(
+            "INSERT INTO history_events (run_id, seq, group_id, data)
+             SELECT ?1, COALESCE(MAX(seq), 0) + 1, ?2, ?3 FROM history_events WHERE run_id = ?1",
+            params![run_id, event.event.group_id(), to_json(event)?],
+        )?;
+        Ok(())
+    }
+
+    fn runs(&self) -> Result<Vec<RunSummary>> {
+        let run_ids: Vec<String> = {
+            let store = self.store.lock();
+            let mut stmt = store.conn.prepare("SELECT run_id FROM history_runs ORDER BY run_id")?;
+            stmt.query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?
+        };
+        run_ids
+            .into_iter()
+            .map(|run_id| {
+                let events = self.timeline(&run_id)?;
+                Ok(history::summarize(run_id, &events))
+            })
+            .collect()
+    }
+
+    fn timeline(&self, run_id: &str) -> Result<Vec<RecordedEvent>> {
+        let store = self.store.lock();
+        let known = store
+            .conn
+            .query_row("SELECT 1 FROM history_runs WHERE run_id = ?1", [run_id], |_| Ok(()))
+            .optional()?;
+        if known.is_none() {
+            return Err(ImmichError::NotFound {
+                message: format!("no run {} in the history database", run_id),
+            });
+        }
+
+        let mut stmt = store
+            .conn
+            .prepare("SELECT data FROM history_events WHERE run_id = ?1 ORDER BY seq")?;
+        let rows = stmt
+            .query_map([run_id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.iter().map(|json| from_json(json)).collect()
+    }
+}
+
+/// Overall status of a group in an execution: `failed`, `skipped`, or `success`.
+fn group_status(result: &GroupResult) -> &'static str {
+    let download_failed = result
+        .download_results
+        .iter()
+        .any(|r| matches!(r, OperationResult::Failed { .. }));
+    match &result.delete_result {
+        _ if download_failed => "failed",
+        Some(OperationResult::Failed { .. }) => "failed",
+        Some(OperationResult::Success { .. }) => "success",
+        Some(OperationResult::Skipped { .. }) | None => "skipped",
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value).map_err(std::io::Error::other)?)
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    serde_json::from_str(json).map_err(|e| invalid_data(e.to_string()))
+}
+
+fn invalid_data(message: String) -> ImmichError {
+    ImmichError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::MetadataScore;
+
+    fn asset(id: &str, file_size: u64) -> ScoredAsset {
+        ScoredAsset {
+            asset_id: id.into(),
+            filename: format!("{}.jpg", id).into(),
+            checksum: Some(format!("sha1-{}", id).into()),
+            updated_at: None,
+            score: MetadataScore::default(),
+            file_size: Some(file_size),
+            dimensions: Some((4032, 3024)),
+            megapixels: Some(12.2),
+            is_external: false,
+            library_id: None,
+            owner_id: None,
+            capture_time: None,
+            gps: Some((35.5, 24.0)),
+            description: None,
+            city: Some("Chania".into()),
+            country: None,
+            camera: None,
+            duration: None,
+            fps: None,
+            web_url: None,
+            api_url: None,
+        }
+    }
+
+    fn group(id: &str, needs_review: bool) -> DuplicateAnalysis {
+        DuplicateAnalysis {
+            duplicate_id: id.to_string(),
+            winner: asset(&format!("{}-w", id), 2_000),
+            losers: vec![asset(&format!("{}-a", id), 1_000), asset(&format!("{}-b", id), 500)],
+            conflicts: if needs_review {
+                vec![MetadataConflict::Timezone {
+                    values: vec!["+02:00".into(), "+03:00".into()],
+                }]
+            } else {
+                Vec::new()
+            },
+            needs_review,
+            review_reasons: Vec::new(),
+            decision: None,
+            corrupt_asset_ids: Vec::new(),
+            confidence: 90,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_groups_round_trip_and_query() {
+        let mut store = AnalysisStore::open_in_memory().unwrap();
+        let groups = vec![group("dup-1", false), group("dup-2", true), group("dup-3", false)];
+        store.replace_groups(&groups).unwrap();
+        store.set_meta("server_url", &"http://immich.local").unwrap();
+
+        assert_eq!(store.group_count().unwrap(), 3);
+        let loaded = store.groups().unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&groups).unwrap()
+        );
+        assert_eq!(store.meta::<String>("server_url").unwrap().unwrap(), "http://immich.local");
+        assert!(store.meta::<String>("missing").unwrap().is_none());
+
+        let review = store.groups_needing_review().unwrap();
+        assert_eq!(review.len(), 1);
+        assert_eq!(review[0].duplicate_id, "dup-2");
+        assert_eq!(store.groups_with_conflict("timezone").unwrap().len(), 1);
+        assert_eq!(store.groups_containing("dup-3-b").unwrap()[0].duplicate_id, "dup-3");
+        assert!(store.group("dup-9").unwrap().is_none());
+        assert_eq!(store.reclaimable_bytes().unwrap(), 3 * 1_500);
+
+        // Replacing drops the old groups
+        store.replace_groups(&groups[..1]).unwrap();
+        assert_eq!(store.group_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_execution_runs_recorded() {
+        let mut store = AnalysisStore::open_in_memory().unwrap();
+        let mut report = ExecutionReport::new();
+        report.add_group_result(GroupResult {
+            duplicate_id: "dup-1".to_string(),
+            winner_id: "w1".to_string(),
+            consolidation_result: None,
+            download_results: vec![OperationResult::Success { id: "l1".to_string(), path: None }],
+            delete_result: Some(OperationResult::Success { id: "dup-1".to_string(), path: None }),
+            deleted_ids: vec!["l1".to_string()],
+            rolled_back_ids: Vec::new(),
+            reclaimed_bytes: 0,
+            trashed_bytes: 0,
+            promotions: Vec::new(),
+            decision: None,
+        });
+        report.add_group_result(GroupResult {
+            duplicate_id: "dup-2".to_string(),
+            winner_id: "w2".to_string(),
+            consolidation_result: None,
+            download_results: vec![OperationResult::Failed {
+                id: "l2".to_string(),
+                error: "timeout".to_string(),
+            }],
+            delete_result: Some(OperationResult::Skipped {
+                id: "dup-2".to_string(),
+                reason: "No assets were successfully downloaded".to_string(),
+            }),
+            deleted_ids: Vec::new(),
+            rolled_back_ids: Vec::new(),
+            reclaimed_bytes: 0,
+            trashed_bytes: 0,
+            promotions: Vec::new(),
+            decision: None,
+        });
+
+        let run_id = store.record_execution(&report).unwrap();
+        let runs = store.execution_runs().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run_id, run_id);
+        assert_eq!(runs[0].total_groups, 2);
+        assert_eq!(store.failed_groups(run_id).unwrap(), vec!["dup-2"]);
+    }
+
+    #[test]
+    fn test_history_recorded_in_store() {
+        use crate::clock::FixedClock;
+        use crate::history::{ExecutionEvent, RunRecorder};
+        use std::sync::Arc;
+
+        let history: Arc<dyn HistoryStore> = Arc::new(SqliteHistory::new(AnalysisStore::open_in_memory().unwrap()));
+        let clock = Arc::new(FixedClock::new("2025-01-01T12:00:00Z".parse().unwrap()));
+        let first = RunRecorder::start(Arc::clone(&history), clock.clone()).unwrap();
+        first
+            .record(ExecutionEvent::GroupStarted {
+                group_id: "dup-1".to_string(),
+                winner_id: "w".to_string(),
+                losers: 1,
+            })
+            .unwrap();
+        first
+            .record(ExecutionEvent::RunFinished {
+                deleted: 1,
+                failed: 0,
+                skipped: 0,
+            })
+            .unwrap();
+        // A second run in the same second gets its own ID
+        let second = RunRecorder::start(Arc::clone(&history), clock).unwrap();
+        assert_eq!((first.run_id(), second.run_id()), ("20250101-120000", "20250101-120000-2"));
+
+        let runs = history.runs().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!((runs[0].groups, runs[0].finished), (1, true));
+        assert!(!runs[1].finished);
+
+        let timeline = history.timeline(first.run_id()).unwrap();
+        assert_eq!(timeline[0].event.group_id(), Some("dup-1"));
+        assert!(matches!(timeline[1].event, ExecutionEvent::RunFinished { .. }));
+        assert!(matches!(history.timeline("missing"), Err(ImmichError::NotFound { .. })));
+    }
+
+    #[test]
+    fn test_store_persists_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("analysis.db");
+        AnalysisStore::open(&path)
+            .unwrap()
+            .replace_groups(&[group("dup-1", false)])
+            .unwrap();
+
+        let reopened = AnalysisStore::open(&path).unwrap();
+        assert_eq!(reopened.group("dup-1").unwrap().unwrap().losers.len(), 2);
+    }
+}
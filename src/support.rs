@@ -0,0 +1,243 @@
+//! Support bundles for bug reports.
+//!
+//! A [`SupportBundle`] collects diagnostic files (doctor findings, recent
+//! reports, logs, environment info) and writes them as a single `.tar.gz`.
+//! Every text file is scrubbed of known secrets (API keys) as it is added,
+//! so the bundle can be attached to a public issue.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+
+use crate::archive::{ustar_header, BLOCK};
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+
+/// Replacement for redacted secrets.
+pub const REDACTED: &str = "<redacted>";
+
+/// Secrets shorter than this are not redacted (they would match ordinary text).
+const MIN_SECRET_LEN: usize = 8;
+
+/// Longest member name a plain ustar header can hold, including the
+/// bundle's root directory.
+const MAX_NAME_LEN: usize = 99;
+
+/// Environment the tool is running in.
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentInfo {
+    /// Version of this tool
+    pub version: String,
+
+    /// Operating system (e.g. `linux`)
+    pub os: String,
+
+    /// CPU architecture (e.g. `x86_64`)
+    pub arch: String,
+
+    /// When the bundle was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl EnvironmentInfo {
+    /// Describe the current process.
+    pub fn current() -> Self {
+        Self::current_with_clock(&SystemClock)
+    }
+
+    /// Like [`current`](Self::current), stamped with the time from `clock`.
+    pub(crate) fn current_with_clock(clock: &dyn Clock) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            created_at: clock.now(),
+        }
+    }
+}
+
+/// Files to include in a support bundle, redacted as they are added.
+#[derive(Debug, Default)]
+pub struct SupportBundle {
+    root: String,
+    secrets: Vec<String>,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl SupportBundle {
+    /// Create a bundle whose files are written under `root/`, redacting the
+    /// given secrets from every text file.
+    pub fn new<S: AsRef<str>>(root: &str, secrets: &[S]) -> Self {
+        Self {
+            root: root.trim_matches('/').to_string(),
+            secrets: secrets
+                .iter()
+                .map(|s| s.as_ref().trim().to_string())
+                .filter(|s| s.len() >= MIN_SECRET_LEN)
+                .collect(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Add a file under `name` (a relative path inside the bundle).
+    ///
+    /// UTF-8 contents are redacted; binary contents are added unchanged.
+    /// The name is refused if, with the root directory in front of it, it
+    /// wouldn't fit in a tar header.
+    pub fn add_bytes(&mut self, name: &str, contents: Vec<u8>) -> Result<()> {
+        let name = name.trim_start_matches('/');
+        let too_long = self.member(name).len() > MAX_NAME_LEN;
+        if name.is_empty() || too_long || name.split('/').any(|part| part == "..") {
+            return Err(ImmichError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid bundle member name '{}'", name),
+            )));
+        }
+
+        let contents = match String::from_utf8(contents) {
+            Ok(text) => self.redact(&text).into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+        self.files.push((name.to_string(), contents));
+        Ok(())
+    }
+
+    /// Add a value as pretty-printed JSON.
+    pub fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_vec_pretty(value).map_err(std::io::Error::other)?;
+        self.add_bytes(name, json)
+    }
+
+    /// Add a file from disk, decompressing `.zst` reports so they are readable.
+    pub fn add_file(&mut self, name: &str, path: &Path) -> Result<()> {
+        let contents = std::fs::read(path)?;
+        let contents = if crate::report_io::is_compressed(path) {
+            zstd::decode_all(contents.as_slice())?
+        } else {
+            contents
+        };
+        self.add_bytes(name.trim_end_matches(".zst"), contents)
+    }
+
+    /// Names of the files added so far.
+    pub fn file_names(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Replace every known secret in `text`.
+    pub fn redact(&self, text: &str) -> String {
+        self.secrets
+            .iter()
+            .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+    }
+
+    /// Path of a file inside the archive: `name` under the root directory.
+    fn member(&self, name: &str) -> String {
+        format!("{}/{}", self.root, name)
+    }
+
+    /// Write the bundle as a gzipped tar archive, with every file under the
+    /// root directory.
+    pub fn write_tar_gz(&self, path: &Path) -> Result<()> {
+        self.write_tar_gz_with_clock(path, &SystemClock)
+    }
+
+    /// Like [`write_tar_gz`](Self::write_tar_gz), with the files' modification
+    /// times taken from `clock`.
+    pub(crate) fn write_tar_gz_with_clock(&self, path: &Path, clock: &dyn Clock) -> Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        let mtime = clock.now().timestamp().max(0) as u64;
+
+        for (name, contents) in &self.files {
+            let member = self.member(name);
+            encoder.write_all(&ustar_header(&member, contents.len() as u64, mtime, b'0')?)?;
+            encoder.write_all(contents)?;
+            let padding = (BLOCK - contents.len() % BLOCK) % BLOCK;
+            encoder.write_all(&vec![0u8; padding])?;
+        }
+
+        // Two zero blocks mark the end of the archive
+        encoder.write_all(&[0u8; BLOCK * 2])?;
+        encoder.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_secrets_redacted_and_short_values_ignored() {
+        let bundle = SupportBundle::new("support-bundle", &["abcdef1234567890", "short", ""]);
+        assert_eq!(
+            bundle.redact("x-api-key: abcdef1234567890 (short)"),
+            "x-api-key: <redacted> (short)"
+        );
+    }
+
+    #[test]
+    fn test_member_names_validated() {
+        let root = "immich-dupes-support-20250101-120000";
+        let mut bundle = SupportBundle::new::<&str>(root, &[]);
+        assert!(bundle.add_bytes("../escape.txt", Vec::new()).is_err());
+        assert!(bundle.add_bytes(&"x".repeat(120), Vec::new()).is_err());
+
+        // Fits on its own, but not behind the root directory
+        let longest = MAX_NAME_LEN - root.len() - 1;
+        assert!(bundle.add_bytes(&"x".repeat(longest + 1), Vec::new()).is_err());
+        assert!(bundle.add_bytes(&"x".repeat(longest), Vec::new()).is_ok());
+        bundle.files.clear();
+
+        assert!(bundle.add_bytes("/reports/run.json", Vec::new()).is_ok());
+        assert_eq!(bundle.file_names().collect::<Vec<_>>(), vec!["reports/run.json"]);
+    }
+
+    #[test]
+    fn test_tar_gz_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut bundle = SupportBundle::new("support-bundle", &["secret-api-key-123"]);
+        bundle
+            .add_bytes("logs/run.log", b"using key secret-api-key-123\n".to_vec())
+            .unwrap();
+        bundle.add_bytes("binary.bin", vec![0xff, 0xfe, 0x00]).unwrap();
+
+        let path = dir.path().join("bundle.tar.gz");
+        bundle.write_tar_gz(&path).unwrap();
+
+        let mut tar = Vec::new();
+        flate2::read::GzDecoder::new(File::open(&path).unwrap())
+            .read_to_end(&mut tar)
+            .unwrap();
+
+        // First member: header, then contents padded to a block
+        let name = String::from_utf8_lossy(&tar[..100]).trim_end_matches('\0').to_string();
+        assert_eq!(name, "support-bundle/logs/run.log");
+        let size = u64::from_str_radix(std::str::from_utf8(&tar[124..135]).unwrap(), 8).unwrap();
+        let contents = &tar[BLOCK..BLOCK + size as usize];
+        assert_eq!(contents, b"using key <redacted>\n");
+        assert_eq!(&tar[257..262], b"ustar");
+
+        // Checksum covers the header with the checksum field as spaces
+        let mut header = tar[..BLOCK].to_vec();
+        let stored = u32::from_str_radix(std::str::from_utf8(&header[148..154]).unwrap(), 8).unwrap();
+        header[148..156].copy_from_slice(b"        ");
+        assert_eq!(stored, header.iter().map(|&b| u32::from(b)).sum::<u32>());
+
+        // Second member starts on the next block; binary contents are untouched
+        let second = &tar[BLOCK * 2..];
+        assert!(second.starts_with(b"support-bundle/binary.bin"));
+        assert_eq!(&second[BLOCK..BLOCK + 3], &[0xff, 0xfe, 0x00]);
+
+        // Archive ends with two zero blocks
+        assert_eq!(tar.len(), BLOCK * 6);
+        assert!(tar[BLOCK * 4..].iter().all(|&b| b == 0));
+    }
+}
@@ -0,0 +1,185 @@
+//! Tag-driven runs.
+//!
+//! A run can be scoped to the duplicate groups touching assets that carry a
+//! tag (say `dedupe/queue`, applied in the Immich UI), and the winners it
+//! processes can be tagged afterwards (say `dedupe/processed-2025-01`), so
+//! the whole workflow can be queued and followed up from Immich itself.
+//! An analysis can also be mirrored as tags ([`tag_analysis`]), so each
+//! group's state is visible and searchable in the Immich UI.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::client::ImmichClient;
+use crate::error::{ImmichError, Result};
+use crate::models::{DuplicateGroup, TagResponse};
+use crate::scoring::DuplicateAnalysis;
+
+/// Tag [`tag_analysis`] puts on winners unless another is given.
+pub const DEFAULT_WINNER_TAG: &str = "dedupe:winner";
+
+/// Tag [`tag_analysis`] puts on groups flagged for review unless another is given.
+pub const DEFAULT_REVIEW_TAG: &str = "dedupe:review";
+
+/// Assets tagged by [`tag_analysis`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnalysisTags {
+    /// Winners tagged with the winner tag
+    pub winners: usize,
+    /// Assets of flagged groups tagged with the review tag
+    pub review: usize,
+}
+
+/// Find a tag by full value (e.g. `dedupe/queue`), name, or ID.
+///
+/// # Errors
+///
+/// Returns [`ImmichError::NotFound`] if no tag matches, or an error if the
+/// tags cannot be fetched.
+pub async fn find_tag(client: &ImmichClient, tag: &str) -> Result<TagResponse> {
+    let tags = client.get_tags().await?;
+    tags.iter()
+        .find(|t| t.value == tag || t.id == tag)
+        .or_else(|| tags.iter().find(|t| t.name == tag))
+        .cloned()
+        .ok_or_else(|| ImmichError::NotFound {
+            message: format!("tag '{}'", tag),
+        })
+}
+
+/// Keep only the groups with at least one of the given assets.
+pub fn scope_to_assets(groups: Vec<DuplicateGroup>, asset_ids: &HashSet<String>) -> Vec<DuplicateGroup> {
+    groups
+        .into_iter()
+        .filter(|g| g.assets.iter().any(|a| asset_ids.contains(&a.id)))
+        .collect()
+}
+
+/// Keep only the groups with at least one asset carrying `tag` (matched as
+/// by [`find_tag`]).
+///
+/// # Errors
+///
+/// Returns an error if the tag does not exist or its assets cannot be fetched.
+pub async fn scope_to_tag(client: &ImmichClient, groups: Vec<DuplicateGroup>, tag: &str) -> Result<Vec<DuplicateGroup>> {
+    let tag = find_tag(client, tag).await?;
+    let tagged: HashSet<String> = client
+        .get_tagged_assets(&tag.id)
+        .await?
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    Ok(scope_to_assets(groups, &tagged))
+}
+
+/// Attach the tag with full value `value` to assets, creating the tag first
+/// if it doesn't exist yet. Returns the tag.
+///
+/// # Errors
+///
+/// Returns an error if the tag cannot be created or any asset could not be tagged.
+pub async fn tag_assets(client: &ImmichClient, value: &str, asset_ids: &[String]) -> Result<TagResponse> {
+    let tag = client
+        .upsert_tags(&[value.to_string()])
+        .await?
+        .into_iter()
+        .find(|t| t.value == value)
+        .ok_or_else(|| ImmichError::NotFound {
+            message: format!("tag '{}' after creating it", value),
+        })?;
+    if !asset_ids.is_empty() {
+        client.tag_assets(&tag.id, asset_ids).await?;
+    }
+    Ok(tag)
+}
+
+/// Tag the state of an analysis in Immich: the winner of every group that
+/// is ready to execute gets `winner_tag`, and every asset of a group flagged
+/// for review and not yet decided gets `review_tag` (its winner isn't
+/// settled, so it isn't tagged as one). Tags are created if they don't exist.
+///
+/// # Errors
+///
+/// Returns an error if either tag cannot be created or attached.
+pub async fn tag_analysis(
+    client: &ImmichClient,
+    groups: &[DuplicateAnalysis],
+    winner_tag: &str,
+    review_tag: &str,
+) -> Result<AnalysisTags> {
+    let (review, ready): (Vec<&DuplicateAnalysis>, Vec<&DuplicateAnalysis>) =
+        groups.iter().partition(|g| g.needs_review && g.decision.is_none());
+    let winners: Vec<String> = ready
+        .iter()
+        .filter(|g| g.should_execute(false))
+        .map(|g| g.winner.asset_id.to_string())
+        .collect();
+    let flagged: Vec<String> = review
+        .iter()
+        .flat_map(|g| std::iter::once(&g.winner).chain(&g.losers))
+        .map(|a| a.asset_id.to_string())
+        .collect();
+
+    if !winners.is_empty() {
+        tag_assets(client, winner_tag, &winners).await?;
+    }
+    if !flagged.is_empty() {
+        tag_assets(client, review_tag, &flagged).await?;
+    }
+    Ok(AnalysisTags {
+        winners: winners.len(),
+        review: flagged.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[tokio::test]
+    async fn test_scope_and_tag() {
+        let groups = synthetic_groups(3, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let queued = groups[1].assets[1].id.clone();
+        server.add_tag("tag-queue", "dedupe/queue", &[&queued]);
+
+        let scoped = scope_to_tag(&client, groups.clone(), "dedupe/queue").await.unwrap();
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].duplicate_id, groups[1].duplicate_id);
+
+        // Matched by name too; an unknown tag is an error, not an empty scope
+        assert_eq!(scope_to_tag(&client, groups.clone(), "queue").await.unwrap().len(), 1);
+        assert!(matches!(
+            scope_to_tag(&client, groups.clone(), "dedupe/missing").await,
+            Err(ImmichError::NotFound { .. })
+        ));
+
+        let winners = vec![groups[0].assets[0].id.clone()];
+        tag_assets(&client, "dedupe/processed", &winners).await.unwrap();
+        // Tagging again is not an error
+        let tag = tag_assets(&client, "dedupe/processed", &winners).await.unwrap();
+        assert_eq!(tag.name, "processed");
+        assert_eq!(server.tagged_assets("dedupe/processed"), winners);
+    }
+
+    #[tokio::test]
+    async fn test_tag_analysis() {
+        let groups = synthetic_groups(3, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let mut analyses: Vec<DuplicateAnalysis> = groups.iter().map(DuplicateAnalysis::from_group).collect();
+        for analysis in &mut analyses {
+            analysis.needs_review = false;
+        }
+        analyses[2].needs_review = true;
+
+        let tags = tag_analysis(&client, &analyses, DEFAULT_WINNER_TAG, DEFAULT_REVIEW_TAG).await.unwrap();
+        assert_eq!((tags.winners, tags.review), (2, 2));
+        let winners: Vec<String> = analyses[..2].iter().map(|g| g.winner.asset_id.to_string()).collect();
+        assert_eq!(server.tagged_assets(DEFAULT_WINNER_TAG), winners);
+        assert_eq!(server.tagged_assets(DEFAULT_REVIEW_TAG).len(), 2);
+    }
+}
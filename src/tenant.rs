@@ -0,0 +1,432 @@
+//! Running analyses against several Immich servers in one process.
+//!
+//! Each tenant owns its own client, rate limiter, and execution settings, so
+//! nothing is shared between servers. Reports and backups are namespaced by
+//! tenant name.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::future::join_all;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::AnalysisReport;
+use crate::client::ImmichClient;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ImmichError, Result};
+use crate::executor::Executor;
+use crate::models::{AssetResponse, DuplicateGroup, ExecutionConfig};
+use crate::rate_limit::AdaptiveRateLimiter;
+use crate::scoring::{DuplicateAnalysis, Thresholds};
+use crate::support::REDACTED;
+
+/// Connection and limit settings for one tenant.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// Unique tenant name, used to namespace reports and backups
+    pub name: String,
+
+    /// Immich server URL
+    pub url: String,
+
+    /// API key for the server
+    pub api_key: String,
+
+    /// Maximum requests per second to this server
+    #[serde(default = "default_requests_per_sec")]
+    pub requests_per_sec: u32,
+
+    /// Maximum concurrent operations against this server
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+}
+
+/// Prints the API key as [`REDACTED`].
+impl fmt::Debug for TenantConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantConfig")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("api_key", &REDACTED)
+            .field("requests_per_sec", &self.requests_per_sec)
+            .field("max_concurrent", &self.max_concurrent)
+            .finish()
+    }
+}
+
+fn default_requests_per_sec() -> u32 {
+    ExecutionConfig::default().requests_per_sec
+}
+
+fn default_max_concurrent() -> usize {
+    ExecutionConfig::default().max_concurrent
+}
+
+/// A single tenant with its own client and rate limiter.
+pub struct Tenant {
+    /// Tenant settings
+    config: TenantConfig,
+
+    /// Client for this tenant's server
+    client: ImmichClient,
+
+    /// Rate limiter for every request to this tenant's server, shared with
+    /// its executors
+    rate_limiter: Arc<AdaptiveRateLimiter>,
+
+    /// Thresholds groups are analyzed with
+    thresholds: Thresholds,
+
+    /// Source of report and execution timestamps
+    clock: Arc<dyn Clock>,
+}
+
+impl Tenant {
+    /// Create a tenant from its configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name isn't a safe path component or the
+    /// client can't be created.
+    pub fn new(config: TenantConfig) -> Result<Self> {
+        validate_tenant_name(&config.name)?;
+
+        let client = ImmichClient::new(&config.url, &config.api_key)?;
+        // Start at the configured requests per second (10 if unset), as executors do
+        let requests_per_sec = if config.requests_per_sec == 0 { 10 } else { config.requests_per_sec };
+
+        Ok(Self {
+            config,
+            client,
+            rate_limiter: Arc::new(AdaptiveRateLimiter::new(requests_per_sec)),
+            thresholds: Thresholds::default(),
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Take timestamps from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Analyze groups with `thresholds` instead of the defaults.
+    pub fn with_thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Tenant name.
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Client for this tenant's server.
+    pub fn client(&self) -> &ImmichClient {
+        &self.client
+    }
+
+    /// Fetch and score this tenant's duplicate groups as `analyze` does.
+    ///
+    /// Assets listed without their EXIF data are filled in, groups are
+    /// scored with the tenant's thresholds and linked to its server, and
+    /// memories are flagged if the key may list them. Every request waits
+    /// on the tenant's rate limiter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the duplicates or an incomplete asset can't be
+    /// fetched.
+    pub async fn analyze(&self) -> Result<TenantReport> {
+        let limiter = &self.rate_limiter;
+        let mut duplicates = limiter.run(|| self.client.get_duplicates()).await?;
+
+        // Fill in slim assets a few at a time; ones the server no longer has stay slim
+        let incomplete = DuplicateGroup::incomplete_asset_ids(&duplicates);
+        let fetched: Vec<Result<AssetResponse>> = futures::stream::iter(&incomplete)
+            .map(|id| limiter.run(move || self.client.get_asset(id)))
+            .buffered(self.config.max_concurrent.max(1))
+            .collect()
+            .await;
+        let mut assets = Vec::with_capacity(fetched.len());
+        for result in fetched {
+            match result {
+                Ok(asset) => assets.push(asset),
+                Err(ImmichError::NotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        DuplicateGroup::fill_in(&mut duplicates, assets);
+
+        let mut groups = DuplicateAnalysis::from_groups_with_options(&duplicates, &self.thresholds);
+        // Memories are best effort: the key may lack permission to list them
+        if let Ok(memories) = limiter.run(|| self.client.get_memories()).await {
+            for group in &mut groups {
+                group.flag_memories(&memories);
+            }
+        }
+
+        Ok(TenantReport {
+            tenant: self.config.name.clone(),
+            report: AnalysisReport::new_with_clock(&self.config.url, groups, self.clock.as_ref()),
+        })
+    }
+
+    /// Execution settings for this tenant, with backups under `backup_root/<tenant>`.
+    pub fn execution_config(&self, backup_root: &Path, force_delete: bool) -> ExecutionConfig {
+        ExecutionConfig {
+            requests_per_sec: self.config.requests_per_sec,
+            max_concurrent: self.config.max_concurrent,
+            backup_dir: backup_root.join(&self.config.name),
+            force_delete,
+            ..Default::default()
+        }
+    }
+
+    /// Build an executor bound to this tenant's server and limits.
+    pub fn executor(&self, backup_root: &Path, force_delete: bool) -> Executor {
+        Executor::new(
+            self.client.clone(),
+            self.execution_config(backup_root, force_delete),
+        )
+        .with_clock(Arc::clone(&self.clock))
+        .with_rate_limiter(Arc::clone(&self.rate_limiter))
+    }
+}
+
+/// Analysis results for one tenant: an [`AnalysisReport`] with the tenant's
+/// name alongside, which `execute` and the other commands read as is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantReport {
+    /// Tenant name
+    pub tenant: String,
+
+    /// The tenant's analysis
+    #[serde(flatten)]
+    pub report: AnalysisReport,
+}
+
+impl TenantReport {
+    /// Path of this report under `dir` (`dir/<tenant>/analysis.json`).
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        tenant_report_path(dir, &self.tenant)
+    }
+
+    /// Write the report as JSON to its namespaced path, creating directories.
+    pub async fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let path = self.path(dir);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        tokio::fs::write(&path, json).await?;
+        Ok(path)
+    }
+}
+
+/// Manages a set of independent tenants.
+///
+/// # Example
+///
+/// ```no_run
+/// use immich_lib::tenant::{TenantConfig, TenantManager};
+///
+/// # async fn example() -> immich_lib::Result<()> {
+/// let manager = TenantManager::new(vec![
+///     TenantConfig {
+///         name: "mum".into(),
+///         url: "https://immich.mum.example".into(),
+///         api_key: "key-1".into(),
+///         requests_per_sec: 5,
+///         max_concurrent: 2,
+///     },
+/// ])?;
+///
+/// for (name, result) in manager.analyze_all().await {
+///     match result {
+///         Ok(tenant) => println!("{}: {} groups", name, tenant.report.total_groups),
+///         Err(e) => eprintln!("{}: {}", name, e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TenantManager {
+    tenants: Vec<Tenant>,
+}
+
+impl TenantManager {
+    /// Create a manager from tenant configurations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any tenant is invalid or two tenants share a name.
+    pub fn new(configs: Vec<TenantConfig>) -> Result<Self> {
+        let mut tenants: Vec<Tenant> = Vec::with_capacity(configs.len());
+
+        for config in configs {
+            if tenants.iter().any(|t| t.name() == config.name) {
+                return Err(ImmichError::InvalidTenant(format!(
+                    "duplicate tenant name '{}'",
+                    config.name
+                )));
+            }
+            tenants.push(Tenant::new(config)?);
+        }
+
+        Ok(Self { tenants })
+    }
+
+    /// Take every tenant's timestamps from `clock` instead of the system clock.
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        let tenants = self
+            .tenants
+            .into_iter()
+            .map(|tenant| tenant.with_clock(Arc::clone(&clock)))
+            .collect();
+        Self { tenants }
+    }
+
+    /// Analyze every tenant's groups with `thresholds`.
+    pub fn with_thresholds(self, thresholds: Thresholds) -> Self {
+        let tenants = self
+            .tenants
+            .into_iter()
+            .map(|tenant| tenant.with_thresholds(thresholds.clone()))
+            .collect();
+        Self { tenants }
+    }
+
+    /// All managed tenants.
+    pub fn tenants(&self) -> &[Tenant] {
+        &self.tenants
+    }
+
+    /// Look up a tenant by name.
+    pub fn get(&self, name: &str) -> Option<&Tenant> {
+        self.tenants.iter().find(|t| t.name() == name)
+    }
+
+    /// Analyze every tenant concurrently.
+    ///
+    /// Each tenant is limited by its own rate limiter. A failure on one server
+    /// doesn't affect the others; results are returned in tenant order.
+    pub async fn analyze_all(&self) -> Vec<(String, Result<TenantReport>)> {
+        let analyses = self.tenants.iter().map(|tenant| async move {
+            (tenant.name().to_string(), tenant.analyze().await)
+        });
+        join_all(analyses).await
+    }
+}
+
+/// Path of a tenant's analysis report under `dir`.
+pub fn tenant_report_path(dir: &Path, tenant: &str) -> PathBuf {
+    dir.join(tenant).join("analysis.json")
+}
+
+/// Check that a tenant name is non-empty and safe to use as a directory name.
+fn validate_tenant_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ImmichError::InvalidTenant(format!(
+            "'{}' must contain only letters, digits, '-' or '_'",
+            name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str) -> TenantConfig {
+        TenantConfig {
+            name: name.to_string(),
+            url: format!("https://{}.example.com", name),
+            api_key: "key".to_string(),
+            requests_per_sec: 3,
+            max_concurrent: 1,
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_api_key() {
+        let debug = format!("{:?}", config("mum"));
+        assert!(debug.contains("api_key: \"<redacted>\""));
+        assert!(!debug.contains("\"key\""));
+    }
+
+    #[test]
+    fn test_duplicate_tenant_names_rejected() {
+        let result = TenantManager::new(vec![config("mum"), config("mum")]);
+        assert!(matches!(result, Err(ImmichError::InvalidTenant(_))));
+    }
+
+    #[test]
+    fn test_unsafe_tenant_name_rejected() {
+        assert!(Tenant::new(config("../etc")).is_err());
+        assert!(Tenant::new(config("")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_analyze_follows_analysis_path() {
+        use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+        let mut groups = synthetic_groups(2, 2).unwrap();
+        groups[0].assets[1].is_trashed = true;
+        let server = MockServer::start(&groups).await.unwrap();
+        server.rate_limit_next(2);
+        let tenant = Tenant::new(TenantConfig {
+            url: server.url(),
+            requests_per_sec: 100,
+            ..config("mum")
+        })
+        .unwrap()
+        .with_thresholds(Thresholds {
+            trashed: crate::scoring::TrashedPolicy::Skip,
+            ..Thresholds::default()
+        });
+
+        let analysis = tenant.analyze().await.unwrap();
+        assert_eq!(analysis.tenant, "mum");
+        let report = &analysis.report;
+        assert_eq!(report.schema_version, crate::analysis::SCHEMA_VERSION);
+        assert_eq!(report.total_groups, 2);
+        // Scored with the tenant's thresholds, which skip the trashed group
+        assert!(!report.groups[0].should_execute(false));
+        assert!(report.groups[1].should_execute(false));
+        assert!(report.groups[0].winner.web_url.as_deref().is_some_and(|u| u.starts_with(&server.url())));
+
+        // The 429s were absorbed by the limiter the tenant's executors share
+        assert_eq!(tenant.rate_limiter.stats().throttled, 2);
+
+        let json = serde_json::to_value(&analysis).unwrap();
+        assert_eq!(json["tenant"], "mum");
+        assert_eq!(json["schema_version"], crate::analysis::SCHEMA_VERSION);
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(analysis.save(dir.path()).await.unwrap(), dir.path().join("mum/analysis.json"));
+        assert_eq!(AnalysisReport::load(analysis.path(dir.path())).unwrap().total_groups, 2);
+    }
+
+    #[test]
+    fn test_tenant_paths_are_namespaced() {
+        let manager = TenantManager::new(vec![config("mum"), config("dad")]).unwrap();
+        let tenant = manager.get("dad").unwrap();
+
+        let exec = tenant.execution_config(Path::new("/backups"), false);
+        assert_eq!(exec.backup_dir, PathBuf::from("/backups/dad"));
+        assert_eq!(exec.requests_per_sec, 3);
+        assert_eq!(
+            tenant_report_path(Path::new("/reports"), "mum"),
+            PathBuf::from("/reports/mum/analysis.json")
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Proptest strategies for assets and duplicate groups.
+//!
+//! The strategies draw dimensions and file sizes from small pools as well as
+//! wide ranges, so generated groups regularly contain ties, and build assets
+//! with [`AssetBuilder`] so every combination of present and missing
+//! metadata shows up.
+
+use chrono::{TimeZone, Utc};
+use proptest::prelude::*;
+
+use super::builder::{AssetBuilder, GroupBuilder};
+use crate::models::{AssetResponse, DuplicateGroup};
+
+/// Dimensions: common camera sizes (to force ties) or anything up to 8000px.
+pub fn arb_dimensions() -> impl Strategy<Value = (u32, u32)> {
+    prop_oneof![
+        Just((4032, 3024)),
+        Just((3024, 4032)),
+        Just((1920, 1080)),
+        (1u32..8000, 1u32..8000),
+    ]
+}
+
+/// File size in bytes: a shared size (to force ties) or anything up to 50 MB.
+pub fn arb_file_size() -> impl Strategy<Value = u64> {
+    prop_oneof![Just(2_000_000u64), 1u64..50_000_000]
+}
+
+/// Metadata that can be added to an asset, each part present or not.
+#[derive(Debug, Clone)]
+pub struct ArbMetadata {
+    /// GPS coordinates
+    pub gps: Option<(f64, f64)>,
+    /// Camera make and model
+    pub camera: Option<(String, String)>,
+    /// Lens model
+    pub lens: Option<String>,
+    /// Timezone
+    pub timezone: Option<String>,
+    /// Capture time, in seconds since the epoch
+    pub capture_time: Option<i64>,
+    /// City and country
+    pub location: Option<(String, String)>,
+}
+
+impl ArbMetadata {
+    /// Add this metadata to an asset.
+    pub fn apply(&self, mut asset: AssetBuilder) -> AssetBuilder {
+        if let Some((latitude, longitude)) = self.gps {
+            asset = asset.with_gps(latitude, longitude);
+        }
+        if let Some((make, model)) = &self.camera {
+            asset = asset.with_camera(make, model);
+        }
+        if let Some(lens) = &self.lens {
+            asset = asset.with_lens(lens);
+        }
+        if let Some(timezone) = &self.timezone {
+            asset = asset.with_timezone(timezone);
+        }
+        if let Some(taken_at) = self.capture_time.and_then(|t| Utc.timestamp_opt(t, 0).single()) {
+            asset = asset.with_capture_time(taken_at);
+        }
+        if let Some((city, country)) = &self.location {
+            asset = asset.with_location(city, country);
+        }
+        asset
+    }
+}
+
+/// Any combination of metadata.
+pub fn arb_metadata() -> impl Strategy<Value = ArbMetadata> {
+    let camera = prop::sample::select(vec![("Apple", "iPhone 15 Pro"), ("Canon", "EOS R5"), ("Sony", "ILCE-7M4")]);
+    let lens = prop::sample::select(vec!["24-70mm F2.8", "iPhone 15 Pro back camera"]);
+    let timezone = prop::sample::select(vec!["Europe/London", "America/New_York", "UTC+9"]);
+    let location = prop::sample::select(vec![("London", "United Kingdom"), ("Paris", "France")]);
+    (
+        prop::option::of((-90.0f64..90.0, -180.0f64..180.0)),
+        prop::option::of(camera.prop_map(|(make, model)| (make.to_string(), model.to_string()))),
+        prop::option::of(lens.prop_map(str::to_string)),
+        prop::option::of(timezone.prop_map(str::to_string)),
+        prop::option::of(0i64..2_000_000_000),
+        prop::option::of(location.prop_map(|(city, country)| (city.to_string(), country.to_string()))),
+    )
+        .prop_map(|(gps, camera, lens, timezone, capture_time, location)| ArbMetadata {
+            gps,
+            camera,
+            lens,
+            timezone,
+            capture_time,
+            location,
+        })
+}
+
+/// An image asset with ID `id`, with or without dimensions, size, and metadata.
+pub fn arb_image(id: String) -> impl Strategy<Value = AssetResponse> {
+    (
+        prop::option::of(arb_dimensions()),
+        prop::option::of(arb_file_size()),
+        arb_metadata(),
+    )
+        .prop_map(move |(dimensions, file_size, metadata)| {
+            let mut asset = metadata.apply(AssetBuilder::new(id.clone()));
+            if let Some((width, height)) = dimensions {
+                asset = asset.with_dimensions(width, height);
+            }
+            if let Some(bytes) = file_size {
+                asset = asset.with_file_size(bytes);
+            }
+            asset.build()
+        })
+}
+
+/// A group of 2 to `max_assets` images, with IDs `asset-0`, `asset-1`, ...
+pub fn arb_image_group(max_assets: usize) -> impl Strategy<Value = DuplicateGroup> {
+    (2..=max_assets.max(2))
+        .prop_flat_map(|count| (0..count).map(|i| arb_image(format!("asset-{}", i))).collect::<Vec<_>>())
+        .prop_map(|assets| GroupBuilder::new("arbitrary-group").with_assets(assets).build())
+}
@@ -0,0 +1,270 @@
+//! Throughput benchmark against the in-process mock server.
+//!
+//! Measures analysis and execution throughput on synthetic data so runs on
+//! different machines and versions can be compared, along with how much
+//! string data the analyses hold once repeated values are shared.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::client::ImmichClient;
+use crate::error::Result;
+use crate::executor::Executor;
+use crate::intern::SharedStr;
+use crate::models::ExecutionConfig;
+use crate::scoring::{DuplicateAnalysis, MetadataConflict, Thresholds};
+use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+/// Benchmark parameters.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Number of synthetic duplicate groups
+    pub groups: usize,
+
+    /// Assets per group (winner + losers)
+    pub assets_per_group: usize,
+
+    /// Whether to run the executor phase (fake downloads and deletes)
+    pub execute: bool,
+
+    /// Maximum concurrent operations for the executor
+    pub max_concurrent: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            groups: 1000,
+            assets_per_group: 2,
+            execute: true,
+            max_concurrent: ExecutionConfig::default().max_concurrent,
+        }
+    }
+}
+
+/// Timing for one benchmark phase.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    /// Wall-clock duration in seconds
+    pub seconds: f64,
+
+    /// Groups processed per second
+    pub groups_per_sec: f64,
+}
+
+impl PhaseTiming {
+    fn new(elapsed: Duration, groups: usize) -> Self {
+        let seconds = elapsed.as_secs_f64();
+        Self {
+            seconds,
+            groups_per_sec: if seconds > 0.0 { groups as f64 / seconds } else { 0.0 },
+        }
+    }
+}
+
+/// String data held by a set of analyses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StringFootprint {
+    /// Bytes actually allocated, counting each shared string once
+    pub shared_bytes: u64,
+
+    /// Bytes the same strings would take as one owned copy per field
+    pub unshared_bytes: u64,
+}
+
+impl StringFootprint {
+    /// Measure the string fields of `analyses`: asset identifiers and
+    /// metadata, and conflict values.
+    pub fn measure(analyses: &[DuplicateAnalysis]) -> Self {
+        let mut seen: HashSet<*const u8> = HashSet::new();
+        let mut footprint = Self::default();
+        let mut add = |value: &SharedStr| {
+            let len = value.len() as u64;
+            footprint.unshared_bytes += len;
+            if seen.insert(value.as_ptr()) {
+                footprint.shared_bytes += len;
+            }
+        };
+
+        for analysis in analyses {
+            for asset in std::iter::once(&analysis.winner).chain(&analysis.losers) {
+                add(&asset.asset_id);
+                add(&asset.filename);
+                for value in [
+                    &asset.checksum,
+                    &asset.library_id,
+                    &asset.capture_time,
+                    &asset.description,
+                    &asset.city,
+                    &asset.country,
+                    &asset.camera,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    add(value);
+                }
+            }
+            for conflict in &analysis.conflicts {
+                if let MetadataConflict::Timezone { values }
+                | MetadataConflict::CameraInfo { values }
+                | MetadataConflict::CaptureTime { values, .. }
+                | MetadataConflict::TimezoneShift { values, .. } = conflict
+                {
+                    values.iter().for_each(&mut add);
+                }
+            }
+        }
+        footprint
+    }
+}
+
+/// Benchmark results.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// Crate version that produced the report
+    pub version: String,
+
+    /// Number of groups benchmarked
+    pub groups: usize,
+
+    /// Total assets across all groups
+    pub assets: usize,
+
+    /// Fetch + score + conflict detection
+    pub analyze: PhaseTiming,
+
+    /// Executor run with fake downloads (if enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute: Option<PhaseTiming>,
+
+    /// Assets downloaded by the executor
+    pub downloaded: usize,
+
+    /// Failed executor operations
+    pub failed: usize,
+
+    /// String data held by the analyses
+    pub strings: StringFootprint,
+
+    /// Peak resident memory in KiB (Linux only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_memory_kib: Option<u64>,
+}
+
+impl std::fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Benchmark (immich-lib {})", self.version)?;
+        writeln!(f, "==========================")?;
+        writeln!(f, "Groups: {}  Assets: {}", self.groups, self.assets)?;
+        writeln!(
+            f,
+            "Analyze: {:.3}s ({:.0} groups/s)",
+            self.analyze.seconds, self.analyze.groups_per_sec
+        )?;
+        if let Some(execute) = &self.execute {
+            writeln!(
+                f,
+                "Execute: {:.3}s ({:.0} groups/s, {} downloaded, {} failed)",
+                execute.seconds, execute.groups_per_sec, self.downloaded, self.failed
+            )?;
+        }
+        writeln!(
+            f,
+            "String data: {:.1} MiB shared ({:.1} MiB as owned copies)",
+            self.strings.shared_bytes as f64 / MIB,
+            self.strings.unshared_bytes as f64 / MIB
+        )?;
+        match self.peak_memory_kib {
+            Some(kib) => writeln!(f, "Peak memory: {:.1} MiB", kib as f64 / 1024.0),
+            None => writeln!(f, "Peak memory: unavailable on this platform"),
+        }
+    }
+}
+
+/// Run the benchmark, writing fake backups under `scratch_dir`.
+pub async fn run_bench(options: &BenchOptions, scratch_dir: &Path) -> Result<BenchReport> {
+    let duplicate_groups = synthetic_groups(options.groups, options.assets_per_group)?;
+    let assets = duplicate_groups.iter().map(|g| g.assets.len()).sum();
+
+    let server = MockServer::start(&duplicate_groups).await?;
+    drop(duplicate_groups);
+    let client = ImmichClient::new(&server.url(), "bench-api-key")?;
+
+    // Analyze: fetch and score every group
+    let started = Instant::now();
+    let fetched = client.get_duplicates().await?;
+    let analyses = DuplicateAnalysis::from_groups_with_options(&fetched, &Thresholds::default());
+    let analyze = PhaseTiming::new(started.elapsed(), analyses.len());
+    drop(fetched);
+    let strings = StringFootprint::measure(&analyses);
+
+    // Execute: consolidate, download, and delete against the mock
+    let (execute, downloaded, failed) = if options.execute {
+        let config = ExecutionConfig {
+            requests_per_sec: u32::MAX,
+            max_concurrent: options.max_concurrent,
+            backup_dir: scratch_dir.join("backups"),
+            ..Default::default()
+        };
+        let executor = Executor::new(client, config);
+
+        let started = Instant::now();
+        let report = executor.execute_all(&analyses).await;
+        (
+            Some(PhaseTiming::new(started.elapsed(), analyses.len())),
+            report.downloaded,
+            report.failed,
+        )
+    } else {
+        (None, 0, 0)
+    };
+
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        groups: analyses.len(),
+        assets,
+        analyze,
+        execute,
+        downloaded,
+        failed,
+        strings,
+        peak_memory_kib: peak_memory_kib(),
+    })
+}
+
+/// Peak resident set size of this process in KiB, from `/proc/self/status`.
+fn peak_memory_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_bench_small() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = BenchOptions {
+            groups: 5,
+            ..Default::default()
+        };
+
+        let report = run_bench(&options, dir.path()).await.unwrap();
+        assert_eq!(report.groups, 5);
+        assert_eq!(report.assets, 10);
+        assert_eq!(report.downloaded, 5);
+        assert_eq!(report.failed, 0);
+        // Camera and capture time repeat across every asset
+        assert!(report.strings.shared_bytes < report.strings.unshared_bytes);
+    }
+}
@@ -0,0 +1,328 @@
+//! Builders for synthetic duplicate groups.
+//!
+//! [`GroupBuilder`] and [`AssetBuilder`] construct [`DuplicateGroup`]s and
+//! [`AssetResponse`]s in memory, so scoring, conflict and executor tests can
+//! describe exactly the metadata they need instead of loading JSON fixtures.
+//!
+//! ```
+//! use immich_lib::testing::{AssetBuilder, GroupBuilder};
+//! use immich_lib::DuplicateAnalysis;
+//!
+//! let group = GroupBuilder::new("group-1")
+//!     .with_asset(AssetBuilder::new("large").with_dimensions(4032, 3024))
+//!     .with_asset(AssetBuilder::new("small").with_dimensions(1024, 768).with_gps(51.5, -0.12))
+//!     .build();
+//!
+//! let analysis = DuplicateAnalysis::from_group(&group);
+//! assert_eq!(analysis.losers.len(), 1);
+//! ```
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{AssetResponse, AssetType, DuplicateGroup, ExifInfo};
+
+/// Timestamp given to assets that don't set a capture time.
+const DEFAULT_TIMESTAMP: &str = "2024-06-15T10:00:00.000Z";
+
+/// Builder for a synthetic [`AssetResponse`].
+///
+/// A new asset is an image with no EXIF data; each `with_*` EXIF setter
+/// creates the EXIF block on first use and fills in one field.
+#[derive(Debug, Clone)]
+pub struct AssetBuilder {
+    asset: AssetResponse,
+}
+
+impl AssetBuilder {
+    /// Start an image asset named `{id}.jpg`, without EXIF data.
+    pub fn new(id: impl Into<String>) -> Self {
+        let id = id.into();
+        Self {
+            asset: AssetResponse {
+                original_file_name: format!("{}.jpg", id),
+                file_created_at: DEFAULT_TIMESTAMP.to_string(),
+                local_date_time: DEFAULT_TIMESTAMP.to_string(),
+                updated_at: None,
+                asset_type: AssetType::Image,
+                exif_info: None,
+                checksum: format!("checksum-{}", id),
+                is_trashed: false,
+                is_favorite: false,
+                is_archived: false,
+                has_metadata: true,
+                duration: "0:00:00.000000".to_string(),
+                owner_id: "test-owner".to_string(),
+                original_mime_type: None,
+                duplicate_id: None,
+                thumbhash: None,
+                is_external: false,
+                library_id: None,
+                tags: Vec::new(),
+                people: Vec::new(),
+                stack: None,
+                visibility: None,
+                extra: Default::default(),
+                id,
+            },
+        }
+    }
+
+    /// Set the original filename.
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.asset.original_file_name = filename.into();
+        self
+    }
+
+    /// Set the checksum (assets with equal checksums are byte-identical).
+    pub fn with_checksum(mut self, checksum: impl Into<String>) -> Self {
+        self.asset.checksum = checksum.into();
+        self
+    }
+
+    /// Set the owner user ID.
+    pub fn with_owner(mut self, owner_id: impl Into<String>) -> Self {
+        self.asset.owner_id = owner_id.into();
+        self
+    }
+
+    /// Make the asset a video of `seconds` length.
+    pub fn video(mut self, seconds: f64) -> Self {
+        let seconds = seconds.max(0.0);
+        let hours = (seconds / 3600.0).floor();
+        let minutes = ((seconds - hours * 3600.0) / 60.0).floor();
+        let rest = seconds - hours * 3600.0 - minutes * 60.0;
+        self.asset.asset_type = AssetType::Video;
+        self.asset.duration = format!("{}:{:02}:{:09.6}", hours, minutes, rest);
+        if self.asset.original_file_name.ends_with(".jpg") {
+            self.asset.original_file_name = self.asset.original_file_name.replace(".jpg", ".mp4");
+        }
+        self
+    }
+
+    /// Mark the asset as trashed.
+    pub fn trashed(mut self) -> Self {
+        self.asset.is_trashed = true;
+        self
+    }
+
+    /// Mark the asset as archived.
+    pub fn archived(mut self) -> Self {
+        self.asset.is_archived = true;
+        self
+    }
+
+    /// Mark the asset as a favorite.
+    pub fn favorite(mut self) -> Self {
+        self.asset.is_favorite = true;
+        self
+    }
+
+    /// Place the asset in the external library `library_id`.
+    pub fn external(mut self, library_id: impl Into<String>) -> Self {
+        self.asset.is_external = true;
+        self.asset.library_id = Some(library_id.into());
+        self
+    }
+
+    /// Set the EXIF image dimensions.
+    pub fn with_dimensions(mut self, width: u32, height: u32) -> Self {
+        let exif = self.exif();
+        exif.exif_image_width = Some(width);
+        exif.exif_image_height = Some(height);
+        self
+    }
+
+    /// Set the file size in bytes.
+    pub fn with_file_size(mut self, bytes: u64) -> Self {
+        self.exif().file_size_in_byte = Some(bytes);
+        self
+    }
+
+    /// Set the GPS coordinates.
+    pub fn with_gps(mut self, latitude: f64, longitude: f64) -> Self {
+        let exif = self.exif();
+        exif.latitude = Some(latitude);
+        exif.longitude = Some(longitude);
+        self
+    }
+
+    /// Set the reverse-geocoded city and country.
+    pub fn with_location(mut self, city: impl Into<String>, country: impl Into<String>) -> Self {
+        let exif = self.exif();
+        exif.city = Some(city.into());
+        exif.country = Some(country.into());
+        self
+    }
+
+    /// Set the camera make and model.
+    pub fn with_camera(mut self, make: impl Into<String>, model: impl Into<String>) -> Self {
+        let exif = self.exif();
+        exif.make = Some(make.into());
+        exif.model = Some(model.into());
+        self
+    }
+
+    /// Set the lens model.
+    pub fn with_lens(mut self, lens_model: impl Into<String>) -> Self {
+        self.exif().lens_model = Some(lens_model.into());
+        self
+    }
+
+    /// Set the capture time; the asset's file and local timestamps follow it.
+    pub fn with_capture_time(mut self, taken_at: DateTime<Utc>) -> Self {
+        let timestamp = taken_at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        self.asset.file_created_at = timestamp.clone();
+        self.asset.local_date_time = timestamp.clone();
+        self.exif().date_time_original = Some(timestamp);
+        self
+    }
+
+    /// Set the timezone (e.g. `"Europe/London"` or `"UTC+5"`).
+    pub fn with_timezone(mut self, time_zone: impl Into<String>) -> Self {
+        self.exif().time_zone = Some(time_zone.into());
+        self
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.exif().description = Some(description.into());
+        self
+    }
+
+    /// Set the EXIF orientation (e.g. `"6"` for a quarter turn).
+    pub fn with_orientation(mut self, orientation: impl Into<String>) -> Self {
+        self.exif().orientation = Some(orientation.into());
+        self
+    }
+
+    /// Edit the EXIF block directly, for fields without a setter.
+    pub fn with_exif(mut self, edit: impl FnOnce(&mut ExifInfo)) -> Self {
+        edit(self.exif());
+        self
+    }
+
+    /// The finished asset.
+    pub fn build(self) -> AssetResponse {
+        self.asset
+    }
+
+    fn exif(&mut self) -> &mut ExifInfo {
+        self.asset.exif_info.get_or_insert_with(empty_exif)
+    }
+}
+
+/// Continue building from an existing asset, e.g. to add metadata to it.
+impl From<AssetResponse> for AssetBuilder {
+    fn from(asset: AssetResponse) -> Self {
+        Self { asset }
+    }
+}
+
+impl From<AssetBuilder> for AssetResponse {
+    fn from(builder: AssetBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builder for a synthetic [`DuplicateGroup`].
+#[derive(Debug, Clone)]
+pub struct GroupBuilder {
+    duplicate_id: String,
+    assets: Vec<AssetResponse>,
+}
+
+impl GroupBuilder {
+    /// Start an empty group.
+    pub fn new(duplicate_id: impl Into<String>) -> Self {
+        Self {
+            duplicate_id: duplicate_id.into(),
+            assets: Vec::new(),
+        }
+    }
+
+    /// Add an asset.
+    pub fn with_asset(mut self, asset: impl Into<AssetResponse>) -> Self {
+        self.assets.push(asset.into());
+        self
+    }
+
+    /// Add several assets.
+    pub fn with_assets<A: Into<AssetResponse>>(mut self, assets: impl IntoIterator<Item = A>) -> Self {
+        self.assets.extend(assets.into_iter().map(Into::into));
+        self
+    }
+
+    /// The finished group; every asset's `duplicate_id` is set to the group's.
+    pub fn build(self) -> DuplicateGroup {
+        let duplicate_id = self.duplicate_id;
+        let assets = self
+            .assets
+            .into_iter()
+            .map(|mut asset| {
+                asset.duplicate_id = Some(duplicate_id.clone());
+                asset
+            })
+            .collect();
+        DuplicateGroup { duplicate_id, assets, extra: Default::default() }
+    }
+}
+
+fn empty_exif() -> ExifInfo {
+    ExifInfo {
+        latitude: None,
+        longitude: None,
+        city: None,
+        state: None,
+        country: None,
+        time_zone: None,
+        date_time_original: None,
+        make: None,
+        model: None,
+        lens_model: None,
+        exposure_time: None,
+        f_number: None,
+        focal_length: None,
+        iso: None,
+        exif_image_width: None,
+        exif_image_height: None,
+        file_size_in_byte: None,
+        description: None,
+        rating: None,
+        orientation: None,
+        modify_date: None,
+        projection_type: None,
+        fps: None,
+        extra: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::{DuplicateAnalysis, MetadataConflict};
+
+    #[test]
+    fn test_built_group_scores_like_a_fixture() {
+        let group = GroupBuilder::new("group-1")
+            .with_asset(
+                AssetBuilder::new("tagged")
+                    .with_dimensions(4032, 3024)
+                    .with_gps(51.5, -0.12)
+                    .with_camera("Apple", "iPhone 15 Pro")
+                    .with_timezone("Europe/London"),
+            )
+            .with_asset(AssetBuilder::new("elsewhere").with_dimensions(1024, 768).with_gps(48.85, 2.35))
+            .with_asset(AssetBuilder::new("bare").video(75.5).trashed())
+            .build();
+
+        assert!(group.assets.iter().all(|a| a.duplicate_id.as_deref() == Some("group-1")));
+        let bare = &group.assets[2];
+        assert!(!bare.has_exif() && bare.is_trashed);
+        assert_eq!(bare.original_file_name, "bare.mp4");
+        assert_eq!(bare.duration_seconds(), Some(75.5));
+
+        let analysis = DuplicateAnalysis::from_group(&group);
+        assert_eq!(&*analysis.winner.asset_id, "tagged");
+        assert!(analysis.conflicts.iter().any(|c| matches!(c, MetadataConflict::Gps { .. })));
+    }
+}
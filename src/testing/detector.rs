@@ -1,570 +1,497 @@
 //! Scenario detection logic for duplicate groups.
+//!
+//! Each built-in scenario has a matcher here, named after its fixture, that
+//! inspects a [`ScenarioContext`] and returns one detail string per match.
 
-use chrono::{Datelike, Utc};
-
-use crate::models::{AssetType, DuplicateGroup};
-use crate::scoring::{detect_conflicts, MetadataConflict};
+use std::collections::HashSet;
 
-use super::scenarios::{ScenarioMatch, TestScenario};
+use chrono::{Datelike, Utc};
 
-/// GPS coordinate threshold for conflict detection (~11 meters).
-const GPS_THRESHOLD: f64 = 0.0001;
+use crate::models::{AssetResponse, AssetType, DuplicateGroup};
+use crate::scoring::{self, detect_conflicts_with_options, rank_assets, MetadataConflict, Thresholds, WinnerStrategy};
 
-/// Large file threshold in bytes (50MB).
-const LARGE_FILE_THRESHOLD: u64 = 50 * 1024 * 1024;
+use super::scenarios::{ScenarioMatch, ScenarioRegistry};
 
-/// Detect all matching test scenarios for a duplicate group.
+/// Detect all matching built-in test scenarios for a duplicate group.
 ///
 /// Analyzes the group and returns matches for all applicable scenarios.
 pub fn detect_scenarios(group: &DuplicateGroup) -> Vec<ScenarioMatch> {
-    let mut matches = Vec::new();
-    let dup_id = &group.duplicate_id;
+    detect_scenarios_with(group, &Thresholds::default())
+}
 
-    // Group size checks
-    detect_group_size_scenarios(group, &mut matches, dup_id);
+/// Like [`detect_scenarios`], with custom detection thresholds.
+pub fn detect_scenarios_with(group: &DuplicateGroup, thresholds: &Thresholds) -> Vec<ScenarioMatch> {
+    ScenarioRegistry::builtin().detect_with(group, thresholds)
+}
 
-    // Dimension-based winner selection
-    detect_dimension_scenarios(group, &mut matches, dup_id);
+/// A duplicate group with the facts most matchers need, computed once.
+#[derive(Debug)]
+pub struct ScenarioContext<'a> {
+    /// The group being matched
+    pub group: &'a DuplicateGroup,
 
-    // Consolidation scenarios (winner vs loser metadata)
-    detect_consolidation_scenarios(group, &mut matches, dup_id);
+    /// Assets from winner to last loser, ranked by [`rank_assets`] (with
+    /// the default strategy, the same ranking as `DuplicateAnalysis`)
+    pub ranked: Vec<&'a AssetResponse>,
 
-    // Conflict scenarios
-    detect_conflict_scenarios(group, &mut matches, dup_id);
+    /// Display dimensions of each asset, in group order
+    pub dimensions: Vec<Option<(u32, u32)>>,
 
-    // Edge cases
-    detect_edge_case_scenarios(group, &mut matches, dup_id);
+    /// Conflicts detected with `thresholds`
+    pub conflicts: Vec<MetadataConflict>,
 
-    matches
+    /// Thresholds the matchers use, the same ones scoring uses
+    pub thresholds: Thresholds,
 }
 
-/// Detect group size scenarios (W7, X1, X2).
-fn detect_group_size_scenarios(
-    group: &DuplicateGroup,
-    matches: &mut Vec<ScenarioMatch>,
-    dup_id: &str,
-) {
-    let count = group.assets.len();
-
-    if count == 1 {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::X1SingleAssetGroup,
-            duplicate_id: dup_id.to_string(),
-            details: "Only 1 asset in group".to_string(),
-        });
+impl<'a> ScenarioContext<'a> {
+    /// Compute the context for a group with the default thresholds.
+    pub fn new(group: &'a DuplicateGroup) -> Self {
+        Self::with_thresholds(group, &Thresholds::default())
     }
 
-    if count >= 3 {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::W7ThreePlusDuplicates,
-            duplicate_id: dup_id.to_string(),
-            details: format!("{} assets in group", count),
-        });
+    /// Compute the context for a group.
+    pub fn with_thresholds(group: &'a DuplicateGroup, thresholds: &Thresholds) -> Self {
+        Self::with_strategy(group, thresholds, WinnerStrategy::default())
     }
 
-    if count >= 10 {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::X2LargeGroup,
-            duplicate_id: dup_id.to_string(),
-            details: format!("{} assets in group", count),
-        });
+    /// Compute the context for a group, ranking its assets with `strategy`.
+    pub fn with_strategy(group: &'a DuplicateGroup, thresholds: &Thresholds, strategy: WinnerStrategy) -> Self {
+        Self {
+            group,
+            ranked: rank_assets(&group.assets, strategy)
+                .into_iter()
+                .map(|i| &group.assets[i])
+                .collect(),
+            dimensions: group
+                .assets
+                .iter()
+                .map(|a| a.exif_info.as_ref().and_then(|e| e.display_dimensions()))
+                .collect(),
+            conflicts: detect_conflicts_with_options(&group.assets, thresholds),
+            thresholds: thresholds.clone(),
+        }
     }
-}
 
-/// Detect dimension-based winner selection scenarios (W1-W6, W8).
-fn detect_dimension_scenarios(
-    group: &DuplicateGroup,
-    matches: &mut Vec<ScenarioMatch>,
-    dup_id: &str,
-) {
-    // Collect dimensions for each asset
-    let dims: Vec<Option<(u32, u32)>> = group
-        .assets
-        .iter()
-        .map(|a| {
-            a.exif_info.as_ref().and_then(|e| {
-                match (e.exif_image_width, e.exif_image_height) {
-                    (Some(w), Some(h)) => Some((w, h)),
-                    _ => None,
-                }
-            })
-        })
-        .collect();
+    /// The asset that would win, if the group has one.
+    pub fn winner(&self) -> Option<&'a AssetResponse> {
+        self.ranked.first().copied()
+    }
 
-    let has_dims: Vec<(u32, u32)> = dims.iter().filter_map(|d| *d).collect();
-    let with_dims_count = has_dims.len();
-    let without_dims_count = dims.len() - with_dims_count;
-
-    // W6: All missing dimensions
-    if with_dims_count == 0 && dims.len() > 1 {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::W6AllMissingDimensions,
-            duplicate_id: dup_id.to_string(),
-            details: format!("None of {} assets have dimensions", dims.len()),
-        });
-        return; // Can't check other dimension scenarios
+    /// Every asset but the winner.
+    pub fn losers(&self) -> &[&'a AssetResponse] {
+        self.ranked.get(1..).unwrap_or_default()
     }
 
-    // W5: Only one has dimensions
-    if with_dims_count == 1 && without_dims_count > 0 {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::W5OnlyOneHasDimensions,
-            duplicate_id: dup_id.to_string(),
-            details: format!(
-                "1 asset has dimensions, {} missing",
-                without_dims_count
-            ),
-        });
+    /// Dimensions of the assets that have them.
+    fn known_dimensions(&self) -> Vec<(u32, u32)> {
+        self.dimensions.iter().filter_map(|d| *d).collect()
     }
 
-    // W4: Some missing dimensions (but not all, not just one)
-    if with_dims_count > 1 && without_dims_count > 0 {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::W4SomeMissingDimensions,
-            duplicate_id: dup_id.to_string(),
-            details: format!(
-                "{} have dimensions, {} missing",
-                with_dims_count, without_dims_count
-            ),
-        });
+    /// Winner and losers, for groups of at least two assets.
+    fn pair(&self) -> Option<(&'a AssetResponse, &[&'a AssetResponse])> {
+        if self.ranked.len() < 2 {
+            return None;
+        }
+        Some((self.ranked[0], &self.ranked[1..]))
     }
+}
 
-    // Now analyze dimension differences (only if we have multiple with dims)
-    if has_dims.len() >= 2 {
-        let pixels: Vec<u64> = has_dims.iter().map(|(w, h)| u64::from(*w) * u64::from(*h)).collect();
-        let all_same_pixels = pixels.iter().all(|&p| p == pixels[0]);
+fn has_gps(asset: &AssetResponse) -> bool {
+    asset.exif_info.as_ref().is_some_and(|e| e.has_gps())
+}
 
-        // Check if all dimensions are exactly the same
-        let all_same_dims = has_dims.iter().all(|d| *d == has_dims[0]);
+fn has_datetime(asset: &AssetResponse) -> bool {
+    asset
+        .exif_info
+        .as_ref()
+        .is_some_and(|e| e.date_time_original.is_some())
+}
 
-        if all_same_dims {
-            // W2 or W3: Same dimensions, check file sizes
-            let sizes: Vec<Option<u64>> = group
-                .assets
-                .iter()
-                .filter_map(|a| a.exif_info.as_ref())
-                .map(|e| e.file_size_in_byte)
-                .collect();
+fn has_description(asset: &AssetResponse) -> bool {
+    asset
+        .exif_info
+        .as_ref()
+        .is_some_and(|e| e.description.as_ref().is_some_and(|d| !d.is_empty()))
+}
 
-            let valid_sizes: Vec<u64> = sizes.iter().filter_map(|s| *s).collect();
-            if valid_sizes.len() >= 2 {
-                let all_same_size = valid_sizes.iter().all(|&s| s == valid_sizes[0]);
-                if all_same_size {
-                    matches.push(ScenarioMatch {
-                        scenario: TestScenario::W3SameDimensionsSameSize,
-                        duplicate_id: dup_id.to_string(),
-                        details: format!(
-                            "{}x{}, all {} bytes",
-                            has_dims[0].0, has_dims[0].1, valid_sizes[0]
-                        ),
-                    });
-                } else {
-                    matches.push(ScenarioMatch {
-                        scenario: TestScenario::W2SameDimensionsDifferentSize,
-                        duplicate_id: dup_id.to_string(),
-                        details: format!(
-                            "{}x{}, sizes: {:?}",
-                            has_dims[0].0, has_dims[0].1, valid_sizes
-                        ),
-                    });
-                }
-            }
-        } else if all_same_pixels {
-            // W8: Same pixel count, different aspect ratio
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::W8SamePixelsDifferentAspect,
-                duplicate_id: dup_id.to_string(),
-                details: format!(
-                    "Same {} pixels, dims: {:?}",
-                    pixels[0], has_dims
-                ),
-            });
-        } else {
-            // W1: Clear dimension winner
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::W1ClearDimensionWinner,
-                duplicate_id: dup_id.to_string(),
-                details: format!("Dimensions: {:?}", has_dims),
-            });
-        }
-    }
+/// One match with `details` if `matched`, otherwise none.
+fn matched_if(matched: bool, details: impl FnOnce() -> String) -> Vec<String> {
+    if matched { vec![details()] } else { Vec::new() }
+}
+
+// ===== Group size =====
+
+pub(super) fn w7_three_plus_duplicates(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let count = ctx.group.assets.len();
+    matched_if(count >= 3, || format!("{} assets in group", count))
+}
+
+pub(super) fn x1_single_asset_group(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    matched_if(ctx.group.assets.len() == 1, || "Only 1 asset in group".to_string())
+}
+
+pub(super) fn x2_large_group(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let count = ctx.group.assets.len();
+    matched_if(count >= 10, || format!("{} assets in group", count))
+}
+
+// ===== Dimension-based winner selection =====
+
+pub(super) fn w6_all_missing_dimensions(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let total = ctx.dimensions.len();
+    matched_if(ctx.known_dimensions().is_empty() && total > 1, || {
+        format!("None of {} assets have dimensions", total)
+    })
+}
+
+pub(super) fn w5_only_one_has_dimensions(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let with_dims = ctx.known_dimensions().len();
+    let without_dims = ctx.dimensions.len() - with_dims;
+    matched_if(with_dims == 1 && without_dims > 0, || {
+        format!("1 asset has dimensions, {} missing", without_dims)
+    })
+}
+
+pub(super) fn w4_some_missing_dimensions(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let with_dims = ctx.known_dimensions().len();
+    let without_dims = ctx.dimensions.len() - with_dims;
+    matched_if(with_dims > 1 && without_dims > 0, || {
+        format!("{} have dimensions, {} missing", with_dims, without_dims)
+    })
 }
 
-/// Detect consolidation scenarios (C1-C8).
-fn detect_consolidation_scenarios(
-    group: &DuplicateGroup,
-    matches: &mut Vec<ScenarioMatch>,
-    dup_id: &str,
-) {
-    if group.assets.len() < 2 {
-        return;
+/// Which of W1/W2/W3/W8 a group with at least two known dimensions falls into.
+enum DimensionOutcome {
+    ClearWinner,
+    SameDimensionsDifferentSize,
+    SameDimensionsSameSize,
+    SamePixelsDifferentAspect,
+}
+
+fn dimension_outcome(ctx: &ScenarioContext<'_>) -> Option<(DimensionOutcome, String)> {
+    let dims = ctx.known_dimensions();
+    if dims.len() < 2 {
+        return None;
     }
 
-    // Determine winner based on dimensions (same logic as DuplicateAnalysis)
-    let mut sorted = group.assets.clone();
-    sorted.sort_by(|a, b| {
-        let pixels_a = a
-            .exif_info
-            .as_ref()
-            .and_then(|e| match (e.exif_image_width, e.exif_image_height) {
-                (Some(w), Some(h)) => Some(u64::from(w) * u64::from(h)),
-                _ => None,
-            })
-            .unwrap_or(0);
-        let pixels_b = b
-            .exif_info
-            .as_ref()
-            .and_then(|e| match (e.exif_image_width, e.exif_image_height) {
-                (Some(w), Some(h)) => Some(u64::from(w) * u64::from(h)),
-                _ => None,
-            })
-            .unwrap_or(0);
-
-        match pixels_b.cmp(&pixels_a) {
-            std::cmp::Ordering::Equal => {
-                let size_a = a
-                    .exif_info
-                    .as_ref()
-                    .and_then(|e| e.file_size_in_byte)
-                    .unwrap_or(0);
-                let size_b = b
-                    .exif_info
-                    .as_ref()
-                    .and_then(|e| e.file_size_in_byte)
-                    .unwrap_or(0);
-                size_b.cmp(&size_a)
-            }
-            other => other,
+    let pixels: Vec<u64> = dims.iter().map(|(w, h)| u64::from(*w) * u64::from(*h)).collect();
+    let all_same_pixels = pixels.iter().all(|&p| p == pixels[0]);
+    let all_same_dims = dims.iter().all(|d| *d == dims[0]);
+
+    if all_same_dims {
+        // Same dimensions: file size decides
+        let sizes: Vec<u64> = ctx
+            .group
+            .assets
+            .iter()
+            .filter_map(|a| a.exif_info.as_ref())
+            .filter_map(|e| e.file_size_in_byte)
+            .collect();
+        if sizes.len() < 2 {
+            return None;
+        }
+        if sizes.iter().all(|&s| s == sizes[0]) {
+            Some((
+                DimensionOutcome::SameDimensionsSameSize,
+                format!("{}x{}, all {} bytes", dims[0].0, dims[0].1, sizes[0]),
+            ))
+        } else {
+            Some((
+                DimensionOutcome::SameDimensionsDifferentSize,
+                format!("{}x{}, sizes: {:?}", dims[0].0, dims[0].1, sizes),
+            ))
         }
-    });
-
-    let winner = &sorted[0];
-    let losers = &sorted[1..];
-
-    // Check winner metadata
-    let winner_exif = winner.exif_info.as_ref();
-    let winner_has_gps = winner_exif.is_some_and(|e| e.has_gps());
-    let winner_has_datetime = winner_exif.is_some_and(|e| e.date_time_original.is_some());
-    let winner_has_description = winner_exif
-        .is_some_and(|e| e.description.as_ref().is_some_and(|d| !d.is_empty()));
-
-    // Check loser metadata
-    let any_loser_has_gps = losers.iter().any(|l| {
-        l.exif_info.as_ref().is_some_and(|e| e.has_gps())
-    });
-    let any_loser_has_datetime = losers.iter().any(|l| {
-        l.exif_info.as_ref().is_some_and(|e| e.date_time_original.is_some())
-    });
-    let any_loser_has_description = losers.iter().any(|l| {
-        l.exif_info.as_ref()
-            .is_some_and(|e| e.description.as_ref().is_some_and(|d| !d.is_empty()))
-    });
-
-    // C8: Winner has everything
-    if winner_has_gps && winner_has_datetime && winner_has_description {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::C8WinnerHasEverything,
-            duplicate_id: dup_id.to_string(),
-            details: "Winner has GPS, datetime, description".to_string(),
-        });
+    } else if all_same_pixels {
+        Some((
+            DimensionOutcome::SamePixelsDifferentAspect,
+            format!("Same {} pixels, dims: {:?}", pixels[0], dims),
+        ))
+    } else {
+        Some((DimensionOutcome::ClearWinner, format!("Dimensions: {:?}", dims)))
     }
+}
 
-    // C5: Both have GPS
-    if winner_has_gps && any_loser_has_gps {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::C5BothHaveGps,
-            duplicate_id: dup_id.to_string(),
-            details: "Winner and loser(s) have GPS".to_string(),
-        });
+pub(super) fn w1_clear_dimension_winner(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    match dimension_outcome(ctx) {
+        Some((DimensionOutcome::ClearWinner, details)) => vec![details],
+        _ => Vec::new(),
     }
+}
 
-    // C1: Winner lacks GPS, loser has GPS
-    if !winner_has_gps && any_loser_has_gps {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::C1WinnerLacksGpsLoserHas,
-            duplicate_id: dup_id.to_string(),
-            details: "Winner missing GPS, loser has it".to_string(),
-        });
+pub(super) fn w2_same_dimensions_different_size(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    match dimension_outcome(ctx) {
+        Some((DimensionOutcome::SameDimensionsDifferentSize, details)) => vec![details],
+        _ => Vec::new(),
     }
+}
 
-    // C2: Winner lacks datetime, loser has datetime
-    if !winner_has_datetime && any_loser_has_datetime {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::C2WinnerLacksDatetimeLoserHas,
-            duplicate_id: dup_id.to_string(),
-            details: "Winner missing datetime, loser has it".to_string(),
-        });
+pub(super) fn w3_same_dimensions_same_size(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    match dimension_outcome(ctx) {
+        Some((DimensionOutcome::SameDimensionsSameSize, details)) => vec![details],
+        _ => Vec::new(),
     }
+}
 
-    // C3: Winner lacks description, loser has description
-    if !winner_has_description && any_loser_has_description {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::C3WinnerLacksDescriptionLoserHas,
-            duplicate_id: dup_id.to_string(),
-            details: "Winner missing description, loser has it".to_string(),
-        });
+pub(super) fn w8_same_pixels_different_aspect(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    match dimension_outcome(ctx) {
+        Some((DimensionOutcome::SamePixelsDifferentAspect, details)) => vec![details],
+        _ => Vec::new(),
     }
+}
 
-    // C4: Winner lacks all, loser has all
-    if !winner_has_gps && !winner_has_datetime && !winner_has_description {
-        let loser_has_all = losers.iter().any(|l| {
-            let e = l.exif_info.as_ref();
-            let has_gps = e.is_some_and(|e| e.has_gps());
-            let has_dt = e.is_some_and(|e| e.date_time_original.is_some());
-            let has_desc = e.is_some_and(|e| e.description.as_ref().is_some_and(|d| !d.is_empty()));
-            has_gps && has_dt && has_desc
-        });
-        if loser_has_all {
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::C4WinnerLacksAllLoserHasAll,
-                duplicate_id: dup_id.to_string(),
-                details: "Winner lacks GPS/datetime/description, loser has all".to_string(),
-            });
-        }
-    }
+// ===== Consolidation (winner vs loser metadata) =====
 
-    // C6: Multiple losers contribute different fields
-    if losers.len() >= 2 {
-        let loser_gps: Vec<bool> = losers
-            .iter()
-            .map(|l| l.exif_info.as_ref().is_some_and(|e| e.has_gps()))
-            .collect();
-        let loser_dt: Vec<bool> = losers
-            .iter()
-            .map(|l| l.exif_info.as_ref().is_some_and(|e| e.date_time_original.is_some()))
-            .collect();
-        let loser_desc: Vec<bool> = losers
-            .iter()
-            .map(|l| {
-                l.exif_info.as_ref()
-                    .is_some_and(|e| e.description.as_ref().is_some_and(|d| !d.is_empty()))
-            })
-            .collect();
+pub(super) fn c1_winner_lacks_gps_loser_has(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((winner, losers)) = ctx.pair() else { return Vec::new() };
+    matched_if(!has_gps(winner) && losers.iter().any(|l| has_gps(l)), || {
+        "Winner missing GPS, loser has it".to_string()
+    })
+}
 
-        // Check if different losers contribute different things
-        let gps_sources: Vec<usize> = loser_gps.iter().enumerate().filter_map(|(i, &v)| if v { Some(i) } else { None }).collect();
-        let dt_sources: Vec<usize> = loser_dt.iter().enumerate().filter_map(|(i, &v)| if v { Some(i) } else { None }).collect();
-        let desc_sources: Vec<usize> = loser_desc.iter().enumerate().filter_map(|(i, &v)| if v { Some(i) } else { None }).collect();
-
-        // If different losers are the source for different fields
-        let contributions = [gps_sources.first(), dt_sources.first(), desc_sources.first()];
-        let unique_contributors: std::collections::HashSet<_> = contributions.iter().filter_map(|&o| o).collect();
-        if unique_contributors.len() >= 2 {
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::C6MultipleLosersContribute,
-                duplicate_id: dup_id.to_string(),
-                details: "Different losers contribute different metadata".to_string(),
-            });
-        }
-    }
+pub(super) fn c2_winner_lacks_datetime_loser_has(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((winner, losers)) = ctx.pair() else { return Vec::new() };
+    matched_if(!has_datetime(winner) && losers.iter().any(|l| has_datetime(l)), || {
+        "Winner missing datetime, loser has it".to_string()
+    })
+}
 
-    // C7: No loser has what winner lacks
-    let winner_needs_gps = !winner_has_gps;
-    let winner_needs_datetime = !winner_has_datetime;
-    let winner_needs_description = !winner_has_description;
-
-    if (winner_needs_gps || winner_needs_datetime || winner_needs_description)
-        && !any_loser_has_gps
-        && !any_loser_has_datetime
-        && !any_loser_has_description
-    {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::C7NoLoserHasNeeded,
-            duplicate_id: dup_id.to_string(),
-            details: "Winner missing metadata, no loser has it".to_string(),
-        });
-    }
+pub(super) fn c3_winner_lacks_description_loser_has(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((winner, losers)) = ctx.pair() else { return Vec::new() };
+    matched_if(!has_description(winner) && losers.iter().any(|l| has_description(l)), || {
+        "Winner missing description, loser has it".to_string()
+    })
 }
 
-/// Detect conflict scenarios (F1-F7).
-fn detect_conflict_scenarios(
-    group: &DuplicateGroup,
-    matches: &mut Vec<ScenarioMatch>,
-    dup_id: &str,
-) {
-    let conflicts = detect_conflicts(&group.assets);
-
-    if conflicts.is_empty() {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::F7NoConflicts,
-            duplicate_id: dup_id.to_string(),
-            details: "No metadata conflicts".to_string(),
-        });
-        return;
-    }
+pub(super) fn c4_winner_lacks_all_loser_has_all(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((winner, losers)) = ctx.pair() else { return Vec::new() };
+    let winner_lacks_all = !has_gps(winner) && !has_datetime(winner) && !has_description(winner);
+    let loser_has_all = losers
+        .iter()
+        .any(|l| has_gps(l) && has_datetime(l) && has_description(l));
+    matched_if(winner_lacks_all && loser_has_all, || {
+        "Winner lacks GPS/datetime/description, loser has all".to_string()
+    })
+}
 
-    let mut has_gps_conflict = false;
-    let mut has_timezone_conflict = false;
-    let mut has_camera_conflict = false;
-    let mut has_capture_time_conflict = false;
-
-    for conflict in &conflicts {
-        match conflict {
-            MetadataConflict::Gps { values } => {
-                has_gps_conflict = true;
-                matches.push(ScenarioMatch {
-                    scenario: TestScenario::F1GpsConflict,
-                    duplicate_id: dup_id.to_string(),
-                    details: format!("{} different locations", values.len()),
-                });
-            }
-            MetadataConflict::Timezone { values } => {
-                has_timezone_conflict = true;
-                matches.push(ScenarioMatch {
-                    scenario: TestScenario::F3TimezoneConflict,
-                    duplicate_id: dup_id.to_string(),
-                    details: format!("Timezones: {:?}", values),
-                });
-            }
-            MetadataConflict::CameraInfo { values } => {
-                has_camera_conflict = true;
-                matches.push(ScenarioMatch {
-                    scenario: TestScenario::F4CameraConflict,
-                    duplicate_id: dup_id.to_string(),
-                    details: format!("Cameras: {:?}", values),
-                });
-            }
-            MetadataConflict::CaptureTime { values } => {
-                has_capture_time_conflict = true;
-                matches.push(ScenarioMatch {
-                    scenario: TestScenario::F5CaptureTimeConflict,
-                    duplicate_id: dup_id.to_string(),
-                    details: format!("Times: {:?}", values),
-                });
-            }
-        }
+pub(super) fn c5_both_have_gps(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((winner, losers)) = ctx.pair() else { return Vec::new() };
+    matched_if(has_gps(winner) && losers.iter().any(|l| has_gps(l)), || {
+        "Winner and loser(s) have GPS".to_string()
+    })
+}
+
+pub(super) fn c6_multiple_losers_contribute(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((_, losers)) = ctx.pair() else { return Vec::new() };
+    if losers.len() < 2 {
+        return Vec::new();
     }
 
-    // F6: Multiple conflicts
-    let conflict_count = [has_gps_conflict, has_timezone_conflict, has_camera_conflict, has_capture_time_conflict]
+    // First loser that could supply each field; different losers for different fields?
+    let first_source = |has: fn(&AssetResponse) -> bool| losers.iter().position(|l| has(l));
+    let contributors: HashSet<usize> = [
+        first_source(has_gps),
+        first_source(has_datetime),
+        first_source(has_description),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    matched_if(contributors.len() >= 2, || {
+        "Different losers contribute different metadata".to_string()
+    })
+}
+
+pub(super) fn c7_no_loser_has_needed(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((winner, losers)) = ctx.pair() else { return Vec::new() };
+    let winner_needs_something = !has_gps(winner) || !has_datetime(winner) || !has_description(winner);
+    let loser_has_anything = losers
         .iter()
-        .filter(|&&v| v)
-        .count();
-    if conflict_count >= 2 {
-        matches.push(ScenarioMatch {
-            scenario: TestScenario::F6MultipleConflicts,
-            duplicate_id: dup_id.to_string(),
-            details: format!("{} different conflict types", conflict_count),
-        });
+        .any(|l| has_gps(l) || has_datetime(l) || has_description(l));
+    matched_if(winner_needs_something && !loser_has_anything, || {
+        "Winner missing metadata, no loser has it".to_string()
+    })
+}
+
+pub(super) fn c8_winner_has_everything(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let Some((winner, _)) = ctx.pair() else { return Vec::new() };
+    matched_if(has_gps(winner) && has_datetime(winner) && has_description(winner), || {
+        "Winner has GPS, datetime, description".to_string()
+    })
+}
+
+// ===== Conflicts =====
+
+pub(super) fn f1_gps_conflict(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.conflicts
+        .iter()
+        .filter_map(|c| match c {
+            MetadataConflict::Gps { values } => Some(format!("{} different locations", values.len())),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(super) fn f2_gps_within_threshold(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    // Only checked for groups that have some other conflict
+    let has_gps_conflict = ctx.conflicts.iter().any(|c| matches!(c, MetadataConflict::Gps { .. }));
+    if ctx.conflicts.is_empty() || has_gps_conflict {
+        return Vec::new();
     }
 
-    // F2: GPS within threshold (no conflict detected but multiple GPS values exist)
-    if !has_gps_conflict {
-        let gps_values: Vec<(f64, f64)> = group
-            .assets
-            .iter()
-            .filter_map(|a| a.exif_info.as_ref())
-            .filter_map(|e| match (e.latitude, e.longitude) {
-                (Some(lat), Some(lon)) => Some((lat, lon)),
-                _ => None,
-            })
-            .collect();
+    let gps_values: Vec<(f64, f64)> = ctx
+        .group
+        .assets
+        .iter()
+        .filter_map(|a| a.exif_info.as_ref())
+        .filter_map(|e| match (e.latitude, e.longitude) {
+            (Some(lat), Some(lon)) => Some((lat, lon)),
+            _ => None,
+        })
+        .collect();
 
-        if gps_values.len() >= 2 {
-            // Check if they're all within threshold
-            let mut all_within = true;
-            for i in 0..gps_values.len() {
-                for j in (i + 1)..gps_values.len() {
-                    let (lat1, lon1) = gps_values[i];
-                    let (lat2, lon2) = gps_values[j];
-                    if (lat1 - lat2).abs() > GPS_THRESHOLD || (lon1 - lon2).abs() > GPS_THRESHOLD {
-                        all_within = false;
-                        break;
-                    }
-                }
-            }
-            if all_within {
-                matches.push(ScenarioMatch {
-                    scenario: TestScenario::F2GpsWithinThreshold,
-                    duplicate_id: dup_id.to_string(),
-                    details: format!("{} GPS values within threshold", gps_values.len()),
-                });
+    matched_if(
+        gps_values.len() >= 2 && !scoring::has_gps_conflict(&gps_values, ctx.thresholds.gps_threshold_m),
+        || format!("{} GPS values within threshold", gps_values.len()),
+    )
+}
+
+pub(super) fn f3_timezone_conflict(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.conflicts
+        .iter()
+        .filter_map(|c| match c {
+            MetadataConflict::Timezone { values } => Some(format!("Timezones: {:?}", values)),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(super) fn f4_camera_conflict(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.conflicts
+        .iter()
+        .filter_map(|c| match c {
+            MetadataConflict::CameraInfo { values } => Some(format!("Cameras: {:?}", values)),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(super) fn f5_capture_time_conflict(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.conflicts
+        .iter()
+        .filter_map(|c| match c {
+            MetadataConflict::TimezoneShift { values, hours } => {
+                Some(format!("Timezone shift of {}h: {:?}", hours, values))
             }
-        }
-    }
+            MetadataConflict::CaptureTime { values, .. } => Some(format!("Times: {:?}", values)),
+            _ => None,
+        })
+        .collect()
 }
 
-/// Detect edge case scenarios (X3-X11).
-fn detect_edge_case_scenarios(
-    group: &DuplicateGroup,
-    matches: &mut Vec<ScenarioMatch>,
-    dup_id: &str,
-) {
-    for asset in &group.assets {
-        let filename = &asset.original_file_name;
-        let lowercase = filename.to_lowercase();
-
-        // X3: Large file (>50MB)
-        if let Some(size) = asset.exif_info.as_ref().and_then(|e| e.file_size_in_byte)
-            && size > LARGE_FILE_THRESHOLD
-        {
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::X3LargeFile,
-                duplicate_id: dup_id.to_string(),
-                details: format!("{}: {} bytes", filename, size),
-            });
-        }
+pub(super) fn f6_multiple_conflicts(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    // Timezone shifts count as capture time conflicts
+    let kinds: HashSet<&str> = ctx
+        .conflicts
+        .iter()
+        .map(|c| match c {
+            MetadataConflict::Gps { .. } => "gps",
+            MetadataConflict::Timezone { .. } => "timezone",
+            MetadataConflict::CameraInfo { .. } => "camera",
+            MetadataConflict::CaptureTime { .. } | MetadataConflict::TimezoneShift { .. } => "capture_time",
+        })
+        .collect();
+    matched_if(kinds.len() >= 2, || format!("{} different conflict types", kinds.len()))
+}
 
-        // X4: Special characters in filename
-        if filename.chars().any(|c| "!@#$%^&*()[]{}|;'\"<>?".contains(c)) {
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::X4SpecialCharsFilename,
-                duplicate_id: dup_id.to_string(),
-                details: format!("Filename: {}", filename),
-            });
-        }
+pub(super) fn f7_no_conflicts(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    matched_if(ctx.conflicts.is_empty(), || "No metadata conflicts".to_string())
+}
 
-        // X5: Video
-        if asset.asset_type == AssetType::Video {
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::X5Video,
-                duplicate_id: dup_id.to_string(),
-                details: format!("Video: {}", filename),
-            });
-        }
+// ===== Edge cases (per asset) =====
 
-        // X7: PNG
-        if lowercase.ends_with(".png") {
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::X7Png,
-                duplicate_id: dup_id.to_string(),
-                details: format!("PNG: {}", filename),
-            });
-        }
+pub(super) fn x3_large_file(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.group
+        .assets
+        .iter()
+        .filter_map(|a| {
+            let size = a.exif_info.as_ref()?.file_size_in_byte?;
+            (size > ctx.thresholds.large_file_bytes).then(|| format!("{}: {} bytes", a.original_file_name, size))
+        })
+        .collect()
+}
 
-        // X9: Unicode in description
-        if let Some(desc) = asset.exif_info.as_ref().and_then(|e| e.description.as_ref())
-            && !desc.is_ascii()
-        {
-            matches.push(ScenarioMatch {
-                scenario: TestScenario::X9UnicodeDescription,
-                duplicate_id: dup_id.to_string(),
-                details: format!("Description: {}", desc),
-            });
-        }
+pub(super) fn x4_special_chars_filename(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.group
+        .assets
+        .iter()
+        .filter(|a| a.original_file_name.chars().any(|c| "!@#$%^&*()[]{}|;'\"<>?".contains(c)))
+        .map(|a| format!("Filename: {}", a.original_file_name))
+        .collect()
+}
 
-        // X10: Very old date (<1990) and X11: Future date
-        if let Some(dt) = asset.exif_info.as_ref().and_then(|e| e.date_time_original.as_ref())
-            && let Some(year) = extract_year(dt)
-        {
-            if year < 1990 {
-                matches.push(ScenarioMatch {
-                    scenario: TestScenario::X10VeryOldDate,
-                    duplicate_id: dup_id.to_string(),
-                    details: format!("Date: {}", dt),
-                });
-            }
+pub(super) fn x5_video(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.group
+        .assets
+        .iter()
+        .filter(|a| a.asset_type == AssetType::Video)
+        .map(|a| format!("Video: {}", a.original_file_name))
+        .collect()
+}
 
-            let current_year = Utc::now().year();
-            if year > current_year {
-                matches.push(ScenarioMatch {
-                    scenario: TestScenario::X11FutureDate,
-                    duplicate_id: dup_id.to_string(),
-                    details: format!("Date: {} (future)", dt),
-                });
-            }
-        }
-    }
+pub(super) fn x6_heic(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.group
+        .assets
+        .iter()
+        .filter(|a| {
+            let name = a.original_file_name.to_lowercase();
+            name.ends_with(".heic") || name.ends_with(".heif")
+        })
+        .map(|a| format!("HEIC: {}", a.original_file_name))
+        .collect()
+}
+
+pub(super) fn x7_png(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.group
+        .assets
+        .iter()
+        .filter(|a| a.original_file_name.to_lowercase().ends_with(".png"))
+        .map(|a| format!("PNG: {}", a.original_file_name))
+        .collect()
+}
+
+pub(super) fn x9_unicode_description(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    ctx.group
+        .assets
+        .iter()
+        .filter_map(|a| a.exif_info.as_ref()?.description.as_ref())
+        .filter(|desc| !desc.is_ascii())
+        .map(|desc| format!("Description: {}", desc))
+        .collect()
+}
+
+/// Capture dates of the group's assets with their parsed years.
+fn capture_years<'a>(ctx: &ScenarioContext<'a>) -> impl Iterator<Item = (&'a String, i32)> {
+    ctx.group.assets.iter().filter_map(|a| {
+        let dt = a.exif_info.as_ref()?.date_time_original.as_ref()?;
+        Some((dt, extract_year(dt)?))
+    })
+}
+
+pub(super) fn x10_very_old_date(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    capture_years(ctx)
+        .filter(|(_, year)| *year < 1990)
+        .map(|(dt, _)| format!("Date: {}", dt))
+        .collect()
+}
+
+pub(super) fn x11_future_date(ctx: &ScenarioContext<'_>) -> Vec<String> {
+    let current_year = Utc::now().year();
+    capture_years(ctx)
+        .filter(|(_, year)| *year > current_year)
+        .map(|(dt, _)| format!("Date: {} (future)", dt))
+        .collect()
 }
 
 /// Extract year from a date string (various formats).
@@ -579,3 +506,55 @@ fn extract_year(date_str: &str) -> Option<i32> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::DuplicateAnalysis;
+    use crate::testing::{synthetic_groups, AssetBuilder, GroupBuilder};
+
+    /// Scenario detection must judge the same winner execution will keep.
+    #[test]
+    fn test_context_ranking_matches_analysis() {
+        let mut groups = synthetic_groups(2, 3).unwrap();
+        groups.push(
+            GroupBuilder::new("videos")
+                .with_asset(AssetBuilder::new("truncated").video(30.0).with_dimensions(3840, 2160).with_file_size(90_000_000))
+                .with_asset(AssetBuilder::new("original").video(72.0).with_dimensions(1920, 1080).with_file_size(40_000_000))
+                .build(),
+        );
+        groups.push(
+            GroupBuilder::new("ties")
+                .with_asset(AssetBuilder::new("bare"))
+                .with_asset(AssetBuilder::new("sized").with_file_size(1024))
+                .with_asset(AssetBuilder::new("same").with_file_size(1024).with_gps(51.5, -0.12))
+                .build(),
+        );
+
+        for group in &groups {
+            let context = ScenarioContext::new(group);
+            let analysis = DuplicateAnalysis::from_group(group);
+            let expected: Vec<&str> = std::iter::once(&analysis.winner)
+                .chain(&analysis.losers)
+                .map(|a| &*a.asset_id)
+                .collect();
+            let ranked: Vec<&str> = context.ranked.iter().map(|a| a.id.as_str()).collect();
+            assert_eq!(ranked, expected, "detector and analysis disagree on {}", group.duplicate_id);
+        }
+        assert_eq!(ScenarioContext::new(&groups[2]).winner().unwrap().id, "original");
+    }
+
+    #[test]
+    fn test_metadata_strategy_prefers_richer_copy() {
+        let group = GroupBuilder::new("group")
+            .with_asset(AssetBuilder::new("large").with_dimensions(4032, 3024))
+            .with_asset(AssetBuilder::new("tagged").with_dimensions(1024, 768).with_gps(51.5, -0.12))
+            .build();
+        let thresholds = Thresholds::default();
+
+        let quality = ScenarioContext::with_strategy(&group, &thresholds, WinnerStrategy::Quality);
+        let metadata = ScenarioContext::with_strategy(&group, &thresholds, WinnerStrategy::Metadata);
+        assert_eq!(quality.winner().unwrap().id, "large");
+        assert_eq!(metadata.winner().unwrap().id, "tagged");
+    }
+}
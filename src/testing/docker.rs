@@ -0,0 +1,346 @@
+//! Disposable Immich servers for integration tests.
+//!
+//! [`TestHarness::start`] brings up an Immich stack (server, machine
+//! learning, Redis, Postgres) as its own Docker Compose project, creates an
+//! admin user and an API key, and tunes duplicate detection for synthetic
+//! fixtures. [`TestHarness::seed`] uploads generated fixtures and waits for
+//! their duplicate groups, and [`TestHarness::teardown`] removes the stack
+//! and its volumes, waiting until it is gone. A harness that is dropped
+//! without a teardown starts removing its stack in the background, so a
+//! panicking test doesn't leave containers behind.
+//!
+//! Requires the `docker` CLI with the Compose plugin.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::fixtures::FixtureManifest;
+use super::seed::{map_duplicate_groups, upload_scenario, wait_for_duplicates, SeededScenario};
+use crate::client::ImmichClient;
+use crate::error::{ImmichError, Result};
+
+/// Compose file for the stack.
+const COMPOSE_FILE: &str = include_str!("immich-compose.yml");
+
+/// Interval between readiness and job queue checks.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How to start a [`TestHarness`].
+#[derive(Debug, Clone)]
+pub struct HarnessOptions {
+    /// Compose project name; defaults to a unique `immich-test-*` name
+    pub project: String,
+
+    /// Host port the server listens on
+    pub port: u16,
+
+    /// Immich image tag (e.g. `release` or `v1.132.0`)
+    pub immich_version: String,
+
+    /// How long to wait for the server and machine learning to come up
+    pub ready_timeout: Duration,
+
+    /// Admin account created on the fresh server
+    pub admin_email: String,
+
+    /// Password of the admin account
+    pub admin_password: String,
+
+    /// Duplicate detection distance to configure; Immich's default of 0.01
+    /// is too strict for the scale/quality variants the fixtures use
+    pub duplicate_max_distance: Option<f64>,
+}
+
+impl Default for HarnessOptions {
+    fn default() -> Self {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            project: format!("immich-test-{}", &id[..8]),
+            port: 2283,
+            immich_version: "release".to_string(),
+            ready_timeout: Duration::from_secs(300),
+            admin_email: "admin@test.local".to_string(),
+            admin_password: "testpassword123".to_string(),
+            duplicate_max_distance: Some(0.06),
+        }
+    }
+}
+
+/// A running Immich stack with an admin API key.
+#[derive(Debug)]
+pub struct TestHarness {
+    /// Base URL of the server
+    pub base_url: String,
+
+    /// API key of the admin user
+    pub api_key: String,
+
+    /// Client authenticated with the API key
+    pub client: ImmichClient,
+
+    project: String,
+    dir: PathBuf,
+    running: bool,
+}
+
+impl TestHarness {
+    /// Start a fresh stack and set up an admin user and API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Docker can't start the stack, the server doesn't
+    /// become ready within the timeout, or the admin setup fails. The stack
+    /// is removed again on failure.
+    pub async fn start(options: HarnessOptions) -> Result<Self> {
+        let dir = std::env::temp_dir().join(&options.project);
+        tokio::fs::create_dir_all(&dir).await?;
+        tokio::fs::write(dir.join("docker-compose.yml"), COMPOSE_FILE).await?;
+
+        let base_url = format!("http://localhost:{}", options.port);
+        let mut harness = Self {
+            client: ImmichClient::new(&base_url, "pending")?,
+            base_url,
+            api_key: String::new(),
+            project: options.project.clone(),
+            dir,
+            running: true,
+        };
+
+        let command = compose(&harness.project, &harness.dir)
+            .args(["up", "-d"])
+            .env("IMMICH_PORT", options.port.to_string())
+            .env("IMMICH_VERSION", &options.immich_version)
+            .output()
+            .await;
+        if let Err(e) = check(command, "docker compose up") {
+            harness.teardown().await.ok();
+            return Err(e);
+        }
+
+        match harness.bootstrap(&options).await {
+            Ok(api_key) => {
+                harness.client = ImmichClient::new(&harness.base_url, &api_key)?;
+                harness.api_key = api_key;
+                Ok(harness)
+            }
+            Err(e) => {
+                harness.teardown().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    /// Upload every generated scenario under `fixtures_dir` (the scenario
+    /// directories with a manifest) and wait up to `timeout` for their
+    /// duplicate groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or a manifest can't be read, or the
+    /// server can't be queried.
+    pub async fn seed(&self, fixtures_dir: &Path, timeout: Duration) -> Result<Vec<SeededScenario>> {
+        let mut scenario_dirs: Vec<PathBuf> = std::fs::read_dir(fixtures_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.join(FixtureManifest::FILE_NAME).is_file())
+            .collect();
+        scenario_dirs.sort();
+
+        let mut seeded = Vec::new();
+        for dir in scenario_dirs {
+            seeded.push(upload_scenario(&self.client, &dir).await?);
+        }
+
+        let groups = wait_for_duplicates(&self.client, &seeded, POLL_INTERVAL, timeout).await?;
+        map_duplicate_groups(&mut seeded, &groups);
+        Ok(seeded)
+    }
+
+    /// Stop the stack and remove its containers and volumes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `docker compose down` fails.
+    pub async fn teardown(mut self) -> Result<()> {
+        self.running = false;
+        let output = compose(&self.project, &self.dir).args(["down", "-v"]).output().await;
+        tokio::fs::remove_dir_all(&self.dir).await.ok();
+        check(output, "docker compose down")
+    }
+
+    /// Wait for the server, create the admin user, and return a new API key.
+    async fn bootstrap(&self, options: &HarnessOptions) -> Result<String> {
+        let http = reqwest::Client::new();
+        self.wait_until_ready(&http, options.ready_timeout).await?;
+
+        // The stack is fresh, so there's no admin yet; anything else is an error
+        request_json(http.post(self.url("/api/auth/admin-sign-up")).json(&json!({
+            "email": options.admin_email,
+            "password": options.admin_password,
+            "name": "Test Admin",
+        })))
+        .await
+        .map_err(|e| ImmichError::Docker(format!("admin sign-up on {} failed: {}", self.base_url, e)))?;
+
+        let login = request_json(http.post(self.url("/api/auth/login")).json(&json!({
+            "email": options.admin_email,
+            "password": options.admin_password,
+        })))
+        .await?;
+        let token = string_field(&login, "accessToken")?;
+
+        let key = request_json(
+            http.post(self.url("/api/api-keys"))
+                .bearer_auth(&token)
+                .json(&json!({ "name": "test-harness", "permissions": ["all"] })),
+        )
+        .await?;
+        let api_key = string_field(&key, "secret")?;
+
+        if let Some(distance) = options.duplicate_max_distance {
+            let mut config = request_json(http.get(self.url("/api/system-config")).header("x-api-key", &api_key)).await?;
+            config["machineLearning"]["duplicateDetection"]["maxDistance"] = json!(distance);
+            request_json(
+                http.put(self.url("/api/system-config"))
+                    .header("x-api-key", &api_key)
+                    .json(&config),
+            )
+            .await?;
+        }
+
+        Ok(api_key)
+    }
+
+    /// Wait until the server answers and duplicate detection is available
+    /// (which needs machine learning to be up).
+    async fn wait_until_ready(&self, http: &reqwest::Client, timeout: Duration) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let features = match http.get(self.url("/api/server/features")).send().await {
+                Ok(response) if response.status().is_success() => response.json::<Value>().await.ok(),
+                _ => None,
+            };
+            if features.is_some_and(|f| f["duplicateDetection"] == json!(true)) {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(ImmichError::Docker(format!(
+                    "server at {} not ready after {}s",
+                    self.base_url,
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        // Drop can't await, and blocking here would stall the test's runtime
+        // until the stack is down, so start the removal and leave it running.
+        // Compose finds the project's containers by name, without the file.
+        if self.running {
+            let _ = std::process::Command::new("docker")
+                .args(["compose", "-p", &self.project, "down", "-v"])
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn();
+            std::fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+}
+
+/// A `docker compose` command for the project.
+fn compose(project: &str, dir: &Path) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new("docker");
+    command
+        .args(["compose", "-p", project, "-f"])
+        .arg(dir.join("docker-compose.yml"));
+    command
+}
+
+/// Turn a finished command into an error unless it succeeded.
+fn check(output: std::io::Result<std::process::Output>, what: &str) -> Result<()> {
+    let output = output.map_err(|e| ImmichError::Docker(format!("failed to run {}: {}. Is Docker installed?", what, e)))?;
+    if !output.status.success() {
+        return Err(ImmichError::Docker(format!(
+            "{} failed: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Send a request and parse its JSON response, failing on an error status.
+async fn request_json(request: reqwest::RequestBuilder) -> Result<Value> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(ImmichError::Api {
+            status: status.as_u16(),
+            message,
+        });
+    }
+    Ok(response.json().await?)
+}
+
+fn string_field(value: &Value, field: &str) -> Result<String> {
+    value[field]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| ImmichError::Docker(format!("response has no {}: {}", field, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_harnesses_get_their_own_project() {
+        let (a, b) = (HarnessOptions::default(), HarnessOptions::default());
+        assert!(a.project.starts_with("immich-test-"));
+        assert_ne!(a.project, b.project);
+        assert!(COMPOSE_FILE.contains("${IMMICH_PORT:-2283}:2283"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_admin_sign_up_fails_bootstrap() {
+        // The mock server answers readiness checks but has no sign-up endpoint
+        let server = crate::testing::mock_server::MockServer::start(&[]).await.unwrap();
+        let harness = TestHarness {
+            base_url: server.url(),
+            api_key: String::new(),
+            client: ImmichClient::new(&server.url(), "pending").unwrap(),
+            project: "immich-test-mock".to_string(),
+            dir: PathBuf::new(),
+            running: false,
+        };
+
+        let err = harness.bootstrap(&HarnessOptions::default()).await.unwrap_err();
+        assert!(err.to_string().contains("admin sign-up"), "{}", err);
+    }
+
+    /// Needs Docker and pulls the Immich images.
+    ///
+    /// Run with: `cargo test --features docker test_harness_lifecycle -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_harness_lifecycle() {
+        let options = HarnessOptions {
+            port: 2284,
+            ..Default::default()
+        };
+        let harness = TestHarness::start(options).await.unwrap();
+        harness.client.get_server_version().await.unwrap();
+        harness.teardown().await.unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-//! Test fixture specifications for all 32 test scenarios.
+//! Test fixture specifications for all 32 test scenarios (33 with the `heic` feature).
 //!
 //! Each fixture defines the images, metadata, and expected outcomes
 //! for integration testing. All images are created by transforming
@@ -10,10 +10,15 @@
 //!
 //! This maintains visual similarity for CLIP while giving clear dimension winner.
 
+use std::path::Path;
+
 use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 
 use super::generator::{ExifSpec, TestImage, TransformSpec};
-use super::scenarios::TestScenario;
+use super::scenarios::{ScenarioRegistry, TestScenario};
+use crate::error::Result;
+use crate::report_io;
 
 /// A complete test fixture for a scenario.
 #[derive(Debug, Clone)]
@@ -28,52 +33,67 @@ pub struct ScenarioFixture {
     pub description: String,
 }
 
-/// Returns fixture definitions for all 32 test scenarios.
+impl ScenarioFixture {
+    /// Manifest for this fixture, listing the `images` that were generated.
+    pub fn manifest(&self, images: Vec<String>) -> FixtureManifest {
+        FixtureManifest {
+            scenario: self.scenario.code().to_uppercase(),
+            description: self.description.clone(),
+            images,
+            expected_winner: self
+                .images
+                .get(self.expected_winner_index)
+                .map(|i| i.filename.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The `manifest.json` written next to a scenario's generated images.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureManifest {
+    /// Scenario code, upper case (e.g. `W1`)
+    pub scenario: String,
+    /// What the scenario tests
+    pub description: String,
+    /// Files in the duplicate group
+    pub images: Vec<String>,
+    /// Filename of the expected winner
+    pub expected_winner: String,
+}
+
+impl FixtureManifest {
+    /// Name of the manifest file in a scenario directory.
+    pub const FILE_NAME: &str = "manifest.json";
+
+    /// Load the manifest from a scenario directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest is missing or malformed.
+    pub fn load(scenario_dir: &Path) -> Result<Self> {
+        report_io::read_json(scenario_dir.join(Self::FILE_NAME))
+    }
+
+    /// Write the manifest to a scenario directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, scenario_dir: &Path) -> Result<()> {
+        report_io::write_json(scenario_dir.join(Self::FILE_NAME), self)
+    }
+}
+
+/// Returns fixture definitions for all built-in test scenarios.
 pub fn all_fixtures() -> Vec<ScenarioFixture> {
-    vec![
-        // ===== Winner Selection Scenarios (W) =====
-        w1_clear_dimension_winner(),
-        w2_same_dimensions_different_size(),
-        w3_same_dimensions_same_size(),
-        w4_some_missing_dimensions(),
-        w5_only_one_has_dimensions(),
-        w6_all_missing_dimensions(),
-        w7_three_plus_duplicates(),
-        w8_same_pixels_different_aspect(),
-        // ===== Consolidation Scenarios (C) =====
-        c1_winner_lacks_gps_loser_has(),
-        c2_winner_lacks_datetime_loser_has(),
-        c3_winner_lacks_description_loser_has(),
-        c4_winner_lacks_all_loser_has_all(),
-        c5_both_have_gps(),
-        c6_multiple_losers_contribute(),
-        c7_no_loser_has_needed(),
-        c8_winner_has_everything(),
-        // ===== Conflict Scenarios (F) =====
-        f1_gps_conflict(),
-        f2_gps_within_threshold(),
-        f3_timezone_conflict(),
-        f4_camera_conflict(),
-        f5_capture_time_conflict(),
-        f6_multiple_conflicts(),
-        f7_no_conflicts(),
-        // ===== Edge Case Scenarios (X) =====
-        x1_single_asset_group(),
-        x2_large_group(),
-        x3_large_file(),
-        x4_special_chars_filename(),
-        x5_video(),
-        x7_png(),
-        x9_unicode_description(),
-        x10_very_old_date(),
-        x11_future_date(),
-    ]
+    ScenarioRegistry::builtin().fixtures()
 }
 
 // ===== Winner Selection Scenarios =====
 // Each uses its own unique base image
 
-fn w1_clear_dimension_winner() -> ScenarioFixture {
+pub(super) fn w1_clear_dimension_winner() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::W1ClearDimensionWinner,
         images: vec![
@@ -95,7 +115,7 @@ fn w1_clear_dimension_winner() -> ScenarioFixture {
     }
 }
 
-fn w2_same_dimensions_different_size() -> ScenarioFixture {
+pub(super) fn w2_same_dimensions_different_size() -> ScenarioFixture {
     // Same dimensions but different file size via quality
     ScenarioFixture {
         scenario: TestScenario::W2SameDimensionsDifferentSize,
@@ -118,7 +138,7 @@ fn w2_same_dimensions_different_size() -> ScenarioFixture {
     }
 }
 
-fn w3_same_dimensions_same_size() -> ScenarioFixture {
+pub(super) fn w3_same_dimensions_same_size() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::W3SameDimensionsSameSize,
         images: vec![
@@ -148,7 +168,7 @@ fn w3_same_dimensions_same_size() -> ScenarioFixture {
     }
 }
 
-fn w4_some_missing_dimensions() -> ScenarioFixture {
+pub(super) fn w4_some_missing_dimensions() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::W4SomeMissingDimensions,
         images: vec![
@@ -171,7 +191,7 @@ fn w4_some_missing_dimensions() -> ScenarioFixture {
     }
 }
 
-fn w5_only_one_has_dimensions() -> ScenarioFixture {
+pub(super) fn w5_only_one_has_dimensions() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::W5OnlyOneHasDimensions,
         images: vec![
@@ -194,7 +214,7 @@ fn w5_only_one_has_dimensions() -> ScenarioFixture {
     }
 }
 
-fn w6_all_missing_dimensions() -> ScenarioFixture {
+pub(super) fn w6_all_missing_dimensions() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::W6AllMissingDimensions,
         images: vec![
@@ -218,7 +238,7 @@ fn w6_all_missing_dimensions() -> ScenarioFixture {
     }
 }
 
-fn w7_three_plus_duplicates() -> ScenarioFixture {
+pub(super) fn w7_three_plus_duplicates() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::W7ThreePlusDuplicates,
         images: vec![
@@ -246,7 +266,7 @@ fn w7_three_plus_duplicates() -> ScenarioFixture {
     }
 }
 
-fn w8_same_pixels_different_aspect() -> ScenarioFixture {
+pub(super) fn w8_same_pixels_different_aspect() -> ScenarioFixture {
     // Use explicit dimensions to control aspect ratio
     ScenarioFixture {
         scenario: TestScenario::W8SamePixelsDifferentAspect,
@@ -271,7 +291,7 @@ fn w8_same_pixels_different_aspect() -> ScenarioFixture {
 
 // ===== Consolidation Scenarios =====
 
-fn c1_winner_lacks_gps_loser_has() -> ScenarioFixture {
+pub(super) fn c1_winner_lacks_gps_loser_has() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::C1WinnerLacksGpsLoserHas,
         images: vec![
@@ -297,7 +317,7 @@ fn c1_winner_lacks_gps_loser_has() -> ScenarioFixture {
     }
 }
 
-fn c2_winner_lacks_datetime_loser_has() -> ScenarioFixture {
+pub(super) fn c2_winner_lacks_datetime_loser_has() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::C2WinnerLacksDatetimeLoserHas,
         images: vec![
@@ -323,7 +343,7 @@ fn c2_winner_lacks_datetime_loser_has() -> ScenarioFixture {
     }
 }
 
-fn c3_winner_lacks_description_loser_has() -> ScenarioFixture {
+pub(super) fn c3_winner_lacks_description_loser_has() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::C3WinnerLacksDescriptionLoserHas,
         images: vec![
@@ -349,7 +369,7 @@ fn c3_winner_lacks_description_loser_has() -> ScenarioFixture {
     }
 }
 
-fn c4_winner_lacks_all_loser_has_all() -> ScenarioFixture {
+pub(super) fn c4_winner_lacks_all_loser_has_all() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::C4WinnerLacksAllLoserHasAll,
         images: vec![
@@ -379,7 +399,7 @@ fn c4_winner_lacks_all_loser_has_all() -> ScenarioFixture {
     }
 }
 
-fn c5_both_have_gps() -> ScenarioFixture {
+pub(super) fn c5_both_have_gps() -> ScenarioFixture {
     let gps = Some((48.8566, 2.3522)); // Paris
     ScenarioFixture {
         scenario: TestScenario::C5BothHaveGps,
@@ -410,7 +430,7 @@ fn c5_both_have_gps() -> ScenarioFixture {
     }
 }
 
-fn c6_multiple_losers_contribute() -> ScenarioFixture {
+pub(super) fn c6_multiple_losers_contribute() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::C6MultipleLosersContribute,
         images: vec![
@@ -446,7 +466,7 @@ fn c6_multiple_losers_contribute() -> ScenarioFixture {
     }
 }
 
-fn c7_no_loser_has_needed() -> ScenarioFixture {
+pub(super) fn c7_no_loser_has_needed() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::C7NoLoserHasNeeded,
         images: vec![
@@ -468,7 +488,7 @@ fn c7_no_loser_has_needed() -> ScenarioFixture {
     }
 }
 
-fn c8_winner_has_everything() -> ScenarioFixture {
+pub(super) fn c8_winner_has_everything() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::C8WinnerHasEverything,
         images: vec![
@@ -500,7 +520,7 @@ fn c8_winner_has_everything() -> ScenarioFixture {
 
 // ===== Conflict Scenarios =====
 
-fn f1_gps_conflict() -> ScenarioFixture {
+pub(super) fn f1_gps_conflict() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::F1GpsConflict,
         images: vec![
@@ -530,7 +550,7 @@ fn f1_gps_conflict() -> ScenarioFixture {
     }
 }
 
-fn f2_gps_within_threshold() -> ScenarioFixture {
+pub(super) fn f2_gps_within_threshold() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::F2GpsWithinThreshold,
         images: vec![
@@ -560,7 +580,7 @@ fn f2_gps_within_threshold() -> ScenarioFixture {
     }
 }
 
-fn f3_timezone_conflict() -> ScenarioFixture {
+pub(super) fn f3_timezone_conflict() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::F3TimezoneConflict,
         images: vec![
@@ -590,7 +610,7 @@ fn f3_timezone_conflict() -> ScenarioFixture {
     }
 }
 
-fn f4_camera_conflict() -> ScenarioFixture {
+pub(super) fn f4_camera_conflict() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::F4CameraConflict,
         images: vec![
@@ -622,7 +642,7 @@ fn f4_camera_conflict() -> ScenarioFixture {
     }
 }
 
-fn f5_capture_time_conflict() -> ScenarioFixture {
+pub(super) fn f5_capture_time_conflict() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::F5CaptureTimeConflict,
         images: vec![
@@ -652,7 +672,7 @@ fn f5_capture_time_conflict() -> ScenarioFixture {
     }
 }
 
-fn f6_multiple_conflicts() -> ScenarioFixture {
+pub(super) fn f6_multiple_conflicts() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::F6MultipleConflicts,
         images: vec![
@@ -686,7 +706,7 @@ fn f6_multiple_conflicts() -> ScenarioFixture {
     }
 }
 
-fn f7_no_conflicts() -> ScenarioFixture {
+pub(super) fn f7_no_conflicts() -> ScenarioFixture {
     let exif = ExifSpec {
         gps: Some((51.5074, -0.1278)),
         camera_make: Some("Canon".into()),
@@ -717,7 +737,7 @@ fn f7_no_conflicts() -> ScenarioFixture {
 
 // ===== Edge Case Scenarios =====
 
-fn x1_single_asset_group() -> ScenarioFixture {
+pub(super) fn x1_single_asset_group() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X1SingleAssetGroup,
         images: vec![TestImage::new(
@@ -731,7 +751,7 @@ fn x1_single_asset_group() -> ScenarioFixture {
     }
 }
 
-fn x2_large_group() -> ScenarioFixture {
+pub(super) fn x2_large_group() -> ScenarioFixture {
     // Use narrow scale range (97-99%) for CLIP detection
     // All images are very similar size, quality varies
     let scales: [u32; 12] = [97, 97, 97, 98, 98, 98, 98, 99, 99, 99, 99, 100];
@@ -756,7 +776,7 @@ fn x2_large_group() -> ScenarioFixture {
     }
 }
 
-fn x3_large_file() -> ScenarioFixture {
+pub(super) fn x3_large_file() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X3LargeFile,
         images: vec![
@@ -778,7 +798,7 @@ fn x3_large_file() -> ScenarioFixture {
     }
 }
 
-fn x4_special_chars_filename() -> ScenarioFixture {
+pub(super) fn x4_special_chars_filename() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X4SpecialCharsFilename,
         images: vec![
@@ -800,7 +820,7 @@ fn x4_special_chars_filename() -> ScenarioFixture {
     }
 }
 
-fn x5_video() -> ScenarioFixture {
+pub(super) fn x5_video() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X5Video,
         images: vec![
@@ -818,7 +838,29 @@ fn x5_video() -> ScenarioFixture {
     }
 }
 
-fn x7_png() -> ScenarioFixture {
+pub(super) fn x6_heic() -> ScenarioFixture {
+    ScenarioFixture {
+        scenario: TestScenario::X6Heic,
+        images: vec![
+            TestImage::new(
+                "x6_photo.heic",
+                TransformSpec::new("base_x6.jpg")
+                    .with_scale(100)
+                    .with_quality(90),
+            ),
+            TestImage::new(
+                "x6_photo_converted.jpg",
+                TransformSpec::new("base_x6.jpg")
+                    .with_scale(99)
+                    .with_quality(60),
+            ),
+        ],
+        expected_winner_index: 0,
+        description: "HEIC original vs converted JPEG - HEIC larger".into(),
+    }
+}
+
+pub(super) fn x7_png() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X7Png,
         images: vec![
@@ -838,7 +880,7 @@ fn x7_png() -> ScenarioFixture {
     }
 }
 
-fn x9_unicode_description() -> ScenarioFixture {
+pub(super) fn x9_unicode_description() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X9UnicodeDescription,
         images: vec![
@@ -864,7 +906,7 @@ fn x9_unicode_description() -> ScenarioFixture {
     }
 }
 
-fn x10_very_old_date() -> ScenarioFixture {
+pub(super) fn x10_very_old_date() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X10VeryOldDate,
         images: vec![
@@ -890,7 +932,7 @@ fn x10_very_old_date() -> ScenarioFixture {
     }
 }
 
-fn x11_future_date() -> ScenarioFixture {
+pub(super) fn x11_future_date() -> ScenarioFixture {
     ScenarioFixture {
         scenario: TestScenario::X11FutureDate,
         images: vec![
@@ -927,7 +969,8 @@ mod tests {
     #[test]
     fn test_all_fixtures_count() {
         let fixtures = all_fixtures();
-        assert_eq!(fixtures.len(), 32, "Should have exactly 32 fixtures");
+        let expected = if cfg!(feature = "heic") { 33 } else { 32 };
+        assert_eq!(fixtures.len(), expected, "Should have exactly {} fixtures", expected);
     }
 
     #[test]
@@ -151,9 +151,10 @@ pub fn generate_image(spec: &TestImage, base_dir: &Path, output_dir: &Path) -> R
         "mp4" | "mov" | "avi" => {
             return generate_video(&spec.filename, output_dir, spec.transform.width, spec.transform.height);
         }
+        #[cfg(not(feature = "heic"))]
         "heic" | "heif" => {
             return Err(ImmichError::Io(std::io::Error::other(
-                "HEIC encoding not available - requires platform-specific encoder",
+                "HEIC encoding not available - rebuild with `--features heic` (requires libheif's heif-enc)",
             )));
         }
         "cr3" | "cr2" | "nef" | "arw" | "dng" | "raf" | "orf" => {
@@ -212,6 +213,10 @@ pub fn generate_image(spec: &TestImage, base_dir: &Path, output_dir: &Path) -> R
                     ImmichError::Io(std::io::Error::other(format!("Failed to save PNG: {}", e)))
                 })?;
         }
+        #[cfg(feature = "heic")]
+        "heic" | "heif" => {
+            encode_heic(&resized, &output_path, spec.transform.quality)?;
+        }
         _ => {
             // JPEG with quality control
             let mut output_file = std::fs::File::create(&output_path).map_err(|e| {
@@ -282,6 +287,41 @@ fn generate_video(
     Ok(output_path)
 }
 
+/// Encode an image as HEIC using libheif's `heif-enc` CLI.
+///
+/// The image is handed to `heif-enc` as a lossless PNG, so the HEIC is the
+/// only lossy step.
+#[cfg(feature = "heic")]
+fn encode_heic(img: &image::DynamicImage, output_path: &Path, quality: u8) -> Result<()> {
+    let source = output_path.with_extension("heif-enc.png");
+    img.save_with_format(&source, image::ImageFormat::Png).map_err(|e| {
+        ImmichError::Io(std::io::Error::other(format!("Failed to save PNG for heif-enc: {}", e)))
+    })?;
+
+    let output = Command::new("heif-enc")
+        .args(["-q", &quality.min(100).to_string(), "-o"])
+        .arg(output_path)
+        .arg(&source)
+        .output();
+    let _ = std::fs::remove_file(&source);
+    let output = output.map_err(|e| {
+        ImmichError::Io(std::io::Error::other(format!(
+            "Failed to run heif-enc: {}. Is libheif installed?",
+            e
+        )))
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ImmichError::Io(std::io::Error::other(format!(
+            "heif-enc failed: {}",
+            stderr
+        ))));
+    }
+
+    Ok(())
+}
+
 /// Apply EXIF metadata to an image using exiftool CLI.
 fn apply_exif(path: &Path, exif: &ExifSpec, strip_dimensions: bool) -> Result<()> {
     let mut args: Vec<String> = vec!["-overwrite_original".to_string()];
@@ -377,4 +417,21 @@ mod tests {
         assert_eq!(spec.width, Some(50));
         assert_eq!(spec.height, None);
     }
+
+    #[test]
+    fn test_heic_in_matrix_only_with_feature() {
+        use crate::testing::{ScenarioRegistry, TestScenario};
+
+        let registry = ScenarioRegistry::builtin();
+        assert_eq!(registry.get("x6").is_some(), cfg!(feature = "heic"));
+        assert_eq!(TestScenario::X6Heic.code(), "x6");
+
+        #[cfg(not(feature = "heic"))]
+        {
+            let dir = tempfile::tempdir().unwrap();
+            let spec = TestImage::new("x6_photo.heic", TransformSpec::new("base_x6.jpg"));
+            let err = generate_image(&spec, dir.path(), dir.path()).unwrap_err();
+            assert!(err.to_string().contains("--features heic"));
+        }
+    }
 }
@@ -0,0 +1,1392 @@
+//! Minimal in-process Immich API mock for benchmarks and tests.
+//!
+//! Serves a fixed set of duplicate groups over plain HTTP on a loopback port,
+//! implementing just the endpoints the analyzer, executor, doctor, thumbnail cache, and fixture seeding use. Downloads
+//! return a small fake JPEG so backup integrity checks pass.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use chrono::{TimeZone, Utc};
+use parking_lot::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::error::{ImmichError, Result};
+use crate::models::{AssetResponse, DuplicateGroup};
+use crate::testing::builder::AssetBuilder;
+
+/// Size in bytes of the fake original returned for downloads.
+const FAKE_ORIGINAL_SIZE: usize = 4096;
+
+/// Size in bytes of the fake thumbnail (a prefix of the fake original).
+const FAKE_THUMBNAIL_SIZE: usize = 512;
+
+/// Server version reported by the mock.
+const SERVER_VERSION_JSON: &[u8] = br#"{"major":1,"minor":132,"patch":0}"#;
+
+/// ID of the user the mock server's API key belongs to.
+const USER_ID: &str = "bench-owner";
+
+/// User that owns every API key.
+const USER_JSON: &[u8] = br#"{"id":"bench-owner","email":"bench@example.com","name":"Bench","isAdmin":true}"#;
+
+/// API key with full permissions.
+const API_KEY_JSON: &[u8] = br#"{"id":"bench-key","name":"bench","permissions":["all"]}"#;
+
+/// Idle duplicate-detection queue.
+const JOBS_JSON: &[u8] = br#"{"duplicateDetection":{"jobCounts":{"active":0,"completed":1,"failed":0,"delayed":0,"waiting":0,"paused":0},"queueStatus":{"isActive":false,"isPaused":false}}}"#;
+
+/// Empty trash statistics.
+const STATISTICS_JSON: &[u8] = br#"{"images":0,"videos":0,"total":0}"#;
+
+/// Server statistics for a small library.
+const SERVER_STATISTICS_JSON: &[u8] = br#"{"photos":120,"videos":8,"usage":734003200,"usageByUser":[{"userId":"bench-owner","userName":"Bench","photos":120,"videos":8,"usage":734003200}]}"#;
+
+/// Content type of JSON responses.
+const JSON: &str = "application/json";
+
+/// Shared, read-only data served by the mock.
+struct MockState {
+    /// Pre-serialized `GET /api/duplicates` response
+    duplicates_json: Vec<u8>,
+
+    /// Pre-serialized asset responses by ID
+    assets: HashMap<String, Vec<u8>>,
+
+    /// Fake original file contents
+    original: Vec<u8>,
+
+    /// Thumbnail and preview requests served
+    thumbnail_requests: AtomicUsize,
+
+    /// Number of shared link listings served
+    shared_link_requests: AtomicUsize,
+
+    /// Requests still to be answered with 429 Too Many Requests
+    rate_limited_requests: AtomicUsize,
+
+    /// Albums, which tests can add and the executor can modify
+    albums: Mutex<Vec<MockAlbum>>,
+
+    /// Shared links, which tests can add
+    shared_links: Mutex<Vec<MockSharedLink>>,
+
+    /// Memories (ID and member asset IDs), which tests can add
+    memories: Mutex<Vec<(String, Vec<String>)>>,
+
+    /// Tags, which tests can add and the client can create and attach
+    tags: Mutex<Vec<MockTag>>,
+
+    /// People (ID and name), which tests can add
+    people: Mutex<Vec<(String, String)>>,
+
+    /// Stacks (ID and asset IDs, primary first), which the client can create
+    /// and remove
+    stacks: Mutex<Vec<(String, Vec<String>)>>,
+
+    /// Asset responses changed by `PUT /api/assets/{id}`, served instead of
+    /// the originals
+    updated_assets: Mutex<HashMap<String, Vec<u8>>>,
+
+    /// Bulk updates (`PUT /api/assets`) served
+    bulk_update_requests: AtomicUsize,
+
+    /// Fields (as sent, e.g. `latitude`) that `PUT /api/assets/{id}` accepts
+    /// but silently ignores
+    ignored_update_fields: Mutex<HashSet<String>>,
+
+    /// Assets removed by `DELETE /api/assets`, which then answer 404
+    deleted: Mutex<HashSet<String>>,
+
+    /// Assets that `DELETE /api/assets` fails to remove, failing the request
+    undeletable: Mutex<HashSet<String>>,
+
+    /// Tags that `PUT /api/tags/{id}/assets` fails to attach
+    untaggable: Mutex<HashSet<String>>,
+
+    /// Assets whose original downloads never get an answer
+    stalled_downloads: Mutex<HashSet<String>>,
+
+    /// Assets whose originals are served as an HTML error page, as a
+    /// broken original would be
+    corrupt_originals: Mutex<HashSet<String>>,
+
+    /// Status `GET /api/server/statistics` answers with instead of the
+    /// statistics
+    server_statistics_status: Mutex<Option<&'static str>>,
+
+    /// Most IDs a `DELETE /api/assets` request may carry before it's
+    /// refused as too large
+    max_delete_ids: AtomicUsize,
+
+    /// If set, every API request gets a proxy's 404 page, as when the
+    /// server sits under a path prefix the client doesn't know about
+    behind_path_prefix: AtomicBool,
+
+    /// Objects uploaded with an S3-style `PUT /{bucket}/{key}`, or
+    /// assembled from a multipart upload, by `bucket/key`
+    objects: Mutex<HashMap<String, Vec<u8>>>,
+
+    /// Parts of S3 multipart uploads in progress, by upload ID and part number
+    object_parts: Mutex<HashMap<(String, usize), Vec<u8>>>,
+
+    /// S3 multipart uploads started so far, for numbering upload IDs
+    multipart_uploads: AtomicUsize,
+
+    /// S3 multipart upload parts received so far
+    object_part_count: AtomicUsize,
+
+    /// Bodies POSTed to webhooks (`/hooks/...`), in order
+    webhooks: Mutex<Vec<Vec<u8>>>,
+
+    /// Filenames uploaded with `POST /api/assets`, in order; an upload's
+    /// asset ID is `uploaded-{index}`
+    uploads: Mutex<Vec<String>>,
+
+    /// Sidecar filenames uploaded with `POST /api/assets`, in order
+    sidecar_uploads: Mutex<Vec<String>>,
+}
+
+/// A shared link held by the mock: either an album or individual assets.
+struct MockSharedLink {
+    id: String,
+    album_id: Option<String>,
+    asset_ids: Vec<String>,
+}
+
+/// A tag held by the mock.
+struct MockTag {
+    id: String,
+    value: String,
+    asset_ids: Vec<String>,
+}
+
+/// An album held by the mock.
+struct MockAlbum {
+    id: String,
+    name: String,
+    asset_ids: Vec<String>,
+
+    /// Cover asset (the first asset, until changed)
+    thumbnail_asset_id: Option<String>,
+
+    /// Report additions as successful without applying them
+    ignores_adds: bool,
+
+    /// User who owns the album; only the owner may change its assets
+    owner_id: String,
+}
+
+/// A running mock Immich server.
+///
+/// The server stops when this value is dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+    state: Arc<MockState>,
+}
+
+impl MockServer {
+    /// Start a mock server serving the given duplicate groups.
+    pub async fn start(groups: &[DuplicateGroup]) -> Result<Self> {
+        let duplicates_json = serde_json::to_vec(groups).map_err(std::io::Error::other)?;
+
+        let mut assets = HashMap::new();
+        for asset in groups.iter().flat_map(|g| g.assets.iter()) {
+            let json = serde_json::to_vec(asset).map_err(std::io::Error::other)?;
+            assets.insert(asset.id.clone(), json);
+        }
+
+        let mut original = vec![0u8; FAKE_ORIGINAL_SIZE];
+        original[..4].copy_from_slice(&[0xFF, 0xD8, 0xFF, 0xE0]);
+
+        let state = Arc::new(MockState {
+            duplicates_json,
+            assets,
+            original,
+            thumbnail_requests: AtomicUsize::new(0),
+            shared_link_requests: AtomicUsize::new(0),
+            rate_limited_requests: AtomicUsize::new(0),
+            albums: Mutex::new(Vec::new()),
+            shared_links: Mutex::new(Vec::new()),
+            memories: Mutex::new(Vec::new()),
+            tags: Mutex::new(Vec::new()),
+            people: Mutex::new(Vec::new()),
+            stacks: Mutex::new(Vec::new()),
+            updated_assets: Mutex::new(HashMap::new()),
+            bulk_update_requests: AtomicUsize::new(0),
+            ignored_update_fields: Mutex::new(HashSet::new()),
+            deleted: Mutex::new(HashSet::new()),
+            undeletable: Mutex::new(HashSet::new()),
+            untaggable: Mutex::new(HashSet::new()),
+            stalled_downloads: Mutex::new(HashSet::new()),
+            corrupt_originals: Mutex::new(HashSet::new()),
+            server_statistics_status: Mutex::new(None),
+            max_delete_ids: AtomicUsize::new(usize::MAX),
+            behind_path_prefix: AtomicBool::new(false),
+            objects: Mutex::new(HashMap::new()),
+            object_parts: Mutex::new(HashMap::new()),
+            multipart_uploads: AtomicUsize::new(0),
+            object_part_count: AtomicUsize::new(0),
+            webhooks: Mutex::new(Vec::new()),
+            uploads: Mutex::new(Vec::new()),
+            sidecar_uploads: Mutex::new(Vec::new()),
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let server_state = Arc::clone(&state);
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                // Header and body are separate writes; avoid Nagle/delayed-ACK stalls
+                let _ = stream.set_nodelay(true);
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    // Connection errors just end that connection
+                    let _ = serve_connection(stream, &state).await;
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            handle,
+            state: server_state,
+        })
+    }
+
+    /// Add an album containing the given assets.
+    pub fn add_album(&self, id: &str, name: &str, asset_ids: &[&str]) {
+        self.insert_album(id, name, asset_ids, false, USER_ID);
+    }
+
+    /// Add an album owned by another user, shared with the API key's user
+    /// as a viewer: adding or removing its assets answers 403.
+    pub fn add_shared_album(&self, id: &str, name: &str, owner_id: &str, asset_ids: &[&str]) {
+        self.insert_album(id, name, asset_ids, false, owner_id);
+    }
+
+    /// Add an album that reports additions as successful but never applies
+    /// them, for testing post-transfer verification.
+    pub fn add_album_ignoring_adds(&self, id: &str, name: &str, asset_ids: &[&str]) {
+        self.insert_album(id, name, asset_ids, true, USER_ID);
+    }
+
+    /// Add a shared link for individual assets.
+    pub fn add_shared_link(&self, id: &str, asset_ids: &[&str]) {
+        self.state.shared_links.lock().push(MockSharedLink {
+            id: id.to_string(),
+            album_id: None,
+            asset_ids: asset_ids.iter().map(|a| a.to_string()).collect(),
+        });
+    }
+
+    /// Add a shared link for an album.
+    pub fn share_album(&self, id: &str, album_id: &str) {
+        self.state.shared_links.lock().push(MockSharedLink {
+            id: id.to_string(),
+            album_id: Some(album_id.to_string()),
+            asset_ids: Vec::new(),
+        });
+    }
+
+    /// Assets shared by an individual shared link.
+    pub fn shared_link_assets(&self, id: &str) -> Vec<String> {
+        let links = self.state.shared_links.lock();
+        links.iter().find(|l| l.id == id).map(|l| l.asset_ids.clone()).unwrap_or_default()
+    }
+
+    /// Add an "On this day" memory.
+    pub fn add_memory(&self, id: &str, asset_ids: &[&str]) {
+        let mut memories = self.state.memories.lock();
+        memories.push((id.to_string(), asset_ids.iter().map(|a| a.to_string()).collect()));
+    }
+
+    /// Assets in a memory.
+    pub fn memory_assets(&self, id: &str) -> Vec<String> {
+        let memories = self.state.memories.lock();
+        memories.iter().find(|(m, _)| m == id).map(|(_, a)| a.clone()).unwrap_or_default()
+    }
+
+    /// Add a tag (by full value) attached to the given assets.
+    pub fn add_tag(&self, id: &str, value: &str, asset_ids: &[&str]) {
+        self.state.tags.lock().push(MockTag {
+            id: id.to_string(),
+            value: value.to_string(),
+            asset_ids: asset_ids.iter().map(|a| a.to_string()).collect(),
+        });
+    }
+
+    /// Assets carrying the tag with the given full value.
+    pub fn tagged_assets(&self, value: &str) -> Vec<String> {
+        let tags = self.state.tags.lock();
+        tags.iter().find(|t| t.value == value).map(|t| t.asset_ids.clone()).unwrap_or_default()
+    }
+
+    /// Add a recognized person.
+    pub fn add_person(&self, id: &str, name: &str) {
+        self.state.people.lock().push((id.to_string(), name.to_string()));
+    }
+
+    /// An object uploaded to the server acting as an S3 bucket, by `bucket/key`.
+    pub fn object(&self, path: &str) -> Option<Vec<u8>> {
+        self.state.objects.lock().get(path).cloned()
+    }
+
+    /// Number of S3 multipart upload parts received.
+    pub fn object_parts(&self) -> usize {
+        self.state.object_part_count.load(Ordering::Relaxed)
+    }
+
+    /// URL of a webhook on the mock; what is POSTed to it is kept for
+    /// [`webhook_bodies`](Self::webhook_bodies).
+    pub fn webhook_url(&self) -> String {
+        format!("{}/hooks/run-finished", self.url())
+    }
+
+    /// Bodies POSTed to webhooks so far, in order.
+    pub fn webhook_bodies(&self) -> Vec<Vec<u8>> {
+        self.state.webhooks.lock().clone()
+    }
+
+    /// Filenames uploaded so far, in order.
+    pub fn uploads(&self) -> Vec<String> {
+        self.state.uploads.lock().clone()
+    }
+
+    /// Sidecar filenames uploaded with assets so far, in order.
+    pub fn sidecar_uploads(&self) -> Vec<String> {
+        self.state.sidecar_uploads.lock().clone()
+    }
+
+    /// Cover asset of an album.
+    pub fn album_cover(&self, id: &str) -> Option<String> {
+        let albums = self.state.albums.lock();
+        albums.iter().find(|a| a.id == id).and_then(|a| a.thumbnail_asset_id.clone())
+    }
+
+    /// Answer the next `count` requests with 429 Too Many Requests and a
+    /// `Retry-After: 0` header.
+    pub fn rate_limit_next(&self, count: usize) {
+        self.state.rate_limited_requests.store(count, Ordering::Relaxed);
+    }
+
+    /// Number of bulk asset updates (`PUT /api/assets`) served so far.
+    pub fn bulk_update_requests(&self) -> usize {
+        self.state.bulk_update_requests.load(Ordering::Relaxed)
+    }
+
+    /// Make asset updates accept these fields (as sent, e.g. `latitude`)
+    /// without applying them, as Immich sometimes does.
+    pub fn ignore_updates_of(&self, fields: &[&str]) {
+        let mut ignored = self.state.ignored_update_fields.lock();
+        ignored.extend(fields.iter().map(|f| f.to_string()));
+    }
+
+    /// Make deletes of these assets fail. A delete request that includes
+    /// one still removes the others, then answers 500, as a server that
+    /// fails partway through would.
+    pub fn fail_deletes_of(&self, asset_ids: &[&str]) {
+        let mut undeletable = self.state.undeletable.lock();
+        undeletable.extend(asset_ids.iter().map(|id| id.to_string()));
+    }
+
+    /// Make attaching these tags (by ID) to assets fail with 500.
+    pub fn fail_tagging_with(&self, tag_ids: &[&str]) {
+        let mut untaggable = self.state.untaggable.lock();
+        untaggable.extend(tag_ids.iter().map(|id| id.to_string()));
+    }
+
+    /// Never answer downloads of these assets' originals, as a hung server
+    /// wouldn't.
+    pub fn stall_downloads_of(&self, asset_ids: &[&str]) {
+        let mut stalled = self.state.stalled_downloads.lock();
+        stalled.extend(asset_ids.iter().map(|id| id.to_string()));
+    }
+
+    /// Serve these assets' originals as an HTML error page instead of a JPEG.
+    pub fn corrupt_originals_of(&self, asset_ids: &[&str]) {
+        let mut corrupt = self.state.corrupt_originals.lock();
+        corrupt.extend(asset_ids.iter().map(|id| id.to_string()));
+    }
+
+    /// Answer `GET /api/server/statistics` with `status`, such as
+    /// `"403 Forbidden"` as Immich does for a key without admin rights.
+    pub fn refuse_server_statistics(&self, status: &'static str) {
+        *self.state.server_statistics_status.lock() = Some(status);
+    }
+
+    /// Refuse `DELETE /api/assets` requests with more than `max` IDs with
+    /// 413 Payload Too Large, removing nothing.
+    pub fn limit_delete_ids(&self, max: usize) {
+        self.state.max_delete_ids.store(max, Ordering::Relaxed);
+    }
+
+    /// Answer every API request with a reverse proxy's HTML 404 page, as a
+    /// server under a path prefix the client wasn't given would.
+    pub fn hide_behind_path_prefix(&self) {
+        self.state.behind_path_prefix.store(true, Ordering::Relaxed);
+    }
+
+    /// Asset IDs removed through the API so far.
+    pub fn deleted_assets(&self) -> HashSet<String> {
+        self.state.deleted.lock().clone()
+    }
+
+    /// Number of thumbnail or preview requests served so far.
+    pub fn thumbnail_requests(&self) -> usize {
+        self.state.thumbnail_requests.load(Ordering::Relaxed)
+    }
+
+    /// Number of times the shared links were listed so far.
+    pub fn shared_link_requests(&self) -> usize {
+        self.state.shared_link_requests.load(Ordering::Relaxed)
+    }
+
+    /// Asset IDs currently in an album.
+    pub fn album_assets(&self, id: &str) -> Vec<String> {
+        let albums = self.state.albums.lock();
+        albums
+            .iter()
+            .find(|a| a.id == id)
+            .map(|a| a.asset_ids.clone())
+            .unwrap_or_default()
+    }
+
+    fn insert_album(&self, id: &str, name: &str, asset_ids: &[&str], ignores_adds: bool, owner_id: &str) {
+        self.state.albums.lock().push(MockAlbum {
+            id: id.to_string(),
+            name: name.to_string(),
+            asset_ids: asset_ids.iter().map(|a| a.to_string()).collect(),
+            thumbnail_asset_id: asset_ids.first().map(|a| a.to_string()),
+            ignores_adds,
+            owner_id: owner_id.to_string(),
+        });
+    }
+
+    /// Base URL of the server (e.g. `http://127.0.0.1:54321`).
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Serve HTTP/1.1 requests on one keep-alive connection.
+async fn serve_connection(mut stream: TcpStream, state: &MockState) -> std::io::Result<()> {
+    let mut buffer: Vec<u8> = Vec::new();
+
+    loop {
+        // Read until the end of the request head
+        let head_end = loop {
+            if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            let mut chunk = [0u8; 4096];
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        };
+
+        let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+        let mut lines = head.lines();
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().unwrap_or_default().to_string();
+        let target = request_line.next().unwrap_or_default();
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path.to_string(), query.to_string()),
+            None => (target.to_string(), String::new()),
+        };
+
+        let content_length = lines
+            .filter_map(|l| l.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+
+        while buffer.len() < head_end + content_length {
+            let mut chunk = [0u8; 4096];
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        }
+        let request_body: Vec<u8> = buffer.drain(..head_end + content_length).skip(head_end).collect();
+
+        if let ("GET", ["", "api", "assets", id, "original"]) = (method.as_str(), path.split('/').collect::<Vec<_>>().as_slice())
+            && state.stalled_downloads.lock().contains(*id)
+        {
+            std::future::pending::<()>().await;
+        }
+
+        let rate_limited = state
+            .rate_limited_requests
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok();
+        let (status, content_type, body, extra_headers) = if rate_limited {
+            ("429 Too Many Requests", JSON, Cow::Borrowed(&b"{}"[..]), "Retry-After: 0\r\n")
+        } else if state.behind_path_prefix.load(Ordering::Relaxed) && path.starts_with("/api/") {
+            ("404 Not Found", "text/html", Cow::Borrowed(&b"<html><body>404 Not Found</body></html>"[..]), "")
+        } else {
+            let (status, content_type, body) = route(&method, &path, &query, &request_body, state);
+            // S3 clients need an ETag for each object and part they upload
+            let is_object_put = method == "PUT" && !path.starts_with("/api/");
+            (status, content_type, body, if is_object_put { "ETag: \"mock\"\r\n" } else { "" })
+        };
+        let header = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}\r\n",
+            status,
+            content_type,
+            body.len(),
+            extra_headers
+        );
+        stream.write_all(header.as_bytes()).await?;
+        stream.write_all(&body).await?;
+    }
+}
+
+/// A response: status line, content type, and body.
+type Response<'a> = (&'static str, &'static str, Cow<'a, [u8]>);
+
+/// Pick a response for a request.
+fn route<'a>(method: &str, path: &str, query: &str, body: &[u8], state: &'a MockState) -> Response<'a> {
+    let not_found: Response<'a> = ("404 Not Found", JSON, Cow::Borrowed(b"{\"message\":\"Not found\"}"));
+    let asset_not_found: Response<'a> = (
+        "404 Not Found",
+        JSON,
+        Cow::Borrowed(b"{\"message\":\"Not found or no asset.read access\",\"statusCode\":404}"),
+    );
+    let ok = |body: &'a [u8]| -> Response<'a> { ("200 OK", JSON, Cow::Borrowed(body)) };
+
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["api", "albums"]) => {
+            let asset_id = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("assetId="))
+                .unwrap_or_default();
+            ("200 OK", JSON, Cow::Owned(albums_containing(state, asset_id)))
+        }
+        ("POST", ["api", "albums"]) => ("201 Created", JSON, Cow::Owned(create_album(state, body))),
+        ("GET", ["api", "albums", id]) => match get_album(state, id) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("GET", ["api", "shared-links"]) => {
+            state.shared_link_requests.fetch_add(1, Ordering::Relaxed);
+            ("200 OK", JSON, Cow::Owned(shared_links(state)))
+        }
+        ("PUT" | "DELETE", ["api", "albums", id, "assets"]) if !owns_album(state, id) => {
+            ("403 Forbidden", JSON, Cow::Borrowed(b"{\"message\":\"Not found or no albumAsset.create access\"}"))
+        }
+        ("PUT", ["api", "albums", id, "assets"]) => match add_to_album(state, id, body) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("GET", ["api", "tags"]) => ("200 OK", JSON, Cow::Owned(tags(state))),
+        ("PUT", ["api", "tags"]) => ("200 OK", JSON, Cow::Owned(upsert_tags(state, body))),
+        ("GET", ["api", "tags", id]) => match get_tag(state, id) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("GET", ["api", "people"]) => ("200 OK", JSON, Cow::Owned(people(state))),
+        ("GET", ["api", "people", id]) => match get_person(state, id) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("GET", ["api", "stacks"]) => {
+            let primary = query.split('&').find_map(|pair| pair.strip_prefix("primaryAssetId="));
+            ("200 OK", JSON, Cow::Owned(stacks(state, primary)))
+        }
+        ("POST", ["api", "stacks"]) => ("201 Created", JSON, Cow::Owned(create_stack(state, body))),
+        ("GET", ["api", "stacks", id]) => match get_stack(state, id) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("DELETE", ["api", "stacks", id]) => {
+            let mut stacks = state.stacks.lock();
+            match stacks.iter().position(|(stack_id, _)| stack_id == id) {
+                Some(index) => {
+                    stacks.remove(index);
+                    ("204 No Content", JSON, Cow::Borrowed(&b""[..]))
+                }
+                None => not_found,
+            }
+        }
+        ("PUT", ["api", "tags", id, "assets"]) if state.untaggable.lock().contains(*id) => {
+            ("500 Internal Server Error", JSON, Cow::Borrowed(br#"{"message":"tagging failed"}"#.as_slice()))
+        }
+        ("PUT", ["api", "tags", id, "assets"]) => match tag_assets(state, id, body) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("POST", ["api", "search", "metadata"]) => ("200 OK", JSON, Cow::Owned(search_metadata(state, body))),
+        ("GET", ["api", "memories"]) => ("200 OK", JSON, Cow::Owned(memories(state))),
+        ("PUT", ["api", "memories", id, "assets"]) => match add_to_memory(state, id, body) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("PUT", ["api", "shared-links", id, "assets"]) => match add_to_shared_link(state, id, body) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("DELETE", ["api", "albums", id, "assets"]) => match remove_from_album(state, id, body) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("PATCH", ["api", "albums", id]) => match update_album(state, id, body) {
+            Some(Ok(json)) => ("200 OK", JSON, Cow::Owned(json)),
+            Some(Err(message)) => ("400 Bad Request", JSON, Cow::Owned(message)),
+            None => not_found,
+        },
+        ("GET", ["api", "duplicates"]) => ok(&state.duplicates_json),
+        ("GET", ["api", "server", "version"]) => ok(SERVER_VERSION_JSON),
+        ("GET", ["api", "server", "features"]) => ok(br#"{"duplicateDetection":true,"search":true}"#),
+        ("GET", ["api", "server", "statistics"]) => {
+            match *state.server_statistics_status.lock() {
+                Some(status) => (status, JSON, Cow::Borrowed(b"{\"message\":\"Statistics refused\"}")),
+                None => ok(SERVER_STATISTICS_JSON),
+            }
+        }
+        ("GET", ["api", "users", "me"]) => ok(USER_JSON),
+        ("GET", ["api", "api-keys", "me"]) => ok(API_KEY_JSON),
+        ("GET", ["api", "jobs"]) => ok(JOBS_JSON),
+        ("GET", ["api", "assets", "statistics"]) => ok(STATISTICS_JSON),
+        ("DELETE", ["api", "assets"]) if delete_too_large(state, body) => {
+            ("413 Payload Too Large", JSON, Cow::Borrowed(b"{\"message\":\"Payload too large\"}"))
+        }
+        ("DELETE", ["api", "assets"]) => {
+            if delete_assets(state, body) {
+                ("204 No Content", JSON, Cow::Borrowed(b""))
+            } else {
+                ("500 Internal Server Error", JSON, Cow::Borrowed(b"{\"message\":\"Delete failed\"}"))
+            }
+        }
+        ("POST", ["api", "trash", "restore", "assets"]) => {
+            restore_assets(state, body);
+            ("204 No Content", JSON, Cow::Borrowed(b""))
+        }
+        ("GET", ["api", "assets", id, "original"])
+            if state.corrupt_originals.lock().contains(*id) =>
+        {
+            ("200 OK", "image/jpeg", Cow::Borrowed(b"<html>error</html>".as_slice()))
+        }
+        ("GET", ["api", "assets", id, "original"]) if state.assets.contains_key(*id) => {
+            ("200 OK", "image/jpeg", Cow::Borrowed(&state.original))
+        }
+        ("GET", ["api", "assets", id, "thumbnail"]) if state.assets.contains_key(*id) => {
+            state.thumbnail_requests.fetch_add(1, Ordering::Relaxed);
+            ("200 OK", "image/jpeg", Cow::Borrowed(&state.original[..FAKE_THUMBNAIL_SIZE]))
+        }
+        ("GET" | "PUT", ["api", "assets", id]) if state.deleted.lock().contains(*id) => {
+            asset_not_found
+        }
+        ("POST", ["api", "assets"]) => ("201 Created", JSON, Cow::Owned(upload(state, body))),
+        ("POST", ["api", "assets", "bulk-upload-check"]) => ("200 OK", JSON, Cow::Owned(bulk_upload_check(state, body))),
+        ("PUT", ["api", "assets"]) => {
+            state.bulk_update_requests.fetch_add(1, Ordering::Relaxed);
+            match update_assets_bulk(state, body) {
+                Some(()) => ("204 No Content", JSON, Cow::Borrowed(&b""[..])),
+                None => ("400 Bad Request", JSON, Cow::Borrowed(&b"{\"message\":\"Bad request\"}"[..])),
+            }
+        }
+        ("PUT", ["api", "assets", id]) => match update_asset(state, id, body) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(json)),
+            None => not_found,
+        },
+        ("GET", ["api", "assets", id]) => match state.updated_assets.lock().get(*id) {
+            Some(json) => ("200 OK", JSON, Cow::Owned(with_tags(state, id, json))),
+            None => match state.assets.get(*id) {
+                Some(json) => ("200 OK", JSON, Cow::Owned(with_tags(state, id, json))),
+                None => asset_not_found,
+            },
+        },
+        ("POST", ["hooks", _]) => {
+            state.webhooks.lock().push(body.to_vec());
+            ("204 No Content", JSON, Cow::Borrowed(&b""[..]))
+        }
+        // Anything else outside the API is an S3 object upload
+        ("POST", [bucket, key @ ..]) if *bucket != "api" && !key.is_empty() && query == "uploads=" => {
+            let upload_id = state.multipart_uploads.fetch_add(1, Ordering::Relaxed);
+            let xml = format!(
+                "<InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key><UploadId>upload-{}</UploadId></InitiateMultipartUploadResult>",
+                bucket,
+                key.join("/"),
+                upload_id
+            );
+            ("200 OK", "application/xml", Cow::Owned(xml.into_bytes()))
+        }
+        ("PUT", [bucket, key @ ..]) if *bucket != "api" && !key.is_empty() && query.contains("uploadId=") => {
+            let param = |name: &str| query.split('&').find_map(|pair| pair.strip_prefix(name)).unwrap_or_default();
+            let part = param("partNumber=").parse().unwrap_or_default();
+            state.object_part_count.fetch_add(1, Ordering::Relaxed);
+            state
+                .object_parts
+                .lock()
+                .insert((param("uploadId=").to_string(), part), body.to_vec());
+            ("200 OK", "application/xml", Cow::Borrowed(&b""[..]))
+        }
+        ("POST", [bucket, key @ ..]) if *bucket != "api" && !key.is_empty() && query.starts_with("uploadId=") => {
+            let upload_id = &query["uploadId=".len()..];
+            let mut parts = state.object_parts.lock();
+            let listed = String::from_utf8_lossy(body);
+            let mut object = Vec::new();
+            for number in listed.split("<PartNumber>").skip(1) {
+                let number = number.split('<').next().unwrap_or_default().parse().unwrap_or_default();
+                match parts.remove(&(upload_id.to_string(), number)) {
+                    Some(part) => object.extend_from_slice(&part),
+                    None => return ("400 Bad Request", "application/xml", Cow::Borrowed(b"<Error><Code>InvalidPart</Code></Error>")),
+                }
+            }
+            let path = format!("{}/{}", bucket, key.join("/"));
+            state.objects.lock().insert(path, object);
+            let xml = "<CompleteMultipartUploadResult><ETag>\"complete\"</ETag></CompleteMultipartUploadResult>";
+            ("200 OK", "application/xml", Cow::Borrowed(xml.as_bytes()))
+        }
+        ("DELETE", [bucket, key @ ..]) if *bucket != "api" && !key.is_empty() && query.starts_with("uploadId=") => {
+            let upload_id = &query["uploadId=".len()..];
+            state.object_parts.lock().retain(|(id, _), _| id != upload_id);
+            ("204 No Content", "application/xml", Cow::Borrowed(&b""[..]))
+        }
+        ("PUT", [bucket, key @ ..]) if *bucket != "api" && !key.is_empty() => {
+            let path = format!("{}/{}", bucket, key.join("/"));
+            state.objects.lock().insert(path, body.to_vec());
+            ("200 OK", "application/xml", Cow::Borrowed(&b""[..]))
+        }
+        _ => not_found,
+    }
+}
+
+/// `POST /api/assets` response: record the multipart upload's filename.
+fn upload(state: &MockState, body: &[u8]) -> Vec<u8> {
+    let body = String::from_utf8_lossy(body);
+    let filename_of = |part: &str| {
+        body.split(&format!("name=\"{}\"; filename=\"", part))
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .map(str::to_string)
+    };
+    let filename = filename_of("assetData").unwrap_or_default();
+    if let Some(sidecar) = filename_of("sidecarData") {
+        state.sidecar_uploads.lock().push(sidecar);
+    }
+
+    let mut uploads = state.uploads.lock();
+    uploads.push(filename.clone());
+    let id = format!("uploaded-{}", uploads.len() - 1);
+
+    // The new asset can then be fetched and updated like any other
+    let asset = AssetBuilder::new(id.clone()).with_filename(filename).build();
+    if let Ok(json) = serde_json::to_vec(&asset) {
+        state.updated_assets.lock().insert(id.clone(), json);
+    }
+    serde_json::to_vec(&serde_json::json!({ "id": id, "status": "created" })).unwrap_or_default()
+}
+
+/// `POST /api/assets/bulk-upload-check` response: reject checksums of
+/// assets the mock holds (including removed ones, as trashed) as duplicates.
+fn bulk_upload_check(state: &MockState, body: &[u8]) -> Vec<u8> {
+    #[derive(serde::Deserialize)]
+    struct Item {
+        id: String,
+        checksum: String,
+    }
+    #[derive(serde::Deserialize)]
+    struct Check {
+        assets: Vec<Item>,
+    }
+
+    let items = serde_json::from_slice::<Check>(body).map(|c| c.assets).unwrap_or_default();
+    let existing: HashMap<String, String> = state
+        .assets
+        .values()
+        .filter_map(|json| serde_json::from_slice::<AssetResponse>(json).ok())
+        .map(|asset| (asset.checksum, asset.id))
+        .collect();
+    let deleted = state.deleted.lock();
+    let results: Vec<serde_json::Value> = items
+        .into_iter()
+        .map(|item| match existing.get(&item.checksum) {
+            Some(asset_id) => serde_json::json!({
+                "id": item.id,
+                "action": "reject",
+                "reason": "duplicate",
+                "assetId": asset_id,
+                "isTrashed": deleted.contains(asset_id),
+            }),
+            None => serde_json::json!({ "id": item.id, "action": "accept" }),
+        })
+        .collect();
+    serde_json::to_vec(&serde_json::json!({ "results": results })).unwrap_or_default()
+}
+
+/// `GET /api/albums?assetId=` response: albums containing the asset.
+fn albums_containing(state: &MockState, asset_id: &str) -> Vec<u8> {
+    let albums = state.albums.lock();
+    let matching: Vec<serde_json::Value> = albums
+        .iter()
+        .filter(|a| asset_id.is_empty() || a.asset_ids.iter().any(|id| id == asset_id))
+        .map(album_json)
+        .collect();
+    serde_json::to_vec(&matching).unwrap_or_default()
+}
+
+fn album_json(album: &MockAlbum) -> serde_json::Value {
+    serde_json::json!({
+        "id": album.id,
+        "albumName": album.name,
+        "assetCount": album.asset_ids.len(),
+        "albumThumbnailAssetId": album.thumbnail_asset_id,
+        "ownerId": album.owner_id,
+    })
+}
+
+/// `POST /api/albums`: create an album with the requested assets.
+fn create_album(state: &MockState, body: &[u8]) -> Vec<u8> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Create {
+        album_name: String,
+        #[serde(default)]
+        asset_ids: Vec<String>,
+    }
+    let Ok(create) = serde_json::from_slice::<Create>(body) else {
+        return Vec::new();
+    };
+
+    let mut albums = state.albums.lock();
+    let album = MockAlbum {
+        id: format!("album-{}", albums.len() + 1),
+        name: create.album_name,
+        thumbnail_asset_id: create.asset_ids.first().cloned(),
+        asset_ids: create.asset_ids,
+        ignores_adds: false,
+        owner_id: USER_ID.to_string(),
+    };
+    let json = album_json(&album);
+    albums.push(album);
+    serde_json::to_vec(&json).unwrap_or_default()
+}
+
+/// Whether the API key's user owns the album (or there is no such album).
+fn owns_album(state: &MockState, album_id: &str) -> bool {
+    let albums = state.albums.lock();
+    albums.iter().find(|a| a.id == album_id).is_none_or(|a| a.owner_id == USER_ID)
+}
+
+/// `GET /api/albums/{id}`: the album with its members.
+fn get_album(state: &MockState, album_id: &str) -> Option<Vec<u8>> {
+    let albums = state.albums.lock();
+    let album = albums.iter().find(|a| a.id == album_id)?;
+    let mut json = album_json(album);
+    json["assets"] = album.asset_ids.iter().map(|id| serde_json::json!({ "id": id })).collect();
+    serde_json::to_vec(&json).ok()
+}
+
+/// `GET /api/shared-links`: every shared link, album links with their album
+/// but not its members (as Immich does).
+fn shared_links(state: &MockState) -> Vec<u8> {
+    let albums = state.albums.lock();
+    let links: Vec<serde_json::Value> = state
+        .shared_links
+        .lock()
+        .iter()
+        .map(|link| {
+            let album = link
+                .album_id
+                .as_ref()
+                .and_then(|id| albums.iter().find(|a| &a.id == id))
+                .map(album_json);
+            serde_json::json!({
+                "id": link.id,
+                "type": if link.album_id.is_some() { "ALBUM" } else { "INDIVIDUAL" },
+                "assets": link.asset_ids.iter().map(|id| serde_json::json!({ "id": id })).collect::<Vec<_>>(),
+                "album": album,
+            })
+        })
+        .collect();
+    serde_json::to_vec(&links).unwrap_or_default()
+}
+
+/// `PUT /api/shared-links/{id}/assets`: add the requested IDs to an
+/// individual link, returning per-ID results.
+fn add_to_shared_link(state: &MockState, link_id: &str, body: &[u8]) -> Option<Vec<u8>> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct AssetIds {
+        asset_ids: Vec<String>,
+    }
+    let ids = serde_json::from_slice::<AssetIds>(body).map(|b| b.asset_ids).unwrap_or_default();
+
+    let mut links = state.shared_links.lock();
+    let link = links.iter_mut().find(|l| l.id == link_id && l.album_id.is_none())?;
+
+    let results: Vec<serde_json::Value> = ids
+        .into_iter()
+        .map(|id| {
+            if link.asset_ids.contains(&id) {
+                serde_json::json!({ "assetId": id, "success": false, "error": "duplicate" })
+            } else {
+                link.asset_ids.push(id.clone());
+                serde_json::json!({ "assetId": id, "success": true })
+            }
+        })
+        .collect();
+    serde_json::to_vec(&results).ok()
+}
+
+/// `GET /api/memories`: every memory with its assets.
+fn memories(state: &MockState) -> Vec<u8> {
+    let memories: Vec<serde_json::Value> = state
+        .memories
+        .lock()
+        .iter()
+        .map(|(id, asset_ids)| {
+            serde_json::json!({
+                "id": id,
+                "type": "on_this_day",
+                "assets": asset_ids.iter().map(|id| serde_json::json!({ "id": id })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    serde_json::to_vec(&memories).unwrap_or_default()
+}
+
+/// `PUT /api/memories/{id}/assets`: add the requested IDs, returning per-ID results.
+fn add_to_memory(state: &MockState, memory_id: &str, body: &[u8]) -> Option<Vec<u8>> {
+    let ids = serde_json::from_slice::<Ids>(body).map(|b| b.ids).unwrap_or_default();
+
+    let mut memories = state.memories.lock();
+    let (_, asset_ids) = memories.iter_mut().find(|(id, _)| id == memory_id)?;
+
+    let results: Vec<serde_json::Value> = ids
+        .into_iter()
+        .map(|id| {
+            if asset_ids.contains(&id) {
+                serde_json::json!({ "id": id, "success": false, "error": "duplicate" })
+            } else {
+                asset_ids.push(id.clone());
+                serde_json::json!({ "id": id, "success": true })
+            }
+        })
+        .collect();
+    serde_json::to_vec(&results).ok()
+}
+
+/// Serialize a tag as `TagResponse`.
+fn tag_json(tag: &MockTag) -> serde_json::Value {
+    let name = tag.value.rsplit('/').next().unwrap_or_default();
+    serde_json::json!({ "id": tag.id, "name": name, "value": tag.value })
+}
+
+/// An asset's JSON with the tags attached to it, as `GET /api/assets/{id}`
+/// returns them.
+fn with_tags(state: &MockState, asset_id: &str, json: &[u8]) -> Vec<u8> {
+    let tags = state.tags.lock();
+    let attached: Vec<_> = tags.iter().filter(|t| t.asset_ids.iter().any(|a| a == asset_id)).map(tag_json).collect();
+    match serde_json::from_slice::<serde_json::Value>(json) {
+        Ok(serde_json::Value::Object(mut asset)) if !attached.is_empty() => {
+            asset.insert("tags".to_string(), attached.into());
+            serde_json::to_vec(&asset).unwrap_or_else(|_| json.to_vec())
+        }
+        _ => json.to_vec(),
+    }
+}
+
+/// `GET /api/tags`: every tag.
+fn tags(state: &MockState) -> Vec<u8> {
+    let tags = state.tags.lock();
+    serde_json::to_vec(&tags.iter().map(tag_json).collect::<Vec<_>>()).unwrap_or_default()
+}
+
+/// `GET /api/tags/{id}`: one tag.
+fn get_tag(state: &MockState, tag_id: &str) -> Option<Vec<u8>> {
+    let tags = state.tags.lock();
+    let tag = tags.iter().find(|t| t.id == tag_id)?;
+    serde_json::to_vec(&tag_json(tag)).ok()
+}
+
+/// Serialize a person as `PersonResponse`.
+fn person_json(id: &str, name: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "name": name,
+        "birthDate": null,
+        "thumbnailPath": format!("/thumbs/{}.jpeg", id),
+        "isHidden": false,
+    })
+}
+
+/// `GET /api/people`: every person, on one page.
+fn people(state: &MockState) -> Vec<u8> {
+    let people = state.people.lock();
+    let page: Vec<serde_json::Value> = people.iter().map(|(id, name)| person_json(id, name)).collect();
+    serde_json::to_vec(&serde_json::json!({
+        "total": page.len(),
+        "hidden": 0,
+        "people": page,
+        "hasNextPage": false,
+    }))
+    .unwrap_or_default()
+}
+
+/// `GET /api/people/{id}`: one person.
+fn get_person(state: &MockState, person_id: &str) -> Option<Vec<u8>> {
+    let people = state.people.lock();
+    let (id, name) = people.iter().find(|(id, _)| id == person_id)?;
+    serde_json::to_vec(&person_json(id, name)).ok()
+}
+
+/// Serialize a stack as `StackResponse`, with the assets the mock holds.
+fn stack_json(state: &MockState, id: &str, asset_ids: &[String]) -> serde_json::Value {
+    let updated = state.updated_assets.lock();
+    let assets: Vec<serde_json::Value> = asset_ids
+        .iter()
+        .filter_map(|asset_id| updated.get(asset_id).or_else(|| state.assets.get(asset_id)))
+        .filter_map(|json| serde_json::from_slice(json).ok())
+        .collect();
+    serde_json::json!({ "id": id, "primaryAssetId": asset_ids.first(), "assets": assets })
+}
+
+/// `GET /api/stacks`: every stack, or the one with the given primary asset.
+fn stacks(state: &MockState, primary_asset_id: Option<&str>) -> Vec<u8> {
+    let stacks = state.stacks.lock();
+    let matching: Vec<serde_json::Value> = stacks
+        .iter()
+        .filter(|(_, asset_ids)| primary_asset_id.is_none_or(|p| asset_ids.first().is_some_and(|a| a == p)))
+        .map(|(id, asset_ids)| stack_json(state, id, asset_ids))
+        .collect();
+    serde_json::to_vec(&matching).unwrap_or_default()
+}
+
+/// `GET /api/stacks/{id}`: one stack.
+fn get_stack(state: &MockState, stack_id: &str) -> Option<Vec<u8>> {
+    let stacks = state.stacks.lock();
+    let (id, asset_ids) = stacks.iter().find(|(id, _)| id == stack_id)?;
+    serde_json::to_vec(&stack_json(state, id, asset_ids)).ok()
+}
+
+/// `POST /api/stacks`: stack the requested assets, taking them out of any
+/// other stack.
+fn create_stack(state: &MockState, body: &[u8]) -> Vec<u8> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Create {
+        asset_ids: Vec<String>,
+    }
+    let asset_ids = serde_json::from_slice::<Create>(body).map(|b| b.asset_ids).unwrap_or_default();
+
+    let mut stacks = state.stacks.lock();
+    stacks.retain(|(_, members)| !members.iter().any(|m| asset_ids.contains(m)));
+    let id = format!("stack-{}", uuid::Uuid::new_v4());
+    let json = stack_json(state, &id, &asset_ids);
+    stacks.push((id, asset_ids));
+    serde_json::to_vec(&json).unwrap_or_default()
+}
+
+/// `PUT /api/tags`: create the requested tags that don't exist, returning all of them.
+fn upsert_tags(state: &MockState, body: &[u8]) -> Vec<u8> {
+    #[derive(serde::Deserialize)]
+    struct Upsert {
+        tags: Vec<String>,
+    }
+    let values = serde_json::from_slice::<Upsert>(body).map(|b| b.tags).unwrap_or_default();
+
+    let mut tags = state.tags.lock();
+    let mut upserted = Vec::new();
+    for value in values {
+        if !tags.iter().any(|t| t.value == value) {
+            let id = format!("tag-{}", tags.len() + 1);
+            tags.push(MockTag { id, value: value.clone(), asset_ids: Vec::new() });
+        }
+        upserted.extend(tags.iter().filter(|t| t.value == value).map(tag_json));
+    }
+    serde_json::to_vec(&upserted).unwrap_or_default()
+}
+
+/// `PUT /api/tags/{id}/assets`: attach the tag to the requested IDs, returning per-ID results.
+fn tag_assets(state: &MockState, tag_id: &str, body: &[u8]) -> Option<Vec<u8>> {
+    let ids = serde_json::from_slice::<Ids>(body).map(|b| b.ids).unwrap_or_default();
+
+    let mut tags = state.tags.lock();
+    let tag = tags.iter_mut().find(|t| t.id == tag_id)?;
+
+    let results: Vec<serde_json::Value> = ids
+        .into_iter()
+        .map(|id| {
+            if tag.asset_ids.contains(&id) {
+                serde_json::json!({ "id": id, "success": false, "error": "duplicate" })
+            } else {
+                tag.asset_ids.push(id.clone());
+                serde_json::json!({ "id": id, "success": true })
+            }
+        })
+        .collect();
+    serde_json::to_vec(&results).ok()
+}
+
+/// `POST /api/search/metadata`: every asset (or only those with one of the
+/// requested `tagIds`), in a single page.
+fn search_metadata(state: &MockState, body: &[u8]) -> Vec<u8> {
+    #[derive(serde::Deserialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    struct Search {
+        #[serde(default)]
+        page: Option<usize>,
+        #[serde(default)]
+        tag_ids: Option<Vec<String>>,
+    }
+    let search = serde_json::from_slice::<Search>(body).unwrap_or_default();
+
+    let tagged: Option<HashSet<String>> = search.tag_ids.map(|tag_ids| {
+        let tags = state.tags.lock();
+        tags.iter()
+            .filter(|t| tag_ids.contains(&t.id))
+            .flat_map(|t| t.asset_ids.iter().cloned())
+            .collect()
+    });
+    let deleted = state.deleted.lock();
+
+    let mut ids: Vec<&String> = state
+        .assets
+        .keys()
+        .filter(|id| !deleted.contains(*id) && tagged.as_ref().is_none_or(|t| t.contains(*id)))
+        .collect();
+    ids.sort();
+
+    let items: Vec<serde_json::Value> = if search.page.unwrap_or(1) > 1 {
+        Vec::new()
+    } else {
+        ids.iter()
+            .filter_map(|id| serde_json::from_slice(&state.assets[*id]).ok())
+            .collect()
+    };
+    serde_json::to_vec(&serde_json::json!({ "assets": { "items": items, "nextPage": null } })).unwrap_or_default()
+}
+
+/// `PATCH /api/albums/{id}`: change the cover, which must be in the album.
+fn update_album(state: &MockState, album_id: &str, body: &[u8]) -> Option<std::result::Result<Vec<u8>, Vec<u8>>> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Update {
+        album_thumbnail_asset_id: Option<String>,
+    }
+    let update = serde_json::from_slice::<Update>(body).ok();
+
+    let mut albums = state.albums.lock();
+    let album = albums.iter_mut().find(|a| a.id == album_id)?;
+
+    if let Some(cover) = update.and_then(|u| u.album_thumbnail_asset_id) {
+        if !album.asset_ids.contains(&cover) {
+            return Some(Err(b"{\"message\":\"Invalid album thumbnail\"}".to_vec()));
+        }
+        album.thumbnail_asset_id = Some(cover);
+    }
+    Some(Ok(serde_json::to_vec(&album_json(album)).unwrap_or_default()))
+}
+
+/// `{"ids": [...]}` request body.
+#[derive(serde::Deserialize)]
+struct Ids {
+    ids: Vec<String>,
+}
+
+/// `DELETE /api/assets`: whether the request carries more IDs than allowed.
+fn delete_too_large(state: &MockState, body: &[u8]) -> bool {
+    let ids = serde_json::from_slice::<Ids>(body).map(|b| b.ids).unwrap_or_default();
+    ids.len() > state.max_delete_ids.load(Ordering::Relaxed)
+}
+
+/// `DELETE /api/assets`: record the IDs as removed, except undeletable ones.
+///
+/// Returns false if any ID could not be removed.
+fn delete_assets(state: &MockState, body: &[u8]) -> bool {
+    let ids = serde_json::from_slice::<Ids>(body).map(|b| b.ids).unwrap_or_default();
+    let undeletable = state.undeletable.lock();
+    let (failed, removed): (Vec<String>, Vec<String>) = ids.into_iter().partition(|id| undeletable.contains(id));
+    state.deleted.lock().extend(removed);
+    failed.is_empty()
+}
+
+/// `POST /api/trash/restore/assets`: bring removed IDs back.
+fn restore_assets(state: &MockState, body: &[u8]) {
+    let ids = serde_json::from_slice::<Ids>(body).map(|b| b.ids).unwrap_or_default();
+    let mut deleted = state.deleted.lock();
+    for id in ids {
+        deleted.remove(&id);
+    }
+}
+
+/// `PUT /api/albums/{id}/assets`: add the requested IDs, returning per-ID results.
+fn add_to_album(state: &MockState, album_id: &str, body: &[u8]) -> Option<Vec<u8>> {
+    let ids = serde_json::from_slice::<Ids>(body).map(|b| b.ids).unwrap_or_default();
+
+    let mut albums = state.albums.lock();
+    let album = albums.iter_mut().find(|a| a.id == album_id)?;
+
+    let results: Vec<serde_json::Value> = ids
+        .into_iter()
+        .map(|id| {
+            if album.asset_ids.contains(&id) {
+                serde_json::json!({ "id": id, "success": false, "error": "duplicate" })
+            } else {
+                if !album.ignores_adds {
+                    album.asset_ids.push(id.clone());
+                }
+                serde_json::json!({ "id": id, "success": true })
+            }
+        })
+        .collect();
+    serde_json::to_vec(&results).ok()
+}
+
+/// `DELETE /api/albums/{id}/assets`: remove the requested IDs, returning per-ID results.
+fn remove_from_album(state: &MockState, album_id: &str, body: &[u8]) -> Option<Vec<u8>> {
+    let ids = serde_json::from_slice::<Ids>(body).map(|b| b.ids).unwrap_or_default();
+
+    let mut albums = state.albums.lock();
+    let album = albums.iter_mut().find(|a| a.id == album_id)?;
+
+    let results: Vec<serde_json::Value> = ids
+        .into_iter()
+        .map(|id| match album.asset_ids.iter().position(|a| *a == id) {
+            Some(index) => {
+                album.asset_ids.remove(index);
+                serde_json::json!({ "id": id, "success": true })
+            }
+            None => serde_json::json!({ "id": id, "success": false, "error": "not_found" }),
+        })
+        .collect();
+    serde_json::to_vec(&results).ok()
+}
+
+/// Apply a `PUT /api/assets/{id}` body to the asset's EXIF info, skipping
+/// ignored fields, and return the updated asset.
+fn update_asset(state: &MockState, asset_id: &str, body: &[u8]) -> Option<Vec<u8>> {
+    let changes: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(body).ok()?;
+    apply_asset_update(state, asset_id, &changes)
+}
+
+/// `PUT /api/assets`: apply the same changes to every asset in `ids`.
+fn update_assets_bulk(state: &MockState, body: &[u8]) -> Option<()> {
+    let mut changes: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(body).ok()?;
+    let ids: Vec<String> = serde_json::from_value(changes.remove("ids")?).ok()?;
+    for id in ids {
+        apply_asset_update(state, &id, &changes)?;
+    }
+    Some(())
+}
+
+fn apply_asset_update(
+    state: &MockState,
+    asset_id: &str,
+    changes: &serde_json::Map<String, serde_json::Value>,
+) -> Option<Vec<u8>> {
+    let mut updated = state.updated_assets.lock();
+    let current = updated.get(asset_id).or_else(|| state.assets.get(asset_id))?;
+    let mut asset: serde_json::Value = serde_json::from_slice(current).ok()?;
+
+    let ignored = state.ignored_update_fields.lock();
+    if !asset["exifInfo"].is_object() {
+        asset["exifInfo"] = serde_json::json!({});
+    }
+    for (field, value) in changes.iter().filter(|(field, _)| !ignored.contains(*field)) {
+        asset["exifInfo"][field] = value.clone();
+    }
+
+    let json = serde_json::to_vec(&asset).ok()?;
+    updated.insert(asset_id.to_string(), json.clone());
+    Some(json)
+}
+
+/// Generate synthetic duplicate groups for benchmarking.
+///
+/// Each group has `assets_per_group` JPEG assets (at least 2) with varying
+/// dimensions, file sizes, and partial GPS so scoring and consolidation do
+/// representative work.
+///
+/// # Errors
+///
+/// Returns an error if an asset's capture time can't be built.
+pub fn synthetic_groups(count: usize, assets_per_group: usize) -> Result<Vec<DuplicateGroup>> {
+    let assets_per_group = assets_per_group.max(2);
+
+    (0..count)
+        .map(|g| {
+            Ok(DuplicateGroup {
+                duplicate_id: format!("dup-{:06}", g),
+                assets: (0..assets_per_group)
+                    .map(|a| synthetic_asset(g, a))
+                    .collect::<Result<_>>()?,
+                extra: Default::default(),
+            })
+        })
+        .collect()
+}
+
+/// Build one synthetic asset.
+fn synthetic_asset(group: usize, index: usize) -> Result<AssetResponse> {
+    let captured = Utc
+        .with_ymd_and_hms(2024, 6, 15, 10, 0, 0)
+        .single()
+        .ok_or_else(|| ImmichError::InvalidScenario("synthetic capture time out of range".to_string()))?;
+    let width = 4032u32.saturating_sub(index as u32 * 800).max(640);
+    let height = 3024u32.saturating_sub(index as u32 * 600).max(480);
+
+    let asset = AssetBuilder::new(format!("asset-{:06}-{}", group, index))
+        .with_filename(format!("IMG_{:06}_{}.jpg", group, index))
+        .with_checksum(format!("checksum-{}-{}", group, index))
+        .with_owner("bench-owner")
+        .with_camera("Apple", "iPhone 15 Pro")
+        .with_dimensions(width, height)
+        .with_file_size(FAKE_ORIGINAL_SIZE as u64 * (4 - index as u64).max(1))
+        .with_capture_time(captured)
+        .with_timezone("Europe/London");
+
+    // Only the second copy (a loser) has GPS, so consolidation has something to transfer
+    Ok(if index == 1 {
+        asset.with_gps(51.5 + (group % 100) as f64 * 0.001, -0.12).build()
+    } else {
+        asset.build()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ImmichClient;
+
+    #[tokio::test]
+    async fn test_mock_server_serves_duplicates_and_assets() {
+        let groups = synthetic_groups(3, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "bench-key").unwrap();
+
+        let fetched = client.get_duplicates().await.unwrap();
+        assert_eq!(fetched.len(), 3);
+
+        let asset = client.get_asset("asset-000001-1").await.unwrap();
+        assert!(asset.exif_info.unwrap().has_gps());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.jpg");
+        let bytes = client.download_asset("asset-000001-1", &path).await.unwrap();
+        assert_eq!(bytes, FAKE_ORIGINAL_SIZE as u64);
+
+        assert!(client.get_asset("missing").await.is_err());
+    }
+}
@@ -3,14 +3,28 @@
 //! This module provides functionality to analyze duplicate groups
 //! and categorize them by test scenario for validation purposes.
 
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+pub mod bench;
+pub mod builder;
 pub mod detector;
+#[cfg(feature = "docker")]
+pub mod docker;
 pub mod fixtures;
 pub mod generator;
+pub mod mock_server;
 pub mod report;
 pub mod scenarios;
+pub mod seed;
 
-pub use detector::detect_scenarios;
-pub use fixtures::{all_fixtures, ScenarioFixture};
+pub use bench::{run_bench, BenchOptions, BenchReport};
+pub use builder::{AssetBuilder, GroupBuilder};
+pub use detector::{detect_scenarios, detect_scenarios_with, ScenarioContext};
+#[cfg(feature = "docker")]
+pub use docker::{HarnessOptions, TestHarness};
+pub use fixtures::{all_fixtures, FixtureManifest, ScenarioFixture};
 pub use generator::{generate_image, ExifSpec, TestImage, TransformSpec};
-pub use report::{format_report, ScenarioReport};
-pub use scenarios::{ScenarioMatch, TestScenario};
+pub use mock_server::{synthetic_groups, MockServer};
+pub use report::{format_report, CategoryCoverage, ScenarioReport, SeedSuggestion};
+pub use scenarios::{ScenarioDef, ScenarioMatch, ScenarioRegistry, Severity, TestScenario};
+pub use seed::{map_duplicate_groups, upload_scenario, wait_for_duplicates, SeededAsset, SeededScenario};
@@ -3,7 +3,7 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
-use super::scenarios::{ScenarioMatch, TestScenario};
+use super::scenarios::{ScenarioMatch, ScenarioRegistry, Severity, TestScenario};
 
 /// Test scenario coverage report.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,16 +14,66 @@ pub struct ScenarioReport {
     /// Matches grouped by scenario
     pub coverage: HashMap<String, Vec<ScenarioMatch>>,
 
-    /// Scenarios with no matches
+    /// Scenarios with no matches, most severe first
     pub uncovered: Vec<String>,
 
+    /// Every scenario checked, in registry order
+    #[serde(default)]
+    pub scenarios: Vec<String>,
+
     /// Unexpected patterns discovered
     pub unexpected: Vec<String>,
+
+    /// Coverage of each category, in registry order
+    #[serde(default)]
+    pub categories: Vec<CategoryCoverage>,
+
+    /// Fixtures to seed for the uncovered scenarios that have one, most
+    /// severe first
+    #[serde(default)]
+    pub seed_suggestions: Vec<SeedSuggestion>,
+}
+
+/// How many of a category's scenarios have matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryCoverage {
+    /// Category name (e.g. "Winner Selection")
+    pub category: String,
+
+    /// Scenarios in the category with at least one match
+    pub covered: usize,
+
+    /// Scenarios in the category
+    pub total: usize,
+
+    /// `covered` as a percentage of `total`
+    pub percent: f64,
+}
+
+/// A synthetic fixture that would cover a scenario the library lacks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedSuggestion {
+    /// Scenario code, as accepted by `seed-fixtures --scenario` (e.g. "X5")
+    pub scenario: String,
+
+    /// Scenario display name
+    pub name: String,
+
+    /// Scenario severity
+    pub severity: Severity,
+
+    /// What the fixture reproduces
+    pub description: String,
 }
 
 impl ScenarioReport {
-    /// Create a new report from scenario matches.
+    /// Create a new report from matches of the built-in scenarios.
     pub fn from_matches(matches: Vec<ScenarioMatch>, total_groups: usize) -> Self {
+        Self::from_registry(&ScenarioRegistry::builtin(), matches, total_groups)
+    }
+
+    /// Create a new report from matches of the scenarios in `registry`.
+    pub fn from_registry(registry: &ScenarioRegistry, matches: Vec<ScenarioMatch>, total_groups: usize) -> Self {
         let mut coverage: HashMap<String, Vec<ScenarioMatch>> = HashMap::new();
 
         // Group matches by scenario
@@ -32,19 +82,64 @@ impl ScenarioReport {
             coverage.entry(key).or_default().push(m);
         }
 
-        // Find uncovered scenarios
-        let all_scenarios = TestScenario::all();
-        let uncovered: Vec<String> = all_scenarios
+        let scenarios: Vec<String> = registry.scenarios().iter().map(|s| s.to_string()).collect();
+
+        // Find uncovered scenarios, most severe first
+        let mut missing: Vec<&TestScenario> = registry
+            .scenarios()
             .iter()
             .filter(|s| !coverage.contains_key(&s.to_string()))
-            .map(|s| s.to_string())
+            .collect();
+        missing.sort_by_key(|s| std::cmp::Reverse(s.severity()));
+        let uncovered: Vec<String> = missing.iter().map(|s| s.to_string()).collect();
+
+        // Per-category coverage, categories in order of first appearance
+        let mut categories: Vec<CategoryCoverage> = Vec::new();
+        for scenario in registry.scenarios() {
+            let index = match categories.iter().position(|c| c.category == scenario.category()) {
+                Some(index) => index,
+                None => {
+                    categories.push(CategoryCoverage {
+                        category: scenario.category().to_string(),
+                        covered: 0,
+                        total: 0,
+                        percent: 0.0,
+                    });
+                    categories.len() - 1
+                }
+            };
+            let category = &mut categories[index];
+            category.total += 1;
+            if coverage.contains_key(&scenario.to_string()) {
+                category.covered += 1;
+            }
+        }
+        for category in &mut categories {
+            category.percent = category.covered as f64 / category.total as f64 * 100.0;
+        }
+
+        // Fixtures that would fill the gaps
+        let seed_suggestions = missing
+            .iter()
+            .filter_map(|s| {
+                let fixture = (s.def().fixture?)();
+                Some(SeedSuggestion {
+                    scenario: s.code().to_uppercase(),
+                    name: s.to_string(),
+                    severity: s.severity(),
+                    description: fixture.description,
+                })
+            })
             .collect();
 
         Self {
             total_groups,
             coverage,
             uncovered,
+            scenarios,
             unexpected: Vec::new(),
+            categories,
+            seed_suggestions,
         }
     }
 
@@ -61,9 +156,9 @@ pub fn format_report(report: &ScenarioReport) -> String {
     output.push_str("=== Test Scenario Coverage Report ===\n\n");
 
     // Coverage statistics
-    let total_scenarios = TestScenario::all().len();
+    let total_scenarios = report.scenarios.len().max(report.coverage.len() + report.uncovered.len());
     let covered_count = report.coverage.len();
-    let coverage_pct = (covered_count as f64 / total_scenarios as f64) * 100.0;
+    let coverage_pct = (covered_count as f64 / total_scenarios.max(1) as f64) * 100.0;
 
     // Covered scenarios by category
     output.push_str(&format!(
@@ -71,39 +166,62 @@ pub fn format_report(report: &ScenarioReport) -> String {
         covered_count, total_scenarios, coverage_pct
     ));
 
-    // Group by category
-    let categories = ["Winner Selection", "Consolidation", "Conflicts", "Edge Cases"];
+    // Registry order, then any scenarios the report didn't list
+    let mut covered: Vec<(&String, &Vec<ScenarioMatch>)> = report
+        .scenarios
+        .iter()
+        .filter_map(|name| report.coverage.get_key_value(name))
+        .collect();
+    let mut unlisted: Vec<_> = report
+        .coverage
+        .iter()
+        .filter(|(name, _)| !report.scenarios.contains(name))
+        .collect();
+    unlisted.sort_by_key(|(name, _)| name.as_str());
+    covered.extend(unlisted);
+
+    // Most severe first, so the riskiest scenarios (and their categories) lead
+    covered.sort_by_key(|(_, matches)| std::cmp::Reverse(matches.first().map(|m| m.scenario.severity())));
+
+    // Group by category, in order of first appearance
+    let mut categories: Vec<&str> = Vec::new();
+    for (_, matches) in &covered {
+        if let Some(first) = matches.first()
+            && !categories.contains(&first.scenario.category())
+        {
+            categories.push(first.scenario.category());
+        }
+    }
+
     for category in categories {
-        let category_scenarios: Vec<(&String, &Vec<ScenarioMatch>)> = report
-            .coverage
+        output.push_str(&format!("\n  {}:\n", category));
+        for (scenario, matches) in covered
             .iter()
-            .filter(|(k, _)| {
-                let prefix = k.chars().next().unwrap_or('?');
-                match category {
-                    "Winner Selection" => prefix == 'W',
-                    "Consolidation" => prefix == 'C',
-                    "Conflicts" => prefix == 'F',
-                    "Edge Cases" => prefix == 'X',
-                    _ => false,
-                }
-            })
-            .collect();
-
-        if !category_scenarios.is_empty() {
-            output.push_str(&format!("\n  {}:\n", category));
-            for (scenario, matches) in category_scenarios {
-                output.push_str(&format!("    {}: {} groups\n", scenario, matches.len()));
-                // Show first example
-                if let Some(first) = matches.first() {
-                    output.push_str(&format!(
-                        "      Example: {} ({})\n",
-                        first.duplicate_id, first.details
-                    ));
-                }
+            .filter(|(_, m)| m.first().is_some_and(|m| m.scenario.category() == category))
+        {
+            let severity = matches.first().map(|m| m.scenario.severity().to_string()).unwrap_or_default();
+            output.push_str(&format!("    {} [{}]: {} groups\n", scenario, severity, matches.len()));
+            // Show first example
+            if let Some(first) = matches.first() {
+                output.push_str(&format!(
+                    "      Example: {} ({})\n",
+                    first.duplicate_id, first.details
+                ));
             }
         }
     }
 
+    // Per-category percentages
+    if !report.categories.is_empty() {
+        output.push_str("\nBY CATEGORY:\n");
+        for category in &report.categories {
+            output.push_str(&format!(
+                "  {}: {}/{} ({:.0}%)\n",
+                category.category, category.covered, category.total, category.percent
+            ));
+        }
+    }
+
     // Uncovered scenarios
     if !report.uncovered.is_empty() {
         output.push_str(&format!(
@@ -115,6 +233,21 @@ pub fn format_report(report: &ScenarioReport) -> String {
         }
     }
 
+    // Fixtures to seed for full coverage
+    if !report.seed_suggestions.is_empty() {
+        output.push_str(&format!(
+            "\nSUGGESTED FIXTURES ({} to seed for full coverage):\n",
+            report.seed_suggestions.len()
+        ));
+        for suggestion in &report.seed_suggestions {
+            output.push_str(&format!(
+                "  {} [{}]: {}\n",
+                suggestion.scenario, suggestion.severity, suggestion.description
+            ));
+        }
+        output.push_str("  Seed with: immich-dupes seed-fixtures --scenario <code>\n");
+    }
+
     // Unexpected patterns
     if !report.unexpected.is_empty() {
         output.push_str("\nUNEXPECTED PATTERNS:\n");
@@ -137,3 +270,63 @@ pub fn format_report(report: &ScenarioReport) -> String {
 
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report_io::to_stable_json;
+    use crate::testing::scenarios::TestScenario;
+    use crate::testing::synthetic_groups;
+
+    /// A report over a fixed set of scenarios, so the built-in registry's
+    /// feature-dependent scenarios don't change the output.
+    fn sample_report() -> ScenarioReport {
+        let mut registry = ScenarioRegistry::default();
+        for scenario in [
+            TestScenario::W1ClearDimensionWinner,
+            TestScenario::W2SameDimensionsDifferentSize,
+            TestScenario::W7ThreePlusDuplicates,
+            TestScenario::C1WinnerLacksGpsLoserHas,
+            TestScenario::F1GpsConflict,
+            TestScenario::F7NoConflicts,
+            TestScenario::X5Video,
+        ] {
+            registry.register(scenario).unwrap();
+        }
+        let groups = synthetic_groups(3, 3).unwrap();
+        let matches = groups.iter().flat_map(|g| registry.detect(g)).collect();
+        let mut report = ScenarioReport::from_registry(&registry, matches, groups.len());
+        report.add_unexpected("winner smaller than a loser".to_string());
+        report
+    }
+
+    #[test]
+    fn test_category_coverage_and_seed_suggestions() {
+        let report = sample_report();
+        let coverage: Vec<(&str, usize, usize)> = report
+            .categories
+            .iter()
+            .map(|c| (c.category.as_str(), c.covered, c.total))
+            .collect();
+        assert_eq!(
+            coverage,
+            [("Winner Selection", 2, 3), ("Consolidation", 1, 1), ("Conflicts", 1, 2), ("Edge Cases", 0, 1)]
+        );
+        assert_eq!(report.categories[2].percent, 50.0);
+
+        // Most severe gaps first
+        let suggested: Vec<&str> = report.seed_suggestions.iter().map(|s| s.scenario.as_str()).collect();
+        assert_eq!(suggested, ["F1", "W2", "X5"]);
+        assert_eq!(report.uncovered[0], "F1: GPS conflict");
+    }
+
+    #[test]
+    fn test_text_report_snapshot() {
+        insta::assert_snapshot!(format_report(&sample_report()));
+    }
+
+    #[test]
+    fn test_json_report_snapshot() {
+        insta::assert_snapshot!(to_stable_json(&sample_report()).unwrap());
+    }
+}
@@ -1,244 +1,411 @@
-//! Test scenario types for duplicate group categorization.
+//! Test scenario definitions and the registry they are detected from.
+//!
+//! Each scenario is a [`ScenarioDef`]: a code, display name, category,
+//! severity, a matcher that recognizes it in a duplicate group, and optionally a fixture
+//! that reproduces it. A built-in scenario is one `TestScenario` constant
+//! plus an entry in [`ScenarioRegistry::builtin`]; other crates can define
+//! their own `ScenarioDef`s and [`ScenarioRegistry::register`] them.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
-/// Test scenarios for categorizing duplicate groups.
+use crate::error::{ImmichError, Result};
+use crate::intern::SharedStr;
+use crate::models::DuplicateGroup;
+use crate::scoring::{Thresholds, WinnerStrategy};
+
+use super::detector::{self, ScenarioContext};
+use super::fixtures::{self, ScenarioFixture};
+
+const WINNER_SELECTION: &str = "Winner Selection";
+const CONSOLIDATION: &str = "Consolidation";
+const CONFLICTS: &str = "Conflicts";
+const EDGE_CASES: &str = "Edge Cases";
+
+/// How much is at stake if a scenario is mishandled, so the riskiest
+/// scenarios can be tested first.
 ///
-/// Each scenario represents a specific test case that needs coverage
-/// in the integration test suite.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum TestScenario {
+/// Ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Edge cases and groups where nothing needs deciding
+    Low,
+    /// The winner is a close call, or metadata must be moved to it
+    Medium,
+    /// Copies disagree, so metadata can be silently lost or wrong
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        })
+    }
+}
+
+/// Definition of a test scenario.
+#[derive(Debug, Clone, Copy)]
+pub struct ScenarioDef {
+    /// Short code, unique within a registry (e.g. `w1`); also the fixture directory name
+    pub code: &'static str,
+
+    /// Display name (e.g. `W1: Clear dimension winner`)
+    pub name: &'static str,
+
+    /// Category the scenario is reported under
+    pub category: &'static str,
+
+    /// How much is at stake if the scenario is mishandled
+    pub severity: Severity,
+
+    /// Returns one detail string per match in the group; empty if the scenario doesn't apply
+    pub matcher: fn(&ScenarioContext<'_>) -> Vec<String>,
+
+    /// Builds a fixture that reproduces the scenario, if there is one
+    pub fixture: Option<fn() -> ScenarioFixture>,
+}
+
+/// A test scenario: a handle to its [`ScenarioDef`].
+///
+/// Scenarios compare, hash, and serialize by code.
+#[derive(Clone, Copy)]
+pub struct TestScenario(&'static ScenarioDef);
+
+const fn builtin(
+    code: &'static str,
+    name: &'static str,
+    category: &'static str,
+    severity: Severity,
+    matcher: fn(&ScenarioContext<'_>) -> Vec<String>,
+    fixture: fn() -> ScenarioFixture,
+) -> ScenarioDef {
+    ScenarioDef {
+        code,
+        name,
+        category,
+        severity,
+        matcher,
+        fixture: Some(fixture),
+    }
+}
+
+// Named like the enum variants they replaced, so existing matches keep compiling
+#[allow(non_upper_case_globals)]
+impl TestScenario {
     // Winner selection scenarios (W)
     /// Clear dimension winner (different width x height)
-    W1ClearDimensionWinner,
+    pub const W1ClearDimensionWinner: Self = Self(&builtin("w1", "W1: Clear dimension winner", WINNER_SELECTION, Severity::Low, detector::w1_clear_dimension_winner, fixtures::w1_clear_dimension_winner));
     /// Same dimensions, different file size
-    W2SameDimensionsDifferentSize,
+    pub const W2SameDimensionsDifferentSize: Self = Self(&builtin("w2", "W2: Same dimensions, different size", WINNER_SELECTION, Severity::Medium, detector::w2_same_dimensions_different_size, fixtures::w2_same_dimensions_different_size));
     /// Same dimensions, same file size
-    W3SameDimensionsSameSize,
+    pub const W3SameDimensionsSameSize: Self = Self(&builtin("w3", "W3: Same dimensions, same size", WINNER_SELECTION, Severity::Medium, detector::w3_same_dimensions_same_size, fixtures::w3_same_dimensions_same_size));
     /// Some assets missing dimensions
-    W4SomeMissingDimensions,
+    pub const W4SomeMissingDimensions: Self = Self(&builtin("w4", "W4: Some missing dimensions", WINNER_SELECTION, Severity::Medium, detector::w4_some_missing_dimensions, fixtures::w4_some_missing_dimensions));
     /// Only one asset has dimensions
-    W5OnlyOneHasDimensions,
+    pub const W5OnlyOneHasDimensions: Self = Self(&builtin("w5", "W5: Only one has dimensions", WINNER_SELECTION, Severity::Medium, detector::w5_only_one_has_dimensions, fixtures::w5_only_one_has_dimensions));
     /// All assets missing dimensions
-    W6AllMissingDimensions,
+    pub const W6AllMissingDimensions: Self = Self(&builtin("w6", "W6: All missing dimensions", WINNER_SELECTION, Severity::Medium, detector::w6_all_missing_dimensions, fixtures::w6_all_missing_dimensions));
     /// 3+ assets in group
-    W7ThreePlusDuplicates,
+    pub const W7ThreePlusDuplicates: Self = Self(&builtin("w7", "W7: 3+ duplicates", WINNER_SELECTION, Severity::Low, detector::w7_three_plus_duplicates, fixtures::w7_three_plus_duplicates));
     /// Same pixel count, different aspect ratio
-    W8SamePixelsDifferentAspect,
+    pub const W8SamePixelsDifferentAspect: Self = Self(&builtin("w8", "W8: Same pixels, different aspect", WINNER_SELECTION, Severity::Medium, detector::w8_same_pixels_different_aspect, fixtures::w8_same_pixels_different_aspect));
 
     // Consolidation scenarios (C)
     /// Winner lacks GPS, loser has GPS
-    C1WinnerLacksGpsLoserHas,
+    pub const C1WinnerLacksGpsLoserHas: Self = Self(&builtin("c1", "C1: Winner lacks GPS, loser has", CONSOLIDATION, Severity::Medium, detector::c1_winner_lacks_gps_loser_has, fixtures::c1_winner_lacks_gps_loser_has));
     /// Winner lacks datetime, loser has datetime
-    C2WinnerLacksDatetimeLoserHas,
+    pub const C2WinnerLacksDatetimeLoserHas: Self = Self(&builtin("c2", "C2: Winner lacks datetime, loser has", CONSOLIDATION, Severity::Medium, detector::c2_winner_lacks_datetime_loser_has, fixtures::c2_winner_lacks_datetime_loser_has));
     /// Winner lacks description, loser has description
-    C3WinnerLacksDescriptionLoserHas,
+    pub const C3WinnerLacksDescriptionLoserHas: Self = Self(&builtin("c3", "C3: Winner lacks description, loser has", CONSOLIDATION, Severity::Medium, detector::c3_winner_lacks_description_loser_has, fixtures::c3_winner_lacks_description_loser_has));
     /// Winner lacks all three, loser has all
-    C4WinnerLacksAllLoserHasAll,
+    pub const C4WinnerLacksAllLoserHasAll: Self = Self(&builtin("c4", "C4: Winner lacks all, loser has all", CONSOLIDATION, Severity::Medium, detector::c4_winner_lacks_all_loser_has_all, fixtures::c4_winner_lacks_all_loser_has_all));
     /// Both have GPS (no consolidation needed)
-    C5BothHaveGps,
+    pub const C5BothHaveGps: Self = Self(&builtin("c5", "C5: Both have GPS", CONSOLIDATION, Severity::Low, detector::c5_both_have_gps, fixtures::c5_both_have_gps));
     /// Multiple losers contribute different fields
-    C6MultipleLosersContribute,
+    pub const C6MultipleLosersContribute: Self = Self(&builtin("c6", "C6: Multiple losers contribute", CONSOLIDATION, Severity::Medium, detector::c6_multiple_losers_contribute, fixtures::c6_multiple_losers_contribute));
     /// No loser has what winner lacks
-    C7NoLoserHasNeeded,
+    pub const C7NoLoserHasNeeded: Self = Self(&builtin("c7", "C7: No loser has needed", CONSOLIDATION, Severity::Low, detector::c7_no_loser_has_needed, fixtures::c7_no_loser_has_needed));
     /// Winner already has everything
-    C8WinnerHasEverything,
+    pub const C8WinnerHasEverything: Self = Self(&builtin("c8", "C8: Winner has everything", CONSOLIDATION, Severity::Low, detector::c8_winner_has_everything, fixtures::c8_winner_has_everything));
 
     // Conflict scenarios (F)
     /// GPS conflict (different locations)
-    F1GpsConflict,
+    pub const F1GpsConflict: Self = Self(&builtin("f1", "F1: GPS conflict", CONFLICTS, Severity::High, detector::f1_gps_conflict, fixtures::f1_gps_conflict));
     /// GPS within threshold (should NOT conflict)
-    F2GpsWithinThreshold,
+    pub const F2GpsWithinThreshold: Self = Self(&builtin("f2", "F2: GPS within threshold", CONFLICTS, Severity::Medium, detector::f2_gps_within_threshold, fixtures::f2_gps_within_threshold));
     /// Timezone conflict
-    F3TimezoneConflict,
+    pub const F3TimezoneConflict: Self = Self(&builtin("f3", "F3: Timezone conflict", CONFLICTS, Severity::High, detector::f3_timezone_conflict, fixtures::f3_timezone_conflict));
     /// Camera info conflict
-    F4CameraConflict,
+    pub const F4CameraConflict: Self = Self(&builtin("f4", "F4: Camera conflict", CONFLICTS, Severity::High, detector::f4_camera_conflict, fixtures::f4_camera_conflict));
     /// Capture time conflict
-    F5CaptureTimeConflict,
+    pub const F5CaptureTimeConflict: Self = Self(&builtin("f5", "F5: Capture time conflict", CONFLICTS, Severity::High, detector::f5_capture_time_conflict, fixtures::f5_capture_time_conflict));
     /// Multiple conflicts
-    F6MultipleConflicts,
+    pub const F6MultipleConflicts: Self = Self(&builtin("f6", "F6: Multiple conflicts", CONFLICTS, Severity::High, detector::f6_multiple_conflicts, fixtures::f6_multiple_conflicts));
     /// No conflicts
-    F7NoConflicts,
+    pub const F7NoConflicts: Self = Self(&builtin("f7", "F7: No conflicts", CONFLICTS, Severity::Low, detector::f7_no_conflicts, fixtures::f7_no_conflicts));
 
     // Edge case scenarios (X)
     /// Single asset "group"
-    X1SingleAssetGroup,
+    pub const X1SingleAssetGroup: Self = Self(&builtin("x1", "X1: Single asset group", EDGE_CASES, Severity::Low, detector::x1_single_asset_group, fixtures::x1_single_asset_group));
     /// Large group (10+ duplicates)
-    X2LargeGroup,
+    pub const X2LargeGroup: Self = Self(&builtin("x2", "X2: Large group (10+)", EDGE_CASES, Severity::Low, detector::x2_large_group, fixtures::x2_large_group));
     /// Large file (>50MB)
-    X3LargeFile,
+    pub const X3LargeFile: Self = Self(&builtin("x3", "X3: Large file (>50MB)", EDGE_CASES, Severity::Low, detector::x3_large_file, fixtures::x3_large_file));
     /// Special characters in filename
-    X4SpecialCharsFilename,
+    pub const X4SpecialCharsFilename: Self = Self(&builtin("x4", "X4: Special chars in filename", EDGE_CASES, Severity::Low, detector::x4_special_chars_filename, fixtures::x4_special_chars_filename));
     /// Video duplicates
-    X5Video,
+    pub const X5Video: Self = Self(&builtin("x5", "X5: Video", EDGE_CASES, Severity::Low, detector::x5_video, fixtures::x5_video));
+    /// HEIC files (generated with the `heic` feature)
+    pub const X6Heic: Self = Self(&builtin("x6", "X6: HEIC", EDGE_CASES, Severity::Low, detector::x6_heic, fixtures::x6_heic));
     /// PNG files (limited EXIF)
-    X7Png,
+    pub const X7Png: Self = Self(&builtin("x7", "X7: PNG", EDGE_CASES, Severity::Low, detector::x7_png, fixtures::x7_png));
     /// Unicode in description
-    X9UnicodeDescription,
+    pub const X9UnicodeDescription: Self = Self(&builtin("x9", "X9: Unicode description", EDGE_CASES, Severity::Low, detector::x9_unicode_description, fixtures::x9_unicode_description));
     /// Very old date (<1990)
-    X10VeryOldDate,
+    pub const X10VeryOldDate: Self = Self(&builtin("x10", "X10: Very old date (<1990)", EDGE_CASES, Severity::Low, detector::x10_very_old_date, fixtures::x10_very_old_date));
     /// Future date
-    X11FutureDate,
+    pub const X11FutureDate: Self = Self(&builtin("x11", "X11: Future date", EDGE_CASES, Severity::Low, detector::x11_future_date, fixtures::x11_future_date));
 }
 
 impl TestScenario {
-    /// Returns all test scenarios.
+    /// Wrap a scenario definition (e.g. a custom scenario from another crate).
+    pub const fn new(def: &'static ScenarioDef) -> Self {
+        Self(def)
+    }
+
+    /// Returns all built-in test scenarios.
     pub fn all() -> Vec<TestScenario> {
-        vec![
-            // Winner selection
-            Self::W1ClearDimensionWinner,
-            Self::W2SameDimensionsDifferentSize,
-            Self::W3SameDimensionsSameSize,
-            Self::W4SomeMissingDimensions,
-            Self::W5OnlyOneHasDimensions,
-            Self::W6AllMissingDimensions,
-            Self::W7ThreePlusDuplicates,
-            Self::W8SamePixelsDifferentAspect,
-            // Consolidation
-            Self::C1WinnerLacksGpsLoserHas,
-            Self::C2WinnerLacksDatetimeLoserHas,
-            Self::C3WinnerLacksDescriptionLoserHas,
-            Self::C4WinnerLacksAllLoserHasAll,
-            Self::C5BothHaveGps,
-            Self::C6MultipleLosersContribute,
-            Self::C7NoLoserHasNeeded,
-            Self::C8WinnerHasEverything,
-            // Conflicts
-            Self::F1GpsConflict,
-            Self::F2GpsWithinThreshold,
-            Self::F3TimezoneConflict,
-            Self::F4CameraConflict,
-            Self::F5CaptureTimeConflict,
-            Self::F6MultipleConflicts,
-            Self::F7NoConflicts,
-            // Edge cases
-            Self::X1SingleAssetGroup,
-            Self::X2LargeGroup,
-            Self::X3LargeFile,
-            Self::X4SpecialCharsFilename,
-            Self::X5Video,
-            Self::X7Png,
-            Self::X9UnicodeDescription,
-            Self::X10VeryOldDate,
-            Self::X11FutureDate,
-        ]
+        ScenarioRegistry::builtin().scenarios().to_vec()
+    }
+
+    /// The scenario's definition.
+    pub fn def(&self) -> &'static ScenarioDef {
+        self.0
     }
 
     /// Returns the short code (e.g., "w1", "c2", "f3", "x5").
     pub fn code(&self) -> &'static str {
-        match self {
-            Self::W1ClearDimensionWinner => "w1",
-            Self::W2SameDimensionsDifferentSize => "w2",
-            Self::W3SameDimensionsSameSize => "w3",
-            Self::W4SomeMissingDimensions => "w4",
-            Self::W5OnlyOneHasDimensions => "w5",
-            Self::W6AllMissingDimensions => "w6",
-            Self::W7ThreePlusDuplicates => "w7",
-            Self::W8SamePixelsDifferentAspect => "w8",
-            Self::C1WinnerLacksGpsLoserHas => "c1",
-            Self::C2WinnerLacksDatetimeLoserHas => "c2",
-            Self::C3WinnerLacksDescriptionLoserHas => "c3",
-            Self::C4WinnerLacksAllLoserHasAll => "c4",
-            Self::C5BothHaveGps => "c5",
-            Self::C6MultipleLosersContribute => "c6",
-            Self::C7NoLoserHasNeeded => "c7",
-            Self::C8WinnerHasEverything => "c8",
-            Self::F1GpsConflict => "f1",
-            Self::F2GpsWithinThreshold => "f2",
-            Self::F3TimezoneConflict => "f3",
-            Self::F4CameraConflict => "f4",
-            Self::F5CaptureTimeConflict => "f5",
-            Self::F6MultipleConflicts => "f6",
-            Self::F7NoConflicts => "f7",
-            Self::X1SingleAssetGroup => "x1",
-            Self::X2LargeGroup => "x2",
-            Self::X3LargeFile => "x3",
-            Self::X4SpecialCharsFilename => "x4",
-            Self::X5Video => "x5",
-            Self::X7Png => "x7",
-            Self::X9UnicodeDescription => "x9",
-            Self::X10VeryOldDate => "x10",
-            Self::X11FutureDate => "x11",
-        }
+        self.0.code
     }
 
-    /// Returns the category prefix (W, C, F, or X).
+    /// Returns the category (e.g., "Winner Selection").
     pub fn category(&self) -> &'static str {
-        match self {
-            Self::W1ClearDimensionWinner
-            | Self::W2SameDimensionsDifferentSize
-            | Self::W3SameDimensionsSameSize
-            | Self::W4SomeMissingDimensions
-            | Self::W5OnlyOneHasDimensions
-            | Self::W6AllMissingDimensions
-            | Self::W7ThreePlusDuplicates
-            | Self::W8SamePixelsDifferentAspect => "Winner Selection",
-            Self::C1WinnerLacksGpsLoserHas
-            | Self::C2WinnerLacksDatetimeLoserHas
-            | Self::C3WinnerLacksDescriptionLoserHas
-            | Self::C4WinnerLacksAllLoserHasAll
-            | Self::C5BothHaveGps
-            | Self::C6MultipleLosersContribute
-            | Self::C7NoLoserHasNeeded
-            | Self::C8WinnerHasEverything => "Consolidation",
-            Self::F1GpsConflict
-            | Self::F2GpsWithinThreshold
-            | Self::F3TimezoneConflict
-            | Self::F4CameraConflict
-            | Self::F5CaptureTimeConflict
-            | Self::F6MultipleConflicts
-            | Self::F7NoConflicts => "Conflicts",
-            Self::X1SingleAssetGroup
-            | Self::X2LargeGroup
-            | Self::X3LargeFile
-            | Self::X4SpecialCharsFilename
-            | Self::X5Video
-            | Self::X7Png
-            | Self::X9UnicodeDescription
-            | Self::X10VeryOldDate
-            | Self::X11FutureDate => "Edge Cases",
-        }
+        self.0.category
+    }
+
+    /// Returns the severity.
+    pub fn severity(&self) -> Severity {
+        self.0.severity
+    }
+}
+
+impl PartialEq for TestScenario {
+    fn eq(&self, other: &Self) -> bool {
+        self.code() == other.code()
+    }
+}
+
+impl Eq for TestScenario {}
+
+impl Hash for TestScenario {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.code().hash(state);
+    }
+}
+
+impl fmt::Debug for TestScenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TestScenario").field(&self.code()).finish()
     }
 }
 
 impl fmt::Display for TestScenario {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = match self {
-            Self::W1ClearDimensionWinner => "W1: Clear dimension winner",
-            Self::W2SameDimensionsDifferentSize => "W2: Same dimensions, different size",
-            Self::W3SameDimensionsSameSize => "W3: Same dimensions, same size",
-            Self::W4SomeMissingDimensions => "W4: Some missing dimensions",
-            Self::W5OnlyOneHasDimensions => "W5: Only one has dimensions",
-            Self::W6AllMissingDimensions => "W6: All missing dimensions",
-            Self::W7ThreePlusDuplicates => "W7: 3+ duplicates",
-            Self::W8SamePixelsDifferentAspect => "W8: Same pixels, different aspect",
-            Self::C1WinnerLacksGpsLoserHas => "C1: Winner lacks GPS, loser has",
-            Self::C2WinnerLacksDatetimeLoserHas => "C2: Winner lacks datetime, loser has",
-            Self::C3WinnerLacksDescriptionLoserHas => "C3: Winner lacks description, loser has",
-            Self::C4WinnerLacksAllLoserHasAll => "C4: Winner lacks all, loser has all",
-            Self::C5BothHaveGps => "C5: Both have GPS",
-            Self::C6MultipleLosersContribute => "C6: Multiple losers contribute",
-            Self::C7NoLoserHasNeeded => "C7: No loser has needed",
-            Self::C8WinnerHasEverything => "C8: Winner has everything",
-            Self::F1GpsConflict => "F1: GPS conflict",
-            Self::F2GpsWithinThreshold => "F2: GPS within threshold",
-            Self::F3TimezoneConflict => "F3: Timezone conflict",
-            Self::F4CameraConflict => "F4: Camera conflict",
-            Self::F5CaptureTimeConflict => "F5: Capture time conflict",
-            Self::F6MultipleConflicts => "F6: Multiple conflicts",
-            Self::F7NoConflicts => "F7: No conflicts",
-            Self::X1SingleAssetGroup => "X1: Single asset group",
-            Self::X2LargeGroup => "X2: Large group (10+)",
-            Self::X3LargeFile => "X3: Large file (>50MB)",
-            Self::X4SpecialCharsFilename => "X4: Special chars in filename",
-            Self::X5Video => "X5: Video",
-            Self::X7Png => "X7: PNG",
-            Self::X9UnicodeDescription => "X9: Unicode description",
-            Self::X10VeryOldDate => "X10: Very old date (<1990)",
-            Self::X11FutureDate => "X11: Future date",
-        };
-        write!(f, "{}", name)
+        f.write_str(self.0.name)
+    }
+}
+
+impl Serialize for TestScenario {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+/// Names the built-in scenarios were serialized under while `TestScenario`
+/// was an enum, with their codes, so older reports still read.
+const FORMER_NAMES: &[(&str, &str)] = &[
+    ("W1ClearDimensionWinner", "w1"),
+    ("W2SameDimensionsDifferentSize", "w2"),
+    ("W3SameDimensionsSameSize", "w3"),
+    ("W4SomeMissingDimensions", "w4"),
+    ("W5OnlyOneHasDimensions", "w5"),
+    ("W6AllMissingDimensions", "w6"),
+    ("W7ThreePlusDuplicates", "w7"),
+    ("W8SamePixelsDifferentAspect", "w8"),
+    ("C1WinnerLacksGpsLoserHas", "c1"),
+    ("C2WinnerLacksDatetimeLoserHas", "c2"),
+    ("C3WinnerLacksDescriptionLoserHas", "c3"),
+    ("C4WinnerLacksAllLoserHasAll", "c4"),
+    ("C5BothHaveGps", "c5"),
+    ("C6MultipleLosersContribute", "c6"),
+    ("C7NoLoserHasNeeded", "c7"),
+    ("C8WinnerHasEverything", "c8"),
+    ("F1GpsConflict", "f1"),
+    ("F2GpsWithinThreshold", "f2"),
+    ("F3TimezoneConflict", "f3"),
+    ("F4CameraConflict", "f4"),
+    ("F5CaptureTimeConflict", "f5"),
+    ("F6MultipleConflicts", "f6"),
+    ("F7NoConflicts", "f7"),
+    ("X1SingleAssetGroup", "x1"),
+    ("X2LargeGroup", "x2"),
+    ("X3LargeFile", "x3"),
+    ("X4SpecialCharsFilename", "x4"),
+    ("X5Video", "x5"),
+    ("X7Png", "x7"),
+    ("X9UnicodeDescription", "x9"),
+    ("X10VeryOldDate", "x10"),
+    ("X11FutureDate", "x11"),
+];
+
+/// Deserializes built-in scenarios only; custom scenarios have no global registry.
+impl<'de> Deserialize<'de> for TestScenario {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        let code = FORMER_NAMES
+            .iter()
+            .find(|(name, _)| *name == code)
+            .map_or(code.as_str(), |(_, code)| code);
+        ScenarioRegistry::builtin()
+            .get(code)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown test scenario '{}'", code)))
+    }
+}
+
+/// An ordered set of scenarios to detect and generate fixtures for.
+#[derive(Debug, Clone, Default)]
+pub struct ScenarioRegistry {
+    scenarios: Vec<TestScenario>,
+}
+
+impl ScenarioRegistry {
+    /// The built-in scenarios.
+    pub fn builtin() -> Self {
+        Self {
+            scenarios: vec![
+                // Winner selection
+                TestScenario::W1ClearDimensionWinner,
+                TestScenario::W2SameDimensionsDifferentSize,
+                TestScenario::W3SameDimensionsSameSize,
+                TestScenario::W4SomeMissingDimensions,
+                TestScenario::W5OnlyOneHasDimensions,
+                TestScenario::W6AllMissingDimensions,
+                TestScenario::W7ThreePlusDuplicates,
+                TestScenario::W8SamePixelsDifferentAspect,
+                // Consolidation
+                TestScenario::C1WinnerLacksGpsLoserHas,
+                TestScenario::C2WinnerLacksDatetimeLoserHas,
+                TestScenario::C3WinnerLacksDescriptionLoserHas,
+                TestScenario::C4WinnerLacksAllLoserHasAll,
+                TestScenario::C5BothHaveGps,
+                TestScenario::C6MultipleLosersContribute,
+                TestScenario::C7NoLoserHasNeeded,
+                TestScenario::C8WinnerHasEverything,
+                // Conflicts
+                TestScenario::F1GpsConflict,
+                TestScenario::F2GpsWithinThreshold,
+                TestScenario::F3TimezoneConflict,
+                TestScenario::F4CameraConflict,
+                TestScenario::F5CaptureTimeConflict,
+                TestScenario::F6MultipleConflicts,
+                TestScenario::F7NoConflicts,
+                // Edge cases
+                TestScenario::X1SingleAssetGroup,
+                TestScenario::X2LargeGroup,
+                TestScenario::X3LargeFile,
+                TestScenario::X4SpecialCharsFilename,
+                TestScenario::X5Video,
+                #[cfg(feature = "heic")]
+                TestScenario::X6Heic,
+                TestScenario::X7Png,
+                TestScenario::X9UnicodeDescription,
+                TestScenario::X10VeryOldDate,
+                TestScenario::X11FutureDate,
+            ],
+        }
+    }
+
+    /// Add a scenario. Fails if its code is already registered.
+    pub fn register(&mut self, scenario: TestScenario) -> Result<()> {
+        if self.get(scenario.code()).is_some() {
+            return Err(ImmichError::InvalidScenario(format!(
+                "scenario code '{}' is already registered",
+                scenario.code()
+            )));
+        }
+        self.scenarios.push(scenario);
+        Ok(())
+    }
+
+    /// Registered scenarios, in registration order.
+    pub fn scenarios(&self) -> &[TestScenario] {
+        &self.scenarios
+    }
+
+    /// Look up a scenario by code (case-insensitive).
+    pub fn get(&self, code: &str) -> Option<TestScenario> {
+        self.scenarios
+            .iter()
+            .find(|s| s.code().eq_ignore_ascii_case(code))
+            .copied()
+    }
+
+    /// Detect every registered scenario that applies to a duplicate group.
+    pub fn detect(&self, group: &DuplicateGroup) -> Vec<ScenarioMatch> {
+        self.detect_with(group, &Thresholds::default())
+    }
+
+    /// Like [`detect`](Self::detect), with custom detection thresholds.
+    pub fn detect_with(&self, group: &DuplicateGroup, thresholds: &Thresholds) -> Vec<ScenarioMatch> {
+        self.detect_with_strategy(group, thresholds, WinnerStrategy::default())
+    }
+
+    /// Like [`detect_with`](Self::detect_with), with winner-based scenarios
+    /// judged against the winner `strategy` picks.
+    pub fn detect_with_strategy(
+        &self,
+        group: &DuplicateGroup,
+        thresholds: &Thresholds,
+        strategy: WinnerStrategy,
+    ) -> Vec<ScenarioMatch> {
+        let context = ScenarioContext::with_strategy(group, thresholds, strategy);
+        let duplicate_id = SharedStr::from(&group.duplicate_id);
+        self.scenarios
+            .iter()
+            .flat_map(|scenario| {
+                (scenario.def().matcher)(&context)
+                    .into_iter()
+                    .map(|details| ScenarioMatch {
+                        scenario: *scenario,
+                        duplicate_id: duplicate_id.clone(),
+                        details,
+                    })
+            })
+            .collect()
+    }
+
+    /// Fixtures for every registered scenario that has one.
+    pub fn fixtures(&self) -> Vec<ScenarioFixture> {
+        self.scenarios
+            .iter()
+            .filter_map(|s| s.def().fixture)
+            .map(|build| build())
+            .collect()
     }
 }
 
@@ -247,8 +414,102 @@ impl fmt::Display for TestScenario {
 pub struct ScenarioMatch {
     /// The matched scenario
     pub scenario: TestScenario,
-    /// Duplicate group ID
-    pub duplicate_id: String,
+    /// Duplicate group ID (shared by every match in the group)
+    pub duplicate_id: SharedStr,
     /// Description of why this matched
     pub details: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::synthetic_groups;
+
+    fn live_photo(context: &ScenarioContext<'_>) -> Vec<String> {
+        context
+            .group
+            .assets
+            .iter()
+            .filter(|a| a.original_file_name.ends_with(".MOV"))
+            .map(|a| format!("Live photo video: {}", a.original_file_name))
+            .collect()
+    }
+
+    static LIVE_PHOTO: ScenarioDef = ScenarioDef {
+        code: "l1",
+        name: "L1: Live photo",
+        category: "Live Photos",
+        severity: Severity::Low,
+        matcher: live_photo,
+        fixture: None,
+    };
+
+    // Same code as a built-in, differing only in case
+    static CLASHING: ScenarioDef = ScenarioDef {
+        code: "W1",
+        ..LIVE_PHOTO
+    };
+
+    #[test]
+    fn test_builtin_codes_unique_and_round_trip() {
+        let registry = ScenarioRegistry::builtin();
+        for scenario in registry.scenarios() {
+            assert_eq!(registry.get(scenario.code()), Some(*scenario));
+            let json = serde_json::to_string(scenario).unwrap();
+            assert_eq!(serde_json::from_str::<TestScenario>(&json).unwrap(), *scenario);
+        }
+        assert_eq!(TestScenario::X10VeryOldDate.to_string(), "X10: Very old date (<1990)");
+        assert_eq!(registry.get("W8"), Some(TestScenario::W8SamePixelsDifferentAspect));
+    }
+
+    #[test]
+    fn test_former_enum_names_deserialize() {
+        for (name, code) in FORMER_NAMES {
+            let scenario: TestScenario = serde_json::from_str(&format!("\"{}\"", name)).unwrap();
+            assert_eq!(scenario.code(), *code);
+        }
+        let scenario: TestScenario = serde_json::from_str("\"F3TimezoneConflict\"").unwrap();
+        assert_eq!(scenario, TestScenario::F3TimezoneConflict);
+    }
+
+    #[test]
+    fn test_custom_scenario_registered_and_detected() {
+        let mut registry = ScenarioRegistry::builtin();
+        let custom = TestScenario::new(&LIVE_PHOTO);
+        registry.register(custom).unwrap();
+        assert!(registry.register(custom).is_err());
+        assert!(registry.register(TestScenario::new(&CLASHING)).is_err());
+
+        let mut group = synthetic_groups(1, 2).unwrap().remove(0);
+        group.assets[1].original_file_name = "IMG_0001.MOV".to_string();
+
+        let matches = registry.detect(&group);
+        let live: Vec<_> = matches.iter().filter(|m| m.scenario == custom).collect();
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].scenario.category(), "Live Photos");
+        assert_eq!(registry.fixtures().len(), ScenarioRegistry::builtin().fixtures().len());
+    }
+
+    #[test]
+    fn test_detection_uses_given_thresholds() {
+        let mut group = synthetic_groups(1, 2).unwrap().remove(0);
+        for asset in &mut group.assets {
+            asset.exif_info.as_mut().unwrap().file_size_in_byte = Some(2 * 1024 * 1024);
+        }
+        let registry = ScenarioRegistry::builtin();
+        let large = |thresholds: &Thresholds| {
+            registry
+                .detect_with(&group, thresholds)
+                .iter()
+                .filter(|m| m.scenario == TestScenario::X3LargeFile)
+                .count()
+        };
+
+        assert_eq!(large(&Thresholds::default()), 0);
+        let thresholds = Thresholds {
+            large_file_bytes: 1024 * 1024,
+            ..Default::default()
+        };
+        assert_eq!(large(&thresholds), 2);
+    }
+}
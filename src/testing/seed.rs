@@ -0,0 +1,197 @@
+//! Seeding generated fixtures into an Immich server.
+//!
+//! [`upload_scenario`] uploads the images a scenario's manifest lists,
+//! [`wait_for_duplicates`] waits for Immich to process them and detect
+//! duplicates, and [`map_duplicate_groups`] records which duplicate group
+//! each scenario ended up in, so tests can find it by ID.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::fixtures::FixtureManifest;
+use crate::client::{file_checksum, ImmichClient, UploadCheck};
+use crate::error::Result;
+use crate::models::DuplicateGroup;
+
+/// An image uploaded for a scenario.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeededAsset {
+    /// Fixture filename
+    pub filename: String,
+
+    /// Asset ID assigned by the server
+    pub asset_id: String,
+
+    /// Whether the server already had the file (same checksum)
+    pub duplicate: bool,
+}
+
+/// A scenario's fixtures as seeded into the server.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeededScenario {
+    /// Scenario code, upper case (e.g. `W1`)
+    pub scenario: String,
+
+    /// Filename of the expected winner
+    pub expected_winner: String,
+
+    /// Images uploaded
+    pub assets: Vec<SeededAsset>,
+
+    /// Duplicate groups containing the scenario's assets (set by
+    /// [`map_duplicate_groups`]; one, unless detection split the scenario)
+    pub duplicate_ids: Vec<String>,
+
+    /// Images that could not be uploaded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Upload the images listed in a scenario directory's manifest.
+///
+/// Images the server already has are not uploaded again; they are recorded
+/// as duplicates of the existing asset. Failed uploads are recorded in
+/// [`SeededScenario::errors`] rather than stopping the scenario.
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be read, or the server cannot be
+/// asked which images it already has.
+pub async fn upload_scenario(client: &ImmichClient, scenario_dir: &Path) -> Result<SeededScenario> {
+    let manifest = FixtureManifest::load(scenario_dir)?;
+    let mut seeded = SeededScenario {
+        scenario: manifest.scenario,
+        expected_winner: manifest.expected_winner,
+        assets: Vec::new(),
+        duplicate_ids: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    // Files the server already has (from an earlier seeding) aren't re-uploaded
+    let mut checksums = Vec::with_capacity(manifest.images.len());
+    for filename in &manifest.images {
+        // Unreadable images fail their upload below
+        if let Ok(checksum) = file_checksum(&scenario_dir.join(filename)).await {
+            checksums.push((filename.clone(), checksum));
+        }
+    }
+    let checks = client.check_bulk_upload(&checksums).await?;
+
+    for filename in manifest.images {
+        if let Some(UploadCheck::Duplicate { asset_id, .. }) = checks.get(&filename) {
+            seeded.assets.push(SeededAsset {
+                filename,
+                asset_id: asset_id.clone(),
+                duplicate: true,
+            });
+            continue;
+        }
+        match client.upload_asset(&scenario_dir.join(&filename)).await {
+            Ok(response) => seeded.assets.push(SeededAsset {
+                filename,
+                duplicate: response.is_duplicate(),
+                asset_id: response.id,
+            }),
+            Err(e) => seeded.errors.push(format!("{}: {}", filename, e)),
+        }
+    }
+
+    Ok(seeded)
+}
+
+/// Wait for the server to finish processing uploads, then return its
+/// duplicate groups.
+///
+/// Polls every `poll` until no job queue has active or waiting jobs (Immich
+/// detects duplicates after generating CLIP embeddings) and a duplicate group
+/// contains one of the `seeded` assets. Gives up after `timeout` and returns
+/// the groups found by then.
+///
+/// # Errors
+///
+/// Returns an error if the job status or duplicates can't be fetched.
+pub async fn wait_for_duplicates(
+    client: &ImmichClient,
+    seeded: &[SeededScenario],
+    poll: Duration,
+    timeout: Duration,
+) -> Result<Vec<DuplicateGroup>> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let ids: HashSet<&str> = seeded
+        .iter()
+        .flat_map(|s| &s.assets)
+        .map(|a| a.asset_id.as_str())
+        .collect();
+
+    loop {
+        let jobs = client.get_job_statuses().await?;
+        let idle = jobs.values().all(|job| {
+            !job.queue_status.is_active && job.job_counts.active == 0 && job.job_counts.waiting == 0
+        });
+        let timed_out = tokio::time::Instant::now() >= deadline;
+
+        if idle || timed_out {
+            let groups = client.get_duplicates().await?;
+            let found = groups.iter().flat_map(|g| &g.assets).any(|a| ids.contains(a.id.as_str()));
+            if found || timed_out {
+                return Ok(groups);
+            }
+        }
+        tokio::time::sleep(poll).await;
+    }
+}
+
+/// Record the duplicate groups each scenario's assets ended up in.
+pub fn map_duplicate_groups(seeded: &mut [SeededScenario], groups: &[DuplicateGroup]) {
+    for scenario in seeded {
+        let ids: HashSet<&str> = scenario.assets.iter().map(|a| a.asset_id.as_str()).collect();
+        scenario.duplicate_ids = groups
+            .iter()
+            .filter(|g| g.assets.iter().any(|a| ids.contains(a.id.as_str())))
+            .map(|g| g.duplicate_id.clone())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::builder::{AssetBuilder, GroupBuilder};
+    use crate::testing::mock_server::MockServer;
+
+    #[tokio::test]
+    async fn test_seed_scenario_and_map_groups() {
+        let server = MockServer::start(&[]).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = FixtureManifest {
+            scenario: "W1".to_string(),
+            description: "Larger dimensions should win".to_string(),
+            images: vec!["w1_large.jpg".to_string(), "w1_small.jpg".to_string(), "missing.jpg".to_string()],
+            expected_winner: "w1_large.jpg".to_string(),
+        };
+        manifest.save(dir.path()).unwrap();
+        std::fs::write(dir.path().join("w1_large.jpg"), b"large").unwrap();
+        std::fs::write(dir.path().join("w1_small.jpg"), b"small").unwrap();
+
+        let seeded = upload_scenario(&client, dir.path()).await.unwrap();
+        assert_eq!(server.uploads(), ["w1_large.jpg", "w1_small.jpg"]);
+        assert_eq!(seeded.assets.len(), 2);
+        assert_eq!(seeded.errors.len(), 1);
+        assert!(seeded.assets.iter().all(|a| !a.duplicate));
+
+        let mut seeded = vec![seeded];
+        let groups = [
+            GroupBuilder::new("unrelated").with_asset(AssetBuilder::new("other")).build(),
+            GroupBuilder::new("w1-group")
+                .with_assets(seeded[0].assets.iter().map(|a| AssetBuilder::new(&a.asset_id)))
+                .build(),
+        ];
+        map_duplicate_groups(&mut seeded, &groups);
+        assert_eq!(seeded[0].duplicate_ids, ["w1-group"]);
+    }
+}
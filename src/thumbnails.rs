@@ -0,0 +1,397 @@
+//! Local, content-addressable cache of asset thumbnails.
+//!
+//! Review tools show the same few hundred thumbnails over and over, and
+//! every redraw would otherwise be a request to the Immich server. The
+//! [`ThumbnailCache`] stores each image on disk under the asset's checksum,
+//! so identical copies in different groups share one entry and an asset
+//! re-uploaded under a new ID is still a hit. The cache has a size limit and
+//! evicts the least recently used images when it is exceeded.
+//!
+//! One cache can be shared (behind an `Arc`) by everything that displays or
+//! compares thumbnails.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::client::ImmichClient;
+use crate::error::Result;
+
+/// Default cache size limit: 512 MiB.
+pub const DEFAULT_MAX_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Which rendition of an asset to fetch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailSize {
+    /// Small square thumbnail, as in the Immich timeline
+    #[default]
+    Thumbnail,
+    /// Larger preview, as in the Immich asset viewer
+    Preview,
+    /// Full-resolution JPEG rendition (the original itself for web-friendly
+    /// formats), without downloading a RAW or HEIC original
+    Fullsize,
+}
+
+impl ThumbnailSize {
+    /// Every size, smallest first.
+    pub const ALL: [Self; 3] = [Self::Thumbnail, Self::Preview, Self::Fullsize];
+
+    /// Value of the `size` query parameter and cache subdirectory.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Thumbnail => "thumbnail",
+            Self::Preview => "preview",
+            Self::Fullsize => "fullsize",
+        }
+    }
+}
+
+impl std::fmt::Display for ThumbnailSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ThumbnailSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "thumbnail" => Ok(Self::Thumbnail),
+            "preview" => Ok(Self::Preview),
+            "fullsize" => Ok(Self::Fullsize),
+            other => Err(format!(
+                "Unknown thumbnail size '{}' (expected thumbnail, preview, or fullsize)",
+                other
+            )),
+        }
+    }
+}
+
+/// Usage of a thumbnail cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Cached images
+    pub entries: usize,
+    /// Bytes on disk
+    pub bytes: u64,
+    /// Size limit in bytes
+    pub max_bytes: u64,
+}
+
+/// A size-limited, least-recently-used thumbnail cache on disk.
+#[derive(Debug)]
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    index: Mutex<CacheIndex>,
+}
+
+/// In-memory view of the files in the cache directory.
+#[derive(Debug, Default)]
+struct CacheIndex {
+    entries: HashMap<PathBuf, CacheEntry>,
+    total_bytes: u64,
+    /// Incremented on every access; higher means more recently used
+    clock: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CacheEntry {
+    bytes: u64,
+    last_used: u64,
+}
+
+impl CacheIndex {
+    fn touch(&mut self, path: &Path) {
+        self.clock += 1;
+        if let Some(entry) = self.entries.get_mut(path) {
+            entry.last_used = self.clock;
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, bytes: u64) {
+        self.clock += 1;
+        let entry = CacheEntry { bytes, last_used: self.clock };
+        if let Some(previous) = self.entries.insert(path, entry) {
+            self.total_bytes -= previous.bytes;
+        }
+        self.total_bytes += bytes;
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.total_bytes -= entry.bytes;
+        }
+    }
+
+    /// Delete least recently used files until the cache fits in `max_bytes`.
+    fn evict(&mut self, max_bytes: u64) {
+        if self.total_bytes <= max_bytes {
+            return;
+        }
+        let mut by_age: Vec<(u64, PathBuf)> =
+            self.entries.iter().map(|(path, entry)| (entry.last_used, path.clone())).collect();
+        by_age.sort();
+        for (_, path) in by_age {
+            if self.total_bytes <= max_bytes {
+                break;
+            }
+            // A file someone else already removed is just as evicted
+            let _ = fs::remove_file(&path);
+            self.remove(&path);
+        }
+    }
+}
+
+impl ThumbnailCache {
+    /// Open (creating if needed) a cache in `dir` limited to `max_bytes`.
+    ///
+    /// Existing entries are kept, in their previous order of use, and the
+    /// cache is trimmed if it is over the new limit.
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut files = Vec::new();
+        for size in ThumbnailSize::ALL {
+            let size_dir = dir.join(size.as_str());
+            let Ok(shards) = fs::read_dir(&size_dir) else {
+                continue;
+            };
+            for shard in shards.flatten() {
+                let Ok(entries) = fs::read_dir(shard.path()) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let Ok(metadata) = entry.metadata() else {
+                        continue;
+                    };
+                    if metadata.is_file() {
+                        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                        files.push((modified, entry.path(), metadata.len()));
+                    }
+                }
+            }
+        }
+
+        // Modification times record last use across runs
+        files.sort();
+        let mut index = CacheIndex::default();
+        for (_, path, bytes) in files {
+            index.insert(path, bytes);
+        }
+        index.evict(max_bytes);
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            index: Mutex::new(index),
+        })
+    }
+
+    /// Directory holding the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Current usage.
+    pub fn stats(&self) -> CacheStats {
+        let index = self.index.lock();
+        CacheStats {
+            entries: index.entries.len(),
+            bytes: index.total_bytes,
+            max_bytes: self.max_bytes,
+        }
+    }
+
+    /// The cached image for `checksum`, if present.
+    pub fn get(&self, checksum: &str, size: ThumbnailSize) -> Option<Vec<u8>> {
+        let path = self.entry_path(checksum, size);
+        let mut index = self.index.lock();
+        if !index.entries.contains_key(&path) {
+            return None;
+        }
+        match fs::read(&path) {
+            Ok(bytes) => {
+                index.touch(&path);
+                // Best effort: the order survives restarts only if this works
+                if let Ok(file) = fs::File::options().append(true).open(&path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+                Some(bytes)
+            }
+            Err(_) => {
+                index.remove(&path);
+                None
+            }
+        }
+    }
+
+    /// Store an image for `checksum`, evicting older entries if needed.
+    ///
+    /// Images larger than the whole cache are not stored.
+    pub fn insert(&self, checksum: &str, size: ThumbnailSize, bytes: &[u8]) -> Result<()> {
+        if bytes.len() as u64 > self.max_bytes {
+            return Ok(());
+        }
+        let path = self.entry_path(checksum, size);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write then rename, so a reader never sees a partial image
+        let partial = path.with_extension("partial");
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, &path)?;
+
+        let mut index = self.index.lock();
+        index.insert(path, bytes.len() as u64);
+        index.evict(self.max_bytes);
+        Ok(())
+    }
+
+    /// The image for an asset, from the cache or else from the server.
+    ///
+    /// Assets without a checksum are fetched every time.
+    pub async fn get_or_fetch(
+        &self,
+        client: &ImmichClient,
+        asset_id: &str,
+        checksum: &str,
+        size: ThumbnailSize,
+    ) -> Result<Vec<u8>> {
+        if checksum.is_empty() {
+            return client.download_thumbnail(asset_id, size).await;
+        }
+        if let Some(bytes) = self.get(checksum, size) {
+            return Ok(bytes);
+        }
+        let bytes = client.download_thumbnail(asset_id, size).await?;
+        self.insert(checksum, size, &bytes)?;
+        Ok(bytes)
+    }
+
+    /// Remove every cached image.
+    pub fn clear(&self) -> Result<()> {
+        let mut index = self.index.lock();
+        for size in ThumbnailSize::ALL {
+            let size_dir = self.dir.join(size.as_str());
+            if size_dir.exists() {
+                fs::remove_dir_all(&size_dir)?;
+            }
+        }
+        *index = CacheIndex::default();
+        Ok(())
+    }
+
+    /// `<dir>/<size>/<shard>/<key>`, sharded by the first two key characters.
+    fn entry_path(&self, checksum: &str, size: ThumbnailSize) -> PathBuf {
+        let key = cache_key(checksum);
+        let shard: String = key.chars().take(2).collect();
+        self.dir.join(size.as_str()).join(shard).join(key)
+    }
+}
+
+/// File name for a checksum.
+///
+/// Immich checksums are base64, so `+` and `/` are mapped to their URL-safe
+/// equivalents and padding is dropped. Anything else outside `[A-Za-z0-9]`
+/// is hex-escaped, keeping distinct checksums distinct.
+fn cache_key(checksum: &str) -> String {
+    let mut key = String::with_capacity(checksum.len());
+    for c in checksum.trim_end_matches('=').chars() {
+        match c {
+            '+' => key.push('-'),
+            '/' => key.push('_'),
+            c if c.is_ascii_alphanumeric() => key.push(c),
+            c => {
+                let mut buffer = [0u8; 4];
+                for byte in c.encode_utf8(&mut buffer).bytes() {
+                    key.push_str(&format!("%{:02x}", byte));
+                }
+            }
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[test]
+    fn test_cache_key_is_filename_safe() {
+        assert_eq!(cache_key("ab+c/d=="), "ab-c_d");
+        assert_eq!(cache_key("a.b"), "a%2eb");
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ThumbnailCache::open(dir.path(), 250).unwrap();
+
+        cache.insert("a", ThumbnailSize::Thumbnail, &[1; 100]).unwrap();
+        cache.insert("b", ThumbnailSize::Thumbnail, &[2; 100]).unwrap();
+        assert!(cache.get("a", ThumbnailSize::Thumbnail).is_some());
+        cache.insert("c", ThumbnailSize::Thumbnail, &[3; 100]).unwrap();
+
+        // "b" was used least recently
+        assert!(cache.get("b", ThumbnailSize::Thumbnail).is_none());
+        assert_eq!(cache.get("a", ThumbnailSize::Thumbnail), Some(vec![1; 100]));
+        assert_eq!(cache.stats().entries, 2);
+        assert_eq!(cache.stats().bytes, 200);
+
+        // Entries survive reopening
+        drop(cache);
+        let reopened = ThumbnailCache::open(dir.path(), 250).unwrap();
+        assert_eq!(reopened.get("c", ThumbnailSize::Thumbnail), Some(vec![3; 100]));
+        reopened.clear().unwrap();
+        assert_eq!(reopened.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_or_fetch_hits_server_once() {
+        let groups = synthetic_groups(1, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ThumbnailCache::open(dir.path(), DEFAULT_MAX_BYTES).unwrap();
+
+        let asset = &groups[0].assets[0];
+        let first = cache
+            .get_or_fetch(&client, &asset.id, &asset.checksum, ThumbnailSize::Thumbnail)
+            .await
+            .unwrap();
+        let second = cache
+            .get_or_fetch(&client, &asset.id, &asset.checksum, ThumbnailSize::Thumbnail)
+            .await
+            .unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(server.thumbnail_requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_thumbnail_sizes() {
+        let groups = synthetic_groups(1, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+
+        for size in ThumbnailSize::ALL {
+            assert_eq!(size.as_str().parse::<ThumbnailSize>(), Ok(size));
+            assert!(!client.download_thumbnail(&groups[0].assets[0].id, size).await.unwrap().is_empty());
+        }
+        assert!(matches!(
+            client.download_thumbnail("missing", ThumbnailSize::Preview).await,
+            Err(crate::error::ImmichError::NotFound { .. })
+        ));
+    }
+}
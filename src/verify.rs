@@ -0,0 +1,418 @@
+//! Post-execution verification.
+//!
+//! After an execution, [`Verifier`] re-fetches every group's winner and
+//! losers to confirm the winner survived, the losers are trashed or gone,
+//! and GPS was transferred to winners that lacked it. Large runs can be
+//! verified progressively from a random sample (see [`crate::sampling`]).
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::client::ImmichClient;
+use crate::clock::{Clock, SystemClock};
+use crate::error::ImmichError;
+use crate::models::AssetState;
+use crate::review::ReviewDecision;
+use crate::sampling::{self, FailureEstimate, SampleOptions, SampleStep};
+use crate::scoring::{DuplicateAnalysis, ScoredAsset};
+
+/// Result of verifying a single group
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupVerification {
+    /// Duplicate group ID
+    pub duplicate_id: String,
+
+    /// Winner verification status
+    pub winner_status: AssetStatus,
+
+    /// Loser verification statuses
+    pub loser_statuses: Vec<AssetStatus>,
+
+    /// Consolidation checks (GPS transferred, etc.)
+    pub consolidation_checks: Vec<ConsolidationCheck>,
+
+    /// Reviewer decision recorded for the group, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<ReviewDecision>,
+}
+
+/// Status of a single asset in verification
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetStatus {
+    pub asset_id: String,
+    pub filename: String,
+    /// Observed server-side state
+    pub status: AssetState,
+    /// Optional error message
+    pub error: Option<String>,
+}
+
+/// A consolidation check result
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidationCheck {
+    /// What was checked (e.g., "gps_transferred", "datetime_transferred")
+    pub check_type: String,
+    /// Whether the check passed
+    pub passed: bool,
+    /// Details about the check
+    pub details: String,
+}
+
+/// Full verification report
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationReport {
+    /// When verification was performed
+    pub verified_at: DateTime<Utc>,
+
+    /// Server URL
+    pub server_url: String,
+
+    /// Groups verified
+    pub groups_verified: usize,
+
+    /// Winners present count
+    pub winners_present: usize,
+
+    /// Winners missing count (errors)
+    pub winners_missing: usize,
+
+    /// Losers confirmed deleted
+    pub losers_deleted: usize,
+
+    /// Losers still present (errors)
+    pub losers_still_present: usize,
+
+    /// Consolidation checks passed
+    pub consolidation_passed: usize,
+
+    /// Consolidation checks failed
+    pub consolidation_failed: usize,
+
+    /// Per-group verification results
+    pub groups: Vec<GroupVerification>,
+
+    /// Any anomalies detected
+    pub anomalies: Vec<String>,
+
+    /// Sample and extrapolated failure rate, when verifying a sample
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingSummary>,
+}
+
+impl VerificationReport {
+    /// Whether every verified winner is present, every loser is gone, and
+    /// every consolidation check passed.
+    pub fn passed(&self) -> bool {
+        self.winners_missing == 0 && self.losers_still_present == 0 && self.consolidation_failed == 0
+    }
+}
+
+/// Outcome of a sampled verification
+#[derive(Debug, Clone, Serialize)]
+pub struct SamplingSummary {
+    /// Sampling options used
+    pub options: SampleOptions,
+
+    /// Failure rate of the sample, extrapolated to all groups
+    pub estimate: FailureEstimate,
+
+    /// Projected range of failing groups across the whole run
+    pub projected_failures: (usize, usize),
+
+    /// Whether the sample found problems, so every group was verified
+    pub escalated: bool,
+}
+
+/// Progress of a verification, for reporting as it runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerifyProgress {
+    /// Verification is starting with `checking` of `total` groups
+    Started { checking: usize, total: usize, sampled: bool },
+    /// A group was verified; `verified` groups so far
+    GroupVerified { verified: usize },
+    /// The sample was clean, but the failure rate could still be up to
+    /// `upper`, so the sample grows to `next` groups
+    SampleExtended { upper: f64, next: usize },
+    /// The sample found `failed` failing groups, so all `total` are verified
+    Escalated { failed: usize, total: usize },
+}
+
+/// Verifies the outcome of an execution against the analysis it used.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    client: ImmichClient,
+    sampling: Option<SampleOptions>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Verifier {
+    /// Verify every group through `client`.
+    pub fn new(client: ImmichClient) -> Self {
+        Self {
+            client,
+            sampling: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Verify a random sample first, escalating to every group only if it
+    /// finds problems.
+    pub fn with_sampling(mut self, options: SampleOptions) -> Self {
+        self.sampling = Some(options);
+        self
+    }
+
+    /// Stamp reports with the time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Verify the analyzed groups.
+    pub async fn verify(&self, groups: &[DuplicateAnalysis]) -> VerificationReport {
+        self.verify_with(groups, |_| {}).await
+    }
+
+    /// Like [`verify`](Self::verify), calling `on_progress` as verification
+    /// starts, after each group, and whenever a sample grows or escalates.
+    pub async fn verify_with<F>(&self, groups: &[DuplicateAnalysis], mut on_progress: F) -> VerificationReport
+    where
+        F: FnMut(VerifyProgress),
+    {
+        let mut report = VerificationReport {
+            verified_at: self.clock.now(),
+            server_url: self.client.base_url().to_string(),
+            groups_verified: 0,
+            winners_present: 0,
+            winners_missing: 0,
+            losers_deleted: 0,
+            losers_still_present: 0,
+            consolidation_passed: 0,
+            consolidation_failed: 0,
+            groups: Vec::new(),
+            anomalies: Vec::new(),
+            sampling: None,
+        };
+
+        // With sampling, verify groups in random order and check after each sample
+        let sampling = self.sampling.as_ref();
+        let total = groups.len();
+        let order: Vec<usize> = match sampling {
+            Some(options) => sampling::sample_order(total, options.seed),
+            None => (0..total).collect(),
+        };
+        let mut checkpoint = sampling.map(|options| sampling::sample_size(total, options.fraction));
+        let mut failed_groups = 0;
+        let mut sample_estimate = None;
+        let mut escalated = false;
+
+        on_progress(VerifyProgress::Started {
+            checking: checkpoint.unwrap_or(total),
+            total,
+            sampled: checkpoint.is_some(),
+        });
+
+        for (position, &index) in order.iter().enumerate() {
+            if let (Some(size), Some(options)) = (checkpoint, sampling)
+                && position == size
+            {
+                let estimate = FailureEstimate::new(failed_groups, position, total);
+                sample_estimate = Some(estimate);
+                match sampling::next_step(&estimate, options) {
+                    SampleStep::Stop => break,
+                    SampleStep::Extend(next) => {
+                        on_progress(VerifyProgress::SampleExtended {
+                            upper: estimate.upper,
+                            next,
+                        });
+                        checkpoint = Some(next);
+                    }
+                    SampleStep::Escalate => {
+                        on_progress(VerifyProgress::Escalated {
+                            failed: failed_groups,
+                            total,
+                        });
+                        checkpoint = None;
+                        escalated = true;
+                    }
+                }
+            }
+
+            if self.verify_group(&groups[index], &mut report).await {
+                failed_groups += 1;
+            }
+            on_progress(VerifyProgress::GroupVerified {
+                verified: report.groups_verified,
+            });
+        }
+
+        report.sampling = sampling.map(|options| {
+            // A sample that ran to the end covers every group
+            let estimate = match sample_estimate {
+                Some(estimate) if !escalated && report.groups_verified < total => estimate,
+                _ => FailureEstimate::new(failed_groups, report.groups_verified, total),
+            };
+            SamplingSummary {
+                options: *options,
+                estimate,
+                projected_failures: estimate.projected_failures(),
+                escalated,
+            }
+        });
+        report
+    }
+
+    /// Verify one group, adding it to `report`. Returns true if the group
+    /// had any anomalies.
+    async fn verify_group(&self, group: &DuplicateAnalysis, report: &mut VerificationReport) -> bool {
+        let anomalies_before = report.anomalies.len();
+        report.groups_verified += 1;
+
+        // Check winner exists
+        let mut consolidation_checks = Vec::new();
+        let winner_had_gps = group.winner.score.gps > 0;
+        let any_loser_had_gps = group.losers.iter().any(|l| l.score.gps > 0);
+        let winner_status = match self.client.get_asset(&group.winner.asset_id).await {
+            Ok(asset) => {
+                report.winners_present += 1;
+
+                if !winner_had_gps && any_loser_had_gps {
+                    // GPS should have been consolidated from loser to winner
+                    let has_gps_now = asset.exif_info.as_ref().is_some_and(|e| e.has_gps());
+                    if has_gps_now {
+                        report.consolidation_passed += 1;
+                        consolidation_checks.push(ConsolidationCheck {
+                            check_type: "gps_transferred".to_string(),
+                            passed: true,
+                            details: "GPS coordinates successfully transferred from loser".to_string(),
+                        });
+                    } else {
+                        report.consolidation_failed += 1;
+                        consolidation_checks.push(ConsolidationCheck {
+                            check_type: "gps_transferred".to_string(),
+                            passed: false,
+                            details: "GPS coordinates were NOT transferred from loser".to_string(),
+                        });
+                        report.anomalies.push(format!(
+                            "Group {}: GPS not transferred to winner {}",
+                            group.duplicate_id, group.winner.asset_id
+                        ));
+                    }
+                } else if winner_had_gps {
+                    consolidation_checks.push(ConsolidationCheck {
+                        check_type: "gps_retained".to_string(),
+                        passed: true,
+                        details: "Winner already had GPS, no transfer needed".to_string(),
+                    });
+                } else {
+                    consolidation_checks.push(ConsolidationCheck {
+                        check_type: "no_gps".to_string(),
+                        passed: true,
+                        details: "No GPS in group, no transfer needed".to_string(),
+                    });
+                }
+
+                status(&group.winner, AssetState::Present, None)
+            }
+            Err(ImmichError::NotFound { .. }) => {
+                report.winners_missing += 1;
+                report.anomalies.push(format!(
+                    "CRITICAL: Winner {} ({}) was deleted!",
+                    group.winner.asset_id, group.winner.filename
+                ));
+                status(&group.winner, AssetState::Deleted, Some("Winner was incorrectly deleted".to_string()))
+            }
+            Err(e) => {
+                report.winners_missing += 1;
+                report
+                    .anomalies
+                    .push(format!("Error checking winner {}: {}", group.winner.asset_id, e));
+                status(&group.winner, AssetState::Error, Some(e.to_string()))
+            }
+        };
+
+        // Check all losers are deleted (or trashed)
+        let mut loser_statuses = Vec::new();
+        for loser in &group.losers {
+            let lookup = self.client.get_asset(&loser.asset_id).await;
+            let loser_status = match AssetState::from_lookup(&lookup) {
+                // Trashed counts as deleted
+                state @ (AssetState::Trashed | AssetState::Deleted) => {
+                    report.losers_deleted += 1;
+                    status(loser, state, None)
+                }
+                AssetState::Present => {
+                    report.losers_still_present += 1;
+                    report.anomalies.push(format!(
+                        "Loser {} ({}) still exists (not trashed), should be deleted",
+                        loser.asset_id, loser.filename
+                    ));
+                    status(loser, AssetState::Present, Some("Loser should have been deleted".to_string()))
+                }
+                AssetState::Error => {
+                    let error = lookup.err().map(|e| e.to_string()).unwrap_or_default();
+                    report
+                        .anomalies
+                        .push(format!("Error checking loser {}: {}", loser.asset_id, error));
+                    status(loser, AssetState::Error, Some(error))
+                }
+            };
+            loser_statuses.push(loser_status);
+        }
+
+        report.groups.push(GroupVerification {
+            duplicate_id: group.duplicate_id.clone(),
+            winner_status,
+            loser_statuses,
+            consolidation_checks,
+            decision: group.decision.clone(),
+        });
+        report.anomalies.len() > anomalies_before
+    }
+}
+
+fn status(asset: &ScoredAsset, status: AssetState, error: Option<String>) -> AssetStatus {
+    AssetStatus {
+        asset_id: asset.asset_id.to_string(),
+        filename: asset.filename.to_string(),
+        status,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{synthetic_groups, MockServer};
+
+    #[tokio::test]
+    async fn test_verify_flags_surviving_losers() {
+        let groups = synthetic_groups(2, 2).unwrap();
+        let server = MockServer::start(&groups).await.unwrap();
+        let client = ImmichClient::new(&server.url(), "test-key").unwrap();
+        let analyses: Vec<DuplicateAnalysis> = groups.iter().map(DuplicateAnalysis::from_group).collect();
+        // Only the first group was executed
+        let executed: Vec<String> = analyses[0].losers.iter().map(|l| l.asset_id.to_string()).collect();
+        client.delete_assets(&executed, true).await.unwrap();
+
+        let mut progress = Vec::new();
+        let report = Verifier::new(client)
+            .verify_with(&analyses, |event| progress.push(event))
+            .await;
+
+        assert_eq!(report.groups_verified, 2);
+        assert_eq!(report.winners_present, 2);
+        assert_eq!(report.losers_deleted, 1);
+        assert_eq!(report.losers_still_present, 1);
+        assert_eq!(report.groups[0].loser_statuses[0].status, AssetState::Deleted);
+        assert_eq!(report.anomalies.iter().filter(|a| a.contains("still exists")).count(), 1);
+        // Every group's GPS check is listed with the group, as well as counted
+        let checks = report.groups.iter().map(|g| g.consolidation_checks.len()).sum::<usize>();
+        assert_eq!(checks, 2);
+        assert!(!report.passed());
+        assert_eq!(progress[0], VerifyProgress::Started { checking: 2, total: 2, sampled: false });
+        assert_eq!(progress.last(), Some(&VerifyProgress::GroupVerified { verified: 2 }));
+    }
+}
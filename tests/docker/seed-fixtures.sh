@@ -1,6 +1,8 @@
 #!/bin/sh
 # Seed test fixtures into Immich
 # Uploads all fixture images and waits for ML processing
+# (`immich-dupes seed-fixtures` does the same, and also generates missing
+# fixtures and prints each scenario's duplicate group)
 
 # Don't use set -e - we handle errors explicitly
 
@@ -92,7 +92,8 @@ fn run_conflict_tests(
                             MetadataConflict::Gps { values } => format!("GPS({} locations)", values.len()),
                             MetadataConflict::Timezone { values } => format!("TZ({:?})", values),
                             MetadataConflict::CameraInfo { values } => format!("Camera({:?})", values),
-                            MetadataConflict::CaptureTime { values } => format!("Time({} times)", values.len()),
+                            MetadataConflict::CaptureTime { values, .. } => format!("Time({} times)", values.len()),
+                            MetadataConflict::TimezoneShift { hours, .. } => format!("TzShift({}h)", hours),
                         }
                     }).collect();
 
@@ -113,6 +114,7 @@ fn run_conflict_tests(
                             MetadataConflict::Timezone { .. } => "Timezone",
                             MetadataConflict::CameraInfo { .. } => "Camera",
                             MetadataConflict::CaptureTime { .. } => "CaptureTime",
+                            MetadataConflict::TimezoneShift { .. } => "TimezoneShift",
                         }.to_string()
                     }).collect();
 
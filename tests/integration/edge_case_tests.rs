@@ -1,7 +1,7 @@
 //! Edge case integration tests.
 //!
-//! Tests X1-X5, X7, X9-X11 scenarios against a live Immich instance.
-//! Note: X6 (HEIC) and X8 (RAW) were removed - cannot generate without proprietary encoders.
+//! Tests X1-X7, X9-X11 scenarios against a live Immich instance.
+//! X6 (HEIC) needs the `heic` feature; X8 (RAW) was removed - cannot generate without proprietary encoders.
 
 use immich_lib::DuplicateAnalysis;
 
@@ -17,6 +17,7 @@ use super::winner_tests::{fetch_full_duplicates, find_group_for_manifest, Scenar
 /// - X3: Large file (48MP)
 /// - X4: Special characters in filename
 /// - X5: Video duplicates
+/// - X6: HEIC format
 /// - X7: PNG format
 /// - X9: Unicode in description
 /// - X10: Very old date (1985)
@@ -111,6 +112,10 @@ fn run_edge_case_tests(
                         // Video - verify it's handled
                         format!("Video handling OK, winner: '{}'", analysis.winner.filename)
                     }
+                    "x6" => {
+                        // HEIC format
+                        format!("HEIC format OK, winner: '{}'", analysis.winner.filename)
+                    }
                     "x7" => {
                         // PNG format
                         format!("PNG format OK, winner: '{}'", analysis.winner.filename)
@@ -152,9 +157,10 @@ fn run_edge_case_tests(
     results
 }
 
-/// Test edge cases (X1-X5, X7, X9-X11).
+/// Test edge cases (X1-X7, X9-X11).
 ///
-/// Note: X6 (HEIC) and X8 (RAW) were removed - cannot generate without proprietary encoders.
+/// Note: X6 (HEIC) only runs with the `heic` feature; X8 (RAW) was removed - cannot generate
+/// without proprietary encoders.
 ///
 /// Scenarios:
 /// - X1: Single asset - will NOT appear in duplicates (expected)
@@ -162,6 +168,7 @@ fn run_edge_case_tests(
 /// - X3: Large file (48MP) - verify dimensions parsed
 /// - X4: Special characters in filename - verify matching works
 /// - X5: Video duplicates (MP4) - verify video handling
+/// - X6: HEIC vs converted JPEG - verify format handling
 /// - X7: PNG format - verify format handling
 /// - X9: Unicode in description - verify unicode handling
 /// - X10: Very old date (1985) - verify date parsing
@@ -199,8 +206,11 @@ fn test_edge_cases() {
 
     println!("Found {} duplicate groups", groups.len());
 
-    // Test X scenarios (excluding X6 and X8 which were removed)
-    let scenarios = ["x1", "x2", "x3", "x4", "x5", "x7", "x9", "x10", "x11"];
+    // Test X scenarios (X6 needs the heic feature; X8 was removed)
+    #[allow(unused_mut)]
+    let mut scenarios = vec!["x1", "x2", "x3", "x4", "x5", "x7", "x9", "x10", "x11"];
+    #[cfg(feature = "heic")]
+    scenarios.insert(5, "x6");
     let results = run_edge_case_tests(&scenarios, &groups);
 
     // Print results
@@ -1,6 +1,8 @@
 //! Test harness for integration tests.
 //!
 //! Provides setup, teardown, and waiting utilities for Docker-based Immich testing.
+//! This drives the scripts in `tests/docker`; library users can get a
+//! disposable server from `immich_lib::testing::TestHarness` (`docker` feature).
 
 use std::fs;
 use std::path::PathBuf;
@@ -12,6 +12,7 @@
 
 use immich_lib::models::DuplicateGroup;
 use immich_lib::scoring::MetadataConflict;
+use immich_lib::testing::{detect_scenarios, TestScenario};
 use immich_lib::DuplicateAnalysis;
 
 /// Load recorded duplicate groups from fixture file.
@@ -20,6 +21,29 @@ fn load_recorded_duplicates() -> Vec<DuplicateGroup> {
     serde_json::from_str(json).expect("Failed to parse recorded duplicates")
 }
 
+/// Load rotated-pair groups (one copy stores pixels rotated, the other relies
+/// on the EXIF orientation tag).
+fn load_rotated_duplicates() -> Vec<DuplicateGroup> {
+    let json = include_str!("fixtures/recorded/rotated.json");
+    serde_json::from_str(json).expect("Failed to parse rotated duplicates")
+}
+
+/// Load groups that pair an upload with an external library copy.
+fn load_cross_library_duplicates() -> Vec<DuplicateGroup> {
+    let json = include_str!("fixtures/recorded/cross_library.json");
+    serde_json::from_str(json).expect("Failed to parse cross-library duplicates")
+}
+
+/// Load a duplicate response in the shape of another Immich release.
+fn load_version_duplicates(version: &str) -> Vec<DuplicateGroup> {
+    let json = match version {
+        "v1.106" => include_str!("fixtures/versions/v1.106.json"),
+        "v2.0" => include_str!("fixtures/versions/v2.0.json"),
+        other => panic!("No fixture for {}", other),
+    };
+    serde_json::from_str(json).expect("Failed to parse versioned duplicates")
+}
+
 /// Find a duplicate group containing a specific filename.
 fn find_group_by_filename<'a>(
     groups: &'a [DuplicateGroup],
@@ -376,3 +400,143 @@ mod edge_cases {
         }
     }
 }
+
+// ============================================================================
+// Orientation Tests (rotated copies report swapped width/height)
+// ============================================================================
+
+mod orientation {
+    use super::*;
+
+    #[test]
+    fn test_rotated_pair_has_matching_dimensions() {
+        let groups = load_rotated_duplicates();
+        for group in &groups {
+            let analysis = DuplicateAnalysis::from_group(group);
+            assert_eq!(
+                analysis.winner.dimensions, analysis.losers[0].dimensions,
+                "Rotated copies should compare with the same display dimensions"
+            );
+            assert_eq!(analysis.winner.dimensions, Some((400, 600)));
+            assert_eq!(analysis.confidence, 100, "No aspect-ratio penalty for rotated copies");
+        }
+    }
+
+    #[test]
+    fn test_rotated_pair_larger_file_wins() {
+        let groups = load_rotated_duplicates();
+        let group = find_group_by_filename(&groups, "r1_camera.jpg").expect("R1 group not found");
+
+        let analysis = DuplicateAnalysis::from_group(group);
+        assert_eq!(analysis.winner.filename, "r1_camera.jpg",
+            "Same display dimensions, so file size decides");
+    }
+
+    #[test]
+    fn test_rotated_pair_not_detected_as_w8() {
+        let groups = load_rotated_duplicates();
+        for group in &groups {
+            let scenarios: Vec<TestScenario> =
+                detect_scenarios(group).into_iter().map(|m| m.scenario).collect();
+            assert!(!scenarios.contains(&TestScenario::W8SamePixelsDifferentAspect));
+            assert!(scenarios.contains(&TestScenario::W2SameDimensionsDifferentSize));
+        }
+    }
+}
+
+// ============================================================================
+// Cross-library Tests (assets from an external library and uploads)
+// ============================================================================
+
+mod cross_library {
+    use super::*;
+    use immich_lib::SelectionWarning;
+
+    #[test]
+    fn test_cross_library_groups_warned() {
+        let groups = load_cross_library_duplicates();
+        for group in &groups {
+            let analysis = DuplicateAnalysis::from_group(group);
+            assert!(analysis.is_cross_library());
+            assert!(matches!(
+                analysis.warnings.as_slice(),
+                [SelectionWarning::CrossLibrary { library_ids }] if library_ids.len() == 2
+            ));
+        }
+    }
+
+    #[test]
+    fn test_external_winner_keeps_library() {
+        let groups = load_cross_library_duplicates();
+        let group = find_group_by_filename(&groups, "l1_external_large.jpg").expect("L1 group not found");
+
+        let analysis = DuplicateAnalysis::from_group(group);
+        assert_eq!(analysis.winner.filename, "l1_external_large.jpg",
+            "Larger dimensions win whichever library they are in");
+        assert!(analysis.winner.library_id.is_some());
+        assert_eq!(analysis.losers[0].library_id, None);
+    }
+
+    #[test]
+    fn test_recorded_groups_are_single_library() {
+        let groups = load_recorded_duplicates();
+        for group in &groups {
+            let analysis = DuplicateAnalysis::from_group(group);
+            assert!(!analysis.is_cross_library());
+            assert!(analysis.warnings.is_empty());
+        }
+    }
+}
+
+// ============================================================================
+// API Version Tests (responses from older and newer Immich releases)
+// ============================================================================
+
+mod api_versions {
+    use super::*;
+
+    #[test]
+    fn test_all_versions_parse() {
+        for version in ["v1.106", "v2.0"] {
+            let groups = load_version_duplicates(version);
+            assert_eq!(groups.len(), 1, "{}", version);
+            let analysis = DuplicateAnalysis::from_group(&groups[0]);
+            assert_eq!(analysis.losers.len(), 1, "{}", version);
+        }
+        assert!(!load_recorded_duplicates().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_fields_kept() {
+        let groups = load_version_duplicates("v1.106");
+        let asset = &groups[0].assets[0];
+        assert_eq!(asset.extra["isReadOnly"], false);
+        assert!(asset.extra.contains_key("stackParentId"));
+        assert!(!asset.extra.contains_key("isArchived"), "known fields aren't duplicated");
+
+        let groups = load_version_duplicates("v2.0");
+        assert!(groups[0].extra.contains_key("suggestedKeepAssetIds"));
+        let asset = &groups[0].assets[0];
+        assert_eq!(asset.extra["width"], 4080);
+        assert_eq!(asset.exif_info.as_ref().unwrap().extra["lensMake"], "Google");
+
+        // Unknown fields survive a round trip
+        let json = serde_json::to_value(&groups[0]).unwrap();
+        assert_eq!(json["assets"][0]["isEdited"], false);
+        assert_eq!(json["suggestedKeepAssetIds"][0], asset.id.as_str());
+    }
+
+    #[test]
+    fn test_archived_from_either_field() {
+        let groups = load_version_duplicates("v1.106");
+        assert!(!groups[0].assets[0].archived());
+        assert!(groups[0].assets[1].archived());
+
+        let groups = load_version_duplicates("v2.0");
+        let assets = &groups[0].assets;
+        assert!(assets.iter().all(|a| !a.is_archived));
+        assert!(!assets[0].archived());
+        assert!(assets[1].archived(), "visibility: archive");
+        assert_eq!(assets[1].stack.as_ref().map(|s| s.asset_count), Some(2));
+    }
+}
@@ -0,0 +1,116 @@
+//! Property tests for winner selection.
+//!
+//! Image winners are chosen by display pixels, then file size; metadata only
+//! decides what gets consolidated, never which copy wins. Assets with equal
+//! pixels and size keep their order in the group, so the first of them wins.
+//!
+//! Run with: `cargo test --features proptest --test winner_properties`
+
+use immich_lib::models::DuplicateGroup;
+use immich_lib::scoring::{MetadataScore, ScoredAsset};
+use immich_lib::testing::arbitrary::{arb_image_group, arb_metadata};
+use immich_lib::testing::{AssetBuilder, ScenarioContext};
+use immich_lib::DuplicateAnalysis;
+use proptest::prelude::*;
+
+/// What winner selection ranks image assets by.
+fn selection_key(asset: &ScoredAsset) -> (u64, u64) {
+    let pixels = asset
+        .dimensions
+        .map(|(w, h)| u64::from(w) * u64::from(h))
+        .unwrap_or(0);
+    (pixels, asset.file_size.unwrap_or(0))
+}
+
+/// Asset IDs from winner to last loser.
+fn ranking(analysis: &DuplicateAnalysis) -> Vec<String> {
+    std::iter::once(&analysis.winner)
+        .chain(&analysis.losers)
+        .map(|a| a.asset_id.to_string())
+        .collect()
+}
+
+/// A group and one of its asset indices.
+fn group_and_index() -> impl Strategy<Value = (DuplicateGroup, usize)> {
+    arb_image_group(6).prop_flat_map(|group| {
+        let count = group.assets.len();
+        (Just(group), 0..count)
+    })
+}
+
+proptest! {
+    #[test]
+    fn winner_ranks_at_least_as_high_as_every_loser(group in arb_image_group(8)) {
+        let analysis = DuplicateAnalysis::from_group(&group);
+        let winner = selection_key(&analysis.winner);
+        for loser in &analysis.losers {
+            prop_assert!(winner >= selection_key(loser));
+        }
+
+        // Every asset is either the winner or a loser, exactly once
+        let mut ids = ranking(&analysis);
+        ids.sort();
+        let mut expected: Vec<String> = group.assets.iter().map(|a| a.id.clone()).collect();
+        expected.sort();
+        prop_assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn scenario_detection_ranks_like_analysis(group in arb_image_group(8)) {
+        let analysis = DuplicateAnalysis::from_group(&group);
+        let context = ScenarioContext::new(&group);
+        let ranked: Vec<String> = context.ranked.iter().map(|a| a.id.clone()).collect();
+        prop_assert_eq!(ranked, ranking(&analysis));
+    }
+
+    #[test]
+    fn adding_metadata_never_demotes_an_asset(
+        (group, index) in group_and_index(),
+        metadata in arb_metadata(),
+    ) {
+        let before = DuplicateAnalysis::from_group(&group);
+        let id = group.assets[index].id.clone();
+        let rank_before = ranking(&before).iter().position(|a| *a == id).unwrap();
+
+        let mut enriched = group.clone();
+        let asset = enriched.assets[index].clone();
+        enriched.assets[index] = metadata.apply(AssetBuilder::from(asset)).build();
+        let after = DuplicateAnalysis::from_group(&enriched);
+        let rank_after = ranking(&after).iter().position(|a| *a == id).unwrap();
+
+        prop_assert!(rank_after <= rank_before);
+        prop_assert!(
+            MetadataScore::from_asset(&enriched.assets[index]).total
+                >= MetadataScore::from_asset(&group.assets[index]).total
+        );
+    }
+
+    #[test]
+    fn analysis_ignores_asset_order_except_ties(
+        (group, shuffled) in arb_image_group(8).prop_flat_map(|group| {
+            let assets = group.assets.clone();
+            (Just(group), Just(assets).prop_shuffle())
+        }),
+    ) {
+        let original = DuplicateAnalysis::from_group(&group);
+        let reordered = DuplicateAnalysis::from_group(&DuplicateGroup {
+            duplicate_id: group.duplicate_id.clone(),
+            assets: shuffled,
+            extra: Default::default(),
+        });
+
+        let keys = |a: &DuplicateAnalysis| -> Vec<(u64, u64)> {
+            std::iter::once(&a.winner).chain(&a.losers).map(selection_key).collect()
+        };
+        prop_assert_eq!(keys(&original), keys(&reordered));
+        prop_assert_eq!(original.conflicts.len(), reordered.conflicts.len());
+        prop_assert_eq!(original.confidence, reordered.confidence);
+
+        // Tie case: with another asset ranked the same, either may win
+        let top = selection_key(&original.winner);
+        let tied = original.losers.iter().any(|l| selection_key(l) == top);
+        if !tied {
+            prop_assert_eq!(&original.winner.asset_id, &reordered.winner.asset_id);
+        }
+    }
+}